@@ -191,10 +191,8 @@ impl Database {
         let mut attributes = Vec::new();
         for row in rows {
             let (mut attr, attr_id) = row?;
-            // Load enum values if attribute type is enum
-            if attr.attr_type == AttributeType::Enum {
-                attr.enum_values = self.get_enum_values(attr_id)?;
-            }
+            // Enumerations are also stored on string and yes/no attributes
+            attr.enum_values = self.get_enum_values(attr_id)?;
             attributes.push(attr);
         }
         Ok(attributes)
@@ -949,6 +947,57 @@ mod tests {
         assert!(results.iter().any(|e| e.name == "PropertyRef"));
     }
 
+    #[test]
+    fn test_get_attributes_enum_values_for_all_types() {
+        let db = Database::open_memory().unwrap();
+        let element_id = db
+            .insert_element(&Element {
+                id: 0,
+                name: "CustomAction".to_string(),
+                namespace: "wix".to_string(),
+                since_version: None,
+                deprecated_version: None,
+                description: None,
+                documentation_url: None,
+                remarks: None,
+                example: None,
+            })
+            .unwrap();
+
+        let attribute = |name: &str, attr_type: AttributeType, values: &[&str]| Attribute {
+            id: 0,
+            element_id,
+            name: name.to_string(),
+            attr_type,
+            required: false,
+            default_value: None,
+            description: None,
+            since_version: None,
+            deprecated_version: None,
+            enum_values: values.iter().map(|v| v.to_string()).collect(),
+        };
+        for attr in [
+            attribute("Script", AttributeType::String, &["vbscript", "jscript"]),
+            attribute("Impersonate", AttributeType::YesNo, &["yes", "no"]),
+            attribute("Id", AttributeType::Identifier, &[]),
+        ] {
+            db.insert_attribute(&attr).unwrap();
+        }
+
+        let attributes = db.get_attributes("CustomAction").unwrap();
+        let values = |name: &str| {
+            attributes
+                .iter()
+                .find(|a| a.name == name)
+                .unwrap()
+                .enum_values
+                .clone()
+        };
+        assert_eq!(values("Script"), vec!["jscript", "vbscript"]);
+        assert_eq!(values("Impersonate"), vec!["no", "yes"]);
+        assert!(values("Id").is_empty());
+    }
+
     #[test]
     fn test_get_stats() {
         let db = Database::open_memory().unwrap();
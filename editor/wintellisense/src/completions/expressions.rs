@@ -0,0 +1,350 @@
+//! Completions inside `$(...)`, `!(...)` and `[...]` references
+
+use super::values::matches_prefix;
+use crate::index::ProjectIndex;
+use crate::loader::SchemaData;
+use crate::types::{CompletionItem, CompletionKind, CursorContext, ExpressionKind};
+use std::collections::HashSet;
+
+/// Built-in `$(sys.X)` preprocessor variables
+const SYSTEM_VARIABLES: &[(&str, &str)] = &[
+    ("BUILDARCH", "Platform being built for (x86, x64, arm64)"),
+    ("BUILDARCHSHORT", "Short platform name (x86, x64, a64)"),
+    ("CURRENTDIR", "Current directory of the build"),
+    (
+        "PLATFORM",
+        "Platform being built for; deprecated in favor of BUILDARCH",
+    ),
+    ("SOURCEFILEDIR", "Directory of the file being processed"),
+    ("SOURCEFILEPATH", "Full path of the file being processed"),
+    ("WIXMAJORVERSION", "Major version of the WiX Toolset"),
+];
+
+/// `!(bind.KIND.FileId)` variables filled in from a file at bind time
+const BIND_VARIABLE_KINDS: &[(&str, &str)] = &[
+    ("FileVersion", "Version of the file"),
+    ("FileLanguage", "Language of the file"),
+    ("AssemblyCulture", "Culture of the assembly"),
+    ("AssemblyFileVersion", "File version of the assembly"),
+    ("AssemblyFullName", "Full name of the assembly"),
+    ("AssemblyName", "Name of the assembly"),
+    (
+        "AssemblyProcessorArchitecture",
+        "Processor architecture of the assembly",
+    ),
+    ("AssemblyPublicKeyToken", "Public key token of the assembly"),
+    ("AssemblyVersion", "Version of the assembly"),
+];
+
+/// Longest translation shown in a detail line
+const MAX_TRANSLATION_CHARS: usize = 40;
+
+/// Complete the reference the cursor is in
+pub fn complete_expression(
+    schema: &SchemaData,
+    index: &ProjectIndex,
+    ctx: &CursorContext,
+    source: &str,
+    defines: &[(String, String)],
+) -> Vec<CompletionItem> {
+    match ctx.expression {
+        Some(ExpressionKind::Variable) => complete_variables(index, source, defines, &ctx.prefix),
+        Some(ExpressionKind::System) => complete_system_variables(&ctx.prefix),
+        Some(ExpressionKind::Localization) => complete_localization(index, &ctx.prefix),
+        Some(ExpressionKind::Bind) => complete_bind(schema, index, &ctx.prefix),
+        Some(ExpressionKind::Property) => complete_properties(schema, index, &ctx.prefix),
+        None => Vec::new(),
+    }
+}
+
+/// `<?define?>` names from build defines, the current file and the project
+fn complete_variables(
+    index: &ProjectIndex,
+    source: &str,
+    defines: &[(String, String)],
+    prefix: &str,
+) -> Vec<CompletionItem> {
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+
+    for (name, value) in defines {
+        if matches_prefix(name, prefix) && seen.insert(name.as_str()) {
+            items.push(
+                CompletionItem::new(name, CompletionKind::Variable)
+                    .with_detail(format!("Build define = {}", value))
+                    .with_priority(5),
+            );
+        }
+    }
+
+    let local = source_defines(source);
+    for (name, line) in &local {
+        if matches_prefix(name, prefix) && seen.insert(name) {
+            items.push(
+                CompletionItem::new(*name, CompletionKind::Variable)
+                    .with_detail(*line)
+                    .with_priority(10),
+            );
+        }
+    }
+
+    for sym in index.get_symbols_by_kind("Define") {
+        if matches_prefix(&sym.name, prefix) && seen.insert(&sym.name) {
+            items.push(
+                CompletionItem::new(&sym.name, CompletionKind::Variable)
+                    .with_detail(format!("Defined in {}", sym.location.path.display()))
+                    .with_documentation(sym.preview.clone().unwrap_or_default())
+                    .with_priority(20),
+            );
+        }
+    }
+
+    items
+}
+
+/// `<?define?>` names in the file being edited, which may not be saved yet
+fn source_defines(source: &str) -> Vec<(&str, &str)> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let start = line.find("<?define")?;
+            let name = line[start + "<?define".len()..]
+                .split(|c: char| c == '=' || c == '?' || c.is_whitespace())
+                .find(|s| !s.is_empty())?;
+            Some((name, line.trim()))
+        })
+        .collect()
+}
+
+fn complete_system_variables(prefix: &str) -> Vec<CompletionItem> {
+    SYSTEM_VARIABLES
+        .iter()
+        .filter(|(name, _)| matches_prefix(name, prefix))
+        .map(|(name, description)| {
+            CompletionItem::new(*name, CompletionKind::Variable).with_detail(*description)
+        })
+        .collect()
+}
+
+/// String ids from `.wxl` files, with their translations
+fn complete_localization(index: &ProjectIndex, prefix: &str) -> Vec<CompletionItem> {
+    index
+        .get_localized_strings()
+        .into_iter()
+        .filter(|(id, _)| matches_prefix(id, prefix))
+        .map(|(id, strings)| {
+            let translations: Vec<String> = strings
+                .iter()
+                .map(|s| {
+                    let value = shorten(&s.value);
+                    match &s.culture {
+                        Some(culture) => format!("{}: {}", culture, value),
+                        None => value,
+                    }
+                })
+                .collect();
+            CompletionItem::new(id, CompletionKind::Variable).with_detail(translations.join("; "))
+        })
+        .collect()
+}
+
+/// Bind variable kinds, then the File (or Property) Ids they apply to
+fn complete_bind(schema: &SchemaData, index: &ProjectIndex, prefix: &str) -> Vec<CompletionItem> {
+    let Some((kind, id_prefix)) = prefix.split_once('.') else {
+        let mut items: Vec<CompletionItem> = BIND_VARIABLE_KINDS
+            .iter()
+            .filter(|(kind, _)| matches_prefix(kind, prefix))
+            .map(|(kind, description)| {
+                CompletionItem::new(*kind, CompletionKind::Keyword)
+                    .with_insert_text(format!("{}.", kind))
+                    .with_detail(*description)
+            })
+            .collect();
+        if matches_prefix("property", prefix) {
+            items.push(
+                CompletionItem::new("property", CompletionKind::Keyword)
+                    .with_insert_text("property.")
+                    .with_detail("Value of a property at bind time"),
+            );
+        }
+        return items;
+    };
+
+    if kind.eq_ignore_ascii_case("property") {
+        return complete_properties(schema, index, id_prefix);
+    }
+
+    index
+        .get_symbols_by_kind("File")
+        .into_iter()
+        .filter(|sym| matches_prefix(&sym.name, id_prefix))
+        .map(|sym| {
+            CompletionItem::new(&sym.name, CompletionKind::Reference)
+                .with_detail(format!("File in {}", sym.location.path.display()))
+        })
+        .collect()
+}
+
+/// Public project properties and standard Windows Installer properties
+fn complete_properties(
+    schema: &SchemaData,
+    index: &ProjectIndex,
+    prefix: &str,
+) -> Vec<CompletionItem> {
+    let mut seen = HashSet::new();
+    let mut items = Vec::new();
+
+    // Public properties are the all-uppercase ones
+    for sym in index.get_symbols_by_kind("Property") {
+        let public = !sym.name.chars().any(|c| c.is_ascii_lowercase());
+        if public && matches_prefix(&sym.name, prefix) && seen.insert(sym.name.clone()) {
+            items.push(
+                CompletionItem::new(&sym.name, CompletionKind::Reference)
+                    .with_detail(format!("Property in {}", sym.location.path.display()))
+                    .with_priority(5),
+            );
+        }
+    }
+
+    for property in schema.get_standard_properties() {
+        if matches_prefix(&property.name, prefix) && seen.insert(property.name.clone()) {
+            let detail = if property.readonly {
+                "Standard property (read-only)"
+            } else {
+                "Standard property"
+            };
+            let mut item =
+                CompletionItem::new(&property.name, CompletionKind::Keyword).with_detail(detail);
+            if !property.description.is_empty() {
+                item = item.with_documentation(&property.description);
+            }
+            items.push(item);
+        }
+    }
+
+    items
+}
+
+fn shorten(value: &str) -> String {
+    if value.chars().count() <= MAX_TRANSLATION_CHARS {
+        return value.to_string();
+    }
+    let mut short: String = value.chars().take(MAX_TRANSLATION_CHARS - 1).collect();
+    short.push('…');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Keywords, PropertyDef};
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|i| i.label.as_str()).collect()
+    }
+
+    fn expression(kind: ExpressionKind, partial: &str) -> CursorContext {
+        CursorContext {
+            expression: Some(kind),
+            prefix: partial.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn project() -> ProjectIndex {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Product.wxs"),
+            r#"<Wix>
+<?define Version = "1.0" ?>
+<Property Id="INSTALLMODE" Value="full" />
+<Property Id="PrivateState" Value="1" />
+<File Id="AppExe" Source="app.exe" />
+</Wix>"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("en-us.wxl"),
+            r#"<WixLocalization Culture="en-US"><String Id="Title" Value="My App" /></WixLocalization>"#,
+        )
+        .unwrap();
+        let mut index = ProjectIndex::new();
+        index.index_directory(dir.path()).unwrap();
+        index
+    }
+
+    #[test]
+    fn test_complete_variables() {
+        let index = project();
+        let defines = vec![("Configuration".to_string(), "Release".to_string())];
+        let source = "<Wix>\n  <?define Bin = \"out\" ?>\n  <?define Version = \"2.0\" ?>\n</Wix>";
+        let ctx = expression(ExpressionKind::Variable, "");
+
+        let items = complete_expression(&SchemaData::default(), &index, &ctx, source, &defines);
+        assert_eq!(labels(&items), vec!["Configuration", "Bin", "Version"]);
+        assert_eq!(items[0].detail.as_deref(), Some("Build define = Release"));
+        // The open document wins over its saved copy
+        assert_eq!(
+            items[2].detail.as_deref(),
+            Some("<?define Version = \"2.0\" ?>")
+        );
+    }
+
+    #[test]
+    fn test_complete_localization_and_system() {
+        let index = project();
+        let schema = SchemaData::default();
+
+        let ctx = expression(ExpressionKind::Localization, "Ti");
+        let items = complete_expression(&schema, &index, &ctx, "", &[]);
+        assert_eq!(labels(&items), vec!["Title"]);
+        assert_eq!(items[0].detail.as_deref(), Some("en-US: My App"));
+
+        let ctx = expression(ExpressionKind::System, "SOURCE");
+        let items = complete_expression(&schema, &index, &ctx, "", &[]);
+        assert_eq!(labels(&items), vec!["SOURCEFILEDIR", "SOURCEFILEPATH"]);
+    }
+
+    #[test]
+    fn test_complete_bind() {
+        let index = project();
+        let schema = SchemaData::default();
+
+        let ctx = expression(ExpressionKind::Bind, "FileV");
+        let items = complete_expression(&schema, &index, &ctx, "", &[]);
+        assert_eq!(labels(&items), vec!["FileVersion"]);
+        assert_eq!(items[0].insert_text, "FileVersion.");
+
+        let ctx = expression(ExpressionKind::Bind, "FileVersion.");
+        let items = complete_expression(&schema, &index, &ctx, "", &[]);
+        assert_eq!(labels(&items), vec!["AppExe"]);
+    }
+
+    #[test]
+    fn test_complete_properties() {
+        let index = project();
+        let schema = SchemaData {
+            properties: vec![PropertyDef {
+                name: "INSTALLLEVEL".to_string(),
+                description: "Installation level".to_string(),
+                readonly: false,
+            }],
+            keywords: Keywords {
+                builtin_properties: vec!["ProductName".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let ctx = expression(ExpressionKind::Property, "");
+        let items = complete_expression(&schema, &index, &ctx, "", &[]);
+        assert_eq!(
+            labels(&items),
+            vec!["INSTALLMODE", "INSTALLLEVEL", "ProductName"]
+        );
+
+        let ctx = expression(ExpressionKind::Bind, "property.INSTALLL");
+        let items = complete_expression(&schema, &index, &ctx, "", &[]);
+        assert_eq!(labels(&items), vec!["INSTALLLEVEL"]);
+    }
+}
@@ -2,6 +2,7 @@
 
 mod attributes;
 mod elements;
+mod expressions;
 mod snippets;
 mod values;
 mod words;
@@ -15,12 +16,18 @@ pub fn get_completions(
     schema: &SchemaData,
     index: &ProjectIndex,
     ctx: &CursorContext,
-    _source: &str,
+    source: &str,
+    defines: &[(String, String)],
     max: usize,
 ) -> CompletionResult {
     let mut items = Vec::new();
 
-    if ctx.should_suggest_values() {
+    if ctx.should_suggest_expressions() {
+        // Variable, localization and property references inside a value
+        items.extend(expressions::complete_expression(
+            schema, index, ctx, source, defines,
+        ));
+    } else if ctx.should_suggest_values() {
         // Attribute value completions
         items.extend(values::complete_values(schema, index, ctx));
     } else if ctx.should_suggest_attributes() {
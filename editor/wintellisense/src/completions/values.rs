@@ -209,7 +209,7 @@ fn complete_reference_values(
     items
 }
 
-fn matches_prefix(value: &str, prefix: &str) -> bool {
+pub(super) fn matches_prefix(value: &str, prefix: &str) -> bool {
     if prefix.is_empty() {
         return true;
     }
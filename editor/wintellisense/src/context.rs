@@ -1,6 +1,6 @@
 //! XML context parser for determining cursor position and completion context.
 
-use crate::types::CursorContext;
+use crate::types::{CursorContext, ExpressionKind};
 
 /// Parse XML content and determine completion context at position (1-based line/column)
 pub fn parse_context(content: &str, line: u32, column: u32) -> CursorContext {
@@ -29,6 +29,9 @@ pub fn parse_context(content: &str, line: u32, column: u32) -> CursorContext {
         }
     }
 
+    // Text that may end inside a $(...), !(...) or [...] reference
+    let mut expression_scope: Option<&str> = None;
+
     // Find last < and > positions
     let last_open = before_cursor.rfind('<');
     let last_close = before_cursor.rfind('>');
@@ -49,6 +52,11 @@ pub fn parse_context(content: &str, line: u32, column: u32) -> CursorContext {
                 return ctx;
             }
 
+            // Processing instructions like <?if $(var.X) ?> can reference variables
+            if tag_content.starts_with('?') {
+                expression_scope = Some(tag_content);
+            }
+
             let parts: Vec<&str> = tag_content.split_whitespace().collect();
 
             if parts.is_empty() {
@@ -95,6 +103,7 @@ pub fn parse_context(content: &str, line: u32, column: u32) -> CursorContext {
 
                             // Get partial value (everything after the opening quote)
                             ctx.prefix = after_open_quote.to_string();
+                            expression_scope = Some(after_open_quote);
                         }
                     } else if trimmed.is_empty() {
                         // Right after =
@@ -122,18 +131,59 @@ pub fn parse_context(content: &str, line: u32, column: u32) -> CursorContext {
             .unwrap_or(&before_cursor);
 
         ctx.prefix = extract_partial_word(after_last_close);
+        expression_scope = Some(after_last_close);
 
         if ctx.prefix.starts_with('<') || after_last_close.trim().starts_with('<') {
             ctx.prefix = ctx.prefix.trim_start_matches('<').to_string();
         }
     }
 
+    if let Some((kind, partial)) = expression_scope.and_then(parse_expression) {
+        ctx.expression = Some(kind);
+        ctx.prefix = partial.to_string();
+    }
+
     // Extract word at cursor for hover/definition
     ctx.word_at_cursor = extract_word_at_cursor(content, line, column);
 
     ctx
 }
 
+/// Find an unclosed variable or property reference at the end of `text`,
+/// returning its kind and the partial name typed so far
+fn parse_expression(text: &str) -> Option<(ExpressionKind, &str)> {
+    let is_name = |s: &str| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+    };
+
+    let open = text.rfind(['(', '['])?;
+    let inner = &text[open + 1..];
+    if !is_name(inner) {
+        return None;
+    }
+
+    if text[..open].ends_with('$') {
+        [
+            ("var.", ExpressionKind::Variable),
+            ("sys.", ExpressionKind::System),
+        ]
+        .into_iter()
+        .find_map(|(ns, kind)| inner.strip_prefix(ns).map(|partial| (kind, partial)))
+    } else if text[..open].ends_with('!') {
+        [
+            ("loc.", ExpressionKind::Localization),
+            ("bind.", ExpressionKind::Bind),
+        ]
+        .into_iter()
+        .find_map(|(ns, kind)| inner.strip_prefix(ns).map(|partial| (kind, partial)))
+    } else if text[open..].starts_with('[') {
+        Some((ExpressionKind::Property, inner))
+    } else {
+        None
+    }
+}
+
 /// Extract partial word at end of string
 fn extract_partial_word(s: &str) -> String {
     let trimmed = s.trim_end();
@@ -267,7 +317,7 @@ fn extract_word_at_cursor(content: &str, line: u32, column: u32) -> Option<Strin
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::ContextKind;
+    use crate::types::{ContextKind, ExpressionKind};
 
     // =========================================================================
     // Basic context tests
@@ -710,4 +760,67 @@ mod tests {
         assert!(ctx.in_opening_tag);
         assert_eq!(ctx.current_element, Some("Component".to_string()));
     }
+
+    // =========================================================================
+    // Expression context tests
+    // =========================================================================
+
+    #[test]
+    fn test_expression_in_attribute_value() {
+        let source = r#"<File Source="$(var.Bi"#;
+        let ctx = parse_context(source, 1, 23);
+
+        assert_eq!(
+            ctx.kind(),
+            ContextKind::Expression {
+                kind: ExpressionKind::Variable,
+                partial: "Bi".to_string(),
+            }
+        );
+        assert_eq!(ctx.current_attribute, Some("Source".to_string()));
+    }
+
+    #[test]
+    fn test_expression_namespaces() {
+        let cases = [
+            ("$(sys.", ExpressionKind::System, ""),
+            ("!(loc.Tit", ExpressionKind::Localization, "Tit"),
+            ("!(bind.FileVersion.", ExpressionKind::Bind, "FileVersion."),
+            ("[INSTALL", ExpressionKind::Property, "INSTALL"),
+            ("$(var.Dir)\\[", ExpressionKind::Property, ""),
+        ];
+        for (value, kind, partial) in cases {
+            assert_eq!(parse_expression(value), Some((kind, partial)), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_closed_or_unknown_expression() {
+        assert_eq!(parse_expression("$(var.Dir)\\bin"), None);
+        assert_eq!(parse_expression("[#File"), None);
+        assert_eq!(parse_expression("$(env."), None);
+        assert_eq!(parse_expression("(plain"), None);
+
+        let ctx = parse_context(r#"<File Source="$(var.Dir)\"#, 1, 26);
+        assert_eq!(ctx.expression, None);
+        assert!(ctx.should_suggest_values());
+    }
+
+    #[test]
+    fn test_expression_in_processing_instruction() {
+        let source = "<Wix>\n  <?if $(var.Pla";
+        let ctx = parse_context(source, 2, 19);
+
+        assert_eq!(ctx.expression, Some(ExpressionKind::Variable));
+        assert_eq!(ctx.prefix, "Pla");
+    }
+
+    #[test]
+    fn test_expression_in_element_text() {
+        let source = "<Condition Message=\"x\">\n  Installed OR [VersionNT";
+        let ctx = parse_context(source, 2, 26);
+
+        assert_eq!(ctx.expression, Some(ExpressionKind::Property));
+        assert_eq!(ctx.prefix, "VersionNT");
+    }
 }
@@ -24,12 +24,28 @@ pub struct Symbol {
     pub preview: Option<String>,
 }
 
+/// A translation of a localization string from a `.wxl` file
+#[derive(Debug, Clone)]
+pub struct LocalizedString {
+    /// Culture of the file (e.g., "en-US")
+    pub culture: Option<String>,
+
+    /// Translated text
+    pub value: String,
+
+    /// Location of the `String` element
+    pub location: Location,
+}
+
 /// Index of all symbols across project files
 #[derive(Debug, Default)]
 pub struct ProjectIndex {
     /// Symbols by kind -> name -> locations
     symbols: HashMap<String, HashMap<String, Vec<Symbol>>>,
 
+    /// Localization strings by id
+    strings: HashMap<String, Vec<LocalizedString>>,
+
     /// All words from all files (for All Autocomplete)
     words: HashMap<String, Vec<PathBuf>>,
 
@@ -42,7 +58,7 @@ impl ProjectIndex {
         Self::default()
     }
 
-    /// Index all WiX files in a directory
+    /// Index all WiX source and localization files in a directory
     pub fn index_directory(&mut self, root: &Path) -> Result<usize> {
         use walkdir::WalkDir;
 
@@ -54,7 +70,7 @@ impl ProjectIndex {
             .filter(|e| {
                 e.path()
                     .extension()
-                    .map(|ext| ext == "wxs" || ext == "wxi")
+                    .map(|ext| ext == "wxs" || ext == "wxi" || ext == "wxl")
                     .unwrap_or(false)
             })
         {
@@ -75,7 +91,11 @@ impl ProjectIndex {
         self.remove_file(&path_buf);
 
         // Parse and index symbols
-        self.parse_symbols(&content, &path_buf);
+        if path.extension().is_some_and(|ext| ext == "wxl") {
+            self.parse_localization(&content, &path_buf);
+        } else {
+            self.parse_symbols(&content, &path_buf);
+        }
 
         // Index words
         self.index_words(&content, &path_buf);
@@ -94,6 +114,11 @@ impl ProjectIndex {
             }
         }
 
+        for strings in self.strings.values_mut() {
+            strings.retain(|s| s.location.path != *path);
+        }
+        self.strings.retain(|_, strings| !strings.is_empty());
+
         // Remove from words
         for paths in self.words.values_mut() {
             paths.retain(|p| p != path);
@@ -116,15 +141,32 @@ impl ProjectIndex {
             "Property",
             "CustomAction",
             "Binary",
+            "File",
             "Fragment",
         ];
 
         for (line_num, line) in content.lines().enumerate() {
             let line_num = line_num + 1; // 1-based
 
+            // <?define NAME = "value" ?>
+            if let Some(start) = line.find("<?define") {
+                let name = line[start + "<?define".len()..]
+                    .split(|c: char| c == '=' || c == '?' || c.is_whitespace())
+                    .find(|s| !s.is_empty());
+                if let Some(name) = name {
+                    self.add_symbol(Symbol {
+                        name: name.to_string(),
+                        kind: "Define".to_string(),
+                        location: Location::point(path.clone(), line_num as u32, start as u32 + 1),
+                        parent_id: None,
+                        preview: Some(line.trim().to_string()),
+                    });
+                }
+            }
+
             for elem in &symbol_elements {
                 // Look for <Element ... Id="..."
-                if let Some(start) = line.find(&format!("<{}", elem)) {
+                if let Some(start) = find_tag(line, elem) {
                     let after_tag = &line[start..];
 
                     // Extract Id attribute
@@ -149,6 +191,34 @@ impl ProjectIndex {
         }
     }
 
+    /// Parse `String` elements from a `.wxl` file
+    fn parse_localization(&mut self, content: &str, path: &Path) {
+        let Ok(doc) = roxmltree::Document::parse(content) else {
+            return;
+        };
+        let culture = doc.root_element().attribute("Culture").map(str::to_string);
+
+        for node in doc.descendants().filter(|n| n.has_tag_name("String")) {
+            let Some(id) = node.attribute("Id") else {
+                continue;
+            };
+            // WiX v4 uses Value=, v3 the element text
+            let value = node
+                .attribute("Value")
+                .or_else(|| node.text())
+                .unwrap_or_default();
+            let pos = doc.text_pos_at(node.range().start);
+            self.strings
+                .entry(id.to_string())
+                .or_default()
+                .push(LocalizedString {
+                    culture: culture.clone(),
+                    value: value.to_string(),
+                    location: Location::point(path.to_path_buf(), pos.row, pos.col),
+                });
+        }
+    }
+
     /// Index words from content
     fn index_words(&mut self, content: &str, path: &PathBuf) {
         let word_re = |c: char| c.is_alphanumeric() || c == '_';
@@ -234,6 +304,17 @@ impl ProjectIndex {
             .unwrap_or_default()
     }
 
+    /// Get localization strings by id, sorted by id
+    pub fn get_localized_strings(&self) -> Vec<(&str, &[LocalizedString])> {
+        let mut strings: Vec<_> = self
+            .strings
+            .iter()
+            .map(|(id, strings)| (id.as_str(), strings.as_slice()))
+            .collect();
+        strings.sort_by_key(|(id, _)| *id);
+        strings
+    }
+
     /// Get file count
     pub fn file_count(&self) -> usize {
         self.indexed_files.len()
@@ -250,6 +331,19 @@ impl ProjectIndex {
     }
 }
 
+/// Find `<elem` followed by the end of the element name
+fn find_tag(line: &str, elem: &str) -> Option<usize> {
+    let tag = format!("<{}", elem);
+    line.match_indices(&tag)
+        .map(|(start, _)| start)
+        .find(|start| {
+            line[start + tag.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_whitespace() || c == '>' || c == '/')
+        })
+}
+
 /// Extract attribute value from tag string
 fn extract_attribute(tag: &str, attr_name: &str) -> Option<String> {
     // Look for attr="value" or attr='value'
@@ -359,4 +453,62 @@ mod tests {
         assert_eq!(index.symbol_count(), 1);
         assert_eq!(index.file_count(), 1);
     }
+
+    #[test]
+    fn test_index_defines_and_files() {
+        let mut index = ProjectIndex::new();
+        let content = r#"
+<?define Bin = "out\bin" ?>
+<ComponentGroup Id="Group">
+  <Component>
+    <File Id="AppExe" Source="$(var.Bin)\app.exe" />
+    <FileSearch Id="Search" Name="x.dll" />
+  </Component>
+</ComponentGroup>
+"#;
+
+        index.parse_symbols(content, &PathBuf::from("test.wxs"));
+
+        let defines = index.get_symbols_by_kind("Define");
+        assert_eq!(defines.len(), 1);
+        assert_eq!(defines[0].name, "Bin");
+
+        let files = index.get_symbols_by_kind("File");
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "AppExe");
+
+        // <ComponentGroup is not a <Component
+        assert!(index.get_symbols_by_kind("Component").is_empty());
+    }
+
+    #[test]
+    fn test_index_localization() {
+        let mut index = ProjectIndex::new();
+        let en = PathBuf::from("en-us.wxl");
+        let de = PathBuf::from("de-de.wxl");
+
+        index.parse_localization(
+            r#"<WixLocalization Culture="en-US">
+  <String Id="Title" Value="My App" />
+  <String Id="Legacy">Old style</String>
+</WixLocalization>"#,
+            &en,
+        );
+        index.parse_localization(
+            r#"<WixLocalization Culture="de-DE"><String Id="Title" Value="Meine App" /></WixLocalization>"#,
+            &de,
+        );
+
+        let strings = index.get_localized_strings();
+        let ids: Vec<&str> = strings.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec!["Legacy", "Title"]);
+        assert_eq!(strings[0].1[0].value, "Old style");
+        assert_eq!(strings[1].1.len(), 2);
+        assert_eq!(strings[1].1[0].location.start.line, 2);
+
+        index.remove_file(&de);
+        let strings = index.get_localized_strings();
+        assert_eq!(strings[1].1.len(), 1);
+        assert_eq!(strings[1].1[0].culture.as_deref(), Some("en-US"));
+    }
 }
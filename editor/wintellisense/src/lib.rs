@@ -15,6 +15,7 @@
 //!
 //! - **Schema Autocomplete**: Elements, attributes, values from wixkb
 //! - **Snippets**: Code templates with placeholders
+//! - **Expressions**: `$(var.X)`, `$(sys.X)`, `!(loc.X)`, `!(bind.X.Y)` and `[PROPERTY]`
 //! - **Go-to-Definition**: Navigate to symbol definitions
 //! - **Hover**: Documentation on hover
 //! - **Project Index**: Cross-file symbol references
@@ -54,7 +55,7 @@ pub use index::ProjectIndex;
 pub use loader::SchemaData;
 pub use types::{
     CompletionItem, CompletionKind, CompletionResult, CursorContext,
-    Definition, DefinitionResult, HoverInfo, HoverResult, Location, Position,
+    Definition, DefinitionResult, ExpressionKind, HoverInfo, HoverResult, Location, Position,
 };
 
 use anyhow::Result;
@@ -71,6 +72,9 @@ pub struct Engine {
 
     /// Maximum completions to return
     max_completions: usize,
+
+    /// Preprocessor variables defined by the build (`-d NAME=VALUE`)
+    defines: Vec<(String, String)>,
 }
 
 impl Engine {
@@ -81,6 +85,7 @@ impl Engine {
             schema,
             index: ProjectIndex::new(),
             max_completions: 50,
+            defines: Vec::new(),
         })
     }
 
@@ -90,6 +95,7 @@ impl Engine {
             schema: Arc::new(schema),
             index: ProjectIndex::new(),
             max_completions: 50,
+            defines: Vec::new(),
         }
     }
 
@@ -99,6 +105,14 @@ impl Engine {
         self
     }
 
+    /// Add a build define, given as `NAME=VALUE` or `NAME`
+    pub fn with_define(mut self, spec: &str) -> Self {
+        let (name, value) = spec.split_once('=').unwrap_or((spec, ""));
+        self.defines
+            .push((name.trim().to_string(), value.trim().to_string()));
+        self
+    }
+
     /// Index a project directory for cross-file symbols
     pub fn index_project(&mut self, root: &Path) -> Result<usize> {
         self.index.index_directory(root)
@@ -117,7 +131,14 @@ impl Engine {
 
     /// Get completions with pre-parsed context
     pub fn complete_with_context(&self, ctx: &CursorContext, source: &str) -> CompletionResult {
-        completions::get_completions(&self.schema, &self.index, ctx, source, self.max_completions)
+        completions::get_completions(
+            &self.schema,
+            &self.index,
+            ctx,
+            source,
+            &self.defines,
+            self.max_completions,
+        )
     }
 
     /// Get definition for symbol at position
@@ -174,4 +195,16 @@ mod tests {
         assert_eq!(stats.elements, 0);
         assert_eq!(stats.indexed_files, 0);
     }
+
+    #[test]
+    fn test_complete_build_define() {
+        let engine = Engine::with_schema(SchemaData::default())
+            .with_define("Configuration=Release")
+            .with_define("Debug");
+        let source = r#"<File Source="$(var."#;
+        let result = engine.complete(source, 1, 21);
+
+        let labels: Vec<&str> = result.items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["Configuration", "Debug"]);
+    }
 }
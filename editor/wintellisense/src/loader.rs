@@ -1,6 +1,6 @@
 //! Schema data loader from wixkb database
 
-use crate::types::{AttributeDef, ElementDef, Keywords, Snippet};
+use crate::types::{AttributeDef, ElementDef, Keywords, PropertyDef, Snippet};
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::Path;
@@ -11,6 +11,7 @@ pub struct SchemaData {
     pub elements: HashMap<String, ElementDef>,
     pub snippets: Vec<Snippet>,
     pub keywords: Keywords,
+    pub properties: Vec<PropertyDef>,
 }
 
 impl SchemaData {
@@ -130,6 +131,22 @@ impl SchemaData {
             }
         }
 
+        // Load standard properties (older databases may lack the table)
+        if let Ok(mut stmt) =
+            conn.prepare("SELECT name, description, readonly FROM builtin_properties ORDER BY name")
+        {
+            let property_rows = stmt.query_map([], |row| {
+                Ok(PropertyDef {
+                    name: row.get(0)?,
+                    description: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    readonly: row.get::<_, Option<bool>>(2)?.unwrap_or_default(),
+                })
+            })?;
+            for row in property_rows {
+                self.properties.push(row?);
+            }
+        }
+
         Ok(())
     }
 
@@ -216,6 +233,21 @@ impl SchemaData {
             .unwrap_or_default()
     }
 
+    /// Get standard properties: those from the database, then any other
+    /// names listed in the templates
+    pub fn get_standard_properties(&self) -> Vec<PropertyDef> {
+        let mut properties = self.properties.clone();
+        for name in &self.keywords.builtin_properties {
+            if !properties.iter().any(|p| &p.name == name) {
+                properties.push(PropertyDef {
+                    name: name.clone(),
+                    ..Default::default()
+                });
+            }
+        }
+        properties
+    }
+
     /// Get snippets matching prefix
     pub fn get_snippets_by_prefix(&self, prefix: &str) -> Vec<&Snippet> {
         let prefix_lower = prefix.to_lowercase();
@@ -307,4 +339,36 @@ mod tests {
         let all = schema.get_snippets_by_prefix("");
         assert_eq!(all.len(), 2);
     }
+
+    #[test]
+    fn test_load_builtin_properties() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("database")).unwrap();
+        let conn = rusqlite::Connection::open(temp.path().join("database/wixkb.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE elements (id INTEGER, name TEXT, description TEXT, documentation_url TEXT);
+             CREATE TABLE element_children (element_id INTEGER, child_id INTEGER);
+             CREATE TABLE attributes (element_id INTEGER, name TEXT, attr_type TEXT,
+                 required INTEGER, default_value TEXT, description TEXT);
+             CREATE TABLE builtin_properties (name TEXT, property_type TEXT,
+                 description TEXT, default_value TEXT, readonly INTEGER);
+             INSERT INTO builtin_properties VALUES
+                 ('VersionNT', 'integer', 'Windows version', NULL, 1),
+                 ('ALLUSERS', 'string', 'Installation context', NULL, 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut schema = SchemaData::load(temp.path()).unwrap();
+        schema.keywords.builtin_properties =
+            vec!["ProductName".to_string(), "VersionNT".to_string()];
+
+        let names: Vec<String> = schema
+            .get_standard_properties()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["ALLUSERS", "VersionNT", "ProductName"]);
+        assert!(schema.properties[1].readonly);
+    }
 }
@@ -67,6 +67,10 @@ enum Commands {
         /// Project directory to index for cross-file references
         #[arg(long)]
         project: Option<PathBuf>,
+
+        /// Preprocessor variable defined by the build (NAME=VALUE)
+        #[arg(long = "define", short = 'd')]
+        defines: Vec<String>,
     },
 
     /// Go to definition for symbol at position
@@ -168,12 +172,17 @@ fn main() -> Result<()> {
             column,
             max,
             project,
+            defines,
         } => {
             if let Some(project_path) = project {
                 engine.index_project(&project_path)?;
             }
 
-            let engine = engine.with_max_completions(max);
+            let engine = defines
+                .iter()
+                .fold(engine.with_max_completions(max), |engine, spec| {
+                    engine.with_define(spec)
+                });
             let source = std::fs::read_to_string(&file)?;
             let result = engine.complete(&source, line, column);
 
@@ -274,6 +283,7 @@ fn main() -> Result<()> {
                         prefix: String,
                         word_at_cursor: Option<String>,
                         existing_attributes: Vec<String>,
+                        expression: Option<wintellisense::ExpressionKind>,
                     }
 
                     let output = ContextOutput {
@@ -286,6 +296,7 @@ fn main() -> Result<()> {
                         prefix: ctx.prefix.clone(),
                         word_at_cursor: ctx.word_at_cursor.clone(),
                         existing_attributes: ctx.existing_attributes.clone(),
+                        expression: ctx.expression,
                     };
 
                     println!("{}", serde_json::to_string_pretty(&output)?);
@@ -301,11 +312,13 @@ fn main() -> Result<()> {
                     println!("  Prefix:            {:?}", ctx.prefix);
                     println!("  Word at cursor:    {:?}", ctx.word_at_cursor);
                     println!("  Existing attrs:    {:?}", ctx.existing_attributes);
+                    println!("  Expression:        {:?}", ctx.expression);
                     println!();
                     println!("Should suggest:");
                     println!("  Elements:   {}", ctx.should_suggest_elements());
                     println!("  Attributes: {}", ctx.should_suggest_attributes());
                     println!("  Values:     {}", ctx.should_suggest_values());
+                    println!("  Expressions:{}", ctx.should_suggest_expressions());
                 }
             }
         }
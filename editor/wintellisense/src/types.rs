@@ -74,10 +74,34 @@ pub enum ContextKind {
         partial: String,
     },
 
+    /// Cursor is inside a variable or property reference within a value
+    Expression {
+        /// What the reference names
+        kind: ExpressionKind,
+        /// Partial name already typed after the namespace
+        partial: String,
+    },
+
     /// Unknown/unsupported context
     Unknown,
 }
 
+/// Kind of reference being typed inside a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExpressionKind {
+    /// `$(var.NAME)` preprocessor variable
+    Variable,
+    /// `$(sys.NAME)` built-in system variable
+    System,
+    /// `!(loc.ID)` localization string
+    Localization,
+    /// `!(bind.KIND.ID)` bind-time variable
+    Bind,
+    /// `[NAME]` formatted property
+    Property,
+}
+
 impl Default for ContextKind {
     fn default() -> Self {
         Self::Unknown
@@ -119,6 +143,9 @@ pub struct CursorContext {
 
     /// Existing attributes on current element
     pub existing_attributes: Vec<String>,
+
+    /// Reference being typed inside a value (prefix holds its partial name)
+    pub expression: Option<ExpressionKind>,
 }
 
 impl CursorContext {
@@ -134,8 +161,19 @@ impl CursorContext {
         self.in_attribute_value && self.current_attribute.is_some()
     }
 
+    pub fn should_suggest_expressions(&self) -> bool {
+        self.expression.is_some()
+    }
+
     /// Convert to enum-based context kind for pattern matching
     pub fn kind(&self) -> ContextKind {
+        if let Some(kind) = self.expression {
+            return ContextKind::Expression {
+                kind,
+                partial: self.prefix.clone(),
+            };
+        }
+
         if self.in_attribute_value {
             if let (Some(element), Some(attribute)) =
                 (&self.current_element, &self.current_attribute)
@@ -235,6 +273,7 @@ pub enum CompletionKind {
     Reference,
     Keyword,
     Word,
+    Variable,
 }
 
 /// A completion item
@@ -472,6 +511,16 @@ pub struct AttributeDef {
     pub values: Option<Vec<String>>,
 }
 
+/// Standard Windows Installer property
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PropertyDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub readonly: bool,
+}
+
 /// Snippet definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Snippet {
@@ -22,6 +22,11 @@ pub fn detect_hover_target(source: &str, line: u32, column: u32) -> HoverTarget
         return HoverTarget::None;
     }
 
+    // References inside values, text and processing instructions
+    if let Some(target) = find_reference(source, line, column) {
+        return target;
+    }
+
     // Find if we're inside a tag
     if let Some((tag_start, tag_end)) = find_enclosing_tag(&chars, offset) {
         return parse_hover_in_tag(&chars, tag_start, tag_end, offset, line, column, source);
@@ -299,6 +304,70 @@ fn find_attribute_hover(
     None
 }
 
+/// Find a `$(var.X)`, `!(loc.X)` or `[X]` reference under the cursor.
+///
+/// References never span lines, so only the cursor's line is searched.
+fn find_reference(source: &str, line: u32, column: u32) -> Option<HoverTarget> {
+    let text = source.lines().nth(line.checked_sub(1)? as usize)?;
+    let chars: Vec<char> = text.chars().collect();
+    let cursor = column.checked_sub(1)? as usize;
+
+    let mut pos = 0;
+    while pos < chars.len() {
+        let Some((open, close)) = reference_delimiters(&chars[pos..]) else {
+            pos += 1;
+            continue;
+        };
+        let name_start = pos + open.chars().count();
+        let Some(len) = chars[name_start..].iter().position(|&c| c == close) else {
+            break;
+        };
+        let name: String = chars[name_start..name_start + len].iter().collect();
+        let end = name_start + len + 1;
+
+        if !is_reference_name(&name) {
+            pos += 1;
+            continue;
+        }
+        if (pos..end).contains(&cursor) {
+            let range = Range {
+                start_line: line,
+                start_col: pos as u32 + 1,
+                end_line: line,
+                end_col: end as u32 + 1,
+            };
+            return Some(match open {
+                "$(var." => HoverTarget::Variable { name, range },
+                "!(loc." => HoverTarget::Localization { id: name, range },
+                _ => HoverTarget::Property { name, range },
+            });
+        }
+        pos = end;
+    }
+
+    None
+}
+
+/// Opening marker and closing character of a reference starting at `chars`.
+fn reference_delimiters(chars: &[char]) -> Option<(&'static str, char)> {
+    ["$(var.", "!(loc.", "["]
+        .into_iter()
+        .find(|open| {
+            open.chars().count() <= chars.len() && open.chars().zip(chars).all(|(a, &b)| a == b)
+        })
+        .map(|open| (open, if open == "[" { ']' } else { ')' }))
+}
+
+/// Names of variables, strings and properties; `[#File]`, `[%ENV]` and
+/// escapes like `[\[]` are other formatted-string references.
+fn is_reference_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
 /// Check if cursor is on a closing tag element name.
 fn check_closing_tag(
     chars: &[char],
@@ -503,4 +572,49 @@ mod tests {
             _ => panic!("Expected AttributeName, got {:?}", target),
         }
     }
+
+    #[test]
+    fn test_hover_variable_reference() {
+        let source = "<File Source=\"$(var.Bin)\\app.exe\" />";
+        let target = detect_hover_target(source, 1, 18); // On "var.Bin"
+
+        match target {
+            HoverTarget::Variable { name, range } => {
+                assert_eq!(name, "Bin");
+                assert_eq!((range.start_col, range.end_col), (15, 25));
+            }
+            _ => panic!("Expected Variable, got {:?}", target),
+        }
+
+        // The rest of the value is still an attribute value
+        let target = detect_hover_target(source, 1, 28);
+        assert!(matches!(target, HoverTarget::AttributeValue { .. }));
+    }
+
+    #[test]
+    fn test_hover_localization_and_property() {
+        let source = "<Property Id=\"T\" Value=\"!(loc.Title) [INSTALLFOLDER] [#File]\" />";
+
+        match detect_hover_target(source, 1, 30) {
+            HoverTarget::Localization { id, .. } => assert_eq!(id, "Title"),
+            target => panic!("Expected Localization, got {:?}", target),
+        }
+        match detect_hover_target(source, 1, 40) {
+            HoverTarget::Property { name, .. } => assert_eq!(name, "INSTALLFOLDER"),
+            target => panic!("Expected Property, got {:?}", target),
+        }
+        // File references are not properties
+        assert!(matches!(
+            detect_hover_target(source, 1, 55),
+            HoverTarget::AttributeValue { .. }
+        ));
+    }
+
+    #[test]
+    fn test_hover_reference_in_processing_instruction() {
+        let source = "<Wix>\n  <?if $(var.Platform) = x64 ?>\n</Wix>";
+        let target = detect_hover_target(source, 2, 12);
+
+        assert!(matches!(target, HoverTarget::Variable { ref name, .. } if name == "Platform"));
+    }
 }
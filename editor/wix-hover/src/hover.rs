@@ -1,11 +1,14 @@
 //! Core hover logic.
 //!
 //! This module contains the [`HoverProvider`] which generates formatted
-//! hover documentation for WiX elements, attributes, and values.
+//! hover documentation for WiX elements, attributes, values, and the
+//! variables, strings and properties referenced from values.
 
 use crate::context::detect_hover_target;
 use crate::loader::WixData;
+use crate::project::{DefinitionSite, ProjectContext};
 use crate::types::{HoverInfo, HoverTarget};
+use wix_references::{reference_attribute_kind, SymbolKind};
 
 /// Hover information provider.
 ///
@@ -32,6 +35,21 @@ impl HoverProvider {
     /// `Some(HoverInfo)` with formatted documentation, or `None` if no hover
     /// information is available at the position.
     pub fn hover(&self, source: &str, line: u32, column: u32) -> Option<HoverInfo> {
+        self.hover_in_project(source, line, column, &ProjectContext::new())
+    }
+
+    /// Get hover information using definitions from the rest of the project.
+    ///
+    /// Like [`hover`](Self::hover), but references to variables, localization
+    /// strings, properties and Ids are looked up in `project`. The hovered
+    /// file should already be in `project` with its current content.
+    pub fn hover_in_project(
+        &self,
+        source: &str,
+        line: u32,
+        column: u32,
+        project: &ProjectContext,
+    ) -> Option<HoverInfo> {
         let target = detect_hover_target(source, line, column);
 
         match target {
@@ -53,7 +71,20 @@ impl HoverProvider {
                 value,
                 range,
             } => self
-                .hover_value(&element, &attribute, &value)
+                .hover_id_reference(&element, &attribute, &value, project)
+                .or_else(|| self.hover_value(&element, &attribute, &value))
+                .map(|info| info.with_range(range)),
+
+            HoverTarget::Variable { name, range } => {
+                Some(self.hover_variable(&name, project).with_range(range))
+            }
+
+            HoverTarget::Localization { id, range } => {
+                Some(self.hover_localization(&id, project).with_range(range))
+            }
+
+            HoverTarget::Property { name, range } => self
+                .hover_property(&name, project)
                 .map(|info| info.with_range(range)),
 
             HoverTarget::None => None,
@@ -172,6 +203,137 @@ impl HoverProvider {
         None
     }
 
+    /// Preview of the element an Id reference points to.
+    fn hover_id_reference(
+        &self,
+        element: &str,
+        attribute: &str,
+        value: &str,
+        project: &ProjectContext,
+    ) -> Option<HoverInfo> {
+        let kind = if attribute == "Id" && SymbolKind::is_reference_element(element) {
+            SymbolKind::from_element(element)
+        } else {
+            reference_attribute_kind(element, attribute)?
+        };
+        let preview = project.definition_preview(value, kind)?;
+
+        Some(HoverInfo::new(format!(
+            "### {} `{}`\n\nDefined in {}\n\n```xml\n{}\n```",
+            preview.element,
+            value,
+            format_site(&preview.site),
+            preview.text
+        )))
+    }
+
+    /// Values of a preprocessor variable under each condition it is defined.
+    fn hover_variable(&self, name: &str, project: &ProjectContext) -> HoverInfo {
+        let mut content = format!("### $(var.{})\n\n", name);
+        let definitions = project.variables(name);
+        let build = project.build_define(name);
+
+        if definitions.is_empty() && build.is_none() {
+            content.push_str("Not defined in the project or by the build.");
+            return HoverInfo::new(content);
+        }
+
+        content.push_str("| Condition | Value | Defined in |\n|---|---|---|\n");
+        if let Some(value) = build {
+            content.push_str(&format!(
+                "| build | `{}` | command line |\n",
+                escape_cell(value)
+            ));
+        }
+        for definition in definitions {
+            let condition = match &definition.condition {
+                Some(condition) => format!("`{}`", escape_cell(condition)),
+                None => "always".to_string(),
+            };
+            content.push_str(&format!(
+                "| {} | `{}` | {} |\n",
+                condition,
+                escape_cell(&definition.value),
+                format_site(&definition.site)
+            ));
+        }
+
+        HoverInfo::new(content.trim().to_string())
+    }
+
+    /// Translations of a localization string, flagging cultures without one.
+    fn hover_localization(&self, id: &str, project: &ProjectContext) -> HoverInfo {
+        let mut content = format!("### !(loc.{})\n\n", id);
+        let cultures = project.cultures();
+        if cultures.is_empty() {
+            content.push_str("No localization files in the project.");
+            return HoverInfo::new(content);
+        }
+
+        let translations = project.translations(id);
+        let mut missing = Vec::new();
+        content.push_str("| Culture | Translation |\n|---|---|\n");
+        for culture in cultures {
+            let label = culture.unwrap_or("(neutral)");
+            match translations
+                .iter()
+                .find(|t| t.culture.as_deref() == culture)
+            {
+                Some(translation) => content.push_str(&format!(
+                    "| {} | {} |\n",
+                    label,
+                    escape_cell(&translation.value)
+                )),
+                None => {
+                    content.push_str(&format!("| {} | ⚠ *missing* |\n", label));
+                    missing.push(label);
+                }
+            }
+        }
+        if !missing.is_empty() {
+            content.push_str(&format!("\n**Missing:** {}", missing.join(", ")));
+        }
+
+        HoverInfo::new(content.trim().to_string())
+    }
+
+    /// Where a property is defined, its default value and how it is exposed.
+    fn hover_property(&self, name: &str, project: &ProjectContext) -> Option<HoverInfo> {
+        let definitions = project.properties(name);
+        if definitions.is_empty() {
+            return self
+                .data
+                .is_builtin_property(name)
+                .then(|| self.hover_builtin_property(name));
+        }
+
+        let public = !name.chars().any(|c| c.is_ascii_lowercase());
+        let secure = definitions.iter().any(|d| d.secure);
+        let mut content = format!("### [{}]\n\n", name);
+        content.push_str(if public {
+            "**Public** property"
+        } else {
+            "**Private** property"
+        });
+        if secure {
+            content.push_str(", **Secure**");
+        }
+        content.push_str("\n\n");
+
+        for definition in definitions {
+            match &definition.value {
+                Some(value) => content.push_str(&format!("**Default:** `{}`", value)),
+                None => content.push_str("**Default:** *none*"),
+            }
+            content.push_str(&format!(
+                " (defined in {})\n\n",
+                format_site(&definition.site)
+            ));
+        }
+
+        Some(HoverInfo::new(content.trim().to_string()))
+    }
+
     /// Hover info for standard directories.
     fn hover_standard_directory(&self, name: &str) -> HoverInfo {
         let description = match name {
@@ -222,6 +384,21 @@ impl HoverProvider {
     }
 }
 
+/// `File.wxs:12` for a definition site.
+fn format_site(site: &DefinitionSite) -> String {
+    let file = site
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_else(|| site.path.to_string_lossy());
+    format!("`{}:{}`", file, site.line)
+}
+
+/// Escape a value for a markdown table cell.
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
 /// Format attribute type for display.
 fn format_type(type_name: &str) -> String {
     match type_name {
@@ -241,6 +418,7 @@ fn format_type(type_name: &str) -> String {
 mod tests {
     use super::*;
     use std::fs;
+    use std::path::Path;
     use tempfile::TempDir;
 
     fn create_test_data() -> (TempDir, WixData) {
@@ -502,4 +680,126 @@ mod tests {
             );
         }
     }
+
+    fn create_test_project() -> ProjectContext {
+        let mut project = ProjectContext::new().with_define("Configuration=Release");
+        project
+            .update_file(
+                Path::new("/src/Product.wxs"),
+                r#"<Wix>
+  <?if $(var.Configuration) = Debug ?>
+  <?define Bin = "out\debug" ?>
+  <?else?>
+  <?define Bin = "out|release" ?>
+  <?endif?>
+  <Property Id="INSTALLMODE" Value="full" Secure="yes" />
+  <Property Id="CustomState" />
+  <Fragment>
+    <Component Id="Core" Directory="INSTALLFOLDER" Guid="*" />
+  </Fragment>
+</Wix>"#,
+            )
+            .unwrap();
+        project
+            .update_file(
+                Path::new("/src/en-us.wxl"),
+                r#"<WixLocalization Culture="en-US"><String Id="Title" Value="My App" /></WixLocalization>"#,
+            )
+            .unwrap();
+        project
+            .update_file(
+                Path::new("/src/de-de.wxl"),
+                r#"<WixLocalization Culture="de-DE"><String Id="Other" Value="x" /></WixLocalization>"#,
+            )
+            .unwrap();
+        project
+    }
+
+    #[test]
+    fn test_hover_variable_per_configuration() {
+        let (_temp, data) = create_test_data();
+        let provider = HoverProvider::new(data);
+        let project = create_test_project();
+
+        let source = r#"<File Source="$(var.Bin)\app.exe" />"#;
+        let info = provider.hover_in_project(source, 1, 17, &project).unwrap();
+
+        assert!(info.contents.starts_with("### $(var.Bin)"));
+        assert!(info
+            .contents
+            .contains("| `$(var.Configuration) = Debug` | `out\\debug` | `Product.wxs:3` |"));
+        assert!(info.contents.contains(
+            "| `not ($(var.Configuration) = Debug)` | `out\\|release` | `Product.wxs:5` |"
+        ));
+        assert_eq!(info.range.unwrap().start_col, 15);
+
+        let source = r#"<?if $(var.Configuration) = Debug ?>"#;
+        let info = provider.hover_in_project(source, 1, 8, &project).unwrap();
+        assert!(info
+            .contents
+            .contains("| build | `Release` | command line |"));
+
+        let info = provider
+            .hover(r#"<File Source="$(var.Bin)" />"#, 1, 17)
+            .unwrap();
+        assert!(info.contents.contains("Not defined"));
+    }
+
+    #[test]
+    fn test_hover_localization_translations() {
+        let (_temp, data) = create_test_data();
+        let provider = HoverProvider::new(data);
+        let project = create_test_project();
+
+        let source = r#"<Package Name="!(loc.Title)" />"#;
+        let info = provider.hover_in_project(source, 1, 20, &project).unwrap();
+
+        assert!(info.contents.contains("| en-US | My App |"));
+        assert!(info.contents.contains("| de-DE | ⚠ *missing* |"));
+        assert!(info.contents.contains("**Missing:** de-DE"));
+    }
+
+    #[test]
+    fn test_hover_formatted_property() {
+        let (_temp, data) = create_test_data();
+        let provider = HoverProvider::new(data);
+        let project = create_test_project();
+
+        let source = r#"<SetProperty Value="[INSTALLMODE] [CustomState] [ProductName] [Nope]" />"#;
+        let info = provider.hover_in_project(source, 1, 23, &project).unwrap();
+        assert!(info.contents.contains("**Public** property, **Secure**"));
+        assert!(info
+            .contents
+            .contains("**Default:** `full` (defined in `Product.wxs:7`)"));
+
+        let info = provider.hover_in_project(source, 1, 37, &project).unwrap();
+        assert!(info.contents.contains("**Private** property"));
+        assert!(info.contents.contains("**Default:** *none*"));
+
+        let info = provider.hover_in_project(source, 1, 52, &project).unwrap();
+        assert!(info.contents.contains("Built-in Property"));
+
+        assert!(provider.hover_in_project(source, 1, 65, &project).is_none());
+    }
+
+    #[test]
+    fn test_hover_id_reference_preview() {
+        let (_temp, data) = create_test_data();
+        let provider = HoverProvider::new(data);
+        let project = create_test_project();
+
+        let source = r#"<ComponentRef Id="Core" />"#;
+        let info = provider.hover_in_project(source, 1, 20, &project).unwrap();
+
+        assert!(info.contents.starts_with("### Component `Core`"));
+        assert!(info.contents.contains("Defined in `Product.wxs:10`"));
+        assert!(info.contents.contains(
+            "```xml\n<Component Id=\"Core\" Directory=\"INSTALLFOLDER\" Guid=\"*\" />\n```"
+        ));
+
+        // Unresolved references fall back to value hover
+        let source = r#"<DirectoryRef Id="ProgramFilesFolder" />"#;
+        let info = provider.hover_in_project(source, 1, 22, &project).unwrap();
+        assert!(info.contents.contains("Standard Directory"));
+    }
 }
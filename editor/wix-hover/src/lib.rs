@@ -11,12 +11,16 @@
 //! - Element hover: description, parents, children, documentation links
 //! - Attribute hover: type, required, default value, enum values
 //! - Value hover: standard directories, builtin properties, auto-GUID
+//! - Reference hover: `$(var.X)` values per configuration, `!(loc.X)`
+//!   translations per culture, `[PROPERTY]` definitions and previews of Ids
+//!   defined in other files, given a [`ProjectContext`]
 //! - Range tracking for precise highlighting
 //!
 //! ## Usage
 //!
 //! ```rust,ignore
-//! use wix_hover::{HoverProvider, WixData};
+//! use std::path::Path;
+//! use wix_hover::{HoverProvider, ProjectContext, WixData};
 //!
 //! // Load WiX schema data
 //! let data = WixData::load("path/to/wix-data")?;
@@ -29,6 +33,11 @@
 //! if let Some(info) = provider.hover(source, 1, 3) {
 //!     println!("{}", info.contents);
 //! }
+//!
+//! // Hover over references with the rest of the project in view
+//! let mut project = ProjectContext::new().with_define("Configuration=Release");
+//! project.load_directory(Path::new("installer"))?;
+//! let info = provider.hover_in_project(source, 1, 3, &project);
 //! ```
 //!
 //! ## CLI Usage
@@ -40,9 +49,14 @@
 mod context;
 mod hover;
 mod loader;
+mod project;
 mod types;
 
 pub use context::detect_hover_target;
 pub use hover::HoverProvider;
 pub use loader::{LoadError, WixData};
+pub use project::{
+    DefinitionPreview, DefinitionSite, ProjectContext, PropertyDefinition, Translation,
+    VariableDefinition,
+};
 pub use types::{AttributeDef, ElementDef, HoverInfo, HoverTarget, Keywords, Range};
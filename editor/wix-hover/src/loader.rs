@@ -23,6 +23,13 @@ pub enum LoadError {
         source: serde_json::Error,
     },
 
+    /// A project file is not well-formed XML.
+    #[error("Failed to parse {file}: {source}")]
+    Xml {
+        file: PathBuf,
+        source: roxmltree::Error,
+    },
+
     /// The wix-data directory was not found.
     #[error("wix-data directory not found: {0}")]
     NotFound(PathBuf),
@@ -11,6 +11,9 @@
 //! # Read from stdin
 //! cat file.wxs | wix-hover - 10 15 --wix-data ./wix-data
 //!
+//! # Resolve variables, strings and Ids across a project
+//! wix-hover file.wxs 10 15 --wix-data ./wix-data --project . -d Configuration=Release
+//!
 //! # Different output formats
 //! wix-hover file.wxs 10 15 --wix-data ./wix-data --format json
 //! wix-hover file.wxs 10 15 --wix-data ./wix-data --format plain
@@ -21,7 +24,7 @@ use std::fs;
 use std::io::{self, Read};
 use std::path::PathBuf;
 use std::process::ExitCode;
-use wix_hover::{HoverProvider, WixData};
+use wix_hover::{HoverProvider, ProjectContext, WixData};
 
 #[derive(Parser)]
 #[command(name = "wix-hover")]
@@ -41,6 +44,14 @@ struct Cli {
     #[arg(long)]
     wix_data: Option<PathBuf>,
 
+    /// Project directory whose files resolve references
+    #[arg(long)]
+    project: Option<PathBuf>,
+
+    /// Preprocessor variable defined by the build (NAME=VALUE)
+    #[arg(short = 'd', long = "define")]
+    defines: Vec<String>,
+
     /// Output format
     #[arg(long, value_enum, default_value = "markdown")]
     format: OutputFormat,
@@ -100,9 +111,27 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("Position: line {}, column {}", cli.line, cli.column);
     }
 
+    // Load the project, with the hovered file as read
+    let mut project = cli
+        .defines
+        .iter()
+        .fold(ProjectContext::new(), |project, spec| {
+            project.with_define(spec)
+        });
+    if let Some(ref root) = cli.project {
+        let count = project.load_directory(root)?;
+        if cli.verbose {
+            eprintln!("Loaded {} project files from {}", count, root.display());
+        }
+    }
+    if cli.file != "-" {
+        let path = fs::canonicalize(&cli.file).unwrap_or_else(|_| PathBuf::from(&cli.file));
+        let _ = project.update_file(&path, &source);
+    }
+
     // Get hover info
     let provider = HoverProvider::new(data);
-    let hover = provider.hover(&source, cli.line, cli.column);
+    let hover = provider.hover_in_project(&source, cli.line, cli.column, &project);
 
     match hover {
         Some(info) => {
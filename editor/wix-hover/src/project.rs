@@ -0,0 +1,470 @@
+//! Project context for hover.
+//!
+//! Hovering a reference is only useful with what the rest of the project
+//! says about it: where a preprocessor variable is defined and under which
+//! `<?if?>` conditions, how a localization string is translated, and where
+//! a property or other Id is defined. [`ProjectContext`] collects these from
+//! the project's `.wxs`, `.wxi` and `.wxl` files.
+
+use crate::loader::LoadError;
+use roxmltree::{Document, Node};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wix_references::{ReferenceIndex, SymbolKind};
+
+/// Directories never searched for project files.
+const SKIPPED_DIRS: &[&str] = &["bin", "obj", "target", "node_modules"];
+
+/// Longest definition preview, in lines.
+const MAX_PREVIEW_LINES: usize = 10;
+
+/// Where something is defined (1-based line).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionSite {
+    pub path: PathBuf,
+    pub line: u32,
+}
+
+/// A `<?define?>` and the conditions it is nested in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableDefinition {
+    pub name: String,
+    pub value: String,
+    /// Enclosing `<?if?>` conditions, `None` when always defined
+    pub condition: Option<String>,
+    pub site: DefinitionSite,
+}
+
+/// A `<String>` from a `.wxl` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Translation {
+    pub id: String,
+    /// Culture of the file, `None` for culture-neutral files
+    pub culture: Option<String>,
+    pub value: String,
+    pub site: DefinitionSite,
+}
+
+/// A `<Property>` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyDefinition {
+    pub id: String,
+    pub value: Option<String>,
+    pub secure: bool,
+    pub site: DefinitionSite,
+}
+
+/// Source text of an element defined elsewhere in the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionPreview {
+    pub element: String,
+    pub site: DefinitionSite,
+    /// Element source, dedented and cut at [`MAX_PREVIEW_LINES`]
+    pub text: String,
+}
+
+/// What one file contributes to the project.
+#[derive(Debug, Default)]
+struct FileFacts {
+    variables: Vec<VariableDefinition>,
+    culture: Option<String>,
+    is_localization: bool,
+    translations: Vec<Translation>,
+    properties: Vec<PropertyDefinition>,
+}
+
+/// Definitions across a project, used by
+/// [`HoverProvider::hover_in_project`](crate::HoverProvider::hover_in_project).
+#[derive(Default)]
+pub struct ProjectContext {
+    /// Defines passed to the build (`-d NAME=VALUE`)
+    build_defines: Vec<(String, String)>,
+    files: BTreeMap<PathBuf, FileFacts>,
+    references: ReferenceIndex,
+}
+
+impl ProjectContext {
+    /// Create an empty project.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a build define, given as `NAME=VALUE` or `NAME`.
+    pub fn with_define(mut self, spec: &str) -> Self {
+        let (name, value) = spec.split_once('=').unwrap_or((spec, ""));
+        self.build_defines
+            .push((name.trim().to_string(), value.trim().to_string()));
+        self
+    }
+
+    /// Add every WiX source and localization file under `root`.
+    ///
+    /// Files are keyed by canonical path. Files that fail to parse are
+    /// skipped. Returns the number added.
+    pub fn load_directory(&mut self, root: &Path) -> Result<usize, LoadError> {
+        let mut count = 0;
+        let mut pending = vec![fs::canonicalize(root)?];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                if path.is_dir() {
+                    if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name) {
+                        pending.push(path);
+                    }
+                } else if is_project_file(&path) {
+                    let content = fs::read_to_string(&path)?;
+                    if self.update_file(&path, &content).is_ok() {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Add a file or replace its content, e.g. with an unsaved editor buffer.
+    ///
+    /// On a parse error the file's previous definitions are kept.
+    pub fn update_file(&mut self, path: &Path, content: &str) -> Result<(), LoadError> {
+        let doc = Document::parse(content).map_err(|e| LoadError::Xml {
+            file: path.to_path_buf(),
+            source: e,
+        })?;
+        self.files
+            .insert(path.to_path_buf(), FileFacts::new(path, &doc));
+        let _ = self.references.update_file(&path_key(path), content);
+        Ok(())
+    }
+
+    /// Forget a file.
+    pub fn remove_file(&mut self, path: &Path) {
+        self.files.remove(path);
+        self.references.remove_file(&path_key(path));
+    }
+
+    /// Value of a build define.
+    pub fn build_define(&self, name: &str) -> Option<&str> {
+        self.build_defines
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `<?define?>`s of a variable, by file and then document order.
+    pub fn variables(&self, name: &str) -> Vec<&VariableDefinition> {
+        self.files
+            .values()
+            .flat_map(|f| &f.variables)
+            .filter(|v| v.name == name)
+            .collect()
+    }
+
+    /// Translations of a localization string, by file.
+    pub fn translations(&self, id: &str) -> Vec<&Translation> {
+        self.files
+            .values()
+            .flat_map(|f| &f.translations)
+            .filter(|t| t.id == id)
+            .collect()
+    }
+
+    /// Cultures of all localization files, sorted; `None` is culture-neutral.
+    pub fn cultures(&self) -> Vec<Option<&str>> {
+        let mut cultures: Vec<Option<&str>> = self
+            .files
+            .values()
+            .filter(|f| f.is_localization)
+            .map(|f| f.culture.as_deref())
+            .collect();
+        cultures.sort();
+        cultures.dedup();
+        cultures
+    }
+
+    /// `<Property>` elements defining a property.
+    pub fn properties(&self, id: &str) -> Vec<&PropertyDefinition> {
+        self.files
+            .values()
+            .flat_map(|f| &f.properties)
+            .filter(|p| p.id == id)
+            .collect()
+    }
+
+    /// Source of the element defining an Id of the given kind.
+    pub fn definition_preview(&self, name: &str, kind: SymbolKind) -> Option<DefinitionPreview> {
+        let definition = self
+            .references
+            .find_all_definitions(name)
+            .into_iter()
+            .find(|d| d.kind == kind)?;
+        let content = self.references.file_content(&definition.location.file)?;
+        let range = definition.location.range;
+        let lines: Vec<&str> = content
+            .lines()
+            .skip(range.start.line.saturating_sub(1) as usize)
+            .take((range.end.line - range.start.line + 1) as usize)
+            .collect();
+
+        Some(DefinitionPreview {
+            element: definition.element.clone(),
+            site: DefinitionSite {
+                path: PathBuf::from(&definition.location.file),
+                line: range.start.line,
+            },
+            text: preview_text(&lines, range.start.character),
+        })
+    }
+}
+
+impl FileFacts {
+    fn new(path: &Path, doc: &Document) -> Self {
+        let site = |node: Node| DefinitionSite {
+            path: path.to_path_buf(),
+            line: doc.text_pos_at(node.range().start).row,
+        };
+        let root = doc.root_element();
+        let mut facts = FileFacts {
+            is_localization: root.has_tag_name("WixLocalization"),
+            culture: root.attribute("Culture").map(str::to_string),
+            ..Default::default()
+        };
+        let mut conditions = Conditions::default();
+
+        for node in doc.descendants() {
+            if let Some(pi) = node.pi() {
+                let value = pi.value.unwrap_or_default().trim();
+                if pi.target == "define" {
+                    if let Some((name, value)) = value.split_once('=') {
+                        facts.variables.push(VariableDefinition {
+                            name: name.trim().to_string(),
+                            value: value.trim().trim_matches('"').to_string(),
+                            condition: conditions.current(),
+                            site: site(node),
+                        });
+                    }
+                } else {
+                    conditions.apply(pi.target, value);
+                }
+            } else if node.has_tag_name("String") && facts.is_localization {
+                if let Some(id) = node.attribute("Id") {
+                    // WiX v4 uses Value=, v3 the element text
+                    let value = node.attribute("Value").or_else(|| node.text());
+                    facts.translations.push(Translation {
+                        id: id.to_string(),
+                        culture: facts.culture.clone(),
+                        value: value.unwrap_or_default().to_string(),
+                        site: site(node),
+                    });
+                }
+            } else if node.has_tag_name("Property") {
+                if let Some(id) = node.attribute("Id") {
+                    facts.properties.push(PropertyDefinition {
+                        id: id.to_string(),
+                        value: node.attribute("Value").map(str::to_string),
+                        secure: node.attribute("Secure") == Some("yes"),
+                        site: site(node),
+                    });
+                }
+            }
+        }
+        facts
+    }
+}
+
+/// Open `<?if?>` blocks while walking a document.
+#[derive(Default)]
+struct Conditions {
+    /// Per block: conditions of earlier branches and of the current one
+    blocks: Vec<(Vec<String>, String)>,
+}
+
+impl Conditions {
+    fn apply(&mut self, target: &str, value: &str) {
+        match target {
+            "if" => self.blocks.push((Vec::new(), value.to_string())),
+            "ifdef" => self
+                .blocks
+                .push((Vec::new(), format!("{} is defined", value))),
+            "ifndef" => self
+                .blocks
+                .push((Vec::new(), format!("{} is not defined", value))),
+            "elseif" | "else" => {
+                if let Some((earlier, current)) = self.blocks.last_mut() {
+                    earlier.push(std::mem::take(current));
+                    *current = if target == "elseif" {
+                        value.to_string()
+                    } else {
+                        earlier
+                            .iter()
+                            .map(|c| format!("not ({})", c))
+                            .collect::<Vec<_>>()
+                            .join(" and ")
+                    };
+                }
+            }
+            "endif" => {
+                self.blocks.pop();
+            }
+            _ => {}
+        }
+    }
+
+    fn current(&self) -> Option<String> {
+        if self.blocks.is_empty() {
+            return None;
+        }
+        let parts: Vec<&str> = self.blocks.iter().map(|(_, c)| c.as_str()).collect();
+        Some(parts.join(" and "))
+    }
+}
+
+/// Element lines with the first one starting at the element, dedented.
+fn preview_text(lines: &[&str], start_column: u32) -> String {
+    let first_indent = start_column.saturating_sub(1) as usize;
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .chain(std::iter::once(first_indent))
+        .min()
+        .unwrap_or(0);
+
+    let mut text: Vec<String> = lines
+        .iter()
+        .take(MAX_PREVIEW_LINES)
+        .enumerate()
+        .map(|(i, line)| {
+            let skip = if i == 0 { first_indent } else { indent };
+            let cut: String = line.chars().skip(skip).collect();
+            if i == 0 {
+                format!("{}{}", " ".repeat(first_indent - indent), cut)
+            } else {
+                cut
+            }
+        })
+        .collect();
+    if lines.len() > MAX_PREVIEW_LINES {
+        text.push("…".to_string());
+    }
+    text.join("\n").trim_end().to_string()
+}
+
+fn is_project_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "wxs" | "wxi" | "wxl"))
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_variables_with_conditions() {
+        let mut project = ProjectContext::new().with_define("Configuration=Release");
+        let source = r#"<Wix>
+  <?define Name = "App" ?>
+  <?if $(var.Configuration) = Debug ?>
+    <?define Bin = "out\debug" ?>
+  <?elseif $(var.Configuration) = Test ?>
+    <?define Bin = "out\test" ?>
+  <?else?>
+    <?ifdef Signed?>
+      <?define Bin = "out\signed" ?>
+    <?endif?>
+  <?endif?>
+</Wix>"#;
+        project
+            .update_file(Path::new("Product.wxs"), source)
+            .unwrap();
+
+        assert_eq!(project.build_define("Configuration"), Some("Release"));
+        assert_eq!(project.variables("Name")[0].condition, None);
+
+        let bin: Vec<(&str, Option<&str>, u32)> = project
+            .variables("Bin")
+            .iter()
+            .map(|v| (v.value.as_str(), v.condition.as_deref(), v.site.line))
+            .collect();
+        assert_eq!(
+            bin,
+            vec![
+                ("out\\debug", Some("$(var.Configuration) = Debug"), 4),
+                ("out\\test", Some("$(var.Configuration) = Test"), 6),
+                (
+                    "out\\signed",
+                    Some(
+                        "not ($(var.Configuration) = Debug) and not ($(var.Configuration) = Test) \
+                         and Signed is defined"
+                    ),
+                    9
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_directory() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("loc")).unwrap();
+        fs::create_dir_all(temp.path().join("bin")).unwrap();
+        fs::write(
+            temp.path().join("loc/en-us.wxl"),
+            r#"<WixLocalization Culture="en-US"><String Id="Title" Value="My App" /></WixLocalization>"#,
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("loc/de-de.wxl"),
+            r#"<WixLocalization Culture="de-DE"><String Id="Other">Andere</String></WixLocalization>"#,
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("Product.wxs"),
+            r#"<Wix><Property Id="INSTALLMODE" Value="full" Secure="yes" /></Wix>"#,
+        )
+        .unwrap();
+        fs::write(temp.path().join("bin/Copy.wxs"), "<Wix />").unwrap();
+        fs::write(temp.path().join("Broken.wxs"), "<Wix>").unwrap();
+
+        let mut project = ProjectContext::new();
+        assert_eq!(project.load_directory(temp.path()).unwrap(), 3);
+
+        assert_eq!(project.cultures(), vec![Some("de-DE"), Some("en-US")]);
+        let title = project.translations("Title");
+        assert_eq!(title.len(), 1);
+        assert_eq!(title[0].culture.as_deref(), Some("en-US"));
+        assert_eq!(project.translations("Other")[0].value, "Andere");
+
+        let property = project.properties("INSTALLMODE");
+        assert_eq!(property[0].value.as_deref(), Some("full"));
+        assert!(property[0].secure);
+    }
+
+    #[test]
+    fn test_definition_preview() {
+        let mut project = ProjectContext::new();
+        let source = "<Wix>\n  <Fragment>\n    <Component Id=\"Core\" Guid=\"*\">\n      <File Source=\"a.exe\" />\n    </Component>\n  </Fragment>\n</Wix>";
+        project.update_file(Path::new("Files.wxs"), source).unwrap();
+
+        let preview = project
+            .definition_preview("Core", SymbolKind::Component)
+            .unwrap();
+        assert_eq!(preview.element, "Component");
+        assert_eq!(preview.site.line, 3);
+        assert_eq!(
+            preview.text,
+            "<Component Id=\"Core\" Guid=\"*\">\n  <File Source=\"a.exe\" />\n</Component>"
+        );
+        assert!(project
+            .definition_preview("Core", SymbolKind::Feature)
+            .is_none());
+    }
+}
@@ -45,6 +45,30 @@ pub enum HoverTarget {
         range: Range,
     },
 
+    /// Hovering over a preprocessor variable: `$(var.Version)`
+    Variable {
+        /// Variable name without the `var.` prefix
+        name: String,
+        /// Source range of the whole reference
+        range: Range,
+    },
+
+    /// Hovering over a localization reference: `!(loc.Title)`
+    Localization {
+        /// String id without the `loc.` prefix
+        id: String,
+        /// Source range of the whole reference
+        range: Range,
+    },
+
+    /// Hovering over a property in a formatted string: `[INSTALLFOLDER]`
+    Property {
+        /// Property name without brackets
+        name: String,
+        /// Source range including the brackets
+        range: Range,
+    },
+
     /// Nothing to show hover for
     None,
 }
@@ -2,6 +2,7 @@
 //!
 //! Loads settings from YAML configuration files.
 
+use crate::plugins::BuildSettings;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
@@ -14,6 +15,10 @@ pub struct EngineConfig {
     pub engine: EngineSettings,
     /// Plugin settings
     pub plugins: PluginSettings,
+    /// Diagnostics settings
+    pub diagnostics: DiagnosticSettings,
+    /// Bind paths, include paths and defines used to resolve sources
+    pub build: BuildSettings,
 }
 
 /// Core engine settings
@@ -40,6 +45,26 @@ pub struct PluginSettings {
     pub enabled: Vec<String>,
 }
 
+/// Diagnostics publishing settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticSettings {
+    /// Delay after the last edit before a document is re-checked
+    pub debounce_ms: u64,
+    /// Documentation link for rules that don't provide one; `{rule}` is
+    /// replaced with the rule id
+    pub rule_docs_url: Option<String>,
+}
+
+impl Default for DiagnosticSettings {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 300,
+            rule_docs_url: None,
+        }
+    }
+}
+
 impl Default for EngineSettings {
     fn default() -> Self {
         Self {
@@ -282,6 +307,49 @@ engine:
         assert_eq!(config.engine.name, "custom-name");
     }
 
+    #[test]
+    fn test_diagnostic_settings() {
+        let config = EngineConfig::default();
+        assert_eq!(config.diagnostics.debounce_ms, 300);
+        assert!(config.diagnostics.rule_docs_url.is_none());
+
+        let yaml = r#"
+diagnostics:
+  debounce_ms: 50
+  rule_docs_url: https://example.com/rules/{rule}
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.diagnostics.debounce_ms, 50);
+        assert_eq!(
+            config.diagnostics.rule_docs_url.as_deref(),
+            Some("https://example.com/rules/{rule}")
+        );
+    }
+
+    #[test]
+    fn test_build_settings() {
+        let config = EngineConfig::default();
+        assert!(config.build.bind_paths.is_empty());
+
+        let yaml = r#"
+build:
+  bind_paths:
+    - bin/Release
+    - tools=third-party/tools
+  include_paths:
+    - include
+  defines:
+    - Configuration=Release
+"#;
+        let config: EngineConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.build.bind_paths,
+            vec!["bin/Release", "tools=third-party/tools"]
+        );
+        assert_eq!(config.build.include_paths, vec!["include"]);
+        assert_eq!(config.build.defines, vec!["Configuration=Release"]);
+    }
+
     #[test]
     fn test_capability_config_default() {
         let caps = CapabilityConfig::default();
@@ -1,14 +1,24 @@
 //! Type conversions from plugin types to LSP types
 
 use crate::plugins::{
-    Completion, CompletionKind, Diagnostic, DiagnosticSeverity, HoverInfo, Symbol, SymbolKind,
-    SymbolRange,
+    CodeAction, CodeActionKind, Command, Completion, CompletionKind, Diagnostic, DiagnosticFix,
+    DiagnosticSeverity, DiagnosticTag, DocumentLink, FileEdit, FoldingKind, FoldingRange,
+    HierarchyItem, HoverInfo, InlayHint, RelatedInformation, SemanticTokenKind, Symbol, SymbolKind,
+    SymbolLocation, SymbolRange, WorkspaceSymbol,
 };
+use std::collections::HashMap;
 use tower_lsp::lsp_types::{
-    self, CompletionItem as LspCompletionItem, CompletionItemKind as LspCompletionItemKind,
-    Diagnostic as LspDiagnostic, DiagnosticSeverity as LspDiagnosticSeverity,
-    DocumentSymbol as LspDocumentSymbol, Hover, HoverContents, MarkupContent, MarkupKind,
-    Position as LspPosition, Range as LspRange, SymbolKind as LspSymbolKind,
+    self, CallHierarchyItem, CodeAction as LspCodeAction, CodeActionKind as LspCodeActionKind,
+    CodeDescription, Command as LspCommand, CompletionItem as LspCompletionItem,
+    CompletionItemKind as LspCompletionItemKind, Diagnostic as LspDiagnostic,
+    DiagnosticRelatedInformation, DiagnosticSeverity as LspDiagnosticSeverity,
+    DiagnosticTag as LspDiagnosticTag, DocumentLink as LspDocumentLink,
+    DocumentSymbol as LspDocumentSymbol, FoldingRange as LspFoldingRange,
+    FoldingRangeKind as LspFoldingRangeKind, Hover, HoverContents, InlayHint as LspInlayHint,
+    InlayHintLabel, InlayHintTooltip, Location, MarkupContent, MarkupKind, Position as LspPosition,
+    Range as LspRange, SemanticToken as LspSemanticToken, SemanticTokenModifier, SemanticTokenType,
+    SemanticTokensLegend, SymbolInformation, SymbolKind as LspSymbolKind, TextEdit,
+    TypeHierarchyItem, Url, WorkspaceEdit,
 };
 
 /// Convert plugin completion to LSP completion item
@@ -95,6 +105,112 @@ fn to_lsp_symbol_kind(kind: &SymbolKind) -> LspSymbolKind {
     }
 }
 
+/// Convert LSP symbol kind to plugin symbol kind
+fn from_lsp_symbol_kind(kind: LspSymbolKind) -> SymbolKind {
+    match kind {
+        LspSymbolKind::FILE => SymbolKind::File,
+        LspSymbolKind::MODULE => SymbolKind::Module,
+        LspSymbolKind::NAMESPACE => SymbolKind::Namespace,
+        LspSymbolKind::CLASS => SymbolKind::Class,
+        LspSymbolKind::FUNCTION => SymbolKind::Function,
+        LspSymbolKind::VARIABLE => SymbolKind::Variable,
+        LspSymbolKind::CONSTANT => SymbolKind::Constant,
+        LspSymbolKind::STRING => SymbolKind::String,
+        LspSymbolKind::PROPERTY => SymbolKind::Property,
+        LspSymbolKind::KEY => SymbolKind::Key,
+        LspSymbolKind::EVENT => SymbolKind::Event,
+        LspSymbolKind::OPERATOR => SymbolKind::Operator,
+        LspSymbolKind::TYPE_PARAMETER => SymbolKind::TypeParameter,
+        _ => SymbolKind::Struct,
+    }
+}
+
+/// Convert plugin hierarchy item to LSP call hierarchy item
+pub fn to_lsp_call_hierarchy_item(item: &HierarchyItem) -> Option<CallHierarchyItem> {
+    Some(CallHierarchyItem {
+        name: item.name.clone(),
+        kind: to_lsp_symbol_kind(&item.kind),
+        tags: None,
+        detail: item.detail.clone(),
+        uri: Url::from_file_path(&item.path).ok()?,
+        range: to_lsp_range(&item.range),
+        selection_range: to_lsp_range(&item.selection_range),
+        data: Some(hierarchy_data(item)),
+    })
+}
+
+/// Convert plugin hierarchy item to LSP type hierarchy item
+pub fn to_lsp_type_hierarchy_item(item: &HierarchyItem) -> Option<TypeHierarchyItem> {
+    Some(TypeHierarchyItem {
+        name: item.name.clone(),
+        kind: to_lsp_symbol_kind(&item.kind),
+        tags: None,
+        detail: item.detail.clone(),
+        uri: Url::from_file_path(&item.path).ok()?,
+        range: to_lsp_range(&item.range),
+        selection_range: to_lsp_range(&item.selection_range),
+        data: Some(hierarchy_data(item)),
+    })
+}
+
+/// Recover the plugin item a call hierarchy item was made from
+pub fn from_lsp_call_hierarchy_item(item: &CallHierarchyItem) -> Option<HierarchyItem> {
+    from_lsp_hierarchy_item(
+        &item.name,
+        item.kind,
+        item.detail.as_deref(),
+        &item.uri,
+        (item.range, item.selection_range),
+        item.data.as_ref()?,
+    )
+}
+
+/// Recover the plugin item a type hierarchy item was made from
+pub fn from_lsp_type_hierarchy_item(item: &TypeHierarchyItem) -> Option<HierarchyItem> {
+    from_lsp_hierarchy_item(
+        &item.name,
+        item.kind,
+        item.detail.as_deref(),
+        &item.uri,
+        (item.range, item.selection_range),
+        item.data.as_ref()?,
+    )
+}
+
+/// Data the client sends back with an item
+fn hierarchy_data(item: &HierarchyItem) -> serde_json::Value {
+    serde_json::json!({ "element": item.element })
+}
+
+fn from_lsp_hierarchy_item(
+    name: &str,
+    kind: LspSymbolKind,
+    detail: Option<&str>,
+    uri: &Url,
+    (range, selection_range): (LspRange, LspRange),
+    data: &serde_json::Value,
+) -> Option<HierarchyItem> {
+    Some(HierarchyItem {
+        name: name.to_string(),
+        kind: from_lsp_symbol_kind(kind),
+        element: data.get("element")?.as_str()?.to_string(),
+        detail: detail.map(str::to_string),
+        path: uri.to_file_path().ok()?,
+        // Only the element and name identify the item, so client units are kept
+        range: from_lsp_range(range),
+        selection_range: from_lsp_range(selection_range),
+    })
+}
+
+fn from_lsp_range(range: LspRange) -> SymbolRange {
+    SymbolRange {
+        start_line: range.start.line,
+        start_col: range.start.character,
+        end_line: range.end.line,
+        end_col: range.end.character,
+    }
+}
+
 /// Convert plugin range to LSP range
 pub fn to_lsp_range(range: &SymbolRange) -> LspRange {
     LspRange {
@@ -109,30 +225,165 @@ pub fn to_lsp_range(range: &SymbolRange) -> LspRange {
     }
 }
 
+/// Convert plugin symbol location to LSP location
+pub fn to_lsp_location(location: &SymbolLocation) -> Option<Location> {
+    Some(Location {
+        uri: Url::from_file_path(&location.path).ok()?,
+        range: to_lsp_range(&location.range),
+    })
+}
+
+/// Convert plugin workspace symbol to LSP symbol information
+pub fn to_lsp_workspace_symbol(symbol: &WorkspaceSymbol) -> Option<SymbolInformation> {
+    #[allow(deprecated)]
+    Some(SymbolInformation {
+        name: symbol.name.clone(),
+        kind: to_lsp_symbol_kind(&symbol.kind),
+        tags: None,
+        deprecated: None,
+        location: to_lsp_location(&symbol.location)?,
+        container_name: symbol.container.clone(),
+    })
+}
+
+/// Group plugin file edits into an LSP workspace edit
+pub fn to_lsp_workspace_edit(edits: &[FileEdit]) -> WorkspaceEdit {
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    for edit in edits {
+        if let Ok(uri) = Url::from_file_path(&edit.path) {
+            changes.entry(uri).or_default().push(TextEdit {
+                range: to_lsp_range(&edit.range),
+                new_text: edit.new_text.clone(),
+            });
+        }
+    }
+    WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+        change_annotations: None,
+    }
+}
+
+/// Convert a plugin code action; edits are left in character columns
+pub fn to_lsp_code_action(action: &CodeAction) -> LspCodeAction {
+    LspCodeAction {
+        title: action.title.clone(),
+        kind: Some(to_lsp_code_action_kind(&action.kind)),
+        diagnostics: None,
+        edit: (!action.edits.is_empty()).then(|| to_lsp_workspace_edit(&action.edits)),
+        command: action.command.as_ref().map(to_lsp_command),
+        is_preferred: action.is_preferred.then_some(true),
+        disabled: None,
+        data: None,
+    }
+}
+
+/// Convert a diagnostic's fix to a quick fix resolving `diagnostic`
+pub fn to_lsp_quick_fix(fix: &DiagnosticFix, diagnostic: LspDiagnostic) -> LspCodeAction {
+    LspCodeAction {
+        title: fix.title.clone(),
+        kind: Some(LspCodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic]),
+        edit: Some(to_lsp_workspace_edit(&fix.edits)),
+        command: None,
+        is_preferred: fix.is_preferred.then_some(true),
+        disabled: None,
+        data: None,
+    }
+}
+
+pub fn to_lsp_code_action_kind(kind: &CodeActionKind) -> LspCodeActionKind {
+    match kind {
+        CodeActionKind::QuickFix => LspCodeActionKind::QUICKFIX,
+        CodeActionKind::Refactor => LspCodeActionKind::REFACTOR,
+        CodeActionKind::RefactorExtract => LspCodeActionKind::REFACTOR_EXTRACT,
+        CodeActionKind::RefactorRewrite => LspCodeActionKind::REFACTOR_REWRITE,
+        CodeActionKind::Source => LspCodeActionKind::SOURCE,
+    }
+}
+
+/// Convert a kind from a request's `only` filter; kinds plugins can't
+/// produce, such as `source.organizeImports`, map to `None`
+pub fn from_lsp_code_action_kind(kind: &LspCodeActionKind) -> Option<CodeActionKind> {
+    [
+        CodeActionKind::QuickFix,
+        CodeActionKind::Refactor,
+        CodeActionKind::RefactorExtract,
+        CodeActionKind::RefactorRewrite,
+        CodeActionKind::Source,
+    ]
+    .into_iter()
+    .find(|k| to_lsp_code_action_kind(k) == *kind)
+}
+
+fn to_lsp_command(command: &Command) -> LspCommand {
+    LspCommand {
+        title: command.title.clone(),
+        command: command.command.clone(),
+        arguments: (!command.arguments.is_empty()).then(|| command.arguments.clone()),
+    }
+}
+
 /// Convert plugin diagnostic to LSP diagnostic
 pub fn to_lsp_diagnostic(diag: &Diagnostic) -> LspDiagnostic {
     LspDiagnostic {
-        range: LspRange {
-            start: LspPosition {
-                line: diag.line.saturating_sub(1),
-                character: diag.column.saturating_sub(1),
-            },
-            end: LspPosition {
-                line: diag.line.saturating_sub(1),
-                character: diag.column.saturating_sub(1) + diag.length,
-            },
-        },
+        range: to_lsp_line_range(diag.line, diag.column, diag.length),
         severity: Some(to_lsp_severity(&diag.severity)),
         code: Some(lsp_types::NumberOrString::String(diag.rule_id.clone())),
-        code_description: None,
-        source: Some("wix-lsp".to_string()),
+        code_description: diag
+            .code_description
+            .as_deref()
+            .and_then(|url| Url::parse(url).ok())
+            .map(|href| CodeDescription { href }),
+        source: Some(diag.source.clone().unwrap_or_else(|| "wix-lsp".to_string())),
         message: diag.message.clone(),
-        related_information: None,
-        tags: None,
+        related_information: if diag.related.is_empty() {
+            None
+        } else {
+            Some(diag.related.iter().filter_map(to_lsp_related).collect())
+        },
+        tags: if diag.tags.is_empty() {
+            None
+        } else {
+            Some(diag.tags.iter().map(to_lsp_diagnostic_tag).collect())
+        },
         data: None,
     }
 }
 
+/// Convert a 1-based single-line span to an LSP range
+fn to_lsp_line_range(line: u32, column: u32, length: u32) -> LspRange {
+    LspRange {
+        start: LspPosition {
+            line: line.saturating_sub(1),
+            character: column.saturating_sub(1),
+        },
+        end: LspPosition {
+            line: line.saturating_sub(1),
+            character: column.saturating_sub(1) + length,
+        },
+    }
+}
+
+/// Convert related information; locations without a file URI are dropped
+fn to_lsp_related(related: &RelatedInformation) -> Option<DiagnosticRelatedInformation> {
+    Some(DiagnosticRelatedInformation {
+        location: Location {
+            uri: Url::from_file_path(&related.path).ok()?,
+            range: to_lsp_line_range(related.line, related.column, related.length),
+        },
+        message: related.message.clone(),
+    })
+}
+
+/// Convert plugin diagnostic tag to LSP diagnostic tag
+fn to_lsp_diagnostic_tag(tag: &DiagnosticTag) -> LspDiagnosticTag {
+    match tag {
+        DiagnosticTag::Unnecessary => LspDiagnosticTag::UNNECESSARY,
+        DiagnosticTag::Deprecated => LspDiagnosticTag::DEPRECATED,
+    }
+}
+
 /// Convert plugin severity to LSP severity
 fn to_lsp_severity(severity: &DiagnosticSeverity) -> LspDiagnosticSeverity {
     match severity {
@@ -165,6 +416,110 @@ pub fn to_lsp_hover(info: &HoverInfo) -> Hover {
     }
 }
 
+/// Token types in the legend; a token's type is its index here
+const SEMANTIC_TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::PROPERTY,
+];
+
+/// Token modifiers in the legend; a modifier's bit is its index here
+const SEMANTIC_TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[SemanticTokenModifier::DECLARATION];
+
+/// Legend for the tokens built by `to_lsp_semantic_tokens`
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+        token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Legend index and modifier bits of a token kind
+fn to_lsp_semantic_token_type(kind: &SemanticTokenKind) -> (u32, u32) {
+    let (token_type, modifiers) = match kind {
+        SemanticTokenKind::Definition => (SemanticTokenType::VARIABLE, 1),
+        SemanticTokenKind::Reference => (SemanticTokenType::VARIABLE, 0),
+        SemanticTokenKind::PreprocessorVariable => (SemanticTokenType::MACRO, 0),
+        SemanticTokenKind::BindVariable => (SemanticTokenType::PARAMETER, 0),
+        SemanticTokenKind::Localization => (SemanticTokenType::STRING, 0),
+        SemanticTokenKind::Property => (SemanticTokenType::PROPERTY, 0),
+    };
+    let index = SEMANTIC_TOKEN_TYPES
+        .iter()
+        .position(|t| *t == token_type)
+        .unwrap_or_default();
+    (index as u32, modifiers)
+}
+
+/// Encode tokens, given as start position and length in the client's
+/// units, relative to the token before
+pub fn to_lsp_semantic_tokens(
+    tokens: &[(LspPosition, u32, SemanticTokenKind)],
+) -> Vec<LspSemanticToken> {
+    let mut previous = LspPosition::new(0, 0);
+    tokens
+        .iter()
+        .map(|(start, length, kind)| {
+            let delta_line = start.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                start.character - previous.character
+            } else {
+                start.character
+            };
+            previous = *start;
+            let (token_type, token_modifiers_bitset) = to_lsp_semantic_token_type(kind);
+            LspSemanticToken {
+                delta_line,
+                delta_start,
+                length: *length,
+                token_type,
+                token_modifiers_bitset,
+            }
+        })
+        .collect()
+}
+
+/// Convert plugin folding range to LSP folding range
+pub fn to_lsp_folding_range(range: &FoldingRange) -> LspFoldingRange {
+    LspFoldingRange {
+        start_line: range.start_line,
+        start_character: None,
+        end_line: range.end_line,
+        end_character: None,
+        kind: range.kind.map(|kind| match kind {
+            FoldingKind::Comment => LspFoldingRangeKind::Comment,
+            FoldingKind::Region => LspFoldingRangeKind::Region,
+        }),
+        collapsed_text: None,
+    }
+}
+
+/// Convert plugin document link; the range is left in character columns
+pub fn to_lsp_document_link(link: &DocumentLink) -> Option<LspDocumentLink> {
+    Some(LspDocumentLink {
+        range: to_lsp_range(&link.range),
+        target: Some(Url::from_file_path(&link.target).ok()?),
+        tooltip: link.tooltip.clone(),
+        data: None,
+    })
+}
+
+/// Convert plugin inlay hint; the position is left in character columns
+pub fn to_lsp_inlay_hint(hint: &InlayHint) -> LspInlayHint {
+    LspInlayHint {
+        position: LspPosition::new(hint.line, hint.column),
+        label: InlayHintLabel::String(hint.label.clone()),
+        kind: None,
+        text_edits: None,
+        tooltip: hint.tooltip.clone().map(InlayHintTooltip::String),
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,6 +541,37 @@ mod tests {
         assert_eq!(lsp_item.kind, Some(LspCompletionItemKind::CLASS));
     }
 
+    #[test]
+    fn test_hierarchy_item_round_trip() {
+        let range = SymbolRange {
+            start_line: 3,
+            start_col: 4,
+            end_line: 5,
+            end_col: 6,
+        };
+        let item = HierarchyItem {
+            name: "Core".to_string(),
+            kind: SymbolKind::Class,
+            element: "ComponentGroup".to_string(),
+            detail: None,
+            path: std::env::temp_dir().join("core.wxs"),
+            range,
+            selection_range: range,
+        };
+
+        let lsp_item = to_lsp_call_hierarchy_item(&item).unwrap();
+        assert_eq!(lsp_item.kind, LspSymbolKind::CLASS);
+        let back = from_lsp_call_hierarchy_item(&lsp_item).unwrap();
+        assert_eq!(back.element, "ComponentGroup");
+        assert_eq!(back.path, item.path);
+        assert_eq!(back.kind, SymbolKind::Class);
+
+        let mut lsp_item = to_lsp_type_hierarchy_item(&item).unwrap();
+        assert_eq!(lsp_item.range.start.character, 4);
+        lsp_item.data = None;
+        assert!(from_lsp_type_hierarchy_item(&lsp_item).is_none());
+    }
+
     #[test]
     fn test_to_lsp_completion_kinds() {
         assert_eq!(
@@ -302,6 +688,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_lsp_code_action() {
+        let path = std::env::temp_dir().join("product.wxs");
+        let action = CodeAction {
+            title: "Harvest folder 'bin' into a new Fragment".to_string(),
+            kind: CodeActionKind::Refactor,
+            edits: Vec::new(),
+            command: Some(Command {
+                title: "Harvest".to_string(),
+                command: "wix.harvestFolder".to_string(),
+                arguments: vec![serde_json::Value::from("bin")],
+            }),
+            is_preferred: false,
+        };
+        let lsp_action = to_lsp_code_action(&action);
+        assert_eq!(lsp_action.kind, Some(LspCodeActionKind::REFACTOR));
+        assert!(lsp_action.edit.is_none());
+        assert_eq!(lsp_action.command.unwrap().arguments.unwrap().len(), 1);
+
+        let fix = DiagnosticFix {
+            title: "Use auto-generated GUID".to_string(),
+            edits: vec![FileEdit {
+                path,
+                range: SymbolRange {
+                    start_line: 2,
+                    start_col: 4,
+                    end_line: 2,
+                    end_col: 8,
+                },
+                new_text: "*".to_string(),
+            }],
+            is_preferred: true,
+        };
+        let diagnostic = LspDiagnostic::default();
+        let quick_fix = to_lsp_quick_fix(&fix, diagnostic.clone());
+        assert_eq!(quick_fix.kind, Some(LspCodeActionKind::QUICKFIX));
+        assert_eq!(quick_fix.diagnostics, Some(vec![diagnostic]));
+        assert_eq!(quick_fix.is_preferred, Some(true));
+        assert_eq!(quick_fix.edit.unwrap().changes.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_from_lsp_code_action_kind() {
+        assert_eq!(
+            from_lsp_code_action_kind(&LspCodeActionKind::REFACTOR_EXTRACT),
+            Some(CodeActionKind::RefactorExtract)
+        );
+        assert_eq!(
+            from_lsp_code_action_kind(&LspCodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+            None
+        );
+    }
+
     #[test]
     fn test_to_lsp_diagnostic() {
         let diag = Diagnostic {
@@ -312,6 +751,11 @@ mod tests {
             column: 5,
             length: 15,
             help: None,
+            source: None,
+            code_description: None,
+            related: Vec::new(),
+            tags: Vec::new(),
+            fixes: Vec::new(),
         };
 
         let lsp_diag = to_lsp_diagnostic(&diag);
@@ -319,6 +763,115 @@ mod tests {
         assert_eq!(lsp_diag.severity, Some(LspDiagnosticSeverity::ERROR));
         assert_eq!(lsp_diag.range.start.line, 9); // 1-indexed to 0-indexed
         assert_eq!(lsp_diag.range.start.character, 4);
+        assert_eq!(lsp_diag.source.as_deref(), Some("wix-lsp"));
+        assert!(lsp_diag.code_description.is_none());
+    }
+
+    #[test]
+    fn test_to_lsp_diagnostic_details() {
+        let diag = Diagnostic {
+            rule_id: "BP-IDIOM-003".to_string(),
+            message: "Product element is deprecated".to_string(),
+            severity: DiagnosticSeverity::Warning,
+            line: 2,
+            column: 3,
+            length: 7,
+            help: None,
+            source: Some("wix-analyzer".to_string()),
+            code_description: Some("https://example.com/rules/BP-IDIOM-003".to_string()),
+            related: vec![RelatedInformation {
+                path: std::env::temp_dir().join("defs.wxs"),
+                line: 5,
+                column: 1,
+                length: 4,
+                message: "Defined here".to_string(),
+            }],
+            tags: vec![DiagnosticTag::Deprecated],
+            fixes: Vec::new(),
+        };
+
+        let lsp_diag = to_lsp_diagnostic(&diag);
+        assert_eq!(lsp_diag.source.as_deref(), Some("wix-analyzer"));
+        assert_eq!(
+            lsp_diag.code_description.unwrap().href.as_str(),
+            "https://example.com/rules/BP-IDIOM-003"
+        );
+        let related = lsp_diag.related_information.unwrap();
+        assert_eq!(related[0].location.range.start.line, 4);
+        assert!(related[0].location.uri.path().ends_with("defs.wxs"));
+        assert_eq!(lsp_diag.tags, Some(vec![LspDiagnosticTag::DEPRECATED]));
+    }
+
+    #[test]
+    fn test_to_lsp_workspace_edit() {
+        let range = SymbolRange {
+            start_line: 2,
+            start_col: 19,
+            end_line: 2,
+            end_col: 23,
+        };
+        let edit = |path: &str| FileEdit {
+            path: std::path::PathBuf::from(path),
+            range,
+            new_text: "Renamed".to_string(),
+        };
+        let workspace_edit =
+            to_lsp_workspace_edit(&[edit("/src/a.wxs"), edit("/src/b.wxl"), edit("/src/a.wxs")]);
+
+        let changes = workspace_edit.changes.unwrap();
+        assert_eq!(changes.len(), 2);
+        let a = &changes[&Url::from_file_path("/src/a.wxs").unwrap()];
+        assert_eq!(a.len(), 2);
+        assert_eq!(a[0].range.start.character, 19);
+        assert_eq!(a[0].new_text, "Renamed");
+    }
+
+    #[test]
+    fn test_to_lsp_semantic_tokens() {
+        let tokens = [
+            (LspPosition::new(2, 4), 4, SemanticTokenKind::Definition),
+            (
+                LspPosition::new(2, 12),
+                10,
+                SemanticTokenKind::PreprocessorVariable,
+            ),
+            (LspPosition::new(5, 8), 4, SemanticTokenKind::Reference),
+        ];
+        let encoded = to_lsp_semantic_tokens(&tokens);
+        let fields: Vec<_> = encoded
+            .iter()
+            .map(|t| {
+                (
+                    t.delta_line,
+                    t.delta_start,
+                    t.length,
+                    t.token_type,
+                    t.token_modifiers_bitset,
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![(2, 4, 4, 0, 1), (0, 8, 10, 1, 0), (3, 8, 4, 0, 0)]
+        );
+
+        let legend = semantic_tokens_legend();
+        assert_eq!(legend.token_types[1], SemanticTokenType::MACRO);
+        assert_eq!(
+            legend.token_modifiers,
+            vec![SemanticTokenModifier::DECLARATION]
+        );
+    }
+
+    #[test]
+    fn test_to_lsp_folding_range() {
+        let range = to_lsp_folding_range(&FoldingRange {
+            start_line: 1,
+            end_line: 4,
+            kind: Some(FoldingKind::Region),
+        });
+        assert_eq!((range.start_line, range.end_line), (1, 4));
+        assert_eq!(range.kind, Some(LspFoldingRangeKind::Region));
     }
 
     #[test]
@@ -0,0 +1,186 @@
+//! Debounced diagnostics publishing
+//!
+//! Every change schedules a run after a short delay. A run only goes ahead,
+//! and its results are only published, if the document is still at the
+//! version it was scheduled for.
+
+use super::config::DiagnosticSettings;
+use super::convert;
+use super::document::DocumentManager;
+use crate::plugins::{Diagnostic, DiagnosticFix, PluginRegistry};
+use dashmap::DashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_lsp::lsp_types::{Diagnostic as LspDiagnostic, Url};
+use tower_lsp::Client;
+
+/// Schedules diagnostics runs for open documents
+#[derive(Clone)]
+pub struct DiagnosticScheduler {
+    client: Client,
+    documents: Arc<DocumentManager>,
+    plugins: Arc<PluginRegistry>,
+    settings: DiagnosticSettings,
+    /// Fixes of the last published diagnostics, by document
+    fixes: Arc<DashMap<Url, PublishedFixes>>,
+}
+
+/// Quick fixes for the diagnostics published for one document version
+struct PublishedFixes {
+    version: i32,
+    fixes: Vec<(LspDiagnostic, DiagnosticFix)>,
+}
+
+impl DiagnosticScheduler {
+    pub fn new(
+        client: Client,
+        documents: Arc<DocumentManager>,
+        plugins: Arc<PluginRegistry>,
+        settings: DiagnosticSettings,
+    ) -> Self {
+        Self {
+            client,
+            documents,
+            plugins,
+            settings,
+            fixes: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Quick fixes published for a document version, with the diagnostic
+    /// each resolves
+    pub fn fixes(&self, uri: &Url, version: i32) -> Vec<(LspDiagnostic, DiagnosticFix)> {
+        match self.fixes.get(uri) {
+            Some(published) if published.version == version => published.fixes.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Forget a closed document
+    pub fn clear(&self, uri: &Url) {
+        self.fixes.remove(uri);
+    }
+
+    /// Check a document after the debounce delay
+    pub fn schedule(&self, uri: Url, version: i32) {
+        self.schedule_after(
+            uri,
+            version,
+            Duration::from_millis(self.settings.debounce_ms),
+        );
+    }
+
+    /// Check a document right away
+    pub fn schedule_now(&self, uri: Url, version: i32) {
+        self.schedule_after(uri, version, Duration::ZERO);
+    }
+
+    /// Re-check every open document, e.g. after the workspace was loaded
+    pub fn refresh_all(&self) {
+        for uri in self.documents.uris() {
+            if let Some(version) = self.documents.version(&uri) {
+                self.schedule_now(uri, version);
+            }
+        }
+    }
+
+    fn schedule_after(&self, uri: Url, version: i32, delay: Duration) {
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            scheduler.run(uri, version).await;
+        });
+    }
+
+    async fn run(&self, uri: Url, version: i32) {
+        let doc = match self.documents.get(&uri) {
+            Some(doc) if doc.version() == version => doc,
+            // Closed or superseded by a newer edit
+            _ => return,
+        };
+        let plugin = match self.plugins.plugin_for_uri(uri.as_str()) {
+            Some(plugin) => Arc::clone(plugin),
+            None => return,
+        };
+        let path = uri
+            .to_file_path()
+            .unwrap_or_else(|_| PathBuf::from("document.wxs"));
+
+        let mut changes = self.documents.subscribe();
+        let text = doc.text();
+        let task = tokio::task::spawn_blocking(move || {
+            // Workspace indexes follow the buffer at the same pace as diagnostics
+            plugin.as_language().document_changed(&path, &text);
+            plugin.as_diagnostic().diagnose(&text, &path)
+        });
+        tokio::pin!(task);
+        let diagnostics = loop {
+            tokio::select! {
+                result = &mut task => break result.unwrap_or_default(),
+                changed = changes.changed() => {
+                    if changed.is_err() || self.documents.version(&uri) != Some(version) {
+                        return;
+                    }
+                }
+            }
+        };
+
+        if self.documents.version(&uri) != Some(version) {
+            return;
+        }
+        let mut fixes = Vec::new();
+        let lsp_diagnostics = diagnostics
+            .iter()
+            .map(|d| {
+                let mut diagnostic = convert::to_lsp_diagnostic(&self.with_docs_link(d));
+                diagnostic.range = doc.lsp_range(diagnostic.range);
+                for fix in &d.fixes {
+                    fixes.push((diagnostic.clone(), fix.clone()));
+                }
+                diagnostic
+            })
+            .collect();
+        self.fixes
+            .insert(uri.clone(), PublishedFixes { version, fixes });
+        self.client
+            .publish_diagnostics(uri, lsp_diagnostics, Some(version))
+            .await;
+    }
+
+    /// Fill in the configured documentation link for rules without one
+    fn with_docs_link(&self, diag: &Diagnostic) -> Diagnostic {
+        let mut diag = diag.clone();
+        if diag.code_description.is_none() {
+            diag.code_description = rule_docs_url(&self.settings, &diag.rule_id);
+        }
+        diag
+    }
+}
+
+/// Documentation URL for a rule from the configured template
+fn rule_docs_url(settings: &DiagnosticSettings, rule_id: &str) -> Option<String> {
+    settings
+        .rule_docs_url
+        .as_ref()
+        .map(|template| template.replace("{rule}", rule_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_docs_url() {
+        let mut settings = DiagnosticSettings::default();
+        assert!(rule_docs_url(&settings, "SEC-001").is_none());
+
+        settings.rule_docs_url = Some("https://example.com/rules/{rule}.html".to_string());
+        assert_eq!(
+            rule_docs_url(&settings, "SEC-001").as_deref(),
+            Some("https://example.com/rules/SEC-001.html")
+        );
+    }
+}
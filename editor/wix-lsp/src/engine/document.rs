@@ -1,24 +1,143 @@
 //! Document management for the LSP engine
 //!
 //! Manages open documents in the LSP session with thread-safe access.
+//! Documents are rope-backed so incremental edits don't copy the whole
+//! buffer, and every version is an immutable snapshot that requests can hold
+//! on to while newer edits arrive.
 
 use dashmap::DashMap;
-use tower_lsp::lsp_types::Url;
+use ropey::Rope;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::watch;
+use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+/// One version of an open document
+///
+/// LSP positions count UTF-16 code units while plugins work in characters;
+/// the conversions here map between the two.
+#[derive(Debug)]
+pub struct Document {
+    rope: Rope,
+    version: i32,
+    /// Full text, built on first use and shared by every request
+    text: OnceLock<Arc<str>>,
+}
+
+impl Document {
+    /// Create a document from its full text
+    pub fn new(text: &str, version: i32) -> Self {
+        Self::from_rope(Rope::from_str(text), version)
+    }
+
+    fn from_rope(rope: Rope, version: i32) -> Self {
+        Self {
+            rope,
+            version,
+            text: OnceLock::new(),
+        }
+    }
+
+    /// Document version
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Full text of this version
+    pub fn text(&self) -> Arc<str> {
+        Arc::clone(self.text.get_or_init(|| Arc::from(self.rope.to_string())))
+    }
+
+    /// Number of lines
+    pub fn line_count(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Apply content changes in order, producing the next version
+    pub fn apply_changes(&self, changes: &[TextDocumentContentChangeEvent], version: i32) -> Self {
+        let mut rope = self.rope.clone();
+        for change in changes {
+            match change.range {
+                Some(range) => {
+                    let start = char_index(&rope, range.start);
+                    let end = char_index(&rope, range.end).max(start);
+                    rope.remove(start..end);
+                    rope.insert(start, &change.text);
+                }
+                None => rope = Rope::from_str(&change.text),
+            }
+        }
+        Self::from_rope(rope, version)
+    }
+
+    /// Map an LSP position to a 0-based line and character column
+    pub fn char_position(&self, position: Position) -> (u32, u32) {
+        let index = char_index(&self.rope, position);
+        let line = self.rope.char_to_line(index);
+        let column = index - self.rope.line_to_char(line);
+        (line as u32, column as u32)
+    }
+
+    /// Map a 0-based line and character column to an LSP position
+    pub fn lsp_position(&self, line: u32, column: u32) -> Position {
+        let line_index = line as usize;
+        if line_index >= self.rope.len_lines() {
+            return Position::new(line, column);
+        }
+        let text = self.rope.line(line_index);
+        let column = (column as usize).min(line_len_chars(&text));
+        Position::new(line, text.char_to_utf16_cu(column) as u32)
+    }
+
+    /// Map a range in character columns to an LSP range
+    pub fn lsp_range(&self, range: Range) -> Range {
+        Range::new(
+            self.lsp_position(range.start.line, range.start.character),
+            self.lsp_position(range.end.line, range.end.character),
+        )
+    }
+
+    /// Range covering the whole document
+    pub fn full_range(&self) -> Range {
+        let last = self.rope.len_lines() - 1;
+        let end = self.rope.line(last).len_utf16_cu();
+        Range::new(Position::new(0, 0), Position::new(last as u32, end as u32))
+    }
+}
+
+/// Character index of an LSP position, clamped to the document
+fn char_index(rope: &Rope, position: Position) -> usize {
+    let line = position.line as usize;
+    if line >= rope.len_lines() {
+        return rope.len_chars();
+    }
+    let text = rope.line(line);
+    let units = (position.character as usize).min(text.len_utf16_cu());
+    let column = text.utf16_cu_to_char(units).min(line_len_chars(&text));
+    rope.line_to_char(line) + column
+}
+
+/// Characters in a line, not counting its line break
+fn line_len_chars(line: &ropey::RopeSlice) -> usize {
+    let mut len = line.len_chars();
+    while len > 0 && matches!(line.char(len - 1), '\n' | '\r') {
+        len -= 1;
+    }
+    len
+}
 
 /// Manages open documents in the LSP session
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct DocumentManager {
-    /// Map of document URI to content
-    documents: DashMap<Url, DocumentState>,
+    /// Map of document URI to its latest version
+    documents: DashMap<Url, Arc<Document>>,
+    /// Bumped on every edit, so requests can notice they went stale
+    generation: watch::Sender<u64>,
 }
 
-/// State of an open document
-#[derive(Debug, Clone)]
-pub struct DocumentState {
-    /// Document content
-    pub content: String,
-    /// Document version
-    pub version: i32,
+impl Default for DocumentManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DocumentManager {
@@ -26,36 +145,59 @@ impl DocumentManager {
     pub fn new() -> Self {
         Self {
             documents: DashMap::new(),
+            generation: watch::channel(0).0,
         }
     }
 
     /// Open a document
     pub fn open(&self, uri: Url, content: String, version: i32) {
         self.documents
-            .insert(uri, DocumentState { content, version });
+            .insert(uri, Arc::new(Document::new(&content, version)));
+        self.bump();
     }
 
     /// Update a document (full content replacement)
     pub fn update(&self, uri: &Url, content: String, version: i32) {
         if let Some(mut doc) = self.documents.get_mut(uri) {
-            doc.content = content;
-            doc.version = version;
+            *doc = Arc::new(Document::new(&content, version));
         }
+        self.bump();
+    }
+
+    /// Apply incremental changes, returning the new version
+    pub fn apply_changes(
+        &self,
+        uri: &Url,
+        changes: &[TextDocumentContentChangeEvent],
+        version: i32,
+    ) -> Option<Arc<Document>> {
+        let updated = self.documents.get_mut(uri).map(|mut doc| {
+            *doc = Arc::new(doc.apply_changes(changes, version));
+            Arc::clone(&doc)
+        });
+        self.bump();
+        updated
     }
 
     /// Close a document
     pub fn close(&self, uri: &Url) {
         self.documents.remove(uri);
+        self.bump();
     }
 
     /// Get document content
-    pub fn get_content(&self, uri: &Url) -> Option<String> {
-        self.documents.get(uri).map(|doc| doc.content.clone())
+    pub fn get_content(&self, uri: &Url) -> Option<Arc<str>> {
+        self.documents.get(uri).map(|doc| doc.text())
     }
 
-    /// Get document state
-    pub fn get(&self, uri: &Url) -> Option<DocumentState> {
-        self.documents.get(uri).map(|doc| doc.clone())
+    /// Get the latest version of a document
+    pub fn get(&self, uri: &Url) -> Option<Arc<Document>> {
+        self.documents.get(uri).map(|doc| Arc::clone(&doc))
+    }
+
+    /// Latest version number of a document
+    pub fn version(&self, uri: &Url) -> Option<i32> {
+        self.documents.get(uri).map(|doc| doc.version)
     }
 
     /// Check if document is open
@@ -72,6 +214,15 @@ impl DocumentManager {
     pub fn uris(&self) -> Vec<Url> {
         self.documents.iter().map(|r| r.key().clone()).collect()
     }
+
+    /// Subscribe to edits of any open document
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.generation.subscribe()
+    }
+
+    fn bump(&self) {
+        self.generation.send_modify(|generation| *generation += 1);
+    }
 }
 
 #[cfg(test)]
@@ -82,6 +233,17 @@ mod tests {
         Url::parse(&format!("file://{}", path)).unwrap()
     }
 
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
     #[test]
     fn test_document_manager_new() {
         let manager = DocumentManager::new();
@@ -96,7 +258,7 @@ mod tests {
 
         assert!(manager.is_open(&uri));
         assert_eq!(manager.count(), 1);
-        assert_eq!(manager.get_content(&uri), Some("content".to_string()));
+        assert_eq!(manager.get_content(&uri).as_deref(), Some("content"));
     }
 
     #[test]
@@ -107,8 +269,8 @@ mod tests {
         manager.update(&uri, "new".to_string(), 2);
 
         let doc = manager.get(&uri).unwrap();
-        assert_eq!(doc.content, "new");
-        assert_eq!(doc.version, 2);
+        assert_eq!(&*doc.text(), "new");
+        assert_eq!(doc.version(), 2);
     }
 
     #[test]
@@ -153,8 +315,8 @@ mod tests {
         manager.open(uri2.clone(), "content2".to_string(), 1);
 
         assert_eq!(manager.count(), 2);
-        assert_eq!(manager.get_content(&uri1), Some("content1".to_string()));
-        assert_eq!(manager.get_content(&uri2), Some("content2".to_string()));
+        assert_eq!(manager.get_content(&uri1).as_deref(), Some("content1"));
+        assert_eq!(manager.get_content(&uri2).as_deref(), Some("content2"));
     }
 
     #[test]
@@ -173,13 +335,87 @@ mod tests {
     }
 
     #[test]
-    fn test_document_state_clone() {
-        let state = DocumentState {
-            content: "test".to_string(),
-            version: 1,
+    fn test_incremental_changes() {
+        let manager = DocumentManager::new();
+        let uri = test_uri("/test.wxs");
+        manager.open(
+            uri.clone(),
+            "<Wix>\r\n  <Fragment />\r\n</Wix>".to_string(),
+            1,
+        );
+
+        let changes = [
+            change((1, 3), (1, 11), "Package"),
+            change((2, 0), (2, 0), "<!-- end -->\r\n"),
+        ];
+        let doc = manager.apply_changes(&uri, &changes, 2).unwrap();
+        assert_eq!(
+            &*doc.text(),
+            "<Wix>\r\n  <Package />\r\n<!-- end -->\r\n</Wix>"
+        );
+        assert_eq!(doc.version(), 2);
+        assert_eq!(doc.line_count(), 4);
+
+        // A full replacement has no range
+        let full = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "<Wix />".to_string(),
         };
-        let cloned = state.clone();
-        assert_eq!(cloned.content, "test");
-        assert_eq!(cloned.version, 1);
+        let doc = manager.apply_changes(&uri, &[full], 3).unwrap();
+        assert_eq!(&*doc.text(), "<Wix />");
+    }
+
+    #[test]
+    fn test_utf16_positions() {
+        // U+1F600 takes two UTF-16 code units, U+00E9 one
+        let doc = Document::new("<a t=\"\u{1f600}\u{e9}\"/>\n<b/>", 1);
+
+        assert_eq!(doc.char_position(Position::new(0, 6)), (0, 6));
+        assert_eq!(doc.char_position(Position::new(0, 8)), (0, 7));
+        assert_eq!(doc.char_position(Position::new(0, 9)), (0, 8));
+        assert_eq!(doc.lsp_position(0, 8), Position::new(0, 9));
+        assert_eq!(doc.lsp_position(0, 7), Position::new(0, 8));
+
+        // Past the end of a line clamps before the line break
+        assert_eq!(doc.char_position(Position::new(0, 50)), (0, 11));
+        assert_eq!(doc.lsp_position(0, 50), Position::new(0, 12));
+        assert_eq!(doc.char_position(Position::new(9, 0)), (1, 4));
+
+        assert_eq!(doc.full_range().end, Position::new(1, 4));
+    }
+
+    #[test]
+    fn test_edit_after_surrogate_pair() {
+        let doc = Document::new("x\u{1f600}y", 1);
+        let doc = doc.apply_changes(&[change((0, 3), (0, 4), "z")], 2);
+        assert_eq!(&*doc.text(), "x\u{1f600}z");
+    }
+
+    #[test]
+    fn test_snapshots_are_immutable() {
+        let manager = DocumentManager::new();
+        let uri = test_uri("/test.wxs");
+        manager.open(uri.clone(), "one".to_string(), 1);
+        let snapshot = manager.get(&uri).unwrap();
+
+        manager.apply_changes(&uri, &[change((0, 0), (0, 3), "two")], 2);
+        assert_eq!(&*snapshot.text(), "one");
+        assert_eq!(manager.version(&uri), Some(2));
+    }
+
+    #[test]
+    fn test_subscribe_sees_edits() {
+        let manager = DocumentManager::new();
+        let uri = test_uri("/test.wxs");
+        let mut changes = manager.subscribe();
+        assert!(!changes.has_changed().unwrap());
+
+        manager.open(uri.clone(), "content".to_string(), 1);
+        assert!(changes.has_changed().unwrap());
+        changes.mark_unchanged();
+
+        manager.close(&uri);
+        assert!(changes.has_changed().unwrap());
     }
 }
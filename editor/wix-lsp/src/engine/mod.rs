@@ -4,9 +4,11 @@
 
 pub mod config;
 pub mod convert;
+pub mod diagnostics;
 pub mod document;
 pub mod server;
 
 pub use config::EngineConfig;
+pub use diagnostics::DiagnosticScheduler;
 pub use document::DocumentManager;
 pub use server::LspServer;
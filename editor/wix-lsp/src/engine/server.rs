@@ -4,11 +4,16 @@
 
 use super::config::EngineConfig;
 use super::convert;
-use super::document::DocumentManager;
-use crate::plugins::PluginRegistry;
-use std::path::PathBuf;
+use super::diagnostics::DiagnosticScheduler;
+use super::document::{Document, DocumentManager};
+use crate::plugins::{
+    CodeActionKind as PluginActionKind, FileEdit, FullPluginDyn, HierarchyItem, PluginRegistry,
+    SymbolLocation, SymbolRange,
+};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tower_lsp::jsonrpc::Result;
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
@@ -17,11 +22,18 @@ pub struct LspServer {
     /// LSP client for sending notifications
     client: Client,
     /// Document manager
-    documents: DocumentManager,
+    documents: Arc<DocumentManager>,
+    /// Debounced diagnostics runs
+    diagnostics: DiagnosticScheduler,
     /// Plugin registry
     plugins: Arc<PluginRegistry>,
     /// Engine configuration
     config: EngineConfig,
+    /// Whether the client lets us register file watchers
+    watch_files: AtomicBool,
+    /// Whether the client lets us register type hierarchy support, which
+    /// the server capabilities can't announce
+    type_hierarchy: AtomicBool,
     /// Data path (discovered or configured)
     #[allow(dead_code)]
     data_path: Option<PathBuf>,
@@ -30,22 +42,27 @@ pub struct LspServer {
 impl LspServer {
     /// Create a new LSP server with the given plugins
     pub fn new(client: Client, plugins: PluginRegistry) -> Self {
-        Self {
-            client,
-            documents: DocumentManager::new(),
-            plugins: Arc::new(plugins),
-            config: EngineConfig::default(),
-            data_path: None,
-        }
+        Self::with_config(client, plugins, EngineConfig::default())
     }
 
     /// Create with configuration
     pub fn with_config(client: Client, plugins: PluginRegistry, config: EngineConfig) -> Self {
+        let documents = Arc::new(DocumentManager::new());
+        let plugins = Arc::new(plugins);
+        let diagnostics = DiagnosticScheduler::new(
+            client.clone(),
+            Arc::clone(&documents),
+            Arc::clone(&plugins),
+            config.diagnostics.clone(),
+        );
         Self {
             client,
-            documents: DocumentManager::new(),
-            plugins: Arc::new(plugins),
+            documents,
+            diagnostics,
+            plugins,
             config,
+            watch_files: AtomicBool::new(false),
+            type_hierarchy: AtomicBool::new(false),
             data_path: None,
         }
     }
@@ -58,7 +75,7 @@ impl LspServer {
             text_document_sync: Some(TextDocumentSyncCapability::Options(
                 TextDocumentSyncOptions {
                     open_close: Some(true),
-                    change: Some(TextDocumentSyncKind::FULL),
+                    change: Some(TextDocumentSyncKind::INCREMENTAL),
                     will_save: None,
                     will_save_wait_until: None,
                     save: None,
@@ -81,54 +98,233 @@ impl LspServer {
             document_symbol_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
 
+            definition_provider: Some(OneOf::Left(true)),
+            references_provider: Some(OneOf::Left(true)),
+            document_highlight_provider: Some(OneOf::Left(true)),
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            })),
+
             // Not yet implemented
-            definition_provider: None,
-            references_provider: None,
             signature_help_provider: None,
             declaration_provider: None,
             type_definition_provider: None,
             implementation_provider: None,
-            code_action_provider: None,
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![
+                    CodeActionKind::QUICKFIX,
+                    CodeActionKind::REFACTOR,
+                    CodeActionKind::REFACTOR_EXTRACT,
+                    CodeActionKind::REFACTOR_REWRITE,
+                    CodeActionKind::SOURCE,
+                ]),
+                work_done_progress_options: Default::default(),
+                resolve_provider: Some(false),
+            })),
             code_lens_provider: None,
-            document_highlight_provider: None,
-            workspace_symbol_provider: None,
-            execute_command_provider: None,
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: self.plugins.all_commands(),
+                work_done_progress_options: Default::default(),
+            }),
             workspace: None,
             selection_range_provider: None,
-            rename_provider: None,
             document_range_formatting_provider: None,
             document_on_type_formatting_provider: None,
-            folding_range_provider: None,
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
             linked_editing_range_provider: None,
-            call_hierarchy_provider: None,
-            semantic_tokens_provider: None,
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+            semantic_tokens_provider: Some(
+                SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                    legend: convert::semantic_tokens_legend(),
+                    range: None,
+                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                }),
+            ),
             moniker_provider: None,
-            inlay_hint_provider: None,
+            inlay_hint_provider: Some(OneOf::Left(true)),
             inline_value_provider: None,
             color_provider: None,
-            document_link_provider: None,
+            document_link_provider: Some(DocumentLinkOptions {
+                resolve_provider: Some(false),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
             diagnostic_provider: None,
             experimental: None,
             position_encoding: None,
         }
     }
 
-    /// Publish diagnostics for a document
-    async fn publish_diagnostics(&self, uri: &Url, content: &str) {
-        if let Some(plugin) = self.plugins.plugin_for_uri(uri.as_str()) {
-            let path = uri
-                .to_file_path()
-                .unwrap_or_else(|_| PathBuf::from("document.wxs"));
+    /// Plugin, file path and current version of a document
+    fn document_at(&self, uri: &Url) -> Option<(Arc<dyn FullPluginDyn>, PathBuf, Arc<Document>)> {
+        let plugin = self.plugins.plugin_for_uri(uri.as_str())?;
+        let path = uri
+            .to_file_path()
+            .unwrap_or_else(|_| PathBuf::from(uri.path()));
+        let doc = self.documents.get(uri)?;
+        Some((Arc::clone(plugin), path, doc))
+    }
+
+    /// Run provider work off the async runtime, giving up with
+    /// `ContentModified` as soon as the document moves past `version`
+    async fn run_cancellable<T, F>(&self, uri: &Url, version: i32, work: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let mut changes = self.documents.subscribe();
+        let task = tokio::task::spawn_blocking(work);
+        tokio::pin!(task);
+        loop {
+            if self.documents.version(uri) != Some(version) {
+                tracing::debug!("Cancelled stale request for {} v{}", uri, version);
+                return Err(Error::content_modified());
+            }
+            tokio::select! {
+                result = &mut task => return result.map_err(|_| Error::internal_error()),
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        return Err(Error::internal_error());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Convert a plugin location, mapping columns if the file is open
+    fn to_lsp_location(&self, location: &SymbolLocation) -> Option<Location> {
+        let mut lsp_location = convert::to_lsp_location(location)?;
+        if let Some(doc) = self.documents.get(&lsp_location.uri) {
+            lsp_location.range = doc.lsp_range(lsp_location.range);
+        }
+        Some(lsp_location)
+    }
+
+    /// Convert plugin ranges within a file, mapping columns if it is open
+    fn to_lsp_ranges(&self, uri: &Url, ranges: &[SymbolRange]) -> Vec<Range> {
+        let doc = self.documents.get(uri);
+        ranges
+            .iter()
+            .map(|range| {
+                let range = convert::to_lsp_range(range);
+                match &doc {
+                    Some(doc) => doc.lsp_range(range),
+                    None => range,
+                }
+            })
+            .collect()
+    }
+
+    /// Convert a plugin hierarchy item, mapping columns if its file is open
+    fn to_lsp_call_hierarchy_item(&self, item: &HierarchyItem) -> Option<CallHierarchyItem> {
+        let mut lsp_item = convert::to_lsp_call_hierarchy_item(item)?;
+        if let Some(doc) = self.documents.get(&lsp_item.uri) {
+            lsp_item.range = doc.lsp_range(lsp_item.range);
+            lsp_item.selection_range = doc.lsp_range(lsp_item.selection_range);
+        }
+        Some(lsp_item)
+    }
+
+    /// Convert a plugin hierarchy item, mapping columns if its file is open
+    fn to_lsp_type_hierarchy_item(&self, item: &HierarchyItem) -> Option<TypeHierarchyItem> {
+        let mut lsp_item = convert::to_lsp_type_hierarchy_item(item)?;
+        if let Some(doc) = self.documents.get(&lsp_item.uri) {
+            lsp_item.range = doc.lsp_range(lsp_item.range);
+            lsp_item.selection_range = doc.lsp_range(lsp_item.selection_range);
+        }
+        Some(lsp_item)
+    }
+
+    /// Convert plugin edits, mapping columns in open files
+    fn to_lsp_workspace_edit(&self, edits: &[FileEdit]) -> WorkspaceEdit {
+        let mut workspace_edit = convert::to_lsp_workspace_edit(edits);
+        self.map_workspace_edit(&mut workspace_edit);
+        workspace_edit
+    }
 
-            let diagnostics = plugin.as_diagnostic().diagnose(content, &path);
-            let lsp_diagnostics: Vec<_> =
-                diagnostics.iter().map(convert::to_lsp_diagnostic).collect();
+    fn map_workspace_edit(&self, workspace_edit: &mut WorkspaceEdit) {
+        for (uri, edits) in workspace_edit.changes.iter_mut().flatten() {
+            if let Some(doc) = self.documents.get(uri) {
+                for edit in edits {
+                    edit.range = doc.lsp_range(edit.range);
+                }
+            }
+        }
+    }
+
+    /// Ask the client to report changes to plugin files made outside the editor
+    async fn register_file_watchers(&self) {
+        let extensions: Vec<String> = self
+            .plugins
+            .all_extensions()
+            .iter()
+            .map(|ext| ext.trim_start_matches('.').to_string())
+            .collect();
+        if extensions.is_empty() {
+            return;
+        }
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String(format!("**/*.{{{}}}", extensions.join(","))),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: "workspace-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            tracing::warn!("Failed to register file watchers: {}", e);
+        }
+    }
 
-            self.client
-                .publish_diagnostics(uri.clone(), lsp_diagnostics, None)
-                .await;
+    /// Offer type hierarchies for plugin files
+    async fn register_type_hierarchy(&self) {
+        let extensions = self.plugins.all_extensions();
+        if extensions.is_empty() {
+            return;
+        }
+        let options = TypeHierarchyRegistrationOptions {
+            text_document_registration_options: TextDocumentRegistrationOptions {
+                document_selector: Some(vec![DocumentFilter {
+                    language: None,
+                    scheme: Some("file".to_string()),
+                    pattern: Some(format!("**/*.{{{}}}", extensions.join(","))),
+                }]),
+            },
+            ..Default::default()
+        };
+        let registration = Registration {
+            id: "type-hierarchy".to_string(),
+            method: "textDocument/prepareTypeHierarchy".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            tracing::warn!("Failed to register type hierarchy: {}", e);
         }
     }
+
+    /// Let plugins load workspace state in the background, then re-check
+    /// open documents against it
+    fn open_workspace(&self, root: PathBuf) {
+        let plugins = Arc::clone(&self.plugins);
+        let diagnostics = self.diagnostics.clone();
+        let build = self.config.build.clone();
+        tokio::spawn(async move {
+            let loaded = tokio::task::spawn_blocking(move || {
+                for plugin in plugins.plugins() {
+                    plugin.as_language().open_workspace(&root, &build);
+                }
+            })
+            .await;
+            if loaded.is_ok() {
+                diagnostics.refresh_all();
+            }
+        });
+    }
 }
 
 #[tower_lsp::async_trait]
@@ -136,17 +332,36 @@ impl LanguageServer for LspServer {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         tracing::info!("{} initializing", self.config.engine.name);
 
+        let root_path = params.root_uri.and_then(|uri| uri.to_file_path().ok());
+        let type_hierarchy = params
+            .capabilities
+            .text_document
+            .as_ref()
+            .and_then(|text_document| text_document.type_hierarchy.as_ref())
+            .and_then(|type_hierarchy| type_hierarchy.dynamic_registration)
+            .unwrap_or(false);
+        self.type_hierarchy.store(type_hierarchy, Ordering::Relaxed);
+        let watch_files = params
+            .capabilities
+            .workspace
+            .and_then(|workspace| workspace.did_change_watched_files)
+            .and_then(|watched| watched.dynamic_registration)
+            .unwrap_or(false);
+        self.watch_files.store(watch_files, Ordering::Relaxed);
+
         // Try to find data path from workspace
         if self.config.engine.workspace_discovery {
-            if let Some(root_uri) = params.root_uri {
-                if let Ok(root_path) = root_uri.to_file_path() {
-                    if let Some(data_path) = self.config.find_data_path(&root_path) {
-                        tracing::info!("Found data at: {}", data_path.display());
-                    }
+            if let Some(root_path) = &root_path {
+                if let Some(data_path) = self.config.find_data_path(root_path) {
+                    tracing::info!("Found data at: {}", data_path.display());
                 }
             }
         }
 
+        if let Some(root_path) = root_path {
+            self.open_workspace(root_path);
+        }
+
         Ok(InitializeResult {
             capabilities: self.capabilities(),
             server_info: Some(ServerInfo {
@@ -164,6 +379,12 @@ impl LanguageServer for LspServer {
                 format!("{} ready", self.config.engine.name),
             )
             .await;
+        if self.watch_files.load(Ordering::Relaxed) {
+            self.register_file_watchers().await;
+        }
+        if self.type_hierarchy.load(Ordering::Relaxed) {
+            self.register_type_hierarchy().await;
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -177,17 +398,20 @@ impl LanguageServer for LspServer {
         let version = params.text_document.version;
 
         tracing::debug!("Document opened: {}", uri);
-        self.documents.open(uri.clone(), content.clone(), version);
-        self.publish_diagnostics(&uri, &content).await;
+        self.documents.open(uri.clone(), content, version);
+        self.diagnostics.schedule_now(uri, version);
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
         let version = params.text_document.version;
 
-        if let Some(change) = params.content_changes.into_iter().next() {
-            self.documents.update(&uri, change.text.clone(), version);
-            self.publish_diagnostics(&uri, &change.text).await;
+        if self
+            .documents
+            .apply_changes(&uri, &params.content_changes, version)
+            .is_some()
+        {
+            self.diagnostics.schedule(uri, version);
         }
     }
 
@@ -195,25 +419,55 @@ impl LanguageServer for LspServer {
         let uri = params.text_document.uri;
         tracing::debug!("Document closed: {}", uri);
         self.documents.close(&uri);
+        self.diagnostics.clear(&uri);
+        // Unsaved edits are dropped, so go back to the file on disk
+        if let (Some(plugin), Ok(path)) = (
+            self.plugins.plugin_for_uri(uri.as_str()),
+            uri.to_file_path(),
+        ) {
+            plugin.as_language().file_changed(&path);
+        }
         self.client.publish_diagnostics(uri, vec![], None).await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let mut changed = false;
+        for event in params.changes {
+            // Open documents are kept in sync from their buffers
+            if self.documents.is_open(&event.uri) {
+                continue;
+            }
+            let (Some(plugin), Ok(path)) = (
+                self.plugins.plugin_for_uri(event.uri.as_str()),
+                event.uri.to_file_path(),
+            ) else {
+                continue;
+            };
+            tracing::debug!("Workspace file changed: {}", event.uri);
+            plugin.as_language().file_changed(&path);
+            changed = true;
+        }
+        if changed {
+            self.diagnostics.refresh_all();
+        }
+    }
+
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = &params.text_document_position.text_document.uri;
         let position = params.text_document_position.position;
 
-        if let Some(plugin) = self.plugins.plugin_for_uri(uri.as_str()) {
-            if let Some(content) = self.documents.get_content(uri) {
-                let completions = plugin.as_completion().complete(
-                    &content,
-                    position.line + 1,
-                    position.character + 1,
-                );
+        if let Some((plugin, _, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let completions = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_completion().complete(&text, line + 1, column + 1)
+                })
+                .await?;
 
-                let items: Vec<_> = completions.iter().map(convert::to_lsp_completion).collect();
+            let items: Vec<_> = completions.iter().map(convert::to_lsp_completion).collect();
 
-                return Ok(Some(CompletionResponse::Array(items)));
-            }
+            return Ok(Some(CompletionResponse::Array(items)));
         }
 
         Ok(None)
@@ -223,15 +477,18 @@ impl LanguageServer for LspServer {
         let uri = &params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
 
-        if let Some(plugin) = self.plugins.plugin_for_uri(uri.as_str()) {
-            if let Some(content) = self.documents.get_content(uri) {
-                if let Some(info) =
-                    plugin
-                        .as_hover()
-                        .hover(&content, position.line + 1, position.character + 1)
-                {
-                    return Ok(Some(convert::to_lsp_hover(&info)));
-                }
+        if let Some((plugin, _, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let info = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_hover().hover(&text, line + 1, column + 1)
+                })
+                .await?;
+            if let Some(info) = info {
+                let mut hover = convert::to_lsp_hover(&info);
+                hover.range = hover.range.map(|range| doc.lsp_range(range));
+                return Ok(Some(hover));
             }
         }
 
@@ -244,20 +501,28 @@ impl LanguageServer for LspServer {
     ) -> Result<Option<DocumentSymbolResponse>> {
         let uri = &params.text_document.uri;
 
-        if let Some(plugin) = self.plugins.plugin_for_uri(uri.as_str()) {
-            if let Some(content) = self.documents.get_content(uri) {
-                match plugin.as_symbol().symbols(&content) {
-                    Ok(symbols) => {
-                        let lsp_symbols: Vec<_> = symbols
-                            .iter()
-                            .map(convert::to_lsp_document_symbol)
-                            .collect();
+        if let Some((plugin, _, doc)) = self.document_at(uri) {
+            let text = doc.text();
+            let symbols = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_symbol().symbols(&text)
+                })
+                .await?;
+            match symbols {
+                Ok(symbols) => {
+                    let lsp_symbols: Vec<_> = symbols
+                        .iter()
+                        .map(|symbol| {
+                            let mut symbol = convert::to_lsp_document_symbol(symbol);
+                            map_symbol_ranges(&doc, &mut symbol);
+                            symbol
+                        })
+                        .collect();
 
-                        return Ok(Some(DocumentSymbolResponse::Nested(lsp_symbols)));
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to extract symbols: {}", e);
-                    }
+                    return Ok(Some(DocumentSymbolResponse::Nested(lsp_symbols)));
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to extract symbols: {}", e);
                 }
             }
         }
@@ -268,39 +533,553 @@ impl LanguageServer for LspServer {
     async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
         let uri = &params.text_document.uri;
 
-        if let Some(plugin) = self.plugins.plugin_for_uri(uri.as_str()) {
-            if let Some(content) = self.documents.get_content(uri) {
-                match plugin.as_format().format(&content) {
-                    Ok(formatted) => {
-                        let lines: Vec<_> = content.lines().collect();
-                        let last_line = lines.len().saturating_sub(1);
-                        let last_col = lines.last().map(|l| l.len()).unwrap_or(0);
-
-                        let edit = TextEdit {
-                            range: Range {
-                                start: Position {
-                                    line: 0,
-                                    character: 0,
-                                },
-                                end: Position {
-                                    line: last_line as u32,
-                                    character: last_col as u32,
-                                },
-                            },
-                            new_text: formatted,
-                        };
-
-                        return Ok(Some(vec![edit]));
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to format document: {}", e);
-                    }
+        if let Some((plugin, _, doc)) = self.document_at(uri) {
+            let text = doc.text();
+            let formatted = self
+                .run_cancellable(uri, doc.version(), move || plugin.as_format().format(&text))
+                .await?;
+            match formatted {
+                Ok(formatted) => {
+                    let edit = TextEdit {
+                        range: doc.full_range(),
+                        new_text: formatted,
+                    };
+
+                    return Ok(Some(vec![edit]));
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to format document: {}", e);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let location = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin
+                        .as_definition()
+                        .definition(&path, &text, line + 1, column + 1)
+                })
+                .await?;
+            if let Some(location) = location {
+                return Ok(self
+                    .to_lsp_location(&location)
+                    .map(GotoDefinitionResponse::Scalar));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let locations = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin
+                        .as_references()
+                        .references(&path, &text, line + 1, column + 1)
+                })
+                .await?
+                .iter()
+                .filter(|l| params.context.include_declaration || !l.is_definition)
+                .filter_map(|l| self.to_lsp_location(l))
+                .collect();
+            return Ok(Some(locations));
+        }
+
+        Ok(None)
+    }
+
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> Result<Option<Vec<DocumentHighlight>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let request_path = path.clone();
+            let highlights = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin
+                        .as_references()
+                        .references(&request_path, &text, line + 1, column + 1)
+                })
+                .await?
+                .iter()
+                .filter(|l| l.path == path)
+                .map(|l| DocumentHighlight {
+                    range: doc.lsp_range(convert::to_lsp_range(&l.range)),
+                    kind: Some(if l.is_definition {
+                        DocumentHighlightKind::WRITE
+                    } else {
+                        DocumentHighlightKind::READ
+                    }),
+                })
+                .collect();
+            return Ok(Some(highlights));
+        }
+
+        Ok(None)
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let uri = &params.text_document.uri;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(params.position);
+            let text = doc.text();
+            let range = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin
+                        .as_rename()
+                        .prepare_rename(&path, &text, line + 1, column + 1)
+                })
+                .await?;
+            return Ok(range
+                .map(|r| PrepareRenameResponse::Range(doc.lsp_range(convert::to_lsp_range(&r)))));
+        }
+
+        Ok(None)
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let new_name = params.new_name.clone();
+            let edits = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin
+                        .as_rename()
+                        .rename(&path, &text, line + 1, column + 1, &new_name)
+                })
+                .await?
+                .map_err(Error::invalid_params)?;
+
+            return Ok(Some(self.to_lsp_workspace_edit(&edits)));
+        }
+
+        Ok(None)
+    }
+
+    async fn prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<Option<Vec<CallHierarchyItem>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let item = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_call_hierarchy().prepare_call_hierarchy(
+                        &path,
+                        &text,
+                        line + 1,
+                        column + 1,
+                    )
+                })
+                .await?;
+            return Ok(item
+                .and_then(|item| self.to_lsp_call_hierarchy_item(&item))
+                .map(|item| vec![item]));
+        }
+
+        Ok(None)
+    }
+
+    async fn incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>> {
+        let (Some(plugin), Some(item)) = (
+            self.plugins.plugin_for_uri(params.item.uri.as_str()),
+            convert::from_lsp_call_hierarchy_item(&params.item),
+        ) else {
+            return Ok(None);
+        };
+        let plugin = Arc::clone(plugin);
+        let calls = run_blocking(move || plugin.as_call_hierarchy().incoming_calls(&item)).await?;
+        Ok(Some(
+            calls
+                .iter()
+                .filter_map(|call| {
+                    let from = self.to_lsp_call_hierarchy_item(&call.item)?;
+                    // Ranges are within the caller
+                    let from_ranges = self.to_lsp_ranges(&from.uri, &call.ranges);
+                    Some(CallHierarchyIncomingCall { from, from_ranges })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>> {
+        let (Some(plugin), Some(item)) = (
+            self.plugins.plugin_for_uri(params.item.uri.as_str()),
+            convert::from_lsp_call_hierarchy_item(&params.item),
+        ) else {
+            return Ok(None);
+        };
+        let plugin = Arc::clone(plugin);
+        let calls = run_blocking(move || plugin.as_call_hierarchy().outgoing_calls(&item)).await?;
+        Ok(Some(
+            calls
+                .iter()
+                .filter_map(|call| {
+                    Some(CallHierarchyOutgoingCall {
+                        to: self.to_lsp_call_hierarchy_item(&call.item)?,
+                        from_ranges: self.to_lsp_ranges(&params.item.uri, &call.ranges),
+                    })
+                })
+                .collect(),
+        ))
+    }
+
+    async fn prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (line, column) = doc.char_position(position);
+            let text = doc.text();
+            let item = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_type_hierarchy().prepare_type_hierarchy(
+                        &path,
+                        &text,
+                        line + 1,
+                        column + 1,
+                    )
+                })
+                .await?;
+            return Ok(item
+                .and_then(|item| self.to_lsp_type_hierarchy_item(&item))
+                .map(|item| vec![item]));
+        }
+
+        Ok(None)
+    }
+
+    async fn supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let (Some(plugin), Some(item)) = (
+            self.plugins.plugin_for_uri(params.item.uri.as_str()),
+            convert::from_lsp_type_hierarchy_item(&params.item),
+        ) else {
+            return Ok(None);
+        };
+        let plugin = Arc::clone(plugin);
+        let items = run_blocking(move || plugin.as_type_hierarchy().supertypes(&item)).await?;
+        Ok(Some(
+            items
+                .iter()
+                .filter_map(|item| self.to_lsp_type_hierarchy_item(item))
+                .collect(),
+        ))
+    }
+
+    async fn subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Result<Option<Vec<TypeHierarchyItem>>> {
+        let (Some(plugin), Some(item)) = (
+            self.plugins.plugin_for_uri(params.item.uri.as_str()),
+            convert::from_lsp_type_hierarchy_item(&params.item),
+        ) else {
+            return Ok(None);
+        };
+        let plugin = Arc::clone(plugin);
+        let items = run_blocking(move || plugin.as_type_hierarchy().subtypes(&item)).await?;
+        Ok(Some(
+            items
+                .iter()
+                .filter_map(|item| self.to_lsp_type_hierarchy_item(item))
+                .collect(),
+        ))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let Some((plugin, path, doc)) = self.document_at(uri) else {
+            return Ok(None);
+        };
+        let only: Option<Vec<PluginActionKind>> = params.context.only.as_ref().map(|kinds| {
+            kinds
+                .iter()
+                .filter_map(convert::from_lsp_code_action_kind)
+                .collect()
+        });
+
+        let mut actions = Vec::new();
+        if PluginActionKind::QuickFix.is_requested(only.as_deref()) {
+            let requested = params.range;
+            for (diagnostic, fix) in self.diagnostics.fixes(uri, doc.version()) {
+                if diagnostic.range.start <= requested.end
+                    && requested.start <= diagnostic.range.end
+                {
+                    let mut action = convert::to_lsp_quick_fix(&fix, diagnostic);
+                    action
+                        .edit
+                        .iter_mut()
+                        .for_each(|e| self.map_workspace_edit(e));
+                    actions.push(CodeActionOrCommand::CodeAction(action));
                 }
             }
         }
 
+        let (start_line, start_col) = doc.char_position(params.range.start);
+        let (end_line, end_col) = doc.char_position(params.range.end);
+        let range = SymbolRange {
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        };
+        let text = doc.text();
+        let plugin_actions = self
+            .run_cancellable(uri, doc.version(), move || {
+                plugin
+                    .as_code_actions()
+                    .code_actions(&path, &text, range, only.as_deref())
+            })
+            .await?;
+        for plugin_action in &plugin_actions {
+            let mut action = convert::to_lsp_code_action(plugin_action);
+            action
+                .edit
+                .iter_mut()
+                .for_each(|e| self.map_workspace_edit(e));
+            actions.push(CodeActionOrCommand::CodeAction(action));
+        }
+
+        Ok(Some(actions))
+    }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>> {
+        let Some(plugin) = self.plugins.plugin_for_command(&params.command) else {
+            return Err(Error::invalid_params(format!(
+                "Unknown command: {}",
+                params.command
+            )));
+        };
+        let plugin = Arc::clone(plugin);
+        let documents = Arc::clone(&self.documents);
+        let edits = tokio::task::spawn_blocking(move || {
+            let open_text = |path: &Path| {
+                let uri = Url::from_file_path(path).ok()?;
+                documents.get_content(&uri).map(|text| text.to_string())
+            };
+            plugin
+                .as_code_actions()
+                .execute_command(&params.command, &params.arguments, &open_text)
+        })
+        .await
+        .map_err(|_| Error::internal_error())?
+        .map_err(Error::invalid_params)?;
+
+        if !edits.is_empty() {
+            match self
+                .client
+                .apply_edit(self.to_lsp_workspace_edit(&edits))
+                .await
+            {
+                Ok(response) if !response.applied => tracing::warn!(
+                    "Client rejected edit: {}",
+                    response.failure_reason.unwrap_or_default()
+                ),
+                Err(e) => tracing::warn!("Failed to apply edit: {}", e),
+                Ok(_) => {}
+            }
+        }
+        Ok(None)
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let uri = &params.text_document.uri;
+
+        if let Some((plugin, _, doc)) = self.document_at(uri) {
+            let text = doc.text();
+            let tokens = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_semantic_tokens().semantic_tokens(&text)
+                })
+                .await?;
+            // Lengths are measured in the client's units too
+            let positioned: Vec<_> = tokens
+                .iter()
+                .map(|token| {
+                    let start = doc.lsp_position(token.line, token.start_col);
+                    let end = doc.lsp_position(token.line, token.start_col + token.length);
+                    (start, end.character - start.character, token.kind)
+                })
+                .collect();
+            return Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: convert::to_lsp_semantic_tokens(&positioned),
+            })));
+        }
+
         Ok(None)
     }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let uri = &params.text_document.uri;
+
+        if let Some((plugin, _, doc)) = self.document_at(uri) {
+            let text = doc.text();
+            let ranges = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_folding().folding_ranges(&text)
+                })
+                .await?;
+            return Ok(Some(
+                ranges.iter().map(convert::to_lsp_folding_range).collect(),
+            ));
+        }
+
+        Ok(None)
+    }
+
+    async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+        let uri = &params.text_document.uri;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let text = doc.text();
+            let links = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_document_links().document_links(&path, &text)
+                })
+                .await?
+                .iter()
+                .filter_map(convert::to_lsp_document_link)
+                .map(|mut link| {
+                    link.range = doc.lsp_range(link.range);
+                    link
+                })
+                .collect();
+            return Ok(Some(links));
+        }
+
+        Ok(None)
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = &params.text_document.uri;
+
+        if let Some((plugin, path, doc)) = self.document_at(uri) {
+            let (start_line, start_col) = doc.char_position(params.range.start);
+            let (end_line, end_col) = doc.char_position(params.range.end);
+            let range = SymbolRange {
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+            };
+            let text = doc.text();
+            let hints = self
+                .run_cancellable(uri, doc.version(), move || {
+                    plugin.as_inlay_hints().inlay_hints(&path, &text, range)
+                })
+                .await?
+                .iter()
+                .map(|hint| {
+                    let mut lsp_hint = convert::to_lsp_inlay_hint(hint);
+                    lsp_hint.position = doc.lsp_position(hint.line, hint.column);
+                    lsp_hint
+                })
+                .collect();
+            return Ok(Some(hints));
+        }
+
+        Ok(None)
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        let symbols = self
+            .plugins
+            .plugins()
+            .iter()
+            .flat_map(|plugin| {
+                plugin
+                    .as_workspace_symbols()
+                    .workspace_symbols(&params.query)
+            })
+            .filter_map(|symbol| {
+                let mut info = convert::to_lsp_workspace_symbol(&symbol)?;
+                if let Some(doc) = self.documents.get(&info.location.uri) {
+                    info.location.range = doc.lsp_range(info.location.range);
+                }
+                Some(info)
+            })
+            .collect();
+        Ok(Some(symbols))
+    }
+}
+
+/// Run workspace-wide provider work, which no document version ties to,
+/// off the async runtime
+async fn run_blocking<T, F>(work: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|_| Error::internal_error())
+}
+
+/// Map a document symbol's character ranges to UTF-16, children included
+fn map_symbol_ranges(doc: &Document, symbol: &mut DocumentSymbol) {
+    symbol.range = doc.lsp_range(symbol.range);
+    symbol.selection_range = doc.lsp_range(symbol.selection_range);
+    for child in symbol.children.iter_mut().flatten() {
+        map_symbol_ranges(doc, child);
+    }
 }
 
 #[cfg(test)]
@@ -5,8 +5,12 @@
 //!
 //! - Hover documentation (wix-hover)
 //! - Document symbols (wix-symbols)
-//! - Diagnostics/linting (wix-lint)
+//! - Diagnostics/linting (winter and wix-analyzer)
 //! - Formatting (wix-fmt)
+//! - Go-to-definition, references, rename and workspace symbols (wix-references)
+//! - Call and type hierarchies over what installs what (wix-symbols)
+//! - Quick fixes, refactorings and source actions, with folder harvesting (wix-scan)
+//! - Semantic highlighting, folding, document links and inlay hints
 //!
 //! # Architecture
 //!
@@ -39,7 +43,21 @@
 //!   data_search_paths:
 //!     - wix-data
 //!     - .wix-data
+//!
+//! diagnostics:
+//!   debounce_ms: 300
+//!
+//! build:
+//!   bind_paths:
+//!     - bin/Release
+//!   include_paths:
+//!     - include
+//!   defines:
+//!     - Configuration=Release
 //! ```
+//!
+//! Diagnostics also follow the workspace `.winter.yaml` and
+//! `.wixanalyzer.json`.
 
 pub mod engine;
 pub mod plugins;
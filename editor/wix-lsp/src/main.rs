@@ -6,7 +6,7 @@
 
 use tower_lsp::{LspService, Server};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
-use wix_lsp::{LspServer, PluginRegistry, WixPlugin};
+use wix_lsp::{EngineConfig, LspServer, PluginRegistry, WixPlugin};
 
 #[tokio::main]
 async fn main() {
@@ -25,7 +25,10 @@ async fn main() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(|client| LspServer::new(client, registry));
+    let config = EngineConfig::load_default(&std::env::current_dir().unwrap_or_default());
+
+    let (service, socket) =
+        LspService::new(|client| LspServer::with_config(client, registry, config));
     Server::new(stdin, stdout, socket).serve(service).await;
 }
 
@@ -9,7 +9,12 @@ pub mod wix;
 
 pub use registry::{FullPluginDyn, PluginRegistry};
 pub use traits::{
-    Completion, CompletionKind, CompletionProvider, Diagnostic, DiagnosticProvider,
-    DiagnosticSeverity, FormatProvider, FullPlugin, HoverInfo, HoverProvider, HoverRange,
-    LanguagePlugin, Symbol, SymbolKind, SymbolProvider, SymbolRange,
+    BuildSettings, CallHierarchyProvider, CodeAction, CodeActionKind, CodeActionProvider, Command,
+    Completion, CompletionKind, CompletionProvider, DefinitionProvider, Diagnostic, DiagnosticFix,
+    DiagnosticProvider, DiagnosticSeverity, DiagnosticTag, DocumentLink, DocumentLinkProvider,
+    FileEdit, FoldingKind, FoldingRange, FoldingRangeProvider, FormatProvider, FullPlugin,
+    HierarchyCall, HierarchyItem, HoverInfo, HoverProvider, HoverRange, InlayHint,
+    InlayHintProvider, LanguagePlugin, ReferenceProvider, RelatedInformation, RenameProvider,
+    SemanticToken, SemanticTokenKind, SemanticTokenProvider, Symbol, SymbolKind, SymbolLocation,
+    SymbolProvider, SymbolRange, TypeHierarchyProvider, WorkspaceSymbol, WorkspaceSymbolProvider,
 };
@@ -4,8 +4,10 @@
 //! based on file extensions.
 
 use super::traits::{
-    Completion, CompletionProvider, Diagnostic, DiagnosticProvider, FormatProvider, HoverInfo,
-    HoverProvider, LanguagePlugin, Symbol, SymbolProvider,
+    CallHierarchyProvider, CodeActionProvider, Completion, CompletionProvider, DefinitionProvider,
+    Diagnostic, DiagnosticProvider, DocumentLinkProvider, FoldingRangeProvider, FormatProvider,
+    HoverInfo, HoverProvider, InlayHintProvider, LanguagePlugin, ReferenceProvider, RenameProvider,
+    SemanticTokenProvider, Symbol, SymbolProvider, TypeHierarchyProvider, WorkspaceSymbolProvider,
 };
 use std::collections::HashMap;
 use std::path::Path;
@@ -27,6 +29,17 @@ pub trait FullPluginDyn: Send + Sync {
     fn as_symbol(&self) -> &dyn SymbolProvider;
     fn as_diagnostic(&self) -> &dyn DiagnosticProvider;
     fn as_format(&self) -> &dyn FormatProvider;
+    fn as_definition(&self) -> &dyn DefinitionProvider;
+    fn as_references(&self) -> &dyn ReferenceProvider;
+    fn as_rename(&self) -> &dyn RenameProvider;
+    fn as_workspace_symbols(&self) -> &dyn WorkspaceSymbolProvider;
+    fn as_call_hierarchy(&self) -> &dyn CallHierarchyProvider;
+    fn as_type_hierarchy(&self) -> &dyn TypeHierarchyProvider;
+    fn as_code_actions(&self) -> &dyn CodeActionProvider;
+    fn as_semantic_tokens(&self) -> &dyn SemanticTokenProvider;
+    fn as_folding(&self) -> &dyn FoldingRangeProvider;
+    fn as_document_links(&self) -> &dyn DocumentLinkProvider;
+    fn as_inlay_hints(&self) -> &dyn InlayHintProvider;
 }
 
 impl PluginRegistry {
@@ -85,6 +98,22 @@ impl PluginRegistry {
         chars.into_iter().map(|c| c.to_string()).collect()
     }
 
+    /// Get all commands offered by plugins
+    pub fn all_commands(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .flat_map(|p| p.as_code_actions().commands().to_vec())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Get the plugin that handles a command
+    pub fn plugin_for_command(&self, command: &str) -> Option<&Arc<dyn FullPluginDyn>> {
+        self.plugins
+            .iter()
+            .find(|p| p.as_code_actions().commands().contains(&command))
+    }
+
     /// Get all supported extensions
     pub fn all_extensions(&self) -> Vec<String> {
         self.extension_map.keys().cloned().collect()
@@ -3,7 +3,8 @@
 //! These traits define the interface that language plugins must implement
 //! to provide IDE features like completion, hover, symbols, etc.
 
-use std::path::Path;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
 
 /// Completion item returned by plugins
 #[derive(Debug, Clone)]
@@ -83,6 +84,55 @@ pub struct SymbolRange {
     pub end_col: u32,
 }
 
+/// Location of a symbol in a workspace file
+#[derive(Debug, Clone)]
+pub struct SymbolLocation {
+    pub path: PathBuf,
+    pub range: SymbolRange,
+    /// Whether this is where the symbol is defined
+    pub is_definition: bool,
+}
+
+/// Symbol returned by workspace symbol search
+#[derive(Debug, Clone)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Element that defines it (e.g., "Component")
+    pub container: Option<String>,
+    pub location: SymbolLocation,
+}
+
+/// Item of a call or type hierarchy
+#[derive(Debug, Clone)]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Element that defines it (e.g., "Component"); with the name this
+    /// identifies the item between requests
+    pub element: String,
+    pub detail: Option<String>,
+    pub path: PathBuf,
+    pub range: SymbolRange,
+    pub selection_range: SymbolRange,
+}
+
+/// Item linked to the one a hierarchy request is about
+#[derive(Debug, Clone)]
+pub struct HierarchyCall {
+    pub item: HierarchyItem,
+    /// Where the links are made, within the including item's file
+    pub ranges: Vec<SymbolRange>,
+}
+
+/// Text replacement in a workspace file
+#[derive(Debug, Clone)]
+pub struct FileEdit {
+    pub path: PathBuf,
+    pub range: SymbolRange,
+    pub new_text: String,
+}
+
 /// Diagnostic returned by plugins
 #[derive(Debug, Clone)]
 pub struct Diagnostic {
@@ -93,6 +143,43 @@ pub struct Diagnostic {
     pub column: u32,
     pub length: u32,
     pub help: Option<String>,
+    /// Tool that reported it (e.g., "winter")
+    pub source: Option<String>,
+    /// Rule documentation URL
+    pub code_description: Option<String>,
+    /// Other locations involved in the problem
+    pub related: Vec<RelatedInformation>,
+    pub tags: Vec<DiagnosticTag>,
+    /// Edits that resolve the problem, offered as quick fixes
+    pub fixes: Vec<DiagnosticFix>,
+}
+
+/// A quick fix attached to a diagnostic
+#[derive(Debug, Clone)]
+pub struct DiagnosticFix {
+    pub title: String,
+    pub edits: Vec<FileEdit>,
+    /// Safe to apply without review
+    pub is_preferred: bool,
+}
+
+/// A location related to a diagnostic
+#[derive(Debug, Clone)]
+pub struct RelatedInformation {
+    pub path: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub length: u32,
+    pub message: String,
+}
+
+/// Rendering hints for a diagnostic (maps to LSP DiagnosticTag)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticTag {
+    /// Unused or unnecessary code, usually faded out
+    Unnecessary,
+    /// Deprecated code, usually struck through
+    Deprecated,
 }
 
 /// Diagnostic severity
@@ -104,6 +191,129 @@ pub enum DiagnosticSeverity {
     Hint,
 }
 
+/// Kind of code action (maps to LSP CodeActionKind)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeActionKind {
+    QuickFix,
+    Refactor,
+    RefactorExtract,
+    RefactorRewrite,
+    Source,
+}
+
+impl CodeActionKind {
+    /// Whether this kind is `other` or one of its sub-kinds
+    pub fn is_within(self, other: CodeActionKind) -> bool {
+        match other {
+            CodeActionKind::Refactor => matches!(
+                self,
+                CodeActionKind::Refactor
+                    | CodeActionKind::RefactorExtract
+                    | CodeActionKind::RefactorRewrite
+            ),
+            _ => self == other,
+        }
+    }
+
+    /// Whether a request's `only` filter, if any, asks for this kind
+    pub fn is_requested(self, only: Option<&[CodeActionKind]>) -> bool {
+        only.is_none_or(|only| only.iter().any(|kind| self.is_within(*kind)))
+    }
+}
+
+/// Command run on the server through `workspace/executeCommand`
+#[derive(Debug, Clone)]
+pub struct Command {
+    pub title: String,
+    pub command: String,
+    pub arguments: Vec<serde_json::Value>,
+}
+
+/// Refactoring or source action returned by plugins
+#[derive(Debug, Clone)]
+pub struct CodeAction {
+    pub title: String,
+    pub kind: CodeActionKind,
+    pub edits: Vec<FileEdit>,
+    /// Run after the edits are applied
+    pub command: Option<Command>,
+    pub is_preferred: bool,
+}
+
+/// Kind of semantically highlighted text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemanticTokenKind {
+    /// Name defined by an element, e.g. `Component/@Id`
+    Definition,
+    /// Name referring to a definition, e.g. `ComponentRef/@Id`
+    Reference,
+    /// `$(var.X)`, `$(env.X)` or `$(sys.X)`
+    PreprocessorVariable,
+    /// `!(bind.X)` or `!(bindpath.X)`
+    BindVariable,
+    /// `!(loc.X)`
+    Localization,
+    /// `[PROPERTY]` in a formatted value
+    Property,
+}
+
+/// Highlighted span within one line (0-based, columns in characters)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub line: u32,
+    pub start_col: u32,
+    pub length: u32,
+    pub kind: SemanticTokenKind,
+}
+
+/// Kind of folding range (maps to LSP FoldingRangeKind)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldingKind {
+    Comment,
+    /// Conditional or other preprocessor region
+    Region,
+}
+
+/// Foldable span of lines (0-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FoldingRange {
+    pub start_line: u32,
+    pub end_line: u32,
+    /// `None` for ordinary elements
+    pub kind: Option<FoldingKind>,
+}
+
+/// Text that opens a file
+#[derive(Debug, Clone)]
+pub struct DocumentLink {
+    pub range: SymbolRange,
+    pub target: PathBuf,
+    pub tooltip: Option<String>,
+}
+
+/// Annotation shown inline after a position (0-based, column in characters)
+#[derive(Debug, Clone)]
+pub struct InlayHint {
+    pub line: u32,
+    pub column: u32,
+    pub label: String,
+    pub tooltip: Option<String>,
+}
+
+/// Search paths and variables the project is built with
+///
+/// Relative paths are resolved against the workspace root.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BuildSettings {
+    /// Bind paths, `DIR` or `NAME=DIR` as on the wix command line
+    pub bind_paths: Vec<String>,
+    /// Directories searched for `<?include?>` files
+    pub include_paths: Vec<String>,
+    /// Preprocessor variables, `NAME=VALUE`
+    pub defines: Vec<String>,
+}
+
 /// Core plugin trait - provides metadata about the plugin
 pub trait LanguagePlugin: Send + Sync {
     /// Plugin name
@@ -120,6 +330,17 @@ pub trait LanguagePlugin: Send + Sync {
 
     /// Check if plugin is initialized
     fn is_initialized(&self) -> bool;
+
+    /// Load workspace-wide state such as configuration and cross-file indexes
+    ///
+    /// Called once the workspace root is known; the default does nothing.
+    fn open_workspace(&self, _root: &Path, _build: &BuildSettings) {}
+
+    /// An open document was edited
+    fn document_changed(&self, _path: &Path, _source: &str) {}
+
+    /// A workspace file was created, changed or deleted on disk
+    fn file_changed(&self, _path: &Path) {}
 }
 
 /// Provides code completion
@@ -147,6 +368,138 @@ pub trait FormatProvider: Send + Sync {
     fn format(&self, source: &str) -> Result<String, String>;
 }
 
+/// Provides go-to-definition
+pub trait DefinitionProvider: Send + Sync {
+    fn definition(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<SymbolLocation>;
+}
+
+/// Provides find-references and document highlights
+pub trait ReferenceProvider: Send + Sync {
+    /// Every usage of the symbol at a position, its definition included
+    fn references(&self, path: &Path, source: &str, line: u32, column: u32) -> Vec<SymbolLocation>;
+}
+
+/// Provides workspace-wide renames
+pub trait RenameProvider: Send + Sync {
+    /// Range of the name a rename at this position would change
+    fn prepare_rename(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<SymbolRange>;
+
+    /// Edits renaming the symbol at a position, or why it can't be renamed
+    fn rename(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+        new_name: &str,
+    ) -> Result<Vec<FileEdit>, String>;
+}
+
+/// Provides workspace symbol search
+pub trait WorkspaceSymbolProvider: Send + Sync {
+    fn workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol>;
+}
+
+/// Provides call hierarchies over what installs what
+pub trait CallHierarchyProvider: Send + Sync {
+    /// Item at a position
+    fn prepare_call_hierarchy(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<HierarchyItem>;
+
+    /// Items that include `item`, e.g. the Features installing a Component
+    fn incoming_calls(&self, item: &HierarchyItem) -> Vec<HierarchyCall>;
+
+    /// Items `item` includes, e.g. what a Feature installs
+    fn outgoing_calls(&self, item: &HierarchyItem) -> Vec<HierarchyCall>;
+}
+
+/// Provides type hierarchies over what contains what, Directory placement
+/// included
+pub trait TypeHierarchyProvider: Send + Sync {
+    /// Item at a position
+    fn prepare_type_hierarchy(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<HierarchyItem>;
+
+    /// Items containing `item`
+    fn supertypes(&self, item: &HierarchyItem) -> Vec<HierarchyItem>;
+
+    /// Items contained in `item`
+    fn subtypes(&self, item: &HierarchyItem) -> Vec<HierarchyItem>;
+}
+
+/// Provides refactorings, source actions and server-side commands
+///
+/// Quick fixes come from [`Diagnostic::fixes`]; this trait covers actions
+/// that don't depend on a diagnostic.
+pub trait CodeActionProvider: Send + Sync {
+    /// Actions for a selection, restricted to `only` when the client asks
+    fn code_actions(
+        &self,
+        path: &Path,
+        source: &str,
+        range: SymbolRange,
+        only: Option<&[CodeActionKind]>,
+    ) -> Vec<CodeAction>;
+
+    /// Commands handled by `execute_command`
+    fn commands(&self) -> &[&str];
+
+    /// Run a command, returning the edits to apply
+    ///
+    /// `open_text` returns the buffer of a document open in the editor.
+    fn execute_command(
+        &self,
+        command: &str,
+        arguments: &[serde_json::Value],
+        open_text: &dyn Fn(&Path) -> Option<String>,
+    ) -> Result<Vec<FileEdit>, String>;
+}
+
+/// Provides semantic highlighting
+pub trait SemanticTokenProvider: Send + Sync {
+    /// Tokens of a document, in document order
+    fn semantic_tokens(&self, source: &str) -> Vec<SemanticToken>;
+}
+
+/// Provides folding ranges
+pub trait FoldingRangeProvider: Send + Sync {
+    fn folding_ranges(&self, source: &str) -> Vec<FoldingRange>;
+}
+
+/// Provides clickable file references
+pub trait DocumentLinkProvider: Send + Sync {
+    /// Links whose targets exist on disk
+    fn document_links(&self, path: &Path, source: &str) -> Vec<DocumentLink>;
+}
+
+/// Provides inlay hints
+pub trait InlayHintProvider: Send + Sync {
+    /// Hints within a range of the document
+    fn inlay_hints(&self, path: &Path, source: &str, range: SymbolRange) -> Vec<InlayHint>;
+}
+
 /// Combined trait for a full-featured plugin
 pub trait FullPlugin:
     LanguagePlugin
@@ -155,6 +508,17 @@ pub trait FullPlugin:
     + SymbolProvider
     + DiagnosticProvider
     + FormatProvider
+    + DefinitionProvider
+    + ReferenceProvider
+    + RenameProvider
+    + WorkspaceSymbolProvider
+    + CallHierarchyProvider
+    + TypeHierarchyProvider
+    + CodeActionProvider
+    + SemanticTokenProvider
+    + FoldingRangeProvider
+    + DocumentLinkProvider
+    + InlayHintProvider
 {
 }
 
@@ -166,6 +530,17 @@ impl<T> FullPlugin for T where
         + SymbolProvider
         + DiagnosticProvider
         + FormatProvider
+        + DefinitionProvider
+        + ReferenceProvider
+        + RenameProvider
+        + WorkspaceSymbolProvider
+        + CallHierarchyProvider
+        + TypeHierarchyProvider
+        + CodeActionProvider
+        + SemanticTokenProvider
+        + FoldingRangeProvider
+        + DocumentLinkProvider
+        + InlayHintProvider
 {
 }
 
@@ -185,6 +560,16 @@ mod tests {
         assert_ne!(DiagnosticSeverity::Error, DiagnosticSeverity::Warning);
     }
 
+    #[test]
+    fn test_code_action_kind_is_within() {
+        assert!(CodeActionKind::RefactorExtract.is_within(CodeActionKind::Refactor));
+        assert!(CodeActionKind::Refactor.is_within(CodeActionKind::Refactor));
+        assert!(!CodeActionKind::Refactor.is_within(CodeActionKind::RefactorExtract));
+        assert!(!CodeActionKind::Source.is_within(CodeActionKind::QuickFix));
+        assert!(CodeActionKind::Source.is_requested(None));
+        assert!(!CodeActionKind::Source.is_requested(Some(&[CodeActionKind::Refactor])));
+    }
+
     #[test]
     fn test_symbol_kind() {
         assert_eq!(SymbolKind::File, SymbolKind::File);
@@ -0,0 +1,750 @@
+//! Refactorings, source actions and commands
+//!
+//! Quick fixes travel with diagnostics; everything here is computed for the
+//! selection a client asks about. Work that needs more than the current
+//! buffer, such as scanning a folder, runs as a server-side command.
+
+use super::edits::{self, ChildPosition, SourceMap};
+use super::parse::ParsedDocument;
+use super::references::WorkspaceReferences;
+use crate::plugins::traits::{CodeAction, CodeActionKind, Command, FileEdit, SymbolRange};
+use roxmltree::{Document, Node};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tower_lsp::lsp_types::Url;
+use wix_fmt::WixData;
+
+/// Scan a folder with wix-scan and append the result as new fragments
+pub const HARVEST_FOLDER: &str = "wix.harvestFolder";
+
+/// Commands run through `workspace/executeCommand`
+pub const COMMANDS: &[&str] = &[HARVEST_FOLDER];
+
+/// Attributes holding a GUID
+const GUID_ATTRIBUTES: &[&str] = &["Guid", "UpgradeCode", "ProductCode", "PackageCode"];
+
+/// Elements that include a nested Component in the install
+const COMPONENT_CONTAINERS: &[&str] = &["Feature", "FeatureGroup", "FeatureRef", "ComponentGroup"];
+
+/// Elements that give a nested Component its install folder
+const DIRECTORY_ELEMENTS: &[&str] = &["Directory", "DirectoryRef", "StandardDirectory"];
+
+/// Reference left behind for an element moved into a fragment, so the
+/// linker still pulls the fragment in
+const REFERENCE_ELEMENTS: &[(&str, &str)] = &[
+    ("Binary", "BinaryRef"),
+    ("Component", "ComponentRef"),
+    ("ComponentGroup", "ComponentGroupRef"),
+    ("CustomAction", "CustomActionRef"),
+    ("Dialog", "DialogRef"),
+    ("Feature", "FeatureRef"),
+    ("FeatureGroup", "FeatureGroupRef"),
+    ("Property", "PropertyRef"),
+    ("UI", "UIRef"),
+];
+
+/// Features offered as targets for a new ComponentRef
+const MAX_FEATURE_TARGETS: usize = 5;
+
+/// Install folder used when a harvest has no enclosing directory
+const DEFAULT_DIRECTORY: &str = "INSTALLFOLDER";
+
+/// Everything the actions for one request look at
+pub struct ActionContext<'a> {
+    pub parsed: &'a ParsedDocument,
+    pub path: &'a Path,
+    pub range: SymbolRange,
+    pub references: Option<&'a WorkspaceReferences>,
+    pub ordering: Option<&'a WixData>,
+}
+
+/// Actions for a selection, restricted to `only` when given
+pub fn code_actions(cx: &ActionContext, only: Option<&[CodeActionKind]>) -> Vec<CodeAction> {
+    let Ok(doc) = cx.parsed.tree() else {
+        return Vec::new();
+    };
+    let map = SourceMap::new(cx.path, cx.parsed.text());
+    let start = map.offset(cx.range.start_line, cx.range.start_col);
+    let end = map.offset(cx.range.end_line, cx.range.end_col);
+    let wants = |kind: CodeActionKind| kind.is_requested(only);
+
+    let mut actions = Vec::new();
+    if let Some(node) = edits::element_at(doc, start) {
+        if wants(CodeActionKind::RefactorRewrite) {
+            actions.extend(guid_actions(&map, node, start));
+            if let Some(references) = cx.references {
+                actions.extend(component_ref_actions(&map, node, references));
+            }
+        }
+        if wants(CodeActionKind::Refactor) {
+            actions.extend(harvest_action(&map, node, start));
+        }
+    }
+    if wants(CodeActionKind::RefactorExtract) && start < end {
+        actions.extend(extract_fragment(&map, doc, start, end));
+    }
+    if wants(CodeActionKind::Source) {
+        actions.extend(sort_attributes(&map, doc, cx.ordering));
+        if let Some(ordering) = cx.ordering {
+            actions.extend(organize_children(&map, doc, ordering));
+        }
+    }
+    actions
+}
+
+/// Run a command from [`COMMANDS`]
+pub fn execute_command(
+    command: &str,
+    arguments: &[Value],
+    open_text: &dyn Fn(&Path) -> Option<String>,
+) -> Result<Vec<FileEdit>, String> {
+    match command {
+        HARVEST_FOLDER => harvest_folder(arguments, open_text),
+        _ => Err(format!("Unknown command: {}", command)),
+    }
+}
+
+fn action(title: impl Into<String>, kind: CodeActionKind, edits: Vec<FileEdit>) -> CodeAction {
+    CodeAction {
+        title: title.into(),
+        kind,
+        edits,
+        command: None,
+        is_preferred: false,
+    }
+}
+
+/// "Generate GUID" and "Convert Guid to `*`" on a GUID attribute
+fn guid_actions(map: &SourceMap, node: Node, offset: usize) -> Vec<CodeAction> {
+    if offset >= edits::start_tag_end(map.text(), node) {
+        return Vec::new();
+    }
+    // The attribute under the cursor, else the element's Guid
+    let attribute = node
+        .attributes()
+        .find(|a| GUID_ATTRIBUTES.contains(&a.name()) && a.range().contains(&offset))
+        .or_else(|| node.attributes().find(|a| a.name() == "Guid"));
+    let Some(attribute) = attribute else {
+        return Vec::new();
+    };
+
+    let value = attribute.range_value();
+    let guid = uuid::Uuid::new_v4().to_string().to_uppercase();
+    let mut actions = vec![action(
+        format!("Generate GUID for {}", attribute.name()),
+        CodeActionKind::RefactorRewrite,
+        vec![map.edit(value.start, value.end, guid)],
+    )];
+    if attribute.name() == "Guid" && attribute.value() != "*" {
+        actions.push(action(
+            "Convert Guid to '*'",
+            CodeActionKind::RefactorRewrite,
+            vec![map.edit(value.start, value.end, "*")],
+        ));
+    }
+    actions
+}
+
+/// Reference a Component that nothing includes from a workspace Feature
+fn component_ref_actions(
+    map: &SourceMap,
+    node: Node,
+    references: &WorkspaceReferences,
+) -> Vec<CodeAction> {
+    use wix_references::SymbolKind as Wix;
+
+    if !node.has_tag_name("Component") {
+        return Vec::new();
+    }
+    let Some(id) = node.attribute("Id") else {
+        return Vec::new();
+    };
+    let contained = node
+        .ancestors()
+        .any(|a| COMPONENT_CONTAINERS.contains(&a.tag_name().name()));
+    if contained || references.is_referenced(id, Wix::Component) {
+        return Vec::new();
+    }
+
+    let reference = format!("<ComponentRef Id=\"{}\" />", edits::escape_attribute(id));
+    references
+        .definitions_of_kind(Wix::Feature)
+        .into_iter()
+        .take(MAX_FEATURE_TARGETS)
+        .filter_map(|(feature, path)| {
+            let edit = if path == map.path() {
+                add_to_feature(map, &feature, &reference)?
+            } else {
+                let content = references.file_content(&path)?;
+                add_to_feature(&SourceMap::new(&path, &content), &feature, &reference)?
+            };
+            Some(action(
+                format!("Add ComponentRef to feature '{}'", feature),
+                CodeActionKind::RefactorRewrite,
+                vec![edit],
+            ))
+        })
+        .collect()
+}
+
+fn add_to_feature(map: &SourceMap, feature: &str, element: &str) -> Option<FileEdit> {
+    let doc = Document::parse(map.text()).ok()?;
+    let node = doc
+        .descendants()
+        .find(|n| n.has_tag_name("Feature") && n.attribute("Id") == Some(feature))?;
+    Some(edits::add_child(map, node, element, &ChildPosition::Last))
+}
+
+/// Move whole sibling elements into a new Fragment at the end of the document
+fn extract_fragment(
+    map: &SourceMap,
+    doc: &Document,
+    start: usize,
+    end: usize,
+) -> Option<CodeAction> {
+    let source = map.text();
+    let root = doc.root_element();
+    let parent = edits::element_at(doc, start)?
+        .ancestors()
+        .filter(|n| n.is_element())
+        .find(|n| n.range().start < start && end <= n.range().end)?;
+    if parent == root {
+        return None;
+    }
+
+    // Every element touched by the selection must be inside it
+    let mut selected = Vec::new();
+    for child in parent.children().filter(|c| c.is_element()) {
+        let range = child.range();
+        if range.end <= start || range.start >= end {
+            continue;
+        }
+        if range.start < start || range.end > end {
+            return None;
+        }
+        selected.push(child);
+    }
+    let (first, last) = (*selected.first()?, *selected.last()?);
+
+    let newline = map.newline();
+    let fragment_indent = edits::child_indent(map, root);
+    let unit = fragment_indent
+        .strip_prefix(map.indent_at(root.range().start))
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or("  ")
+        .to_string();
+    let old_indent = map.indent_at(first.range().start);
+    let moved = &source[first.range().start..last.range().end];
+
+    // Components keep their install folder through a DirectoryRef
+    let directory = DIRECTORY_ELEMENTS
+        .contains(&parent.tag_name().name())
+        .then(|| parent.attribute("Id"))
+        .flatten();
+    let mut body_indent = format!("{}{}", fragment_indent, unit);
+    let mut fragment = format!("<Fragment>{}", newline);
+    if let Some(directory) = directory {
+        fragment.push_str(&format!(
+            "{}<DirectoryRef Id=\"{}\">{}",
+            body_indent, directory, newline
+        ));
+        body_indent.push_str(&unit);
+    }
+    fragment.push_str(&reindent(moved, old_indent, &body_indent, newline));
+    fragment.push_str(newline);
+    if directory.is_some() {
+        fragment.push_str(&format!(
+            "{}{}</DirectoryRef>{}",
+            fragment_indent, unit, newline
+        ));
+    }
+    fragment.push_str(&format!("{}</Fragment>", fragment_indent));
+
+    let refs: Vec<String> = if directory.is_some() {
+        Vec::new()
+    } else {
+        selected
+            .iter()
+            .filter_map(|node| {
+                let (_, reference) = REFERENCE_ELEMENTS
+                    .iter()
+                    .find(|(element, _)| node.has_tag_name(*element))?;
+                let id = node.attribute("Id")?;
+                Some(format!(
+                    "<{} Id=\"{}\" />",
+                    reference,
+                    edits::escape_attribute(id)
+                ))
+            })
+            .collect()
+    };
+    let removal = if refs.is_empty() {
+        let (start, end) = map.whole_lines(first.range().start, last.range().end);
+        map.edit(start, end, "")
+    } else {
+        map.edit(
+            first.range().start,
+            last.range().end,
+            refs.join(&format!("{}{}", newline, old_indent)),
+        )
+    };
+
+    Some(action(
+        "Extract to Fragment",
+        CodeActionKind::RefactorExtract,
+        vec![
+            removal,
+            edits::add_child(map, root, &fragment, &ChildPosition::Last),
+        ],
+    ))
+}
+
+/// Move a block from one indentation to another; its first line is unindented
+fn reindent(text: &str, from: &str, to: &str, newline: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = if i == 0 {
+                line
+            } else {
+                line.strip_prefix(from).unwrap_or(line.trim_start())
+            };
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", to, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(newline)
+}
+
+/// Offer to harvest a folder named by the attribute under the cursor
+fn harvest_action(map: &SourceMap, node: Node, offset: usize) -> Option<CodeAction> {
+    let attribute = node
+        .attributes()
+        .find(|a| a.range_value().start <= offset && offset <= a.range_value().end)?;
+    let value = attribute.value();
+    // Preprocessor variables and bind paths can't be resolved here
+    if value.is_empty() || value.contains(['$', '!', '[']) {
+        return None;
+    }
+    let document = map.path();
+    let folder = document.parent().unwrap_or(Path::new("")).join(value);
+    if !folder.is_dir() {
+        return None;
+    }
+    let directory = node
+        .ancestors()
+        .find(|n| DIRECTORY_ELEMENTS.contains(&n.tag_name().name()))
+        .and_then(|n| n.attribute("Id"))
+        .unwrap_or(DEFAULT_DIRECTORY);
+
+    let title = format!("Harvest folder '{}' into a new Fragment", value);
+    Some(CodeAction {
+        title: title.clone(),
+        kind: CodeActionKind::Refactor,
+        edits: Vec::new(),
+        command: Some(Command {
+            title,
+            command: HARVEST_FOLDER.to_string(),
+            arguments: vec![
+                Value::from(document.to_string_lossy().into_owned()),
+                Value::from(folder.to_string_lossy().into_owned()),
+                Value::from(directory),
+            ],
+        }),
+        is_preferred: false,
+    })
+}
+
+/// `[document, folder, directory?]`; paths may also be `file:` URIs
+fn harvest_folder(
+    arguments: &[Value],
+    open_text: &dyn Fn(&Path) -> Option<String>,
+) -> Result<Vec<FileEdit>, String> {
+    let argument = |i: usize| arguments.get(i).and_then(Value::as_str);
+    let path_argument = |i: usize| {
+        let value = argument(i)?;
+        match Url::parse(value) {
+            Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+            _ => Some(PathBuf::from(value)),
+        }
+    };
+    let usage = || format!("{} expects a document and a folder", HARVEST_FOLDER);
+    let document = path_argument(0).ok_or_else(usage)?;
+    let folder = path_argument(1).ok_or_else(usage)?;
+    let directory = argument(2).unwrap_or(DEFAULT_DIRECTORY);
+
+    let source = match open_text(&document) {
+        Some(text) => text,
+        None => std::fs::read_to_string(&document)
+            .map_err(|e| format!("Failed to read {}: {}", document.display(), e))?,
+    };
+    let doc = Document::parse(&source).map_err(|e| format!("XML parse error: {}", e))?;
+    let map = SourceMap::new(&document, &source);
+
+    let name: String = folder
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect();
+    let options = wix_scan::ScanOptions {
+        component_group: format!(
+            "{}Components",
+            if name.is_empty() { "Harvested" } else { &name }
+        ),
+        directory_ref: directory.to_string(),
+        ..Default::default()
+    };
+    let result = wix_scan::Scanner::new(options)
+        .scan(&folder)
+        .map_err(|e| e.to_string())?;
+
+    // Keep the fragments, re-indented under this document's root
+    let wxs = result.to_wxs();
+    let start = wxs
+        .find("<Fragment>")
+        .ok_or("wix-scan produced no fragment")?;
+    let end = wxs
+        .rfind("</Fragment>")
+        .ok_or("wix-scan produced no fragment")?
+        + "</Fragment>".len();
+    let fragments = reindent(
+        &wxs[start..end],
+        "  ",
+        &edits::child_indent(&map, doc.root_element()),
+        map.newline(),
+    );
+    Ok(vec![edits::add_child(
+        &map,
+        doc.root_element(),
+        fragments.trim_start(),
+        &ChildPosition::Last,
+    )])
+}
+
+/// Put attributes in wix-data priority order, Id first without data
+fn sort_attributes(
+    map: &SourceMap,
+    doc: &Document,
+    ordering: Option<&WixData>,
+) -> Option<CodeAction> {
+    let source = map.text();
+    let mut changes = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_element()) {
+        let attributes: Vec<_> = node.attributes().collect();
+        if attributes.len() < 2 {
+            continue;
+        }
+        // Namespace declarations between attributes aren't moved
+        let separators: Vec<&str> = attributes
+            .windows(2)
+            .map(|pair| &source[pair[0].range().end..pair[1].range().start])
+            .collect();
+        if separators.iter().any(|s| !s.trim().is_empty()) {
+            continue;
+        }
+
+        let mut sorted: Vec<(String, String)> = attributes
+            .iter()
+            .map(|a| (a.name().to_string(), source[a.range()].to_string()))
+            .collect();
+        wix_fmt::sort_attributes(&mut sorted, node.tag_name().name(), ordering);
+        if sorted
+            .iter()
+            .zip(&attributes)
+            .all(|((name, _), a)| name == a.name())
+        {
+            continue;
+        }
+
+        let mut text = sorted[0].1.clone();
+        for (separator, (_, attribute)) in separators.iter().zip(&sorted[1..]) {
+            text.push_str(separator);
+            text.push_str(attribute);
+        }
+        let span = attributes[0].range().start..attributes[attributes.len() - 1].range().end;
+        changes.push(map.edit(span.start, span.end, text));
+    }
+    (!changes.is_empty()).then(|| action("Sort attributes", CodeActionKind::Source, changes))
+}
+
+/// Put child elements in wix-data order, throughout the document
+fn organize_children(map: &SourceMap, doc: &Document, ordering: &WixData) -> Option<CodeAction> {
+    let source = map.text();
+    let root = doc.root_element();
+    let organized = organized(source, root, ordering);
+    if organized == source[root.range()] {
+        return None;
+    }
+    Some(action(
+        "Organize children",
+        CodeActionKind::Source,
+        vec![map.edit(root.range().start, root.range().end, organized)],
+    ))
+}
+
+/// An element's text with its children, recursively, in canonical order
+///
+/// Children trade places; whitespace and comments between them stay put.
+/// Processing instructions (`<?if?>`, `<?foreach?>`, ...) fence the sort:
+/// children only move within the run between two of them.
+fn organized(source: &str, node: Node, ordering: &WixData) -> String {
+    let children: Vec<Node> = node.children().filter(|c| c.is_element()).collect();
+    let mut order: Vec<(String, usize)> = Vec::with_capacity(children.len());
+    let mut run: Vec<(String, usize)> = Vec::new();
+    for child in node.children() {
+        if child.is_element() {
+            run.push((child.tag_name().name().to_string(), order.len() + run.len()));
+        } else if child.is_pi() {
+            wix_fmt::sort_children(&mut run, node.tag_name().name(), ordering);
+            order.append(&mut run);
+        }
+    }
+    wix_fmt::sort_children(&mut run, node.tag_name().name(), ordering);
+    order.append(&mut run);
+
+    let mut text = String::new();
+    let mut position = node.range().start;
+    for (slot, (_, index)) in children.iter().zip(&order) {
+        text.push_str(&source[position..slot.range().start]);
+        text.push_str(&organized(source, children[*index], ordering));
+        position = slot.range().end;
+    }
+    text.push_str(&source[position..node.range().end]);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::wix::edits::apply_edits;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    const PATH: &str = "product.wxs";
+
+    fn actions_at(
+        source: &str,
+        range: (u32, u32, u32, u32),
+        references: Option<&WorkspaceReferences>,
+        ordering: Option<&WixData>,
+    ) -> Vec<CodeAction> {
+        let parsed = ParsedDocument::new(source);
+        let cx = ActionContext {
+            parsed: &parsed,
+            path: Path::new(PATH),
+            range: SymbolRange {
+                start_line: range.0,
+                start_col: range.1,
+                end_line: range.2,
+                end_col: range.3,
+            },
+            references,
+            ordering,
+        };
+        code_actions(&cx, None)
+    }
+
+    fn find<'a>(actions: &'a [CodeAction], title: &str) -> &'a CodeAction {
+        actions
+            .iter()
+            .find(|a| a.title == title)
+            .unwrap_or_else(|| panic!("no '{}' in {:?}", title, actions))
+    }
+
+    #[test]
+    fn test_guid_actions() {
+        let source = "<Wix>\n  <Component Id=\"Core\" Guid=\"12345678-1234-1234-1234-123456789012\" />\n</Wix>";
+        let actions = actions_at(source, (1, 30, 1, 30), None, None);
+
+        let convert = find(&actions, "Convert Guid to '*'");
+        assert!(apply_edits(source, &convert.edits).contains("Guid=\"*\""));
+
+        let generate = find(&actions, "Generate GUID for Guid");
+        let generated = apply_edits(source, &generate.edits);
+        let guid = generated.split('"').nth(3).unwrap();
+        assert!(uuid::Uuid::parse_str(guid).is_ok());
+        assert_eq!(guid, guid.to_uppercase());
+
+        // Nothing outside the start tag
+        assert!(actions_at(source, (2, 1, 2, 1), None, None).is_empty());
+    }
+
+    #[test]
+    fn test_add_component_ref() {
+        let temp = TempDir::new().unwrap();
+        let product = "<Wix>\n  <Package>\n    <Feature Id=\"Main\">\n      <ComponentRef Id=\"Core\" />\n    </Feature>\n  </Package>\n</Wix>";
+        std::fs::write(temp.path().join("product.wxs"), product).unwrap();
+        let components = "<Wix>\n  <Fragment>\n    <Component Id=\"Core\" />\n    <Component Id=\"Extra\" />\n  </Fragment>\n</Wix>";
+        std::fs::write(temp.path().join("components.wxs"), components).unwrap();
+        let references = WorkspaceReferences::for_workspace(temp.path());
+
+        let parsed = ParsedDocument::new(components);
+        let path = temp.path().join("components.wxs");
+        let at_line = |line| ActionContext {
+            parsed: &parsed,
+            path: &path,
+            range: SymbolRange {
+                start_line: line,
+                start_col: 8,
+                end_line: line,
+                end_col: 8,
+            },
+            references: Some(&references),
+            ordering: None,
+        };
+
+        let actions = code_actions(&at_line(3), None);
+        let add = find(&actions, "Add ComponentRef to feature 'Main'");
+        assert_eq!(add.edits[0].path, temp.path().join("product.wxs"));
+        assert!(apply_edits(product, &add.edits).contains(
+            "<ComponentRef Id=\"Core\" />\n      <ComponentRef Id=\"Extra\" />\n    </Feature>"
+        ));
+
+        // Core is already referenced
+        let actions = code_actions(&at_line(2), None);
+        assert!(actions
+            .iter()
+            .all(|a| !a.title.starts_with("Add ComponentRef")));
+    }
+
+    #[test]
+    fn test_extract_fragment() {
+        let source = "<Wix>\n  <Package>\n    <Property Id=\"A\" Value=\"1\" />\n    <Property Id=\"B\" Value=\"2\" />\n    <MajorUpgrade />\n  </Package>\n</Wix>";
+        let actions = actions_at(source, (2, 0, 4, 0), None, None);
+        let extract = find(&actions, "Extract to Fragment");
+        assert_eq!(extract.kind, CodeActionKind::RefactorExtract);
+        assert_eq!(
+            apply_edits(source, &extract.edits),
+            "<Wix>\n  <Package>\n    <PropertyRef Id=\"A\" />\n    <PropertyRef Id=\"B\" />\n    <MajorUpgrade />\n  </Package>\n  <Fragment>\n    <Property Id=\"A\" Value=\"1\" />\n    <Property Id=\"B\" Value=\"2\" />\n  </Fragment>\n</Wix>"
+        );
+
+        // A selection cutting an element in half can't be extracted
+        let actions = actions_at(source, (2, 0, 3, 10), None, None);
+        assert!(actions.iter().all(|a| a.title != "Extract to Fragment"));
+    }
+
+    #[test]
+    fn test_extract_components_from_directory() {
+        let source = "<Wix>\n  <Package>\n    <Directory Id=\"App\">\n      <Component Id=\"Core\" />\n    </Directory>\n  </Package>\n</Wix>";
+        let actions = actions_at(source, (3, 6, 3, 29), None, None);
+        let extract = find(&actions, "Extract to Fragment");
+        assert_eq!(
+            apply_edits(source, &extract.edits),
+            "<Wix>\n  <Package>\n    <Directory Id=\"App\">\n    </Directory>\n  </Package>\n  <Fragment>\n    <DirectoryRef Id=\"App\">\n      <Component Id=\"Core\" />\n    </DirectoryRef>\n  </Fragment>\n</Wix>"
+        );
+    }
+
+    #[test]
+    fn test_harvest_folder() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("bin")).unwrap();
+        std::fs::write(temp.path().join("bin").join("app.exe"), "").unwrap();
+        let document = temp.path().join("product.wxs");
+        let source =
+            "<Wix>\n  <Package>\n    <Directory Id=\"App\" Name=\"bin\" />\n  </Package>\n</Wix>";
+        std::fs::write(&document, source).unwrap();
+
+        let parsed = ParsedDocument::new(source);
+        let cx = ActionContext {
+            parsed: &parsed,
+            path: &document,
+            range: SymbolRange {
+                start_line: 2,
+                start_col: 31,
+                end_line: 2,
+                end_col: 31,
+            },
+            references: None,
+            ordering: None,
+        };
+        let actions = code_actions(&cx, Some(&[CodeActionKind::Refactor]));
+        let harvest = find(&actions, "Harvest folder 'bin' into a new Fragment");
+        let command = harvest.command.as_ref().unwrap();
+        assert_eq!(command.arguments[2], "App");
+
+        let edits = execute_command(&command.command, &command.arguments, &|_| None).unwrap();
+        let harvested = apply_edits(source, &edits);
+        assert!(harvested
+            .contains("  </Package>\n  <Fragment>\n    <ComponentGroup Id=\"binComponents\">"));
+        assert!(harvested.contains("<DirectoryRef Id=\"App\">"));
+        assert!(harvested.contains("app.exe"));
+        assert!(harvested.ends_with("</Fragment>\n</Wix>"));
+
+        assert!(execute_command(HARVEST_FOLDER, &[], &|_| None).is_err());
+        assert!(execute_command("wix.unknown", &[], &|_| None).is_err());
+    }
+
+    #[test]
+    fn test_source_actions() {
+        let source = "<Wix>\n  <Package Version=\"1.0\" Name=\"App\">\n    <Feature Id=\"Main\" />\n    <Directory Id=\"App\" />\n  </Package>\n</Wix>";
+        let ordering = WixData {
+            child_order: HashMap::from([(
+                "Package".to_string(),
+                vec!["Directory".to_string(), "Feature".to_string()],
+            )]),
+            attr_priority: HashMap::from([(
+                "Package".to_string(),
+                vec!["Name".to_string(), "Version".to_string()],
+            )]),
+            ..Default::default()
+        };
+        let actions = actions_at(source, (0, 0, 0, 0), None, Some(&ordering));
+
+        let sort = find(&actions, "Sort attributes");
+        assert!(apply_edits(source, &sort.edits).contains("<Package Name=\"App\" Version=\"1.0\">"));
+
+        let organize = find(&actions, "Organize children");
+        assert!(apply_edits(source, &organize.edits)
+            .contains("<Directory Id=\"App\" />\n    <Feature Id=\"Main\" />"));
+
+        // Only requested kinds are computed
+        let parsed = ParsedDocument::new(source);
+        let cx = ActionContext {
+            parsed: &parsed,
+            path: Path::new(PATH),
+            range: SymbolRange {
+                start_line: 0,
+                start_col: 0,
+                end_line: 0,
+                end_col: 0,
+            },
+            references: None,
+            ordering: Some(&ordering),
+        };
+        assert!(code_actions(&cx, Some(&[CodeActionKind::QuickFix])).is_empty());
+    }
+
+    #[test]
+    fn test_organize_children_keeps_preprocessor_blocks() {
+        let source = "<Wix>
+  <Package>
+    <Feature Id=\"Main\" />
+    <?if $(var.Debug) = 1?>
+    <Feature Id=\"Symbols\" />
+    <Directory Id=\"Debug\" />
+    <?else?>
+    <Directory Id=\"Release\" />
+    <?endif?>
+  </Package>
+</Wix>";
+        let ordering = WixData {
+            child_order: HashMap::from([(
+                "Package".to_string(),
+                vec!["Directory".to_string(), "Feature".to_string()],
+            )]),
+            ..Default::default()
+        };
+        let actions = actions_at(source, (0, 0, 0, 0), None, Some(&ordering));
+
+        let organize = find(&actions, "Organize children");
+        assert_eq!(
+            apply_edits(source, &organize.edits),
+            "<Wix>\n  <Package>\n    <Feature Id=\"Main\" />\n    <?if $(var.Debug) = 1?>\n    <Directory Id=\"Debug\" />\n    <Feature Id=\"Symbols\" />\n    <?else?>\n    <Directory Id=\"Release\" />\n    <?endif?>\n  </Package>\n</Wix>"
+        );
+    }
+}
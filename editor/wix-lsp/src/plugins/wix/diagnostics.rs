@@ -0,0 +1,420 @@
+//! Diagnostics from the winter linter and the wix-analyzer analyzers
+//!
+//! Both tools are configured from the workspace: winter from `.winter.yaml`
+//! (or its `.linterrc` names), wix-analyzer from `.wixanalyzer.json`. A
+//! workspace symbol index is kept up to date with every diagnosed buffer so
+//! cross-file reference errors reflect unsaved edits.
+
+use super::edits::{self, SourceMap};
+use super::parse::ParsedDocument;
+use crate::plugins::traits::{
+    Diagnostic, DiagnosticFix, DiagnosticSeverity, DiagnosticTag, FileEdit, RelatedInformation,
+    SymbolRange,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use wix_analyzer::{SymbolIndex, WixDocument};
+
+/// Winter configuration files, in lookup order
+const WINTER_CONFIG_NAMES: &[&str] = &[
+    ".winter.yaml",
+    ".winter.yml",
+    ".linterrc.yaml",
+    ".linterrc.yml",
+    ".linterrc.json",
+    "linter.yaml",
+    "linter.yml",
+    "linter.json",
+];
+
+/// Rule metadata not carried on winter diagnostics
+struct RuleInfo {
+    docs: Option<String>,
+    tags: Vec<DiagnosticTag>,
+}
+
+/// Runs both linters over a document
+pub struct WorkspaceDiagnostics {
+    linter: winter::Engine,
+    rules: HashMap<String, RuleInfo>,
+    analyzer_config: wix_analyzer::Config,
+    index: RwLock<SymbolIndex>,
+}
+
+impl WorkspaceDiagnostics {
+    /// Default configuration and an empty index
+    pub fn new() -> Self {
+        Self::with_configs(winter::Config::default(), wix_analyzer::Config::default())
+    }
+
+    /// Load both configurations from `root` and index its source files
+    pub fn for_workspace(root: &Path) -> Self {
+        let winter_config = WINTER_CONFIG_NAMES
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.exists())
+            .and_then(|path| match winter::Config::load(&path) {
+                Ok(config) => Some(config),
+                Err(e) => {
+                    tracing::warn!("Failed to load {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let analyzer_config = wix_analyzer::Config::find_and_load(root).unwrap_or_default();
+
+        let diagnostics = Self::with_configs(winter_config, analyzer_config);
+        if let Ok(mut index) = diagnostics.index.write() {
+            match index.index_directory(root) {
+                Ok(count) => tracing::info!("Indexed {} WiX files", count),
+                Err(e) => tracing::warn!("Failed to index workspace: {}", e),
+            }
+        }
+        diagnostics
+    }
+
+    fn with_configs(
+        winter_config: winter::Config,
+        mut analyzer_config: wix_analyzer::Config,
+    ) -> Self {
+        // Dead code needs the whole project and is left to the CLI
+        analyzer_config.analyzers.dead_code = false;
+
+        let mut linter = winter::Engine::new(winter_config);
+        linter.register_plugin(Arc::new(winter::plugins::wix::WixPlugin::new()));
+        let rules = linter
+            .rules()
+            .into_iter()
+            .map(|rule| {
+                let info = RuleInfo {
+                    docs: rule.docs.clone(),
+                    tags: tags_for(&rule.tags),
+                };
+                (rule.id.clone(), info)
+            })
+            .collect();
+
+        Self {
+            linter,
+            rules,
+            analyzer_config,
+            index: RwLock::new(SymbolIndex::new()),
+        }
+    }
+
+    /// Lint and analyze a buffer
+    pub fn diagnose(&self, source: &str, path: &Path) -> Vec<Diagnostic> {
+        self.diagnose_parsed(&ParsedDocument::new(source), path)
+    }
+
+    /// Lint and analyze a buffer that was already parsed
+    pub fn diagnose_parsed(&self, parsed: &ParsedDocument, path: &Path) -> Vec<Diagnostic> {
+        let source = parsed.text();
+        let lines: Vec<&str> = source.lines().collect();
+        let mut diagnostics: Vec<Diagnostic> = self
+            .linter
+            .lint_source(path, source)
+            .diagnostics
+            .iter()
+            .map(|d| self.convert_winter(d, path))
+            .collect();
+
+        if let Ok(mut index) = self.index.write() {
+            // On a parse error the last good symbols are kept
+            let _ = index.reindex_source(source, path);
+        }
+        if let (Ok(tree), Ok(index)) = (parsed.tree(), self.index.read()) {
+            let doc = WixDocument::from_document(source, tree, path);
+            let result = wix_analyzer::analyze_with_source(
+                &doc,
+                &index,
+                &self.analyzer_config,
+                Some(source),
+            );
+            let map = SourceMap::new(path, source);
+            diagnostics.extend(result.diagnostics.iter().map(|d| {
+                let mut diagnostic = convert_analyzer(d, &lines);
+                diagnostic.fixes = analyzer_fixes(d, &map, tree);
+                diagnostic
+            }));
+        }
+
+        diagnostics
+    }
+
+    /// Re-read a file into the symbol index, dropping it if it was deleted
+    pub fn reload(&self, path: &Path) {
+        let Ok(mut index) = self.index.write() else {
+            return;
+        };
+        match std::fs::read_to_string(path) {
+            Ok(source) => {
+                let _ = index.reindex_source(&source, path);
+            }
+            Err(_) => index.remove_file(path),
+        }
+    }
+
+    fn convert_winter(&self, diag: &winter::Diagnostic, path: &Path) -> Diagnostic {
+        let rule = self.rules.get(&diag.rule_id);
+        Diagnostic {
+            rule_id: diag.rule_id.clone(),
+            message: diag.message.clone(),
+            severity: match diag.severity {
+                winter::Severity::Error => DiagnosticSeverity::Error,
+                winter::Severity::Warning => DiagnosticSeverity::Warning,
+                winter::Severity::Info => DiagnosticSeverity::Info,
+            },
+            line: diag.location.line as u32,
+            column: diag.location.column as u32,
+            length: diag.location.length as u32,
+            help: diag.help.clone(),
+            source: Some("winter".to_string()),
+            code_description: rule.and_then(|r| r.docs.clone()),
+            related: diag
+                .related
+                .iter()
+                .map(|r| RelatedInformation {
+                    path: r.location.file.clone(),
+                    line: r.location.line as u32,
+                    column: r.location.column as u32,
+                    length: r.location.length as u32,
+                    message: r.message.clone(),
+                })
+                .collect(),
+            tags: rule.map(|r| r.tags.clone()).unwrap_or_default(),
+            fixes: winter_fixes(diag, path),
+        }
+    }
+}
+
+impl Default for WorkspaceDiagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn convert_analyzer(diag: &wix_analyzer::Diagnostic, lines: &[&str]) -> Diagnostic {
+    use wix_analyzer::Severity;
+
+    let (line, column, length) = span(&diag.location.range, |line| {
+        lines.get(line.wrapping_sub(1)).map(|l| l.chars().count())
+    });
+    let mut tags = tags_for(&diag.tags);
+    if diag.rule_id.starts_with("DEAD-") && !tags.contains(&DiagnosticTag::Unnecessary) {
+        tags.push(DiagnosticTag::Unnecessary);
+    }
+
+    Diagnostic {
+        rule_id: diag.rule_id.clone(),
+        message: diag.message.clone(),
+        severity: match diag.severity {
+            Severity::Blocker | Severity::High => DiagnosticSeverity::Error,
+            Severity::Medium => DiagnosticSeverity::Warning,
+            Severity::Low => DiagnosticSeverity::Info,
+            Severity::Info => DiagnosticSeverity::Hint,
+        },
+        line,
+        column,
+        length,
+        help: diag.help.clone(),
+        source: Some("wix-analyzer".to_string()),
+        code_description: diag.doc_url.clone(),
+        related: diag
+            .related
+            .iter()
+            .map(|r| {
+                // Other files aren't loaded, so spans stop at the start line
+                let (line, column, length) = span(&r.location.range, |_| None);
+                RelatedInformation {
+                    path: PathBuf::from(&r.location.file),
+                    line,
+                    column,
+                    length,
+                    message: r.message.clone(),
+                }
+            })
+            .collect(),
+        tags,
+        fixes: Vec::new(),
+    }
+}
+
+/// Quick fix for a winter diagnostic; winter fixes rewrite a single line
+fn winter_fixes(diag: &winter::Diagnostic, path: &Path) -> Vec<DiagnosticFix> {
+    let (Some(fix), Some(edit)) = (&diag.fix, winter::FixEdit::from_diagnostic(diag)) else {
+        return Vec::new();
+    };
+    let line = (edit.line as u32).saturating_sub(1);
+    vec![DiagnosticFix {
+        title: fix.description.clone(),
+        edits: vec![FileEdit {
+            path: path.to_path_buf(),
+            range: SymbolRange {
+                start_line: line,
+                start_col: (edit.start_column as u32).saturating_sub(1),
+                end_line: line,
+                end_col: (edit.end_column as u32).saturating_sub(1),
+            },
+            new_text: edit.new_text,
+        }],
+        is_preferred: fix.is_safe(),
+    }]
+}
+
+/// Quick fix for an analyzer diagnostic, if its fix still matches the tree
+fn analyzer_fixes(
+    diag: &wix_analyzer::Diagnostic,
+    map: &SourceMap,
+    tree: &roxmltree::Document,
+) -> Vec<DiagnosticFix> {
+    let Some(fix) = &diag.fix else {
+        return Vec::new();
+    };
+    match edits::analyzer_fix(map, tree, &fix.action) {
+        Some(edits) => vec![DiagnosticFix {
+            title: fix.description.clone(),
+            edits,
+            is_preferred: false,
+        }],
+        None => Vec::new(),
+    }
+}
+
+/// Clip an analyzer range to its first line; ranges often cover a whole element
+fn span(range: &wix_analyzer::Range, line_len: impl Fn(usize) -> Option<usize>) -> (u32, u32, u32) {
+    let start = &range.start;
+    let length = if range.end.line == start.line {
+        range.end.character.saturating_sub(start.character)
+    } else {
+        line_len(start.line)
+            .map(|len| (len + 1).saturating_sub(start.character))
+            .unwrap_or(0)
+    };
+    (start.line as u32, start.character as u32, length as u32)
+}
+
+/// Map rule tags onto LSP rendering hints
+fn tags_for(tags: &[String]) -> Vec<DiagnosticTag> {
+    let mut result = Vec::new();
+    for tag in tags {
+        let tag = match tag.to_lowercase().as_str() {
+            "deprecated" => DiagnosticTag::Deprecated,
+            "unused" | "unnecessary" | "redundant" => DiagnosticTag::Unnecessary,
+            _ => continue,
+        };
+        if !result.contains(&tag) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_diagnostics_from_both_tools() {
+        let diagnostics = WorkspaceDiagnostics::new();
+        let source = "<Wix>\n  <Product Id=\"*\" Name=\"App\" />\n</Wix>";
+        let result = diagnostics.diagnose(source, Path::new("product.wxs"));
+
+        let winter = result
+            .iter()
+            .find(|d| d.rule_id == "deprecated-product-element")
+            .expect("winter rule should fire");
+        assert_eq!(winter.source.as_deref(), Some("winter"));
+        assert_eq!(winter.tags, vec![DiagnosticTag::Deprecated]);
+
+        let analyzer = result
+            .iter()
+            .find(|d| d.rule_id == "BP-IDIOM-003")
+            .expect("analyzer rule should fire");
+        assert_eq!(analyzer.source.as_deref(), Some("wix-analyzer"));
+        assert_eq!(analyzer.tags, vec![DiagnosticTag::Deprecated]);
+        assert_eq!((analyzer.line, analyzer.column), (2, 3));
+    }
+
+    #[test]
+    fn test_workspace_index_and_config() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("defs.wxs"),
+            "<Wix><Fragment><Component Id=\"Core\" Guid=\"*\" /></Fragment></Wix>",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join(".winter.yaml"),
+            "rules:\n  disabled:\n    - deprecated-product-element\n",
+        )
+        .unwrap();
+        let diagnostics = WorkspaceDiagnostics::for_workspace(temp.path());
+        let path = temp.path().join("main.wxs");
+
+        let resolved = diagnostics.diagnose(
+            "<Wix><Fragment><ComponentRef Id=\"Core\" /></Fragment></Wix>",
+            &path,
+        );
+        assert!(resolved.iter().all(|d| d.rule_id != "VAL-REF-001"));
+
+        let unresolved = diagnostics.diagnose(
+            "<Wix><Fragment><ComponentRef Id=\"Missing\" /></Fragment></Wix>",
+            &path,
+        );
+        assert!(unresolved.iter().any(|d| d.rule_id == "VAL-REF-001"));
+
+        let product = diagnostics.diagnose("<Wix><Product Id=\"*\" /></Wix>", &path);
+        assert!(product
+            .iter()
+            .all(|d| d.rule_id != "deprecated-product-element"));
+    }
+
+    #[test]
+    fn test_quick_fixes() {
+        let diagnostics = WorkspaceDiagnostics::new();
+        let source = "<Wix>\n  <Fragment>\n    <Component Id=\"Core\" Guid=\"12345678-1234-1234-1234-123456789012\" />\n    <Component Id=\"Other\" />\n  </Fragment>\n</Wix>";
+        let result = diagnostics.diagnose(source, Path::new("product.wxs"));
+
+        let hardcoded = result
+            .iter()
+            .find(|d| d.rule_id == "BP-IDIOM-002")
+            .expect("analyzer rule should fire");
+        let fix = &hardcoded.fixes[0];
+        assert_eq!(fix.title, "Use auto-generated GUID");
+        assert!(
+            edits::apply_edits(source, &fix.edits).contains("<Component Id=\"Core\" Guid=\"*\" />")
+        );
+
+        let missing = result
+            .iter()
+            .find(|d| d.rule_id == "component-requires-guid")
+            .expect("winter rule should fire");
+        let fix = &missing.fixes[0];
+        assert!(fix.is_preferred);
+        assert_eq!(fix.edits[0].range.start_line, 3);
+        let fixed = edits::apply_edits(source, &fix.edits);
+        assert!(fixed.contains("<Component Id=\"Other\"  Guid=\"*\"/>"));
+    }
+
+    #[test]
+    fn test_span_clips_to_first_line() {
+        let range = wix_analyzer::Range::new(
+            wix_analyzer::Position::new(2, 3),
+            wix_analyzer::Position::new(4, 10),
+        );
+        assert_eq!(span(&range, |_| Some(20)), (2, 3, 18));
+        assert_eq!(span(&range, |_| None), (2, 3, 0));
+    }
+
+    #[test]
+    fn test_tags_for() {
+        let tags = ["Deprecated", "security", "unused", "redundant"].map(String::from);
+        assert_eq!(
+            tags_for(&tags),
+            vec![DiagnosticTag::Deprecated, DiagnosticTag::Unnecessary]
+        );
+    }
+}
@@ -0,0 +1,486 @@
+//! Text edits on WiX sources
+//!
+//! Fixes and refactorings describe changes in terms of elements and
+//! attributes. These helpers turn them into character-range edits that keep
+//! the indentation and line endings of the surrounding document.
+
+use crate::plugins::traits::{FileEdit, SymbolRange};
+use roxmltree::{Document, Node};
+use std::path::Path;
+use wix_analyzer::{FixAction, InsertPosition};
+
+/// Indentation used when a document gives no hint
+const DEFAULT_INDENT: &str = "  ";
+
+/// A document's text with byte offsets mapped to 0-based lines and columns
+pub struct SourceMap<'a> {
+    path: &'a Path,
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(path: &'a Path, text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            path,
+            text,
+            line_starts,
+        }
+    }
+
+    pub fn path(&self) -> &'a Path {
+        self.path
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// 0-based line and character column of a byte offset
+    pub fn position(&self, offset: usize) -> (u32, u32) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let column = self.text[self.line_starts[line]..offset].chars().count();
+        (line as u32, column as u32)
+    }
+
+    /// Byte offset of a 0-based line and character column, clamped to the line
+    pub fn offset(&self, line: u32, column: u32) -> usize {
+        let Some(&start) = self.line_starts.get(line as usize) else {
+            return self.text.len();
+        };
+        let end = self
+            .line_starts
+            .get(line as usize + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        self.text[start..end]
+            .char_indices()
+            .nth(column as usize)
+            .map_or(end, |(i, _)| start + i)
+    }
+
+    /// Replace the text between two byte offsets
+    pub fn edit(&self, start: usize, end: usize, new_text: impl Into<String>) -> FileEdit {
+        let (start_line, start_col) = self.position(start);
+        let (end_line, end_col) = self.position(end);
+        FileEdit {
+            path: self.path.to_path_buf(),
+            range: SymbolRange {
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+            },
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Line break used by the document
+    pub fn newline(&self) -> &'static str {
+        if self.text.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        }
+    }
+
+    /// Leading whitespace of the line containing `offset`
+    pub fn indent_at(&self, offset: usize) -> &'a str {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let rest = &self.text[start..];
+        &rest[..rest.len() - rest.trim_start_matches([' ', '\t']).len()]
+    }
+
+    /// Whether only whitespace precedes `offset` on its line
+    pub fn starts_line(&self, offset: usize) -> bool {
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        self.text[line_start..offset].trim().is_empty()
+    }
+
+    /// Widen a span to whole lines when nothing else shares them
+    pub fn whole_lines(&self, start: usize, end: usize) -> (usize, usize) {
+        let line_start = self.text[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.text[end..]
+            .find('\n')
+            .map_or(self.text.len(), |i| end + i + 1);
+        if self.text[line_start..start].trim().is_empty()
+            && self.text[end..line_end].trim().is_empty()
+        {
+            (line_start, line_end)
+        } else {
+            (start, end)
+        }
+    }
+}
+
+/// Where a new child element goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChildPosition {
+    First,
+    Last,
+    Before(String),
+    After(String),
+}
+
+/// Element whose start tag begins at a byte offset
+pub fn element_starting_at<'a, 'input>(
+    doc: &'a Document<'input>,
+    offset: usize,
+) -> Option<Node<'a, 'input>> {
+    doc.descendants()
+        .find(|n| n.is_element() && n.range().start == offset)
+}
+
+/// Innermost element containing a byte offset
+pub fn element_at<'a, 'input>(
+    doc: &'a Document<'input>,
+    offset: usize,
+) -> Option<Node<'a, 'input>> {
+    let mut node = doc.root_element();
+    if !node.range().contains(&offset) {
+        return None;
+    }
+    while let Some(child) = node
+        .children()
+        .find(|c| c.is_element() && c.range().contains(&offset))
+    {
+        node = child;
+    }
+    Some(node)
+}
+
+/// Offset just past the `>` of an element's start tag
+pub fn start_tag_end(source: &str, node: Node) -> usize {
+    let start = node.range().start;
+    let mut quote = None;
+    for (i, c) in source[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return start + i + 1,
+            _ => {}
+        }
+    }
+    node.range().end
+}
+
+/// Whether an element is written as `<Name ... />`
+pub fn is_self_closing(source: &str, node: Node) -> bool {
+    source[..start_tag_end(source, node)].ends_with("/>")
+}
+
+/// Element name as written, prefix included
+pub fn qualified_name<'a>(source: &'a str, node: Node) -> &'a str {
+    let rest = &source[node.range().start + 1..];
+    let len = rest
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(rest.len());
+    &rest[..len]
+}
+
+/// Indentation for children of an element
+pub fn child_indent(map: &SourceMap, node: Node) -> String {
+    if let Some(child) = node
+        .children()
+        .find(|c| c.is_element() && map.starts_line(c.range().start))
+    {
+        return map.indent_at(child.range().start).to_string();
+    }
+    let indent = map.indent_at(node.range().start);
+    let unit = if indent.contains('\t') {
+        "\t"
+    } else {
+        DEFAULT_INDENT
+    };
+    format!("{}{}", indent, unit)
+}
+
+/// Add an attribute at the end of an element's start tag
+pub fn add_attribute(map: &SourceMap, node: Node, name: &str, value: &str) -> FileEdit {
+    let source = map.text();
+    let tag_end = start_tag_end(source, node);
+    let before_close = if is_self_closing(source, node) {
+        tag_end - 2
+    } else {
+        tag_end - 1
+    };
+    let at = source[..before_close].trim_end().len();
+    map.edit(at, at, format!(" {}=\"{}\"", name, escape_attribute(value)))
+}
+
+/// Replace an attribute's value, adding the attribute if it's missing
+pub fn set_attribute(map: &SourceMap, node: Node, name: &str, value: &str) -> FileEdit {
+    match node.attributes().find(|a| a.name() == name) {
+        Some(attribute) => {
+            let range = attribute.range_value();
+            map.edit(range.start, range.end, escape_attribute(value))
+        }
+        None => add_attribute(map, node, name, value),
+    }
+}
+
+/// Remove an attribute along with the whitespace before it
+pub fn remove_attribute(map: &SourceMap, node: Node, name: &str) -> Option<FileEdit> {
+    let attribute = node.attributes().find(|a| a.name() == name)?;
+    let range = attribute.range();
+    let start = map.text()[..range.start].trim_end().len();
+    Some(map.edit(start, range.end, ""))
+}
+
+/// Insert a child element on its own line
+pub fn add_child(map: &SourceMap, node: Node, element: &str, position: &ChildPosition) -> FileEdit {
+    let source = map.text();
+    let newline = map.newline();
+    let indent = map.indent_at(node.range().start);
+    let inner = child_indent(map, node);
+    let tag_end = start_tag_end(source, node);
+
+    if is_self_closing(source, node) {
+        let at = source[..tag_end - 2].trim_end().len();
+        return map.edit(
+            at,
+            tag_end,
+            format!(
+                ">{nl}{inner}{element}{nl}{indent}</{name}>",
+                nl = newline,
+                name = qualified_name(source, node),
+            ),
+        );
+    }
+
+    let children: Vec<Node> = node.children().filter(|c| c.is_element()).collect();
+    let named = |name: &str| children.iter().find(|c| c.tag_name().name() == name);
+    let after = |at: usize| map.edit(at, at, format!("{}{}{}", newline, inner, element));
+    match position {
+        ChildPosition::Before(name) => {
+            if let Some(child) = named(name) {
+                let at = child.range().start;
+                return map.edit(at, at, format!("{}{}{}", element, newline, inner));
+            }
+        }
+        ChildPosition::After(name) => {
+            if let Some(child) = named(name) {
+                return after(child.range().end);
+            }
+        }
+        ChildPosition::First if !children.is_empty() => return after(tag_end),
+        _ => {}
+    }
+
+    match children.last() {
+        Some(last) => after(last.range().end),
+        None => {
+            // Replace blank content so the closing tag lands on its own line
+            let close = source[..node.range().end].rfind("</").unwrap_or(tag_end);
+            let (start, end) = if source[tag_end..close].trim().is_empty() {
+                (tag_end, close)
+            } else {
+                (close, close)
+            };
+            map.edit(
+                start,
+                end,
+                format!("{nl}{inner}{element}{nl}{indent}", nl = newline),
+            )
+        }
+    }
+}
+
+/// Remove an element, and its lines if it's alone on them
+pub fn remove_element(map: &SourceMap, node: Node) -> FileEdit {
+    let range = node.range();
+    let (start, end) = map.whole_lines(range.start, range.end);
+    map.edit(start, end, "")
+}
+
+/// Edits carrying out a wix-analyzer fix
+///
+/// Analyzer fixes locate elements by their 1-based range. `None` means the
+/// fix no longer matches the tree.
+pub fn analyzer_fix(map: &SourceMap, doc: &Document, action: &FixAction) -> Option<Vec<FileEdit>> {
+    let offset = |position: &wix_analyzer::Position| {
+        map.offset(
+            (position.line as u32).saturating_sub(1),
+            (position.character as u32).saturating_sub(1),
+        )
+    };
+    let element = |range: &wix_analyzer::Range| element_starting_at(doc, offset(&range.start));
+
+    let edit = match action {
+        FixAction::AddAttribute { range, name, value } => {
+            add_attribute(map, element(range)?, name, value)
+        }
+        FixAction::RemoveAttribute { range, name } => remove_attribute(map, element(range)?, name)?,
+        FixAction::ReplaceAttribute {
+            range,
+            name,
+            new_value,
+        } => set_attribute(map, element(range)?, name, new_value),
+        FixAction::AddElement {
+            parent_range,
+            element: child,
+            position,
+        } => {
+            let position = match position {
+                InsertPosition::First => ChildPosition::First,
+                InsertPosition::Last => ChildPosition::Last,
+                InsertPosition::BeforeElement(name) => ChildPosition::Before(name.clone()),
+                InsertPosition::AfterElement(name) => ChildPosition::After(name.clone()),
+            };
+            add_child(map, element(parent_range)?, child, &position)
+        }
+        FixAction::RemoveElement { range } => remove_element(map, element(range)?),
+        FixAction::ReplaceText { range, new_text } => {
+            map.edit(offset(&range.start), offset(&range.end), new_text.clone())
+        }
+    };
+    Some(vec![edit])
+}
+
+/// Escape a value for a double-quoted attribute
+pub fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+/// Apply edits to a source, last first
+pub fn apply_edits(source: &str, edits: &[FileEdit]) -> String {
+    let path = Path::new("test.wxs");
+    let map = SourceMap::new(path, source);
+    let mut edits: Vec<&FileEdit> = edits.iter().collect();
+    edits.sort_by_key(|e| (e.range.start_line, e.range.start_col));
+    let mut result = source.to_string();
+    for edit in edits.into_iter().rev() {
+        let start = map.offset(edit.range.start_line, edit.range.start_col);
+        let end = map.offset(edit.range.end_line, edit.range.end_col);
+        result.replace_range(start..end, &edit.new_text);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_element<T>(source: &str, name: &str, f: impl Fn(&SourceMap, Node) -> T) -> T {
+        let doc = Document::parse(source).unwrap();
+        let map = SourceMap::new(Path::new("test.wxs"), source);
+        let node = doc
+            .descendants()
+            .find(|n| n.tag_name().name() == name)
+            .unwrap();
+        f(&map, node)
+    }
+
+    #[test]
+    fn test_source_map_positions() {
+        let source = "<Wix>\n  <Property Id=\"ä\" />\n</Wix>";
+        let map = SourceMap::new(Path::new("test.wxs"), source);
+        let offset = source.find("\" />").unwrap();
+        assert_eq!(map.position(offset), (1, 17));
+        assert_eq!(map.offset(1, 17), offset);
+        // Columns past the end clamp to the line
+        assert_eq!(map.offset(0, 40), 5);
+        assert_eq!(map.indent_at(offset), "  ");
+    }
+
+    #[test]
+    fn test_attribute_edits() {
+        let source = "<Wix>\n  <Component Id=\"Core\" Guid=\"ABC\" />\n</Wix>";
+
+        let edit = with_element(source, "Component", |map, node| {
+            set_attribute(map, node, "Guid", "*")
+        });
+        assert!(apply_edits(source, &[edit]).contains("<Component Id=\"Core\" Guid=\"*\" />"));
+
+        let edit = with_element(source, "Component", |map, node| {
+            add_attribute(map, node, "Bitness", "always64")
+        });
+        assert!(apply_edits(source, &[edit]).contains("Guid=\"ABC\" Bitness=\"always64\" />"));
+
+        let edit = with_element(source, "Component", |map, node| {
+            remove_attribute(map, node, "Guid").unwrap()
+        });
+        assert!(apply_edits(source, &[edit]).contains("<Component Id=\"Core\" />"));
+    }
+
+    #[test]
+    fn test_add_child() {
+        let source =
+            "<Wix>\n  <Feature Id=\"Main\">\n    <ComponentRef Id=\"A\" />\n  </Feature>\n</Wix>";
+        let edit = with_element(source, "Feature", |map, node| {
+            add_child(map, node, "<ComponentRef Id=\"B\" />", &ChildPosition::Last)
+        });
+        assert_eq!(
+            apply_edits(source, &[edit]),
+            "<Wix>\n  <Feature Id=\"Main\">\n    <ComponentRef Id=\"A\" />\n    <ComponentRef Id=\"B\" />\n  </Feature>\n</Wix>"
+        );
+
+        let edit = with_element(source, "Feature", |map, node| {
+            add_child(map, node, "<Level />", &ChildPosition::First)
+        });
+        assert!(
+            apply_edits(source, &[edit]).contains("\"Main\">\n    <Level />\n    <ComponentRef")
+        );
+
+        let source = "<Wix>\n  <Feature Id=\"Main\" />\n</Wix>";
+        let edit = with_element(source, "Feature", |map, node| {
+            add_child(map, node, "<ComponentRef Id=\"B\" />", &ChildPosition::Last)
+        });
+        assert_eq!(
+            apply_edits(source, &[edit]),
+            "<Wix>\n  <Feature Id=\"Main\">\n    <ComponentRef Id=\"B\" />\n  </Feature>\n</Wix>"
+        );
+    }
+
+    #[test]
+    fn test_remove_element() {
+        let source = "<Wix>\n  <Property Id=\"A\" />\n  <Property Id=\"B\" />\n</Wix>";
+        let edit = with_element(source, "Property", remove_element);
+        assert_eq!(
+            apply_edits(source, &[edit]),
+            "<Wix>\n  <Property Id=\"B\" />\n</Wix>"
+        );
+    }
+
+    #[test]
+    fn test_analyzer_fix() {
+        let source = "<Wix>\n  <Component Id=\"Core\" Guid=\"ABC\" />\n</Wix>";
+        let doc = Document::parse(source).unwrap();
+        let map = SourceMap::new(Path::new("test.wxs"), source);
+        let component = doc
+            .descendants()
+            .find(|n| n.has_tag_name("Component"))
+            .unwrap();
+        let range = wix_analyzer::Range::from_offsets(
+            source,
+            component.range().start,
+            component.range().end,
+        );
+
+        let edits = analyzer_fix(
+            &map,
+            &doc,
+            &FixAction::ReplaceAttribute {
+                range,
+                name: "Guid".to_string(),
+                new_value: "*".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(apply_edits(source, &edits).contains("Guid=\"*\""));
+
+        let moved = wix_analyzer::Range::from_offsets(source, 0, 1);
+        assert!(analyzer_fix(&map, &doc, &FixAction::RemoveElement { range: moved }).is_some());
+        let stale = wix_analyzer::Range::from_offsets(source, 3, 4);
+        assert!(analyzer_fix(&map, &doc, &FixAction::RemoveElement { range: stale }).is_none());
+    }
+}
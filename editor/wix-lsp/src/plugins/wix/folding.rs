@@ -0,0 +1,135 @@
+//! Folding ranges
+//!
+//! Elements fold up to their closing tag, which stays visible. Conditional
+//! blocks fold between `<?if?>`, `<?else?>` and `<?endif?>`, and loops
+//! between `<?foreach?>` and `<?endforeach?>`, whatever elements they cut
+//! across.
+
+use super::edits::{self, SourceMap};
+use super::parse::ParsedDocument;
+use crate::plugins::traits::{FoldingKind, FoldingRange};
+use roxmltree::Node;
+use std::path::Path;
+
+/// Folding ranges of a parsed document
+pub fn folding_ranges(parsed: &ParsedDocument) -> Vec<FoldingRange> {
+    let Ok(doc) = parsed.tree() else {
+        return Vec::new();
+    };
+    let map = SourceMap::new(Path::new(""), parsed.text());
+    let mut ranges = Vec::new();
+    // Start lines of the open conditional and loop blocks
+    let mut regions: Vec<u32> = Vec::new();
+    let mut push = |start_line: u32, end_line: u32, kind| {
+        if end_line > start_line {
+            ranges.push(FoldingRange {
+                start_line,
+                end_line,
+                kind,
+            });
+        }
+    };
+
+    for node in doc.descendants() {
+        let (start_line, _) = map.position(node.range().start);
+        let (end_line, _) = map.position(node.range().end);
+        if node.is_element() {
+            push(start_line, element_end_line(&map, node), None);
+        } else if node.is_comment() {
+            push(start_line, end_line, Some(FoldingKind::Comment));
+        } else if let Some(pi) = node.pi() {
+            match pi.target {
+                "if" | "ifdef" | "ifndef" | "foreach" => regions.push(start_line),
+                "else" | "elseif" => {
+                    if let Some(start) = regions.pop() {
+                        push(
+                            start,
+                            start_line.saturating_sub(1),
+                            Some(FoldingKind::Region),
+                        );
+                    }
+                    regions.push(start_line);
+                }
+                "endif" | "endforeach" => {
+                    if let Some(start) = regions.pop() {
+                        push(
+                            start,
+                            start_line.saturating_sub(1),
+                            Some(FoldingKind::Region),
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    ranges.sort_by_key(|r| (r.start_line, r.end_line));
+    ranges
+}
+
+/// Last line to fold: the one before a closing tag that starts its own line
+fn element_end_line(map: &SourceMap, node: Node) -> u32 {
+    let range = node.range();
+    let (end_line, _) = map.position(range.end);
+    if edits::is_self_closing(map.text(), node) {
+        return end_line;
+    }
+    match map.text()[range.clone()].rfind("</") {
+        Some(close) if map.starts_line(range.start + close) => end_line.saturating_sub(1),
+        _ => end_line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folds(source: &str) -> Vec<(u32, u32, Option<FoldingKind>)> {
+        folding_ranges(&ParsedDocument::new(source))
+            .iter()
+            .map(|r| (r.start_line, r.end_line, r.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_elements_and_comments() {
+        let source = r#"<Wix>
+  <!-- Product
+       definition -->
+  <Package Name="App"
+           Version="1.0" />
+  <Fragment><Component>
+  </Component></Fragment>
+</Wix>"#;
+        assert_eq!(
+            folds(source),
+            vec![
+                (0, 6, None),
+                (1, 2, Some(FoldingKind::Comment)),
+                (3, 4, None),
+                (5, 6, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conditional_regions() {
+        let source = r#"<Wix>
+  <?if $(var.Platform) = x64 ?>
+  <Property Id="A" Value="1" />
+  <Property Id="B" Value="1" />
+  <?else?>
+  <Property Id="A" Value="2" />
+  <?endif?>
+  <?ifdef Debug?><?endif?>
+</Wix>"#;
+        assert_eq!(
+            folds(source),
+            vec![
+                (0, 7, None),
+                (1, 3, Some(FoldingKind::Region)),
+                (4, 5, Some(FoldingKind::Region)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Inlay hints
+//!
+//! Directories show the folder they install to, following parents declared
+//! in other workspace files, and preprocessor variables show the value a
+//! build would substitute.
+
+use super::edits::SourceMap;
+use super::parse::ParsedDocument;
+use super::paths::ProjectPaths;
+use super::references::WorkspaceReferences;
+use super::tokens::{embedded_spans, embedding_values};
+use crate::plugins::traits::{InlayHint, SemanticTokenKind, SymbolRange};
+use roxmltree::Document;
+use std::path::{Path, PathBuf};
+use wix_analyzer::payload::DirectoryTree;
+
+/// Longer labels are cut short; the tooltip has the full text
+const MAX_LABEL_CHARS: usize = 60;
+
+/// Other files read to find where a directory's parents are declared
+const MAX_DIRECTORY_LOOKUPS: usize = 16;
+
+/// Everything the hints for one request look at
+pub struct HintContext<'a> {
+    pub parsed: &'a ParsedDocument,
+    pub path: &'a Path,
+    pub range: SymbolRange,
+    pub paths: &'a ProjectPaths,
+    pub references: Option<&'a WorkspaceReferences>,
+}
+
+impl HintContext<'_> {
+    fn in_range(&self, line: u32) -> bool {
+        (self.range.start_line..=self.range.end_line).contains(&line)
+    }
+}
+
+/// Hints within the requested range, in document order
+pub fn inlay_hints(cx: &HintContext) -> Vec<InlayHint> {
+    let Ok(doc) = cx.parsed.tree() else {
+        return Vec::new();
+    };
+    let map = SourceMap::new(cx.path, cx.parsed.text());
+    let mut hints = directory_hints(cx, doc, &map);
+    hints.extend(variable_hints(cx, doc, &map));
+    hints.sort_by_key(|h| (h.line, h.column));
+    hints
+}
+
+/// Install path after the `Name` (or `Id`) of each Directory and DirectoryRef
+fn directory_hints(cx: &HintContext, doc: &Document, map: &SourceMap) -> Vec<InlayHint> {
+    let directories: Vec<_> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("Directory") || n.has_tag_name("DirectoryRef"))
+        .filter_map(|node| {
+            let id = node.attribute("Id")?;
+            let anchor = node
+                .attributes()
+                .find(|a| a.name() == "Name")
+                .or_else(|| node.attributes().find(|a| a.name() == "Id"))?;
+            let (line, column) = map.position(anchor.range().end);
+            cx.in_range(line).then_some((id, line, column))
+        })
+        .collect();
+    if directories.is_empty() {
+        return Vec::new();
+    }
+
+    let ids: Vec<&str> = directories.iter().map(|(id, _, _)| *id).collect();
+    let tree = directory_tree(doc, &ids, cx.references);
+    directories
+        .into_iter()
+        .filter_map(|(id, line, column)| {
+            let install_path = tree.install_path(id);
+            // Standard directories are their own path
+            if install_path.is_empty() || install_path == id {
+                return None;
+            }
+            Some(InlayHint {
+                line,
+                column,
+                label: shorten(&install_path),
+                tooltip: Some(format!("Installs to {}", install_path)),
+            })
+        })
+        .collect()
+}
+
+/// Directories of a document, plus the files declaring their ancestors
+fn directory_tree(
+    doc: &Document,
+    ids: &[&str],
+    references: Option<&WorkspaceReferences>,
+) -> DirectoryTree {
+    let mut tree = DirectoryTree::new();
+    tree.add_document(doc.root());
+    let Some(references) = references else {
+        return tree;
+    };
+
+    let mut tried: Vec<String> = Vec::new();
+    let mut loaded: Vec<PathBuf> = Vec::new();
+    for _ in 0..MAX_DIRECTORY_LOOKUPS {
+        let Some(missing) = ids
+            .iter()
+            .map(|id| topmost_ancestor(&tree, id))
+            .find(|top| !tree.contains(top) && !tried.iter().any(|t| t == top))
+        else {
+            break;
+        };
+        tried.push(missing.clone());
+        let Some(file) =
+            references.definition_file(&missing, wix_references::SymbolKind::Directory)
+        else {
+            continue;
+        };
+        if loaded.contains(&file) {
+            continue;
+        }
+        if let Some(content) = references.file_content(&file) {
+            if let Ok(other) = roxmltree::Document::parse(&content) {
+                tree.add_document(other.root());
+            }
+        }
+        loaded.push(file);
+    }
+    tree
+}
+
+/// Last directory reached by following parents up from `id`
+fn topmost_ancestor(tree: &DirectoryTree, id: &str) -> String {
+    let mut current = id;
+    for _ in 0..64 {
+        match tree.parent(current) {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+    current.to_string()
+}
+
+/// Value after each `$(var.X)` the build would replace
+fn variable_hints(cx: &HintContext, doc: &Document, map: &SourceMap) -> Vec<InlayHint> {
+    let mut defines = None;
+    let mut hints = Vec::new();
+    for (start, value) in embedding_values(doc) {
+        for span in embedded_spans(value) {
+            if span.kind != SemanticTokenKind::PreprocessorVariable {
+                continue;
+            }
+            let (line, column) = map.position(start + span.end);
+            if !cx.in_range(line) {
+                continue;
+            }
+            // Includes are only read when there is something to show
+            let defines = defines.get_or_insert_with(|| cx.paths.defines(doc.root(), cx.path));
+            let variable = &value[span.start..span.end];
+            let expanded = cx.paths.expand(variable, cx.path, defines);
+            if expanded == variable {
+                continue;
+            }
+            hints.push(InlayHint {
+                line,
+                column,
+                label: format!("= {}", shorten(&expanded)),
+                tooltip: Some(format!("{} = {}", variable, expanded)),
+            });
+        }
+    }
+    hints
+}
+
+fn shorten(label: &str) -> String {
+    if label.chars().count() <= MAX_LABEL_CHARS {
+        return label.to_string();
+    }
+    let mut short: String = label.chars().take(MAX_LABEL_CHARS - 1).collect();
+    short.push('…');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::traits::BuildSettings;
+    use tempfile::TempDir;
+
+    fn hints(
+        source: &str,
+        path: &Path,
+        paths: &ProjectPaths,
+        references: Option<&WorkspaceReferences>,
+    ) -> Vec<(u32, u32, String)> {
+        let parsed = ParsedDocument::new(source);
+        let cx = HintContext {
+            parsed: &parsed,
+            path,
+            range: SymbolRange {
+                start_line: 0,
+                start_col: 0,
+                end_line: u32::MAX,
+                end_col: 0,
+            },
+            paths,
+            references,
+        };
+        inlay_hints(&cx)
+            .into_iter()
+            .map(|h| (h.line, h.column, h.label))
+            .collect()
+    }
+
+    #[test]
+    fn test_directory_hints_across_files() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("Package.wxs"),
+            r#"<Wix><Package><StandardDirectory Id="ProgramFiles6432Folder">
+  <Directory Id="INSTALLFOLDER" Name="Acme" />
+</StandardDirectory></Package></Wix>"#,
+        )
+        .unwrap();
+        let references = WorkspaceReferences::for_workspace(temp.path());
+        let source = r#"<Wix><Fragment>
+  <DirectoryRef Id="INSTALLFOLDER">
+    <Directory Id="BinFolder" Name="bin" />
+  </DirectoryRef>
+</Fragment></Wix>"#;
+        let path = temp.path().join("Files.wxs");
+
+        assert_eq!(
+            hints(source, &path, &ProjectPaths::new(), Some(&references)),
+            vec![
+                (1, 34, "ProgramFiles6432Folder\\Acme".to_string()),
+                (2, 40, "ProgramFiles6432Folder\\Acme\\bin".to_string()),
+            ]
+        );
+        // Without the index only the document's own directories are known
+        assert_eq!(
+            hints(source, &path, &ProjectPaths::new(), None),
+            vec![(2, 40, "INSTALLFOLDER\\bin".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_variable_hints() {
+        let temp = TempDir::new().unwrap();
+        let build = BuildSettings {
+            defines: vec!["Configuration=Release".to_string()],
+            ..Default::default()
+        };
+        let paths = ProjectPaths::for_workspace(temp.path(), &build);
+        let source = r#"<Wix>
+  <?define Bin = "out\$(var.Configuration)" ?>
+  <File Source="$(var.Bin)\app.exe" Name="$(var.Unknown)" />
+</Wix>"#;
+        let path = temp.path().join("Product.wxs");
+        assert_eq!(
+            hints(source, &path, &paths, None),
+            vec![
+                (1, 42, "= Release".to_string()),
+                (2, 26, "= out\\Release".to_string()),
+            ]
+        );
+    }
+}
@@ -2,13 +2,35 @@
 //!
 //! Implements all plugin traits for WiX installer development.
 
+mod actions;
+mod diagnostics;
+mod edits;
+mod folding;
+mod hints;
+mod parse;
+mod paths;
+mod references;
+mod tokens;
+
 use crate::plugins::registry::FullPluginDyn;
 use crate::plugins::traits::{
-    Completion, CompletionProvider, Diagnostic, DiagnosticProvider, FormatProvider, HoverInfo,
-    HoverProvider, HoverRange, LanguagePlugin, Symbol, SymbolKind, SymbolProvider, SymbolRange,
+    BuildSettings, CallHierarchyProvider, CodeAction, CodeActionKind, CodeActionProvider,
+    Completion, CompletionProvider, DefinitionProvider, Diagnostic, DiagnosticProvider,
+    DocumentLink, DocumentLinkProvider, FileEdit, FoldingRange, FoldingRangeProvider,
+    FormatProvider, HierarchyCall, HierarchyItem, HoverInfo, HoverProvider, HoverRange, InlayHint,
+    InlayHintProvider, LanguagePlugin, ReferenceProvider, RenameProvider, SemanticToken,
+    SemanticTokenProvider, Symbol, SymbolKind, SymbolLocation, SymbolProvider, SymbolRange,
+    TypeHierarchyProvider, WorkspaceSymbol, WorkspaceSymbolProvider,
 };
 use std::path::Path;
-
+use std::sync::{Arc, RwLock};
+use wix_symbols::Relation;
+
+pub use actions::{COMMANDS, HARVEST_FOLDER};
+pub use diagnostics::WorkspaceDiagnostics;
+pub use parse::{ParseCache, ParsedDocument};
+pub use paths::ProjectPaths;
+pub use references::WorkspaceReferences;
 // Re-export for convenience
 pub use wix_fmt::{FormatConfig, Formatter};
 pub use wix_hover::HoverProvider as WixHoverProvider;
@@ -22,6 +44,14 @@ pub struct WixPlugin {
     hover: Option<wix_hover::HoverProvider>,
     /// Formatter
     formatter: Formatter,
+    /// Parsed trees shared by the providers
+    parsed: ParseCache,
+    /// Linters, replaced when a workspace is opened
+    diagnostics: RwLock<Arc<WorkspaceDiagnostics>>,
+    /// Cross-file symbols, replaced when a workspace is opened
+    references: RwLock<Arc<WorkspaceReferences>>,
+    /// Build search paths, replaced when a workspace is opened
+    paths: RwLock<Arc<ProjectPaths>>,
 }
 
 impl WixPlugin {
@@ -31,6 +61,10 @@ impl WixPlugin {
             initialized: false,
             hover: None,
             formatter: Formatter::new(FormatConfig::default()),
+            parsed: ParseCache::new(),
+            diagnostics: RwLock::new(Arc::new(WorkspaceDiagnostics::new())),
+            references: RwLock::new(Arc::new(WorkspaceReferences::new())),
+            paths: RwLock::new(Arc::new(ProjectPaths::new())),
         }
     }
 
@@ -40,6 +74,27 @@ impl WixPlugin {
         plugin.initialize(data_path)?;
         Ok(plugin)
     }
+
+    fn workspace_diagnostics(&self) -> Option<Arc<WorkspaceDiagnostics>> {
+        self.diagnostics
+            .read()
+            .ok()
+            .map(|current| Arc::clone(&current))
+    }
+
+    fn workspace_references(&self) -> Option<Arc<WorkspaceReferences>> {
+        self.references
+            .read()
+            .ok()
+            .map(|current| Arc::clone(&current))
+    }
+
+    fn project_paths(&self) -> Arc<ProjectPaths> {
+        self.paths
+            .read()
+            .map(|current| Arc::clone(&current))
+            .unwrap_or_default()
+    }
 }
 
 impl Default for WixPlugin {
@@ -91,6 +146,36 @@ impl LanguagePlugin for WixPlugin {
     fn is_initialized(&self) -> bool {
         self.initialized
     }
+
+    fn open_workspace(&self, root: &Path, build: &BuildSettings) {
+        let diagnostics = Arc::new(WorkspaceDiagnostics::for_workspace(root));
+        if let Ok(mut current) = self.diagnostics.write() {
+            *current = diagnostics;
+        }
+        let references = Arc::new(WorkspaceReferences::for_workspace(root));
+        if let Ok(mut current) = self.references.write() {
+            *current = references;
+        }
+        let paths = Arc::new(ProjectPaths::for_workspace(root, build));
+        if let Ok(mut current) = self.paths.write() {
+            *current = paths;
+        }
+    }
+
+    fn document_changed(&self, path: &Path, source: &str) {
+        if let Some(references) = self.workspace_references() {
+            references.update(path, source);
+        }
+    }
+
+    fn file_changed(&self, path: &Path) {
+        if let Some(references) = self.workspace_references() {
+            references.reload(path);
+        }
+        if let Some(diagnostics) = self.workspace_diagnostics() {
+            diagnostics.reload(path);
+        }
+    }
 }
 
 impl CompletionProvider for WixPlugin {
@@ -108,10 +193,10 @@ impl HoverProvider for WixPlugin {
         Some(HoverInfo {
             contents: info.contents,
             range: info.range.map(|r| HoverRange {
-                start_line: r.start_line,
-                start_col: r.start_col,
-                end_line: r.end_line,
-                end_col: r.end_col,
+                start_line: r.start_line.saturating_sub(1),
+                start_col: r.start_col.saturating_sub(1),
+                end_line: r.end_line.saturating_sub(1),
+                end_col: r.end_col.saturating_sub(1),
             }),
         })
     }
@@ -119,7 +204,8 @@ impl HoverProvider for WixPlugin {
 
 impl SymbolProvider for WixPlugin {
     fn symbols(&self, source: &str) -> Result<Vec<Symbol>, String> {
-        let symbols = extract_symbols(source)?;
+        let parsed = self.parsed.get(source);
+        let symbols = parsed.symbols()?;
 
         fn convert_symbol(s: &wix_symbols::Symbol) -> Symbol {
             Symbol {
@@ -141,30 +227,33 @@ impl SymbolProvider for WixPlugin {
                     wix_symbols::SymbolKind::TypeParameter => SymbolKind::TypeParameter,
                 },
                 detail: s.detail.clone(),
-                range: SymbolRange {
-                    start_line: s.range.start.line,
-                    start_col: s.range.start.character,
-                    end_line: s.range.end.line,
-                    end_col: s.range.end.character,
-                },
-                selection_range: SymbolRange {
-                    start_line: s.selection_range.start.line,
-                    start_col: s.selection_range.start.character,
-                    end_line: s.selection_range.end.line,
-                    end_col: s.selection_range.end.character,
-                },
+                range: convert_range(&s.range),
+                selection_range: convert_range(&s.selection_range),
                 children: s.children.iter().map(convert_symbol).collect(),
             }
         }
 
+        // wix-symbols positions are 1-based
+        fn convert_range(r: &wix_symbols::Range) -> SymbolRange {
+            SymbolRange {
+                start_line: r.start.line.saturating_sub(1),
+                start_col: r.start.character.saturating_sub(1),
+                end_line: r.end.line.saturating_sub(1),
+                end_col: r.end.character.saturating_sub(1),
+            }
+        }
+
         Ok(symbols.iter().map(convert_symbol).collect())
     }
 }
 
 impl DiagnosticProvider for WixPlugin {
-    fn diagnose(&self, _source: &str, _path: &Path) -> Vec<Diagnostic> {
-        // TODO: Integrate wix-analyzer for diagnostics
-        Vec::new()
+    fn diagnose(&self, source: &str, path: &Path) -> Vec<Diagnostic> {
+        // Clone the handle so a workspace reload doesn't wait on a run
+        match self.workspace_diagnostics() {
+            Some(diagnostics) => diagnostics.diagnose_parsed(&self.parsed.get(source), path),
+            None => Vec::new(),
+        }
     }
 }
 
@@ -174,6 +263,193 @@ impl FormatProvider for WixPlugin {
     }
 }
 
+impl DefinitionProvider for WixPlugin {
+    fn definition(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<SymbolLocation> {
+        self.workspace_references()?
+            .definition(path, source, line, column)
+    }
+}
+
+impl ReferenceProvider for WixPlugin {
+    fn references(&self, path: &Path, source: &str, line: u32, column: u32) -> Vec<SymbolLocation> {
+        match self.workspace_references() {
+            Some(references) => references.references(path, source, line, column),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl RenameProvider for WixPlugin {
+    fn prepare_rename(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<SymbolRange> {
+        self.workspace_references()?
+            .prepare_rename(path, source, line, column)
+    }
+
+    fn rename(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+        new_name: &str,
+    ) -> Result<Vec<FileEdit>, String> {
+        self.workspace_references()
+            .ok_or_else(|| "Reference index unavailable".to_string())?
+            .rename(path, source, line, column, new_name)
+    }
+}
+
+impl WorkspaceSymbolProvider for WixPlugin {
+    fn workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        match self.workspace_references() {
+            Some(references) => references.workspace_symbols(query),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl CallHierarchyProvider for WixPlugin {
+    fn prepare_call_hierarchy(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<HierarchyItem> {
+        self.workspace_references()?
+            .hierarchy_item(path, source, line, column)
+    }
+
+    fn incoming_calls(&self, item: &HierarchyItem) -> Vec<HierarchyCall> {
+        match self.workspace_references() {
+            Some(references) => references.hierarchy_parents(item, &[Relation::Installs]),
+            None => Vec::new(),
+        }
+    }
+
+    fn outgoing_calls(&self, item: &HierarchyItem) -> Vec<HierarchyCall> {
+        match self.workspace_references() {
+            Some(references) => references.hierarchy_children(item, &[Relation::Installs]),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl TypeHierarchyProvider for WixPlugin {
+    fn prepare_type_hierarchy(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<HierarchyItem> {
+        self.workspace_references()?
+            .hierarchy_item(path, source, line, column)
+    }
+
+    fn supertypes(&self, item: &HierarchyItem) -> Vec<HierarchyItem> {
+        match self.workspace_references() {
+            Some(references) => references
+                .hierarchy_parents(item, &[Relation::Installs, Relation::Placement])
+                .into_iter()
+                .map(|call| call.item)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn subtypes(&self, item: &HierarchyItem) -> Vec<HierarchyItem> {
+        match self.workspace_references() {
+            Some(references) => references
+                .hierarchy_children(item, &[Relation::Installs, Relation::Placement])
+                .into_iter()
+                .map(|call| call.item)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+impl CodeActionProvider for WixPlugin {
+    fn code_actions(
+        &self,
+        path: &Path,
+        source: &str,
+        range: SymbolRange,
+        only: Option<&[CodeActionKind]>,
+    ) -> Vec<CodeAction> {
+        let parsed = self.parsed.get(source);
+        let references = self.workspace_references();
+        let cx = actions::ActionContext {
+            parsed: &parsed,
+            path,
+            range,
+            references: references.as_deref(),
+            ordering: self.formatter.wix_data(),
+        };
+        actions::code_actions(&cx, only)
+    }
+
+    fn commands(&self) -> &[&str] {
+        COMMANDS
+    }
+
+    fn execute_command(
+        &self,
+        command: &str,
+        arguments: &[serde_json::Value],
+        open_text: &dyn Fn(&Path) -> Option<String>,
+    ) -> Result<Vec<FileEdit>, String> {
+        actions::execute_command(command, arguments, open_text)
+    }
+}
+
+impl SemanticTokenProvider for WixPlugin {
+    fn semantic_tokens(&self, source: &str) -> Vec<SemanticToken> {
+        tokens::semantic_tokens(&self.parsed.get(source))
+    }
+}
+
+impl FoldingRangeProvider for WixPlugin {
+    fn folding_ranges(&self, source: &str) -> Vec<FoldingRange> {
+        folding::folding_ranges(&self.parsed.get(source))
+    }
+}
+
+impl DocumentLinkProvider for WixPlugin {
+    fn document_links(&self, path: &Path, source: &str) -> Vec<DocumentLink> {
+        paths::document_links(&self.parsed.get(source), path, &self.project_paths())
+    }
+}
+
+impl InlayHintProvider for WixPlugin {
+    fn inlay_hints(&self, path: &Path, source: &str, range: SymbolRange) -> Vec<InlayHint> {
+        let parsed = self.parsed.get(source);
+        let paths = self.project_paths();
+        let references = self.workspace_references();
+        let cx = hints::HintContext {
+            parsed: &parsed,
+            path,
+            range,
+            paths: &paths,
+            references: references.as_deref(),
+        };
+        hints::inlay_hints(&cx)
+    }
+}
+
 // Implement FullPluginDyn for dynamic dispatch
 impl FullPluginDyn for WixPlugin {
     fn as_language(&self) -> &dyn LanguagePlugin {
@@ -199,6 +475,50 @@ impl FullPluginDyn for WixPlugin {
     fn as_format(&self) -> &dyn FormatProvider {
         self
     }
+
+    fn as_definition(&self) -> &dyn DefinitionProvider {
+        self
+    }
+
+    fn as_references(&self) -> &dyn ReferenceProvider {
+        self
+    }
+
+    fn as_rename(&self) -> &dyn RenameProvider {
+        self
+    }
+
+    fn as_workspace_symbols(&self) -> &dyn WorkspaceSymbolProvider {
+        self
+    }
+
+    fn as_call_hierarchy(&self) -> &dyn CallHierarchyProvider {
+        self
+    }
+
+    fn as_type_hierarchy(&self) -> &dyn TypeHierarchyProvider {
+        self
+    }
+
+    fn as_code_actions(&self) -> &dyn CodeActionProvider {
+        self
+    }
+
+    fn as_semantic_tokens(&self) -> &dyn SemanticTokenProvider {
+        self
+    }
+
+    fn as_folding(&self) -> &dyn FoldingRangeProvider {
+        self
+    }
+
+    fn as_document_links(&self) -> &dyn DocumentLinkProvider {
+        self
+    }
+
+    fn as_inlay_hints(&self) -> &dyn InlayHintProvider {
+        self
+    }
 }
 
 #[cfg(test)]
@@ -265,7 +585,16 @@ mod tests {
     #[test]
     fn test_diagnose_without_init() {
         let plugin = WixPlugin::new();
+        // The linters don't need wix-data
         let diagnostics = plugin.diagnose("<Wix />", Path::new("test.wxs"));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.rule_id == "v4-namespace-missing"));
+
+        let diagnostics = plugin.diagnose(
+            r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs"><Fragment Id="Empty" /></Wix>"#,
+            Path::new("test.wxs"),
+        );
         assert!(diagnostics.is_empty());
     }
 }
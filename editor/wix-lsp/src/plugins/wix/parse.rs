@@ -0,0 +1,158 @@
+//! Parsed documents shared between providers
+//!
+//! Symbols, diagnostics and the analyzer all need the XML tree. The cache is
+//! keyed by content, so each document version is parsed once no matter which
+//! provider asks first, and results derived from the tree are kept with it.
+
+use self_cell::self_cell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Number of recent versions kept; a few open documents plus their last edit
+const CACHE_SIZE: usize = 8;
+
+type XmlTree<'a> = Result<roxmltree::Document<'a>, String>;
+
+self_cell!(
+    struct SyntaxTree {
+        owner: Arc<str>,
+
+        #[covariant]
+        dependent: XmlTree,
+    }
+);
+
+/// A document version parsed once
+pub struct ParsedDocument {
+    hash: u64,
+    tree: SyntaxTree,
+    symbols: OnceLock<Vec<wix_symbols::Symbol>>,
+}
+
+impl ParsedDocument {
+    /// Parse a source text
+    pub fn new(source: &str) -> Self {
+        Self::parse(source, content_hash(source))
+    }
+
+    fn parse(source: &str, hash: u64) -> Self {
+        let tree = SyntaxTree::new(Arc::from(source), |text| {
+            roxmltree::Document::parse(text).map_err(|e| format!("XML parse error: {}", e))
+        });
+        Self {
+            hash,
+            tree,
+            symbols: OnceLock::new(),
+        }
+    }
+
+    /// Source text
+    pub fn text(&self) -> &str {
+        self.tree.borrow_owner()
+    }
+
+    /// XML tree, or the parse error
+    pub fn tree(&self) -> Result<&roxmltree::Document<'_>, &str> {
+        self.tree
+            .borrow_dependent()
+            .as_ref()
+            .map_err(|e| e.as_str())
+    }
+
+    /// Document symbols, extracted on first use
+    pub fn symbols(&self) -> Result<&[wix_symbols::Symbol], String> {
+        let doc = self.tree().map_err(str::to_string)?;
+        Ok(self
+            .symbols
+            .get_or_init(|| wix_symbols::extract_symbols_from_document(doc, self.text())))
+    }
+}
+
+/// Recently parsed documents
+#[derive(Default)]
+pub struct ParseCache {
+    entries: Mutex<VecDeque<Arc<ParsedDocument>>>,
+}
+
+impl ParseCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parsed form of `source`, parsing it if it isn't cached
+    pub fn get(&self, source: &str) -> Arc<ParsedDocument> {
+        let hash = content_hash(source);
+        if let Some(parsed) = self.find(source, hash) {
+            return parsed;
+        }
+
+        // Parse outside the lock so other documents aren't held up
+        let parsed = Arc::new(ParsedDocument::parse(source, hash));
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push_front(Arc::clone(&parsed));
+            entries.truncate(CACHE_SIZE);
+        }
+        parsed
+    }
+
+    fn find(&self, source: &str, hash: u64) -> Option<Arc<ParsedDocument>> {
+        let mut entries = self.entries.lock().ok()?;
+        let index = entries
+            .iter()
+            .position(|p| p.hash == hash && p.text() == source)?;
+        // Most recently used first
+        let parsed = entries.remove(index)?;
+        entries.push_front(Arc::clone(&parsed));
+        Some(parsed)
+    }
+}
+
+fn content_hash(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_each_version_once() {
+        let cache = ParseCache::new();
+        let source = "<Wix><Component Id=\"Core\" Guid=\"*\" /></Wix>";
+
+        let first = cache.get(source);
+        let copy = String::from(source);
+        let second = cache.get(&copy);
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let symbols = first.symbols().unwrap();
+        assert_eq!(symbols[0].name, "Core");
+        assert!(std::ptr::eq(symbols, second.symbols().unwrap()));
+
+        let edited = cache.get("<Wix><Component Id=\"Other\" Guid=\"*\" /></Wix>");
+        assert!(!Arc::ptr_eq(&first, &edited));
+        assert_eq!(edited.symbols().unwrap()[0].name, "Other");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let cache = ParseCache::new();
+        let parsed = cache.get("<Wix><Component");
+        assert!(parsed.tree().unwrap_err().contains("XML parse error"));
+        assert!(parsed.symbols().is_err());
+    }
+
+    #[test]
+    fn test_evicts_oldest() {
+        let cache = ParseCache::new();
+        let first = cache.get("<Wix />");
+        for i in 0..CACHE_SIZE {
+            cache.get(&format!("<Wix Id=\"{}\" />", i));
+        }
+        assert!(!Arc::ptr_eq(&first, &cache.get("<Wix />")));
+    }
+}
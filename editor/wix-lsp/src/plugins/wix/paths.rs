@@ -0,0 +1,311 @@
+//! Files referenced from WiX sources
+//!
+//! `File Source=`, `Binary SourceFile=` and `<?include?>` resolve the way a
+//! build would: preprocessor variables come from the configured defines, the
+//! document and the files it includes; sources are then searched through
+//! the bind paths and includes through the include paths.
+
+use super::edits::SourceMap;
+use super::parse::ParsedDocument;
+use crate::plugins::traits::{BuildSettings, DocumentLink, SymbolRange};
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use wix_analyzer::payload::{collect_defines, PayloadResolver};
+
+/// Attributes naming a file that is built into the package
+const SOURCE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("File", "Source"),
+    ("Binary", "SourceFile"),
+    ("Icon", "SourceFile"),
+    ("Merge", "SourceFile"),
+    ("Payload", "SourceFile"),
+    ("MsiPackage", "SourceFile"),
+    ("ExePackage", "SourceFile"),
+    ("MsuPackage", "SourceFile"),
+];
+
+/// How deep `<?include?>` chains and variables within variables are followed
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Bind paths, include paths and defines of the workspace build
+#[derive(Default)]
+pub struct ProjectPaths {
+    resolver: PayloadResolver,
+    include_paths: Vec<PathBuf>,
+}
+
+impl ProjectPaths {
+    /// No search paths; sources resolve next to the document only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Settings with relative paths taken from `root`
+    pub fn for_workspace(root: &Path, build: &BuildSettings) -> Self {
+        let resolver = build
+            .bind_paths
+            .iter()
+            .fold(PayloadResolver::new(), |r, spec| {
+                r.with_bind_path(&rooted_bind_path(root, spec))
+            });
+        let resolver = build
+            .defines
+            .iter()
+            .fold(resolver, |r, spec| r.with_define(spec));
+        Self {
+            resolver,
+            include_paths: build.include_paths.iter().map(|p| root.join(p)).collect(),
+        }
+    }
+
+    /// Variables defined by a document and the files it includes
+    pub fn defines(&self, root: Node, file: &Path) -> HashMap<String, String> {
+        let mut defines = HashMap::new();
+        let mut seen = vec![file.to_path_buf()];
+        self.collect_defines(root, file, &mut defines, &mut seen);
+
+        // Values can be built from other variables
+        for _ in 0..MAX_INCLUDE_DEPTH {
+            let expanded: HashMap<String, String> = defines
+                .iter()
+                .map(|(name, value)| (name.clone(), self.expand(value, file, &defines)))
+                .collect();
+            if expanded == defines {
+                break;
+            }
+            defines = expanded;
+        }
+        defines
+    }
+
+    fn collect_defines(
+        &self,
+        root: Node,
+        file: &Path,
+        defines: &mut HashMap<String, String>,
+        seen: &mut Vec<PathBuf>,
+    ) {
+        for include in includes(root) {
+            if seen.len() > MAX_INCLUDE_DEPTH {
+                break;
+            }
+            let Some(path) = self.resolve_include(include.value, file, defines) else {
+                continue;
+            };
+            if seen.contains(&path) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(doc) = roxmltree::Document::parse(&content) {
+                seen.push(path.clone());
+                self.collect_defines(doc.root(), &path, defines, seen);
+            }
+        }
+        defines.extend(collect_defines(root));
+    }
+
+    /// Expand preprocessor variables, leaving unknown ones as written
+    pub fn expand(&self, value: &str, file: &Path, defines: &HashMap<String, String>) -> String {
+        self.resolver.expand(value, file, defines)
+    }
+
+    /// File built from a `Source` or `SourceFile` value
+    pub fn resolve_source(
+        &self,
+        value: &str,
+        file: &Path,
+        defines: &HashMap<String, String>,
+    ) -> Option<PathBuf> {
+        self.resolver.resolve(value, file, defines)
+    }
+
+    /// File an `<?include?>` pulls in
+    pub fn resolve_include(
+        &self,
+        value: &str,
+        file: &Path,
+        defines: &HashMap<String, String>,
+    ) -> Option<PathBuf> {
+        let expanded = self.expand(value, file, defines);
+        if expanded.contains("$(") {
+            return None;
+        }
+        let relative = PathBuf::from(expanded.replace('\\', "/"));
+        if relative.is_absolute() {
+            return relative.is_file().then_some(relative);
+        }
+        let source_dir = file.parent().unwrap_or(Path::new(""));
+        std::iter::once(source_dir)
+            .chain(self.include_paths.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(&relative))
+            .find(|candidate| candidate.is_file())
+    }
+}
+
+/// `NAME=DIR` or `DIR` with a relative `DIR` taken from `root`
+fn rooted_bind_path(root: &Path, spec: &str) -> String {
+    match spec.split_once('=') {
+        Some((name, dir)) if !name.is_empty() && !name.contains(['\\', '/']) => {
+            format!("{}={}", name, root.join(dir).display())
+        }
+        _ => root.join(spec).display().to_string(),
+    }
+}
+
+/// An `<?include?>` and the byte range of its file name
+pub struct Include<'a> {
+    pub value: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// `<?include?>` instructions of a document, in document order
+pub fn includes<'input>(root: Node<'_, 'input>) -> Vec<Include<'input>> {
+    let source = root.document().input_text();
+    root.descendants()
+        .filter_map(|node| {
+            let pi = node.pi()?;
+            if pi.target != "include" {
+                return None;
+            }
+            let value = pi.value?.trim().trim_matches('"').trim();
+            let range = node.range();
+            let start = range.start + source[range].find(value)?;
+            Some(Include {
+                value,
+                start,
+                end: start + value.len(),
+            })
+        })
+        .collect()
+}
+
+/// Links for source files and includes that exist on disk
+pub fn document_links(
+    parsed: &ParsedDocument,
+    path: &Path,
+    paths: &ProjectPaths,
+) -> Vec<DocumentLink> {
+    let Ok(doc) = parsed.tree() else {
+        return Vec::new();
+    };
+    let map = SourceMap::new(path, parsed.text());
+    let defines = paths.defines(doc.root(), path);
+    let link = |start: usize, end: usize, target: PathBuf| {
+        let (start_line, start_col) = map.position(start);
+        let (end_line, end_col) = map.position(end);
+        DocumentLink {
+            range: SymbolRange {
+                start_line,
+                start_col,
+                end_line,
+                end_col,
+            },
+            tooltip: Some(target.display().to_string()),
+            target,
+        }
+    };
+
+    let mut links = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_element()) {
+        let element = node.tag_name().name();
+        for attr in node.attributes() {
+            if !SOURCE_ATTRIBUTES.contains(&(element, attr.name())) {
+                continue;
+            }
+            if let Some(target) = paths.resolve_source(attr.value(), path, &defines) {
+                let range = attr.range_value();
+                links.push(link(range.start, range.end, target));
+            }
+        }
+    }
+    for include in includes(doc.root()) {
+        if let Some(target) = paths.resolve_include(include.value, path, &defines) {
+            links.push(link(include.start, include.end, target));
+        }
+    }
+    links.sort_by_key(|l| (l.range.start_line, l.range.start_col));
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_defines_from_includes() {
+        let temp = TempDir::new().unwrap();
+        write(
+            temp.path(),
+            "include/Common.wxi",
+            r#"<Include><?define Bin = "out\bin" ?></Include>"#,
+        );
+        write(
+            temp.path(),
+            "src/Version.wxi",
+            r#"<Include><?include Common.wxi ?><?define Version = "1.2.3" ?></Include>"#,
+        );
+        let source = r#"<Wix><?include "Version.wxi" ?><?define Version = "2.0" ?></Wix>"#;
+        let wxs = temp.path().join("src/Product.wxs");
+        let build = BuildSettings {
+            include_paths: vec!["include".to_string()],
+            ..Default::default()
+        };
+        let paths = ProjectPaths::for_workspace(temp.path(), &build);
+
+        let doc = roxmltree::Document::parse(source).unwrap();
+        let defines = paths.defines(doc.root(), &wxs);
+        assert_eq!(defines["Bin"], "out\\bin");
+        // The document's own defines come last
+        assert_eq!(defines["Version"], "2.0");
+
+        let without_paths = ProjectPaths::new().defines(doc.root(), &wxs);
+        assert!(!without_paths.contains_key("Bin"));
+        assert_eq!(without_paths["Version"], "2.0");
+    }
+
+    #[test]
+    fn test_document_links() {
+        let temp = TempDir::new().unwrap();
+        let app = write(temp.path(), "build/Release/app.exe", "");
+        let icon = write(temp.path(), "assets/app.ico", "");
+        let wxi = write(temp.path(), "src/Vars.wxi", "<Include />");
+        let wxs = temp.path().join("src/Product.wxs");
+        let source = r#"<Wix>
+  <?include Vars.wxi ?>
+  <?define Config = "Release" ?>
+  <Component>
+    <File Source="$(var.Config)\app.exe" />
+    <File Source="missing.exe" />
+  </Component>
+  <Icon Id="AppIcon" SourceFile="!(bindpath.assets)\app.ico" />
+</Wix>"#;
+        let build = BuildSettings {
+            bind_paths: vec!["build".to_string(), "assets=assets".to_string()],
+            ..Default::default()
+        };
+        let paths = ProjectPaths::for_workspace(temp.path(), &build);
+
+        let links = document_links(&ParsedDocument::new(source), &wxs, &paths);
+        let targets: Vec<&Path> = links.iter().map(|l| l.target.as_path()).collect();
+        assert_eq!(targets, vec![wxi.as_path(), app.as_path(), icon.as_path()]);
+        let include = &links[0].range;
+        assert_eq!(
+            (include.start_line, include.start_col, include.end_col),
+            (1, 12, 20)
+        );
+        let file = &links[1].range;
+        assert_eq!((file.start_line, file.start_col, file.end_col), (4, 18, 39));
+    }
+}
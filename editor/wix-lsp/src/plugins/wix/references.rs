@@ -0,0 +1,609 @@
+//! Cross-file navigation over a workspace reference index
+//!
+//! The index covers every `.wxs`, `.wxi` and `.wxl` file under the workspace
+//! root. Open documents replace their on-disk content as they are edited, and
+//! file-watcher events re-read files that change outside the editor.
+//!
+//! The same files feed the install hierarchy behind call and type
+//! hierarchies, which resolves Ref elements into what installs what.
+
+use crate::plugins::traits::{
+    FileEdit, HierarchyCall, HierarchyItem, SymbolKind, SymbolLocation, SymbolRange,
+    WorkspaceSymbol,
+};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use walkdir::WalkDir;
+use wix_references::{ReferenceIndex, SymbolEntry, SymbolType};
+use wix_symbols::{Hierarchy, HierarchyLink, HierarchyNode, NodeKey, NodeKind, Relation};
+
+/// Extensions of the files that take part in references
+const INDEXED_EXTENSIONS: &[&str] = &["wxs", "wxi", "wxl"];
+
+/// Directories never searched for sources
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "bin", "obj"];
+
+/// Upper bound on workspace symbol results
+const MAX_WORKSPACE_SYMBOLS: usize = 500;
+
+/// Workspace-wide reference index
+pub struct WorkspaceReferences {
+    index: RwLock<ReferenceIndex>,
+    hierarchy: RwLock<Hierarchy>,
+}
+
+impl WorkspaceReferences {
+    /// Empty index; documents are added as they are seen
+    pub fn new() -> Self {
+        Self {
+            index: RwLock::new(ReferenceIndex::new()),
+            hierarchy: RwLock::new(Hierarchy::new()),
+        }
+    }
+
+    /// Index every WiX source under `root`
+    pub fn for_workspace(root: &Path) -> Self {
+        let mut index = ReferenceIndex::new();
+        let mut hierarchy = Hierarchy::new();
+        let mut count = 0;
+        let files = WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !is_skipped_dir(e))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && is_indexed(e.path()));
+        for entry in files {
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            match index.add_file(&path_key(entry.path()), &content) {
+                Ok(()) => count += 1,
+                Err(e) => tracing::debug!("Skipping {}: {}", entry.path().display(), e),
+            }
+            let _ = hierarchy.update_file(entry.path(), &content);
+        }
+        tracing::info!("Indexed references in {} WiX files", count);
+
+        Self {
+            index: RwLock::new(index),
+            hierarchy: RwLock::new(hierarchy),
+        }
+    }
+
+    /// Replace a file's content with an editor buffer
+    pub fn update(&self, path: &Path, source: &str) {
+        if !is_indexed(path) {
+            return;
+        }
+        let key = path_key(path);
+        if self
+            .index
+            .read()
+            .map_or(true, |index| index.file_content(&key) == Some(source))
+        {
+            return;
+        }
+        if let Ok(mut index) = self.index.write() {
+            // On a parse error the last good symbols are kept
+            let _ = index.update_file(&key, source);
+        }
+        if let Ok(mut hierarchy) = self.hierarchy.write() {
+            let _ = hierarchy.update_file(path, source);
+        }
+    }
+
+    /// Re-read a file from disk, dropping it if it no longer exists
+    pub fn reload(&self, path: &Path) {
+        if !is_indexed(path) {
+            return;
+        }
+        match std::fs::read_to_string(path) {
+            Ok(content) => self.update(path, &content),
+            Err(_) => {
+                if let Ok(mut index) = self.index.write() {
+                    index.remove_file(&path_key(path));
+                }
+                if let Ok(mut hierarchy) = self.hierarchy.write() {
+                    hierarchy.remove_file(path);
+                }
+            }
+        }
+    }
+
+    /// Definition of the symbol at a 1-based position
+    pub fn definition(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<SymbolLocation> {
+        self.update(path, source);
+        let index = self.index.read().ok()?;
+        index
+            .go_to_definition(&path_key(path), line, column)
+            .map(to_location)
+    }
+
+    /// Definition and references of the symbol at a 1-based position
+    pub fn references(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Vec<SymbolLocation> {
+        self.update(path, source);
+        let Ok(index) = self.index.read() else {
+            return Vec::new();
+        };
+        let Some(symbol) = index.symbol_at_position(&path_key(path), line, column) else {
+            return Vec::new();
+        };
+        index
+            .find_usages_of_kind(&symbol.name, symbol.kind)
+            .into_iter()
+            .map(to_location)
+            .collect()
+    }
+
+    /// Range of the name under a 1-based position, if it can be renamed
+    pub fn prepare_rename(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<SymbolRange> {
+        self.update(path, source);
+        let index = self.index.read().ok()?;
+        let symbol = index.symbol_at_position(&path_key(path), line, column)?;
+        index.find_definition_of_kind(&symbol.name, symbol.kind)?;
+        Some(to_range(&symbol.id_range))
+    }
+
+    /// Edits renaming the symbol at a 1-based position across the workspace
+    pub fn rename(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+        new_name: &str,
+    ) -> Result<Vec<FileEdit>, String> {
+        self.update(path, source);
+        let index = self.index.read().map_err(|e| e.to_string())?;
+        let edits = index
+            .rename(&path_key(path), line, column, new_name)
+            .map_err(|e| e.to_string())?;
+        Ok(edits
+            .into_iter()
+            .map(|edit| FileEdit {
+                path: PathBuf::from(edit.file),
+                range: to_range(&edit.range),
+                new_text: edit.new_text,
+            })
+            .collect())
+    }
+
+    /// Whether any file refers to a symbol
+    pub fn is_referenced(&self, name: &str, kind: wix_references::SymbolKind) -> bool {
+        self.index.read().is_ok_and(|index| {
+            index
+                .find_usages_of_kind(name, kind)
+                .iter()
+                .any(|usage| usage.symbol_type == SymbolType::Reference)
+        })
+    }
+
+    /// Names and files of every definition of a kind, sorted by name
+    pub fn definitions_of_kind(&self, kind: wix_references::SymbolKind) -> Vec<(String, PathBuf)> {
+        let Ok(index) = self.index.read() else {
+            return Vec::new();
+        };
+        let mut definitions: Vec<(String, PathBuf)> = index
+            .all_definitions()
+            .into_iter()
+            .filter(|d| d.kind == kind)
+            .map(|d| (d.name.clone(), PathBuf::from(&d.location.file)))
+            .collect();
+        definitions.sort();
+        definitions
+    }
+
+    /// File defining a symbol
+    pub fn definition_file(&self, name: &str, kind: wix_references::SymbolKind) -> Option<PathBuf> {
+        let index = self.index.read().ok()?;
+        index
+            .find_all_definitions(name)
+            .into_iter()
+            .find(|d| d.kind == kind)
+            .map(|d| PathBuf::from(&d.location.file))
+    }
+
+    /// Indexed content of a file, following open buffers
+    pub fn file_content(&self, path: &Path) -> Option<String> {
+        let index = self.index.read().ok()?;
+        index.file_content(&path_key(path)).map(str::to_string)
+    }
+
+    /// Definitions whose name contains `query`, case-insensitively
+    pub fn workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        let Ok(index) = self.index.read() else {
+            return Vec::new();
+        };
+        let query = query.to_lowercase();
+        let mut symbols: Vec<WorkspaceSymbol> = index
+            .all_definitions()
+            .into_iter()
+            .filter(|d| d.name.to_lowercase().contains(&query))
+            .map(|d| WorkspaceSymbol {
+                name: d.name.clone(),
+                kind: symbol_kind(d.kind),
+                container: Some(d.element.clone()),
+                location: to_location(d),
+            })
+            .collect();
+        symbols.sort_by(|a, b| {
+            a.name
+                .cmp(&b.name)
+                .then(a.location.path.cmp(&b.location.path))
+        });
+        symbols.truncate(MAX_WORKSPACE_SYMBOLS);
+        symbols
+    }
+
+    /// Hierarchy item defined or referenced at a 1-based position
+    pub fn hierarchy_item(
+        &self,
+        path: &Path,
+        source: &str,
+        line: u32,
+        column: u32,
+    ) -> Option<HierarchyItem> {
+        self.update(path, source);
+        let hierarchy = self.hierarchy.read().ok()?;
+        let key = hierarchy.node_at(path, line, column)?;
+        hierarchy.node(&key).map(to_hierarchy_item)
+    }
+
+    /// Items linked above `item` through any of `relations`
+    pub fn hierarchy_parents(
+        &self,
+        item: &HierarchyItem,
+        relations: &[Relation],
+    ) -> Vec<HierarchyCall> {
+        self.hierarchy_links(item, relations, true)
+    }
+
+    /// Items linked below `item` through any of `relations`
+    pub fn hierarchy_children(
+        &self,
+        item: &HierarchyItem,
+        relations: &[Relation],
+    ) -> Vec<HierarchyCall> {
+        self.hierarchy_links(item, relations, false)
+    }
+
+    fn hierarchy_links(
+        &self,
+        item: &HierarchyItem,
+        relations: &[Relation],
+        parents: bool,
+    ) -> Vec<HierarchyCall> {
+        let Some(kind) = NodeKind::from_definition(&item.element) else {
+            return Vec::new();
+        };
+        let key = NodeKey::new(kind, item.name.as_str());
+        let Ok(hierarchy) = self.hierarchy.read() else {
+            return Vec::new();
+        };
+        let links = if parents {
+            hierarchy.parents(&key)
+        } else {
+            hierarchy.children(&key)
+        };
+
+        let mut calls: Vec<HierarchyCall> = Vec::new();
+        for link in links
+            .into_iter()
+            .filter(|link| relations.contains(&link.relation))
+        {
+            let other = if parents { &link.parent } else { &link.child };
+            let other_item = match hierarchy.node(other) {
+                Some(node) => to_hierarchy_item(node),
+                None => unresolved_item(other, link),
+            };
+            // Ranges are reported within the including item's file
+            let including = if parents {
+                &other_item.path
+            } else {
+                &item.path
+            };
+            let range = (&link.file == including).then(|| to_hierarchy_range(&link.range));
+            match calls
+                .iter_mut()
+                .find(|c| c.item.element == other_item.element && c.item.name == other_item.name)
+            {
+                Some(call) => call.ranges.extend(range),
+                None => calls.push(HierarchyCall {
+                    item: other_item,
+                    ranges: range.into_iter().collect(),
+                }),
+            }
+        }
+        calls
+    }
+}
+
+impl Default for WorkspaceReferences {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_indexed(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            INDEXED_EXTENSIONS
+                .iter()
+                .any(|indexed| ext.eq_ignore_ascii_case(indexed))
+        })
+}
+
+fn is_skipped_dir(entry: &walkdir::DirEntry) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    entry.file_type().is_dir() && (name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()))
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// Convert an index range (1-based, exclusive end) to a 0-based one
+fn to_range(range: &wix_references::Range) -> SymbolRange {
+    SymbolRange {
+        start_line: range.start.line.saturating_sub(1),
+        start_col: range.start.character.saturating_sub(1),
+        end_line: range.end.line.saturating_sub(1),
+        end_col: range.end.character.saturating_sub(1),
+    }
+}
+
+fn to_location(entry: &SymbolEntry) -> SymbolLocation {
+    SymbolLocation {
+        path: PathBuf::from(&entry.location.file),
+        range: to_range(&entry.id_range),
+        is_definition: entry.symbol_type == SymbolType::Definition,
+    }
+}
+
+/// Convert a hierarchy range (1-based, exclusive end) to a 0-based one
+fn to_hierarchy_range(range: &wix_symbols::Range) -> SymbolRange {
+    SymbolRange {
+        start_line: range.start.line.saturating_sub(1),
+        start_col: range.start.character.saturating_sub(1),
+        end_line: range.end.line.saturating_sub(1),
+        end_col: range.end.character.saturating_sub(1),
+    }
+}
+
+fn to_hierarchy_item(node: &HierarchyNode) -> HierarchyItem {
+    HierarchyItem {
+        name: node.key.id.clone(),
+        kind: hierarchy_kind(node.key.kind),
+        element: node.key.kind.element_name().to_string(),
+        detail: node.detail.clone(),
+        path: node.file.clone(),
+        range: to_hierarchy_range(&node.range),
+        selection_range: to_hierarchy_range(&node.selection_range),
+    }
+}
+
+/// Item for a Ref nothing defines, located at the Ref
+fn unresolved_item(key: &NodeKey, link: &HierarchyLink) -> HierarchyItem {
+    let range = to_hierarchy_range(&link.range);
+    HierarchyItem {
+        name: key.id.clone(),
+        kind: hierarchy_kind(key.kind),
+        element: key.kind.element_name().to_string(),
+        detail: Some("not defined".to_string()),
+        path: link.file.clone(),
+        range,
+        selection_range: range,
+    }
+}
+
+/// Same kinds as workspace symbols use
+fn hierarchy_kind(kind: NodeKind) -> SymbolKind {
+    match kind {
+        NodeKind::Component | NodeKind::ComponentGroup => SymbolKind::Class,
+        NodeKind::Package | NodeKind::Module | NodeKind::Feature | NodeKind::FeatureGroup => {
+            SymbolKind::Module
+        }
+        NodeKind::Directory => SymbolKind::Namespace,
+        NodeKind::File => SymbolKind::File,
+    }
+}
+
+fn symbol_kind(kind: wix_references::SymbolKind) -> SymbolKind {
+    use wix_references::SymbolKind as Wix;
+
+    match kind {
+        Wix::Component | Wix::ComponentGroup => SymbolKind::Class,
+        Wix::Feature | Wix::Package => SymbolKind::Module,
+        Wix::Directory | Wix::Fragment => SymbolKind::Namespace,
+        Wix::Property | Wix::Control => SymbolKind::Property,
+        Wix::CustomAction => SymbolKind::Function,
+        Wix::Binary | Wix::File => SymbolKind::File,
+        Wix::Registry => SymbolKind::Key,
+        Wix::UI | Wix::Dialog => SymbolKind::Struct,
+        Wix::Localization => SymbolKind::String,
+        Wix::Other => SymbolKind::Variable,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PRODUCT: &str = r#"<Wix>
+  <Package Name="App">
+    <Feature Id="Main" Title="!(loc.MainTitle)">
+      <ComponentRef Id="Core" />
+    </Feature>
+  </Package>
+</Wix>"#;
+
+    const COMPONENTS: &str = r#"<Wix>
+  <Fragment>
+    <Component Id="Core" Guid="*" />
+    <Component Id="Extra" Guid="*" />
+  </Fragment>
+</Wix>"#;
+
+    const STRINGS: &str = r#"<WixLocalization Culture="en-us">
+  <String Id="MainTitle">Main</String>
+</WixLocalization>"#;
+
+    fn workspace() -> (TempDir, WorkspaceReferences) {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("product.wxs"), PRODUCT).unwrap();
+        std::fs::write(temp.path().join("components.wxs"), COMPONENTS).unwrap();
+        std::fs::write(temp.path().join("strings.wxl"), STRINGS).unwrap();
+        std::fs::create_dir(temp.path().join("obj")).unwrap();
+        std::fs::write(temp.path().join("obj").join("stale.wxs"), COMPONENTS).unwrap();
+        let references = WorkspaceReferences::for_workspace(temp.path());
+        (temp, references)
+    }
+
+    #[test]
+    fn test_definition_and_references() {
+        let (temp, references) = workspace();
+        let product = temp.path().join("product.wxs");
+
+        let definition = references.definition(&product, PRODUCT, 4, 26).unwrap();
+        assert_eq!(definition.path, temp.path().join("components.wxs"));
+        assert!(definition.is_definition);
+        assert_eq!(
+            (definition.range.start_line, definition.range.start_col),
+            (2, 19)
+        );
+        assert_eq!(definition.range.end_col, 23);
+
+        // The copy under obj/ isn't indexed
+        let usages = references.references(&product, PRODUCT, 4, 26);
+        assert_eq!(usages.len(), 2);
+        assert_eq!(usages.iter().filter(|u| u.is_definition).count(), 1);
+    }
+
+    #[test]
+    fn test_rename_across_files() {
+        let (temp, references) = workspace();
+        let product = temp.path().join("product.wxs");
+
+        let range = references.prepare_rename(&product, PRODUCT, 3, 39).unwrap();
+        assert_eq!((range.start_line, range.start_col), (2, 36));
+
+        let edits = references
+            .rename(&product, PRODUCT, 3, 39, "AppTitle")
+            .unwrap();
+        assert_eq!(edits.len(), 2);
+        assert!(edits
+            .iter()
+            .any(|e| e.path == temp.path().join("strings.wxl")));
+
+        let collision = references.rename(&product, PRODUCT, 4, 26, "Extra");
+        assert!(collision.unwrap_err().contains("already defined"));
+    }
+
+    #[test]
+    fn test_updates_from_buffer_and_disk() {
+        let (temp, references) = workspace();
+        let components = temp.path().join("components.wxs");
+
+        let edited = COMPONENTS.replace("\"Core\"", "\"Renamed\"");
+        references.update(&components, &edited);
+        assert_eq!(references.workspace_symbols("renamed").len(), 1);
+        assert!(references.workspace_symbols("core").is_empty());
+
+        std::fs::remove_file(&components).unwrap();
+        references.reload(&components);
+        assert!(references.workspace_symbols("renamed").is_empty());
+    }
+
+    #[test]
+    fn test_feature_lookups() {
+        let (temp, references) = workspace();
+        use wix_references::SymbolKind as Wix;
+
+        assert!(references.is_referenced("Core", Wix::Component));
+        assert!(!references.is_referenced("Extra", Wix::Component));
+        assert_eq!(
+            references.definitions_of_kind(Wix::Feature),
+            vec![("Main".to_string(), temp.path().join("product.wxs"))]
+        );
+        assert_eq!(
+            references.definition_file("Main", Wix::Feature),
+            Some(temp.path().join("product.wxs"))
+        );
+        assert_eq!(references.definition_file("Main", Wix::Component), None);
+        assert_eq!(
+            references
+                .file_content(&temp.path().join("product.wxs"))
+                .as_deref(),
+            Some(PRODUCT)
+        );
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let (temp, references) = workspace();
+        let product = temp.path().join("product.wxs");
+
+        let core = references.hierarchy_item(&product, PRODUCT, 4, 26).unwrap();
+        assert_eq!(
+            (core.element.as_str(), core.name.as_str()),
+            ("Component", "Core")
+        );
+        assert_eq!(core.path, temp.path().join("components.wxs"));
+
+        let incoming = references.hierarchy_parents(&core, &[Relation::Installs]);
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(incoming[0].item.name, "Main");
+        assert_eq!(incoming[0].ranges.len(), 1);
+        assert_eq!(
+            (
+                incoming[0].ranges[0].start_line,
+                incoming[0].ranges[0].start_col
+            ),
+            (3, 24)
+        );
+
+        let main = &incoming[0].item;
+        let outgoing = references.hierarchy_children(main, &[Relation::Installs]);
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].item.name, "Core");
+        assert_eq!(outgoing[0].ranges.len(), 1);
+
+        // A Ref to nothing is listed at the Ref
+        let edited = PRODUCT.replace("\"Core\" />", "\"Core\" /><ComponentRef Id=\"Missing\" />");
+        references.update(&product, &edited);
+        let outgoing = references.hierarchy_children(main, &[Relation::Installs]);
+        assert_eq!(outgoing.len(), 2);
+        assert_eq!(outgoing[1].item.detail.as_deref(), Some("not defined"));
+        assert_eq!(outgoing[1].item.path, product);
+    }
+
+    #[test]
+    fn test_workspace_symbols() {
+        let (_temp, references) = workspace();
+        let symbols = references.workspace_symbols("");
+        let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Core", "Extra", "Main", "MainTitle"]);
+        assert_eq!(symbols[0].kind, SymbolKind::Class);
+        assert_eq!(symbols[0].container.as_deref(), Some("Component"));
+        assert_eq!(symbols[3].kind, SymbolKind::String);
+    }
+}
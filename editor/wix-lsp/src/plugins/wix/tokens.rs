@@ -0,0 +1,244 @@
+//! Semantic highlighting
+//!
+//! XML grammars color every attribute value alike. Tokens here mark which
+//! values define a name and which refer to one, and pick out the variables
+//! and properties embedded in values, text and preprocessor instructions.
+
+use super::edits::SourceMap;
+use super::parse::ParsedDocument;
+use crate::plugins::traits::{SemanticToken, SemanticTokenKind};
+use roxmltree::{Document, Node};
+use std::ops::Range;
+use std::path::Path;
+use wix_references::{reference_attribute_kind, SymbolKind};
+
+/// A variable or property inside a value, as a byte range of the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: SemanticTokenKind,
+}
+
+/// Tokens of a parsed document, in document order
+pub fn semantic_tokens(parsed: &ParsedDocument) -> Vec<SemanticToken> {
+    let Ok(doc) = parsed.tree() else {
+        return Vec::new();
+    };
+    let map = SourceMap::new(Path::new(""), parsed.text());
+    let mut tokens = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_element()) {
+        name_tokens(&map, node, &mut tokens);
+    }
+    for (start, value) in embedding_values(doc) {
+        for span in embedded_spans(value) {
+            push_token(
+                &map,
+                start + span.start,
+                start + span.end,
+                span.kind,
+                &mut tokens,
+            );
+        }
+    }
+    tokens.sort_by_key(|t| (t.line, t.start_col));
+    tokens
+}
+
+/// Attribute values that define or refer to a name
+fn name_tokens(map: &SourceMap, node: Node, tokens: &mut Vec<SemanticToken>) {
+    let element = node.tag_name().name();
+    for attr in node.attributes() {
+        let kind = match attr.name() {
+            "Id" if SymbolKind::is_reference_element(element) => SemanticTokenKind::Reference,
+            "Id" => SemanticTokenKind::Definition,
+            name if reference_attribute_kind(element, name).is_some() => {
+                SemanticTokenKind::Reference
+            }
+            _ => continue,
+        };
+        // A name built from variables is highlighted piece by piece
+        let range = attr.range_value();
+        if map.text().get(range.clone()) == Some(attr.value())
+            && embedded_spans(attr.value()).is_empty()
+        {
+            push_token(map, range.start, range.end, kind, tokens);
+        }
+    }
+}
+
+/// Text that can embed variables, with its byte offset: attribute values
+/// and text written without entities, and processing instructions
+pub fn embedding_values<'a>(doc: &'a Document) -> Vec<(usize, &'a str)> {
+    let source = doc.input_text();
+    let verbatim = |range: Range<usize>, value: &str| {
+        (!value.is_empty() && source.get(range.clone()) == Some(value)).then_some(range.start)
+    };
+    let mut values = Vec::new();
+    for node in doc.descendants() {
+        if node.is_element() {
+            for attr in node.attributes() {
+                if let Some(start) = verbatim(attr.range_value(), attr.value()) {
+                    values.push((start, &source[attr.range_value()]));
+                }
+            }
+        } else if node.is_text() {
+            let text = node.text().unwrap_or_default();
+            if let Some(start) = verbatim(node.range(), text) {
+                values.push((start, &source[node.range()]));
+            }
+        } else if node.pi().is_some() {
+            values.push((node.range().start, &source[node.range()]));
+        }
+    }
+    values
+}
+
+/// Add a token unless it spans lines, which LSP tokens can't
+fn push_token(
+    map: &SourceMap,
+    start: usize,
+    end: usize,
+    kind: SemanticTokenKind,
+    tokens: &mut Vec<SemanticToken>,
+) {
+    let (line, start_col) = map.position(start);
+    let (end_line, end_col) = map.position(end);
+    if end_line == line && end_col > start_col {
+        tokens.push(SemanticToken {
+            line,
+            start_col,
+            length: end_col - start_col,
+            kind,
+        });
+    }
+}
+
+/// Variables, localization strings and properties inside a value
+pub fn embedded_spans(value: &str) -> Vec<EmbeddedSpan> {
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < value.len() {
+        let rest = &value[i..];
+        let (close, kind) = if rest.starts_with("$(") {
+            (')', Some(SemanticTokenKind::PreprocessorVariable))
+        } else if let Some(name) = rest.strip_prefix("!(") {
+            let kind = if name.starts_with("loc.") {
+                Some(SemanticTokenKind::Localization)
+            } else if ["bind.", "bindpath.", "wix."]
+                .iter()
+                .any(|p| name.starts_with(p))
+            {
+                Some(SemanticTokenKind::BindVariable)
+            } else {
+                None
+            };
+            (')', kind)
+        } else if rest.starts_with('[') {
+            (']', Some(SemanticTokenKind::Property))
+        } else {
+            i += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        };
+
+        let Some(len) = rest.find(close) else {
+            i += 1;
+            continue;
+        };
+        match kind {
+            Some(SemanticTokenKind::Property) if !is_property_name(&rest[1..len]) => i += 1,
+            Some(kind) => {
+                let end = i + len + 1;
+                spans.push(EmbeddedSpan {
+                    start: i,
+                    end,
+                    kind,
+                });
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    spans
+}
+
+/// `[NAME]` names a property; `[#File]`, `[$Component]`, `[%ENV]` and
+/// escapes like `[\[]` are other formatted-string references
+fn is_property_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_texts(source: &str) -> Vec<(String, SemanticTokenKind)> {
+        let lines: Vec<&str> = source.lines().collect();
+        semantic_tokens(&ParsedDocument::new(source))
+            .iter()
+            .map(|t| {
+                let text: String = lines[t.line as usize]
+                    .chars()
+                    .skip(t.start_col as usize)
+                    .take(t.length as usize)
+                    .collect();
+                (text, t.kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_embedded_spans() {
+        let spans = embedded_spans(
+            "$(var.Dir)\\[INSTALLFOLDER] !(loc.Title) [#File] !(bind.FileVersion.App)",
+        );
+        let kinds: Vec<_> = spans.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SemanticTokenKind::PreprocessorVariable,
+                SemanticTokenKind::Property,
+                SemanticTokenKind::Localization,
+                SemanticTokenKind::BindVariable,
+            ]
+        );
+        assert_eq!((spans[0].start, spans[0].end), (0, 10));
+        assert!(embedded_spans("[\\[] [%TEMP] $(var.Unclosed").is_empty());
+    }
+
+    #[test]
+    fn test_definitions_and_references() {
+        let source = r#"<Wix>
+  <?if $(var.Platform) = x64 ?>
+  <Component Id="Core" Directory="INSTALLFOLDER" Guid="*">
+    <File Source="$(var.Bin)\é.exe" />
+  </Component>
+  <?endif?>
+  <ComponentRef Id="Core" />
+  <Property Id="TITLE" Value="!(loc.Title) [ProductName]" />
+</Wix>"#;
+        assert_eq!(
+            token_texts(source),
+            vec![
+                (
+                    "$(var.Platform)".to_string(),
+                    SemanticTokenKind::PreprocessorVariable
+                ),
+                ("Core".to_string(), SemanticTokenKind::Definition),
+                ("INSTALLFOLDER".to_string(), SemanticTokenKind::Reference),
+                (
+                    "$(var.Bin)".to_string(),
+                    SemanticTokenKind::PreprocessorVariable
+                ),
+                ("Core".to_string(), SemanticTokenKind::Reference),
+                ("TITLE".to_string(), SemanticTokenKind::Definition),
+                ("!(loc.Title)".to_string(), SemanticTokenKind::Localization),
+                ("[ProductName]".to_string(), SemanticTokenKind::Property),
+            ]
+        );
+    }
+}
@@ -49,6 +49,26 @@ pub enum ReferenceError {
     /// File not found in the index.
     #[error("File not found in index: {0}")]
     FileNotFound(String),
+
+    /// No symbol at the requested position.
+    #[error("No symbol at {0}:{1}:{2}")]
+    NoSymbol(String, u32, u32),
+
+    /// The symbol has no definition in the indexed files.
+    #[error("'{0}' is not defined in the workspace")]
+    NotDefined(String),
+
+    /// The new name is not a valid identifier.
+    #[error("'{0}' is not a valid identifier")]
+    InvalidName(String),
+
+    /// The new name is already used by a symbol in the same namespace.
+    #[error("'{0}' is already defined at {1}:{2}")]
+    AlreadyDefined(String, String, u32),
+
+    /// The name belongs to Windows Installer and can't be renamed or taken.
+    #[error("'{0}' is a standard directory or property")]
+    StandardName(String),
 }
 
 /// Position in source code (1-based line and column).
@@ -79,7 +99,10 @@ impl Range {
     /// Create a zero-width range at a single point.
     pub fn point(line: u32, character: u32) -> Self {
         let pos = Position::new(line, character);
-        Self { start: pos, end: pos }
+        Self {
+            start: pos,
+            end: pos,
+        }
     }
 
     /// Check if a position is within this range.
@@ -143,6 +166,8 @@ pub enum SymbolKind {
     UI,
     Dialog,
     Control,
+    /// Localization string (`<String Id>` in .wxl, `!(loc.Id)` elsewhere)
+    Localization,
     Other,
 }
 
@@ -164,6 +189,7 @@ impl SymbolKind {
             "UI" | "UIRef" => SymbolKind::UI,
             "Dialog" | "DialogRef" => SymbolKind::Dialog,
             "Control" | "ControlRef" => SymbolKind::Control,
+            "String" => SymbolKind::Localization,
             _ => SymbolKind::Other,
         }
     }
@@ -172,6 +198,96 @@ impl SymbolKind {
     pub fn is_reference_element(name: &str) -> bool {
         name.ends_with("Ref")
     }
+
+    /// Whether Ids of the two kinds can collide.
+    ///
+    /// Directory Ids are properties at run time, so Directories and
+    /// Properties share one namespace.
+    pub fn shares_namespace(self, other: SymbolKind) -> bool {
+        let property_like = |kind| matches!(kind, SymbolKind::Directory | SymbolKind::Property);
+        self == other || (property_like(self) && property_like(other))
+    }
+}
+
+/// Directory Ids defined by Windows Installer (`StandardDirectory` in WiX v4).
+pub const STANDARD_DIRECTORIES: &[&str] = &[
+    "AdminToolsFolder",
+    "AppDataFolder",
+    "CommonAppDataFolder",
+    "CommonFilesFolder",
+    "CommonFiles64Folder",
+    "CommonFiles6432Folder",
+    "DesktopFolder",
+    "FavoritesFolder",
+    "FontsFolder",
+    "LocalAppDataFolder",
+    "MyPicturesFolder",
+    "NetHoodFolder",
+    "PersonalFolder",
+    "PrintHoodFolder",
+    "ProgramFilesFolder",
+    "ProgramFiles64Folder",
+    "ProgramFiles6432Folder",
+    "ProgramMenuFolder",
+    "RecentFolder",
+    "SendToFolder",
+    "StartMenuFolder",
+    "StartupFolder",
+    "SystemFolder",
+    "System16Folder",
+    "System64Folder",
+    "System6432Folder",
+    "TempFolder",
+    "TemplateFolder",
+    "WindowsFolder",
+    "WindowsVolume",
+];
+
+/// Properties that Windows Installer sets or reserves.
+pub const STANDARD_PROPERTIES: &[&str] = &[
+    "ADDDEFAULT",
+    "ADDLOCAL",
+    "ADDSOURCE",
+    "ALLUSERS",
+    "ARPINSTALLLOCATION",
+    "ARPNOMODIFY",
+    "ARPNOREMOVE",
+    "ARPNOREPAIR",
+    "ARPPRODUCTICON",
+    "ARPSYSTEMCOMPONENT",
+    "ComputerName",
+    "INSTALLLEVEL",
+    "Installed",
+    "LogonUser",
+    "Manufacturer",
+    "MsiInstallPerUser",
+    "MsiNTProductType",
+    "OriginalDatabase",
+    "Privileged",
+    "ProductCode",
+    "ProductLanguage",
+    "ProductName",
+    "ProductVersion",
+    "REBOOT",
+    "REINSTALL",
+    "REINSTALLMODE",
+    "REMOVE",
+    "SourceDir",
+    "SystemLanguageID",
+    "TARGETDIR",
+    "UILevel",
+    "UpgradeCode",
+    "UserSID",
+    "VersionNT",
+    "VersionNT64",
+    "WindowsBuild",
+];
+
+/// Whether a name belongs to Windows Installer for symbols of this kind:
+/// the standard directories and properties, for Directories and Properties.
+pub fn is_standard_name(kind: SymbolKind, name: &str) -> bool {
+    matches!(kind, SymbolKind::Directory | SymbolKind::Property)
+        && (STANDARD_DIRECTORIES.contains(&name) || STANDARD_PROPERTIES.contains(&name))
 }
 
 /// A symbol entry (definition or reference).
@@ -187,6 +303,55 @@ pub struct SymbolEntry {
     pub location: Location,
     /// Element name (e.g., "Component", "ComponentRef").
     pub element: String,
+    /// Range of the name itself, e.g. the `Id` attribute value.
+    pub id_range: Range,
+}
+
+/// Attributes that refer to a symbol by Id: (element, attribute, kind).
+const ATTRIBUTE_REFERENCES: &[(&str, &str, SymbolKind)] = &[
+    ("Custom", "Action", SymbolKind::CustomAction),
+    ("Component", "Directory", SymbolKind::Directory),
+    ("ComponentGroup", "Directory", SymbolKind::Directory),
+    ("CopyFile", "DestinationDirectory", SymbolKind::Directory),
+    ("CreateFolder", "Directory", SymbolKind::Directory),
+    ("CustomAction", "BinaryKey", SymbolKind::Binary),
+    ("CustomAction", "BinaryRef", SymbolKind::Binary),
+    ("CustomAction", "Directory", SymbolKind::Directory),
+    ("CustomAction", "FileKey", SymbolKind::File),
+    ("CustomAction", "FileRef", SymbolKind::File),
+    ("CustomAction", "Property", SymbolKind::Property),
+    ("Feature", "ConfigurableDirectory", SymbolKind::Directory),
+    ("Publish", "Dialog", SymbolKind::Dialog),
+    ("Shortcut", "Directory", SymbolKind::Directory),
+    ("Show", "Dialog", SymbolKind::Dialog),
+];
+
+/// Kind of symbol an attribute other than `Id` refers to, e.g.
+/// `Component/@Directory`.
+pub fn reference_attribute_kind(element: &str, attribute: &str) -> Option<SymbolKind> {
+    ATTRIBUTE_REFERENCES
+        .iter()
+        .find(|(e, a, _)| *e == element && *a == attribute)
+        .map(|(_, _, kind)| *kind)
+}
+
+/// Marker that starts a localization reference.
+const LOC_PREFIX: &str = "!(loc.";
+
+/// An edit replacing one occurrence of a renamed symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RenameEdit {
+    pub file: String,
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// Check that a name can be used as a WiX identifier.
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        && name.len() <= 72
 }
 
 /// Index of all symbols and references across files.
@@ -218,11 +383,11 @@ impl ReferenceIndex {
         self.files.insert(path.to_string(), content.to_string());
 
         // Parse XML
-        let doc = Document::parse(content)
-            .map_err(|e| ReferenceError::ParseError(e.to_string()))?;
+        let doc =
+            Document::parse(content).map_err(|e| ReferenceError::ParseError(e.to_string()))?;
 
         // Extract symbols
-        self.extract_symbols(path, content, doc.root_element());
+        self.extract_symbols(path, &SourceText::new(content), doc.root_element());
         Ok(())
     }
 
@@ -247,12 +412,19 @@ impl ReferenceIndex {
 
     /// Update a file in the index.
     ///
-    /// Removes the old content and re-indexes the new content.
+    /// Removes the old content and re-indexes the new content. If the new
+    /// content doesn't parse, the file's previous symbols are kept.
     pub fn update_file(&mut self, path: &str, content: &str) -> Result<(), ReferenceError> {
+        Document::parse(content).map_err(|e| ReferenceError::ParseError(e.to_string()))?;
         self.remove_file(path);
         self.add_file(path, content)
     }
 
+    /// Indexed content of a file.
+    pub fn file_content(&self, path: &str) -> Option<&str> {
+        self.files.get(path).map(String::as_str)
+    }
+
     /// Find the definition of a symbol by name.
     ///
     /// Returns the first definition found, or `None` if not found.
@@ -290,29 +462,24 @@ impl ReferenceIndex {
 
     /// Get the symbol at a specific position in a file.
     ///
-    /// Used for hover and go-to-definition from cursor position.
+    /// Used for hover and go-to-definition from cursor position. A name under
+    /// the cursor wins; otherwise the innermost element containing it.
     pub fn symbol_at_position(&self, file: &str, line: u32, column: u32) -> Option<&SymbolEntry> {
         let pos = Position::new(line, column);
-
-        // Check definitions
-        for entries in self.definitions.values() {
-            for entry in entries {
-                if entry.location.file == file && entry.location.range.contains(pos) {
-                    return Some(entry);
-                }
-            }
-        }
-
-        // Check references
-        for entries in self.references.values() {
-            for entry in entries {
-                if entry.location.file == file && entry.location.range.contains(pos) {
-                    return Some(entry);
-                }
-            }
-        }
-
-        None
+        let candidates = || {
+            self.definitions
+                .values()
+                .chain(self.references.values())
+                .flatten()
+                .filter(|e| e.location.file == file && e.location.range.contains(pos))
+        };
+
+        candidates().find(|e| e.id_range.contains(pos)).or_else(|| {
+            candidates().max_by_key(|e| {
+                let Range { start, end } = e.location.range;
+                (start.line, start.character, u32::MAX - end.line)
+            })
+        })
     }
 
     /// Go to definition from a position.
@@ -320,7 +487,79 @@ impl ReferenceIndex {
     /// Finds the symbol at the given position, then returns its definition.
     pub fn go_to_definition(&self, file: &str, line: u32, column: u32) -> Option<&SymbolEntry> {
         let symbol = self.symbol_at_position(file, line, column)?;
-        self.find_definition(&symbol.name)
+        self.find_definition_of_kind(&symbol.name, symbol.kind)
+    }
+
+    /// Find the definition of a symbol, preferring one of the given kind.
+    pub fn find_definition_of_kind(&self, name: &str, kind: SymbolKind) -> Option<&SymbolEntry> {
+        let defs = self.definitions.get(name)?;
+        defs.iter().find(|d| d.kind == kind).or(defs.first())
+    }
+
+    /// Definition and references of the same symbol (same name and kind).
+    pub fn find_usages_of_kind(&self, name: &str, kind: SymbolKind) -> Vec<&SymbolEntry> {
+        self.find_all_usages(name)
+            .into_iter()
+            .filter(|e| e.kind == kind)
+            .collect()
+    }
+
+    /// Compute the edits that rename the symbol at a position.
+    ///
+    /// Refuses names that aren't valid identifiers, symbols without a
+    /// definition in the index (they may come from an extension), standard
+    /// directories and properties, and names already taken by a symbol in
+    /// the same namespace.
+    pub fn rename(
+        &self,
+        file: &str,
+        line: u32,
+        column: u32,
+        new_name: &str,
+    ) -> Result<Vec<RenameEdit>, ReferenceError> {
+        let symbol = self
+            .symbol_at_position(file, line, column)
+            .ok_or_else(|| ReferenceError::NoSymbol(file.to_string(), line, column))?;
+        if !is_valid_identifier(new_name) {
+            return Err(ReferenceError::InvalidName(new_name.to_string()));
+        }
+        if !self
+            .find_all_definitions(&symbol.name)
+            .iter()
+            .any(|d| d.kind == symbol.kind)
+        {
+            return Err(ReferenceError::NotDefined(symbol.name.clone()));
+        }
+        if new_name == symbol.name {
+            return Ok(Vec::new());
+        }
+        if let Some(name) = [symbol.name.as_str(), new_name]
+            .into_iter()
+            .find(|name| is_standard_name(symbol.kind, name))
+        {
+            return Err(ReferenceError::StandardName(name.to_string()));
+        }
+        if let Some(existing) = self
+            .find_all_definitions(new_name)
+            .into_iter()
+            .find(|d| d.kind.shares_namespace(symbol.kind))
+        {
+            return Err(ReferenceError::AlreadyDefined(
+                new_name.to_string(),
+                existing.location.file.clone(),
+                existing.location.line,
+            ));
+        }
+
+        Ok(self
+            .find_usages_of_kind(&symbol.name, symbol.kind)
+            .into_iter()
+            .map(|e| RenameEdit {
+                file: e.location.file.clone(),
+                range: e.id_range,
+                new_text: new_name.to_string(),
+            })
+            .collect())
     }
 
     /// Get all definitions in the index.
@@ -344,67 +583,145 @@ impl ReferenceIndex {
     }
 
     /// Extract symbols from an XML node recursively.
-    fn extract_symbols(&mut self, path: &str, content: &str, node: Node) {
+    fn extract_symbols(&mut self, path: &str, source: &SourceText, node: Node) {
         let element_name = node.tag_name().name();
-
-        // Check for Id attribute (the main identifier)
-        if let Some(id) = node.attribute("Id") {
-            let kind = SymbolKind::from_element(element_name);
-            let is_ref = SymbolKind::is_reference_element(element_name);
-
-            let pos = self.offset_to_position(content, node.range().start);
-            let end_pos = self.offset_to_position(content, node.range().end);
-            let range = Range::new(pos, end_pos);
-
-            let entry = SymbolEntry {
-                name: id.to_string(),
-                kind,
-                symbol_type: if is_ref {
-                    SymbolType::Reference
-                } else {
-                    SymbolType::Definition
-                },
-                location: Location::new(path, pos.line, pos.character, range),
-                element: element_name.to_string(),
+        let pos = source.position(node.range().start);
+        let end_pos = source.position(node.range().end);
+        let range = Range::new(pos, end_pos);
+
+        for attr in node.attributes() {
+            // Check for Id attribute (the main identifier)
+            let id_symbol = match attr.name() {
+                "Id" => Some(SymbolKind::from_element(element_name)),
+                name => reference_attribute_kind(element_name, name),
             };
+            if let Some(kind) = id_symbol {
+                let value = attr.range_value();
+                let id_range = Range::new(source.position(value.start), source.position(value.end));
+                let entry = SymbolEntry {
+                    name: attr.value().to_string(),
+                    kind,
+                    symbol_type: if attr.name() == "Id"
+                        && !SymbolKind::is_reference_element(element_name)
+                    {
+                        SymbolType::Definition
+                    } else {
+                        SymbolType::Reference
+                    },
+                    location: if attr.name() == "Id" {
+                        Location::new(path, pos.line, pos.character, range)
+                    } else {
+                        Location::new(
+                            path,
+                            id_range.start.line,
+                            id_range.start.character,
+                            id_range,
+                        )
+                    },
+                    element: element_name.to_string(),
+                    id_range,
+                };
+                self.insert(entry);
+            }
+
+            self.extract_loc_references(
+                path,
+                source,
+                element_name,
+                attr.value(),
+                attr.range_value().start,
+            );
+        }
 
-            if is_ref {
-                self.references
-                    .entry(id.to_string())
-                    .or_default()
-                    .push(entry);
-            } else {
-                self.definitions
-                    .entry(id.to_string())
-                    .or_default()
-                    .push(entry);
+        for text in node.children().filter(|n| n.is_text()) {
+            if let Some(value) = text.text() {
+                self.extract_loc_references(path, source, element_name, value, text.range().start);
             }
         }
 
         // Recurse into children
         for child in node.children().filter(|n| n.is_element()) {
-            self.extract_symbols(path, content, child);
+            self.extract_symbols(path, source, child);
         }
     }
 
-    /// Convert byte offset to line/column position.
-    fn offset_to_position(&self, content: &str, offset: usize) -> Position {
-        let mut line = 1u32;
-        let mut col = 1u32;
-
-        for (i, ch) in content.char_indices() {
-            if i >= offset {
+    /// Record `!(loc.Id)` references in an attribute value or text node.
+    ///
+    /// Values containing entities are skipped, since their offsets no longer
+    /// line up with the source.
+    fn extract_loc_references(
+        &mut self,
+        path: &str,
+        source: &SourceText,
+        element: &str,
+        value: &str,
+        start: usize,
+    ) {
+        if source.text.get(start..start + value.len()) != Some(value) {
+            return;
+        }
+        let mut rest = 0;
+        while let Some(found) = value[rest..].find(LOC_PREFIX) {
+            let name_start = rest + found + LOC_PREFIX.len();
+            let Some(len) = value[name_start..].find(')') else {
                 break;
+            };
+            let name = &value[name_start..name_start + len];
+            rest = name_start + len;
+            if name.is_empty() {
+                continue;
             }
-            if ch == '\n' {
-                line += 1;
-                col = 1;
-            } else {
-                col += 1;
-            }
+            let id_range = Range::new(
+                source.position(start + name_start),
+                source.position(start + name_start + len),
+            );
+            self.insert(SymbolEntry {
+                name: name.to_string(),
+                kind: SymbolKind::Localization,
+                symbol_type: SymbolType::Reference,
+                location: Location::new(
+                    path,
+                    id_range.start.line,
+                    id_range.start.character,
+                    id_range,
+                ),
+                element: element.to_string(),
+                id_range,
+            });
         }
+    }
 
-        Position::new(line, col)
+    fn insert(&mut self, entry: SymbolEntry) {
+        let map = match entry.symbol_type {
+            SymbolType::Definition => &mut self.definitions,
+            SymbolType::Reference => &mut self.references,
+        };
+        map.entry(entry.name.clone()).or_default().push(entry);
+    }
+}
+
+/// Source text with line starts, for offset to position lookups.
+struct SourceText<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceText<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    /// Convert byte offset to line/column position.
+    fn position(&self, offset: usize) -> Position {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let col = self.text[line_start..offset.min(self.text.len())]
+            .chars()
+            .count();
+        Position::new(line as u32 + 1, col as u32 + 1)
     }
 }
 
@@ -612,6 +929,11 @@ mod tests {
         assert!(SymbolKind::is_reference_element("ComponentRef"));
         assert!(!SymbolKind::is_reference_element("Feature"));
         assert!(SymbolKind::is_reference_element("FeatureRef"));
+        assert_eq!(
+            reference_attribute_kind("Component", "Directory"),
+            Some(SymbolKind::Directory)
+        );
+        assert_eq!(reference_attribute_kind("Component", "Guid"), None);
     }
 
     #[test]
@@ -657,7 +979,10 @@ mod tests {
     fn test_stats() {
         let mut index = ReferenceIndex::new();
         index
-            .add_file("a.wxs", r#"<Wix><Component Id="C1" /><Component Id="C2" /></Wix>"#)
+            .add_file(
+                "a.wxs",
+                r#"<Wix><Component Id="C1" /><Component Id="C2" /></Wix>"#,
+            )
             .unwrap();
         index
             .add_file("b.wxs", r#"<Wix><ComponentRef Id="C1" /></Wix>"#)
@@ -688,4 +1013,152 @@ mod tests {
         let result = index.add_file("bad.wxs", "not valid xml <>");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_update_file_keeps_symbols_on_parse_error() {
+        let mut index = ReferenceIndex::new();
+        index
+            .add_file("a.wxs", r#"<Wix><Component Id="C1" /></Wix>"#)
+            .unwrap();
+        assert!(index.update_file("a.wxs", "<Wix><Component").is_err());
+        assert!(index.find_definition("C1").is_some());
+    }
+
+    #[test]
+    fn test_attribute_and_localization_references() {
+        let mut index = ReferenceIndex::new();
+        index
+            .add_file(
+                "product.wxs",
+                r#"<Wix>
+  <Directory Id="INSTALLFOLDER" Name="!(loc.ProductName)">
+    <Component Id="Main" />
+  </Directory>
+  <Component Id="Other" Directory="INSTALLFOLDER" />
+  <CustomAction Id="RunIt" BinaryRef="Tool" />
+  <Custom Action="RunIt" />
+</Wix>"#,
+            )
+            .unwrap();
+        index
+            .add_file(
+                "en-us.wxl",
+                r#"<WixLocalization><String Id="ProductName">App</String></WixLocalization>"#,
+            )
+            .unwrap();
+
+        assert_eq!(index.find_references("INSTALLFOLDER").len(), 1);
+        assert_eq!(index.find_references("RunIt").len(), 1);
+        assert_eq!(index.find_references("Tool")[0].kind, SymbolKind::Binary);
+
+        let loc = index.find_references("ProductName");
+        assert_eq!(loc.len(), 1);
+        assert_eq!(loc[0].kind, SymbolKind::Localization);
+        assert_eq!(loc[0].id_range.start, Position::new(2, 45));
+        let def = index.go_to_definition("product.wxs", 2, 47).unwrap();
+        assert_eq!(def.location.file, "en-us.wxl");
+
+        // The innermost element wins when not on a name
+        let inner = index.symbol_at_position("product.wxs", 3, 6).unwrap();
+        assert_eq!(inner.name, "Main");
+        // A name beats its enclosing element
+        let on_name = index.symbol_at_position("product.wxs", 5, 40).unwrap();
+        assert_eq!(on_name.name, "INSTALLFOLDER");
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut index = ReferenceIndex::new();
+        index
+            .add_file(
+                "product.wxs",
+                r#"<Wix><Component Id="Main" /><Property Id="Main" /><Component Id="Taken" /></Wix>"#,
+            )
+            .unwrap();
+        index
+            .add_file(
+                "include.wxi",
+                r#"<Include><ComponentRef Id="Main" /><UIRef Id="WixUI_Minimal" /></Include>"#,
+            )
+            .unwrap();
+
+        let edits = index.rename("include.wxi", 1, 30, "Core").unwrap();
+        assert_eq!(edits.len(), 2);
+        assert!(edits.iter().all(|e| e.new_text == "Core"));
+        let def = edits.iter().find(|e| e.file == "product.wxs").unwrap();
+        assert_eq!(
+            def.range,
+            Range::new(Position::new(1, 21), Position::new(1, 25))
+        );
+
+        // Same name, different kind: the property is left alone, and its name
+        // doesn't count as a collision
+        assert!(edits.iter().all(|e| e.range.start.character != 44));
+
+        assert!(matches!(
+            index.rename("include.wxi", 1, 30, "Taken"),
+            Err(ReferenceError::AlreadyDefined(..))
+        ));
+        assert!(matches!(
+            index.rename("include.wxi", 1, 30, "not valid"),
+            Err(ReferenceError::InvalidName(_))
+        ));
+        assert!(matches!(
+            index.rename("include.wxi", 1, 50, "Other"),
+            Err(ReferenceError::NotDefined(_))
+        ));
+        assert!(index
+            .rename("include.wxi", 1, 30, "Main")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rename_directory_property_namespace() {
+        let mut index = ReferenceIndex::new();
+        index
+            .add_file(
+                "product.wxs",
+                r#"<Wix><Directory Id="INSTALLDIR" /><Property Id="Taken" /><StandardDirectory Id="ProgramFilesFolder" /></Wix>"#,
+            )
+            .unwrap();
+
+        // Directories and properties collide with each other
+        assert!(matches!(
+            index.rename("product.wxs", 1, 23, "Taken"),
+            Err(ReferenceError::AlreadyDefined(..))
+        ));
+        assert!(matches!(
+            index.rename("product.wxs", 1, 50, "INSTALLDIR"),
+            Err(ReferenceError::AlreadyDefined(..))
+        ));
+
+        // Standard names are neither taken nor given up
+        assert!(matches!(
+            index.rename("product.wxs", 1, 23, "ProductName"),
+            Err(ReferenceError::StandardName(_))
+        ));
+        assert!(matches!(
+            index.rename("product.wxs", 1, 50, "TARGETDIR"),
+            Err(ReferenceError::StandardName(_))
+        ));
+        assert!(matches!(
+            index.rename("product.wxs", 1, 83, "MyFiles"),
+            Err(ReferenceError::StandardName(_))
+        ));
+
+        assert_eq!(
+            index.rename("product.wxs", 1, 23, "APPDIR").unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_is_valid_identifier() {
+        assert!(is_valid_identifier("INSTALLFOLDER"));
+        assert!(is_valid_identifier("_Comp.1"));
+        assert!(!is_valid_identifier("1Comp"));
+        assert!(!is_valid_identifier("My Comp"));
+        assert!(!is_valid_identifier(""));
+    }
 }
@@ -0,0 +1,392 @@
+//! Snippet body syntax
+//!
+//! Bodies are written once in the TextMate/LSP syntax VS Code uses: `$1`,
+//! `${1:placeholder}`, `${1|a,b|}` and variables such as `$UUID`. Editors
+//! that lack choices or variables get them rendered down to placeholders.
+
+/// Placeholder text used where an editor cannot generate a GUID
+pub const GUID_PLACEHOLDER: &str = "PUT-GUID-HERE";
+
+/// Variable expanding to a fresh GUID
+pub const UUID_VARIABLE: &str = "UUID";
+
+/// A parsed piece of a snippet body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Text(String),
+    /// `$1`, `${1}` or `${1:placeholder}`; index 0 is the final cursor
+    TabStop {
+        index: u32,
+        placeholder: Vec<Token>,
+    },
+    /// `${1|one,two|}`
+    Choice {
+        index: u32,
+        options: Vec<String>,
+    },
+    /// `$NAME` or `${NAME:default}`
+    Variable {
+        name: String,
+        default: Vec<Token>,
+    },
+}
+
+/// What an editor's snippet engine supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    /// Full syntax (VS Code, LuaSnip)
+    Lsp,
+    /// Choices but no `$UUID` (Helix)
+    NoVariables,
+    /// Neither choices nor `$UUID` (Sublime Text)
+    FieldsOnly,
+}
+
+/// Parse a snippet body; malformed constructs are kept as text
+pub fn parse(body: &str) -> Vec<Token> {
+    let chars: Vec<char> = body.chars().collect();
+    let mut pos = 0;
+    parse_until(&chars, &mut pos, false)
+}
+
+fn parse_until(chars: &[char], pos: &mut usize, nested: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+
+    while *pos < chars.len() {
+        let c = chars[*pos];
+        match c {
+            '\\' if matches!(chars.get(*pos + 1), Some('$' | '}' | '\\')) => {
+                text.push(chars[*pos + 1]);
+                *pos += 2;
+            }
+            '}' if nested => break,
+            '$' => match parse_dollar(chars, *pos) {
+                Some((token, end)) => {
+                    flush(&mut tokens, &mut text);
+                    tokens.push(token);
+                    *pos = end;
+                }
+                None => {
+                    text.push('$');
+                    *pos += 1;
+                }
+            },
+            _ => {
+                text.push(c);
+                *pos += 1;
+            }
+        }
+    }
+
+    flush(&mut tokens, &mut text);
+    tokens
+}
+
+fn flush(tokens: &mut Vec<Token>, text: &mut String) {
+    if !text.is_empty() {
+        tokens.push(Token::Text(std::mem::take(text)));
+    }
+}
+
+/// Parse the construct starting at the `$` at `start`, returning it and the
+/// position just past it
+fn parse_dollar(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let mut pos = start + 1;
+    match chars.get(pos)? {
+        c if c.is_ascii_digit() => {
+            let index = read_index(chars, &mut pos)?;
+            Some((
+                Token::TabStop {
+                    index,
+                    placeholder: Vec::new(),
+                },
+                pos,
+            ))
+        }
+        c if is_name_start(*c) => {
+            let name = read_name(chars, &mut pos);
+            Some((
+                Token::Variable {
+                    name,
+                    default: Vec::new(),
+                },
+                pos,
+            ))
+        }
+        '{' => {
+            pos += 1;
+            let first = *chars.get(pos)?;
+            if first.is_ascii_digit() {
+                let index = read_index(chars, &mut pos)?;
+                match chars.get(pos)? {
+                    '}' => Some((
+                        Token::TabStop {
+                            index,
+                            placeholder: Vec::new(),
+                        },
+                        pos + 1,
+                    )),
+                    ':' => {
+                        pos += 1;
+                        let placeholder = parse_until(chars, &mut pos, true);
+                        (chars.get(pos) == Some(&'}'))
+                            .then_some((Token::TabStop { index, placeholder }, pos + 1))
+                    }
+                    '|' => {
+                        pos += 1;
+                        let options = read_choices(chars, &mut pos)?;
+                        Some((Token::Choice { index, options }, pos))
+                    }
+                    _ => None,
+                }
+            } else if is_name_start(first) {
+                let name = read_name(chars, &mut pos);
+                match chars.get(pos)? {
+                    '}' => Some((
+                        Token::Variable {
+                            name,
+                            default: Vec::new(),
+                        },
+                        pos + 1,
+                    )),
+                    ':' => {
+                        pos += 1;
+                        let default = parse_until(chars, &mut pos, true);
+                        (chars.get(pos) == Some(&'}'))
+                            .then_some((Token::Variable { name, default }, pos + 1))
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn read_index(chars: &[char], pos: &mut usize) -> Option<u32> {
+    let start = *pos;
+    while chars.get(*pos).is_some_and(|c| c.is_ascii_digit()) {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect::<String>().parse().ok()
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn read_name(chars: &[char], pos: &mut usize) -> String {
+    let start = *pos;
+    while chars
+        .get(*pos)
+        .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+    {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect()
+}
+
+/// Read `a,b|}` after the opening `${1|`
+fn read_choices(chars: &[char], pos: &mut usize) -> Option<Vec<String>> {
+    let mut options = Vec::new();
+    let mut current = String::new();
+    loop {
+        match chars.get(*pos)? {
+            '\\' if matches!(chars.get(*pos + 1), Some(',' | '|' | '\\' | '$' | '}')) => {
+                current.push(chars[*pos + 1]);
+                *pos += 2;
+            }
+            ',' => {
+                options.push(std::mem::take(&mut current));
+                *pos += 1;
+            }
+            '|' if chars.get(*pos + 1) == Some(&'}') => {
+                options.push(current);
+                *pos += 2;
+                return Some(options);
+            }
+            c => {
+                current.push(*c);
+                *pos += 1;
+            }
+        }
+    }
+}
+
+/// Render tokens back to snippet syntax for an editor
+pub fn render(tokens: &[Token], dialect: Dialect) -> String {
+    let mut out = String::new();
+    render_into(&mut out, tokens, dialect, false);
+    out
+}
+
+fn render_into(out: &mut String, tokens: &[Token], dialect: Dialect, nested: bool) {
+    for token in tokens {
+        match token {
+            Token::Text(text) => escape_into(out, text, nested),
+            Token::TabStop { index, placeholder } if placeholder.is_empty() => {
+                out.push_str(&format!("${index}"));
+            }
+            Token::TabStop { index, placeholder } => {
+                out.push_str(&format!("${{{index}:"));
+                render_into(out, placeholder, dialect, true);
+                out.push('}');
+            }
+            Token::Choice { index, options } => match dialect {
+                Dialect::FieldsOnly => {
+                    out.push_str(&format!("${{{index}:"));
+                    escape_into(out, options.first().map_or("", String::as_str), true);
+                    out.push('}');
+                }
+                Dialect::Lsp | Dialect::NoVariables => {
+                    let options: Vec<String> = options.iter().map(|o| escape_choice(o)).collect();
+                    out.push_str(&format!("${{{index}|{}|}}", options.join(",")));
+                }
+            },
+            Token::Variable { name, default } => match dialect {
+                Dialect::Lsp if default.is_empty() => out.push_str(&format!("${name}")),
+                Dialect::Lsp => {
+                    out.push_str(&format!("${{{name}:"));
+                    render_into(out, default, dialect, true);
+                    out.push('}');
+                }
+                Dialect::NoVariables | Dialect::FieldsOnly => {
+                    if name == UUID_VARIABLE && default.is_empty() {
+                        escape_into(out, GUID_PLACEHOLDER, nested);
+                    } else {
+                        render_into(out, default, dialect, nested);
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn escape_into(out: &mut String, text: &str, nested: bool) {
+    for c in text.chars() {
+        if c == '$' || c == '\\' || (nested && c == '}') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Escape literal text for use anywhere in a body
+pub(crate) fn escape(text: &str) -> String {
+    let mut out = String::new();
+    escape_into(&mut out, text, true);
+    out
+}
+
+/// Escape one option of a choice list
+pub(crate) fn escape_choice(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('|', "\\|")
+}
+
+/// Plain text of tokens as inserted before any editing
+pub fn plain_text(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    for token in tokens {
+        match token {
+            Token::Text(text) => out.push_str(text),
+            Token::TabStop { placeholder, .. } => out.push_str(&plain_text(placeholder)),
+            Token::Choice { options, .. } => {
+                out.push_str(options.first().map_or("", String::as_str))
+            }
+            Token::Variable { name, default } => {
+                if name == UUID_VARIABLE && default.is_empty() {
+                    out.push_str(GUID_PLACEHOLDER);
+                } else {
+                    out.push_str(&plain_text(default));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Token {
+        Token::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_parse_constructs() {
+        let tokens = parse("<A Id=\"${1:Name}\" Kind=\"${2|a,b|}\" Guid=\"${3:$UUID}\">$0</A>");
+        assert_eq!(
+            tokens,
+            vec![
+                text("<A Id=\""),
+                Token::TabStop {
+                    index: 1,
+                    placeholder: vec![text("Name")]
+                },
+                text("\" Kind=\""),
+                Token::Choice {
+                    index: 2,
+                    options: vec!["a".into(), "b".into()]
+                },
+                text("\" Guid=\""),
+                Token::TabStop {
+                    index: 3,
+                    placeholder: vec![Token::Variable {
+                        name: "UUID".into(),
+                        default: vec![]
+                    }],
+                },
+                text("\">"),
+                Token::TabStop {
+                    index: 0,
+                    placeholder: vec![]
+                },
+                text("</A>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preprocessor_variables_are_text() {
+        let body = "Source=\"$(var.SourceDir)\\\\app.exe\"";
+        assert_eq!(
+            parse(body),
+            vec![text("Source=\"$(var.SourceDir)\\app.exe\"")]
+        );
+        assert_eq!(
+            render(&parse(body), Dialect::Lsp),
+            "Source=\"\\$(var.SourceDir)\\\\app.exe\""
+        );
+    }
+
+    #[test]
+    fn test_unterminated_is_text() {
+        assert_eq!(parse("${1:open"), vec![text("${1:open")]);
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        let body = "<A B=\"${1:x\\}y}\" C=\"${2|perMachine,perUser|}\" D=\"${3:$UUID}\" />$0";
+        assert_eq!(render(&parse(body), Dialect::Lsp), body);
+    }
+
+    #[test]
+    fn test_render_dialects() {
+        let tokens = parse("${1|perMachine,perUser|} ${2:$UUID}");
+        assert_eq!(
+            render(&tokens, Dialect::NoVariables),
+            "${1|perMachine,perUser|} ${2:PUT-GUID-HERE}"
+        );
+        assert_eq!(
+            render(&tokens, Dialect::FieldsOnly),
+            "${1:perMachine} ${2:PUT-GUID-HERE}"
+        );
+        assert_eq!(plain_text(&tokens), "perMachine PUT-GUID-HERE");
+    }
+}
@@ -0,0 +1,422 @@
+//! Snippet export for each editor
+//!
+//! VS Code and LuaSnip take the bodies as written. Helix (through a
+//! snippet language server) has no `$UUID`, Sublime Text has neither
+//! choices nor `$UUID`, and JetBrains templates use their own `$NAME$`
+//! variables. Parent tags are kept in the JSON export and become a LuaSnip
+//! `show_condition`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::body::{self, Dialect, Token, UUID_VARIABLE};
+use crate::{Snippet, SnippetError, SnippetLibrary};
+
+/// Sublime Text scope the completions apply to
+const SUBLIME_SCOPE: &str = "text.xml.wix";
+
+/// Language ids Helix snippets are offered for
+const HELIX_SCOPES: &[&str] = &["xml"];
+
+/// JetBrains Live Template group name
+const JETBRAINS_GROUP: &str = "WiX";
+
+/// JetBrains expression generating a GUID
+const JETBRAINS_UUID: &str = "groovyScript(\"UUID.randomUUID().toString().toUpperCase()\")";
+
+/// Editor formats snippets can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Library JSON including parent tags, for completion engines
+    Json,
+    /// VS Code snippets file
+    Vscode,
+    /// Sublime Text completions
+    Sublime,
+    /// JetBrains Live Templates
+    Jetbrains,
+    /// Neovim LuaSnip module
+    Luasnip,
+    /// Helix snippets for simple-completion-language-server
+    Helix,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 6] = [
+        ExportFormat::Json,
+        ExportFormat::Vscode,
+        ExportFormat::Sublime,
+        ExportFormat::Jetbrains,
+        ExportFormat::Luasnip,
+        ExportFormat::Helix,
+    ];
+
+    /// Conventional file name for the export
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "wix-snippets.json",
+            ExportFormat::Vscode => "wix.code-snippets",
+            ExportFormat::Sublime => "WiX.sublime-completions",
+            ExportFormat::Jetbrains => "WiX.xml",
+            ExportFormat::Luasnip => "wix.lua",
+            ExportFormat::Helix => "wix.toml",
+        }
+    }
+}
+
+impl SnippetLibrary {
+    /// Export the library in an editor format
+    pub fn export(&self, format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => self.to_json(),
+            ExportFormat::Vscode => self.to_vscode_json(),
+            ExportFormat::Sublime => self.to_sublime_completions(),
+            ExportFormat::Jetbrains => self.to_jetbrains_xml(),
+            ExportFormat::Luasnip => self.to_luasnip(),
+            ExportFormat::Helix => self.to_helix_toml(),
+        }
+    }
+
+    /// Write every export format into `dir`
+    pub fn write_all(&self, dir: &Path) -> Result<(), SnippetError> {
+        fs::create_dir_all(dir)?;
+        for format in ExportFormat::ALL {
+            fs::write(dir.join(format.file_name()), self.export(format))?;
+        }
+        Ok(())
+    }
+
+    /// All snippets with their parent tags
+    pub fn to_json(&self) -> String {
+        let snippets: Vec<&Snippet> = self.all().collect();
+        serde_json::to_string_pretty(&snippets).unwrap()
+    }
+
+    pub fn to_sublime_completions(&self) -> String {
+        let completions: Vec<serde_json::Value> = self
+            .all()
+            .map(|s| {
+                serde_json::json!({
+                    "trigger": s.prefix,
+                    "annotation": "WiX",
+                    "kind": "snippet",
+                    "details": escape_xml(&s.description),
+                    "contents": s.render(Dialect::FieldsOnly),
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({
+            "scope": SUBLIME_SCOPE,
+            "completions": completions,
+        }))
+        .unwrap()
+    }
+
+    pub fn to_jetbrains_xml(&self) -> String {
+        let mut out = format!("<templateSet group=\"{JETBRAINS_GROUP}\">\n");
+        for snippet in self.all() {
+            out.push_str(&jetbrains_template(snippet));
+        }
+        out.push_str("</templateSet>\n");
+        out
+    }
+
+    /// LuaSnip module returning the snippets, for `from_lua` loaders
+    pub fn to_luasnip(&self) -> String {
+        let mut out = String::from(LUASNIP_HEADER);
+        out.push_str("\nreturn {\n");
+        for snippet in self.all() {
+            out.push_str(&format!(
+                "  parse({{ trig = {}, name = {}, dscr = {}",
+                lua_string(&snippet.prefix),
+                lua_string(&snippet.prefix),
+                lua_string(&snippet.description)
+            ));
+            if !snippet.parents.is_empty() {
+                let parents: Vec<String> = snippet.parents.iter().map(|p| lua_string(p)).collect();
+                out.push_str(&format!(
+                    ", show_condition = inside({{ {} }})",
+                    parents.join(", ")
+                ));
+            }
+            out.push_str(&format!(" }}, {}),\n", lua_string(&snippet.body_text())));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Snippets in the TOML format of simple-completion-language-server
+    pub fn to_helix_toml(&self) -> String {
+        let scopes: Vec<String> = HELIX_SCOPES.iter().map(|s| toml_string(s)).collect();
+        let mut out = String::from("# Generated by wix-snippets. Do not edit.\n");
+        for snippet in self.all() {
+            out.push_str(&format!(
+                "\n[[snippets]]\nprefix = {}\nscope = [{}]\nbody = {}\ndescription = {}\n",
+                toml_string(&snippet.prefix),
+                scopes.join(", "),
+                toml_string(&snippet.render(Dialect::NoVariables)),
+                toml_string(&snippet.description)
+            ));
+        }
+        out
+    }
+}
+
+/// A JetBrains template variable
+struct Variable {
+    name: String,
+    expression: String,
+    default: String,
+    stop: bool,
+}
+
+fn jetbrains_template(snippet: &Snippet) -> String {
+    let mut value = String::new();
+    let mut stops = BTreeMap::new();
+    let mut generated = Vec::new();
+    jetbrains_value(
+        &body::parse(&snippet.body_text()),
+        &mut value,
+        &mut stops,
+        &mut generated,
+    );
+
+    let mut out = format!(
+        "  <template name=\"{}\" value=\"{}\" description=\"{}\" toReformat=\"false\" toShortenFQNames=\"false\">\n",
+        escape_xml(&snippet.prefix),
+        escape_xml(&value),
+        escape_xml(&snippet.description)
+    );
+    for variable in stops.values().chain(&generated) {
+        out.push_str(&format!(
+            "    <variable name=\"{}\" expression=\"{}\" defaultValue=\"{}\" alwaysStopAt=\"{}\" />\n",
+            variable.name,
+            escape_xml(&variable.expression),
+            escape_xml(&variable.default),
+            variable.stop
+        ));
+    }
+    out.push_str("    <context>\n      <option name=\"XML\" value=\"true\" />\n    </context>\n");
+    out.push_str("  </template>\n");
+    out
+}
+
+/// Build a template value, collecting tab stops by index and generated
+/// GUID variables in order
+fn jetbrains_value(
+    tokens: &[Token],
+    value: &mut String,
+    stops: &mut BTreeMap<u32, Variable>,
+    generated: &mut Vec<Variable>,
+) {
+    for token in tokens {
+        match token {
+            Token::Text(text) => value.push_str(&text.replace('$', "$$")),
+            Token::TabStop { index: 0, .. } => value.push_str("$END$"),
+            Token::TabStop { index, placeholder } => {
+                let name = format!("V{index}");
+                value.push_str(&format!("${name}$"));
+                stops.entry(*index).or_insert_with(|| {
+                    if is_uuid(placeholder) {
+                        Variable {
+                            name,
+                            expression: JETBRAINS_UUID.to_string(),
+                            default: String::new(),
+                            stop: true,
+                        }
+                    } else {
+                        Variable {
+                            name,
+                            expression: String::new(),
+                            default: quote(&body::plain_text(placeholder)),
+                            stop: true,
+                        }
+                    }
+                });
+            }
+            Token::Choice { index, options } => {
+                let name = format!("V{index}");
+                value.push_str(&format!("${name}$"));
+                stops.entry(*index).or_insert_with(|| {
+                    let options: Vec<String> = options.iter().map(|o| quote(o)).collect();
+                    Variable {
+                        name,
+                        expression: format!("enum({})", options.join(",")),
+                        default: String::new(),
+                        stop: true,
+                    }
+                });
+            }
+            Token::Variable { name, default } if name == UUID_VARIABLE && default.is_empty() => {
+                let name = format!("GUID{}", generated.len() + 1);
+                value.push_str(&format!("${name}$"));
+                generated.push(Variable {
+                    name,
+                    expression: JETBRAINS_UUID.to_string(),
+                    default: String::new(),
+                    stop: false,
+                });
+            }
+            Token::Variable { default, .. } => jetbrains_value(default, value, stops, generated),
+        }
+    }
+}
+
+fn is_uuid(tokens: &[Token]) -> bool {
+    matches!(tokens, [Token::Variable { name, default }] if name == UUID_VARIABLE && default.is_empty())
+}
+
+/// JetBrains string literal expression
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#10;"),
+            '\t' => out.push_str("&#9;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn lua_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// TOML basic string; JSON string escapes are valid TOML
+fn toml_string(text: &str) -> String {
+    serde_json::to_string(text).unwrap()
+}
+
+const LUASNIP_HEADER: &str = r#"-- Generated by wix-snippets. Do not edit.
+
+local ls = require("luasnip")
+local parse = ls.parser.parse_snippet
+
+-- Innermost element still open before the cursor
+local function enclosing_element()
+  local row, col = unpack(vim.api.nvim_win_get_cursor(0))
+  local lines = vim.api.nvim_buf_get_lines(0, 0, row, false)
+  lines[#lines] = lines[#lines]:sub(1, col)
+  local text = table.concat(lines, "\n"):gsub("<!%-%-.-%-%->", ""):gsub("<%?.-%?>", "")
+  local stack = {}
+  for close, name, empty in text:gmatch("<(/?)([%w_:%.%-]+)[^>]-(/?)>") do
+    if close == "/" then
+      if stack[#stack] == name then
+        table.remove(stack)
+      end
+    elseif empty == "" then
+      table.insert(stack, name)
+    end
+  end
+  return stack[#stack]
+end
+
+-- Offer a snippet only inside one of the given elements
+local function inside(parents)
+  return function()
+    return vim.tbl_contains(parents, enclosing_element())
+  end
+end
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> SnippetLibrary {
+        let mut library = SnippetLibrary::empty();
+        library.add(
+            Snippet::new(
+                "Package",
+                vec![
+                    "<Package Scope=\"${1|perMachine,perUser|}\" UpgradeCode=\"${2:$UUID}\">",
+                    "\t<File Source=\"$(var.Dir)\\\\${3:app.exe}\" />$0",
+                    "</Package>",
+                ],
+                "Package \"root\"",
+            )
+            .with_parents(vec!["Wix".to_string()]),
+        );
+        library
+    }
+
+    #[test]
+    fn test_json_keeps_parents() {
+        let json = library().to_json();
+        assert!(json.contains("\"parents\": [\n      \"Wix\"\n    ]"));
+    }
+
+    #[test]
+    fn test_sublime_completions() {
+        let completions = library().to_sublime_completions();
+        assert!(completions.contains("\"scope\": \"text.xml.wix\""));
+        assert!(completions.contains("${1:perMachine}"));
+        assert!(completions.contains("${2:PUT-GUID-HERE}"));
+    }
+
+    #[test]
+    fn test_jetbrains_template() {
+        let xml = library().to_jetbrains_xml();
+        assert!(xml.starts_with("<templateSet group=\"WiX\">"));
+        assert!(xml.contains(
+            "value=\"&lt;Package Scope=&quot;$V1$&quot; UpgradeCode=&quot;$V2$&quot;&gt;&#10;&#9;&lt;File Source=&quot;$$(var.Dir)\\$V3$&quot; /&gt;$END$&#10;&lt;/Package&gt;\""
+        ));
+        assert!(xml.contains(
+            "<variable name=\"V1\" expression=\"enum(&quot;perMachine&quot;,&quot;perUser&quot;)\" defaultValue=\"\" alwaysStopAt=\"true\" />"
+        ));
+        assert!(xml.contains("<variable name=\"V2\" expression=\"groovyScript("));
+        assert!(xml.contains(
+            "<variable name=\"V3\" expression=\"\" defaultValue=\"&quot;app.exe&quot;\" alwaysStopAt=\"true\" />"
+        ));
+        assert!(xml.contains("<option name=\"XML\" value=\"true\" />"));
+    }
+
+    #[test]
+    fn test_luasnip() {
+        let lua = library().to_luasnip();
+        assert!(lua.contains(
+            "parse({ trig = \"Package\", name = \"Package\", dscr = \"Package \\\"root\\\"\", show_condition = inside({ \"Wix\" }) }, \"<Package Scope=\\\"${1|perMachine,perUser|}\\\""
+        ));
+        assert!(lua.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn test_helix_toml() {
+        let toml = library().to_helix_toml();
+        assert!(toml.contains("[[snippets]]\nprefix = \"Package\"\nscope = [\"xml\"]\n"));
+        assert!(toml.contains("${1|perMachine,perUser|}"));
+        assert!(toml.contains("${2:PUT-GUID-HERE}"));
+        assert!(!toml.contains("$UUID"));
+    }
+
+    #[test]
+    fn test_write_all() {
+        let dir = tempfile::tempdir().unwrap();
+        library().write_all(dir.path()).unwrap();
+        for format in ExportFormat::ALL {
+            assert!(dir.path().join(format.file_name()).exists());
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Snippet generation from wix-data
+//!
+//! Every element gets a snippet with its `Id` and required attributes as
+//! tab stops: enumerations and yes/no attributes become choice lists with
+//! the default first and GUID attributes a `$UUID` placeholder. The
+//! hand-written entries of the snippets table are added on top. Snippets
+//! are tagged with the parents of their root element.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use wix_data::models::{Attribute, AttributeType, Element};
+use wix_data::WixData;
+
+use crate::body::{escape, escape_choice, GUID_PLACEHOLDER, UUID_VARIABLE};
+use crate::{Snippet, SnippetError, SnippetLibrary};
+
+/// Namespace of the core WiX schema; its elements are written unprefixed
+const CORE_NAMESPACE: &str = "wix";
+
+impl SnippetLibrary {
+    /// Generate snippets from the element, attribute and snippet tables
+    pub fn from_wix_data(data: &WixData) -> Result<Self, SnippetError> {
+        let elements = data.search_elements("", i64::MAX as usize)?;
+        let mut namespaces = HashMap::new();
+        for element in &elements {
+            namespaces
+                .entry(element.name.clone())
+                .or_insert_with(|| element.namespace.clone());
+        }
+        let qualify = |name: &str| match namespaces.get(name) {
+            Some(ns) if ns != CORE_NAMESPACE && !ns.is_empty() => format!("{ns}:{name}"),
+            _ => name.to_string(),
+        };
+        let parents_of = |name: &str| -> Result<Vec<String>, SnippetError> {
+            let local = name.rsplit(':').next().unwrap_or(name);
+            Ok(data
+                .get_parents(local)?
+                .iter()
+                .map(|p| qualify(p))
+                .collect())
+        };
+
+        let mut library = Self::empty();
+
+        for element in &elements {
+            let tag = qualify(&element.name);
+            let attributes = data.get_attributes(&element.name)?;
+            let container = !data.get_children(&element.name)?.is_empty();
+            let snippet = element_snippet(&tag, element, &attributes, container)
+                .with_parents(parents_of(&element.name)?);
+            library.add(snippet);
+        }
+
+        for row in data.get_all_snippets()? {
+            let body = row
+                .body
+                .replace(GUID_PLACEHOLDER, &format!("${UUID_VARIABLE}"));
+            // Whole-file templates start with the XML declaration
+            let parents = match root_element(&body) {
+                Some(root) if !body.trim_start().starts_with("<?xml") => parents_of(root)?,
+                _ => Vec::new(),
+            };
+            library.add(Snippet {
+                prefix: row.prefix,
+                body: body.lines().map(String::from).collect(),
+                description: row.description.unwrap_or(row.name),
+                scope: Some(row.scope),
+                parents,
+            });
+        }
+
+        Ok(library)
+    }
+
+    /// Generate snippets from a wix-data database file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SnippetError> {
+        Self::from_wix_data(&WixData::open(path)?)
+    }
+
+    /// Generate snippets from the default wix-data location
+    pub fn open_default() -> Result<Self, SnippetError> {
+        Self::from_wix_data(&WixData::open_default()?)
+    }
+}
+
+/// Snippet inserting `tag` with its `Id` and required attributes
+fn element_snippet(
+    tag: &str,
+    element: &Element,
+    attributes: &[Attribute],
+    container: bool,
+) -> Snippet {
+    let mut selected: Vec<&Attribute> = attributes
+        .iter()
+        .filter(|a| a.required || a.name == "Id")
+        .collect();
+    selected.sort_by_key(|a| a.name != "Id");
+
+    let mut head = format!("<{tag}");
+    for (i, attribute) in selected.iter().enumerate() {
+        head.push_str(&format!(
+            " {}=\"{}\"",
+            attribute.name,
+            attribute_value(attribute, i + 1)
+        ));
+    }
+
+    let body = if container {
+        vec![format!("{head}>"), "\t$0".to_string(), format!("</{tag}>")]
+    } else {
+        vec![format!("{head} />")]
+    };
+
+    let description = element
+        .description
+        .as_deref()
+        .and_then(|d| d.lines().map(str::trim).find(|l| !l.is_empty()))
+        .map_or_else(|| format!("{tag} element"), String::from);
+
+    Snippet {
+        prefix: tag.to_string(),
+        body,
+        description,
+        scope: Some("wxs".to_string()),
+        parents: Vec::new(),
+    }
+}
+
+/// Tab stop for an attribute value
+fn attribute_value(attribute: &Attribute, index: usize) -> String {
+    let mut choices = attribute.enum_values.clone();
+    if choices.is_empty() && attribute.attr_type == AttributeType::YesNo {
+        choices = vec!["yes".to_string(), "no".to_string()];
+    }
+
+    if attribute.attr_type == AttributeType::Guid {
+        format!("${{{index}:${UUID_VARIABLE}}}")
+    } else if !choices.is_empty() {
+        if let Some(default) = attribute.default_value.as_deref() {
+            if let Some(pos) = choices.iter().position(|c| c == default) {
+                let value = choices.remove(pos);
+                choices.insert(0, value);
+            }
+        }
+        let options: Vec<String> = choices.iter().map(|c| escape_choice(c)).collect();
+        format!("${{{index}|{}|}}", options.join(","))
+    } else {
+        let placeholder = attribute
+            .default_value
+            .as_deref()
+            .unwrap_or(&attribute.name);
+        format!("${{{index}:{}}}", escape(placeholder))
+    }
+}
+
+/// Name of the first element in a body, skipping declarations and comments
+fn root_element(body: &str) -> Option<&str> {
+    let mut rest = body;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '.' | '-')))
+                .unwrap_or(rest.len());
+            return Some(&rest[..end]);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(name: &str, attr_type: AttributeType) -> Attribute {
+        Attribute {
+            id: 0,
+            element_id: 0,
+            name: name.to_string(),
+            attr_type,
+            required: true,
+            default_value: None,
+            description: None,
+            since_version: None,
+            deprecated_version: None,
+            enum_values: Vec::new(),
+        }
+    }
+
+    fn element(name: &str, description: Option<&str>) -> Element {
+        Element {
+            id: 0,
+            name: name.to_string(),
+            namespace: "wix".to_string(),
+            since_version: None,
+            deprecated_version: None,
+            description: description.map(String::from),
+            documentation_url: None,
+            remarks: None,
+            example: None,
+        }
+    }
+
+    #[test]
+    fn test_attribute_values() {
+        let mut scope = attribute("Scope", AttributeType::String);
+        scope.enum_values = vec!["perMachine".into(), "perUser".into()];
+        scope.default_value = Some("perUser".into());
+        assert_eq!(attribute_value(&scope, 1), "${1|perUser,perMachine|}");
+
+        let mut compressed = attribute("Compressed", AttributeType::YesNo);
+        compressed.default_value = Some("no".into());
+        assert_eq!(attribute_value(&compressed, 2), "${2|no,yes|}");
+
+        let code = attribute("UpgradeCode", AttributeType::Guid);
+        assert_eq!(attribute_value(&code, 3), "${3:$UUID}");
+
+        let name = attribute("Name", AttributeType::String);
+        assert_eq!(attribute_value(&name, 4), "${4:Name}");
+    }
+
+    #[test]
+    fn test_element_snippet() {
+        let mut id = attribute("Id", AttributeType::String);
+        id.required = false;
+        let mut optional = attribute("Title", AttributeType::String);
+        optional.required = false;
+        let attributes = vec![attribute("Directory", AttributeType::String), id, optional];
+
+        let snippet = element_snippet(
+            "ComponentGroup",
+            &element("ComponentGroup", Some("\nGroups components.\nMore text.")),
+            &attributes,
+            true,
+        );
+        assert_eq!(snippet.prefix, "ComponentGroup");
+        assert_eq!(snippet.description, "Groups components.");
+        assert_eq!(
+            snippet.body,
+            [
+                "<ComponentGroup Id=\"${1:Id}\" Directory=\"${2:Directory}\">",
+                "\t$0",
+                "</ComponentGroup>",
+            ]
+        );
+
+        let leaf = element_snippet("util:User", &element("User", None), &[], false);
+        assert_eq!(leaf.body, ["<util:User />"]);
+        assert_eq!(leaf.description, "util:User element");
+    }
+
+    #[test]
+    fn test_root_element() {
+        assert_eq!(
+            root_element("<?xml version=\"1.0\"?>\n<!-- x -->\n<Include>$0</Include>"),
+            Some("Include")
+        );
+        assert_eq!(root_element("<util:User Id=\"$1\" />"), Some("util:User"));
+        assert_eq!(root_element("text only"), None);
+    }
+
+    #[test]
+    fn test_bundled_database() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../common/wix-data/wix.db");
+        if !path.exists() {
+            return;
+        }
+        let library = SnippetLibrary::open(&path).unwrap();
+
+        let package = library.get("Package").unwrap();
+        assert_eq!(package.parents, ["Wix"]);
+        assert!(package.body[0].contains("${"));
+
+        let component = library.get("Component").unwrap();
+        assert!(component.parents.iter().any(|p| p == "ComponentGroup"));
+
+        // Table snippets get generated GUIDs instead of the old placeholder
+        let table = library.get("package").unwrap();
+        assert!(table.body_text().contains("${4:$UUID}"));
+        assert_eq!(table.parents, ["Wix"]);
+        assert!(library.get("wxi").unwrap().parents.is_empty());
+    }
+}
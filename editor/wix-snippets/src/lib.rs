@@ -1,9 +1,29 @@
 //! wix-snippets - Snippet library for common WiX patterns
 //!
 //! Provides code snippets for VS Code, Sublime Text, and other editors.
+//! Snippets are generated from wix-data's element, attribute and snippet
+//! tables and exported from that one source to VS Code, Sublime Text,
+//! JetBrains Live Templates, Neovim LuaSnip and Helix.
+
+pub mod body;
+pub mod export;
+pub mod generate;
+
+pub use export::ExportFormat;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Errors from generating or writing snippets
+#[derive(Error, Debug)]
+pub enum SnippetError {
+    #[error("wix-data error: {0}")]
+    Data(#[from] wix_data::WixDataError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
 
 /// A code snippet
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +32,9 @@ pub struct Snippet {
     pub body: Vec<String>,
     pub description: String,
     pub scope: Option<String>,
+    /// Elements the snippet's root element may appear in; empty means anywhere
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<String>,
 }
 
 impl Snippet {
@@ -21,9 +44,25 @@ impl Snippet {
             body: body.into_iter().map(String::from).collect(),
             description: description.to_string(),
             scope: Some("xml".to_string()),
+            parents: Vec::new(),
         }
     }
 
+    pub fn with_parents(mut self, parents: Vec<String>) -> Self {
+        self.parents = parents;
+        self
+    }
+
+    /// Body as a single string in snippet syntax
+    pub fn body_text(&self) -> String {
+        self.body.join("\n")
+    }
+
+    /// Body rendered for an editor's snippet engine
+    pub fn render(&self, dialect: body::Dialect) -> String {
+        body::render(&body::parse(&self.body_text()), dialect)
+    }
+
     pub fn to_vscode_format(&self) -> serde_json::Value {
         serde_json::json!({
             "prefix": self.prefix,
@@ -33,7 +72,7 @@ impl Snippet {
     }
 
     pub fn to_sublime_format(&self) -> String {
-        let body = self.body.join("\n");
+        let body = self.render(body::Dialect::FieldsOnly);
         format!(
             "<snippet>\n\
              \t<content><![CDATA[\n{}\n]]></content>\n\
@@ -46,20 +85,26 @@ impl Snippet {
     }
 }
 
-/// Snippet library
+/// Snippet library, ordered by prefix
 pub struct SnippetLibrary {
-    snippets: HashMap<String, Snippet>,
+    snippets: BTreeMap<String, Snippet>,
 }
 
 impl SnippetLibrary {
+    /// Library with the built-in snippets
     pub fn new() -> Self {
-        let mut lib = Self {
-            snippets: HashMap::new(),
-        };
+        let mut lib = Self::empty();
         lib.load_default_snippets();
         lib
     }
 
+    /// Library without any snippets
+    pub fn empty() -> Self {
+        Self {
+            snippets: BTreeMap::new(),
+        }
+    }
+
     fn load_default_snippets(&mut self) {
         // Product snippet
         self.add(Snippet::new(
@@ -69,7 +114,7 @@ impl SnippetLibrary {
                 "\t<Package Name=\"${1:ProductName}\"",
                 "\t         Manufacturer=\"${2:Manufacturer}\"",
                 "\t         Version=\"${3:1.0.0}\"",
-                "\t         UpgradeCode=\"${4:PUT-GUID-HERE}\">",
+                "\t         UpgradeCode=\"${4:$UUID}\">",
                 "\t\t$0",
                 "\t</Package>",
                 "</Wix>",
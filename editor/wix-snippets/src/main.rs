@@ -0,0 +1,106 @@
+//! wix-snippets - Snippet generator for WiX
+//!
+//! Generates snippets from wix-data and exports them for each editor.
+
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::path::PathBuf;
+use wix_snippets::{ExportFormat, SnippetLibrary};
+
+#[derive(Parser)]
+#[command(name = "wix-snippets")]
+#[command(about = "Generate WiX snippets for VS Code, Sublime Text, JetBrains, LuaSnip and Helix")]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    /// wix-data database (defaults to the wix-data search path)
+    #[arg(long, global = true)]
+    database: Option<PathBuf>,
+
+    /// Use the built-in snippets instead of generating from wix-data
+    #[arg(long, global = true, conflicts_with = "database")]
+    builtin: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Export snippets in one format
+    Export {
+        /// Editor format
+        #[arg(short, long, value_enum)]
+        format: Format,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export snippets in every format
+    All {
+        /// Output directory
+        #[arg(short, long, default_value = "snippets")]
+        output: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Vscode,
+    Sublime,
+    Jetbrains,
+    Luasnip,
+    Helix,
+}
+
+impl From<Format> for ExportFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Json => ExportFormat::Json,
+            Format::Vscode => ExportFormat::Vscode,
+            Format::Sublime => ExportFormat::Sublime,
+            Format::Jetbrains => ExportFormat::Jetbrains,
+            Format::Luasnip => ExportFormat::Luasnip,
+            Format::Helix => ExportFormat::Helix,
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    let library = if cli.builtin {
+        SnippetLibrary::new()
+    } else if let Some(path) = &cli.database {
+        SnippetLibrary::open(path)?
+    } else {
+        SnippetLibrary::open_default()?
+    };
+
+    match cli.command {
+        Commands::Export { format, output } => {
+            let content = library.export(format.into());
+            if let Some(path) = output {
+                fs::write(&path, &content)?;
+                println!(
+                    "Generated {} snippets at: {}",
+                    library.count(),
+                    path.display()
+                );
+            } else {
+                println!("{}", content);
+            }
+        }
+        Commands::All { output } => {
+            library.write_all(&output)?;
+            println!(
+                "Generated {} snippets in: {}",
+                library.count(),
+                output.display()
+            );
+        }
+    }
+
+    Ok(())
+}
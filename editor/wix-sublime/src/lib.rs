@@ -3,6 +3,7 @@
 //! Generates Sublime Text package with language support.
 
 use serde::{Deserialize, Serialize};
+use wix_snippets::SnippetLibrary;
 
 /// Sublime Text syntax definition (YAML format as string)
 pub struct SublimeSyntax;
@@ -118,25 +119,12 @@ impl SublimeBuildSystem {
     }
 }
 
-/// Sublime Text completions
+/// Sublime Text completions, exported from the shared snippet library
 pub struct SublimeCompletions;
 
 impl SublimeCompletions {
-    pub fn generate() -> String {
-        r#"{
-    "scope": "text.xml.wix",
-    "completions": [
-        { "trigger": "component\tWiX Component", "contents": "<Component Id=\"${1:ComponentId}\" Guid=\"*\">\n\t<File Source=\"${2:SourcePath}\" />\n</Component>" },
-        { "trigger": "directory\tWiX Directory", "contents": "<Directory Id=\"${1:DirectoryId}\" Name=\"${2:Name}\">\n\t$0\n</Directory>" },
-        { "trigger": "feature\tWiX Feature", "contents": "<Feature Id=\"${1:FeatureId}\" Title=\"${2:Title}\" Level=\"1\">\n\t<ComponentGroupRef Id=\"${3:GroupId}\" />\n</Feature>" },
-        { "trigger": "file\tWiX File", "contents": "<File Source=\"${1:SourcePath}\" />" },
-        { "trigger": "property\tWiX Property", "contents": "<Property Id=\"${1:PROPERTYNAME}\" Value=\"${2:Value}\" />" },
-        { "trigger": "registry\tWiX Registry", "contents": "<RegistryKey Root=\"${1:HKLM}\" Key=\"${2:Software\\\\Company}\">\n\t<RegistryValue Name=\"${3:Name}\" Type=\"string\" Value=\"${4:Value}\" />\n</RegistryKey>" },
-        { "trigger": "shortcut\tWiX Shortcut", "contents": "<Shortcut Id=\"${1:ShortcutId}\" Name=\"${2:Name}\" Directory=\"ProgramMenuFolder\" Target=\"[INSTALLFOLDER]${3:app.exe}\" />" },
-        { "trigger": "customaction\tWiX Custom Action", "contents": "<CustomAction Id=\"${1:ActionId}\" DllEntry=\"${2:EntryPoint}\" BinaryRef=\"${3:BinaryId}\" Execute=\"deferred\" Return=\"check\" />" },
-        { "trigger": "majorupgrade\tWiX Major Upgrade", "contents": "<MajorUpgrade DowngradeErrorMessage=\"A newer version is already installed.\" />" }
-    ]
-}"#.to_string()
+    pub fn generate(snippets: &SnippetLibrary) -> String {
+        snippets.to_sublime_completions()
     }
 }
 
@@ -156,8 +144,8 @@ impl PackageGenerator {
         SublimeBuildSystem::generate()
     }
 
-    pub fn generate_completions() -> String {
-        SublimeCompletions::generate()
+    pub fn generate_completions(snippets: &SnippetLibrary) -> String {
+        SublimeCompletions::generate(snippets)
     }
 }
 
@@ -186,7 +174,8 @@ mod tests {
 
     #[test]
     fn test_completions() {
-        let completions = SublimeCompletions::generate();
+        let completions = SublimeCompletions::generate(&SnippetLibrary::new());
         assert!(completions.contains("component"));
+        assert!(completions.contains("text.xml.wix"));
     }
 }
@@ -5,6 +5,7 @@
 use clap::{Parser, Subcommand};
 use std::fs;
 use std::path::PathBuf;
+use wix_snippets::SnippetLibrary;
 use wix_sublime::PackageGenerator;
 
 #[derive(Parser)]
@@ -23,6 +24,10 @@ enum Commands {
         /// Output directory for the package
         #[arg(short, long, default_value = "WiX")]
         output: PathBuf,
+
+        /// wix-data database the completions are generated from
+        #[arg(long)]
+        database: Option<PathBuf>,
     },
     /// Generate only syntax definition
     Syntax {
@@ -47,6 +52,10 @@ enum Commands {
         /// Output file path
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// wix-data database the completions are generated from
+        #[arg(long)]
+        database: Option<PathBuf>,
     },
 }
 
@@ -54,7 +63,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Generate { output } => {
+        Commands::Generate { output, database } => {
             // Create directory structure
             fs::create_dir_all(&output)?;
 
@@ -68,7 +77,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             fs::write(output.join("WiX.sublime-build"), PackageGenerator::generate_build_system())?;
 
             // Generate completions
-            fs::write(output.join("WiX.sublime-completions"), PackageGenerator::generate_completions())?;
+            let snippets = load_snippets(database)?;
+            fs::write(
+                output.join("WiX.sublime-completions"),
+                PackageGenerator::generate_completions(&snippets),
+            )?;
 
             // Generate README
             let readme = r#"# WiX Package for Sublime Text
@@ -137,8 +150,8 @@ Language support for WiX Toolset files in Sublime Text.
             }
         }
 
-        Commands::Completions { output } => {
-            let completions = PackageGenerator::generate_completions();
+        Commands::Completions { output, database } => {
+            let completions = PackageGenerator::generate_completions(&load_snippets(database)?);
             if let Some(path) = output {
                 fs::write(&path, &completions)?;
                 println!("Generated completions at: {}", path.display());
@@ -150,3 +163,15 @@ Language support for WiX Toolset files in Sublime Text.
 
     Ok(())
 }
+
+/// Snippets generated from wix-data, or the built-in ones when no database
+/// was given and none is found
+fn load_snippets(database: Option<PathBuf>) -> Result<SnippetLibrary, Box<dyn std::error::Error>> {
+    match database {
+        Some(path) => Ok(SnippetLibrary::open(path)?),
+        None => Ok(SnippetLibrary::open_default().unwrap_or_else(|e| {
+            eprintln!("Warning: using built-in snippets ({})", e);
+            SnippetLibrary::new()
+        })),
+    }
+}
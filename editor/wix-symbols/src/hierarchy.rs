@@ -0,0 +1,809 @@
+//! Workspace-level install hierarchy
+//!
+//! [`extract_symbols`](crate::extract_symbols) follows the XML nesting of a
+//! single file. The hierarchy follows what gets installed instead: Ref
+//! elements are resolved across files, so a Feature leads to the
+//! ComponentGroups it references wherever they are defined, and on to their
+//! Components and Files. Directory placement is tracked alongside.
+
+use crate::symbols::{get_attribute_range, get_node_range};
+use crate::types::{Position, Range, SourceText, SymbolKind};
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Kind of element in the hierarchy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum NodeKind {
+    Package,
+    Module,
+    Feature,
+    FeatureGroup,
+    ComponentGroup,
+    Component,
+    Directory,
+    File,
+}
+
+impl NodeKind {
+    /// Kind defined by an element, e.g. `Component`
+    pub fn from_definition(element: &str) -> Option<Self> {
+        match element {
+            "Package" => Some(NodeKind::Package),
+            "Module" => Some(NodeKind::Module),
+            "Feature" => Some(NodeKind::Feature),
+            "FeatureGroup" => Some(NodeKind::FeatureGroup),
+            "ComponentGroup" => Some(NodeKind::ComponentGroup),
+            "Component" => Some(NodeKind::Component),
+            "Directory" | "StandardDirectory" => Some(NodeKind::Directory),
+            "File" => Some(NodeKind::File),
+            _ => None,
+        }
+    }
+
+    /// Kind referred to by an element, e.g. `ComponentRef`
+    pub fn from_reference(element: &str) -> Option<Self> {
+        match element {
+            "FeatureRef" => Some(NodeKind::Feature),
+            "FeatureGroupRef" => Some(NodeKind::FeatureGroup),
+            "ComponentGroupRef" => Some(NodeKind::ComponentGroup),
+            "ComponentRef" => Some(NodeKind::Component),
+            "DirectoryRef" => Some(NodeKind::Directory),
+            _ => None,
+        }
+    }
+
+    /// Name of the defining element
+    pub fn element_name(&self) -> &'static str {
+        match self {
+            NodeKind::Package => "Package",
+            NodeKind::Module => "Module",
+            NodeKind::Feature => "Feature",
+            NodeKind::FeatureGroup => "FeatureGroup",
+            NodeKind::ComponentGroup => "ComponentGroup",
+            NodeKind::Component => "Component",
+            NodeKind::Directory => "Directory",
+            NodeKind::File => "File",
+        }
+    }
+
+    /// Symbol kind, matching the document outline
+    pub fn symbol_kind(&self) -> SymbolKind {
+        match self {
+            NodeKind::Package | NodeKind::Module => SymbolKind::Module,
+            NodeKind::Feature | NodeKind::FeatureGroup => SymbolKind::TypeParameter,
+            NodeKind::ComponentGroup | NodeKind::Component => SymbolKind::Struct,
+            NodeKind::Directory => SymbolKind::Namespace,
+            NodeKind::File => SymbolKind::File,
+        }
+    }
+}
+
+/// Identity of a hierarchy node: its kind and Id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct NodeKey {
+    pub kind: NodeKind,
+    pub id: String,
+}
+
+impl NodeKey {
+    pub fn new(kind: NodeKind, id: impl Into<String>) -> Self {
+        Self {
+            kind,
+            id: id.into(),
+        }
+    }
+}
+
+impl fmt::Display for NodeKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind.element_name(), self.id)
+    }
+}
+
+/// How a parent relates to a child
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Relation {
+    /// The parent installs the child, e.g. Feature → ComponentGroup → Component → File
+    Installs,
+    /// The child is placed in the parent Directory
+    Placement,
+}
+
+/// Element defining a hierarchy node
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyNode {
+    #[serde(flatten)]
+    pub key: NodeKey,
+
+    /// Additional detail (e.g., Feature Title, Directory Name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    /// File the element is in
+    pub file: PathBuf,
+
+    /// Full range of the element
+    pub range: Range,
+
+    /// Range of the Id
+    pub selection_range: Range,
+}
+
+/// Link from a parent node to a child, made by a Ref element or by nesting
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyLink {
+    pub parent: NodeKey,
+    pub child: NodeKey,
+    pub relation: Relation,
+
+    /// File the link is made in
+    pub file: PathBuf,
+
+    /// Range of the referencing Id, or of the nested child's Id
+    pub range: Range,
+}
+
+/// Node with its children resolved, for export
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyTree {
+    #[serde(flatten)]
+    pub key: NodeKey,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    /// Defining file, `None` when nothing defines the node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+
+    /// Line of the definition (1-based)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<HierarchyTree>,
+}
+
+impl HierarchyTree {
+    /// Whether an element defines the node
+    pub fn is_resolved(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// Format for text output
+    pub fn format_text(&self, indent: usize) -> String {
+        let prefix = "  ".repeat(indent);
+        let detail_str = self
+            .detail
+            .as_ref()
+            .map(|d| format!(" ({})", d))
+            .unwrap_or_default();
+        let location = match (&self.file, self.line) {
+            (Some(file), Some(line)) => format!("[{}:{}]", file.display(), line),
+            _ => "[unresolved]".to_string(),
+        };
+
+        let mut result = format!(
+            "{}{}: {}{} {}\n",
+            prefix,
+            self.key.kind.element_name(),
+            self.key.id,
+            detail_str,
+            location
+        );
+
+        for child in &self.children {
+            result.push_str(&child.format_text(indent + 1));
+        }
+
+        result
+    }
+}
+
+/// Install and placement trees of a workspace, for JSON export
+#[derive(Debug, Clone, Serialize)]
+pub struct HierarchyExport {
+    pub installs: Vec<HierarchyTree>,
+    pub placement: Vec<HierarchyTree>,
+}
+
+/// Nodes and links contributed by one file
+#[derive(Debug, Default)]
+struct FileEntries {
+    nodes: Vec<HierarchyNode>,
+    links: Vec<HierarchyLink>,
+}
+
+/// Install hierarchy across the files of a workspace
+///
+/// ```
+/// use wix_symbols::{Hierarchy, NodeKey, NodeKind};
+///
+/// let mut hierarchy = Hierarchy::new();
+/// hierarchy
+///     .update_file("product.wxs", r#"<Wix><Feature Id="Main"><ComponentGroupRef Id="Core" /></Feature></Wix>"#)
+///     .unwrap();
+/// hierarchy
+///     .update_file("core.wxs", r#"<Wix><ComponentGroup Id="Core"><Component Id="App" /></ComponentGroup></Wix>"#)
+///     .unwrap();
+///
+/// let group = NodeKey::new(NodeKind::ComponentGroup, "Core");
+/// assert_eq!(hierarchy.parents(&group)[0].parent.id, "Main");
+/// assert_eq!(hierarchy.children(&group)[0].child.id, "App");
+/// ```
+#[derive(Debug, Default)]
+pub struct Hierarchy {
+    files: BTreeMap<PathBuf, FileEntries>,
+}
+
+impl Hierarchy {
+    /// Create an empty hierarchy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a file
+    ///
+    /// On a parse error the file's previous entries are kept.
+    pub fn update_file(&mut self, path: impl Into<PathBuf>, source: &str) -> Result<(), String> {
+        let doc = Document::parse(source).map_err(|e| format!("XML parse error: {}", e))?;
+        let path = path.into();
+        let mut walker = Walker {
+            source: SourceText::new(source),
+            file: path.clone(),
+            entries: FileEntries::default(),
+        };
+        walker.walk(doc.root(), &Scope::default());
+        self.files.insert(path, walker.entries);
+        Ok(())
+    }
+
+    /// Remove a file
+    pub fn remove_file(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// Files in the hierarchy, sorted
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Every defining element, in file order
+    pub fn nodes(&self) -> impl Iterator<Item = &HierarchyNode> {
+        self.files.values().flat_map(|entries| entries.nodes.iter())
+    }
+
+    /// Every link, in file order
+    pub fn links(&self) -> impl Iterator<Item = &HierarchyLink> {
+        self.files.values().flat_map(|entries| entries.links.iter())
+    }
+
+    /// Definition of a node, the first one if several files define it
+    pub fn node(&self, key: &NodeKey) -> Option<&HierarchyNode> {
+        self.nodes().find(|node| &node.key == key)
+    }
+
+    /// Node at a 1-based position in a file
+    ///
+    /// An Id names its element, a Ref's Id the element it refers to, and
+    /// anywhere else the innermost enclosing node is used.
+    pub fn node_at(&self, path: &Path, line: u32, column: u32) -> Option<NodeKey> {
+        let entries = self.files.get(path)?;
+        let position = Position::new(line, column);
+
+        if let Some(node) = entries
+            .nodes
+            .iter()
+            .find(|node| node.selection_range.contains(position))
+        {
+            return Some(node.key.clone());
+        }
+        if let Some(link) = entries
+            .links
+            .iter()
+            .find(|link| link.range.contains(position))
+        {
+            return Some(link.child.clone());
+        }
+        // Nodes are recorded parents first
+        entries
+            .nodes
+            .iter()
+            .rev()
+            .find(|node| node.range.contains(position))
+            .map(|node| node.key.clone())
+    }
+
+    /// Links from a node to its children
+    pub fn children(&self, key: &NodeKey) -> Vec<&HierarchyLink> {
+        self.links().filter(|link| &link.parent == key).collect()
+    }
+
+    /// Links to a node from its parents
+    pub fn parents(&self, key: &NodeKey) -> Vec<&HierarchyLink> {
+        self.links().filter(|link| &link.child == key).collect()
+    }
+
+    /// Nodes at the top of a relation: defined, able to have children and
+    /// without a parent
+    ///
+    /// For [`Relation::Installs`] this includes Components nothing installs.
+    pub fn roots(&self, relation: Relation) -> Vec<NodeKey> {
+        let children: BTreeSet<&NodeKey> = self
+            .links()
+            .filter(|link| link.relation == relation)
+            .map(|link| &link.child)
+            .collect();
+        let roots: BTreeSet<NodeKey> = self
+            .nodes()
+            .filter(|node| match relation {
+                Relation::Installs => {
+                    !matches!(node.key.kind, NodeKind::Directory | NodeKind::File)
+                }
+                Relation::Placement => node.key.kind == NodeKind::Directory,
+            })
+            .filter(|node| !children.contains(&node.key))
+            .map(|node| node.key.clone())
+            .collect();
+        roots.into_iter().collect()
+    }
+
+    /// Trees of a relation, starting from its roots
+    pub fn tree(&self, relation: Relation) -> Vec<HierarchyTree> {
+        let mut ancestors = Vec::new();
+        self.roots(relation)
+            .iter()
+            .map(|root| self.subtree(root, relation, &mut ancestors))
+            .collect()
+    }
+
+    /// Install and placement trees
+    pub fn export(&self) -> HierarchyExport {
+        HierarchyExport {
+            installs: self.tree(Relation::Installs),
+            placement: self.tree(Relation::Placement),
+        }
+    }
+
+    /// Mermaid flowchart of every link
+    ///
+    /// Placement links are dotted and nodes nothing defines are dashed.
+    pub fn to_mermaid(&self) -> String {
+        let links: BTreeSet<(&NodeKey, &NodeKey, Relation)> = self
+            .links()
+            .map(|link| (&link.parent, &link.child, link.relation))
+            .collect();
+        let keys: BTreeSet<&NodeKey> = self
+            .nodes()
+            .map(|node| &node.key)
+            .chain(
+                links
+                    .iter()
+                    .flat_map(|(parent, child, _)| [*parent, *child]),
+            )
+            .collect();
+        let ids: BTreeMap<&NodeKey, usize> =
+            keys.iter().enumerate().map(|(i, k)| (*k, i)).collect();
+
+        let mut out = String::from("flowchart LR\n");
+        let mut unresolved = Vec::new();
+        for (key, i) in &ids {
+            let label = format!("{} {}", key.kind.element_name(), key.id).replace('"', "#quot;");
+            out.push_str(&format!("    n{}[\"{}\"]\n", i, label));
+            if self.node(key).is_none() {
+                unresolved.push(format!("n{}", i));
+            }
+        }
+        for (parent, child, relation) in &links {
+            let arrow = match relation {
+                Relation::Installs => "-->",
+                Relation::Placement => "-.->",
+            };
+            out.push_str(&format!("    n{} {} n{}\n", ids[parent], arrow, ids[child]));
+        }
+        if !unresolved.is_empty() {
+            out.push_str("    classDef unresolved stroke-dasharray: 4 4\n");
+            out.push_str(&format!("    class {} unresolved\n", unresolved.join(",")));
+        }
+        out
+    }
+
+    fn subtree(
+        &self,
+        key: &NodeKey,
+        relation: Relation,
+        ancestors: &mut Vec<NodeKey>,
+    ) -> HierarchyTree {
+        let node = self.node(key);
+        let mut tree = HierarchyTree {
+            key: key.clone(),
+            detail: node.and_then(|n| n.detail.clone()),
+            file: node.map(|n| n.file.clone()),
+            line: node.map(|n| n.range.start.line),
+            children: Vec::new(),
+        };
+        // A node referencing one of its ancestors would never end
+        if ancestors.contains(key) {
+            return tree;
+        }
+
+        ancestors.push(key.clone());
+        let children: BTreeSet<&NodeKey> = self
+            .children(key)
+            .into_iter()
+            .filter(|link| link.relation == relation)
+            .map(|link| &link.child)
+            .collect();
+        for child in children {
+            tree.children.push(self.subtree(child, relation, ancestors));
+        }
+        ancestors.pop();
+        tree
+    }
+}
+
+/// Parents inherited by nested elements
+#[derive(Debug, Clone, Default)]
+struct Scope {
+    /// Node that installs the elements
+    install: Option<NodeKey>,
+    /// Directory the elements are placed in
+    place: Option<NodeKey>,
+}
+
+/// Collects the nodes and links of one file
+struct Walker<'a> {
+    source: SourceText<'a>,
+    file: PathBuf,
+    entries: FileEntries,
+}
+
+impl Walker<'_> {
+    fn walk(&mut self, node: Node, scope: &Scope) {
+        for child in node.children().filter(|n| n.is_element()) {
+            self.visit(child, scope);
+        }
+    }
+
+    fn visit(&mut self, node: Node, scope: &Scope) {
+        let element = node.tag_name().name();
+
+        if let Some(kind) = NodeKind::from_definition(element) {
+            if let Some(id) = definition_id(&node, kind) {
+                let key = NodeKey::new(kind, id);
+                let range = get_node_range(&node, &self.source);
+                let selection_range = attribute_range(&node, "Id", &self.source)
+                    .or_else(|| attribute_range(&node, "Name", &self.source))
+                    .unwrap_or(range);
+                self.entries.nodes.push(HierarchyNode {
+                    key: key.clone(),
+                    detail: definition_detail(&node, kind),
+                    file: self.file.clone(),
+                    range,
+                    selection_range,
+                });
+                self.link_definition(&node, &key, scope, selection_range);
+                let inner = self.inner_scope(&node, key, scope);
+                self.walk(node, &inner);
+                return;
+            }
+        } else if let Some(kind) = NodeKind::from_reference(element) {
+            if let Some(id) = node.attribute("Id") {
+                let key = NodeKey::new(kind, id);
+                if kind == NodeKind::Directory {
+                    // DirectoryRef only places what it contains
+                    let inner = Scope {
+                        install: None,
+                        place: Some(key),
+                    };
+                    self.walk(node, &inner);
+                    return;
+                }
+
+                let range = attribute_range(&node, "Id", &self.source)
+                    .unwrap_or_else(|| get_node_range(&node, &self.source));
+                if let Some(parent) = &scope.install {
+                    self.link(parent, &key, Relation::Installs, range);
+                }
+                // FeatureRef can add content to the referenced Feature
+                let inner = Scope {
+                    install: Some(key),
+                    place: scope.place.clone(),
+                };
+                self.walk(node, &inner);
+                return;
+            }
+        }
+
+        self.walk(node, scope);
+    }
+
+    fn link_definition(&mut self, node: &Node, key: &NodeKey, scope: &Scope, range: Range) {
+        match key.kind {
+            NodeKind::Directory => {
+                if let Some(parent) = &scope.place {
+                    self.link(parent, key, Relation::Placement, range);
+                }
+            }
+            NodeKind::Component => {
+                if let Some(parent) = &scope.install {
+                    self.link(parent, key, Relation::Installs, range);
+                }
+                if let Some(directory) = node.attribute("Directory") {
+                    let parent = NodeKey::new(NodeKind::Directory, directory);
+                    let range = attribute_range(node, "Directory", &self.source).unwrap_or(range);
+                    self.link(&parent, key, Relation::Placement, range);
+                } else if let Some(parent) = &scope.place {
+                    self.link(parent, key, Relation::Placement, range);
+                }
+            }
+            _ => {
+                if let Some(parent) = &scope.install {
+                    self.link(parent, key, Relation::Installs, range);
+                }
+            }
+        }
+    }
+
+    fn inner_scope(&self, node: &Node, key: NodeKey, scope: &Scope) -> Scope {
+        match key.kind {
+            NodeKind::Directory => Scope {
+                install: None,
+                place: Some(key),
+            },
+            // Components of a group go to its Directory unless they say otherwise
+            NodeKind::ComponentGroup => Scope {
+                install: Some(key),
+                place: node
+                    .attribute("Directory")
+                    .map(|directory| NodeKey::new(NodeKind::Directory, directory))
+                    .or_else(|| scope.place.clone()),
+            },
+            NodeKind::Component => Scope {
+                install: Some(key),
+                place: None,
+            },
+            _ => Scope {
+                install: Some(key),
+                place: scope.place.clone(),
+            },
+        }
+    }
+
+    fn link(&mut self, parent: &NodeKey, child: &NodeKey, relation: Relation, range: Range) {
+        self.entries.links.push(HierarchyLink {
+            parent: parent.clone(),
+            child: child.clone(),
+            relation,
+            file: self.file.clone(),
+            range,
+        });
+    }
+}
+
+/// Range of an attribute's value, if the element itself has it
+fn attribute_range(node: &Node, name: &str, source: &SourceText) -> Option<Range> {
+    node.attribute(name)?;
+    get_attribute_range(node, name, source)
+}
+
+/// Id of a defining element, following the defaults WiX applies
+fn definition_id(node: &Node, kind: NodeKind) -> Option<String> {
+    if let Some(id) = node.attribute("Id") {
+        return Some(id.to_string());
+    }
+    match kind {
+        NodeKind::Package => Some(node.attribute("Name").unwrap_or("Package").to_string()),
+        // Defaults to the file name
+        NodeKind::File => node
+            .attribute("Name")
+            .or_else(|| node.attribute("Source"))
+            .and_then(|path| path.rsplit(['\\', '/']).next())
+            .filter(|name| !name.is_empty())
+            .map(str::to_string),
+        // Defaults to the Id of its key path File
+        NodeKind::Component => node
+            .children()
+            .find(|child| child.tag_name().name() == "File")
+            .and_then(|file| definition_id(&file, NodeKind::File)),
+        _ => None,
+    }
+}
+
+fn definition_detail(node: &Node, kind: NodeKind) -> Option<String> {
+    let attribute = match kind {
+        NodeKind::Package | NodeKind::Module => "Version",
+        NodeKind::Feature => "Title",
+        NodeKind::Directory => "Name",
+        NodeKind::File => "Source",
+        _ => return None,
+    };
+    node.attribute(attribute)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRODUCT: &str = r#"<Wix>
+  <Package Name="App" Version="1.0">
+    <Feature Id="Main" Title="Main Feature">
+      <ComponentGroupRef Id="Core" />
+      <Feature Id="Docs">
+        <ComponentRef Id="Manual" />
+      </Feature>
+    </Feature>
+    <StandardDirectory Id="ProgramFiles6432Folder">
+      <Directory Id="INSTALLFOLDER" Name="App" />
+    </StandardDirectory>
+  </Package>
+</Wix>"#;
+
+    const COMPONENTS: &str = r#"<Wix>
+  <Fragment>
+    <ComponentGroup Id="Core" Directory="INSTALLFOLDER">
+      <Component Id="AppExe">
+        <File Source="bin\app.exe" />
+      </Component>
+      <ComponentRef Id="Missing" />
+    </ComponentGroup>
+    <DirectoryRef Id="INSTALLFOLDER">
+      <Component Id="Manual" Directory="DocsFolder">
+        <File Id="ManualPdf" Source="manual.pdf" />
+      </Component>
+    </DirectoryRef>
+  </Fragment>
+</Wix>"#;
+
+    fn hierarchy() -> Hierarchy {
+        let mut hierarchy = Hierarchy::new();
+        hierarchy.update_file("product.wxs", PRODUCT).unwrap();
+        hierarchy.update_file("components.wxs", COMPONENTS).unwrap();
+        hierarchy
+    }
+
+    fn child_ids(hierarchy: &Hierarchy, key: &NodeKey, relation: Relation) -> Vec<String> {
+        hierarchy
+            .children(key)
+            .into_iter()
+            .filter(|link| link.relation == relation)
+            .map(|link| link.child.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_resolves_refs_across_files() {
+        let hierarchy = hierarchy();
+        let main = NodeKey::new(NodeKind::Feature, "Main");
+        assert_eq!(
+            child_ids(&hierarchy, &main, Relation::Installs),
+            vec!["ComponentGroup:Core", "Feature:Docs"]
+        );
+
+        let core = NodeKey::new(NodeKind::ComponentGroup, "Core");
+        assert_eq!(
+            child_ids(&hierarchy, &core, Relation::Installs),
+            vec!["Component:AppExe", "Component:Missing"]
+        );
+        let link = &hierarchy.parents(&core)[0];
+        assert_eq!(link.file, PathBuf::from("product.wxs"));
+        assert_eq!(link.range, Range::new(4, 30, 4, 34));
+
+        // File Ids default to the file name
+        let app = NodeKey::new(NodeKind::Component, "AppExe");
+        assert_eq!(
+            child_ids(&hierarchy, &app, Relation::Installs),
+            vec!["File:app.exe"]
+        );
+    }
+
+    #[test]
+    fn test_directory_placement() {
+        let hierarchy = hierarchy();
+        let install = NodeKey::new(NodeKind::Directory, "INSTALLFOLDER");
+        assert_eq!(
+            child_ids(&hierarchy, &install, Relation::Placement),
+            vec!["Component:AppExe"]
+        );
+        // An explicit Directory wins over the enclosing DirectoryRef
+        let docs = NodeKey::new(NodeKind::Directory, "DocsFolder");
+        assert_eq!(
+            child_ids(&hierarchy, &docs, Relation::Placement),
+            vec!["Component:Manual"]
+        );
+        assert_eq!(
+            hierarchy.roots(Relation::Placement),
+            vec![NodeKey::new(NodeKind::Directory, "ProgramFiles6432Folder")]
+        );
+    }
+
+    #[test]
+    fn test_node_at() {
+        let hierarchy = hierarchy();
+        let product = Path::new("product.wxs");
+        assert_eq!(
+            hierarchy.node_at(product, 4, 34),
+            Some(NodeKey::new(NodeKind::ComponentGroup, "Core"))
+        );
+        assert_eq!(
+            hierarchy.node_at(product, 3, 20),
+            Some(NodeKey::new(NodeKind::Feature, "Main"))
+        );
+        // Inside Docs but not on an Id
+        assert_eq!(
+            hierarchy.node_at(product, 7, 7),
+            Some(NodeKey::new(NodeKind::Feature, "Docs"))
+        );
+        assert_eq!(hierarchy.node_at(Path::new("other.wxs"), 1, 1), None);
+    }
+
+    #[test]
+    fn test_tree_and_export() {
+        let hierarchy = hierarchy();
+        let trees = hierarchy.tree(Relation::Installs);
+        assert_eq!(trees.len(), 1);
+        assert_eq!(trees[0].key, NodeKey::new(NodeKind::Package, "App"));
+
+        let text = trees[0].format_text(0);
+        assert!(text.contains("Package: App (1.0) [product.wxs:2]"));
+        assert!(text.contains("      Component: Missing [unresolved]"));
+        assert!(text.contains("        File: ManualPdf (manual.pdf) [components.wxs:11]"));
+
+        let json = serde_json::to_value(hierarchy.export()).unwrap();
+        assert_eq!(json["installs"][0]["kind"], "Package");
+        assert_eq!(json["placement"][0]["id"], "ProgramFiles6432Folder");
+    }
+
+    #[test]
+    fn test_cycles_terminate() {
+        let mut hierarchy = Hierarchy::new();
+        hierarchy
+            .update_file(
+                "loop.wxs",
+                r#"<Wix>
+  <FeatureGroup Id="A"><FeatureGroupRef Id="B" /></FeatureGroup>
+  <FeatureGroup Id="B"><FeatureGroupRef Id="A" /></FeatureGroup>
+  <Feature Id="Top"><FeatureGroupRef Id="A" /></Feature>
+</Wix>"#,
+            )
+            .unwrap();
+        let trees = hierarchy.tree(Relation::Installs);
+        assert_eq!(trees.len(), 1);
+        let a = &trees[0].children[0];
+        assert_eq!(a.children[0].children[0].key.id, "A");
+        assert!(a.children[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let mermaid = hierarchy().to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("[\"Feature Main\"]"));
+        assert!(mermaid.contains(" -.-> "));
+        assert!(mermaid.contains("class "));
+        assert!(mermaid.contains(" unresolved\n"));
+    }
+
+    #[test]
+    fn test_update_and_remove_file() {
+        let mut hierarchy = hierarchy();
+        assert!(hierarchy
+            .update_file("components.wxs", "<Wix><Broken")
+            .is_err());
+        let core = NodeKey::new(NodeKind::ComponentGroup, "Core");
+        assert!(hierarchy.node(&core).is_some());
+
+        hierarchy.remove_file(Path::new("components.wxs"));
+        assert!(hierarchy.node(&core).is_none());
+        assert_eq!(hierarchy.files().count(), 1);
+    }
+}
@@ -13,6 +13,8 @@
 //! - Filter/query symbols by name
 //! - LSP-compatible SymbolKind values
 //! - Selection range for precise identifier highlighting
+//! - Workspace install hierarchy (Feature → ComponentGroup → Component → File)
+//!   resolved across files, with Directory placement
 //!
 //! ## Example
 //!
@@ -40,10 +42,22 @@
 //!
 //! # JSON output
 //! wix-symbols file.wxs --format json
+//!
+//! # Install hierarchy of every WiX file under a directory
+//! wix-symbols src/ --hierarchy
+//! wix-symbols src/ --hierarchy --format mermaid
 //! ```
 
+mod hierarchy;
 mod symbols;
 mod types;
 
-pub use symbols::{extract_symbols, filter_symbols, flatten_symbols};
+pub use hierarchy::{
+    Hierarchy, HierarchyExport, HierarchyLink, HierarchyNode, HierarchyTree, NodeKey, NodeKind,
+    Relation,
+};
+
+pub use symbols::{
+    extract_symbols, extract_symbols_from_document, filter_symbols, flatten_symbols,
+};
 pub use types::{Position, Range, Symbol, SymbolKind};
@@ -3,15 +3,19 @@
 use clap::{Parser, ValueEnum};
 use std::fs;
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
-use wix_symbols::{extract_symbols, filter_symbols, flatten_symbols};
+use wix_symbols::{extract_symbols, filter_symbols, flatten_symbols, Hierarchy, Relation};
+
+/// Directories never searched for sources
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules", "bin", "obj"];
 
 #[derive(Parser)]
 #[command(name = "wix-symbols")]
 #[command(about = "WiX document symbols extractor")]
 #[command(version)]
 struct Cli {
-    /// WiX file (or - for stdin)
+    /// WiX file (or - for stdin), or a directory with --hierarchy
     file: String,
 
     /// Output format
@@ -26,6 +30,10 @@ struct Cli {
     #[arg(long)]
     query: Option<String>,
 
+    /// Install hierarchy resolved across files instead of the file outline
+    #[arg(long, conflicts_with_all = ["flat", "query"])]
+    hierarchy: bool,
+
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -35,6 +43,8 @@ struct Cli {
 enum OutputFormat {
     Text,
     Json,
+    /// Mermaid flowchart (with --hierarchy)
+    Mermaid,
 }
 
 fn main() -> ExitCode {
@@ -49,6 +59,13 @@ fn main() -> ExitCode {
 }
 
 fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    if cli.hierarchy {
+        return run_hierarchy(&cli);
+    }
+    if matches!(cli.format, OutputFormat::Mermaid) {
+        return Err("Mermaid output requires --hierarchy".into());
+    }
+
     // Read source
     let source = if cli.file == "-" {
         let mut buffer = String::new();
@@ -84,7 +101,7 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                     print!("{}", symbol.format_text(0));
                 }
             }
-            OutputFormat::Json => {
+            OutputFormat::Json | OutputFormat::Mermaid => {
                 println!("{}", serde_json::to_string_pretty(&filtered)?);
             }
         }
@@ -120,7 +137,7 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
-        OutputFormat::Json => {
+        OutputFormat::Json | OutputFormat::Mermaid => {
             if cli.flat {
                 let flat = flatten_symbols(&symbols);
                 println!("{}", serde_json::to_string_pretty(&flat)?);
@@ -133,6 +150,92 @@ fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn run_hierarchy(cli: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let mut hierarchy = Hierarchy::new();
+    let root = Path::new(&cli.file);
+
+    if cli.file == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        hierarchy.update_file("<stdin>", &buffer)?;
+    } else if root.is_dir() {
+        let mut files = Vec::new();
+        collect_wix_files(root, &mut files)?;
+        for file in &files {
+            let source = fs::read_to_string(file)?;
+            let relative = file.strip_prefix(root).unwrap_or(file);
+            // One broken file shouldn't hide the rest of the tree
+            if let Err(e) = hierarchy.update_file(relative, &source) {
+                eprintln!("Skipping {}: {}", file.display(), e);
+            }
+        }
+        if cli.verbose {
+            eprintln!("Loaded {} WiX files", hierarchy.files().count());
+        }
+    } else {
+        let source = fs::read_to_string(root)?;
+        hierarchy.update_file(root, &source)?;
+    }
+
+    if cli.verbose {
+        eprintln!(
+            "Found {} nodes and {} links",
+            hierarchy.nodes().count(),
+            hierarchy.links().count()
+        );
+    }
+
+    match cli.format {
+        OutputFormat::Text => {
+            println!("Installs:");
+            for tree in hierarchy.tree(Relation::Installs) {
+                print!("{}", tree.format_text(1));
+            }
+            println!("Placement:");
+            for tree in hierarchy.tree(Relation::Placement) {
+                print!("{}", tree.format_text(1));
+            }
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&hierarchy.export())?);
+        }
+        OutputFormat::Mermaid => {
+            print!("{}", hierarchy.to_mermaid());
+        }
+    }
+
+    Ok(())
+}
+
+/// `.wxs` and `.wxi` files under a directory, sorted
+fn collect_wix_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_wix_files(&path, files)?;
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wxs") || ext.eq_ignore_ascii_case("wxi"))
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,5 +246,23 @@ mod tests {
         use clap::ValueEnum;
         let _text = OutputFormat::from_str("text", true).unwrap();
         let _json = OutputFormat::from_str("json", true).unwrap();
+        let _mermaid = OutputFormat::from_str("mermaid", true).unwrap();
+    }
+
+    #[test]
+    fn test_collect_wix_files() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(temp.path().join("b.wxs"), "<Wix />").unwrap();
+        fs::write(temp.path().join("a.wxi"), "<Include />").unwrap();
+        fs::write(temp.path().join("notes.txt"), "").unwrap();
+        fs::create_dir(temp.path().join("obj")).unwrap();
+        fs::write(temp.path().join("obj").join("stale.wxs"), "<Wix />").unwrap();
+
+        let mut files = Vec::new();
+        collect_wix_files(temp.path(), &mut files).unwrap();
+        assert_eq!(
+            files,
+            vec![temp.path().join("a.wxi"), temp.path().join("b.wxs")]
+        );
     }
 }
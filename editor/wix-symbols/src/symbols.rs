@@ -1,20 +1,26 @@
 //! Symbol extraction from WiX XML files
 
-use crate::types::{Range, Symbol, SymbolKind};
+use crate::types::{Range, SourceText, Symbol, SymbolKind};
 use roxmltree::{Document, Node};
 
 /// Extract symbols from WiX source
 pub fn extract_symbols(source: &str) -> Result<Vec<Symbol>, String> {
     let doc = Document::parse(source).map_err(|e| format!("XML parse error: {}", e))?;
+    Ok(extract_symbols_from_document(&doc, source))
+}
 
+/// Extract symbols from an already parsed document
+///
+/// `source` must be the text `doc` was parsed from.
+pub fn extract_symbols_from_document(doc: &Document, source: &str) -> Vec<Symbol> {
+    let source = SourceText::new(source);
     let mut symbols = Vec::new();
-    extract_from_node(doc.root(), source, &mut symbols);
-
-    Ok(symbols)
+    extract_from_node(doc.root(), &source, &mut symbols);
+    symbols
 }
 
 /// Recursively extract symbols from a node
-fn extract_from_node(node: Node, source: &str, symbols: &mut Vec<Symbol>) {
+fn extract_from_node(node: Node, source: &SourceText, symbols: &mut Vec<Symbol>) {
     if node.is_element() {
         if let Some(symbol) = create_symbol_for_node(&node, source) {
             let mut sym = symbol;
@@ -36,7 +42,7 @@ fn extract_from_node(node: Node, source: &str, symbols: &mut Vec<Symbol>) {
 }
 
 /// Create a symbol for a node if it represents a known WiX element
-fn create_symbol_for_node(node: &Node, source: &str) -> Option<Symbol> {
+fn create_symbol_for_node(node: &Node, source: &SourceText) -> Option<Symbol> {
     let tag_name = node.tag_name().name();
 
     let (kind, name_attr, detail_attr) = match tag_name {
@@ -158,17 +164,21 @@ fn create_symbol_for_node(node: &Node, source: &str) -> Option<Symbol> {
 }
 
 /// Get the full range of a node
-fn get_node_range(node: &Node, source: &str) -> Range {
+pub(crate) fn get_node_range(node: &Node, source: &SourceText) -> Range {
     let start = node.range().start;
     let end = node.range().end;
-    Range::from_text_pos(source, start, end)
+    source.range(start, end)
 }
 
 /// Get the range of a specific attribute value
-fn get_attribute_range(node: &Node, attr_name: &str, source: &str) -> Option<Range> {
+pub(crate) fn get_attribute_range(
+    node: &Node,
+    attr_name: &str,
+    source: &SourceText,
+) -> Option<Range> {
     // Find the attribute in the source text
     let node_start = node.range().start;
-    let node_text = &source[node.range()];
+    let node_text = &source.text()[node.range()];
 
     // Search for attr="value" or attr='value'
     let patterns = [
@@ -186,7 +196,7 @@ fn get_attribute_range(node: &Node, attr_name: &str, source: &str) -> Option<Ran
             if let Some(value_end) = node_text[value_start..].find(quote_char) {
                 let abs_start = node_start + value_start;
                 let abs_end = node_start + value_start + value_end;
-                return Some(Range::from_text_pos(source, abs_start, abs_end));
+                return Some(source.range(abs_start, abs_end));
             }
         }
     }
@@ -424,8 +434,7 @@ mod tests {
 
     #[test]
     fn test_service_install() {
-        let source =
-            r#"<Wix><ServiceInstall Name="MyService" DisplayName="My Service" /></Wix>"#;
+        let source = r#"<Wix><ServiceInstall Name="MyService" DisplayName="My Service" /></Wix>"#;
         let symbols = extract_symbols(source).unwrap();
 
         assert_eq!(symbols.len(), 1);
@@ -97,6 +97,48 @@ impl Range {
         let end = offset_to_position(source, end_offset);
         Self { start, end }
     }
+
+    /// Whether a position lies within the range, both ends included
+    pub fn contains(&self, position: Position) -> bool {
+        let at = (position.line, position.character);
+        (self.start.line, self.start.character) <= at && at <= (self.end.line, self.end.character)
+    }
+}
+
+/// Source text with precomputed line starts for fast position lookups
+pub(crate) struct SourceText<'a> {
+    text: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceText<'a> {
+    pub(crate) fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub(crate) fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Range between two byte offsets
+    pub(crate) fn range(&self, start_offset: usize, end_offset: usize) -> Range {
+        Range {
+            start: self.position(start_offset),
+            end: self.position(end_offset),
+        }
+    }
+
+    /// Line/column position (1-based) of a byte offset
+    fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let line_start = self.line_starts[line];
+        let column = self.text[line_start..offset].chars().count();
+        Position::new(line as u32 + 1, column as u32 + 1)
+    }
 }
 
 /// Convert byte offset to line/column position (1-based)
@@ -256,6 +298,16 @@ mod tests {
         assert_eq!(range.end.character, 10);
     }
 
+    #[test]
+    fn test_range_contains() {
+        let range = Range::new(2, 5, 3, 4);
+        assert!(range.contains(Position::new(2, 5)));
+        assert!(range.contains(Position::new(2, 80)));
+        assert!(range.contains(Position::new(3, 4)));
+        assert!(!range.contains(Position::new(2, 4)));
+        assert!(!range.contains(Position::new(3, 5)));
+    }
+
     #[test]
     fn test_offset_to_position() {
         let source = "abc\ndef\nghi";
@@ -265,6 +317,20 @@ mod tests {
         assert_eq!(offset_to_position(source, 8), Position::new(3, 1));
     }
 
+    #[test]
+    fn test_source_text_matches_offset_to_position() {
+        let source = "ab\u{e9}\nd\u{1f600}f\n\nghi";
+        let text = SourceText::new(source);
+        for offset in (0..=source.len()).filter(|&i| source.is_char_boundary(i)) {
+            assert_eq!(
+                text.position(offset),
+                offset_to_position(source, offset),
+                "offset {}",
+                offset
+            );
+        }
+    }
+
     #[test]
     fn test_symbol_with_detail() {
         let range = Range::new(1, 1, 1, 10);
@@ -280,7 +346,12 @@ mod tests {
         let mut parent = Symbol::new("Parent".to_string(), SymbolKind::Module, range, range);
 
         let child_range = Range::new(2, 1, 5, 1);
-        let child = Symbol::new("Child".to_string(), SymbolKind::Struct, child_range, child_range);
+        let child = Symbol::new(
+            "Child".to_string(),
+            SymbolKind::Struct,
+            child_range,
+            child_range,
+        );
 
         parent.add_child(child);
         assert_eq!(parent.children.len(), 1);
@@ -292,7 +363,12 @@ mod tests {
         let mut parent = Symbol::new("Parent".to_string(), SymbolKind::Module, range, range);
 
         let child_range = Range::new(2, 1, 5, 1);
-        let child = Symbol::new("Child".to_string(), SymbolKind::Struct, child_range, child_range);
+        let child = Symbol::new(
+            "Child".to_string(),
+            SymbolKind::Struct,
+            child_range,
+            child_range,
+        );
 
         parent.add_child(child);
 
@@ -303,7 +379,12 @@ mod tests {
     #[test]
     fn test_symbol_format_text() {
         let range = Range::new(1, 1, 1, 20);
-        let symbol = Symbol::new("MainComponent".to_string(), SymbolKind::Struct, range, range);
+        let symbol = Symbol::new(
+            "MainComponent".to_string(),
+            SymbolKind::Struct,
+            range,
+            range,
+        );
 
         let text = symbol.format_text(0);
         assert!(text.contains("Component: MainComponent"));
@@ -313,9 +394,13 @@ mod tests {
     #[test]
     fn test_symbol_format_text_with_detail() {
         let range = Range::new(1, 1, 1, 30);
-        let symbol =
-            Symbol::new("INSTALLFOLDER".to_string(), SymbolKind::Namespace, range, range)
-                .with_detail("MyApp".to_string());
+        let symbol = Symbol::new(
+            "INSTALLFOLDER".to_string(),
+            SymbolKind::Namespace,
+            range,
+            range,
+        )
+        .with_detail("MyApp".to_string());
 
         let text = symbol.format_text(0);
         assert!(text.contains("(MyApp)"));
@@ -0,0 +1,208 @@
+//! Element catalog loaded from wix-data
+//!
+//! The generators only need names: which elements exist (with their
+//! extension namespace), which attributes they take and the values an
+//! enumerated attribute accepts.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use wix_data::models::AttributeType;
+use wix_data::WixData;
+
+use crate::SyntaxError;
+
+/// Namespace of the core WiX schema; its elements are written unprefixed
+const CORE_NAMESPACE: &str = "wix";
+
+/// An attribute as far as syntax generation is concerned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogAttribute {
+    pub name: String,
+    /// Declared as yes/no
+    pub yes_no: bool,
+    pub enum_values: Vec<String>,
+}
+
+impl CatalogAttribute {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            yes_no: false,
+            enum_values: Vec::new(),
+        }
+    }
+
+    pub fn yes_no(mut self) -> Self {
+        self.yes_no = true;
+        self
+    }
+
+    pub fn with_enum_values<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.enum_values = values.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// An element as far as syntax generation is concerned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogElement {
+    pub name: String,
+    /// Schema namespace prefix (`wix` for the core schema, `util`, `iis`, ...)
+    pub namespace: String,
+    pub attributes: Vec<CatalogAttribute>,
+}
+
+impl CatalogElement {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            namespace: CORE_NAMESPACE.to_string(),
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    pub fn with_attribute(mut self, attribute: CatalogAttribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// Tag name as conventionally written in source (`Component`, `util:User`)
+    pub fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() || self.namespace == CORE_NAMESPACE {
+            self.name.clone()
+        } else {
+            format!("{}:{}", self.namespace, self.name)
+        }
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&CatalogAttribute> {
+        self.attributes.iter().find(|a| a.name == name)
+    }
+}
+
+/// All elements known to wix-data, sorted by qualified name
+#[derive(Debug, Clone, Default)]
+pub struct ElementCatalog {
+    elements: Vec<CatalogElement>,
+}
+
+impl ElementCatalog {
+    pub fn new(mut elements: Vec<CatalogElement>) -> Self {
+        elements.sort_by_key(|e| e.qualified_name());
+        elements.dedup_by_key(|e| e.qualified_name());
+        Self { elements }
+    }
+
+    /// Read every element and its attributes from an open wix-data store
+    pub fn from_wix_data(data: &WixData) -> Result<Self, SyntaxError> {
+        let mut elements = Vec::new();
+        for element in data.search_elements("", i64::MAX as usize)? {
+            let attributes = data
+                .get_attributes(&element.name)?
+                .into_iter()
+                .map(|a| CatalogAttribute {
+                    yes_no: a.attr_type == AttributeType::YesNo,
+                    name: a.name,
+                    enum_values: a.enum_values,
+                })
+                .collect();
+            elements.push(CatalogElement {
+                name: element.name,
+                namespace: element.namespace,
+                attributes,
+            });
+        }
+        Ok(Self::new(elements))
+    }
+
+    /// Load the catalog from a wix-data database file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, SyntaxError> {
+        Self::from_wix_data(&WixData::open(path)?)
+    }
+
+    /// Load the catalog from the default wix-data location
+    pub fn open_default() -> Result<Self, SyntaxError> {
+        Self::from_wix_data(&WixData::open_default()?)
+    }
+
+    pub fn elements(&self) -> &[CatalogElement] {
+        &self.elements
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Look up an element by qualified name
+    pub fn element(&self, name: &str) -> Option<&CatalogElement> {
+        self.elements.iter().find(|e| e.qualified_name() == name)
+    }
+
+    /// Distinct attribute names across all elements
+    pub fn attribute_names(&self) -> BTreeSet<&str> {
+        self.elements
+            .iter()
+            .flat_map(|e| e.attributes.iter().map(|a| a.name.as_str()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_qualified_name() {
+        assert_eq!(
+            CatalogElement::new("Component").qualified_name(),
+            "Component"
+        );
+        assert_eq!(
+            CatalogElement::new("User")
+                .with_namespace("util")
+                .qualified_name(),
+            "util:User"
+        );
+    }
+
+    #[test]
+    fn test_catalog_sorted_and_deduplicated() {
+        let catalog = ElementCatalog::new(vec![
+            CatalogElement::new("Package"),
+            CatalogElement::new("User").with_namespace("util"),
+            CatalogElement::new("Component"),
+            CatalogElement::new("Package"),
+        ]);
+        let names: Vec<_> = catalog
+            .elements()
+            .iter()
+            .map(|e| e.qualified_name())
+            .collect();
+        assert_eq!(names, ["Component", "Package", "util:User"]);
+        assert!(catalog.element("util:User").is_some());
+        assert!(catalog.element("User").is_none());
+    }
+
+    #[test]
+    fn test_open_bundled_database() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../common/wix-data/wix.db");
+        if !path.exists() {
+            return;
+        }
+        let catalog = ElementCatalog::open(&path).unwrap();
+        let action = catalog.element("CustomAction").unwrap();
+        let script = action.attribute("Script").unwrap();
+        assert!(script.enum_values.iter().any(|v| v == "vbscript"));
+        assert!(action.attribute("Impersonate").unwrap().yes_no);
+    }
+}
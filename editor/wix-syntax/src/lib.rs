@@ -1,8 +1,27 @@
 //! wix-syntax - Syntax highlighting definitions for WiX files
 //!
-//! Generates TextMate grammars and other syntax definitions.
+//! Generates TextMate grammars and other syntax definitions, including a
+//! tree-sitter grammar with highlight, injection and locals queries built
+//! from the wix-data element catalog.
+
+pub mod catalog;
+pub mod tree_sitter;
+
+pub use catalog::{CatalogAttribute, CatalogElement, ElementCatalog};
+pub use tree_sitter::TreeSitterGrammar;
 
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors from loading the catalog or writing generated files
+#[derive(Error, Debug)]
+pub enum SyntaxError {
+    #[error("wix-data error: {0}")]
+    Data(#[from] wix_data::WixDataError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
 
 /// TextMate grammar for WiX files
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,86 @@
+//! wix-syntax - Syntax definition generator for WiX
+//!
+//! Writes the TextMate grammar, language configuration or the tree-sitter
+//! grammar and queries.
+
+use clap::{Parser, Subcommand};
+use std::fs;
+use std::path::PathBuf;
+use wix_syntax::{ElementCatalog, LanguageConfiguration, TextMateGrammar, TreeSitterGrammar};
+
+#[derive(Parser)]
+#[command(name = "wix-syntax")]
+#[command(about = "Generate syntax definitions for WiX files")]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Generate the TextMate grammar
+    Textmate {
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate the language configuration
+    Config {
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Generate the tree-sitter grammar with highlight, injection and locals queries
+    TreeSitter {
+        /// Output directory (grammar.js and queries/)
+        #[arg(short, long, default_value = "tree-sitter-wix")]
+        output: PathBuf,
+
+        /// wix-data database (defaults to the wix-data search path)
+        #[arg(long)]
+        database: Option<PathBuf>,
+    },
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Commands::Textmate { output } => {
+            write_or_print(output, &TextMateGrammar::wix_grammar().to_json())?;
+        }
+        Commands::Config { output } => {
+            write_or_print(output, &LanguageConfiguration::wix_config().to_json())?;
+        }
+        Commands::TreeSitter { output, database } => {
+            let catalog = match database {
+                Some(path) => ElementCatalog::open(path)?,
+                None => ElementCatalog::open_default()?,
+            };
+            if catalog.is_empty() {
+                return Err("wix-data database has no elements".into());
+            }
+
+            TreeSitterGrammar::generate(&catalog).write_to(&output)?;
+            println!(
+                "Generated tree-sitter grammar for {} elements at: {}",
+                catalog.elements().len(),
+                output.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn write_or_print(output: Option<PathBuf>, content: &str) -> std::io::Result<()> {
+    match output {
+        Some(path) => {
+            fs::write(&path, content)?;
+            println!("Generated: {}", path.display());
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
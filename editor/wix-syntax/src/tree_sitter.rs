@@ -0,0 +1,625 @@
+//! Tree-sitter grammar and queries for WiX
+//!
+//! The grammar is XML with WiX on top: preprocessor instructions are
+//! structured nodes (`<?if?>` blocks nest their content, `<?define?>` has a
+//! name and a value) and attribute values are split into text and
+//! `$(var.X)`, `!(loc.X)`, `!(bind.X)` and `[PROP]` references. The element
+//! catalog drives the queries: known elements, yes/no and enumerated values
+//! get their own captures and script custom actions inject their language.
+//!
+//! Captures use names Neovim, Helix and Zed all understand, and patterns
+//! never capture the same node twice so the result does not depend on
+//! whether an editor lets the first or the last match win.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::catalog::ElementCatalog;
+use crate::SyntaxError;
+
+/// Script languages a custom action's `Script` value maps to
+const SCRIPT_LANGUAGES: &[(&str, &str)] = &[
+    ("vbscript", "vbscript"),
+    ("jscript", "javascript"),
+    ("powershell", "powershell"),
+];
+
+/// Attributes holding a command line that may run PowerShell
+const COMMAND_ATTRIBUTES: &[&str] = &["ExeCommand", "Value"];
+
+/// Patterns recognising a PowerShell command line; plain character classes
+/// read the same as Vim and Rust regexes
+const POWERSHELL_PATTERNS: &[&str] = &["[Pp]ower[Ss]hell", "[Pp]wsh"];
+
+/// Width `#any-of?` lists are wrapped at
+const LIST_WIDTH: usize = 80;
+
+/// Generated tree-sitter grammar with its queries
+#[derive(Debug, Clone)]
+pub struct TreeSitterGrammar {
+    pub grammar_js: String,
+    pub highlights: String,
+    pub injections: String,
+    pub locals: String,
+}
+
+impl TreeSitterGrammar {
+    /// Generate the grammar and queries from the element catalog
+    pub fn generate(catalog: &ElementCatalog) -> Self {
+        Self {
+            grammar_js: grammar_js(catalog),
+            highlights: highlights(catalog),
+            injections: injections(catalog),
+            locals: LOCALS.to_string(),
+        }
+    }
+
+    /// Files in the layout `tree-sitter generate` and editors expect
+    pub fn files(&self) -> [(&'static str, &str); 4] {
+        [
+            ("grammar.js", &self.grammar_js),
+            ("queries/highlights.scm", &self.highlights),
+            ("queries/injections.scm", &self.injections),
+            ("queries/locals.scm", &self.locals),
+        ]
+    }
+
+    /// Write the grammar and queries below `dir`
+    pub fn write_to(&self, dir: &Path) -> Result<(), SyntaxError> {
+        fs::create_dir_all(dir.join("queries"))?;
+        for (name, content) in self.files() {
+            fs::write(dir.join(name), content)?;
+        }
+        Ok(())
+    }
+}
+
+fn grammar_js(catalog: &ElementCatalog) -> String {
+    GRAMMAR_JS
+        .replace("{elements}", &catalog.elements().len().to_string())
+        .replace("{attributes}", &catalog.attribute_names().len().to_string())
+}
+
+fn highlights(catalog: &ElementCatalog) -> String {
+    let elements: BTreeSet<String> = catalog
+        .elements()
+        .iter()
+        .map(|e| e.qualified_name())
+        .collect();
+
+    let mut yes_no = BTreeSet::new();
+    let mut enumerated = BTreeSet::new();
+    let mut enum_values = BTreeSet::new();
+    for attribute in catalog.elements().iter().flat_map(|e| &e.attributes) {
+        if attribute.yes_no {
+            yes_no.insert(attribute.name.as_str());
+        } else if !attribute.enum_values.is_empty() {
+            enumerated.insert(attribute.name.as_str());
+            enum_values.extend(attribute.enum_values.iter().map(String::as_str));
+        }
+    }
+
+    let mut out = String::from(HIGHLIGHTS_HEADER);
+
+    out.push_str("\n; Elements from the catalog, anything else is unknown\n");
+    out.push_str(&format!(
+        "((tag_name) @tag.builtin\n {})\n",
+        any_of("any-of?", "tag.builtin", &elements)
+    ));
+    out.push_str(&format!(
+        "((tag_name) @tag\n {})\n",
+        any_of("not-any-of?", "tag", &elements)
+    ));
+
+    if !yes_no.is_empty() {
+        out.push_str("\n; yes/no attributes\n");
+        out.push_str(&format!(
+            "(attribute\n  name: (attribute_name) @_name\n  value: (attribute_value (value_text) @constant.builtin)\n {}\n (#any-of? @constant.builtin \"yes\" \"no\"))\n",
+            any_of("any-of?", "_name", &yes_no)
+        ));
+    }
+
+    if !enumerated.is_empty() {
+        out.push_str("\n; Enumerated attribute values\n");
+        out.push_str(&format!(
+            "(attribute\n  name: (attribute_name) @_name\n  value: (attribute_value (value_text) @constant)\n {}\n {})\n",
+            any_of("any-of?", "_name", &enumerated),
+            any_of("any-of?", "constant", &enum_values)
+        ));
+    }
+
+    out
+}
+
+fn injections(catalog: &ElementCatalog) -> String {
+    let mut out = String::from(INJECTIONS_HEADER);
+
+    for element in catalog.elements() {
+        for attribute in &element.attributes {
+            for value in &attribute.enum_values {
+                let Some((_, language)) = SCRIPT_LANGUAGES
+                    .iter()
+                    .find(|(script, _)| script.eq_ignore_ascii_case(value))
+                else {
+                    continue;
+                };
+                out.push_str(&format!(
+                    "\n; <{tag} {attr}=\"{value}\">\n\
+                     ((element\n   \
+                     (start_tag\n     \
+                     name: (tag_name) @_tag\n     \
+                     (attribute\n       \
+                     name: (attribute_name) @_attr\n       \
+                     value: (attribute_value (value_text) @_script)))\n   \
+                     [(text) @injection.content\n    \
+                     (cdata (cdata_content) @injection.content)])\n \
+                     (#eq? @_tag \"{tag}\")\n \
+                     (#eq? @_attr \"{attr}\")\n \
+                     (#eq? @_script \"{value}\")\n \
+                     (#set! injection.language \"{language}\")\n \
+                     (#set! injection.combined))\n",
+                    tag = element.qualified_name(),
+                    attr = attribute.name,
+                ));
+            }
+        }
+    }
+
+    let attributes: BTreeSet<&str> = COMMAND_ATTRIBUTES.iter().copied().collect();
+    for pattern in POWERSHELL_PATTERNS {
+        out.push_str(&format!(
+            "\n; Command lines running {pattern}\n\
+             ((attribute\n   \
+             name: (attribute_name) @_attr\n   \
+             value: (attribute_value (value_text) @injection.content))\n \
+             {}\n \
+             (#match? @injection.content \"{pattern}\")\n \
+             (#set! injection.language \"powershell\"))\n",
+            any_of("any-of?", "_attr", &attributes),
+        ));
+    }
+
+    out
+}
+
+/// Format an `#any-of?`-style predicate, wrapping long lists
+fn any_of<I, S>(predicate: &str, capture: &str, values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut out = format!("(#{predicate} @{capture}");
+    let mut line = out.len();
+    for value in values {
+        let quoted = format!(
+            "\"{}\"",
+            value.as_ref().replace('\\', "\\\\").replace('"', "\\\"")
+        );
+        if line + 1 + quoted.len() > LIST_WIDTH {
+            out.push_str("\n   ");
+            line = 3;
+        } else {
+            out.push(' ');
+            line += 1;
+        }
+        line += quoted.len();
+        out.push_str(&quoted);
+    }
+    out.push(')');
+    out
+}
+
+const GRAMMAR_JS: &str = r#"/**
+ * @file Tree-sitter grammar for WiX Toolset sources (.wxs, .wxi, .wxl)
+ * @license MIT
+ *
+ * Generated by wix-syntax from the wix-data element catalog
+ * ({elements} elements, {attributes} attribute names). Do not edit.
+ */
+
+/// <reference types="tree-sitter-cli/dsl" />
+// @ts-check
+
+const NAME = /[A-Za-z_][A-Za-z0-9_.\-]*/;
+const QUALIFIED_NAME = /[A-Za-z_][A-Za-z0-9_.\-]*(:[A-Za-z_][A-Za-z0-9_.\-]*)?/;
+const REFERENCE_NAME = /[A-Za-z_][A-Za-z0-9_.\-]*/;
+
+module.exports = grammar({
+  name: 'wix',
+
+  extras: _ => [/\s+/],
+
+  rules: {
+    document: $ => seq(
+      optional($.xml_declaration),
+      repeat($._node),
+    ),
+
+    _node: $ => choice(
+      $.element,
+      $.comment,
+      $.cdata,
+      $.text,
+      $.entity_reference,
+      $._preprocessor,
+    ),
+
+    xml_declaration: $ => seq('<?xml', repeat($.attribute), '?>'),
+
+    // Elements
+
+    element: $ => choice(
+      seq(
+        field('start', $.start_tag),
+        repeat($._node),
+        field('end', $.end_tag),
+      ),
+      $.self_closing_tag,
+    ),
+
+    start_tag: $ => seq('<', field('name', $.tag_name), repeat($.attribute), '>'),
+
+    self_closing_tag: $ => seq('<', field('name', $.tag_name), repeat($.attribute), '/>'),
+
+    end_tag: $ => seq('</', field('name', $.tag_name), '>'),
+
+    tag_name: _ => QUALIFIED_NAME,
+
+    attribute: $ => seq(
+      field('name', $.attribute_name),
+      '=',
+      field('value', $.attribute_value),
+    ),
+
+    attribute_name: _ => QUALIFIED_NAME,
+
+    attribute_value: $ => choice(
+      seq(
+        '"',
+        repeat(choice(alias($._double_quoted_text, $.value_text), $._reference)),
+        token.immediate('"'),
+      ),
+      seq(
+        "'",
+        repeat(choice(alias($._single_quoted_text, $.value_text), $._reference)),
+        token.immediate("'"),
+      ),
+    ),
+
+    // A lone `$` or `!` that does not start a reference is plain text
+    _double_quoted_text: _ => token.immediate(prec(-1, /[^"$!\[&]+|[$!]/)),
+
+    _single_quoted_text: _ => token.immediate(prec(-1, /[^'$!\[&]+|[$!]/)),
+
+    // References inside values
+
+    _reference: $ => choice(
+      $.preprocessor_reference,
+      $.localization_reference,
+      $.bind_reference,
+      $.property_reference,
+      alias($._value_entity, $.entity_reference),
+    ),
+
+    // $(var.Name), $(env.Name), $(sys.Name)
+    preprocessor_reference: $ => seq(
+      token.immediate('$('),
+      field('scope', alias(token.immediate(choice('var', 'env', 'sys')), $.reference_scope)),
+      token.immediate('.'),
+      field('name', alias(token.immediate(REFERENCE_NAME), $.reference_name)),
+      token.immediate(')'),
+    ),
+
+    // !(loc.Name)
+    localization_reference: $ => seq(
+      token.immediate('!('),
+      field('scope', alias(token.immediate('loc'), $.reference_scope)),
+      token.immediate('.'),
+      field('name', alias(token.immediate(REFERENCE_NAME), $.reference_name)),
+      token.immediate(')'),
+    ),
+
+    // !(bind.Name), !(bindpath.Name), !(wix.Name)
+    bind_reference: $ => seq(
+      token.immediate('!('),
+      field('scope', alias(token.immediate(choice('bind', 'bindpath', 'wix')), $.reference_scope)),
+      token.immediate('.'),
+      field('name', alias(token.immediate(REFERENCE_NAME), $.reference_name)),
+      token.immediate(')'),
+    ),
+
+    // [PROPERTY], [#FileId], [!FileId], [$ComponentId], [%ENVVAR]
+    property_reference: $ => seq(
+      token.immediate('['),
+      field('name', alias(token.immediate(/[#!$%]?[A-Za-z_][A-Za-z0-9_.]*/), $.property_name)),
+      token.immediate(']'),
+    ),
+
+    _value_entity: _ => token.immediate(/&(#[0-9]+|#x[0-9A-Fa-f]+|[A-Za-z][A-Za-z0-9]*);/),
+
+    // Content
+
+    text: _ => /[^<&\s]([^<&]*[^<&\s])?/,
+
+    entity_reference: _ => /&(#[0-9]+|#x[0-9A-Fa-f]+|[A-Za-z][A-Za-z0-9]*);/,
+
+    comment: _ => token(seq('<!--', /([^-]|-[^-])*/, '-->')),
+
+    cdata: $ => seq(
+      '<![CDATA[',
+      optional(alias(token.immediate(/([^\]]|\][^\]]|\]\][^>])+/), $.cdata_content)),
+      ']]>',
+    ),
+
+    // Preprocessor
+
+    _preprocessor: $ => choice(
+      $.define,
+      $.undef,
+      $.include,
+      $.conditional,
+      $.foreach,
+      $.diagnostic,
+      $.processing_instruction,
+    ),
+
+    define: $ => seq(
+      '<?define',
+      field('name', $.variable_name),
+      optional(seq('=', field('value', choice($.string, $.pi_text)))),
+      '?>',
+    ),
+
+    undef: $ => seq('<?undef', field('name', $.variable_name), '?>'),
+
+    include: $ => seq('<?include', field('path', choice($.string, $.pi_text)), '?>'),
+
+    conditional: $ => seq(
+      $.if_directive,
+      repeat($._node),
+      repeat($.elseif_clause),
+      optional($.else_clause),
+      $.endif_directive,
+    ),
+
+    if_directive: $ => choice(
+      seq('<?if', field('condition', $.pi_text), '?>'),
+      seq(choice('<?ifdef', '<?ifndef'), field('name', $.variable_name), '?>'),
+    ),
+
+    elseif_clause: $ => seq(
+      '<?elseif',
+      field('condition', $.pi_text),
+      '?>',
+      repeat($._node),
+    ),
+
+    else_clause: $ => seq('<?else', '?>', repeat($._node)),
+
+    endif_directive: _ => seq('<?endif', '?>'),
+
+    foreach: $ => seq(
+      '<?foreach',
+      field('variable', $.variable_name),
+      'in',
+      field('values', $.pi_text),
+      '?>',
+      repeat($._node),
+      '<?endforeach',
+      '?>',
+    ),
+
+    diagnostic: $ => seq(
+      choice('<?error', '<?warning'),
+      field('message', $.pi_text),
+      '?>',
+    ),
+
+    // <?pragma ...?> and instructions for other processors
+    processing_instruction: $ => seq(
+      '<?',
+      field('target', alias(token.immediate(NAME), $.pi_target)),
+      optional(field('content', $.pi_text)),
+      '?>',
+    ),
+
+    variable_name: _ => NAME,
+
+    string: _ => token(prec(1, /"[^"]*"/)),
+
+    pi_text: _ => /([^?\s]|\?[^>])([^?]|\?[^>])*/,
+  },
+});
+"#;
+
+const HIGHLIGHTS_HEADER: &str = r#"; Generated by wix-syntax from the wix-data element catalog. Do not edit.
+
+(comment) @comment
+
+["<" ">" "</" "/>" "<![CDATA[" "]]>"] @punctuation.bracket
+
+"=" @punctuation.delimiter
+
+(attribute_name) @attribute
+
+(attribute_value) @string
+
+(entity_reference) @string.escape
+
+; Preprocessor
+
+[
+  "<?xml"
+  "<?define"
+  "<?undef"
+  "<?include"
+  "<?if"
+  "<?ifdef"
+  "<?ifndef"
+  "<?elseif"
+  "<?else"
+  "<?endif"
+  "<?foreach"
+  "<?endforeach"
+  "<?error"
+  "<?warning"
+  "<?"
+  "?>"
+] @keyword.directive
+
+"in" @keyword
+
+(pi_target) @keyword.directive
+
+(variable_name) @constant.macro
+
+(string) @string
+
+(include path: (_) @string.special.path)
+
+(diagnostic message: (pi_text) @string)
+
+; References inside values
+
+["$(" "!(" ")" "[" "]"] @punctuation.special
+
+(reference_scope) @variable.builtin
+
+(preprocessor_reference "." @punctuation.delimiter)
+
+(localization_reference "." @punctuation.delimiter)
+
+(bind_reference "." @punctuation.delimiter)
+
+(preprocessor_reference name: (reference_name) @constant.macro)
+
+(localization_reference name: (reference_name) @string.special)
+
+(bind_reference name: (reference_name) @variable)
+
+(property_name) @variable
+"#;
+
+const INJECTIONS_HEADER: &str = r#"; Generated by wix-syntax from the wix-data element catalog. Do not edit.
+
+((comment) @injection.content
+ (#set! injection.language "comment"))
+"#;
+
+const LOCALS: &str = r#"; Generated by wix-syntax. Do not edit.
+
+(document) @local.scope
+
+(foreach) @local.scope
+
+(define name: (variable_name) @local.definition)
+
+(foreach variable: (variable_name) @local.definition)
+
+(preprocessor_reference
+  scope: (reference_scope) @_scope
+  name: (reference_name) @local.reference
+  (#eq? @_scope "var"))
+
+(if_directive name: (variable_name) @local.reference)
+
+(undef name: (variable_name) @local.reference)
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::{CatalogAttribute, CatalogElement};
+
+    fn catalog() -> ElementCatalog {
+        ElementCatalog::new(vec![
+            CatalogElement::new("CustomAction")
+                .with_attribute(CatalogAttribute::new("ExeCommand"))
+                .with_attribute(
+                    CatalogAttribute::new("Script").with_enum_values(["jscript", "vbscript"]),
+                )
+                .with_attribute(
+                    CatalogAttribute::new("Impersonate")
+                        .yes_no()
+                        .with_enum_values(["no", "yes"]),
+                )
+                .with_attribute(
+                    CatalogAttribute::new("Execute").with_enum_values(["deferred", "immediate"]),
+                ),
+            CatalogElement::new("Package"),
+            CatalogElement::new("User").with_namespace("util"),
+        ])
+    }
+
+    #[test]
+    fn test_grammar_js() {
+        let grammar = TreeSitterGrammar::generate(&catalog());
+        assert!(grammar.grammar_js.contains("name: 'wix'"));
+        assert!(grammar
+            .grammar_js
+            .contains("(3 elements, 4 attribute names)"));
+        for rule in [
+            "conditional:",
+            "define:",
+            "foreach:",
+            "preprocessor_reference:",
+            "localization_reference:",
+            "property_reference:",
+        ] {
+            assert!(grammar.grammar_js.contains(rule), "missing {rule}");
+        }
+    }
+
+    #[test]
+    fn test_highlights_use_catalog() {
+        let highlights = TreeSitterGrammar::generate(&catalog()).highlights;
+        assert!(highlights.contains(
+            "((tag_name) @tag.builtin\n (#any-of? @tag.builtin \"CustomAction\" \"Package\" \"util:User\"))"
+        ));
+        assert!(
+            highlights.contains("(#not-any-of? @tag \"CustomAction\" \"Package\" \"util:User\")")
+        );
+        assert!(highlights.contains("(#any-of? @_name \"Impersonate\")"));
+        assert!(highlights
+            .contains("(#any-of? @constant \"deferred\" \"immediate\" \"jscript\" \"vbscript\")"));
+        // yes/no attributes are not repeated as enumerations
+        assert!(!highlights.contains("(#any-of? @_name \"Execute\" \"Impersonate\")"));
+    }
+
+    #[test]
+    fn test_script_injections() {
+        let injections = TreeSitterGrammar::generate(&catalog()).injections;
+        assert!(injections
+            .contains("(#eq? @_script \"jscript\")\n (#set! injection.language \"javascript\")"));
+        assert!(injections
+            .contains("(#eq? @_script \"vbscript\")\n (#set! injection.language \"vbscript\")"));
+        assert!(injections.contains("(#match? @injection.content \"[Pp]ower[Ss]hell\")"));
+        assert_eq!(
+            injections.matches("(#eq? @_tag \"CustomAction\")").count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_any_of_wraps() {
+        let values: Vec<String> = (0..30).map(|i| format!("Value{i}")).collect();
+        let predicate = any_of("any-of?", "x", &values);
+        assert!(predicate.starts_with("(#any-of? @x \"Value0\""));
+        assert!(predicate.ends_with("\"Value29\")"));
+        assert!(predicate.lines().all(|line| line.len() <= LIST_WIDTH));
+        assert!(predicate.lines().count() > 1);
+    }
+
+    #[test]
+    fn test_write_to() {
+        let dir = tempfile::tempdir().unwrap();
+        TreeSitterGrammar::generate(&catalog())
+            .write_to(dir.path())
+            .unwrap();
+        assert!(dir.path().join("grammar.js").exists());
+        for query in ["highlights", "injections", "locals"] {
+            assert!(dir.path().join(format!("queries/{query}.scm")).exists());
+        }
+    }
+}
@@ -5,6 +5,7 @@
 use clap::{Parser, Subcommand};
 use std::fs;
 use std::path::PathBuf;
+use wix_snippets::{ExportFormat, SnippetLibrary};
 use wix_vscode::{ExtensionGenerator, ExtensionManifest, generate_extension_ts};
 
 #[derive(Parser)]
@@ -23,6 +24,10 @@ enum Commands {
         /// Output directory for the extension
         #[arg(short, long, default_value = "wix-vscode-extension")]
         output: PathBuf,
+
+        /// wix-data database the snippets are generated from
+        #[arg(long)]
+        database: Option<PathBuf>,
     },
     /// Generate only package.json
     Manifest {
@@ -42,13 +47,23 @@ enum Commands {
         #[arg(short, long)]
         output: Option<PathBuf>,
     },
+    /// Generate only the snippets file
+    Snippets {
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// wix-data database the snippets are generated from
+        #[arg(long)]
+        database: Option<PathBuf>,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Generate { output } => {
+        Commands::Generate { output, database } => {
             // Create directory structure
             fs::create_dir_all(&output)?;
             fs::create_dir_all(output.join("src"))?;
@@ -65,6 +80,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Generate tsconfig.json
             fs::write(output.join("tsconfig.json"), ExtensionGenerator::generate_tsconfig())?;
 
+            // Generate snippets
+            let snippets = load_snippets(database)?;
+            fs::write(
+                output.join("snippets/wix.json"),
+                snippets.export(ExportFormat::Vscode),
+            )?;
+
             // Generate .vscodeignore
             let vscodeignore = r#".vscode/**
 .vscode-test/**
@@ -138,7 +160,29 @@ Language support for WiX Toolset files in Visual Studio Code.
                 println!("{}", config);
             }
         }
+
+        Commands::Snippets { output, database } => {
+            let snippets = load_snippets(database)?.export(ExportFormat::Vscode);
+            if let Some(path) = output {
+                fs::write(&path, &snippets)?;
+                println!("Generated snippets at: {}", path.display());
+            } else {
+                println!("{}", snippets);
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Snippets generated from wix-data, or the built-in ones when no database
+/// was given and none is found
+fn load_snippets(database: Option<PathBuf>) -> Result<SnippetLibrary, Box<dyn std::error::Error>> {
+    match database {
+        Some(path) => Ok(SnippetLibrary::open(path)?),
+        None => Ok(SnippetLibrary::open_default().unwrap_or_else(|e| {
+            eprintln!("Warning: using built-in snippets ({})", e);
+            SnippetLibrary::new()
+        })),
+    }
+}
@@ -265,6 +265,7 @@ mod tests {
             help: None,
             fix: None,
             notes: vec![],
+            related: vec![],
         }
     }
 
@@ -1,231 +1,348 @@
 //! Caching system for incremental linting
 //!
-//! Caches lint results by file content hash to dramatically speed up
-//! subsequent runs when files haven't changed.
+//! The cache is a content-addressed store in a directory. Entries are keyed
+//! by hashes of what determines a result, never by paths or timestamps:
+//!
+//! - lint results: file content + plugin and file extension + effective
+//!   config + rule set
+//! - file symbols: file content + rule set (plugin versions)
+//! - cross-file results: workspace-relative path + file content + effective
+//!   config + the files it depends on through the `SymbolIndex` (definitions
+//!   it references, duplicates of its definitions)
+//!
+//! Because keys don't depend on mtimes or absolute paths, CI jobs can share
+//! one cache directory and still hit after a fresh checkout. Entries are
+//! written atomically, so concurrent jobs can use the same directory. A hit
+//! refreshes the entry's mtime, so `prune` ages entries by last use.
 
+use crate::config::Config;
 use crate::diagnostic::Diagnostic;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
 use std::fs;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
 
-/// Cache entry for a single file
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CacheEntry {
-    /// Content hash of the file
-    pub content_hash: String,
-    /// Last modified time (Unix timestamp)
-    pub mtime: u64,
-    /// File size in bytes
-    pub size: u64,
-    /// Cached diagnostics
-    pub diagnostics: Vec<CachedDiagnostic>,
-    /// Rules that were applied (for invalidation if rules change)
-    pub rule_versions: HashMap<String, String>,
-}
+/// Current cache format version (bump to invalidate all caches)
+pub const CACHE_VERSION: u32 = 2;
 
-/// Simplified diagnostic for caching
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CachedDiagnostic {
-    pub rule_id: String,
-    pub message: String,
-    pub severity: String,
-    pub line: usize,
-    pub column: usize,
-    pub source_line: Option<String>,
+/// Distinguishes temporary files of concurrent writers in one process
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A key into the content-addressed store
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    namespace: &'static str,
+    hash: String,
 }
 
-impl CachedDiagnostic {
-    /// Convert from a full Diagnostic
-    pub fn from_diagnostic(diag: &Diagnostic) -> Self {
+impl CacheKey {
+    fn new(namespace: &'static str, parts: &[&str]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(CACHE_VERSION.to_le_bytes());
+        hasher.update(namespace.as_bytes());
+        for part in parts {
+            hasher.update([0]);
+            hasher.update(part.as_bytes());
+        }
         Self {
-            rule_id: diag.rule_id.clone(),
-            message: diag.message.clone(),
-            severity: format!("{:?}", diag.severity),
-            line: diag.location.line,
-            column: diag.location.column,
-            source_line: diag.source_line.clone(),
+            namespace,
+            hash: hex(&hasher.finalize()),
         }
     }
 
-    /// Convert back to a full Diagnostic
-    pub fn to_diagnostic(&self, file: PathBuf) -> Diagnostic {
-        use crate::diagnostic::{Location, Severity};
+    /// Key for single-file lint results
+    ///
+    /// The same content linted by another plugin or as another file type
+    /// produces different results, so both are part of the key.
+    pub fn lint(
+        content_hash: &str,
+        plugin: &str,
+        extension: &str,
+        config_hash: &str,
+        ruleset_hash: &str,
+    ) -> Self {
+        Self::new(
+            "lint",
+            &[content_hash, plugin, extension, config_hash, ruleset_hash],
+        )
+    }
 
-        Diagnostic {
-            rule_id: self.rule_id.clone(),
-            message: self.message.clone(),
-            severity: match self.severity.to_lowercase().as_str() {
-                "error" => Severity::Error,
-                "warning" => Severity::Warning,
-                _ => Severity::Info,
-            },
-            location: Location::new(file, self.line, self.column),
-            source_line: self.source_line.clone(),
-            context_before: vec![],
-            context_after: vec![],
-            help: None,
-            fix: None,
-            notes: vec![],
-        }
+    /// Key for the symbols a file defines and references
+    pub fn symbols(content_hash: &str, ruleset_hash: &str) -> Self {
+        Self::new("symbols", &[content_hash, ruleset_hash])
+    }
+
+    /// Key for the cross-file results reported in a file
+    ///
+    /// `dependencies` are `(path, content hash)` pairs from
+    /// `SymbolIndex::dependencies`, so editing any file that defines a symbol
+    /// this file uses (or duplicates) invalidates the entry. Paths are made
+    /// workspace-relative so other checkouts hit the same entry.
+    pub fn cross_file(
+        file: &Path,
+        content_hash: &str,
+        config_hash: &str,
+        dependencies: &[(PathBuf, String)],
+    ) -> Self {
+        let file = workspace_relative(file);
+        let file = file.to_string_lossy();
+        let mut parts = vec![file.as_ref(), content_hash, config_hash];
+        let deps: Vec<String> = dependencies
+            .iter()
+            .map(|(path, hash)| format!("{}={}", workspace_relative(path).display(), hash))
+            .collect();
+        parts.extend(deps.iter().map(String::as_str));
+        Self::new("xref", &parts)
+    }
+
+    /// The hex digest of this key
+    pub fn as_str(&self) -> &str {
+        &self.hash
     }
 }
 
-/// Lint cache for incremental runs
-#[derive(Debug, Default, Serialize, Deserialize)]
-pub struct LintCache {
-    /// Cache version (bump to invalidate all caches)
-    pub version: u32,
-    /// Entries by file path
-    pub entries: HashMap<String, CacheEntry>,
-    /// Configuration hash (invalidate if config changes)
-    #[serde(default)]
-    pub config_hash: String,
+/// An additional file read while linting (e.g., an external script)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheInput {
+    /// Path relative to the linted file's directory
+    pub path: PathBuf,
+    /// Content hash at the time the entry was written
+    pub hash: String,
 }
 
-impl LintCache {
-    /// Current cache format version
-    const CACHE_VERSION: u32 = 1;
+/// Cached lint result for one file's content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Path the entry was produced for (locations are rewritten on a hit)
+    pub file: PathBuf,
+    /// Full diagnostics, including fixes, notes and related locations
+    pub diagnostics: Vec<Diagnostic>,
+    /// Other files the result depends on
+    #[serde(default)]
+    pub inputs: Vec<CacheInput>,
+}
 
-    /// Create a new empty cache
-    pub fn new() -> Self {
-        Self {
-            version: Self::CACHE_VERSION,
-            entries: HashMap::new(),
-            config_hash: String::new(),
+impl CacheEntry {
+    /// Diagnostics relocated to `file`, or `None` if an input has changed
+    fn into_diagnostics(self, file: &Path) -> Option<Vec<Diagnostic>> {
+        let base = file.parent().unwrap_or(Path::new(""));
+        for input in &self.inputs {
+            if hash_file(&base.join(&input.path)).ok()? != input.hash {
+                return None;
+            }
         }
-    }
-
-    /// Load cache from file
-    pub fn load(path: &Path) -> Result<Self, std::io::Error> {
-        let file = fs::File::open(path)?;
-        let mut reader = std::io::BufReader::new(file);
-        let mut content = Vec::new();
-        reader.read_to_end(&mut content)?;
 
-        let cache: Self = serde_json::from_slice(&content)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let original = self.file;
+        let relocate = |location: &mut crate::diagnostic::Location| {
+            if location.file == original {
+                location.file = file.to_path_buf();
+            }
+        };
 
-        // Invalidate if version mismatch
-        if cache.version != Self::CACHE_VERSION {
-            return Ok(Self::new());
+        let mut diagnostics = self.diagnostics;
+        for diag in &mut diagnostics {
+            relocate(&mut diag.location);
+            for related in &mut diag.related {
+                relocate(&mut related.location);
+            }
         }
-
-        Ok(cache)
+        Some(diagnostics)
     }
+}
 
-    /// Save cache to file
-    pub fn save(&self, path: &Path) -> Result<(), std::io::Error> {
-        // Create parent directories if needed
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
+/// Content-addressed lint cache backed by a directory
+#[derive(Debug)]
+pub struct LintCache {
+    /// Root directory of the store
+    dir: PathBuf,
+    /// Lookups that found a valid entry
+    hits: AtomicUsize,
+    /// Lookups that found nothing usable
+    misses: AtomicUsize,
+}
 
-        let content = serde_json::to_vec(self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+impl LintCache {
+    /// Open (and create if needed) a cache directory
+    pub fn open(dir: &Path) -> Result<Self, std::io::Error> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        })
+    }
 
-        let mut file = fs::File::create(path)?;
-        file.write_all(&content)?;
-        Ok(())
+    /// Root directory of the store
+    pub fn dir(&self) -> &Path {
+        &self.dir
     }
 
-    /// Set config hash (call before using cache)
-    pub fn set_config_hash(&mut self, hash: &str) {
-        if self.config_hash != hash {
-            // Config changed, invalidate all entries
-            self.entries.clear();
-            self.config_hash = hash.to_string();
-        }
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir
+            .join(key.namespace)
+            .join(&key.hash[..2])
+            .join(format!("{}.json", &key.hash[2..]))
     }
 
-    /// Check if a file is cached and still valid
-    pub fn get(&self, file: &Path) -> Option<&CacheEntry> {
-        let key = file.to_string_lossy().to_string();
-        let entry = self.entries.get(&key)?;
+    /// Look up a raw entry
+    pub fn get<T: DeserializeOwned>(&self, key: &CacheKey) -> Option<T> {
+        self.record(self.read(key))
+    }
 
-        // Validate entry is still fresh
-        if let Ok(metadata) = fs::metadata(file) {
-            let mtime = metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
+    fn read<T: DeserializeOwned>(&self, key: &CacheKey) -> Option<T> {
+        let path = self.entry_path(key);
+        let content = fs::read(&path).ok()?;
+        let value = serde_json::from_slice(&content).ok()?;
+        // Keep entries that are still used from being pruned; best effort
+        let _ = fs::File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()));
+        Some(value)
+    }
 
-            let size = metadata.len();
+    fn record<T>(&self, value: Option<T>) -> Option<T> {
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
 
-            // Quick check: mtime and size
-            if entry.mtime == mtime && entry.size == size {
-                return Some(entry);
-            }
+    /// Store a raw entry
+    ///
+    /// Written to a temporary file and renamed into place, so readers never
+    /// observe a partial entry.
+    pub fn put<T: Serialize>(&self, key: &CacheKey, value: &T) -> Result<(), std::io::Error> {
+        let path = self.entry_path(key);
+        let parent = path.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(parent)?;
 
-            // Slower check: content hash
-            if let Ok(content_hash) = hash_file(file) {
-                if entry.content_hash == content_hash {
-                    return Some(entry);
-                }
-            }
-        }
+        let content = serde_json::to_vec(value)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        let tmp = parent.join(format!(
+            ".{}.{}.{}.tmp",
+            &key.hash[2..],
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&content)?;
+        drop(file);
+        fs::rename(&tmp, &path)
+    }
 
-        None
+    /// Look up lint results for a file
+    pub fn get_lint(&self, key: &CacheKey, file: &Path) -> Option<Vec<Diagnostic>> {
+        self.record(
+            self.read::<CacheEntry>(key)
+                .and_then(|entry| entry.into_diagnostics(file)),
+        )
     }
 
-    /// Store diagnostics for a file
-    pub fn put(
-        &mut self,
+    /// Store lint results for a file
+    ///
+    /// `inputs` are other files that were read, as paths relative to the
+    /// file's directory.
+    pub fn put_lint(
+        &self,
+        key: &CacheKey,
         file: &Path,
         diagnostics: &[Diagnostic],
-        rule_versions: HashMap<String, String>,
-    ) {
-        let key = file.to_string_lossy().to_string();
-
-        // Get file metadata
-        let (mtime, size) = fs::metadata(file)
-            .map(|m| {
-                let mtime = m
-                    .modified()
-                    .ok()
-                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-                (mtime, m.len())
+        inputs: &[PathBuf],
+    ) -> Result<(), std::io::Error> {
+        let base = file.parent().unwrap_or(Path::new(""));
+        let inputs = inputs
+            .iter()
+            .map(|path| {
+                Ok(CacheInput {
+                    hash: hash_file(&base.join(path))?,
+                    path: path.clone(),
+                })
             })
-            .unwrap_or((0, 0));
-
-        // Hash file content
-        let content_hash = hash_file(file).unwrap_or_default();
-
-        let entry = CacheEntry {
-            content_hash,
-            mtime,
-            size,
-            diagnostics: diagnostics
-                .iter()
-                .map(CachedDiagnostic::from_diagnostic)
-                .collect(),
-            rule_versions,
-        };
-
-        self.entries.insert(key, entry);
+            .collect::<Result<Vec<_>, std::io::Error>>()?;
+
+        self.put(
+            key,
+            &CacheEntry {
+                file: file.to_path_buf(),
+                diagnostics: diagnostics.to_vec(),
+                inputs,
+            },
+        )
     }
 
-    /// Remove entries for files that no longer exist
-    pub fn prune(&mut self) {
-        self.entries.retain(|path, _| Path::new(path).exists());
+    /// Remove entries not written or hit within `max_age`
+    ///
+    /// Entries that another run removes meanwhile are skipped.
+    pub fn prune(&self, max_age: Duration) -> Result<usize, std::io::Error> {
+        let cutoff = SystemTime::now()
+            .checked_sub(max_age)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut removed = 0;
+
+        for path in self.entry_files()? {
+            let modified = match fs::metadata(&path).and_then(|m| m.modified()) {
+                Ok(modified) => modified,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            if modified < cutoff {
+                match fs::remove_file(&path) {
+                    Ok(()) => removed += 1,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        Ok(removed)
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
+        let files = self.entry_files().unwrap_or_default();
         CacheStats {
-            total_entries: self.entries.len(),
-            total_diagnostics: self.entries.values().map(|e| e.diagnostics.len()).sum(),
+            total_entries: files.len(),
+            total_bytes: files
+                .iter()
+                .filter_map(|f| fs::metadata(f).ok())
+                .map(|m| m.len())
+                .sum(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
         }
     }
 
     /// Clear all entries
-    pub fn clear(&mut self) {
-        self.entries.clear();
+    pub fn clear(&self) -> Result<(), std::io::Error> {
+        for path in self.entry_files()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// All entry files in the store
+    fn entry_files(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        let mut files = Vec::new();
+        let mut pending = vec![self.dir.clone()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.extension().is_some_and(|e| e == "json") {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
     }
 }
 
@@ -233,37 +350,81 @@ impl LintCache {
 #[derive(Debug)]
 pub struct CacheStats {
     pub total_entries: usize,
-    pub total_diagnostics: usize,
+    pub total_bytes: u64,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash content bytes
+pub fn hash_content(content: &[u8]) -> String {
+    hex(&Sha256::digest(content))
 }
 
 /// Hash a file's content
-fn hash_file(path: &Path) -> Result<String, std::io::Error> {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-
-    let content = fs::read(path)?;
-    let mut hasher = DefaultHasher::new();
-    content.hash(&mut hasher);
-    Ok(format!("{:x}", hasher.finish()))
+pub fn hash_file(path: &Path) -> Result<String, std::io::Error> {
+    Ok(hash_content(&fs::read(path)?))
 }
 
 /// Hash a config for cache invalidation
+///
+/// Goes through `serde_json::Value` so map keys are sorted and the hash is
+/// the same in every process.
 pub fn hash_config(config: &impl serde::Serialize) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+    let value = serde_json::to_value(config).unwrap_or_default();
+    hash_content(value.to_string().as_bytes())
+}
+
+/// Hash the parts of a config that affect diagnostics for one file
+///
+/// Engine, output and file-selection settings are left out, and per-file
+/// ignores are reduced to the patterns that match `file`.
+pub fn hash_effective_config(config: &Config, file: &Path) -> String {
+    let file_str = file.to_string_lossy();
+    let per_file: std::collections::BTreeMap<&String, &Vec<String>> = config
+        .rules
+        .per_file
+        .iter()
+        .filter(|(pattern, _)| {
+            globset::Glob::new(pattern)
+                .map(|g| g.compile_matcher().is_match(file_str.as_ref()))
+                .unwrap_or(false)
+        })
+        .collect();
+
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    if let Some(obj) = value.as_object_mut() {
+        for section in ["engine", "output", "files", "extends"] {
+            obj.remove(section);
+        }
+    }
+    value["rules"]["per_file"] = serde_json::to_value(per_file).unwrap_or_default();
+    hash_content(value.to_string().as_bytes())
+}
 
-    let json = serde_json::to_string(config).unwrap_or_default();
-    let mut hasher = DefaultHasher::new();
-    json.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+/// `path` relative to the working directory, without `.` components
+///
+/// Paths outside the working directory are returned unchanged.
+pub fn workspace_relative(path: &Path) -> PathBuf {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    relative
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .collect()
 }
 
-/// Default cache file location
-pub fn default_cache_path() -> PathBuf {
+/// Default cache directory location
+pub fn default_cache_dir() -> PathBuf {
     dirs::cache_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("winter")
-        .join("cache.json")
+        .join("store")
 }
 
 #[cfg(test)]
@@ -284,171 +445,187 @@ mod tests {
             help: None,
             fix: None,
             notes: vec![],
+            related: vec![],
         }
     }
 
     #[test]
-    fn test_cache_new() {
-        let cache = LintCache::new();
-        assert_eq!(cache.version, LintCache::CACHE_VERSION);
-        assert!(cache.entries.is_empty());
+    fn test_cache_key_depends_on_all_parts() {
+        let key = CacheKey::lint("content", "wix", "wxs", "config", "rules");
+        assert_eq!(
+            key,
+            CacheKey::lint("content", "wix", "wxs", "config", "rules")
+        );
+        assert_ne!(
+            key,
+            CacheKey::lint("content2", "wix", "wxs", "config", "rules")
+        );
+        assert_ne!(
+            key,
+            CacheKey::lint("content", "other", "wxs", "config", "rules")
+        );
+        assert_ne!(
+            key,
+            CacheKey::lint("content", "wix", "wxi", "config", "rules")
+        );
+        assert_ne!(
+            key,
+            CacheKey::lint("content", "wix", "wxs", "config2", "rules")
+        );
+        assert_ne!(
+            key,
+            CacheKey::lint("content", "wix", "wxs", "config", "rules2")
+        );
+        assert_ne!(key.as_str(), CacheKey::symbols("content", "rules").as_str());
     }
 
     #[test]
     fn test_cache_put_get() {
         let temp = TempDir::new().unwrap();
+        let cache = LintCache::open(&temp.path().join("cache")).unwrap();
         let test_file = temp.path().join("test.wxs");
         fs::write(&test_file, "<Wix/>").unwrap();
 
-        let mut cache = LintCache::new();
+        let key = CacheKey::lint(&hash_file(&test_file).unwrap(), "p", "wxs", "c", "r");
+        assert!(cache.get_lint(&key, &test_file).is_none());
+
         let diags = vec![make_diagnostic("rule1", test_file.clone(), 1)];
+        cache.put_lint(&key, &test_file, &diags, &[]).unwrap();
 
-        cache.put(&test_file, &diags, HashMap::new());
+        let cached = cache.get_lint(&key, &test_file).unwrap();
+        assert_eq!(cached.len(), 1);
 
-        let entry = cache.get(&test_file);
-        assert!(entry.is_some());
-        assert_eq!(entry.unwrap().diagnostics.len(), 1);
+        let stats = cache.stats();
+        assert_eq!(stats.total_entries, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
     }
 
     #[test]
-    fn test_cache_invalidation_on_change() {
+    fn test_full_diagnostic_round_trips() {
         let temp = TempDir::new().unwrap();
-        let test_file = temp.path().join("test.wxs");
-        fs::write(&test_file, "<Wix/>").unwrap();
-
-        let mut cache = LintCache::new();
-        cache.put(&test_file, &[], HashMap::new());
-
-        // Modify file
-        fs::write(&test_file, "<Wix><Component/></Wix>").unwrap();
-
-        // Should not find cached entry
-        let entry = cache.get(&test_file);
-        assert!(entry.is_none());
+        let cache = LintCache::open(temp.path()).unwrap();
+        let file = PathBuf::from("a/product.wxs");
+
+        let diag = make_diagnostic("rule1", file.clone(), 3)
+            .with_source_line("<Component Id=\"C\">")
+            .with_unsafe_fix("Add Guid", "<Component Id=\"C\" Guid=\"*\">")
+            .with_note("see docs")
+            .with_related(Location::new(file.clone(), 1, 1), "first here");
+        let key = CacheKey::lint("h", "p", "wxs", "c", "r");
+        cache.put_lint(&key, &file, &[diag], &[]).unwrap();
+
+        let cached = cache.get_lint(&key, &file).unwrap().remove(0);
+        let fix = cached.fix.unwrap();
+        assert!(fix.is_unsafe());
+        assert_eq!(fix.replacement, "<Component Id=\"C\" Guid=\"*\">");
+        assert_eq!(cached.notes, vec!["see docs".to_string()]);
+        assert_eq!(cached.related[0].message, "first here");
     }
 
     #[test]
-    fn test_cache_save_load() {
+    fn test_identical_content_relocates_paths() {
         let temp = TempDir::new().unwrap();
-        let cache_file = temp.path().join("cache.json");
-        let test_file = temp.path().join("test.wxs");
-        fs::write(&test_file, "<Wix/>").unwrap();
-
-        let mut cache = LintCache::new();
-        cache.put(&test_file, &[], HashMap::new());
-        cache.save(&cache_file).unwrap();
-
-        let loaded = LintCache::load(&cache_file).unwrap();
-        assert_eq!(loaded.entries.len(), 1);
+        let cache = LintCache::open(temp.path()).unwrap();
+        let original = PathBuf::from("checkout1/src/product.wxs");
+        let other = PathBuf::from("checkout2/src/product.wxs");
+
+        let key = CacheKey::lint("same-content", "p", "wxs", "c", "r");
+        let diag = make_diagnostic("rule1", original.clone(), 7)
+            .with_related(Location::new(original.clone(), 2, 1), "here");
+        cache.put_lint(&key, &original, &[diag], &[]).unwrap();
+
+        let cached = cache.get_lint(&key, &other).unwrap();
+        assert_eq!(cached[0].location.file, other);
+        assert_eq!(cached[0].related[0].location.file, other);
     }
 
     #[test]
-    fn test_cache_prune() {
+    fn test_changed_input_invalidates_entry() {
         let temp = TempDir::new().unwrap();
-        let test_file = temp.path().join("test.wxs");
-        fs::write(&test_file, "<Wix/>").unwrap();
-
-        let mut cache = LintCache::new();
-        cache.put(&test_file, &[], HashMap::new());
-
-        // Delete the file
-        fs::remove_file(&test_file).unwrap();
+        let cache = LintCache::open(&temp.path().join("cache")).unwrap();
+        let wxs = temp.path().join("product.wxs");
+        let script = temp.path().join("setup.vbs");
+        fs::write(&wxs, "<Wix/>").unwrap();
+        fs::write(&script, "MsgBox 1").unwrap();
+
+        let key = CacheKey::lint("h", "p", "wxs", "c", "r");
+        cache
+            .put_lint(&key, &wxs, &[], &[PathBuf::from("setup.vbs")])
+            .unwrap();
+        assert!(cache.get_lint(&key, &wxs).is_some());
+
+        fs::write(&script, "MsgBox 2").unwrap();
+        assert!(cache.get_lint(&key, &wxs).is_none());
+    }
 
-        cache.prune();
-        assert!(cache.entries.is_empty());
+    #[test]
+    fn test_cross_file_key_tracks_dependencies() {
+        let file = Path::new("product.wxs");
+        let deps = vec![(PathBuf::from("components.wxs"), "h1".to_string())];
+        let changed = vec![(PathBuf::from("components.wxs"), "h2".to_string())];
+
+        let key = CacheKey::cross_file(file, "h", "c", &deps);
+        assert_eq!(key, CacheKey::cross_file(file, "h", "c", &deps));
+        assert_ne!(key, CacheKey::cross_file(file, "h", "c", &changed));
+        assert_ne!(key, CacheKey::cross_file(file, "h", "c", &[]));
+        assert_ne!(key, CacheKey::cross_file(file, "h", "c2", &deps));
     }
 
     #[test]
-    fn test_cache_config_invalidation() {
-        let mut cache = LintCache::new();
-        // Set initial config hash
-        cache.set_config_hash("hash1");
-
-        cache.entries.insert(
-            "test".to_string(),
-            CacheEntry {
-                content_hash: "abc".to_string(),
-                mtime: 0,
-                size: 0,
-                diagnostics: vec![],
-                rule_versions: HashMap::new(),
-            },
+    fn test_cross_file_key_is_workspace_relative() {
+        let cwd = std::env::current_dir().unwrap();
+        let deps = vec![(cwd.join("components.wxs"), "h1".to_string())];
+        let relative = vec![(PathBuf::from("./components.wxs"), "h1".to_string())];
+
+        assert_eq!(
+            CacheKey::cross_file(&cwd.join("product.wxs"), "h", "c", &deps),
+            CacheKey::cross_file(Path::new("product.wxs"), "h", "c", &relative)
         );
-
-        // Same config hash - entries preserved
-        cache.set_config_hash("hash1");
-        assert!(!cache.entries.is_empty());
-
-        // Different config hash - entries cleared
-        cache.set_config_hash("hash2");
-        assert!(cache.entries.is_empty());
     }
 
     #[test]
-    fn test_cached_diagnostic_conversion() {
-        let file = PathBuf::from("test.wxs");
-        let diag = make_diagnostic("test-rule", file.clone(), 42);
-
-        let cached = CachedDiagnostic::from_diagnostic(&diag);
-        assert_eq!(cached.rule_id, "test-rule");
-        assert_eq!(cached.line, 42);
-
-        let restored = cached.to_diagnostic(file);
-        assert_eq!(restored.rule_id, "test-rule");
-        assert_eq!(restored.location.line, 42);
+    fn test_cache_prune_and_clear() {
+        let temp = TempDir::new().unwrap();
+        let cache = LintCache::open(temp.path()).unwrap();
+        cache
+            .put(&CacheKey::symbols("a", "r"), &Vec::<u8>::new())
+            .unwrap();
+        cache
+            .put(&CacheKey::symbols("b", "r"), &Vec::<u8>::new())
+            .unwrap();
+
+        assert_eq!(cache.prune(Duration::from_secs(3600)).unwrap(), 0);
+        assert_eq!(cache.stats().total_entries, 2);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.stats().total_entries, 0);
     }
 
     #[test]
-    fn test_stats() {
-        let mut cache = LintCache::new();
-        cache.entries.insert(
-            "file1".to_string(),
-            CacheEntry {
-                content_hash: "a".to_string(),
-                mtime: 0,
-                size: 0,
-                diagnostics: vec![CachedDiagnostic {
-                    rule_id: "r1".to_string(),
-                    message: "m".to_string(),
-                    severity: "warning".to_string(),
-                    line: 1,
-                    column: 0,
-                    source_line: None,
-                }],
-                rule_versions: HashMap::new(),
-            },
-        );
-        cache.entries.insert(
-            "file2".to_string(),
-            CacheEntry {
-                content_hash: "b".to_string(),
-                mtime: 0,
-                size: 0,
-                diagnostics: vec![
-                    CachedDiagnostic {
-                        rule_id: "r1".to_string(),
-                        message: "m".to_string(),
-                        severity: "warning".to_string(),
-                        line: 1,
-                        column: 0,
-                        source_line: None,
-                    },
-                    CachedDiagnostic {
-                        rule_id: "r2".to_string(),
-                        message: "m".to_string(),
-                        severity: "error".to_string(),
-                        line: 2,
-                        column: 0,
-                        source_line: None,
-                    },
-                ],
-                rule_versions: HashMap::new(),
-            },
-        );
+    fn test_cache_hit_defers_prune() {
+        let temp = TempDir::new().unwrap();
+        let cache = LintCache::open(temp.path()).unwrap();
+        let used = CacheKey::symbols("used", "r");
+        let unused = CacheKey::symbols("unused", "r");
+        cache.put(&used, &Vec::<u8>::new()).unwrap();
+        cache.put(&unused, &Vec::<u8>::new()).unwrap();
+
+        // Both were written two hours ago; only one is still being hit
+        let written = SystemTime::now() - Duration::from_secs(7200);
+        for key in [&used, &unused] {
+            fs::File::options()
+                .append(true)
+                .open(cache.entry_path(key))
+                .unwrap()
+                .set_modified(written)
+                .unwrap();
+        }
+        assert!(cache.get::<Vec<u8>>(&used).is_some());
 
-        let stats = cache.stats();
-        assert_eq!(stats.total_entries, 2);
-        assert_eq!(stats.total_diagnostics, 3);
+        assert_eq!(cache.prune(Duration::from_secs(3600)).unwrap(), 1);
+        assert!(cache.get::<Vec<u8>>(&used).is_some());
+        assert!(cache.get::<Vec<u8>>(&unused).is_none());
     }
 
     #[test]
@@ -465,4 +642,24 @@ mod tests {
         assert_eq!(hash1, hash2);
         assert_ne!(hash1, hash3);
     }
+
+    #[test]
+    fn test_effective_config_ignores_unrelated_settings() {
+        let mut config = Config::new();
+        let file = Path::new("src/product.wxs");
+        let base = hash_effective_config(&config, file);
+
+        config.output.statistics = !config.output.statistics;
+        config
+            .rules
+            .per_file
+            .insert("tests/**".to_string(), vec!["all".to_string()]);
+        assert_eq!(hash_effective_config(&config, file), base);
+
+        config
+            .rules
+            .per_file
+            .insert("src/*.wxs".to_string(), vec!["rule1".to_string()]);
+        assert_ne!(hash_effective_config(&config, file), base);
+    }
 }
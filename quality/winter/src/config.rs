@@ -97,6 +97,8 @@ pub enum OutputFormat {
     Junit,
     Gitlab,
     Azure,
+    Rdjson,
+    Rdjsonl,
 }
 
 impl std::str::FromStr for OutputFormat {
@@ -113,6 +115,8 @@ impl std::str::FromStr for OutputFormat {
             "junit" => Ok(OutputFormat::Junit),
             "gitlab" => Ok(OutputFormat::Gitlab),
             "azure" => Ok(OutputFormat::Azure),
+            "rdjson" => Ok(OutputFormat::Rdjson),
+            "rdjsonl" => Ok(OutputFormat::Rdjsonl),
             _ => Err(format!("Unknown output format: {}", s)),
         }
     }
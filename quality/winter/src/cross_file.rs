@@ -6,11 +6,12 @@
 
 use crate::diagnostic::{Diagnostic, Location, Severity};
 use crate::plugin::Document;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 /// Types of symbols that can be defined/referenced
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SymbolKind {
     Component,
     ComponentGroup,
@@ -83,7 +84,7 @@ impl SymbolKind {
 }
 
 /// A symbol definition
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolDefinition {
     /// The symbol ID
     pub id: String,
@@ -102,7 +103,7 @@ pub struct SymbolDefinition {
 }
 
 /// A symbol reference
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolReference {
     /// The symbol ID being referenced
     pub id: String,
@@ -123,6 +124,34 @@ pub struct SymbolReference {
     pub element: String,
 }
 
+/// Definitions and references collected from a single file
+///
+/// This is what the cache stores so unchanged files need not be re-parsed
+/// to rebuild the project-wide index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileSymbols {
+    /// Symbols defined in the file
+    pub definitions: Vec<SymbolDefinition>,
+
+    /// Symbols referenced from the file
+    pub references: Vec<SymbolReference>,
+}
+
+impl FileSymbols {
+    /// Collect the symbols of a single document
+    pub fn collect(document: &dyn Document, file: &Path) -> Self {
+        let mut validator = CrossFileValidator::new();
+        validator.collect_definitions(document, file);
+        validator.collect_references(document, file);
+
+        let index = validator.index;
+        Self {
+            definitions: index.definitions.into_values().flatten().collect(),
+            references: index.references,
+        }
+    }
+}
+
 /// Index of all symbols across files
 #[derive(Debug, Default)]
 pub struct SymbolIndex {
@@ -216,6 +245,31 @@ impl SymbolIndex {
             .filter(|(_, defs)| defs.len() > 1)
             .collect()
     }
+
+    /// Files whose symbols can change the cross-file results reported in `file`
+    ///
+    /// These are the files defining anything `file` references, plus the files
+    /// defining the same symbols as `file` (duplicates). Sorted for stable keys.
+    pub fn dependencies(&self, file: &Path) -> Vec<PathBuf> {
+        let mut deps = HashSet::new();
+
+        for reference in self.references.iter().filter(|r| r.file == file) {
+            if let Some(defs) = self.get_definitions(reference.kind, &reference.id) {
+                deps.extend(defs.iter().map(|d| d.file.clone()));
+            }
+        }
+
+        for defs in self.definitions.values() {
+            if defs.iter().any(|d| d.file == file) {
+                deps.extend(defs.iter().map(|d| d.file.clone()));
+            }
+        }
+
+        deps.remove(file);
+        let mut deps: Vec<PathBuf> = deps.into_iter().collect();
+        deps.sort();
+        deps
+    }
 }
 
 /// Cross-file validator
@@ -400,6 +454,18 @@ impl CrossFileValidator {
         }
     }
 
+    /// Add symbols collected earlier (e.g., from the cache) for a file
+    pub fn add_symbols(&mut self, file: &Path, symbols: FileSymbols) {
+        for mut def in symbols.definitions {
+            def.file = file.to_path_buf();
+            self.index.add_definition(def);
+        }
+        for mut reference in symbols.references {
+            reference.file = file.to_path_buf();
+            self.index.add_reference(reference);
+        }
+    }
+
     /// Validate all references against definitions
     pub fn validate(&self) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
@@ -446,12 +512,29 @@ impl CrossFileValidator {
                         first.line
                     );
 
-                    diagnostics.push(Diagnostic::new(
+                    let mut diag = Diagnostic::new(
                         &rule_id,
                         Severity::Error,
                         &message,
                         Location::new(def.file.clone(), def.line, def.column),
-                    ));
+                    )
+                    .with_related(
+                        Location::new(first.file.clone(), first.line, first.column),
+                        &format!("'{}' first defined here", id),
+                    );
+
+                    // Point at any further duplicates as well
+                    for other in defs.iter().skip(1) {
+                        if std::ptr::eq(other, def) {
+                            continue;
+                        }
+                        diag = diag.with_related(
+                            Location::new(other.file.clone(), other.line, other.column),
+                            &format!("'{}' also defined here", id),
+                        );
+                    }
+
+                    diagnostics.push(diag);
                 }
             }
         }
@@ -537,6 +620,36 @@ mod tests {
         assert_eq!(duplicates.len(), 1);
     }
 
+    #[test]
+    fn test_symbol_index_dependencies() {
+        let def = |id: &str, file: &str| SymbolDefinition {
+            id: id.to_string(),
+            kind: SymbolKind::Component,
+            file: PathBuf::from(file),
+            line: 1,
+            column: 1,
+        };
+        let mut index = SymbolIndex::new();
+        index.add_definition(def("Shared", "components.wxs"));
+        index.add_definition(def("Dup", "product.wxs"));
+        index.add_definition(def("Dup", "legacy.wxs"));
+        index.add_definition(def("Unrelated", "other.wxs"));
+        index.add_reference(SymbolReference {
+            id: "Shared".to_string(),
+            kind: SymbolKind::Component,
+            file: PathBuf::from("product.wxs"),
+            line: 5,
+            column: 1,
+            element: "ComponentRef".to_string(),
+        });
+
+        assert_eq!(
+            index.dependencies(Path::new("product.wxs")),
+            vec![PathBuf::from("components.wxs"), PathBuf::from("legacy.wxs")]
+        );
+        assert!(index.dependencies(Path::new("other.wxs")).is_empty());
+    }
+
     #[test]
     fn test_validator_undefined_reference() {
         let mut validator = CrossFileValidator::new();
@@ -624,5 +737,13 @@ mod tests {
         let diagnostics = validator.validate();
         assert_eq!(diagnostics.len(), 1);
         assert!(diagnostics[0].rule_id.contains("duplicate"));
+
+        // The first definition is attached as a related location
+        assert_eq!(diagnostics[0].related.len(), 1);
+        assert_eq!(
+            diagnostics[0].related[0].location.file,
+            PathBuf::from("file1.wxs")
+        );
+        assert_eq!(diagnostics[0].related[0].location.line, 10);
     }
 }
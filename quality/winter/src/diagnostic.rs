@@ -93,6 +93,15 @@ impl Location {
     }
 }
 
+/// A secondary location related to a diagnostic (e.g., the first definition of a duplicate)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedLocation {
+    /// Related source location
+    pub location: Location,
+    /// Why this location is relevant
+    pub message: String,
+}
+
 /// A suggested fix for the diagnostic
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fix {
@@ -168,6 +177,9 @@ pub struct Diagnostic {
     pub fix: Option<Fix>,
     /// Additional notes
     pub notes: Vec<String>,
+    /// Related locations in this or other files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedLocation>,
 }
 
 impl Diagnostic {
@@ -184,6 +196,7 @@ impl Diagnostic {
             help: None,
             fix: None,
             notes: Vec::new(),
+            related: Vec::new(),
         }
     }
 
@@ -266,6 +279,15 @@ impl Diagnostic {
         self
     }
 
+    /// Add a related location
+    pub fn with_related(mut self, location: Location, message: &str) -> Self {
+        self.related.push(RelatedLocation {
+            location,
+            message: message.to_string(),
+        });
+        self
+    }
+
     /// Check if this is an error
     pub fn is_error(&self) -> bool {
         self.severity == Severity::Error
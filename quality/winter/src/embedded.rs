@@ -0,0 +1,302 @@
+//! Embedded code extraction for context-specific linting
+//!
+//! Host documents often carry code in another language: VBScript in a WiX
+//! `CustomAction`, a PowerShell command line passed to a quiet-exec action,
+//! a shell step inside a pipeline definition. Plugins extract these blocks
+//! as [`EmbeddedBlock`]s, and the engine evaluates rules whose `context`
+//! matches the block language against them.
+//!
+//! Each block is exposed to rule conditions as a tree of [`EmbeddedNode`]s:
+//! one `script` node holding the whole body and one `line` node per source
+//! line, so rules can target either with `target.kind`. The text is
+//! available to conditions as `content`.
+
+use crate::diagnostic::Location;
+use crate::plugin::Node;
+use crate::plugin_manager::EmbeddedLanguage;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A block of embedded code extracted from a host document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedBlock {
+    /// Language context (e.g., "vbscript", "powershell", "cmdline")
+    pub language: String,
+
+    /// The code itself
+    pub content: String,
+
+    /// Host line where the code starts (1-based)
+    pub line: usize,
+
+    /// Host column where the code starts (1-based)
+    pub column: usize,
+
+    /// Short description of where the code came from (e.g., "CustomAction 'Foo'")
+    pub origin: String,
+
+    /// External file the code was read from, if it is not inline.
+    /// Diagnostics for external code are anchored at `line` in the host.
+    pub external: Option<PathBuf>,
+}
+
+impl EmbeddedBlock {
+    /// Create an inline block starting at the given host position
+    pub fn inline(language: &str, content: &str, line: usize, column: usize) -> Self {
+        Self {
+            language: language.to_string(),
+            content: content.to_string(),
+            line,
+            column,
+            origin: String::new(),
+            external: None,
+        }
+    }
+
+    /// Create a block read from an external file referenced at the given host line
+    pub fn external(language: &str, content: &str, file: PathBuf, line: usize) -> Self {
+        Self {
+            language: language.to_string(),
+            content: content.to_string(),
+            line,
+            column: 1,
+            origin: String::new(),
+            external: Some(file),
+        }
+    }
+
+    /// Set the origin description
+    pub fn with_origin(mut self, origin: &str) -> Self {
+        self.origin = origin.to_string();
+        self
+    }
+
+    /// Map a 1-based line inside the block to a host (line, column)
+    pub fn host_position(&self, block_line: usize) -> (usize, usize) {
+        if self.external.is_some() {
+            return (self.line, self.column);
+        }
+        let line = self.line + block_line.saturating_sub(1);
+        let column = if block_line <= 1 { self.column } else { 1 };
+        (line, column)
+    }
+
+    /// Build the node tree used for rule evaluation
+    pub fn nodes(&self, host: &Path) -> Vec<EmbeddedNode> {
+        let mut nodes = vec![EmbeddedNode {
+            kind: "script",
+            language: self.language.clone(),
+            text: self.content.clone(),
+            attrs: self.attributes(),
+            location: Location::new(host.to_path_buf(), self.line, self.column),
+            block_line: 1,
+        }];
+
+        for (i, line) in self.content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (host_line, host_column) = self.host_position(i + 1);
+            let indent = line.len() - line.trim_start().len();
+            let column = if self.external.is_some() {
+                host_column
+            } else {
+                host_column + indent
+            };
+            nodes.push(EmbeddedNode {
+                kind: "line",
+                language: self.language.clone(),
+                text: line.to_string(),
+                attrs: self.attributes(),
+                location: Location::new(host.to_path_buf(), host_line, column)
+                    .with_length(line.trim().len()),
+                block_line: i + 1,
+            });
+        }
+
+        nodes
+    }
+
+    fn attributes(&self) -> HashMap<String, String> {
+        let mut attrs = HashMap::new();
+        attrs.insert("language".to_string(), self.language.clone());
+        attrs.insert("origin".to_string(), self.origin.clone());
+        if let Some(file) = &self.external {
+            attrs.insert("file".to_string(), file.display().to_string());
+        }
+        attrs
+    }
+}
+
+/// A node view over an embedded block, used for rule evaluation
+#[derive(Debug, Clone)]
+pub struct EmbeddedNode {
+    /// "script" for the whole block, "line" for a single line
+    kind: &'static str,
+
+    /// Block language (exposed as the node name)
+    language: String,
+
+    /// Node text
+    text: String,
+
+    /// Block metadata (language, origin, file)
+    attrs: HashMap<String, String>,
+
+    /// Host location
+    location: Location,
+
+    /// Line number inside the block (1-based)
+    pub block_line: usize,
+}
+
+impl Node for EmbeddedNode {
+    fn kind(&self) -> &str {
+        self.kind
+    }
+
+    fn name(&self) -> &str {
+        &self.language
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|s| s.as_str())
+    }
+
+    fn attributes(&self) -> &HashMap<String, String> {
+        &self.attrs
+    }
+
+    fn children(&self) -> Vec<&dyn Node> {
+        Vec::new()
+    }
+
+    fn parent(&self) -> Option<&dyn Node> {
+        None
+    }
+
+    fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn text(&self) -> Option<&str> {
+        Some(&self.text)
+    }
+}
+
+/// Compute 1-based (line, column) for byte offsets in a source string
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Build a line index for content
+    pub fn new(content: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// Convert a byte offset to a 1-based (line, column)
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let start = self.line_starts[line.saturating_sub(1)];
+        (line, offset - start + 1)
+    }
+}
+
+/// Extract embedded blocks using manifest-defined regex patterns
+///
+/// Each pattern must have a capture group for the code content; the whole
+/// match is used when it has none. Invalid patterns are skipped.
+pub fn extract_with_patterns(content: &str, languages: &[EmbeddedLanguage]) -> Vec<EmbeddedBlock> {
+    let index = LineIndex::new(content);
+    let mut blocks = Vec::new();
+
+    for lang in languages {
+        for pattern in &lang.patterns {
+            let re = match Regex::new(pattern) {
+                Ok(re) => re,
+                Err(_) => continue,
+            };
+
+            for caps in re.captures_iter(content) {
+                let m = match caps.get(1).or_else(|| caps.get(0)) {
+                    Some(m) => m,
+                    None => continue,
+                };
+                if m.as_str().trim().is_empty() {
+                    continue;
+                }
+                let (line, column) = index.position(m.start());
+                blocks.push(
+                    EmbeddedBlock::inline(&lang.language, m.as_str(), line, column)
+                        .with_origin(&lang.extractor),
+                );
+            }
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index_position() {
+        let index = LineIndex::new("ab\ncd\n\nef");
+        assert_eq!(index.position(0), (1, 1));
+        assert_eq!(index.position(1), (1, 2));
+        assert_eq!(index.position(3), (2, 1));
+        assert_eq!(index.position(7), (4, 1));
+    }
+
+    #[test]
+    fn test_inline_host_position() {
+        let block = EmbeddedBlock::inline("vbscript", "a\nb\nc", 10, 5);
+        assert_eq!(block.host_position(1), (10, 5));
+        assert_eq!(block.host_position(3), (12, 1));
+    }
+
+    #[test]
+    fn test_external_host_position() {
+        let block = EmbeddedBlock::external("vbscript", "a\nb", PathBuf::from("x.vbs"), 7);
+        assert_eq!(block.host_position(2), (7, 1));
+    }
+
+    #[test]
+    fn test_nodes_skip_blank_lines() {
+        let block = EmbeddedBlock::inline("powershell", "\n  Write-Host hi\n\n", 3, 20);
+        let nodes = block.nodes(Path::new("test.wxs"));
+
+        assert_eq!(nodes[0].kind(), "script");
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[1].kind(), "line");
+        assert_eq!(nodes[1].name(), "powershell");
+        assert_eq!(nodes[1].text(), Some("  Write-Host hi"));
+        assert_eq!(nodes[1].location().line, 4);
+        assert_eq!(nodes[1].location().column, 3);
+    }
+
+    #[test]
+    fn test_extract_with_patterns() {
+        let lang = EmbeddedLanguage {
+            extractor: "sh_block".to_string(),
+            language: "shell".to_string(),
+            patterns: vec![r#"<sh>([^<]*)</sh>"#.to_string()],
+            source_path: None,
+        };
+        let content = "<job>\n  <sh>rm -rf /tmp/x</sh>\n</job>";
+        let blocks = extract_with_patterns(content, &[lang]);
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, "shell");
+        assert_eq!(blocks[0].content, "rm -rf /tmp/x");
+        assert_eq!((blocks[0].line, blocks[0].column), (2, 7));
+        assert_eq!(blocks[0].origin, "sh_block");
+    }
+}
@@ -1,15 +1,17 @@
 //! Core linter engine
 
+use crate::cache::{hash_content, hash_effective_config, workspace_relative, CacheKey, LintCache};
 use crate::config::Config;
-use crate::cross_file::CrossFileValidator;
+use crate::cross_file::{CrossFileValidator, FileSymbols};
 use crate::diagnostic::{Diagnostic, Location, Severity};
+use crate::embedded::EmbeddedBlock;
 use crate::plugin::{Document, Node, Plugin};
 use crate::rule::Rule;
 use rayon::prelude::*;
 use regex::Regex;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, Instant};
 
 /// Per-rule timing statistics
@@ -127,7 +129,7 @@ impl LintResult {
     /// Get rule timings sorted by total time (descending)
     pub fn sorted_timings(&self) -> Vec<&RuleTiming> {
         let mut timings: Vec<_> = self.rule_timings.values().collect();
-        timings.sort_by(|a, b| b.total_time.cmp(&a.total_time));
+        timings.sort_by_key(|t| std::cmp::Reverse(t.total_time));
         timings
     }
 
@@ -172,6 +174,12 @@ pub struct Engine {
 
     /// Number of context lines to include
     context_lines: usize,
+
+    /// Content-addressed result cache
+    cache: Option<LintCache>,
+
+    /// Hash of all registered plugins and rules (computed on first use)
+    ruleset_hash: OnceLock<String>,
 }
 
 impl Engine {
@@ -181,9 +189,21 @@ impl Engine {
             config,
             plugins: HashMap::new(),
             context_lines: 0,
+            cache: None,
+            ruleset_hash: OnceLock::new(),
         }
     }
 
+    /// Use a result cache for subsequent runs
+    pub fn set_cache(&mut self, cache: LintCache) {
+        self.cache = Some(cache);
+    }
+
+    /// The result cache, if one is set
+    pub fn cache(&self) -> Option<&LintCache> {
+        self.cache.as_ref()
+    }
+
     /// Set the number of context lines to include
     pub fn with_context_lines(mut self, lines: usize) -> Self {
         self.context_lines = lines;
@@ -197,11 +217,50 @@ impl Engine {
 
     /// Register a plugin
     pub fn register_plugin(&mut self, plugin: Arc<dyn Plugin>) {
+        self.ruleset_hash = OnceLock::new();
         for ext in plugin.extensions() {
             self.plugins.insert(ext.to_string(), Arc::clone(&plugin));
         }
     }
 
+    /// All rules from registered plugins
+    pub fn rules(&self) -> Vec<&Rule> {
+        let mut seen = std::collections::HashSet::new();
+        self.plugins
+            .values()
+            .filter(|p| seen.insert(p.id().to_string()))
+            .flat_map(|p| p.rules())
+            .collect()
+    }
+
+    /// Hash identifying the plugins and rules that produce results
+    fn ruleset_hash(&self) -> &str {
+        self.ruleset_hash.get_or_init(|| {
+            let mut plugins: Vec<_> = self.plugins.values().collect();
+            plugins.sort_by(|a, b| a.id().cmp(b.id()));
+            plugins.dedup_by(|a, b| a.id() == b.id());
+
+            let mut input = Vec::new();
+            for plugin in plugins {
+                input.extend_from_slice(plugin.id().as_bytes());
+                input.push(0);
+                input.extend_from_slice(plugin.version().as_bytes());
+                input.push(0);
+                input.extend(serde_json::to_vec(plugin.rules()).unwrap_or_default());
+            }
+            hash_content(&input)
+        })
+    }
+
+    /// Hash of everything besides file content that affects a file's results
+    fn config_hash(&self, path: &Path) -> String {
+        format!(
+            "{}:{}",
+            hash_effective_config(&self.config, path),
+            self.context_lines
+        )
+    }
+
     /// Get plugin for a file
     fn get_plugin(&self, path: &Path) -> Option<Arc<dyn Plugin>> {
         let ext = path.extension()?.to_str()?;
@@ -248,6 +307,26 @@ impl Engine {
         let mut result = self.lint(files);
 
         // Then do cross-file validation
+        let cross_file_diagnostics = match &self.cache {
+            Some(cache) => self.cross_file_cached(cache, files),
+            None => self.cross_file_uncached(files),
+        };
+
+        for diag in cross_file_diagnostics {
+            match diag.severity {
+                Severity::Error => result.error_count += 1,
+                Severity::Warning => result.warning_count += 1,
+                Severity::Info => result.info_count += 1,
+            }
+            result.diagnostics.push(diag);
+        }
+
+        result.duration = start.elapsed();
+        result
+    }
+
+    /// Collect symbols from every file and validate references
+    fn cross_file_uncached(&self, files: &[PathBuf]) -> Vec<Diagnostic> {
         let mut validator = CrossFileValidator::new();
 
         // Collect all definitions and references
@@ -271,39 +350,173 @@ impl Engine {
             validator.collect_references(document.as_ref(), file);
         }
 
-        // Validate and collect diagnostics
-        let cross_file_diagnostics = validator.validate();
+        validator.validate()
+    }
 
-        for diag in cross_file_diagnostics {
-            match diag.severity {
-                Severity::Error => result.error_count += 1,
-                Severity::Warning => result.warning_count += 1,
-                Severity::Info => result.info_count += 1,
+    /// Cross-file validation reusing cached symbols and per-file results
+    ///
+    /// Only files whose content changed are parsed. A file's cross-file
+    /// results are reused unless it, or a file it depends on through the
+    /// symbol index, changed.
+    fn cross_file_cached(&self, cache: &LintCache, files: &[PathBuf]) -> Vec<Diagnostic> {
+        let mut validator = CrossFileValidator::new();
+        let mut content_hashes: HashMap<&Path, String> = HashMap::new();
+
+        for file in files {
+            let plugin = match self.get_plugin(file) {
+                Some(p) => p,
+                None => continue,
+            };
+
+            let bytes = match std::fs::read(file) {
+                Ok(b) => b,
+                Err(_) => continue,
+            };
+            let content_hash = hash_content(&bytes);
+
+            let key = CacheKey::symbols(&content_hash, self.ruleset_hash());
+            let symbols = match cache.get::<FileSymbols>(&key) {
+                Some(symbols) => symbols,
+                None => {
+                    let content = match String::from_utf8(bytes) {
+                        Ok(c) => c,
+                        Err(_) => continue,
+                    };
+                    let document = match plugin.parse(&content, file) {
+                        Ok(d) => d,
+                        Err(_) => continue,
+                    };
+                    let symbols = FileSymbols::collect(document.as_ref(), file);
+                    let _ = cache.put(&key, &symbols);
+                    symbols
+                }
+            };
+
+            validator.add_symbols(file, symbols);
+            content_hashes.insert(file, content_hash);
+        }
+
+        // Entries hold workspace-relative paths; map them back on a hit
+        let originals: HashMap<PathBuf, &PathBuf> = files
+            .iter()
+            .map(|file| (workspace_relative(file), file))
+            .collect();
+
+        let mut diagnostics = Vec::new();
+        let mut misses = Vec::new();
+        for file in files {
+            let Some(content_hash) = content_hashes.get(file.as_path()) else {
+                continue;
+            };
+            let dependencies: Vec<(PathBuf, String)> = validator
+                .index()
+                .dependencies(file)
+                .into_iter()
+                .filter_map(|dep| {
+                    let hash = content_hashes.get(dep.as_path())?.clone();
+                    Some((dep, hash))
+                })
+                .collect();
+
+            let key =
+                CacheKey::cross_file(file, content_hash, &self.config_hash(file), &dependencies);
+            match cache.get::<Vec<Diagnostic>>(&key) {
+                Some(mut cached) => {
+                    map_locations(&mut cached, |path| {
+                        originals.get(path).map(|original| (*original).clone())
+                    });
+                    diagnostics.extend(cached);
+                }
+                None => misses.push((file, key)),
             }
-            result.diagnostics.push(diag);
         }
 
-        result.duration = start.elapsed();
-        result
+        if !misses.is_empty() {
+            let mut by_file: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+            for diag in validator.validate() {
+                by_file
+                    .entry(diag.location.file.clone())
+                    .or_default()
+                    .push(diag);
+            }
+
+            for (file, key) in misses {
+                let file_diagnostics = by_file.remove(file.as_path()).unwrap_or_default();
+                let mut stored = file_diagnostics.clone();
+                map_locations(&mut stored, |path| Some(workspace_relative(path)));
+                let _ = cache.put(&key, &stored);
+                diagnostics.extend(file_diagnostics);
+            }
+        }
+
+        diagnostics
     }
 
     /// Lint a single file
     pub fn lint_file(&self, path: &Path) -> LintResult {
-        let mut result = LintResult {
-            files_processed: 1,
-            ..LintResult::default()
-        };
+        match &self.cache {
+            Some(cache) => self.lint_file_cached(cache, path),
+            None => self.lint_file_uncached(path),
+        }
+    }
 
-        // Get plugin for this file type
-        let plugin = match self.get_plugin(path) {
-            Some(p) => p,
-            None => return result,
+    /// Lint a single file, reusing a cached result for identical inputs
+    fn lint_file_cached(&self, cache: &LintCache, path: &Path) -> LintResult {
+        let (plugin, bytes) = match (self.get_plugin(path), std::fs::read(path)) {
+            (Some(plugin), Ok(bytes)) => (plugin, bytes),
+            _ => return self.lint_file_uncached(path),
         };
 
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let key = CacheKey::lint(
+            &hash_content(&bytes),
+            plugin.id(),
+            extension,
+            &self.config_hash(path),
+            self.ruleset_hash(),
+        );
+        if let Some(diagnostics) = cache.get_lint(&key, path) {
+            return Self::file_result(diagnostics, HashMap::new());
+        }
+
+        let result = self.lint_file_uncached(path);
+
+        // External scripts are inputs too; record them relative to the file
+        let base = path.parent().unwrap_or(Path::new(""));
+        let content = String::from_utf8_lossy(&bytes);
+        let inputs: Vec<PathBuf> = plugin
+            .extract_embedded(&content, path)
+            .into_iter()
+            .filter_map(|block| block.external)
+            .map(|external| {
+                external
+                    .strip_prefix(base)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(external)
+            })
+            .collect();
+        let _ = cache.put_lint(&key, path, &result.diagnostics, &inputs);
+
+        result
+    }
+
+    /// Lint a single file without consulting the cache
+    fn lint_file_uncached(&self, path: &Path) -> LintResult {
+        if self.get_plugin(path).is_none() {
+            return LintResult {
+                files_processed: 1,
+                ..LintResult::default()
+            };
+        }
+
         // Read file content
-        let content = match std::fs::read_to_string(path) {
-            Ok(c) => c,
+        match std::fs::read_to_string(path) {
+            Ok(content) => self.lint_source(path, &content),
             Err(e) => {
+                let mut result = LintResult {
+                    files_processed: 1,
+                    ..LintResult::default()
+                };
                 result.diagnostics.push(Diagnostic::new(
                     "file-read-error",
                     Severity::Error,
@@ -312,12 +525,28 @@ impl Engine {
                 ));
                 result.error_count = 1;
                 result.files_with_errors = 1;
-                return result;
+                result
             }
+        }
+    }
+
+    /// Lint in-memory content as if it were the file at `path`
+    ///
+    /// Used by editors, where the buffer may not match what is on disk.
+    pub fn lint_source(&self, path: &Path, content: &str) -> LintResult {
+        let mut result = LintResult {
+            files_processed: 1,
+            ..LintResult::default()
+        };
+
+        // Get plugin for this file type
+        let plugin = match self.get_plugin(path) {
+            Some(p) => p,
+            None => return result,
         };
 
         // Parse the document
-        let document = match plugin.parse(&content, path) {
+        let document = match plugin.parse(content, path) {
             Ok(d) => d,
             Err(e) => {
                 result.diagnostics.push(Diagnostic::new(
@@ -333,10 +562,37 @@ impl Engine {
         };
 
         // Run rules
-        let (diagnostics, timings) =
-            self.evaluate_rules(plugin.rules(), document.as_ref(), path, &content);
+        let (mut diagnostics, mut timings) =
+            self.evaluate_rules(plugin.rules(), document.as_ref(), path, content);
+
+        // Run context-specific rules over embedded scripts
+        for block in plugin.extract_embedded(content, path) {
+            let (embedded, embedded_timings) =
+                self.evaluate_embedded(plugin.rules(), &block, document.as_ref(), path, content);
+            diagnostics.extend(embedded);
+            for (rule_id, timing) in embedded_timings {
+                let entry = timings
+                    .entry(rule_id)
+                    .or_insert_with(|| RuleTiming::new(&timing.rule_id));
+                entry.total_time += timing.total_time;
+                entry.evaluation_count += timing.evaluation_count;
+                entry.match_count += timing.match_count;
+            }
+        }
+
+        Self::file_result(diagnostics, timings)
+    }
+
+    /// Build a single-file result, counting diagnostics by severity
+    fn file_result(
+        diagnostics: Vec<Diagnostic>,
+        timings: HashMap<String, RuleTiming>,
+    ) -> LintResult {
+        let mut result = LintResult {
+            files_processed: 1,
+            ..LintResult::default()
+        };
 
-        // Count by severity
         for diag in &diagnostics {
             match diag.severity {
                 Severity::Error => result.error_count += 1,
@@ -376,6 +632,11 @@ impl Engine {
                     continue;
                 }
 
+                // Embedded-language rules only run on extracted blocks
+                if !rule.applies_to_context("main") {
+                    continue;
+                }
+
                 // Check per-file ignore
                 if self.config.should_ignore_rule_for_file(&rule.id, file_path) {
                     continue;
@@ -457,6 +718,91 @@ impl Engine {
         (diagnostics, timings)
     }
 
+    /// Evaluate context-specific rules against an embedded code block
+    ///
+    /// Diagnostics are reported at the host document position of the
+    /// offending script line, so inline disable comments apply as usual.
+    fn evaluate_embedded(
+        &self,
+        rules: &[Rule],
+        block: &EmbeddedBlock,
+        document: &dyn Document,
+        file_path: &Path,
+        content: &str,
+    ) -> (Vec<Diagnostic>, HashMap<String, RuleTiming>) {
+        let mut diagnostics = Vec::new();
+        let mut timings: HashMap<String, RuleTiming> = HashMap::new();
+        let source_lines: Vec<&str> = content.lines().collect();
+
+        let rules: Vec<&Rule> = rules
+            .iter()
+            .filter(|r| !r.context.is_empty() && r.applies_to_context(&block.language))
+            .filter(|r| r.enabled && self.config.is_rule_enabled(&r.id))
+            .filter(|r| !self.config.should_ignore_rule_for_file(&r.id, file_path))
+            .filter(|r| !document.is_rule_disabled_for_file(&r.id))
+            .collect();
+
+        if rules.is_empty() {
+            return (diagnostics, timings);
+        }
+
+        for node in block.nodes(file_path) {
+            for rule in &rules {
+                let location = node.location();
+                if document.is_rule_disabled(&rule.id, location.line) {
+                    continue;
+                }
+
+                if !self.matches_target(&node, &rule.target) {
+                    continue;
+                }
+
+                let start = Instant::now();
+                let matched = self.evaluate_condition(&rule.condition, &node);
+                let elapsed = start.elapsed();
+
+                let timing = timings
+                    .entry(rule.id.clone())
+                    .or_insert_with(|| RuleTiming::new(&rule.id));
+                timing.total_time += elapsed;
+                timing.evaluation_count += 1;
+
+                if !matched {
+                    continue;
+                }
+                timing.match_count += 1;
+
+                let severity = self
+                    .config
+                    .get_severity_override(&rule.id)
+                    .unwrap_or(rule.severity);
+                let message = self.format_message(&rule.message, &node);
+                let line_num = location.line;
+                let mut diag = Diagnostic::new(&rule.id, severity, &message, location);
+
+                if line_num > 0 && line_num <= source_lines.len() {
+                    diag = diag.with_source_line(source_lines[line_num - 1]);
+                    if self.context_lines > 0 {
+                        diag = diag.with_context(&source_lines, self.context_lines);
+                    }
+                }
+
+                if let Some(desc) = &rule.description {
+                    diag = diag.with_help(desc);
+                }
+
+                if let Some(file) = &block.external {
+                    diag =
+                        diag.with_note(&format!("in {} line {}", file.display(), node.block_line));
+                }
+
+                diagnostics.push(diag);
+            }
+        }
+
+        (diagnostics, timings)
+    }
+
     /// Check if a node matches a target specification
     fn matches_target(&self, node: &dyn Node, target: &crate::rule::Target) -> bool {
         // Check kind
@@ -608,6 +954,10 @@ impl Engine {
             return Some(node.kind().to_string());
         }
 
+        if path == "content" {
+            return node.text().map(String::from);
+        }
+
         None
     }
 
@@ -756,6 +1106,19 @@ impl Engine {
     }
 }
 
+/// Rewrite the file of every location, including related ones, that `map` returns a path for
+fn map_locations(diagnostics: &mut [Diagnostic], map: impl Fn(&Path) -> Option<PathBuf>) {
+    for diag in diagnostics {
+        let locations = std::iter::once(&mut diag.location)
+            .chain(diag.related.iter_mut().map(|r| &mut r.location));
+        for location in locations {
+            if let Some(file) = map(&location.file) {
+                location.file = file;
+            }
+        }
+    }
+}
+
 /// Find logical operator position (handles nested parentheses)
 fn find_logical_operator(s: &str, op: &str) -> Option<usize> {
     let mut depth = 0;
@@ -815,13 +1178,17 @@ mod tests {
 
     #[test]
     fn test_lint_result_merge() {
-        let mut result1 = LintResult::default();
-        result1.files_processed = 1;
-        result1.error_count = 2;
+        let mut result1 = LintResult {
+            files_processed: 1,
+            error_count: 2,
+            ..Default::default()
+        };
 
-        let mut result2 = LintResult::default();
-        result2.files_processed = 1;
-        result2.warning_count = 3;
+        let result2 = LintResult {
+            files_processed: 1,
+            warning_count: 3,
+            ..Default::default()
+        };
 
         result1.merge(result2);
         assert_eq!(result1.files_processed, 2);
@@ -838,6 +1205,200 @@ mod tests {
         assert!(!is_valid_guid("12345678-1234-1234-1234"));
     }
 
+    #[test]
+    fn test_lint_embedded_script_maps_to_host_line() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let file = temp.path().join("product.wxs");
+        std::fs::write(
+            &file,
+            r#"<Wix>
+  <CustomAction Id="RunIt" Script="vbscript">
+    <![CDATA[
+      On Error Resume Next
+      Set shell = CreateObject("WScript.Shell")
+      shell.Run "C:\Program Files\Contoso\tool.exe", 0, True
+    ]]>
+  </CustomAction>
+</Wix>"#,
+        )
+        .unwrap();
+
+        let mut engine = Engine::new(Config::default());
+        engine.register_plugin(Arc::new(
+            crate::plugins::wix::WixPlugin::with_builtin_rules(),
+        ));
+        let result = engine.lint(&[file]);
+
+        let run = result
+            .diagnostics
+            .iter()
+            .find(|d| d.rule_id == "script-shell-run-unquoted")
+            .expect("unquoted Run should be reported");
+        assert_eq!(run.location.line, 6);
+        assert!(run.source_line.as_deref().unwrap().contains("shell.Run"));
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| d.rule_id != "script-vbscript-missing-error-handling"));
+    }
+
+    #[test]
+    fn test_lint_embedded_missing_error_handling_per_language() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("setup.ps1"), "Remove-Item $env:TEMP\\x\n").unwrap();
+        std::fs::write(
+            temp.path().join("safe.ps1"),
+            "$ErrorActionPreference = 'Stop'\nRemove-Item $env:TEMP\\x\n",
+        )
+        .unwrap();
+        let file = temp.path().join("product.wxs");
+        std::fs::write(
+            &file,
+            r#"<Wix>
+  <CustomAction Id="Bare" Script="jscript">var x = 1;</CustomAction>
+  <CustomAction Id="Guarded" Script="jscript">try { var y = 1; } catch (e) {}</CustomAction>
+  <Binary Id="Ps" SourceFile="setup.ps1" />
+  <Binary Id="SafePs" SourceFile="safe.ps1" />
+</Wix>"#,
+        )
+        .unwrap();
+
+        let mut engine = Engine::new(Config::default());
+        engine.register_plugin(Arc::new(
+            crate::plugins::wix::WixPlugin::with_builtin_rules(),
+        ));
+        let result = engine.lint(&[file]);
+
+        let lines = |rule_id: &str| -> Vec<usize> {
+            result
+                .diagnostics
+                .iter()
+                .filter(|d| d.rule_id == rule_id)
+                .map(|d| d.location.line)
+                .collect()
+        };
+        assert_eq!(lines("script-jscript-missing-error-handling"), vec![2]);
+        assert_eq!(lines("script-powershell-missing-error-handling"), vec![4]);
+    }
+
+    #[test]
+    fn test_context_rules_skip_main_document() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let file = temp.path().join("product.wxs");
+        std::fs::write(
+            &file,
+            "<Wix>\n  <Property Id=\"P\" Value=\"C:\\Windows\\x\" />\n</Wix>",
+        )
+        .unwrap();
+
+        let mut engine = Engine::new(Config::default());
+        engine.register_plugin(Arc::new(
+            crate::plugins::wix::WixPlugin::with_builtin_rules(),
+        ));
+        let result = engine.lint(&[file]);
+
+        assert!(result
+            .diagnostics
+            .iter()
+            .all(|d| !d.rule_id.starts_with("script-")));
+    }
+
+    #[test]
+    fn test_lint_source_ignores_disk_content() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let file = temp.path().join("product.wxs");
+        std::fs::write(&file, "<Wix>\n  <Product Id=\"*\" />\n</Wix>").unwrap();
+
+        let mut engine = Engine::new(Config::default());
+        engine.register_plugin(Arc::new(
+            crate::plugins::wix::WixPlugin::with_builtin_rules(),
+        ));
+
+        let on_disk = engine.lint(std::slice::from_ref(&file));
+        let edited = engine.lint_source(&file, "<Wix>\n  <Fragment />\n</Wix>");
+        assert!(on_disk
+            .diagnostics
+            .iter()
+            .any(|d| d.rule_id == "deprecated-product-element"));
+        assert!(edited
+            .diagnostics
+            .iter()
+            .all(|d| d.rule_id != "deprecated-product-element"));
+        assert_eq!(edited.files_processed, 1);
+    }
+
+    fn cached_engine(cache_dir: &Path) -> Engine {
+        let mut engine = Engine::new(Config::default());
+        engine.register_plugin(Arc::new(
+            crate::plugins::wix::WixPlugin::with_builtin_rules(),
+        ));
+        engine.set_cache(LintCache::open(cache_dir).unwrap());
+        engine
+    }
+
+    #[test]
+    fn test_cached_lint_matches_uncached() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let file = temp.path().join("product.wxs");
+        std::fs::write(
+            &file,
+            "<Wix>\n  <Package Name=\"P\">\n    <Component Id=\"C\">\n    </Component>\n  </Package>\n</Wix>",
+        )
+        .unwrap();
+
+        let first = cached_engine(&temp.path().join("cache")).lint(std::slice::from_ref(&file));
+        // A fresh engine (like another CI job) shares the directory
+        let engine = cached_engine(&temp.path().join("cache"));
+        let second = engine.lint(&[file]);
+
+        assert_eq!(engine.cache().unwrap().stats().hits, 1);
+        assert_eq!(first.diagnostics.len(), second.diagnostics.len());
+        assert_eq!(first.error_count, second.error_count);
+        for (a, b) in first.diagnostics.iter().zip(&second.diagnostics) {
+            assert_eq!(a.rule_id, b.rule_id);
+            assert_eq!(a.fix.is_some(), b.fix.is_some());
+        }
+    }
+
+    #[test]
+    fn test_cross_file_cache_invalidated_by_dependency() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let product = temp.path().join("product.wxs");
+        let components = temp.path().join("components.wxs");
+        std::fs::write(
+            &product,
+            "<Wix>\n  <Feature Id=\"F\">\n    <ComponentRef Id=\"C1\" />\n  </Feature>\n</Wix>",
+        )
+        .unwrap();
+        std::fs::write(
+            &components,
+            "<Wix>\n  <Fragment>\n    <Component Id=\"C1\" />\n  </Fragment>\n</Wix>",
+        )
+        .unwrap();
+        let files = vec![product.clone(), components.clone()];
+        let undefined = |result: &LintResult| {
+            result
+                .diagnostics
+                .iter()
+                .any(|d| d.rule_id == "xref-undefined-component")
+        };
+
+        let cache_dir = temp.path().join("cache");
+        assert!(!undefined(
+            &cached_engine(&cache_dir).lint_with_cross_file(&files)
+        ));
+
+        // Only the defining file changes; product.wxs must still be re-checked
+        std::fs::write(
+            &components,
+            "<Wix>\n  <Fragment>\n    <Component Id=\"C2\" />\n  </Fragment>\n</Wix>",
+        )
+        .unwrap();
+        assert!(undefined(
+            &cached_engine(&cache_dir).lint_with_cross_file(&files)
+        ));
+    }
+
     #[test]
     fn test_find_logical_operator() {
         assert_eq!(find_logical_operator("a && b", "&&"), Some(2));
@@ -31,6 +31,105 @@ pub struct Fix {
     pub safety: FixSafety,
 }
 
+impl Fix {
+    /// Build a fix from a diagnostic's suggested fix
+    pub fn from_diagnostic(diag: &Diagnostic) -> Option<Self> {
+        let fix = diag.fix.as_ref()?;
+        Some(Self {
+            file: diag.location.file.clone(),
+            location: diag.location.clone(),
+            suggestion: FixSuggestion {
+                action: FixAction::Custom,
+                attribute: None,
+                value: Some(fix.replacement.clone()),
+                description: Some(fix.description.clone()),
+            },
+            original: diag.source_line.clone(),
+            rule_id: diag.rule_id.clone(),
+            safety: fix.safety,
+        })
+    }
+
+    /// Compute the minimal text edit this fix makes to its line
+    ///
+    /// Returns `None` when the original line is unknown or the fix
+    /// doesn't change it.
+    pub fn edit(&self) -> Option<FixEdit> {
+        let original = self.original.as_deref()?;
+        let new_line = Fixer::apply_fix_to_line(original, self)?;
+        if new_line == original {
+            return None;
+        }
+
+        let old_chars: Vec<char> = original.chars().collect();
+        let new_chars: Vec<char> = new_line.chars().collect();
+
+        let prefix = old_chars
+            .iter()
+            .zip(&new_chars)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+        let suffix = old_chars
+            .iter()
+            .rev()
+            .zip(new_chars.iter().rev())
+            .take(max_suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Some(FixEdit {
+            file: self.file.clone(),
+            line: self.location.line,
+            start_column: prefix + 1,
+            end_column: old_chars.len() - suffix + 1,
+            new_text: new_chars[prefix..new_chars.len() - suffix].iter().collect(),
+            original_line: original.to_string(),
+            new_line,
+        })
+    }
+}
+
+/// A single-line text edit derived from a fix
+///
+/// Formatters that carry fixes (SARIF, rdjson, review suggestions) all
+/// describe changes through this type so they agree on the exact range.
+/// Lines and columns are 1-based; columns count characters and
+/// `end_column` is exclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixEdit {
+    /// File the edit applies to
+    pub file: PathBuf,
+    /// Line being edited
+    pub line: usize,
+    /// First replaced column
+    pub start_column: usize,
+    /// Column after the last replaced character
+    pub end_column: usize,
+    /// Text inserted in place of the replaced range
+    pub new_text: String,
+    /// The line before the fix
+    pub original_line: String,
+    /// The line after the fix
+    pub new_line: String,
+}
+
+impl FixEdit {
+    /// The edit for a diagnostic's fix, if it has one that changes its line
+    pub fn from_diagnostic(diag: &Diagnostic) -> Option<Self> {
+        Fix::from_diagnostic(diag)?.edit()
+    }
+
+    /// Convert a character column on the original line to a UTF-8 byte column
+    pub fn byte_column(&self, column: usize) -> usize {
+        self.original_line
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map(|(i, _)| i + 1)
+            .unwrap_or(self.original_line.len() + 1)
+    }
+}
+
 /// Result of applying fixes
 #[derive(Debug, Default)]
 pub struct FixResult {
@@ -119,21 +218,7 @@ impl Fixer {
     /// Collect fixes from diagnostics
     pub fn collect_from_diagnostics(&mut self, diagnostics: &[Diagnostic]) {
         for diag in diagnostics {
-            if let Some(fix) = &diag.fix {
-                let fix_entry = Fix {
-                    file: diag.location.file.clone(),
-                    location: diag.location.clone(),
-                    suggestion: FixSuggestion {
-                        action: FixAction::Custom,
-                        attribute: None,
-                        value: Some(fix.replacement.clone()),
-                        description: Some(fix.description.clone()),
-                    },
-                    original: diag.source_line.clone(),
-                    rule_id: diag.rule_id.clone(),
-                    safety: fix.safety,
-                };
-
+            if let Some(fix_entry) = Fix::from_diagnostic(diag) {
                 self.fixes_by_file
                     .entry(diag.location.file.clone())
                     .or_default()
@@ -283,7 +368,7 @@ impl Fixer {
 
         // Sort fixes by line number in reverse order (apply from bottom to top)
         let mut sorted_fixes = fixes.to_vec();
-        sorted_fixes.sort_by(|a, b| b.location.line.cmp(&a.location.line));
+        sorted_fixes.sort_by_key(|f| std::cmp::Reverse(f.location.line));
 
         let mut modified_lines: Vec<String> = lines.iter().map(|s| s.to_string()).collect();
         let mut applied = 0;
@@ -296,7 +381,7 @@ impl Fixer {
             let line_idx = fix.location.line - 1;
             let line = &modified_lines[line_idx];
 
-            if let Some(new_line) = self.apply_fix_to_line(line, fix) {
+            if let Some(new_line) = Self::apply_fix_to_line(line, fix) {
                 modified_lines[line_idx] = new_line;
                 applied += 1;
 
@@ -331,7 +416,7 @@ impl Fixer {
     }
 
     /// Apply a single fix to a line
-    fn apply_fix_to_line(&self, line: &str, fix: &Fix) -> Option<String> {
+    fn apply_fix_to_line(line: &str, fix: &Fix) -> Option<String> {
         match &fix.suggestion.action {
             FixAction::AddAttribute => {
                 // Add attribute to element
@@ -549,6 +634,38 @@ fn generate_unified_diff(file: &Path, original: &str, modified: &str) -> String
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_fix_edit_is_minimal() {
+        let diag = Diagnostic::new(
+            "component-requires-guid",
+            crate::diagnostic::Severity::Error,
+            "Component is missing Guid",
+            Location::new(PathBuf::from("test.wxs"), 3, 5),
+        )
+        .with_source_line(r#"    <Component Id="C1">"#)
+        .with_fix("Add Guid", r#"    <Component Id="C1" Guid="*">"#);
+
+        let edit = Fix::from_diagnostic(&diag).unwrap().edit().unwrap();
+        assert_eq!(edit.line, 3);
+        assert_eq!(edit.start_column, 23);
+        assert_eq!(edit.end_column, 23);
+        assert_eq!(edit.new_text, r#" Guid="*""#);
+        assert_eq!(edit.new_line, r#"    <Component Id="C1" Guid="*">"#);
+    }
+
+    #[test]
+    fn test_fix_edit_requires_source_line() {
+        let diag = Diagnostic::new(
+            "rule",
+            crate::diagnostic::Severity::Warning,
+            "msg",
+            Location::new(PathBuf::from("test.wxs"), 1, 1),
+        )
+        .with_fix("Fix", "new");
+
+        assert!(Fix::from_diagnostic(&diag).unwrap().edit().is_none());
+    }
+
     #[test]
     fn test_fixer_new() {
         let fixer = Fixer::new(true);
@@ -558,7 +675,6 @@ mod tests {
 
     #[test]
     fn test_add_attribute_fix() {
-        let fixer = Fixer::new(true);
         let line = r#"<Component Id="MyComp">"#;
         let fix = Fix {
             file: PathBuf::from("test.wxs"),
@@ -574,14 +690,13 @@ mod tests {
             safety: FixSafety::Safe,
         };
 
-        let result = fixer.apply_fix_to_line(line, &fix);
+        let result = Fixer::apply_fix_to_line(line, &fix);
         assert!(result.is_some());
         assert!(result.unwrap().contains(r#"Guid="*""#));
     }
 
     #[test]
     fn test_set_attribute_fix() {
-        let fixer = Fixer::new(true);
         let line = r#"<Component Id="MyComp" Guid="old-guid">"#;
         let fix = Fix {
             file: PathBuf::from("test.wxs"),
@@ -597,7 +712,7 @@ mod tests {
             safety: FixSafety::Safe,
         };
 
-        let result = fixer.apply_fix_to_line(line, &fix);
+        let result = Fixer::apply_fix_to_line(line, &fix);
         assert!(result.is_some());
         let new_line = result.unwrap();
         assert!(new_line.contains(r#"Guid="*""#));
@@ -606,7 +721,6 @@ mod tests {
 
     #[test]
     fn test_remove_attribute_fix() {
-        let fixer = Fixer::new(true);
         let line = r#"<Component Id="MyComp" Obsolete="yes">"#;
         let fix = Fix {
             file: PathBuf::from("test.wxs"),
@@ -622,14 +736,13 @@ mod tests {
             safety: FixSafety::Safe,
         };
 
-        let result = fixer.apply_fix_to_line(line, &fix);
+        let result = Fixer::apply_fix_to_line(line, &fix);
         assert!(result.is_some());
         assert!(!result.unwrap().contains("Obsolete"));
     }
 
     #[test]
     fn test_remove_element_fix() {
-        let fixer = Fixer::new(true);
         let line = r#"<DeprecatedElement />"#;
         let fix = Fix {
             file: PathBuf::from("test.wxs"),
@@ -645,7 +758,7 @@ mod tests {
             safety: FixSafety::Unsafe, // Removing elements is potentially unsafe
         };
 
-        let result = fixer.apply_fix_to_line(line, &fix);
+        let result = Fixer::apply_fix_to_line(line, &fix);
         assert!(result.is_some());
         assert!(result.unwrap().is_empty());
     }
@@ -0,0 +1,399 @@
+//! Interactive fix review (`--fix --interactive`)
+//!
+//! Walks through pending fixes one at a time, showing the diff, the rule's
+//! rationale and whether the fix is safe. Each fix can be accepted, skipped,
+//! accepted together with every remaining fix for the same rule, or edited
+//! before it is accepted.
+//!
+//! The session is driven by any `BufRead`/`Write` pair so it can run against
+//! a terminal or be scripted in tests.
+
+use crate::diagnostic::{Diagnostic, FixSafety};
+use crate::fixer::{Fix, FixMode, FixResult, Fixer};
+use crate::rule::{FixAction, Rule};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+/// A decision for a single fix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReviewChoice {
+    /// Apply the fix as suggested
+    Accept,
+    /// Leave the code unchanged
+    Skip,
+    /// Apply this and every remaining fix for the same rule
+    AcceptRule,
+    /// Apply a user-edited replacement line instead
+    Edit(String),
+    /// Skip this and all remaining fixes
+    Quit,
+}
+
+/// Outcome of a review session
+#[derive(Debug, Default)]
+pub struct ReviewOutcome {
+    /// Fixes to apply (edited fixes carry the user's replacement)
+    pub accepted: Vec<Fix>,
+    /// Fixes the user declined
+    pub skipped: Vec<Fix>,
+    /// Number of accepted fixes that were edited
+    pub edited: usize,
+}
+
+impl ReviewOutcome {
+    /// Write the accepted fixes to disk
+    pub fn apply(&self) -> FixResult {
+        let mut fixer = Fixer::new(false).with_mode(FixMode::All);
+        for fix in &self.accepted {
+            fixer.add_fix(fix.clone());
+        }
+        fixer.apply_all()
+    }
+
+    /// Diagnostics whose fixes were skipped (for recording in a baseline)
+    pub fn skipped_diagnostics(&self, diagnostics: &[Diagnostic]) -> Vec<Diagnostic> {
+        let skipped: HashSet<_> = self
+            .skipped
+            .iter()
+            .map(|f| (&f.rule_id, &f.file, f.location.line))
+            .collect();
+
+        diagnostics
+            .iter()
+            .filter(|d| {
+                d.fix.is_some()
+                    && skipped.contains(&(&d.rule_id, &d.location.file, d.location.line))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// One-line summary of the session
+    pub fn summary(&self, result: &FixResult) -> String {
+        format!(
+            "Accepted {} fixes ({} edited), skipped {}; applied {} to {} files",
+            self.accepted.len(),
+            self.edited,
+            self.skipped.len(),
+            result.fixes_applied,
+            result.files_modified
+        )
+    }
+}
+
+/// Interactive reviewer for pending fixes
+pub struct InteractiveReview {
+    /// Fixes in review order
+    fixes: Vec<Fix>,
+    /// Rule metadata for rationale display (keyed by rule ID)
+    rules: HashMap<String, Rule>,
+}
+
+impl InteractiveReview {
+    /// Create a review over the fixer's pending fixes
+    ///
+    /// The fixer's mode decides which fixes are offered.
+    pub fn new(fixer: &Fixer) -> Self {
+        Self {
+            fixes: fixer.get_pending_fixes().into_iter().cloned().collect(),
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Provide rule definitions for rationale display
+    pub fn with_rules<'a>(mut self, rules: impl IntoIterator<Item = &'a Rule>) -> Self {
+        for rule in rules {
+            self.rules.insert(rule.id.clone(), rule.clone());
+        }
+        self
+    }
+
+    /// Number of fixes to review
+    pub fn len(&self) -> usize {
+        self.fixes.len()
+    }
+
+    /// Whether there is nothing to review
+    pub fn is_empty(&self) -> bool {
+        self.fixes.is_empty()
+    }
+
+    /// Run the session against stdin/stdout
+    pub fn run_terminal(self) -> io::Result<ReviewOutcome> {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        self.run(&mut stdin.lock(), &mut stdout.lock())
+    }
+
+    /// Run the session
+    ///
+    /// End of input is treated as quitting: remaining fixes are skipped.
+    pub fn run<R: BufRead, W: Write>(
+        self,
+        input: &mut R,
+        output: &mut W,
+    ) -> io::Result<ReviewOutcome> {
+        let mut outcome = ReviewOutcome::default();
+        let mut accept_rules: HashSet<String> = HashSet::new();
+        let mut quit = false;
+        let total = self.fixes.len();
+
+        for (i, fix) in self.fixes.iter().enumerate() {
+            if quit {
+                outcome.skipped.push(fix.clone());
+                continue;
+            }
+            if accept_rules.contains(&fix.rule_id) {
+                outcome.accepted.push(fix.clone());
+                continue;
+            }
+
+            self.show(output, fix, i + 1, total)?;
+
+            match prompt_choice(input, output)? {
+                ReviewChoice::Accept => outcome.accepted.push(fix.clone()),
+                ReviewChoice::Skip => outcome.skipped.push(fix.clone()),
+                ReviewChoice::AcceptRule => {
+                    accept_rules.insert(fix.rule_id.clone());
+                    outcome.accepted.push(fix.clone());
+                }
+                ReviewChoice::Edit(line) => {
+                    outcome.accepted.push(edited_fix(fix, &line));
+                    outcome.edited += 1;
+                }
+                ReviewChoice::Quit => {
+                    quit = true;
+                    outcome.skipped.push(fix.clone());
+                }
+            }
+            writeln!(output)?;
+        }
+
+        Ok(outcome)
+    }
+
+    /// Show one fix: location, safety, rationale and diff
+    fn show<W: Write>(
+        &self,
+        output: &mut W,
+        fix: &Fix,
+        index: usize,
+        total: usize,
+    ) -> io::Result<()> {
+        let safety = fix.safety.to_string();
+        let safety = match fix.safety {
+            FixSafety::Safe => safety.green(),
+            FixSafety::Unsafe => safety.red().bold(),
+            FixSafety::Display => safety.yellow(),
+        };
+
+        writeln!(
+            output,
+            "{} {}:{} {} ({})",
+            format!("[{}/{}]", index, total).dimmed(),
+            fix.file.display(),
+            fix.location.line,
+            fix.rule_id.cyan(),
+            safety
+        )?;
+
+        if let Some(description) = &fix.suggestion.description {
+            writeln!(output, "  {}", description.bold())?;
+        }
+        if let Some(rationale) = self
+            .rules
+            .get(&fix.rule_id)
+            .and_then(|r| r.rationale.as_ref())
+        {
+            writeln!(output, "  {} {}", "why:".blue(), rationale)?;
+        }
+
+        match (fix.original.as_deref(), fix.edit()) {
+            (Some(original), Some(edit)) => {
+                writeln!(output, "  {}", format!("- {}", original).red())?;
+                writeln!(output, "  {}", format!("+ {}", edit.new_line).green())?;
+            }
+            _ => writeln!(output, "  {}", "(no preview available)".dimmed())?,
+        }
+        Ok(())
+    }
+}
+
+/// Prompt until a valid choice is entered
+fn prompt_choice<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> io::Result<ReviewChoice> {
+    loop {
+        write!(
+            output,
+            "Apply? [y]es, [n]o, [a]ll for this rule, [e]dit, [q]uit: "
+        )?;
+        output.flush()?;
+
+        let Some(answer) = read_line(input)? else {
+            return Ok(ReviewChoice::Quit);
+        };
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(ReviewChoice::Accept),
+            "n" | "no" | "s" | "skip" => return Ok(ReviewChoice::Skip),
+            "a" | "all" => return Ok(ReviewChoice::AcceptRule),
+            "q" | "quit" => return Ok(ReviewChoice::Quit),
+            "e" | "edit" => {
+                write!(output, "Replacement line: ")?;
+                output.flush()?;
+                match read_line(input)? {
+                    Some(line) if !line.trim().is_empty() => {
+                        return Ok(ReviewChoice::Edit(line));
+                    }
+                    Some(_) => writeln!(output, "Empty replacement, try again.")?,
+                    None => return Ok(ReviewChoice::Quit),
+                }
+            }
+            _ => writeln!(output, "Please answer y, n, a, e or q.")?,
+        }
+    }
+}
+
+/// Read a line without its terminator; `None` at end of input
+fn read_line<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// A copy of `fix` that replaces its line with `line`
+fn edited_fix(fix: &Fix, line: &str) -> Fix {
+    let mut edited = fix.clone();
+    edited.suggestion.action = FixAction::Custom;
+    edited.suggestion.attribute = None;
+    edited.suggestion.value = Some(line.to_string());
+    edited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::{Location, Severity};
+    use std::io::Cursor;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    fn diag(file: &Path, rule: &str, line: usize, source: &str, fixed: &str) -> Diagnostic {
+        Diagnostic::new(
+            rule,
+            Severity::Warning,
+            "msg",
+            Location::new(file.to_path_buf(), line, 1),
+        )
+        .with_source_line(source)
+        .with_fix("Fix it", fixed)
+    }
+
+    fn review(diagnostics: &[Diagnostic]) -> InteractiveReview {
+        let mut fixer = Fixer::new(false).with_mode(FixMode::All);
+        fixer.collect_from_diagnostics(diagnostics);
+        InteractiveReview::new(&fixer)
+    }
+
+    fn run(review: InteractiveReview, answers: &str) -> (ReviewOutcome, String) {
+        let mut output = Vec::new();
+        let outcome = review
+            .run(&mut Cursor::new(answers.as_bytes()), &mut output)
+            .unwrap();
+        (outcome, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_accept_and_skip() {
+        let file = PathBuf::from("a.wxs");
+        let diags = vec![
+            diag(&file, "r1", 1, "<A/>", "<A x=\"1\"/>"),
+            diag(&file, "r1", 2, "<B/>", "<B x=\"1\"/>"),
+        ];
+        let (outcome, output) = run(review(&diags), "y\nn\n");
+
+        assert_eq!(outcome.accepted.len(), 1);
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(outcome.skipped[0].location.line, 2);
+        assert!(output.contains("- <A/>"));
+        assert!(output.contains("+ <A x=\"1\"/>"));
+    }
+
+    #[test]
+    fn test_accept_all_for_rule() {
+        let file = PathBuf::from("a.wxs");
+        let diags = vec![
+            diag(&file, "r1", 1, "<A/>", "<A1/>"),
+            diag(&file, "r2", 2, "<B/>", "<B1/>"),
+            diag(&file, "r1", 3, "<C/>", "<C1/>"),
+        ];
+        // Only two prompts: r1 is accepted wholesale, then r2 is skipped
+        let (outcome, output) = run(review(&diags), "a\nn\n");
+
+        let accepted: Vec<_> = outcome.accepted.iter().map(|f| f.location.line).collect();
+        assert_eq!(accepted, vec![1, 3]);
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(output.matches("Apply?").count(), 2);
+    }
+
+    #[test]
+    fn test_edit_and_quit() {
+        let file = PathBuf::from("a.wxs");
+        let diags = vec![
+            diag(&file, "r1", 1, "<A/>", "<A1/>"),
+            diag(&file, "r1", 2, "<B/>", "<B1/>"),
+            diag(&file, "r1", 3, "<C/>", "<C1/>"),
+        ];
+        let (outcome, _) = run(review(&diags), "e\n<A custom=\"yes\"/>\nq\n");
+
+        assert_eq!(outcome.edited, 1);
+        assert_eq!(
+            outcome.accepted[0].suggestion.value.as_deref(),
+            Some("<A custom=\"yes\"/>")
+        );
+        assert_eq!(outcome.skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_end_of_input_skips_remaining() {
+        let file = PathBuf::from("a.wxs");
+        let diags = vec![diag(&file, "r1", 1, "<A/>", "<A1/>")];
+        let (outcome, _) = run(review(&diags), "");
+
+        assert!(outcome.accepted.is_empty());
+        assert_eq!(outcome.skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_shows_rationale_and_safety() {
+        let file = PathBuf::from("a.wxs");
+        let diags = vec![diag(&file, "r1", 1, "<A/>", "<A1/>")];
+        let rule = Rule::new("r1", "true", "m").with_rationale("Because reasons");
+        let (_, output) = run(review(&diags).with_rules([&rule]), "n\n");
+
+        assert!(output.contains("Because reasons"));
+        assert!(output.contains("safe"));
+    }
+
+    #[test]
+    fn test_apply_writes_accepted_only() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("a.wxs");
+        std::fs::write(&file, "<A/>\n<B/>\n").unwrap();
+        let diags = vec![
+            diag(&file, "r1", 1, "<A/>", "<A1/>"),
+            diag(&file, "r2", 2, "<B/>", "<B1/>"),
+        ];
+        let (outcome, _) = run(review(&diags), "y\nn\n");
+
+        let result = outcome.apply();
+        assert_eq!(result.fixes_applied, 1);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "<A1/>\n<B/>\n");
+
+        let skipped = outcome.skipped_diagnostics(&diags);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].rule_id, "r2");
+    }
+}
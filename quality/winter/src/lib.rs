@@ -38,8 +38,10 @@ pub mod complexity;
 pub mod config;
 pub mod cross_file;
 pub mod diagnostic;
+pub mod embedded;
 pub mod engine;
 pub mod fixer;
+pub mod interactive;
 pub mod lsp;
 pub mod output;
 pub mod plugin;
@@ -54,8 +56,9 @@ pub use complexity::{ComplexityAnalyzer, ComplexityMetrics, ComplexityRating};
 pub use config::Config;
 pub use cross_file::CrossFileValidator;
 pub use diagnostic::{Diagnostic, Fix as DiagnosticFix, FixSafety, Location, Severity};
+pub use embedded::EmbeddedBlock;
 pub use engine::{Engine, LintResult, RuleTiming};
-pub use fixer::{Fix, FixMode, FixResult, Fixer};
+pub use fixer::{Fix, FixEdit, FixMode, FixResult, Fixer};
 pub use lsp::{
     to_code_action, to_lsp_diagnostics, to_publish_diagnostics, CodeAction, LspDiagnostic,
     LspSeverity, Position as LspPosition, PublishDiagnosticsParams, Range as LspRange,
@@ -63,7 +66,7 @@ pub use lsp::{
 };
 pub use output::{
     AzureFormatter, CompactFormatter, GithubFormatter, GitlabFormatter, GroupedFormatter,
-    JUnitFormatter, OutputFormatter,
+    JUnitFormatter, OutputFormatter, RdjsonFormatter,
 };
 pub use plugin::{Document, Node, Plugin};
 pub use plugin_manager::{DynamicPlugin, EmbeddedLanguage, PluginManager, PluginManifest};
@@ -9,13 +9,14 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::sync::Arc;
 use winter::baseline::Baseline;
-use winter::cache::{default_cache_path, hash_config, LintCache};
+use winter::cache::{default_cache_dir, LintCache};
 use winter::config::{ColorMode, Config, OutputFormat};
 use winter::engine::Engine;
 use winter::fixer::Fixer;
+use winter::interactive::InteractiveReview;
 use winter::output::{
     AzureFormatter, CompactFormatter, GithubFormatter, GitlabFormatter, GroupedFormatter,
-    JUnitFormatter, JsonFormatter, OutputFormatter, SarifFormatter, TextFormatter,
+    JUnitFormatter, JsonFormatter, OutputFormatter, RdjsonFormatter, SarifFormatter, TextFormatter,
 };
 use winter::plugin_manager::PluginManager;
 use winter::plugins::wix::WixPlugin;
@@ -114,6 +115,14 @@ struct Cli {
     #[arg(long, requires = "fix")]
     write: bool,
 
+    /// Review each fix in the terminal and write the accepted ones (requires --fix)
+    #[arg(long, requires = "fix", conflicts_with_all = ["diff", "show_fixes"])]
+    interactive: bool,
+
+    /// Record fixes skipped in --interactive into the baseline (requires --baseline)
+    #[arg(long, requires_all = ["interactive", "baseline"])]
+    baseline_skipped: bool,
+
     /// Use baseline file to ignore existing issues (creates if not found)
     #[arg(long)]
     baseline: Option<PathBuf>,
@@ -122,10 +131,22 @@ struct Cli {
     #[arg(long, requires = "baseline")]
     update_baseline: bool,
 
+    /// With --format github, print review comments with suggestion blocks
+    #[arg(long)]
+    github_suggestions: bool,
+
+    /// Previous SARIF log to compare against (sets baselineState on results)
+    #[arg(long, value_name = "FILE")]
+    sarif_baseline: Option<PathBuf>,
+
     /// Enable caching for faster incremental runs
     #[arg(long)]
     cache: bool,
 
+    /// Cache directory (can be shared between CI jobs; implies --cache)
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
     /// Only lint files changed in git (requires git repository)
     #[arg(long)]
     changed: bool,
@@ -229,6 +250,8 @@ enum Format {
     Junit,
     Gitlab,
     Azure,
+    Rdjson,
+    Rdjsonl,
 }
 
 #[derive(Clone, Copy, ValueEnum)]
@@ -657,6 +680,8 @@ fn main() {
         Format::Junit => OutputFormat::Junit,
         Format::Gitlab => OutputFormat::Gitlab,
         Format::Azure => OutputFormat::Azure,
+        Format::Rdjson => OutputFormat::Rdjson,
+        Format::Rdjsonl => OutputFormat::Rdjsonl,
     };
 
     // Set preview mode from CLI
@@ -1017,20 +1042,26 @@ fn main() {
         }
     }
 
-    // Load cache if enabled
-    let mut cache = if cli.cache {
-        let cache_path = default_cache_path();
-        if cli.verbose {
-            eprintln!("Using cache: {}", cache_path.display());
+    // Use the content-addressed cache if enabled
+    if cli.cache || cli.cache_dir.is_some() || config.engine.cache {
+        let cache_dir = cli
+            .cache_dir
+            .clone()
+            .or_else(|| config.engine.cache_dir.clone())
+            .unwrap_or_else(default_cache_dir);
+        match LintCache::open(&cache_dir) {
+            Ok(cache) => {
+                if cli.verbose {
+                    eprintln!("Using cache: {}", cache_dir.display());
+                }
+                engine.set_cache(cache);
+            }
+            Err(e) => {
+                if cli.verbose {
+                    eprintln!("{}: Failed to open cache: {}", "warning".yellow(), e);
+                }
+            }
         }
-        LintCache::load(&cache_path).unwrap_or_else(|_| LintCache::new())
-    } else {
-        LintCache::new()
-    };
-
-    // Set config hash for cache invalidation
-    if cli.cache {
-        cache.set_config_hash(&hash_config(&config));
     }
 
     // Run linting
@@ -1040,13 +1071,10 @@ fn main() {
         engine.lint(&files)
     };
 
-    // Save cache
-    if cli.cache {
-        let cache_path = default_cache_path();
-        if let Err(e) = cache.save(&cache_path) {
-            if cli.verbose {
-                eprintln!("{}: Failed to save cache: {}", "warning".yellow(), e);
-            }
+    if cli.verbose {
+        if let Some(cache) = engine.cache() {
+            let stats = cache.stats();
+            eprintln!("Cache: {} hits, {} misses", stats.hits, stats.misses);
         }
     }
 
@@ -1138,7 +1166,49 @@ fn main() {
     // Handle auto-fix
     // Handle fix-related flags
     let mut fixes_applied = 0;
-    if cli.fix || cli.diff || cli.show_fixes {
+    if cli.interactive {
+        // Every fix is confirmed individually, so unsafe ones are offered too
+        let mut fixer = Fixer::new(false).with_unsafe_fixes(true);
+        fixer.collect_from_diagnostics(&result.diagnostics);
+
+        let review = InteractiveReview::new(&fixer).with_rules(engine.rules());
+        if review.is_empty() {
+            eprintln!("No fixes available.");
+        } else {
+            match review.run_terminal() {
+                Ok(outcome) => {
+                    let fix_result = outcome.apply();
+                    fixes_applied = fix_result.fixes_applied;
+                    eprintln!("{}", outcome.summary(&fix_result));
+                    for error in &fix_result.errors {
+                        eprintln!("{}: {}", "error".red().bold(), error);
+                    }
+
+                    if cli.baseline_skipped {
+                        if let Some(baseline_path) = &cli.baseline {
+                            let skipped = outcome.skipped_diagnostics(&result.diagnostics);
+                            let mut baseline =
+                                Baseline::load(baseline_path).unwrap_or_else(|_| Baseline::new());
+                            baseline.add_diagnostics(&skipped);
+                            match baseline.save(baseline_path) {
+                                Ok(()) => eprintln!(
+                                    "Recorded {} skipped fixes in {}",
+                                    skipped.len(),
+                                    baseline_path.display()
+                                ),
+                                Err(e) => eprintln!(
+                                    "{}: Failed to save baseline: {}",
+                                    "error".red().bold(),
+                                    e
+                                ),
+                            }
+                        }
+                    }
+                }
+                Err(e) => eprintln!("{}: Interactive review failed: {}", "error".red().bold(), e),
+            }
+        }
+    } else if cli.fix || cli.diff || cli.show_fixes {
         let dry_run = !cli.write;
         let mut fixer = Fixer::new(dry_run);
 
@@ -1236,11 +1306,37 @@ fn main() {
             Box::new(f)
         }
         OutputFormat::Json => Box::new(JsonFormatter::new().pretty()),
-        OutputFormat::Sarif => Box::new(SarifFormatter::new("winter", env!("CARGO_PKG_VERSION"))),
-        OutputFormat::Github => Box::new(GithubFormatter::new()),
+        OutputFormat::Sarif => {
+            let mut f =
+                SarifFormatter::new("winter", env!("CARGO_PKG_VERSION")).with_rules(engine.rules());
+            if let Some(path) = &cli.sarif_baseline {
+                f = match f.with_baseline(path) {
+                    Ok(f) => f,
+                    Err(e) => {
+                        eprintln!(
+                            "{}: Failed to load SARIF baseline {}: {}",
+                            "error".red().bold(),
+                            path.display(),
+                            e
+                        );
+                        std::process::exit(1);
+                    }
+                };
+            }
+            Box::new(f)
+        }
+        OutputFormat::Github => {
+            let mut f = GithubFormatter::new();
+            if cli.github_suggestions {
+                f = f.with_suggestions();
+            }
+            Box::new(f)
+        }
         OutputFormat::Junit => Box::new(JUnitFormatter::new()),
         OutputFormat::Gitlab => Box::new(GitlabFormatter::new()),
         OutputFormat::Azure => Box::new(AzureFormatter::new()),
+        OutputFormat::Rdjson => Box::new(RdjsonFormatter::new()),
+        OutputFormat::Rdjsonl => Box::new(RdjsonFormatter::new().lines()),
     };
 
     // Output results
@@ -95,6 +95,7 @@ mod tests {
             help: None,
             fix: None,
             notes: vec![],
+            related: vec![],
         };
 
         let output = formatter.format_diagnostic(&diag);
@@ -119,6 +120,7 @@ mod tests {
                 help: None,
                 fix: None,
                 notes: vec![],
+                related: vec![],
             }],
             files_processed: 1,
             files_with_errors: 1,
@@ -107,6 +107,7 @@ mod tests {
             help: None,
             fix: None,
             notes: vec![],
+            related: vec![],
         };
 
         let output = formatter.format_diagnostic(&diag);
@@ -127,6 +128,7 @@ mod tests {
             help: None,
             fix: None,
             notes: vec![],
+            related: vec![],
         };
 
         let output = formatter.format_diagnostic(&diag);
@@ -149,6 +151,7 @@ mod tests {
                     help: None,
                     fix: None,
                     notes: vec![],
+                    related: vec![],
                 },
                 Diagnostic {
                     rule_id: "r2".to_string(),
@@ -161,6 +164,7 @@ mod tests {
                     help: None,
                     fix: None,
                     notes: vec![],
+                    related: vec![],
                 },
             ],
             files_processed: 1,
@@ -2,21 +2,32 @@
 //!
 //! Outputs diagnostics in GitHub Actions workflow command format:
 //! ::warning file={name},line={line},col={col}::{message}
+//!
+//! In suggestion mode it instead prints one pull request review comment per
+//! diagnostic, with a ```` ```suggestion ```` block when a fix is available,
+//! for review bots to post on the reported line.
 
-use super::OutputFormatter;
+use super::{suggestion_block, OutputFormatter};
 use crate::diagnostic::{Diagnostic, Severity};
 use crate::engine::LintResult;
+use crate::fixer::FixEdit;
 
 /// Formatter for GitHub Actions annotations
 pub struct GithubFormatter {
     /// Whether to include summary
     pub show_summary: bool,
+
+    /// Print review comments with suggestion blocks instead of annotations
+    pub suggestions: bool,
 }
 
 impl GithubFormatter {
     /// Create a new GitHub formatter
     pub fn new() -> Self {
-        Self { show_summary: true }
+        Self {
+            show_summary: true,
+            suggestions: false,
+        }
     }
 
     /// Disable summary output
@@ -24,6 +35,49 @@ impl GithubFormatter {
         self.show_summary = false;
         self
     }
+
+    /// Print review comments with ```` ```suggestion ```` blocks
+    pub fn with_suggestions(mut self) -> Self {
+        self.suggestions = true;
+        self
+    }
+
+    /// Format a diagnostic as a review comment
+    ///
+    /// The leading HTML comment tells bots where to post it.
+    fn format_review_comment(&self, diagnostic: &Diagnostic) -> String {
+        let level = match diagnostic.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "notice",
+        };
+
+        let mut comment = format!(
+            "<!-- winter path={} line={} rule={} -->\n**{}** `{}`: {}\n",
+            diagnostic
+                .location
+                .file
+                .display()
+                .to_string()
+                .replace('\\', "/"),
+            diagnostic.location.line,
+            diagnostic.rule_id,
+            level,
+            diagnostic.rule_id,
+            diagnostic.message
+        );
+
+        if let (Some(fix), Some(edit)) = (&diagnostic.fix, FixEdit::from_diagnostic(diagnostic)) {
+            comment.push_str(&format!(
+                "\n{} ({})\n\n{}\n",
+                fix.description,
+                fix.safety,
+                suggestion_block(&edit)
+            ));
+        }
+
+        comment
+    }
 }
 
 impl Default for GithubFormatter {
@@ -36,6 +90,14 @@ impl OutputFormatter for GithubFormatter {
     fn format(&self, result: &LintResult) -> String {
         let mut output = String::new();
 
+        if self.suggestions {
+            for diag in &result.diagnostics {
+                output.push_str(&self.format_review_comment(diag));
+                output.push('\n');
+            }
+            return output;
+        }
+
         // Output each diagnostic as a GitHub annotation
         for diag in &result.diagnostics {
             output.push_str(&self.format_diagnostic(diag));
@@ -67,6 +129,10 @@ impl OutputFormatter for GithubFormatter {
     }
 
     fn format_diagnostic(&self, diagnostic: &Diagnostic) -> String {
+        if self.suggestions {
+            return self.format_review_comment(diagnostic);
+        }
+
         let level = match diagnostic.severity {
             Severity::Error => "error",
             Severity::Warning => "warning",
@@ -121,6 +187,7 @@ mod tests {
             help: None,
             fix: None,
             notes: vec![],
+            related: vec![],
         }
     }
 
@@ -207,4 +274,31 @@ mod tests {
         assert!(output.contains("::group::"));
         assert!(output.contains("::endgroup::"));
     }
+
+    #[test]
+    fn test_github_suggestion_mode() {
+        let formatter = GithubFormatter::new().with_suggestions();
+        let diag = make_diagnostic(Severity::Error, "r1", "src\\product.wxs", 3, "Missing Guid")
+            .with_source_line("  <Component Id=\"C\">")
+            .with_fix("Add Guid", "  <Component Id=\"C\" Guid=\"*\">");
+
+        let output = formatter.format_diagnostic(&diag);
+        assert!(output.starts_with("<!-- winter path=src/product.wxs line=3 rule=r1 -->"));
+        assert!(output.contains("**error** `r1`: Missing Guid"));
+        assert!(output.contains("Add Guid (safe)"));
+        assert!(output.contains("```suggestion\n  <Component Id=\"C\" Guid=\"*\">\n```"));
+    }
+
+    #[test]
+    fn test_github_suggestion_mode_without_fix() {
+        let formatter = GithubFormatter::new().with_suggestions();
+        let result = LintResult {
+            diagnostics: vec![make_diagnostic(Severity::Warning, "r1", "a.wxs", 1, "m")],
+            ..Default::default()
+        };
+
+        let output = formatter.format(&result);
+        assert!(!output.contains("```suggestion"));
+        assert!(!output.contains("::notice::"));
+    }
 }
@@ -2,20 +2,35 @@
 //!
 //! Outputs diagnostics in GitLab Code Quality format (JSON).
 //! https://docs.gitlab.com/ee/ci/testing/code_quality.html
+//!
+//! Fingerprints hash the rule, file and line content rather than the line
+//! number, so an issue keeps its identity when unrelated edits move it and
+//! merge request widgets don't report it as fixed and re-introduced.
 
-use super::OutputFormatter;
+use super::{fingerprint, fingerprints, suggestion_block, OutputFormatter};
 use crate::diagnostic::{Diagnostic, Severity};
 use crate::engine::LintResult;
+use crate::fixer::FixEdit;
 use serde::Serialize;
 
 /// GitLab Code Quality issue
 #[derive(Serialize)]
 struct GitLabIssue {
+    #[serde(rename = "type")]
+    kind: &'static str,
     description: String,
     check_name: String,
     fingerprint: String,
     severity: String,
     location: GitLabLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<GitLabContent>,
+}
+
+/// Extended description (Code Climate `content`), used for suggested fixes
+#[derive(Serialize)]
+struct GitLabContent {
+    body: String,
 }
 
 #[derive(Serialize)]
@@ -27,6 +42,7 @@ struct GitLabLocation {
 #[derive(Serialize)]
 struct GitLabLines {
     begin: usize,
+    end: usize,
 }
 
 /// Formatter for GitLab Code Quality JSON output
@@ -46,16 +62,33 @@ impl GitlabFormatter {
         }
     }
 
-    fn generate_fingerprint(diag: &Diagnostic) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let mut hasher = DefaultHasher::new();
-        diag.rule_id.hash(&mut hasher);
-        diag.location.file.hash(&mut hasher);
-        diag.location.line.hash(&mut hasher);
-        diag.message.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
+    fn issue(diag: &Diagnostic, fingerprint: String) -> GitLabIssue {
+        let content = FixEdit::from_diagnostic(diag).map(|edit| GitLabContent {
+            body: format!(
+                "Suggested fix: {}\n\n{}",
+                diag.fix
+                    .as_ref()
+                    .map(|f| f.description.as_str())
+                    .unwrap_or(""),
+                suggestion_block(&edit)
+            ),
+        });
+
+        GitLabIssue {
+            kind: "issue",
+            description: diag.message.clone(),
+            check_name: diag.rule_id.clone(),
+            fingerprint,
+            severity: Self::severity_to_gitlab(diag.severity).to_string(),
+            location: GitLabLocation {
+                path: diag.location.file.display().to_string(),
+                lines: GitLabLines {
+                    begin: diag.location.line,
+                    end: diag.location.line,
+                },
+            },
+            content,
+        }
     }
 }
 
@@ -70,37 +103,15 @@ impl OutputFormatter for GitlabFormatter {
         let issues: Vec<GitLabIssue> = result
             .diagnostics
             .iter()
-            .map(|diag| GitLabIssue {
-                description: diag.message.clone(),
-                check_name: diag.rule_id.clone(),
-                fingerprint: Self::generate_fingerprint(diag),
-                severity: Self::severity_to_gitlab(diag.severity).to_string(),
-                location: GitLabLocation {
-                    path: diag.location.file.display().to_string(),
-                    lines: GitLabLines {
-                        begin: diag.location.line,
-                    },
-                },
-            })
+            .zip(fingerprints(&result.diagnostics))
+            .map(|(diag, fp)| Self::issue(diag, fp))
             .collect();
 
         serde_json::to_string_pretty(&issues).unwrap_or_else(|_| "[]".to_string())
     }
 
     fn format_diagnostic(&self, diagnostic: &Diagnostic) -> String {
-        let issue = GitLabIssue {
-            description: diagnostic.message.clone(),
-            check_name: diagnostic.rule_id.clone(),
-            fingerprint: Self::generate_fingerprint(diagnostic),
-            severity: Self::severity_to_gitlab(diagnostic.severity).to_string(),
-            location: GitLabLocation {
-                path: diagnostic.location.file.display().to_string(),
-                lines: GitLabLines {
-                    begin: diagnostic.location.line,
-                },
-            },
-        };
-
+        let issue = Self::issue(diagnostic, fingerprint(diagnostic, 1));
         serde_json::to_string(&issue).unwrap_or_else(|_| "{}".to_string())
     }
 }
@@ -126,6 +137,7 @@ mod tests {
                 help: None,
                 fix: None,
                 notes: vec![],
+                related: vec![],
             }],
             files_processed: 1,
             files_with_errors: 1,
@@ -142,4 +154,45 @@ mod tests {
         assert!(output.contains("\"check_name\": \"test-rule\""));
         assert!(output.contains("\"path\": \"test.wxs\""));
     }
+
+    fn diag_at(line: usize, source: &str) -> Diagnostic {
+        Diagnostic::new(
+            "component-requires-guid",
+            Severity::Error,
+            "Component is missing Guid",
+            Location::new(PathBuf::from("product.wxs"), line, 5),
+        )
+        .with_source_line(source)
+    }
+
+    fn format(diagnostics: Vec<Diagnostic>) -> serde_json::Value {
+        let result = LintResult {
+            diagnostics,
+            ..Default::default()
+        };
+        serde_json::from_str(&GitlabFormatter::new().format(&result)).unwrap()
+    }
+
+    #[test]
+    fn test_gitlab_fingerprint_stable_across_edits() {
+        let before = format(vec![diag_at(10, "<Component Id=\"C\">")]);
+        let after = format(vec![diag_at(25, "  <Component Id=\"C\">")]);
+        assert_eq!(before[0]["fingerprint"], after[0]["fingerprint"]);
+
+        let twice = format(vec![
+            diag_at(10, "<Component Id=\"C\">"),
+            diag_at(11, "<Component Id=\"C\">"),
+        ]);
+        assert_ne!(twice[0]["fingerprint"], twice[1]["fingerprint"]);
+    }
+
+    #[test]
+    fn test_gitlab_content_has_suggestion() {
+        let issues = format(vec![diag_at(3, "<Component Id=\"C\">")
+            .with_fix("Add Guid", "<Component Id=\"C\" Guid=\"*\">")]);
+        let body = issues[0]["content"]["body"].as_str().unwrap();
+
+        assert!(body.contains("Add Guid"));
+        assert!(body.contains("```suggestion\n<Component Id=\"C\" Guid=\"*\">\n```"));
+    }
 }
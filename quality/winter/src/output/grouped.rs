@@ -165,6 +165,7 @@ mod tests {
                     help: None,
                     fix: None,
                     notes: vec![],
+                    related: vec![],
                 },
                 Diagnostic {
                     rule_id: "rule2".to_string(),
@@ -177,6 +178,7 @@ mod tests {
                     help: None,
                     fix: None,
                     notes: vec![],
+                    related: vec![],
                 },
             ],
             files_processed: 1,
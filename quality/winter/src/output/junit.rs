@@ -133,6 +133,7 @@ mod tests {
                 help: None,
                 fix: None,
                 notes: vec![],
+                related: vec![],
             }],
             files_processed: 1,
             files_with_errors: 1,
@@ -7,6 +7,7 @@ mod gitlab;
 mod grouped;
 mod json;
 mod junit;
+mod rdjson;
 mod sarif;
 mod text;
 
@@ -17,11 +18,14 @@ pub use gitlab::GitlabFormatter;
 pub use grouped::GroupedFormatter;
 pub use json::JsonFormatter;
 pub use junit::JUnitFormatter;
+pub use rdjson::RdjsonFormatter;
 pub use sarif::SarifFormatter;
 pub use text::TextFormatter;
 
 use crate::diagnostic::Diagnostic;
 use crate::engine::LintResult;
+use crate::fixer::FixEdit;
+use std::collections::HashMap;
 
 /// Output formatter trait
 pub trait OutputFormatter: Send + Sync {
@@ -31,3 +35,50 @@ pub trait OutputFormatter: Send + Sync {
     /// Format a single diagnostic
     fn format_diagnostic(&self, diagnostic: &Diagnostic) -> String;
 }
+
+/// FNV-1a, used because fingerprints must be identical across builds and platforms
+fn stable_hash(input: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in input.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Fingerprint a diagnostic by rule, file and line content
+///
+/// The line number is deliberately left out so results keep their identity
+/// when code above them moves. `occurrence` separates identical lines.
+pub(crate) fn fingerprint(diag: &Diagnostic, occurrence: usize) -> String {
+    let content = diag
+        .source_line
+        .as_deref()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .unwrap_or_else(|| diag.message.clone());
+    let key = format!(
+        "{}\u{0}{}\u{0}{}",
+        diag.rule_id,
+        diag.location.file.display().to_string().replace('\\', "/"),
+        content
+    );
+    format!("{:016x}:{}", stable_hash(&key), occurrence)
+}
+
+/// Fingerprints for a list of diagnostics, numbering identical ones in order
+pub(crate) fn fingerprints(diagnostics: &[Diagnostic]) -> Vec<String> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    diagnostics
+        .iter()
+        .map(|d| {
+            let count = occurrences.entry(fingerprint(d, 0)).or_insert(0);
+            *count += 1;
+            fingerprint(d, *count)
+        })
+        .collect()
+}
+
+/// A review-comment suggestion block replacing the diagnostic's line
+pub(crate) fn suggestion_block(edit: &FixEdit) -> String {
+    format!("```suggestion\n{}\n```", edit.new_line)
+}
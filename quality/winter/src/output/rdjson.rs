@@ -0,0 +1,261 @@
+//! Reviewdog Diagnostic Format output formatter
+//!
+//! Outputs diagnostics as rdjson (one JSON document) or rdjsonl (one
+//! diagnostic per line) for reviewdog, which posts them on pull and merge
+//! requests. Fixes become `suggestions` with exact ranges, so reviewdog can
+//! offer them as applicable suggested changes.
+//! https://github.com/reviewdog/reviewdog/tree/master/proto/rdf
+
+use super::OutputFormatter;
+use crate::diagnostic::{Diagnostic, Location, Severity};
+use crate::engine::LintResult;
+use crate::fixer::FixEdit;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct RdResult {
+    source: RdSource,
+    diagnostics: Vec<RdDiagnostic>,
+}
+
+#[derive(Serialize)]
+struct RdSource {
+    name: &'static str,
+    url: &'static str,
+}
+
+#[derive(Serialize)]
+struct RdDiagnostic {
+    message: String,
+    location: RdLocation,
+    severity: &'static str,
+    source: RdSource,
+    code: RdCode,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<RdSuggestion>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<RdRelatedLocation>,
+}
+
+#[derive(Serialize)]
+struct RdCode {
+    value: String,
+}
+
+#[derive(Serialize)]
+struct RdLocation {
+    path: String,
+    range: RdRange,
+}
+
+#[derive(Serialize)]
+struct RdRange {
+    start: RdPosition,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<RdPosition>,
+}
+
+#[derive(Serialize)]
+struct RdPosition {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Serialize)]
+struct RdSuggestion {
+    range: RdRange,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct RdRelatedLocation {
+    message: String,
+    location: RdLocation,
+}
+
+const SOURCE: RdSource = RdSource {
+    name: "winter",
+    url: "https://github.com/Tsahi-Elkayam/wixcraft",
+};
+
+/// Formatter for reviewdog's rdjson / rdjsonl formats
+pub struct RdjsonFormatter {
+    /// Emit one diagnostic per line (rdjsonl) instead of one document
+    pub lines: bool,
+}
+
+impl RdjsonFormatter {
+    /// Create an rdjson formatter
+    pub fn new() -> Self {
+        Self { lines: false }
+    }
+
+    /// Emit rdjsonl (one diagnostic per line)
+    pub fn lines(mut self) -> Self {
+        self.lines = true;
+        self
+    }
+
+    fn severity(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARNING",
+            Severity::Info => "INFO",
+        }
+    }
+
+    fn location(location: &Location) -> RdLocation {
+        let start = RdPosition {
+            line: location.line.max(1),
+            column: location.column.max(1),
+        };
+        let end = (location.length > 0).then(|| RdPosition {
+            line: start.line,
+            column: start.column + location.length,
+        });
+
+        RdLocation {
+            path: location.file.display().to_string().replace('\\', "/"),
+            range: RdRange { start, end },
+        }
+    }
+
+    /// The fix as a suggestion; rdf columns are 1-based UTF-8 byte offsets
+    fn suggestions(diag: &Diagnostic) -> Vec<RdSuggestion> {
+        let Some(edit) = FixEdit::from_diagnostic(diag) else {
+            return Vec::new();
+        };
+
+        vec![RdSuggestion {
+            range: RdRange {
+                start: RdPosition {
+                    line: edit.line,
+                    column: edit.byte_column(edit.start_column),
+                },
+                end: Some(RdPosition {
+                    line: edit.line,
+                    column: edit.byte_column(edit.end_column),
+                }),
+            },
+            text: edit.new_text,
+        }]
+    }
+
+    fn diagnostic(diag: &Diagnostic) -> RdDiagnostic {
+        RdDiagnostic {
+            message: diag.message.clone(),
+            location: Self::location(&diag.location),
+            severity: Self::severity(diag.severity),
+            source: SOURCE,
+            code: RdCode {
+                value: diag.rule_id.clone(),
+            },
+            suggestions: Self::suggestions(diag),
+            related_locations: diag
+                .related
+                .iter()
+                .map(|r| RdRelatedLocation {
+                    message: r.message.clone(),
+                    location: Self::location(&r.location),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for RdjsonFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputFormatter for RdjsonFormatter {
+    fn format(&self, result: &LintResult) -> String {
+        if self.lines {
+            let mut output = String::new();
+            for diag in &result.diagnostics {
+                output.push_str(&self.format_diagnostic(diag));
+                output.push('\n');
+            }
+            return output;
+        }
+
+        let report = RdResult {
+            source: SOURCE,
+            diagnostics: result.diagnostics.iter().map(Self::diagnostic).collect(),
+        };
+        serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    fn format_diagnostic(&self, diagnostic: &Diagnostic) -> String {
+        serde_json::to_string(&Self::diagnostic(diagnostic)).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn diag(source: &str) -> Diagnostic {
+        Diagnostic::new(
+            "component-requires-guid",
+            Severity::Error,
+            "Component is missing Guid",
+            Location::new(PathBuf::from("src/product.wxs"), 4, 5),
+        )
+        .with_source_line(source)
+    }
+
+    #[test]
+    fn test_rdjson_suggestion_range() {
+        let result = LintResult {
+            diagnostics: vec![diag("    <Component Id=\"C\">")
+                .with_fix("Add Guid", "    <Component Id=\"C\" Guid=\"*\">")],
+            ..Default::default()
+        };
+        let output: serde_json::Value =
+            serde_json::from_str(&RdjsonFormatter::new().format(&result)).unwrap();
+
+        assert_eq!(output["source"]["name"], "winter");
+        let diag = &output["diagnostics"][0];
+        assert_eq!(diag["severity"], "ERROR");
+        assert_eq!(diag["code"]["value"], "component-requires-guid");
+
+        let suggestion = &diag["suggestions"][0];
+        assert_eq!(suggestion["range"]["start"]["line"], 4);
+        assert_eq!(suggestion["range"]["start"]["column"], 22);
+        assert_eq!(suggestion["range"]["end"]["column"], 22);
+        assert_eq!(suggestion["text"], " Guid=\"*\"");
+    }
+
+    #[test]
+    fn test_rdjson_columns_are_bytes() {
+        let d = diag("<Property Value=\"café\" X=\"1\"/>")
+            .with_fix("Drop X", "<Property Value=\"café\"/>");
+        let output: serde_json::Value =
+            serde_json::from_str(&RdjsonFormatter::new().format_diagnostic(&d)).unwrap();
+
+        // 'é' is two bytes, so the range starts one byte later than its char column
+        let range = &output["suggestions"][0]["range"];
+        assert_eq!(range["start"]["column"], 24);
+        assert_eq!(range["end"]["column"], 30);
+    }
+
+    #[test]
+    fn test_rdjsonl_one_diagnostic_per_line() {
+        let result = LintResult {
+            diagnostics: vec![diag("<A/>"), diag("<B/>")],
+            ..Default::default()
+        };
+        let output = RdjsonFormatter::new().lines().format(&result);
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["location"]["path"], "src/product.wxs");
+            assert!(value.get("suggestions").is_none());
+        }
+    }
+}
@@ -2,11 +2,25 @@
 //!
 //! SARIF is a standard format for static analysis tools, supported by
 //! GitHub Actions, Azure DevOps, and other CI/CD systems.
-
-use super::OutputFormatter;
-use crate::diagnostic::{Diagnostic, Severity};
+//!
+//! Besides plain results, the formatter emits:
+//! - `fixes[]` built from the diagnostic's fix, as minimal `artifactChanges`
+//! - `relatedLocations` for cross-file diagnostics (e.g., duplicate definitions)
+//! - rule `help` markdown from rationale and examples, when rules are supplied
+//! - `partialFingerprints` keyed on rule, file and line content, not line number
+//! - `baselineState` when compared against a previous SARIF log
+
+use super::{fingerprint, fingerprints, OutputFormatter};
+use crate::diagnostic::{Diagnostic, Location, Severity};
 use crate::engine::LintResult;
+use crate::fixer::FixEdit;
+use crate::rule::Rule;
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Fingerprint key used in `partialFingerprints`
+const FINGERPRINT_KEY: &str = "primaryLocationLineHash";
 
 /// SARIF formatter for CI/CD integration
 #[derive(Default)]
@@ -16,6 +30,12 @@ pub struct SarifFormatter {
 
     /// Tool version
     pub tool_version: String,
+
+    /// Rule metadata for the driver's rule descriptors (keyed by rule ID)
+    rules: HashMap<String, Rule>,
+
+    /// Results from a previous run, for `baselineState`
+    baseline: Option<Vec<serde_json::Value>>,
 }
 
 impl SarifFormatter {
@@ -24,10 +44,42 @@ impl SarifFormatter {
         Self {
             tool_name: tool_name.to_string(),
             tool_version: tool_version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Provide rule definitions for descriptor metadata (help, tags, docs)
+    pub fn with_rules<'a>(mut self, rules: impl IntoIterator<Item = &'a Rule>) -> Self {
+        for rule in rules {
+            self.rules.insert(rule.id.clone(), rule.clone());
         }
+        self
+    }
+
+    /// Compare results against a previous SARIF log to set `baselineState`
+    pub fn with_baseline(mut self, path: &Path) -> Result<Self, std::io::Error> {
+        let content = std::fs::read_to_string(path)?;
+        let log: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.baseline = Some(baseline_results(&log));
+        Ok(self)
     }
 }
 
+/// Extract the results of every run in a SARIF log
+fn baseline_results(log: &serde_json::Value) -> Vec<serde_json::Value> {
+    log.get("runs")
+        .and_then(|r| r.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|run| run.get("results").and_then(|r| r.as_array()))
+        .flatten()
+        // Results already absent last time are not carried forward again
+        .filter(|r| r.get("baselineState").and_then(|s| s.as_str()) != Some("absent"))
+        .cloned()
+        .collect()
+}
+
 #[derive(Serialize)]
 struct SarifReport {
     #[serde(rename = "$schema")]
@@ -39,7 +91,7 @@ struct SarifReport {
 #[derive(Serialize)]
 struct SarifRun {
     tool: SarifTool,
-    results: Vec<SarifResult>,
+    results: Vec<serde_json::Value>,
 }
 
 #[derive(Serialize)]
@@ -61,8 +113,29 @@ struct SarifRule {
     id: String,
     #[serde(rename = "shortDescription")]
     short_description: SarifMessage,
+    #[serde(rename = "fullDescription", skip_serializing_if = "Option::is_none")]
+    full_description: Option<SarifMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<SarifMultiformatMessage>,
+    #[serde(rename = "helpUri", skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
     #[serde(rename = "defaultConfiguration")]
     default_configuration: SarifConfiguration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<SarifRuleProperties>,
+}
+
+#[derive(Serialize)]
+struct SarifMultiformatMessage {
+    text: String,
+    markdown: String,
+}
+
+#[derive(Serialize)]
+struct SarifRuleProperties {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    category: String,
 }
 
 #[derive(Serialize)]
@@ -77,6 +150,12 @@ struct SarifResult {
     level: &'static str,
     message: SarifMessage,
     locations: Vec<SarifLocation>,
+    #[serde(rename = "relatedLocations", skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<SarifRelatedLocation>,
+    #[serde(rename = "partialFingerprints")]
+    partial_fingerprints: HashMap<&'static str, String>,
+    #[serde(rename = "baselineState", skip_serializing_if = "Option::is_none")]
+    baseline_state: Option<&'static str>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     fixes: Vec<SarifFix>,
 }
@@ -92,6 +171,14 @@ struct SarifLocation {
     physical_location: SarifPhysicalLocation,
 }
 
+#[derive(Serialize)]
+struct SarifRelatedLocation {
+    id: usize,
+    #[serde(rename = "physicalLocation")]
+    physical_location: SarifPhysicalLocation,
+    message: SarifMessage,
+}
+
 #[derive(Serialize)]
 struct SarifPhysicalLocation {
     #[serde(rename = "artifactLocation")]
@@ -110,6 +197,8 @@ struct SarifRegion {
     start_line: usize,
     #[serde(rename = "startColumn")]
     start_column: usize,
+    #[serde(rename = "endLine", skip_serializing_if = "Option::is_none")]
+    end_line: Option<usize>,
     #[serde(rename = "endColumn", skip_serializing_if = "Option::is_none")]
     end_column: Option<usize>,
 }
@@ -149,86 +238,263 @@ fn severity_to_level(severity: Severity) -> &'static str {
     }
 }
 
+fn artifact_uri(path: &Path) -> String {
+    path.display().to_string().replace('\\', "/")
+}
+
+fn physical_location(location: &Location) -> SarifPhysicalLocation {
+    SarifPhysicalLocation {
+        artifact_location: SarifArtifactLocation {
+            uri: artifact_uri(&location.file),
+        },
+        region: SarifRegion {
+            start_line: location.line.max(1),
+            start_column: location.column.max(1),
+            end_line: None,
+            end_column: if location.length > 0 {
+                Some(location.column.max(1) + location.length)
+            } else {
+                None
+            },
+        },
+    }
+}
+
+/// Build `fixes[]` for a diagnostic from its fix
+fn sarif_fixes(diag: &Diagnostic) -> Vec<SarifFix> {
+    let (Some(fix), Some(edit)) = (&diag.fix, FixEdit::from_diagnostic(diag)) else {
+        return Vec::new();
+    };
+
+    vec![SarifFix {
+        description: SarifMessage {
+            text: fix.description.clone(),
+        },
+        artifact_changes: vec![SarifArtifactChange {
+            artifact_location: SarifArtifactLocation {
+                uri: artifact_uri(&edit.file),
+            },
+            replacements: vec![SarifReplacement {
+                deleted_region: SarifRegion {
+                    start_line: edit.line,
+                    start_column: edit.start_column,
+                    end_line: Some(edit.line),
+                    end_column: Some(edit.end_column),
+                },
+                inserted_content: SarifContent {
+                    text: edit.new_text,
+                },
+            }],
+        }],
+    }]
+}
+
+/// Markdown help for a rule: description, rationale and examples
+fn help_markdown(rule: &Rule) -> Option<String> {
+    if rule.rationale.is_none() && rule.example_bad.is_none() && rule.example_good.is_none() {
+        return None;
+    }
+
+    let mut md = String::new();
+    if let Some(desc) = &rule.description {
+        md.push_str(desc);
+        md.push_str("\n\n");
+    }
+    if let Some(rationale) = &rule.rationale {
+        md.push_str(&format!("**Why:** {}\n\n", rationale));
+    }
+    if let Some(bad) = &rule.example_bad {
+        md.push_str(&format!("**Incorrect:**\n\n```xml\n{}\n```\n\n", bad));
+    }
+    if let Some(good) = &rule.example_good {
+        md.push_str(&format!("**Correct:**\n\n```xml\n{}\n```\n\n", good));
+    }
+    if let Some(docs) = &rule.docs {
+        md.push_str(&format!("[Documentation]({})\n", docs));
+    }
+    Some(md.trim_end().to_string())
+}
+
+impl SarifFormatter {
+    fn rule_descriptor(&self, diag: &Diagnostic) -> SarifRule {
+        let Some(rule) = self.rules.get(&diag.rule_id) else {
+            return SarifRule {
+                id: diag.rule_id.clone(),
+                short_description: SarifMessage {
+                    text: diag.help.clone().unwrap_or_else(|| diag.message.clone()),
+                },
+                full_description: None,
+                help: None,
+                help_uri: None,
+                default_configuration: SarifConfiguration {
+                    level: severity_to_level(diag.severity),
+                },
+                properties: None,
+            };
+        };
+
+        let short = rule
+            .name
+            .clone()
+            .or_else(|| rule.description.clone())
+            .unwrap_or_else(|| diag.help.clone().unwrap_or_else(|| diag.message.clone()));
+
+        SarifRule {
+            id: rule.id.clone(),
+            short_description: SarifMessage { text: short },
+            full_description: rule
+                .description
+                .as_ref()
+                .map(|d| SarifMessage { text: d.clone() }),
+            help: help_markdown(rule).map(|markdown| SarifMultiformatMessage {
+                text: rule
+                    .rationale
+                    .clone()
+                    .or_else(|| rule.description.clone())
+                    .unwrap_or_default(),
+                markdown,
+            }),
+            help_uri: rule.docs.clone(),
+            default_configuration: SarifConfiguration {
+                level: severity_to_level(rule.severity),
+            },
+            properties: Some(SarifRuleProperties {
+                tags: rule.tags.clone(),
+                category: rule.category.to_string(),
+            }),
+        }
+    }
+
+    fn build_result(&self, diag: &Diagnostic, fingerprint: String) -> SarifResult {
+        let related_locations = diag
+            .related
+            .iter()
+            .enumerate()
+            .map(|(i, r)| SarifRelatedLocation {
+                id: i + 1,
+                physical_location: physical_location(&r.location),
+                message: SarifMessage {
+                    text: r.message.clone(),
+                },
+            })
+            .collect();
+
+        let mut partial_fingerprints = HashMap::new();
+        partial_fingerprints.insert(FINGERPRINT_KEY, fingerprint);
+
+        SarifResult {
+            rule_id: diag.rule_id.clone(),
+            level: severity_to_level(diag.severity),
+            message: SarifMessage {
+                text: diag.message.clone(),
+            },
+            locations: vec![SarifLocation {
+                physical_location: physical_location(&diag.location),
+            }],
+            related_locations,
+            partial_fingerprints,
+            baseline_state: None,
+            fixes: sarif_fixes(diag),
+        }
+    }
+
+    /// Set `baselineState` on current results and append absent ones
+    fn apply_baseline(
+        &self,
+        results: &mut [SarifResult],
+        previous: &[serde_json::Value],
+    ) -> Vec<serde_json::Value> {
+        let keyed: Vec<((String, String), &serde_json::Value)> = previous
+            .iter()
+            .filter_map(|r| {
+                let rule = r.get("ruleId")?.as_str()?.to_string();
+                let fp = r
+                    .get("partialFingerprints")?
+                    .get(FINGERPRINT_KEY)?
+                    .as_str()?
+                    .to_string();
+                Some(((rule, fp), r))
+            })
+            .collect();
+        let mut prev_by_fingerprint: HashMap<&(String, String), &serde_json::Value> =
+            HashMap::new();
+        for (key, prev) in &keyed {
+            prev_by_fingerprint.entry(key).or_insert(prev);
+        }
+
+        let mut matched = HashSet::new();
+        for result in results.iter_mut() {
+            let key = (
+                result.rule_id.clone(),
+                result.partial_fingerprints[FINGERPRINT_KEY].clone(),
+            );
+            result.baseline_state = Some(match prev_by_fingerprint.get(&key) {
+                Some(prev) => {
+                    matched.insert(key);
+                    let same_message = prev
+                        .get("message")
+                        .and_then(|m| m.get("text"))
+                        .and_then(|t| t.as_str())
+                        == Some(result.message.text.as_str());
+                    let same_level =
+                        prev.get("level").and_then(|l| l.as_str()) == Some(result.level);
+                    if same_message && same_level {
+                        "unchanged"
+                    } else {
+                        "updated"
+                    }
+                }
+                None => "new",
+            });
+        }
+
+        // Absent results keep the previous run's order
+        keyed
+            .iter()
+            .filter(|(key, _)| matched.insert(key.clone()))
+            .map(|(_, prev)| {
+                let mut absent = (*prev).clone();
+                absent["baselineState"] = serde_json::Value::from("absent");
+                if let Some(obj) = absent.as_object_mut() {
+                    obj.remove("fixes");
+                }
+                absent
+            })
+            .collect()
+    }
+}
+
 impl OutputFormatter for SarifFormatter {
     fn format(&self, result: &LintResult) -> String {
         // Collect unique rules
-        let mut rules_map = std::collections::HashMap::new();
+        let mut rules_map = HashMap::new();
         for diag in &result.diagnostics {
             rules_map
                 .entry(diag.rule_id.clone())
-                .or_insert_with(|| SarifRule {
-                    id: diag.rule_id.clone(),
-                    short_description: SarifMessage {
-                        text: diag.help.clone().unwrap_or_else(|| diag.message.clone()),
-                    },
-                    default_configuration: SarifConfiguration {
-                        level: severity_to_level(diag.severity),
-                    },
-                });
+                .or_insert_with(|| self.rule_descriptor(diag));
         }
 
-        let rules: Vec<SarifRule> = rules_map.into_values().collect();
+        let mut rules: Vec<SarifRule> = rules_map.into_values().collect();
+        rules.sort_by(|a, b| a.id.cmp(&b.id));
 
-        let results: Vec<SarifResult> = result
+        let mut results: Vec<SarifResult> = result
             .diagnostics
             .iter()
-            .map(|d| {
-                let fixes = if let Some(fix) = &d.fix {
-                    vec![SarifFix {
-                        description: SarifMessage {
-                            text: fix.description.clone(),
-                        },
-                        artifact_changes: vec![SarifArtifactChange {
-                            artifact_location: SarifArtifactLocation {
-                                uri: d.location.file.display().to_string(),
-                            },
-                            replacements: vec![SarifReplacement {
-                                deleted_region: SarifRegion {
-                                    start_line: d.location.line,
-                                    start_column: d.location.column,
-                                    end_column: if d.location.length > 0 {
-                                        Some(d.location.column + d.location.length)
-                                    } else {
-                                        None
-                                    },
-                                },
-                                inserted_content: SarifContent {
-                                    text: fix.replacement.clone(),
-                                },
-                            }],
-                        }],
-                    }]
-                } else {
-                    vec![]
-                };
-
-                SarifResult {
-                    rule_id: d.rule_id.clone(),
-                    level: severity_to_level(d.severity),
-                    message: SarifMessage {
-                        text: d.message.clone(),
-                    },
-                    locations: vec![SarifLocation {
-                        physical_location: SarifPhysicalLocation {
-                            artifact_location: SarifArtifactLocation {
-                                uri: d.location.file.display().to_string(),
-                            },
-                            region: SarifRegion {
-                                start_line: d.location.line,
-                                start_column: d.location.column,
-                                end_column: if d.location.length > 0 {
-                                    Some(d.location.column + d.location.length)
-                                } else {
-                                    None
-                                },
-                            },
-                        },
-                    }],
-                    fixes,
-                }
-            })
+            .zip(fingerprints(&result.diagnostics))
+            .map(|(d, fp)| self.build_result(d, fp))
             .collect();
 
+        let absent = match &self.baseline {
+            Some(previous) => self.apply_baseline(&mut results, previous),
+            None => Vec::new(),
+        };
+
+        let mut results: Vec<serde_json::Value> = results
+            .iter()
+            .filter_map(|r| serde_json::to_value(r).ok())
+            .collect();
+        results.extend(absent);
+
         let report = SarifReport {
             schema: "https://raw.githubusercontent.com/oasis-tcs/sarif-spec/master/Schemata/sarif-schema-2.1.0.json",
             version: "2.1.0",
@@ -249,27 +515,7 @@ impl OutputFormatter for SarifFormatter {
     }
 
     fn format_diagnostic(&self, diagnostic: &Diagnostic) -> String {
-        let result = SarifResult {
-            rule_id: diagnostic.rule_id.clone(),
-            level: severity_to_level(diagnostic.severity),
-            message: SarifMessage {
-                text: diagnostic.message.clone(),
-            },
-            locations: vec![SarifLocation {
-                physical_location: SarifPhysicalLocation {
-                    artifact_location: SarifArtifactLocation {
-                        uri: diagnostic.location.file.display().to_string(),
-                    },
-                    region: SarifRegion {
-                        start_line: diagnostic.location.line,
-                        start_column: diagnostic.location.column,
-                        end_column: None,
-                    },
-                },
-            }],
-            fixes: vec![],
-        };
-
+        let result = self.build_result(diagnostic, fingerprint(diagnostic, 1));
         serde_json::to_string_pretty(&result).unwrap_or_default()
     }
 }
@@ -280,6 +526,28 @@ mod tests {
     use crate::diagnostic::Location;
     use std::path::PathBuf;
 
+    fn diag_at(line: usize, source: &str) -> Diagnostic {
+        Diagnostic::new(
+            "component-requires-guid",
+            Severity::Error,
+            "Component is missing Guid",
+            Location::new(PathBuf::from("src/product.wxs"), line, 5),
+        )
+        .with_source_line(source)
+    }
+
+    fn lint_result(diagnostics: Vec<Diagnostic>) -> LintResult {
+        LintResult {
+            diagnostics,
+            files_processed: 1,
+            ..Default::default()
+        }
+    }
+
+    fn parse(output: &str) -> serde_json::Value {
+        serde_json::from_str(output).unwrap()
+    }
+
     #[test]
     fn test_sarif_format() {
         let formatter = SarifFormatter::new("winter", "0.1.0");
@@ -308,4 +576,142 @@ mod tests {
         assert_eq!(severity_to_level(Severity::Warning), "warning");
         assert_eq!(severity_to_level(Severity::Info), "note");
     }
+
+    #[test]
+    fn test_sarif_fix_artifact_changes() {
+        let diag = diag_at(3, r#"    <Component Id="C1">"#)
+            .with_fix("Add Guid", r#"    <Component Id="C1" Guid="*">"#);
+        let output = SarifFormatter::new("winter", "0.1.0").format(&lint_result(vec![diag]));
+        let sarif = parse(&output);
+
+        let replacement =
+            &sarif["runs"][0]["results"][0]["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["startLine"], 3);
+        assert_eq!(replacement["deletedRegion"]["startColumn"], 23);
+        assert_eq!(replacement["deletedRegion"]["endColumn"], 23);
+        assert_eq!(replacement["insertedContent"]["text"], r#" Guid="*""#);
+    }
+
+    #[test]
+    fn test_sarif_related_locations() {
+        let diag = diag_at(20, "<Feature Id=\"Main\">").with_related(
+            Location::new(PathBuf::from("other.wxs"), 4, 3),
+            "'Main' first defined here",
+        );
+        let output = SarifFormatter::new("winter", "0.1.0").format(&lint_result(vec![diag]));
+        let related = &parse(&output)["runs"][0]["results"][0]["relatedLocations"][0];
+
+        assert_eq!(
+            related["physicalLocation"]["artifactLocation"]["uri"],
+            "other.wxs"
+        );
+        assert_eq!(related["physicalLocation"]["region"]["startLine"], 4);
+        assert_eq!(related["message"]["text"], "'Main' first defined here");
+    }
+
+    #[test]
+    fn test_sarif_rule_help_from_rationale() {
+        let rule = Rule::new("component-requires-guid", "!attributes.Guid", "msg")
+            .with_description("Components need a GUID")
+            .with_rationale("Windows Installer tracks components by GUID")
+            .with_example_bad("<Component Id=\"C\"/>")
+            .with_example_good("<Component Id=\"C\" Guid=\"*\"/>")
+            .with_tag("required");
+        let formatter = SarifFormatter::new("winter", "0.1.0").with_rules([&rule]);
+        let output = formatter.format(&lint_result(vec![diag_at(1, "<Component Id=\"C\"/>")]));
+        let descriptor = &parse(&output)["runs"][0]["tool"]["driver"]["rules"][0];
+
+        let markdown = descriptor["help"]["markdown"].as_str().unwrap();
+        assert!(markdown.contains("**Why:** Windows Installer tracks components by GUID"));
+        assert!(markdown.contains("```xml\n<Component Id=\"C\" Guid=\"*\"/>\n```"));
+        assert_eq!(descriptor["properties"]["tags"][0], "required");
+    }
+
+    #[test]
+    fn test_fingerprint_stable_across_line_shifts() {
+        let before = fingerprint(&diag_at(10, "  <Component Id=\"C1\">"), 1);
+        let after = fingerprint(&diag_at(42, "    <Component   Id=\"C1\">"), 1);
+        let other = fingerprint(&diag_at(10, "  <Component Id=\"C2\">"), 1);
+
+        assert_eq!(before, after);
+        assert_ne!(before, other);
+    }
+
+    #[test]
+    fn test_fingerprint_occurrences_distinguish_identical_lines() {
+        let output = SarifFormatter::new("winter", "0.1.0").format(&lint_result(vec![
+            diag_at(1, "<Component/>"),
+            diag_at(2, "<Component/>"),
+        ]));
+        let sarif = parse(&output);
+        let first = &sarif["runs"][0]["results"][0]["partialFingerprints"][FINGERPRINT_KEY];
+        let second = &sarif["runs"][0]["results"][1]["partialFingerprints"][FINGERPRINT_KEY];
+
+        assert_ne!(first, second);
+        assert!(first.as_str().unwrap().ends_with(":1"));
+        assert!(second.as_str().unwrap().ends_with(":2"));
+    }
+
+    #[test]
+    fn test_sarif_baseline_state() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let baseline_path = temp.path().join("previous.sarif");
+
+        let previous = SarifFormatter::new("winter", "0.1.0").format(&lint_result(vec![
+            diag_at(5, "<Component Id=\"Kept\">"),
+            diag_at(9, "<Component Id=\"Fixed\">"),
+        ]));
+        std::fs::write(&baseline_path, previous).unwrap();
+
+        let formatter = SarifFormatter::new("winter", "0.1.0")
+            .with_baseline(&baseline_path)
+            .unwrap();
+        let output = formatter.format(&lint_result(vec![
+            // Moved down by two lines, same content
+            diag_at(7, "<Component Id=\"Kept\">"),
+            diag_at(12, "<Component Id=\"Added\">"),
+        ]));
+        let results = parse(&output)["runs"][0]["results"].clone();
+        let results = results.as_array().unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["baselineState"], "unchanged");
+        assert_eq!(results[1]["baselineState"], "new");
+        assert_eq!(results[2]["baselineState"], "absent");
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            9
+        );
+    }
+
+    #[test]
+    fn test_sarif_absent_results_keep_previous_order() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let baseline_path = temp.path().join("previous.sarif");
+
+        let fixed: Vec<Diagnostic> = (1..=8)
+            .map(|i| diag_at(i * 10, &format!("<Component Id=\"Fixed{i}\">")))
+            .collect();
+        let previous = SarifFormatter::new("winter", "0.1.0").format(&lint_result(fixed));
+        std::fs::write(&baseline_path, previous).unwrap();
+
+        let formatter = SarifFormatter::new("winter", "0.1.0")
+            .with_baseline(&baseline_path)
+            .unwrap();
+        let output = formatter.format(&lint_result(Vec::new()));
+        let results = parse(&output)["runs"][0]["results"].clone();
+        let lines: Vec<u64> = results
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                assert_eq!(r["baselineState"], "absent");
+                r["locations"][0]["physicalLocation"]["region"]["startLine"]
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect();
+
+        assert_eq!(lines, [10, 20, 30, 40, 50, 60, 70, 80]);
+    }
 }
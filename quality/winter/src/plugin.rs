@@ -1,6 +1,7 @@
 //! Plugin system for format-specific parsing and rule evaluation
 
 use crate::diagnostic::Location;
+use crate::embedded::EmbeddedBlock;
 use crate::rule::Rule;
 use std::collections::HashMap;
 use std::path::Path;
@@ -93,6 +94,11 @@ pub trait Plugin: Send + Sync {
 
     /// Load additional rules from a directory
     fn load_rules(&mut self, dir: &Path) -> Result<usize, RuleLoadError>;
+
+    /// Extract embedded code blocks (scripts, command lines) for rules with a `context`
+    fn extract_embedded(&self, _content: &str, _path: &Path) -> Vec<EmbeddedBlock> {
+        Vec::new()
+    }
 }
 
 /// Error loading rules
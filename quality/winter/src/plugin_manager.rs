@@ -23,6 +23,7 @@
 //! ```
 
 use crate::diagnostic::Severity;
+use crate::embedded::{extract_with_patterns, EmbeddedBlock};
 use crate::plugin::{Document, ParseError, Plugin, RuleLoadError};
 use crate::plugins::xml::XmlDocument;
 use crate::rule::Rule;
@@ -356,6 +357,10 @@ impl Plugin for DynamicPlugin {
 
         Ok(count)
     }
+
+    fn extract_embedded(&self, content: &str, _path: &Path) -> Vec<EmbeddedBlock> {
+        extract_with_patterns(content, &self.embedded_languages)
+    }
 }
 
 /// Plugin Manager for loading and managing plugins
@@ -958,7 +963,6 @@ rules: []
     fn test_engine_with_dynamic_plugin() {
         use crate::config::Config;
         use crate::engine::Engine;
-        use crate::plugin::Document;
 
         let temp = TempDir::new().unwrap();
 
@@ -1017,7 +1021,7 @@ rules:
         }
 
         // Lint
-        let result = engine.lint(&[test_file.clone()]);
+        let result = engine.lint(std::slice::from_ref(&test_file));
 
         println!("Files processed: {}", result.files_processed);
         println!("Diagnostics: {}", result.diagnostics.len());
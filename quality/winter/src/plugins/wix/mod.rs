@@ -3,10 +3,13 @@
 mod document;
 mod parser;
 mod rules;
+mod scripts;
 
 pub use document::WixDocument;
 pub use parser::WixNode;
+pub use scripts::{extract_scripts, script_rules};
 
+use crate::embedded::EmbeddedBlock;
 use crate::plugin::{Document, ParseError, Plugin, RuleLoadError};
 use crate::rule::{Rule, RuleFile};
 use std::path::Path;
@@ -39,9 +42,10 @@ impl WixPlugin {
     /// Falls back to built-in rules if database is unavailable.
     pub fn new() -> Self {
         // Try to load rules from database
-        let (rules, from_db) = Self::load_rules_from_db()
+        let (mut rules, from_db) = Self::load_rules_from_db()
             .map(|r| (r, true))
             .unwrap_or_else(|| (rules::builtin_rules(), false));
+        rules.extend(scripts::script_rules());
 
         Self {
             version: env!("CARGO_PKG_VERSION").to_string(),
@@ -53,9 +57,12 @@ impl WixPlugin {
 
     /// Create a plugin with only built-in rules (no database)
     pub fn with_builtin_rules() -> Self {
+        let mut rules = rules::builtin_rules();
+        rules.extend(scripts::script_rules());
+
         Self {
             version: env!("CARGO_PKG_VERSION").to_string(),
-            rules: rules::builtin_rules(),
+            rules,
             wix_version: 4,
             rules_from_db: false,
         }
@@ -214,6 +221,10 @@ impl Plugin for WixPlugin {
 
         Ok(count)
     }
+
+    fn extract_embedded(&self, content: &str, path: &Path) -> Vec<EmbeddedBlock> {
+        scripts::extract_scripts(content, path)
+    }
 }
 
 #[cfg(test)]
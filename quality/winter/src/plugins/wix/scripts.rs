@@ -0,0 +1,535 @@
+//! Script extraction and script rules for WiX documents
+//!
+//! Finds code that Windows Installer will run on behalf of the package:
+//! - inline `CustomAction Script="vbscript|jscript"` bodies (text or CDATA)
+//! - `CustomAction ScriptSourceFile=` and `Binary SourceFile=` with a
+//!   `.vbs`, `.js` or `.ps1` extension
+//! - command lines handed to the util extension's quiet-exec actions,
+//!   via `SetProperty`, type-51 `CustomAction Property=/Value=` or the
+//!   immediate-mode `QtExecCmdLine` style properties
+
+use crate::diagnostic::Severity;
+use crate::embedded::{EmbeddedBlock, LineIndex};
+use crate::rule::{Rule, RuleCategory};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Language context for VBScript bodies
+pub const VBSCRIPT: &str = "vbscript";
+/// Language context for JScript bodies
+pub const JSCRIPT: &str = "jscript";
+/// Language context for PowerShell scripts
+pub const POWERSHELL: &str = "powershell";
+/// Language context for quiet-exec command lines
+pub const CMDLINE: &str = "cmdline";
+
+/// Properties read by immediate-mode quiet-exec actions
+const QUIET_EXEC_PROPERTIES: &[&str] = &[
+    "QtExecCmdLine",
+    "QtExec64CmdLine",
+    "WixQuietExecCmdLine",
+    "WixQuietExec64CmdLine",
+    "WixSilentExecCmdLine",
+    "WixSilentExec64CmdLine",
+];
+
+/// An element seen while scanning, with its raw source span
+struct ElementSpan {
+    name: String,
+    attrs: HashMap<String, String>,
+    start: usize,
+    end: usize,
+}
+
+impl ElementSpan {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|s| s.as_str())
+    }
+
+    fn label(&self) -> String {
+        match self.get("Id") {
+            Some(id) => format!("{} '{}'", self.name, id),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Map a script file extension to its language context
+fn language_for_extension(path: &str) -> Option<&'static str> {
+    let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "vbs" => Some(VBSCRIPT),
+        "js" => Some(JSCRIPT),
+        "ps1" => Some(POWERSHELL),
+        _ => None,
+    }
+}
+
+/// Map a `CustomAction Script=` value to its language context
+fn language_for_script_attr(value: &str) -> Option<&'static str> {
+    match value.to_ascii_lowercase().as_str() {
+        "vbscript" => Some(VBSCRIPT),
+        "jscript" => Some(JSCRIPT),
+        _ => None,
+    }
+}
+
+/// Strip a namespace prefix (e.g., "util:")
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Extract all scripts and command lines from WiX source
+pub fn extract_scripts(content: &str, path: &Path) -> Vec<EmbeddedBlock> {
+    let index = LineIndex::new(content);
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(false);
+
+    let mut elements: Vec<ElementSpan> = Vec::new();
+    let mut blocks = Vec::new();
+    // Open script custom action: (language, label)
+    let mut open_script: Option<(&'static str, String)> = None;
+    let mut depth_in_script = 0usize;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            // Malformed XML is reported by the parser; lint what we have
+            Err(_) => break,
+        };
+        let end = reader.buffer_position() as usize;
+
+        match event {
+            Event::Start(ref e) | Event::Empty(ref e) => {
+                let is_empty = matches!(event, Event::Empty(_));
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                let attrs: HashMap<String, String> = e
+                    .attributes()
+                    .flatten()
+                    .map(|a| {
+                        let key = String::from_utf8_lossy(a.key.as_ref()).to_string();
+                        let value = a
+                            .unescape_value()
+                            .map(|v| v.to_string())
+                            .unwrap_or_else(|_| String::from_utf8_lossy(&a.value).to_string());
+                        (key, value)
+                    })
+                    .collect();
+                let span = ElementSpan {
+                    name,
+                    attrs,
+                    start,
+                    end,
+                };
+
+                if open_script.is_some() {
+                    if !is_empty {
+                        depth_in_script += 1;
+                    }
+                } else if local_name(&span.name) == "CustomAction" && !is_empty {
+                    if let Some(lang) = span.get("Script").and_then(language_for_script_attr) {
+                        open_script = Some((lang, span.label()));
+                        depth_in_script = 0;
+                    }
+                }
+
+                elements.push(span);
+            }
+
+            Event::End(_) if open_script.is_some() => {
+                if depth_in_script == 0 {
+                    open_script = None;
+                } else {
+                    depth_in_script -= 1;
+                }
+            }
+
+            Event::Text(ref e) => {
+                if let Some((lang, label)) = &open_script {
+                    let text = e
+                        .unescape()
+                        .map(|t| t.to_string())
+                        .unwrap_or_else(|_| String::from_utf8_lossy(e.as_ref()).to_string());
+                    if !text.trim().is_empty() {
+                        let (line, column) = index.position(start);
+                        blocks.push(
+                            EmbeddedBlock::inline(lang, &text, line, column).with_origin(label),
+                        );
+                    }
+                }
+            }
+
+            Event::CData(ref e) => {
+                if let Some((lang, label)) = &open_script {
+                    let text = String::from_utf8_lossy(e.as_ref()).to_string();
+                    if !text.trim().is_empty() {
+                        // Content starts after "<![CDATA["
+                        let (line, column) = index.position(start + "<![CDATA[".len());
+                        blocks.push(
+                            EmbeddedBlock::inline(lang, &text, line, column).with_origin(label),
+                        );
+                    }
+                }
+            }
+
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    blocks.extend(external_scripts(&elements, content, &index, base_dir));
+    blocks.extend(quiet_exec_command_lines(&elements, content, &index));
+    blocks
+}
+
+/// Scripts referenced through `Binary SourceFile=` or `CustomAction ScriptSourceFile=`
+fn external_scripts(
+    elements: &[ElementSpan],
+    content: &str,
+    index: &LineIndex,
+    base_dir: &Path,
+) -> Vec<EmbeddedBlock> {
+    let mut blocks = Vec::new();
+
+    for element in elements {
+        let attr = match local_name(&element.name) {
+            "Binary" => ["SourceFile", "Src"]
+                .into_iter()
+                .find(|a| element.get(a).is_some()),
+            "CustomAction" => Some("ScriptSourceFile").filter(|a| element.get(a).is_some()),
+            _ => None,
+        };
+        let Some(attr) = attr else { continue };
+        let source = element.get(attr).unwrap_or_default();
+
+        let Some(lang) = language_for_extension(source) else {
+            continue;
+        };
+
+        // Preprocessor and bind variables can't be resolved here
+        if source.contains("$(") || source.contains("!(") {
+            continue;
+        }
+
+        let script_path = base_dir.join(source.replace('\\', "/"));
+        let Ok(script) = std::fs::read_to_string(&script_path) else {
+            continue;
+        };
+
+        let (line, _) = index.position(attribute_offset(content, element, attr));
+        blocks.push(
+            EmbeddedBlock::external(lang, &script, script_path, line).with_origin(&element.label()),
+        );
+    }
+
+    blocks
+}
+
+/// Command lines for quiet-exec custom actions
+fn quiet_exec_command_lines(
+    elements: &[ElementSpan],
+    content: &str,
+    index: &LineIndex,
+) -> Vec<EmbeddedBlock> {
+    // Deferred quiet-exec actions read their command line from a property
+    // named after the action itself
+    let quiet_exec_ids: HashSet<&str> = elements
+        .iter()
+        .filter(|e| local_name(&e.name) == "CustomAction")
+        .filter(|e| {
+            e.get("DllEntry")
+                .is_some_and(|d| d.contains("QuietExec") || d.contains("SilentExec"))
+        })
+        .filter_map(|e| e.get("Id"))
+        .collect();
+
+    let mut blocks = Vec::new();
+
+    for element in elements {
+        let target = match local_name(&element.name) {
+            "SetProperty" | "Property" => element.get("Id"),
+            "CustomAction" => element.get("Property"),
+            _ => None,
+        };
+        let Some(target) = target else { continue };
+
+        if !quiet_exec_ids.contains(target) && !QUIET_EXEC_PROPERTIES.contains(&target) {
+            continue;
+        }
+
+        let Some(value) = element.get("Value") else {
+            continue;
+        };
+        if value.trim().is_empty() {
+            continue;
+        }
+
+        let (line, column) = index.position(attribute_value_offset(content, element, "Value"));
+        blocks.push(
+            EmbeddedBlock::inline(CMDLINE, value, line, column).with_origin(&element.label()),
+        );
+    }
+
+    blocks
+}
+
+/// Byte offset of `attr=` inside an element's raw source
+fn attribute_offset(content: &str, element: &ElementSpan, attr: &str) -> usize {
+    let raw = &content[element.start..element.end.min(content.len())];
+    let needle = format!("{}=", attr);
+    raw.match_indices(&needle)
+        .find(|(i, _)| *i == 0 || raw.as_bytes()[i - 1].is_ascii_whitespace())
+        .map(|(i, _)| element.start + i)
+        .unwrap_or(element.start)
+}
+
+/// Byte offset of the first character of an attribute's value
+fn attribute_value_offset(content: &str, element: &ElementSpan, attr: &str) -> usize {
+    let offset = attribute_offset(content, element, attr);
+    content[offset..]
+        .find(['"', '\''])
+        .map(|i| offset + i + 1)
+        .unwrap_or(offset)
+}
+
+/// Rules evaluated against extracted scripts and command lines
+pub fn script_rules() -> Vec<Rule> {
+    vec![
+        Rule::new(
+            "script-hardcoded-path",
+            r"content =~ /(?i)(^|[^\w%])[a-z]:\\{1,2}(program files|windows|users|programdata|temp)/",
+            "{name} in {attributes.origin} uses a hardcoded path - use a property such as [ProgramFilesFolder] or an environment variable",
+        )
+        .with_severity(Severity::Warning)
+        .with_category(RuleCategory::Suspicious)
+        .with_target(Some("line"), None)
+        .with_context(&[VBSCRIPT, JSCRIPT, POWERSHELL, CMDLINE])
+        .with_description("Scripts should not assume the system drive or folder layout")
+        .with_rationale("Program Files, Windows and user folders are redirected or relocated on many machines; hardcoded paths break on those installs and on non-English systems")
+        .with_example_bad(r#"Set-Location "C:\Program Files\Contoso""#)
+        .with_example_good(r#"Set-Location $env:ProgramFiles\Contoso"#)
+        .with_tag("script")
+        .with_tag("best-practice"),
+
+        Rule::new(
+            "script-set-execution-policy",
+            r"content =~ /(?i)Set-ExecutionPolicy|-ExecutionPolicy\s+(Bypass|Unrestricted)/",
+            "{name} in {attributes.origin} changes the PowerShell execution policy",
+        )
+        .with_severity(Severity::Warning)
+        .with_category(RuleCategory::Suspicious)
+        .with_target(Some("line"), None)
+        .with_context(&[POWERSHELL, CMDLINE])
+        .with_description("Installers should not weaken the machine's PowerShell execution policy")
+        .with_rationale("Set-ExecutionPolicy changes machine or user policy beyond the install, and Bypass/Unrestricted is flagged by endpoint protection; sign the script instead")
+        .with_example_bad("powershell.exe -ExecutionPolicy Bypass -File setup.ps1")
+        .with_example_good("powershell.exe -NoProfile -File setup.ps1")
+        .with_tag("script")
+        .with_tag("security"),
+
+        Rule::new(
+            "script-shell-run-unquoted",
+            r#"content =~ /(?i)\.(Run|Exec)\s*\(?\s*"([a-z]:|%\w+%)\\[^"]* [^"]*"/"#,
+            "{name} in {attributes.origin} runs a path containing spaces without inner quotes",
+        )
+        .with_severity(Severity::Warning)
+        .with_category(RuleCategory::Correctness)
+        .with_target(Some("line"), None)
+        .with_context(&[VBSCRIPT, JSCRIPT])
+        .with_description("WScript.Shell Run/Exec arguments with spaces must be quoted inside the string")
+        .with_rationale("Run and Exec split the command on spaces, so an unquoted \"C:\\Program Files\\...\" executes C:\\Program instead")
+        .with_example_bad(r#"shell.Run "C:\Program Files\Contoso\tool.exe", 0, True"#)
+        .with_example_good(r#"shell.Run """C:\Program Files\Contoso\tool.exe""", 0, True"#)
+        .with_tag("script"),
+
+        Rule::new(
+            "script-cmdline-unquoted-path",
+            r"content =~ /(?i)^\s*(\[[^\]]+\][^\x22]*?|[a-z]:\\[^\x22]*\s[^\x22]*?)\.(exe|cmd|bat|com)\b/",
+            "Command line in {attributes.origin} has an unquoted executable path",
+        )
+        .with_severity(Severity::Warning)
+        .with_category(RuleCategory::Correctness)
+        .with_target(Some("script"), None)
+        .with_context(&[CMDLINE])
+        .with_description("Quiet-exec command lines must quote the executable path")
+        .with_rationale("[INSTALLDIR] usually resolves under Program Files; without quotes the command line is split at the first space")
+        .with_example_bad(r#"Value="[INSTALLDIR]bin\tool.exe /install""#)
+        .with_example_good(r#"Value="&quot;[INSTALLDIR]bin\tool.exe&quot; /install""#)
+        .with_tag("script"),
+
+        Rule::new(
+            "script-vbscript-missing-error-handling",
+            r"!(content =~ /(?i)\bOn\s+Error\b/)",
+            "{name} in {attributes.origin} has no error handling",
+        )
+        .with_severity(Severity::Info)
+        .with_category(RuleCategory::Pedantic)
+        .with_target(Some("script"), None)
+        .with_context(&[VBSCRIPT])
+        .with_description("VBScript custom actions should handle errors and return a result")
+        .with_rationale("An unhandled runtime error fails the custom action with error 1720 and no context in the log")
+        .with_tag("script"),
+
+        Rule::new(
+            "script-jscript-missing-error-handling",
+            r"!(content =~ /\btry\s*\{/)",
+            "{name} in {attributes.origin} has no error handling",
+        )
+        .with_severity(Severity::Info)
+        .with_category(RuleCategory::Pedantic)
+        .with_target(Some("script"), None)
+        .with_context(&[JSCRIPT])
+        .with_description("JScript custom actions should wrap their work in try/catch")
+        .with_rationale("An uncaught exception fails the custom action with error 1720 and no context in the log")
+        .with_tag("script"),
+
+        Rule::new(
+            "script-powershell-missing-error-handling",
+            r"!(content =~ /(?i)\$ErrorActionPreference|\btry\s*\{|\btrap\b/)",
+            "{name} in {attributes.origin} has no error handling",
+        )
+        .with_severity(Severity::Info)
+        .with_category(RuleCategory::Pedantic)
+        .with_target(Some("script"), None)
+        .with_context(&[POWERSHELL])
+        .with_description("PowerShell scripts run by the installer should stop on errors")
+        .with_rationale("Non-terminating errors leave the script exiting 0, so the installer reports success after a failed step")
+        .with_example_good("$ErrorActionPreference = 'Stop'")
+        .with_tag("script"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugin::Node;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_extract_inline_cdata_script() {
+        let content = r#"<Wix>
+  <CustomAction Id="CheckIt" Script="vbscript">
+    <![CDATA[
+      Set shell = CreateObject("WScript.Shell")
+      shell.Run "C:\Program Files\x.exe"
+    ]]>
+  </CustomAction>
+</Wix>"#;
+        let blocks = extract_scripts(content, Path::new("test.wxs"));
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, VBSCRIPT);
+        assert_eq!(blocks[0].origin, "CustomAction 'CheckIt'");
+        assert_eq!(blocks[0].line, 3);
+
+        // The Run call is on the 3rd line of the CDATA body
+        let nodes = blocks[0].nodes(Path::new("test.wxs"));
+        let run = nodes
+            .iter()
+            .find(|n| n.kind() == "line" && n.text().unwrap().contains(".Run"))
+            .unwrap();
+        assert_eq!(run.location().line, 5);
+    }
+
+    #[test]
+    fn test_extract_inline_text_script() {
+        let content =
+            "<Wix>\n<CustomAction Id=\"J\" Script=\"jscript\">var x = 1;</CustomAction>\n</Wix>";
+        let blocks = extract_scripts(content, Path::new("test.wxs"));
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, JSCRIPT);
+        assert_eq!(blocks[0].content, "var x = 1;");
+        assert_eq!(blocks[0].line, 2);
+    }
+
+    #[test]
+    fn test_extract_binary_script() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("setup.ps1"),
+            "Set-ExecutionPolicy Bypass\n",
+        )
+        .unwrap();
+        let wxs = temp.path().join("product.wxs");
+        let content = "<Wix>\n\n  <Binary Id=\"Ps\" SourceFile=\"setup.ps1\" />\n</Wix>";
+
+        let blocks = extract_scripts(content, &wxs);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].language, POWERSHELL);
+        assert_eq!(blocks[0].line, 3);
+        assert!(blocks[0].external.is_some());
+    }
+
+    #[test]
+    fn test_extract_quiet_exec_command_lines() {
+        let content = r#"<Wix xmlns:util="http://wixtoolset.org/schemas/v4/wxs/util">
+  <SetProperty Id="RunTool" Before="RunTool" Sequence="execute"
+               Value="[INSTALLDIR]tool.exe /quiet" />
+  <CustomAction Id="RunTool" BinaryRef="Wix4UtilCA_X86" DllEntry="WixQuietExec" Execute="deferred" />
+  <Property Id="QtExecCmdLine" Value="cmd.exe /c echo" />
+  <SetProperty Id="Unrelated" Value="C:\x y\z.exe" Before="InstallFiles" />
+</Wix>"#;
+        let blocks = extract_scripts(content, Path::new("test.wxs"));
+
+        assert_eq!(blocks.len(), 2);
+        assert!(blocks.iter().all(|b| b.language == CMDLINE));
+        assert_eq!(blocks[0].content, "[INSTALLDIR]tool.exe /quiet");
+        assert_eq!(blocks[0].line, 3);
+        assert_eq!(blocks[1].content, "cmd.exe /c echo");
+    }
+
+    #[test]
+    fn test_ignores_non_script_custom_actions() {
+        let content = r#"<Wix><CustomAction Id="A" DllEntry="Foo" BinaryRef="B" /></Wix>"#;
+        assert!(extract_scripts(content, Path::new("test.wxs")).is_empty());
+    }
+
+    fn cmdline_rule_matches(command: &str) -> bool {
+        let rule = script_rules()
+            .into_iter()
+            .find(|r| r.id == "script-cmdline-unquoted-path")
+            .unwrap();
+        let pattern = rule
+            .condition
+            .trim_start_matches("content =~ ")
+            .trim_matches('/');
+        regex::Regex::new(pattern).unwrap().is_match(command)
+    }
+
+    #[test]
+    fn test_cmdline_unquoted_path_matches() {
+        assert!(cmdline_rule_matches(r"[INSTALLDIR]bin\tool.exe /install"));
+        assert!(cmdline_rule_matches(
+            r"[ProgramFilesFolder]Contoso\setup.cmd"
+        ));
+        assert!(cmdline_rule_matches(
+            r"C:\Program Files\Contoso\tool.exe /x"
+        ));
+    }
+
+    #[test]
+    fn test_cmdline_quoted_path_not_matched() {
+        assert!(!cmdline_rule_matches(
+            r#""[INSTALLDIR]bin\tool.exe" /install"#
+        ));
+        assert!(!cmdline_rule_matches(
+            r#""C:\Program Files\Contoso\tool.exe" /x"#
+        ));
+        assert!(!cmdline_rule_matches(r"C:\Tools\tool.exe /x"));
+        assert!(!cmdline_rule_matches("cmd.exe /c echo"));
+    }
+
+    #[test]
+    fn test_script_rules_have_contexts() {
+        for rule in script_rules() {
+            assert!(!rule.context.is_empty(), "{} has no context", rule.id);
+            assert!(!rule.applies_to_context("main"));
+        }
+    }
+}
@@ -246,7 +246,7 @@ mod tests {
         let watched_file = temp.path().join("test.wxs");
         fs::write(&watched_file, "<Wix/>").unwrap();
 
-        let watcher = Watcher::new(&[watched_file.clone()], &["wxs"]).unwrap();
+        let watcher = Watcher::new(std::slice::from_ref(&watched_file), &["wxs"]).unwrap();
 
         assert!(watcher.matches_watched_path(&watched_file));
         assert!(!watcher.matches_watched_path(Path::new("/other/file.wxs")));
@@ -242,12 +242,15 @@ impl BestPracticesAnalyzer {
             if node.tag_name().name() == "Product" {
                 let range = doc.node_range(&node);
                 let location = Location::new(doc.file().to_path_buf(), range);
-                result.add(Diagnostic::warning(
-                    "BP-IDIOM-003",
-                    Category::BestPractice,
-                    "Product element is deprecated in WiX v4. Use Package instead",
-                    location,
-                ));
+                result.add(
+                    Diagnostic::warning(
+                        "BP-IDIOM-003",
+                        Category::BestPractice,
+                        "Product element is deprecated in WiX v4. Use Package instead",
+                        location,
+                    )
+                    .with_tag("deprecated"),
+                );
             }
         }
     }
@@ -1,7 +1,10 @@
 //! Security analyzer - identifies potential security issues in WiX files
 
 use super::Analyzer;
-use crate::core::{AnalysisResult, Category, Diagnostic, Location, SymbolIndex, WixDocument};
+use crate::core::{
+    extract_flows, AnalysisResult, Category, Diagnostic, Location, RelatedInfo, SinkKind,
+    SymbolIndex, TaintPath, TaintSource, WixDocument,
+};
 use regex::Regex;
 use std::sync::LazyLock;
 
@@ -26,7 +29,7 @@ impl Default for SecurityAnalyzer {
 }
 
 impl Analyzer for SecurityAnalyzer {
-    fn analyze(&self, doc: &WixDocument, _index: &SymbolIndex) -> AnalysisResult {
+    fn analyze(&self, doc: &WixDocument, index: &SymbolIndex) -> AnalysisResult {
         let mut result = AnalysisResult::new();
 
         // Check for services running as LocalSystem
@@ -44,6 +47,9 @@ impl Analyzer for SecurityAnalyzer {
         // Check for world-writable directories
         self.check_directory_permissions(doc, &mut result);
 
+        // Check for user-controllable properties reaching elevated actions
+        self.check_taint_flows(doc, index, &mut result);
+
         result
     }
 }
@@ -253,6 +259,97 @@ impl SecurityAnalyzer {
         }
     }
 
+    fn check_taint_flows(
+        &self,
+        doc: &WixDocument,
+        index: &SymbolIndex,
+        result: &mut AnalysisResult,
+    ) {
+        let mut graph = index.property_flow_graph();
+        // Single-file analysis: the document may not be part of the index
+        if !index
+            .property_flows()
+            .iter()
+            .any(|f| f.location().file == doc.file())
+        {
+            graph.extend(&extract_flows(doc));
+        }
+
+        // Report each path once, in the file holding the elevated action
+        for path in graph
+            .taint_paths()
+            .into_iter()
+            .filter(|p| p.sink.location.file == doc.file())
+        {
+            result.add(Self::taint_diagnostic(&path));
+        }
+    }
+
+    fn taint_diagnostic(path: &TaintPath) -> Diagnostic {
+        let sink = &path.sink;
+        let (origin, tags) = match sink.kind {
+            SinkKind::ExeCommand => ("its command line", ["CWE-78", "CWE-250", "OWASP-A03:2021"]),
+            SinkKind::CustomActionData => {
+                ("CustomActionData", ["CWE-20", "CWE-250", "OWASP-A03:2021"])
+            }
+        };
+
+        let mut diagnostic = Diagnostic::error(
+            "SEC-008",
+            Category::Security,
+            format!(
+                "Elevated CustomAction '{}' uses user-controllable property '{}' in {} ({})",
+                sink.action,
+                path.property,
+                origin,
+                path.chain().join(" -> ")
+            ),
+            sink.location.clone(),
+        )
+        .with_help("Validate the value in an immediate action, or avoid passing user-controllable properties to elevated code")
+        .with_tags(tags);
+
+        match &path.source {
+            TaintSource::PublicProperty {
+                definition: Some(location),
+            } => {
+                diagnostic = diagnostic.with_related(RelatedInfo::new(
+                    location.clone(),
+                    format!(
+                        "'{}' is public and can be set on the command line",
+                        path.property
+                    ),
+                ));
+            }
+            TaintSource::PublicProperty { definition: None } => {}
+            TaintSource::UserInput { control, location } => {
+                diagnostic = diagnostic.with_related(RelatedInfo::new(
+                    location.clone(),
+                    format!(
+                        "'{}' is set from user input in Control '{}'",
+                        path.property, control
+                    ),
+                ));
+            }
+        }
+
+        let mut from = path.property.as_str();
+        for step in &path.steps {
+            diagnostic = diagnostic.with_related(RelatedInfo::new(
+                step.location.clone(),
+                format!(
+                    "'{}' flows into '{}' ({})",
+                    from,
+                    step.target,
+                    step.kind.as_str()
+                ),
+            ));
+            from = step.target.as_str();
+        }
+
+        diagnostic
+    }
+
     fn check_directory_permissions(&self, doc: &WixDocument, result: &mut AnalysisResult) {
         for node in doc.root().descendants() {
             if node.tag_name().name() == "Directory"
@@ -656,4 +753,73 @@ mod tests {
         );
         assert!(result.diagnostics.iter().all(|d| d.rule_id != "SEC-007"));
     }
+
+    #[test]
+    fn test_taint_flow_into_exe_command() {
+        let result = analyze(
+            r#"<Wix>
+            <Property Id="TOOLARGS" />
+            <CustomAction Id="RunTool" ExeCommand="tool.exe [TOOLARGS]" Directory="INSTALLFOLDER"
+                          Execute="deferred" Impersonate="no" />
+        </Wix>"#,
+        );
+        let diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.rule_id == "SEC-008")
+            .unwrap();
+        assert!(diag.message.contains("TOOLARGS"));
+        assert!(diag.tags.iter().any(|t| t == "CWE-78"));
+        assert_eq!(diag.related.len(), 1);
+    }
+
+    #[test]
+    fn test_taint_flow_across_files() {
+        let ui = r#"<Wix><Fragment>
+            <Dialog Id="ConfigDlg">
+                <Control Id="Url" Type="Edit" Property="serverUrl" />
+            </Dialog>
+            <SetProperty Id="Configure" Value="url=[serverUrl]" Before="Configure" />
+        </Fragment></Wix>"#;
+        let product = r#"<Wix><Package>
+            <CustomAction Id="Configure" DllEntry="Configure" BinaryRef="Ca"
+                          Execute="deferred" Impersonate="no" />
+        </Package></Wix>"#;
+
+        let mut index = SymbolIndex::new();
+        index.index_source(ui, Path::new("ui.wxs")).unwrap();
+        index
+            .index_source(product, Path::new("product.wxs"))
+            .unwrap();
+
+        let doc = WixDocument::parse(product, Path::new("product.wxs")).unwrap();
+        let result = SecurityAnalyzer::new().analyze(&doc, &index);
+        let diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.rule_id == "SEC-008")
+            .unwrap();
+        assert!(diag.message.contains("serverUrl -> Configure"));
+        assert_eq!(diag.related.len(), 2);
+        assert!(diag
+            .related
+            .iter()
+            .all(|r| r.location.file == Path::new("ui.wxs")));
+
+        // Reported only where the elevated action lives
+        let doc = WixDocument::parse(ui, Path::new("ui.wxs")).unwrap();
+        let result = SecurityAnalyzer::new().analyze(&doc, &index);
+        assert!(result.diagnostics.iter().all(|d| d.rule_id != "SEC-008"));
+    }
+
+    #[test]
+    fn test_no_taint_for_impersonated_action() {
+        let result = analyze(
+            r#"<Wix>
+            <CustomAction Id="RunTool" ExeCommand="tool.exe [TOOLARGS]" Directory="INSTALLFOLDER"
+                          Execute="deferred" />
+        </Wix>"#,
+        );
+        assert!(result.diagnostics.iter().all(|d| d.rule_id != "SEC-008"));
+    }
 }
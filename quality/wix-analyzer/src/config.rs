@@ -77,6 +77,10 @@ pub struct RulesConfig {
     /// Override severity for specific rules
     #[serde(default)]
     pub severity: HashMap<String, String>,
+
+    /// Rule packs to load (bundled files or pack directories)
+    #[serde(default)]
+    pub packs: Vec<PathBuf>,
 }
 
 /// Fix-specific configuration
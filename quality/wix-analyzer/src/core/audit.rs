@@ -0,0 +1,352 @@
+//! Suppression audit - inline suppressions and baseline entries that outlived
+//! their purpose
+//!
+//! A suppression is stale when the issue it hides no longer fires, and
+//! expired when its `until` date has passed. Staleness is judged against an
+//! analysis run with inline suppressions and baselines not applied.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::baseline::{Baseline, BaselineEntry};
+use super::suppression::{SuppressionContext, SuppressionKind, SuppressionMeta};
+use crate::core::Diagnostic;
+
+/// Where a suppression is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressionOrigin {
+    Inline,
+    Baseline,
+}
+
+/// One audited suppression
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub origin: SuppressionOrigin,
+    /// Source file with the comment, or the baseline file
+    pub file: PathBuf,
+    /// Line of the comment, or of the issue recorded in the baseline
+    pub line: usize,
+    /// Suppressed rules; empty means all rules
+    pub rules: Vec<String>,
+    /// File the baseline entry refers to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(flatten)]
+    pub meta: SuppressionMeta,
+    /// Issues currently hidden
+    pub hidden: usize,
+    /// Hides nothing anymore
+    pub stale: bool,
+    /// Past its `until` date (or the date is unreadable)
+    pub expired: bool,
+}
+
+impl AuditEntry {
+    fn status(&self) -> &'static str {
+        match (self.expired, self.stale) {
+            (true, _) => "expired",
+            (false, true) => "stale",
+            _ => "ok",
+        }
+    }
+}
+
+/// Audits suppressions against the current, unsuppressed diagnostics
+pub struct SuppressionAudit<'a> {
+    diagnostics: Vec<&'a Diagnostic>,
+    today: NaiveDate,
+    entries: Vec<AuditEntry>,
+}
+
+impl<'a> SuppressionAudit<'a> {
+    pub fn new(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Self {
+        Self {
+            diagnostics: diagnostics.into_iter().collect(),
+            today: chrono::Local::now().date_naive(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Date expiry is checked against
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = today;
+        self
+    }
+
+    /// Audit the inline suppressions of a source file
+    pub fn audit_source(&mut self, file: &Path, source: &str) {
+        let ctx = SuppressionContext::parse(source);
+        for suppression in ctx.suppressions() {
+            let hidden = self
+                .diagnostics
+                .iter()
+                .filter(|d| {
+                    d.location.file == file
+                        && suppression.covers(&d.rule_id, d.location.range.start.line)
+                })
+                .count();
+            let mut rules: Vec<String> = suppression.rules.iter().cloned().collect();
+            rules.sort();
+            self.entries.push(AuditEntry {
+                origin: SuppressionOrigin::Inline,
+                file: file.to_path_buf(),
+                line: suppression.line,
+                rules,
+                target: None,
+                meta: suppression.meta.clone(),
+                hidden,
+                // A block with nothing in it is deliberate more often than not
+                stale: hidden == 0 && suppression.kind != SuppressionKind::Block,
+                expired: suppression.meta.is_expired(self.today),
+            });
+        }
+    }
+
+    /// Audit the entries of a baseline file
+    ///
+    /// Only entries for the analysed `files` can be judged stale; the others
+    /// refer to files this run didn't look at.
+    pub fn audit_baseline(
+        &mut self,
+        path: &Path,
+        baseline: &Baseline,
+        files: &[PathBuf],
+        base_path: Option<&Path>,
+    ) {
+        let mut current: HashMap<String, usize> = HashMap::new();
+        for diag in &self.diagnostics {
+            *current
+                .entry(BaselineEntry::from_diagnostic(diag, base_path).fingerprint)
+                .or_default() += 1;
+        }
+        let analyzed: HashSet<String> = files
+            .iter()
+            .map(|f| BaselineEntry::relative_file(f, base_path))
+            .collect();
+        for entry in &baseline.issues {
+            let hidden = current.get(&entry.fingerprint).copied().unwrap_or(0);
+            self.entries.push(AuditEntry {
+                origin: SuppressionOrigin::Baseline,
+                file: path.to_path_buf(),
+                line: entry.line,
+                rules: vec![entry.rule_id.clone()],
+                target: Some(entry.file.clone()),
+                meta: entry.meta.clone(),
+                hidden,
+                stale: hidden == 0 && analyzed.contains(&entry.file),
+                expired: entry.meta.is_expired(self.today),
+            });
+        }
+    }
+
+    pub fn finish(self) -> SuppressionReport {
+        SuppressionReport {
+            today: self.today,
+            entries: self.entries,
+        }
+    }
+}
+
+/// Result of a suppression audit
+#[derive(Debug, Clone, Serialize)]
+pub struct SuppressionReport {
+    pub today: NaiveDate,
+    pub entries: Vec<AuditEntry>,
+}
+
+impl SuppressionReport {
+    pub fn stale(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter().filter(|e| e.stale)
+    }
+
+    pub fn expired(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter().filter(|e| e.expired)
+    }
+
+    /// Suppressions that don't say why
+    pub fn without_reason(&self) -> impl Iterator<Item = &AuditEntry> {
+        self.entries.iter().filter(|e| e.meta.reason.is_none())
+    }
+
+    pub fn format_text(&self) -> String {
+        let mut out = String::new();
+        if self.entries.is_empty() {
+            out.push_str("No suppressions found.\n");
+            return out;
+        }
+
+        out.push_str("Suppressions\n");
+        out.push_str("============\n\n");
+        out.push_str(&format!(
+            "{:<8} {:<8} {:<36} {:<14} {:>6} {:<12} {:<10} Reason\n",
+            "Status", "Origin", "Location", "Rules", "Hidden", "Owner", "Until"
+        ));
+        for entry in &self.entries {
+            let location = match &entry.target {
+                Some(target) => format!("{}:{}", target, entry.line),
+                None => format!("{}:{}", entry.file.display(), entry.line),
+            };
+            let rules = if entry.rules.is_empty() {
+                "all".to_string()
+            } else {
+                entry.rules.join(",")
+            };
+            out.push_str(&format!(
+                "{:<8} {:<8} {:<36} {:<14} {:>6} {:<12} {:<10} {}\n",
+                entry.status(),
+                match entry.origin {
+                    SuppressionOrigin::Inline => "inline",
+                    SuppressionOrigin::Baseline => "baseline",
+                },
+                location,
+                rules,
+                entry.hidden,
+                entry.meta.owner.as_deref().unwrap_or("-"),
+                entry.meta.until.as_deref().unwrap_or("-"),
+                entry.meta.reason.as_deref().unwrap_or("-"),
+            ));
+        }
+
+        let inline = self
+            .entries
+            .iter()
+            .filter(|e| e.origin == SuppressionOrigin::Inline)
+            .count();
+        out.push_str(&format!(
+            "\n{} suppressions ({} inline, {} baseline): {} stale, {} expired, {} without reason\n",
+            self.entries.len(),
+            inline,
+            self.entries.len() - inline,
+            self.stale().count(),
+            self.expired().count(),
+            self.without_reason().count(),
+        ));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Category, Location, Position, Range};
+
+    fn diag(rule_id: &str, file: &str, line: usize) -> Diagnostic {
+        Diagnostic::error(
+            rule_id,
+            Category::Security,
+            "Message",
+            Location::new(
+                PathBuf::from(file),
+                Range::new(Position::new(line, 1), Position::new(line, 10)),
+            ),
+        )
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()
+    }
+
+    #[test]
+    fn test_inline_audit() {
+        let source = r#"<Wix>
+  <!-- wix-analyzer-disable-next-line SEC-001 reason="Vendor key" owner=alice until=2025-06-30 -->
+  <Property Id="KEY" Value="secret" />
+  <!-- wix-analyzer-disable-next-line SEC-002 until=2025-01-31 -->
+  <Property Id="GONE" />
+  <!-- wix-analyzer-disable -->
+  <!-- wix-analyzer-enable -->
+</Wix>"#;
+        let diagnostics = [diag("SEC-001", "a.wxs", 3), diag("SEC-001", "b.wxs", 3)];
+        let mut audit = SuppressionAudit::new(&diagnostics).with_today(today());
+        audit.audit_source(Path::new("a.wxs"), source);
+        let report = audit.finish();
+
+        assert_eq!(report.entries.len(), 3);
+        let first = &report.entries[0];
+        assert_eq!((first.line, first.hidden), (2, 1));
+        assert_eq!(first.meta.reason.as_deref(), Some("Vendor key"));
+        assert_eq!(first.meta.owner.as_deref(), Some("alice"));
+        assert!(!first.stale && !first.expired);
+
+        let second = &report.entries[1];
+        assert_eq!(second.rules, vec!["SEC-002"]);
+        assert!(second.stale && second.expired);
+
+        // Empty blocks aren't reported as stale
+        assert!(!report.entries[2].stale);
+        assert!(report.entries[2].rules.is_empty());
+
+        assert_eq!(report.without_reason().count(), 2);
+        let text = report.format_text();
+        assert!(text.contains("3 suppressions (3 inline, 0 baseline): 1 stale, 1 expired"));
+        assert!(text.contains("Vendor key"));
+    }
+
+    #[test]
+    fn test_baseline_audit_and_prune() {
+        let current = diag("SEC-001", "/project/a.wxs", 10);
+        let fixed = diag("SEC-002", "/project/a.wxs", 20);
+        let base = Some(Path::new("/project"));
+
+        let mut baseline = Baseline::new();
+        baseline
+            .issues
+            .push(BaselineEntry::from_diagnostic(&current, base));
+        let mut entry = BaselineEntry::from_diagnostic(&fixed, base);
+        entry.meta.until = Some("someday".to_string());
+        baseline.issues.push(entry);
+
+        let diagnostics = [current];
+        let files = [PathBuf::from("/project/a.wxs")];
+        let mut audit = SuppressionAudit::new(&diagnostics).with_today(today());
+        audit.audit_baseline(Path::new("/project/baseline.json"), &baseline, &files, base);
+        let report = audit.finish();
+
+        assert_eq!(report.stale().count(), 1);
+        let stale = report.stale().next().unwrap();
+        assert_eq!(stale.rules, vec!["SEC-002"]);
+        assert_eq!(stale.target.as_deref(), Some("a.wxs"));
+        // Unreadable dates count as expired
+        assert!(stale.expired);
+
+        let removed = baseline.prune(&diagnostics, &files, base);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].rule_id, "SEC-002");
+        assert_eq!(baseline.len(), 1);
+    }
+
+    #[test]
+    fn test_baseline_audit_partial_paths() {
+        let base = Some(Path::new("/project"));
+        let mut baseline = Baseline::new();
+        baseline.issues.push(BaselineEntry::from_diagnostic(
+            &diag("SEC-001", "/project/a.wxs", 10),
+            base,
+        ));
+        baseline.issues.push(BaselineEntry::from_diagnostic(
+            &diag("SEC-001", "/project/sub/b.wxs", 10),
+            base,
+        ));
+
+        // Only b.wxs is analysed and its issue is fixed; a.wxs wasn't looked at
+        let diagnostics: [Diagnostic; 0] = [];
+        let files = [PathBuf::from("/project/sub/b.wxs")];
+        let mut audit = SuppressionAudit::new(&diagnostics).with_today(today());
+        audit.audit_baseline(Path::new("/project/baseline.json"), &baseline, &files, base);
+        let report = audit.finish();
+
+        assert_eq!(report.entries.len(), 2);
+        let stale: Vec<_> = report.stale().collect();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].target.as_deref(), Some("sub/b.wxs"));
+
+        let removed = baseline.prune(&diagnostics, &files, base);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].file, "sub/b.wxs");
+        assert_eq!(baseline.issues[0].file, "a.wxs");
+    }
+}
@@ -16,18 +16,25 @@
 //!       "rule_id": "SEC-001",
 //!       "file": "src/product.wxs",
 //!       "line": 42,
-//!       "message_hash": "def456..."
+//!       "message_hash": "def456...",
+//!       "reason": "Fixed with the installer rewrite",
+//!       "owner": "alice",
+//!       "until": "2025-06-30"
 //!     }
 //!   ]
 //! }
 //! ```
+//!
+//! `reason`, `owner` and `until` are optional and checked by
+//! `wix-analyzer suppressions audit`.
 
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use super::suppression::SuppressionMeta;
 use crate::core::{AnalysisResult, Diagnostic};
 
 /// Baseline file format version
@@ -49,21 +56,15 @@ pub struct BaselineEntry {
     pub line: usize,
     /// Hash of the message (for detecting rule changes)
     pub message_hash: String,
+    /// Why the issue is accepted, and until when
+    #[serde(flatten)]
+    pub meta: SuppressionMeta,
 }
 
 impl BaselineEntry {
     /// Create a baseline entry from a diagnostic
     pub fn from_diagnostic(diag: &Diagnostic, base_path: Option<&Path>) -> Self {
-        let file = match base_path {
-            Some(base) => diag
-                .location
-                .file
-                .strip_prefix(base)
-                .unwrap_or(&diag.location.file)
-                .to_string_lossy()
-                .to_string(),
-            None => diag.location.file.to_string_lossy().to_string(),
-        };
+        let file = Self::relative_file(&diag.location.file, base_path);
 
         let fingerprint = Self::compute_fingerprint(
             &diag.rule_id,
@@ -80,9 +81,19 @@ impl BaselineEntry {
             file,
             line: diag.location.range.start.line,
             message_hash,
+            meta: SuppressionMeta::default(),
         }
     }
 
+    /// File path as recorded in an entry: relative to the base path when under it
+    pub fn relative_file(file: &Path, base_path: Option<&Path>) -> String {
+        base_path
+            .and_then(|base| file.strip_prefix(base).ok())
+            .unwrap_or(file)
+            .to_string_lossy()
+            .to_string()
+    }
+
     /// Compute a fingerprint for matching issues
     /// Uses rule_id + normalized file path + line region (not exact line)
     fn compute_fingerprint(rule_id: &str, file: &str, line: usize, message: &str) -> String {
@@ -220,6 +231,31 @@ impl Baseline {
         self.issues.iter().map(|e| e.fingerprint.clone()).collect()
     }
 
+    /// Remove entries for the analysed files that no diagnostic matches
+    /// anymore, returning them
+    ///
+    /// Entries for files outside `files` weren't checked and are kept.
+    pub fn prune<'a>(
+        &mut self,
+        diagnostics: impl IntoIterator<Item = &'a Diagnostic>,
+        files: &[PathBuf],
+        base_path: Option<&Path>,
+    ) -> Vec<BaselineEntry> {
+        let current: HashSet<String> = diagnostics
+            .into_iter()
+            .map(|d| BaselineEntry::from_diagnostic(d, base_path).fingerprint)
+            .collect();
+        let analyzed: HashSet<String> = files
+            .iter()
+            .map(|f| BaselineEntry::relative_file(f, base_path))
+            .collect();
+        let (kept, stale) = std::mem::take(&mut self.issues)
+            .into_iter()
+            .partition(|e| current.contains(&e.fingerprint) || !analyzed.contains(&e.file));
+        self.issues = kept;
+        stale
+    }
+
     /// Number of issues in baseline
     pub fn len(&self) -> usize {
         self.issues.len()
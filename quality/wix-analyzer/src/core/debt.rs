@@ -31,7 +31,7 @@ pub struct TechnicalDebt {
 }
 
 /// Issue counts by severity
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SeverityCounts {
     pub blocker: usize,
     pub high: usize,
@@ -188,7 +188,7 @@ fn default_effort(diagnostic: &Diagnostic) -> u32 {
 }
 
 /// Format duration in minutes to human-readable string
-fn format_duration(minutes: u32) -> String {
+pub(crate) fn format_duration(minutes: u32) -> String {
     if minutes == 0 {
         return "0min".to_string();
     }
@@ -8,21 +8,36 @@ use super::types::Range;
 /// A parsed WiX document
 pub struct WixDocument<'a> {
     source: &'a str,
-    doc: Document<'a>,
+    doc: Tree<'a>,
     file: PathBuf,
 }
 
+/// XML tree, parsed here or shared with the caller
+enum Tree<'a> {
+    Owned(Document<'a>),
+    Shared(&'a Document<'a>),
+}
+
 impl<'a> WixDocument<'a> {
     /// Parse a WiX source file
     pub fn parse(source: &'a str, file: &Path) -> Result<Self, String> {
         let doc = Document::parse(source).map_err(|e| format!("XML parse error: {}", e))?;
         Ok(Self {
             source,
-            doc,
+            doc: Tree::Owned(doc),
             file: file.to_path_buf(),
         })
     }
 
+    /// Wrap a tree the caller already parsed from `source`
+    pub fn from_document(source: &'a str, doc: &'a Document<'a>, file: &Path) -> Self {
+        Self {
+            source,
+            doc: Tree::Shared(doc),
+            file: file.to_path_buf(),
+        }
+    }
+
     /// Get the source text
     pub fn source(&self) -> &str {
         self.source
@@ -35,7 +50,10 @@ impl<'a> WixDocument<'a> {
 
     /// Get the root node
     pub fn root(&self) -> Node<'_, '_> {
-        self.doc.root()
+        match &self.doc {
+            Tree::Owned(doc) => doc.root(),
+            Tree::Shared(doc) => doc.root(),
+        }
     }
 
     /// Get range of a node
@@ -223,6 +241,15 @@ mod tests {
         assert!(err.contains("XML parse error"));
     }
 
+    #[test]
+    fn test_from_document() {
+        let source = "<Wix><Package Name=\"Test\" /></Wix>";
+        let tree = Document::parse(source).unwrap();
+        let doc = WixDocument::from_document(source, &tree, Path::new("test.wxs"));
+        let package = doc.element_at(1, 8).unwrap();
+        assert_eq!(package.tag_name().name(), "Package");
+    }
+
     #[test]
     fn test_file_path() {
         let source = "<Wix />";
@@ -1,11 +1,20 @@
 //! Duplication detection for WiX files
 //!
 //! Finds duplicated code blocks, similar structures, and copy-paste issues.
+//!
+//! Two modes are available:
+//! - **Lines** fingerprints sliding windows of raw lines.
+//! - **Structural** hashes normalized element subtrees (Component,
+//!   RegistryKey, CustomAction by default), ignoring Id/Guid values,
+//!   attribute order and formatting, so copy-pasted elements with renamed
+//!   Ids are still found.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
+use super::types::Range;
+
 /// A duplicated code block
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Duplicate {
@@ -19,6 +28,38 @@ pub struct Duplicate {
     pub tokens: usize,
     /// The duplicated content (truncated for display)
     pub preview: String,
+    /// How the instances match
+    #[serde(default)]
+    pub kind: DuplicateKind,
+    /// How to consolidate the instances
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// How duplicate instances match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    /// Identical blocks of lines
+    #[default]
+    Lines,
+    /// Identical element subtrees, apart from Id/Guid values
+    Exact,
+    /// Subtrees whose values differ only by one parameter
+    Parameterized,
+    /// Subtrees with the same shape and a few differing values
+    Near,
+}
+
+impl DuplicateKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lines => "duplicated lines",
+            Self::Exact => "structural clone",
+            Self::Parameterized => "parameterized clone",
+            Self::Near => "near clone",
+        }
+    }
 }
 
 impl Duplicate {
@@ -66,6 +107,51 @@ pub struct DuplicationConfig {
     pub ignore_whitespace: bool,
     /// Ignore attribute value differences (detect structural duplicates)
     pub ignore_values: bool,
+    /// Line windows or element subtrees
+    #[serde(default)]
+    pub mode: DuplicationMode,
+    /// Elements compared in structural mode
+    #[serde(default = "default_structural_elements")]
+    pub structural_elements: Vec<String>,
+    /// Attributes whose values are ignored in structural mode
+    #[serde(default = "default_ignored_attributes")]
+    pub ignored_attributes: Vec<String>,
+    /// Minimum elements in a subtree for structural mode
+    #[serde(default = "default_min_elements")]
+    pub min_elements: usize,
+    /// Most differing values for a near clone in structural mode
+    #[serde(default = "default_max_differences")]
+    pub max_differences: usize,
+}
+
+/// Duplication detection mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicationMode {
+    /// Sliding windows of raw lines
+    #[default]
+    Lines,
+    /// Normalized element subtrees
+    Structural,
+}
+
+fn default_structural_elements() -> Vec<String> {
+    ["Component", "RegistryKey", "CustomAction"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_ignored_attributes() -> Vec<String> {
+    vec!["Id".to_string(), "Guid".to_string()]
+}
+
+fn default_min_elements() -> usize {
+    2
+}
+
+fn default_max_differences() -> usize {
+    3
 }
 
 impl Default for DuplicationConfig {
@@ -75,6 +161,21 @@ impl Default for DuplicationConfig {
             min_tokens: 10,
             ignore_whitespace: true,
             ignore_values: false,
+            mode: DuplicationMode::Lines,
+            structural_elements: default_structural_elements(),
+            ignored_attributes: default_ignored_attributes(),
+            min_elements: default_min_elements(),
+            max_differences: default_max_differences(),
+        }
+    }
+}
+
+impl DuplicationConfig {
+    /// Structural clone detection with default settings
+    pub fn structural() -> Self {
+        Self {
+            mode: DuplicationMode::Structural,
+            ..Default::default()
         }
     }
 }
@@ -165,6 +266,8 @@ pub struct DuplicationDetector {
     config: DuplicationConfig,
     /// Fingerprint -> locations map
     fingerprints: HashMap<u64, Vec<(PathBuf, usize, usize, String)>>,
+    /// Normalized subtrees (structural mode)
+    subtrees: Vec<Subtree>,
     total_lines: usize,
 }
 
@@ -173,6 +276,7 @@ impl DuplicationDetector {
         Self {
             config,
             fingerprints: HashMap::new(),
+            subtrees: Vec::new(),
             total_lines: 0,
         }
     }
@@ -183,6 +287,12 @@ impl DuplicationDetector {
 
     /// Add a file to analyze
     pub fn add_file(&mut self, path: &str, content: &str) {
+        if self.config.mode == DuplicationMode::Structural {
+            self.total_lines += content.lines().count();
+            self.add_subtrees(path, content);
+            return;
+        }
+
         let lines: Vec<&str> = content.lines().collect();
         self.total_lines += lines.len();
 
@@ -211,6 +321,13 @@ impl DuplicationDetector {
             ..Default::default()
         };
 
+        if self.config.mode == DuplicationMode::Structural {
+            for duplicate in self.structural_duplicates() {
+                result.add(duplicate);
+            }
+            return result;
+        }
+
         for (fingerprint, locations) in &self.fingerprints {
             if locations.len() > 1 {
                 let first = &locations[0];
@@ -225,6 +342,8 @@ impl DuplicationDetector {
                     lines,
                     tokens: self.count_tokens(&first.3),
                     preview: first.3.clone(),
+                    kind: DuplicateKind::Lines,
+                    suggestion: None,
                 };
 
                 result.add(duplicate);
@@ -364,6 +483,212 @@ impl DuplicationDetector {
 
         preview
     }
+
+    /// Record the normalized subtrees of the configured elements
+    fn add_subtrees(&mut self, path: &str, content: &str) {
+        // Files that don't parse are left to the validation analyzer
+        let Ok(doc) = roxmltree::Document::parse(content) else {
+            return;
+        };
+
+        for node in doc.descendants().filter(|n| {
+            n.is_element()
+                && self
+                    .config
+                    .structural_elements
+                    .iter()
+                    .any(|e| e == n.tag_name().name())
+        }) {
+            let mut shape = Shape::default();
+            self.normalize(node, content, node.range().start, &mut shape);
+            if shape.elements < self.config.min_elements {
+                continue;
+            }
+
+            let range = node.range();
+            let location = Range::from_offsets(content, range.start, range.end);
+            let indent: String = content[..range.start]
+                .rsplit('\n')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .collect();
+            let text = content[range].to_string();
+            let lines: Vec<&str> = text.lines().collect();
+            self.subtrees.push(Subtree {
+                file: PathBuf::from(path),
+                start_line: location.start.line,
+                end_line: location.end.line,
+                offsets: (node.range().start, node.range().end),
+                tag: node.tag_name().name().to_string(),
+                skeleton: self.hash_content(&shape.skeleton),
+                exact: self.hash_content(&shape.exact),
+                elements: shape.elements,
+                slots: shape.slots,
+                preview: self.create_preview(&lines),
+                indent,
+                text,
+            });
+        }
+    }
+
+    /// Canonical form of a subtree: attributes sorted by name, ignored
+    /// values blanked, whitespace-only text dropped
+    fn normalize(&self, node: roxmltree::Node, source: &str, base: usize, shape: &mut Shape) {
+        let tag = node.tag_name().name();
+        shape.elements += 1;
+
+        let mut attrs: Vec<_> = node.attributes().collect();
+        attrs.sort_by_key(|a| a.name());
+        shape.skeleton.push_str(&format!("<{}", tag));
+        shape.exact.push_str(&format!("<{}", tag));
+        for attr in attrs {
+            let ignored = self
+                .config
+                .ignored_attributes
+                .iter()
+                .any(|i| i == attr.name());
+            shape.skeleton.push_str(&format!(" {}", attr.name()));
+            if ignored {
+                shape.exact.push_str(&format!(" {}=*", attr.name()));
+            } else {
+                shape
+                    .exact
+                    .push_str(&format!(" {}={:?}", attr.name(), attr.value()));
+            }
+            let range = attr.range_value();
+            shape.slots.push(Slot {
+                attribute: attr.name().to_string(),
+                value: attr.value().to_string(),
+                range: (range.start - base, range.end - base),
+                ignored,
+            });
+        }
+        shape.skeleton.push('>');
+        shape.exact.push('>');
+
+        for child in node.children() {
+            if child.is_element() {
+                self.normalize(child, source, base, shape);
+            } else if child.is_text() {
+                let text = child.text().unwrap_or_default().trim();
+                if !text.is_empty() {
+                    shape.skeleton.push_str("#text");
+                    shape.exact.push_str(&format!("{:?}", text));
+                    // Substitute the raw text without its surrounding whitespace
+                    let range = child.range();
+                    let raw = &source[range.clone()];
+                    let start = range.start + raw.len() - raw.trim_start().len();
+                    let end = range.start + raw.trim_end().len();
+                    shape.slots.push(Slot {
+                        attribute: "text".to_string(),
+                        value: text.to_string(),
+                        range: (start - base, end - base),
+                        ignored: false,
+                    });
+                }
+            }
+        }
+        shape.skeleton.push_str(&format!("</{}>", tag));
+        shape.exact.push_str(&format!("</{}>", tag));
+    }
+
+    /// Group subtrees by shape and classify each group
+    fn structural_duplicates(&self) -> Vec<Duplicate> {
+        let mut groups: BTreeMap<u64, Vec<&Subtree>> = BTreeMap::new();
+        for subtree in &self.subtrees {
+            groups.entry(subtree.skeleton).or_default().push(subtree);
+        }
+
+        // Largest subtrees first, so clones nested in reported clones are skipped
+        let mut groups: Vec<Vec<&Subtree>> = groups.into_values().filter(|g| g.len() > 1).collect();
+        groups.sort_by_key(|g| std::cmp::Reverse(g[0].elements));
+
+        let mut covered: Vec<&Subtree> = Vec::new();
+        let mut duplicates = Vec::new();
+        for group in groups {
+            if group.iter().all(|s| covered.iter().any(|c| c.contains(s))) {
+                continue;
+            }
+            if let Some(duplicate) = self.classify(&group) {
+                covered.extend(group.iter().copied());
+                duplicates.push(duplicate);
+            }
+        }
+        duplicates
+    }
+
+    fn classify(&self, group: &[&Subtree]) -> Option<Duplicate> {
+        let first = group[0];
+        let differing: Vec<usize> = (0..first.slots.len())
+            .filter(|&i| {
+                !first.slots[i].ignored
+                    && group
+                        .iter()
+                        .any(|s| s.slots[i].value != first.slots[i].value)
+            })
+            .collect();
+
+        let (kind, suggestion) = if group.iter().all(|s| s.exact == first.exact) {
+            let reference = match first.tag.as_str() {
+                "Component" => " and reference it with ComponentRef",
+                "CustomAction" => " and reference it with CustomActionRef",
+                _ => "",
+            };
+            (
+                DuplicateKind::Exact,
+                format!(
+                    "{} identical <{}> elements (ignoring {}); define it once in a shared Fragment{}",
+                    group.len(),
+                    first.tag,
+                    self.config.ignored_attributes.join("/"),
+                    reference
+                ),
+            )
+        } else if let Some(foreach) = foreach_template(group, &differing) {
+            (
+                DuplicateKind::Parameterized,
+                format!(
+                    "{} <{}> elements differ only by one value; generate them with a preprocessor loop:\n{}",
+                    group.len(),
+                    first.tag,
+                    foreach
+                ),
+            )
+        } else if differing.len() <= self.config.max_differences {
+            let mut names: Vec<&str> = differing
+                .iter()
+                .map(|&i| first.slots[i].attribute.as_str())
+                .collect();
+            names.dedup();
+            (
+                DuplicateKind::Near,
+                format!(
+                    "{} <{}> elements differ only in {}; consider a shared Fragment with preprocessor variables",
+                    group.len(),
+                    first.tag,
+                    names.join(", ")
+                ),
+            )
+        } else {
+            return None;
+        };
+
+        let lines = first.end_line - first.start_line + 1;
+        Some(Duplicate {
+            fingerprint: first.skeleton,
+            locations: group
+                .iter()
+                .map(|s| DuplicateLocation::new(&s.file, s.start_line, s.end_line))
+                .collect(),
+            lines,
+            tokens: first.elements,
+            preview: first.preview.clone(),
+            kind,
+            suggestion: Some(suggestion),
+        })
+    }
 }
 
 /// Internal representation of a code block
@@ -376,6 +701,141 @@ struct CodeBlock {
     preview: String,
 }
 
+/// Normalized form of one subtree while it is built
+#[derive(Default)]
+struct Shape {
+    /// Tags and attribute names only
+    skeleton: String,
+    /// Skeleton plus values, except ignored attributes
+    exact: String,
+    elements: usize,
+    slots: Vec<Slot>,
+}
+
+/// A value in a subtree: an attribute or element text
+struct Slot {
+    attribute: String,
+    value: String,
+    /// Byte range of the raw value within the subtree text
+    range: (usize, usize),
+    ignored: bool,
+}
+
+/// An element subtree recorded in structural mode
+struct Subtree {
+    file: PathBuf,
+    start_line: usize,
+    end_line: usize,
+    offsets: (usize, usize),
+    tag: String,
+    skeleton: u64,
+    exact: u64,
+    elements: usize,
+    /// In document order; equal skeletons give matching slot lists
+    slots: Vec<Slot>,
+    preview: String,
+    /// Whitespace before the element on its first line
+    indent: String,
+    text: String,
+}
+
+impl Subtree {
+    fn contains(&self, other: &Subtree) -> bool {
+        self.file == other.file
+            && self.offsets.0 <= other.offsets.0
+            && other.offsets.1 <= self.offsets.1
+    }
+}
+
+/// `<?foreach?>` loop reproducing a group whose differing values all vary
+/// by the same substring, e.g. `Reg_Foo`/`Foo.dll` and `Reg_Bar`/`Bar.dll`
+fn foreach_template(group: &[&Subtree], differing: &[usize]) -> Option<String> {
+    let first = group[0];
+    let &pivot = differing.first()?;
+    let pivot_values: Vec<&str> = group
+        .iter()
+        .map(|s| s.slots[pivot].value.as_str())
+        .collect();
+    let (_, params) = split_parameter(&pivot_values)?;
+    if params.iter().any(|p| p.is_empty() || p.contains(';')) {
+        return None;
+    }
+
+    // Every differing value, and Ids when they follow along, must vary by
+    // the same parameter
+    let mut substitutions = Vec::new();
+    for (i, slot) in first.slots.iter().enumerate() {
+        let values: Vec<&str> = group.iter().map(|s| s.slots[i].value.as_str()).collect();
+        if values.iter().all(|v| *v == values[0]) {
+            continue;
+        }
+        match split_parameter(&values) {
+            Some(((prefix, suffix), middles)) if middles == params => {
+                substitutions.push((slot.range, format!("{}$(var.Item){}", prefix, suffix)));
+            }
+            _ if slot.ignored && slot.attribute == "Guid" => {
+                substitutions.push((slot.range, "*".to_string()));
+            }
+            _ => return None,
+        }
+    }
+
+    let mut template = first.text.clone();
+    substitutions.sort_by_key(|(range, _)| std::cmp::Reverse(range.0));
+    for ((start, end), text) in substitutions {
+        template.replace_range(start..end, &text);
+    }
+    Some(format!(
+        "<?foreach Item in {}?>\n{}{}\n<?endforeach?>",
+        params.join(";"),
+        first.indent,
+        template
+    ))
+}
+
+/// Split values into a shared prefix/suffix and the part that varies
+fn split_parameter<'a>(values: &[&'a str]) -> Option<((String, String), Vec<&'a str>)> {
+    let first = values.first()?;
+    let prefix_len = values.iter().skip(1).fold(first.len(), |len, v| {
+        first
+            .char_indices()
+            .zip(v.chars())
+            .take_while(|((i, a), b)| *i < len && a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0)
+    });
+    let shortest = values.iter().map(|v| v.len()).min()?;
+    let max_suffix = shortest - prefix_len.min(shortest);
+    let suffix_len = values.iter().skip(1).fold(max_suffix, |len, v| {
+        first
+            .chars()
+            .rev()
+            .zip(v.chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .scan(0, |total, n| {
+                *total += n;
+                Some(*total)
+            })
+            .take_while(|total| *total <= len)
+            .last()
+            .unwrap_or(0)
+    });
+
+    let middles = values
+        .iter()
+        .map(|v| v.get(prefix_len..v.len() - suffix_len))
+        .collect::<Option<Vec<_>>>()?;
+    Some((
+        (
+            first[..prefix_len].to_string(),
+            first[first.len() - suffix_len..].to_string(),
+        ),
+        middles,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,6 +987,8 @@ mod tests {
             lines: 5,
             tokens: 20,
             preview: "...".to_string(),
+            kind: DuplicateKind::Lines,
+            suggestion: None,
         };
 
         assert_eq!(dup.count(), 3);
@@ -558,4 +1020,135 @@ mod tests {
         let result = detector.results();
         assert!(result.duplicates.is_empty());
     }
+
+    fn structural_detector(files: &[(&str, &str)]) -> DuplicationResult {
+        let mut detector = DuplicationDetector::new(DuplicationConfig::structural());
+        for (path, content) in files {
+            detector.add_file(path, content);
+        }
+        detector.results()
+    }
+
+    #[test]
+    fn test_structural_ignores_ids_order_and_indentation() {
+        let a = r#"<Wix>
+  <Component Id="CmpA" Guid="{11111111-1111-1111-1111-111111111111}" Directory="INSTALLFOLDER">
+    <File Source="shared.dll" KeyPath="yes" />
+  </Component>
+</Wix>"#;
+        let b = r#"<Wix>
+      <Fragment>
+          <Component Directory="INSTALLFOLDER" Id="CmpB">
+              <File KeyPath="yes"
+                    Source="shared.dll" />
+          </Component>
+      </Fragment>
+</Wix>"#;
+
+        // Line fingerprints miss it
+        let mut lines = DuplicationDetector::with_default_config();
+        lines.add_file("a.wxs", a);
+        lines.add_file("b.wxs", b);
+        assert!(lines.results().duplicates.is_empty());
+
+        let mut ignored = DuplicationDetector::new(DuplicationConfig::structural());
+        ignored.add_file("a.wxs", a);
+        ignored.add_file("b.wxs", b);
+        // Guid differs in presence, so the shapes differ
+        assert!(ignored.results().duplicates.is_empty());
+
+        let a = a.replace(r#" Guid="{11111111-1111-1111-1111-111111111111}""#, "");
+        let result = structural_detector(&[("a.wxs", &a), ("b.wxs", b)]);
+        assert_eq!(result.duplicates.len(), 1);
+        let dup = &result.duplicates[0];
+        assert_eq!(dup.kind, DuplicateKind::Exact);
+        assert_eq!(dup.count(), 2);
+        assert_eq!(dup.locations[1].file, PathBuf::from("b.wxs"));
+        assert_eq!(
+            (dup.locations[1].start_line, dup.locations[1].end_line),
+            (3, 6)
+        );
+        assert!(dup.suggestion.as_ref().unwrap().contains("shared Fragment"));
+        assert!(result.duplicated_lines > 0);
+    }
+
+    #[test]
+    fn test_structural_parameterized_clone() {
+        let content = r#"<Wix><Fragment>
+    <Component Id="Reg_Foo" Guid="{11111111-1111-1111-1111-111111111111}">
+        <RegistryKey Root="HKLM" Key="Software\Acme\Foo">
+            <RegistryValue Name="Path" Value="[INSTALLFOLDER]Foo.dll" Type="string" />
+        </RegistryKey>
+    </Component>
+    <Component Id="Reg_Bar" Guid="{22222222-2222-2222-2222-222222222222}">
+        <RegistryKey Root="HKLM" Key="Software\Acme\Bar">
+            <RegistryValue Name="Path" Value="[INSTALLFOLDER]Bar.dll" Type="string" />
+        </RegistryKey>
+    </Component>
+</Fragment></Wix>"#;
+        let result = structural_detector(&[("reg.wxs", content)]);
+
+        // The RegistryKey clones are inside the Component clones
+        assert_eq!(result.duplicates.len(), 1);
+        let dup = &result.duplicates[0];
+        assert_eq!(dup.kind, DuplicateKind::Parameterized);
+        let suggestion = dup.suggestion.as_ref().unwrap();
+        assert!(suggestion.contains("<?foreach Item in Foo;Bar?>"));
+        assert!(suggestion.contains(r#"<Component Id="Reg_$(var.Item)" Guid="*">"#));
+        assert!(suggestion.contains(r#"Key="Software\Acme\$(var.Item)""#));
+        assert!(suggestion.contains(r#"Value="[INSTALLFOLDER]$(var.Item).dll""#));
+        assert!(suggestion.ends_with("<?endforeach?>"));
+    }
+
+    #[test]
+    fn test_structural_near_clone_and_distinct() {
+        let ca = |id: &str, exe: &str, dir: &str| {
+            format!(
+                r#"<CustomAction Id="{}" Directory="{}" ExeCommand="{}" Execute="deferred"><Note>x</Note></CustomAction>"#,
+                id, dir, exe
+            )
+        };
+        let content = format!(
+            "<Wix>{}{}</Wix>",
+            ca("A", "setup.exe /q", "INSTALLFOLDER"),
+            ca("B", "other.exe /passive", "TOOLSFOLDER")
+        );
+        let result = structural_detector(&[("ca.wxs", &content)]);
+        assert_eq!(result.duplicates.len(), 1);
+        assert_eq!(result.duplicates[0].kind, DuplicateKind::Near);
+        assert!(result.duplicates[0]
+            .suggestion
+            .as_ref()
+            .unwrap()
+            .contains("Directory, ExeCommand"));
+
+        // Too many differences is not a clone
+        let mut config = DuplicationConfig::structural();
+        config.max_differences = 1;
+        let mut detector = DuplicationDetector::new(config);
+        detector.add_file("ca.wxs", &content);
+        assert!(detector.results().duplicates.is_empty());
+    }
+
+    #[test]
+    fn test_split_parameter() {
+        let ((prefix, suffix), middles) = split_parameter(&["Reg_Foo.dll", "Reg_Bar.dll"]).unwrap();
+        assert_eq!((prefix.as_str(), suffix.as_str()), ("Reg_", ".dll"));
+        assert_eq!(middles, vec!["Foo", "Bar"]);
+
+        // Prefix and suffix never overlap
+        let ((prefix, suffix), middles) = split_parameter(&["aa", "aaa"]).unwrap();
+        assert_eq!(prefix.len() + suffix.len(), 2);
+        assert_eq!(middles, vec!["", "a"]);
+    }
+
+    #[test]
+    fn test_structural_config_deserializes_with_defaults() {
+        let config: DuplicationConfig = serde_json::from_str(
+            r#"{"min_lines":3,"min_tokens":10,"ignore_whitespace":true,"ignore_values":false,"mode":"structural"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.mode, DuplicationMode::Structural);
+        assert_eq!(config.ignored_attributes, vec!["Id", "Guid"]);
+    }
 }
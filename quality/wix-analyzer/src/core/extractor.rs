@@ -4,6 +4,7 @@ use roxmltree::Node;
 use std::path::Path;
 
 use super::document::WixDocument;
+use super::flow::{extract_flows, PropertyFlow};
 use super::types::{
     DefinitionKind, Location, Range, ReferenceKind, SymbolDefinition, SymbolReference,
 };
@@ -13,6 +14,7 @@ use super::types::{
 pub struct ExtractionResult {
     pub definitions: Vec<SymbolDefinition>,
     pub references: Vec<SymbolReference>,
+    pub flows: Vec<PropertyFlow>,
 }
 
 impl ExtractionResult {
@@ -23,6 +25,7 @@ impl ExtractionResult {
     pub fn merge(&mut self, other: ExtractionResult) {
         self.definitions.extend(other.definitions);
         self.references.extend(other.references);
+        self.flows.extend(other.flows);
     }
 }
 
@@ -30,6 +33,7 @@ impl ExtractionResult {
 pub fn extract_symbols(doc: &WixDocument) -> ExtractionResult {
     let mut result = ExtractionResult::new();
     extract_from_node(doc.root(), doc, &mut result);
+    result.flows = extract_flows(doc);
     result
 }
 
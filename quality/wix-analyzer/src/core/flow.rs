@@ -0,0 +1,606 @@
+//! Property data-flow graph
+//!
+//! Models how values move between properties across a project. Edges come
+//! from `SetProperty`, type-51 custom actions and `Publish Property=`, with the
+//! formatted `[PROP]` references in their values as inputs. Sources are public
+//! properties (settable on the msiexec command line) and properties bound to
+//! dialog input controls; sinks are elevated deferred custom actions.
+
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::LazyLock;
+
+use super::document::WixDocument;
+use super::types::Location;
+
+/// Formatted property reference: `[PROP]` (not `[#file]`, `[!file]`, `[$comp]`, `[%ENV]`)
+static PROPERTY_REF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([A-Za-z_][A-Za-z0-9_.]*)\]").unwrap());
+
+/// Public properties set by Windows Installer itself rather than by users
+const BUILTIN_PUBLIC: &[&str] = &[
+    "TARGETDIR",
+    "ALLUSERS",
+    "MSIINSTALLPERUSER",
+    "REINSTALL",
+    "REINSTALLMODE",
+    "REMOVE",
+    "ADDLOCAL",
+    "ADDSOURCE",
+    "ADDDEFAULT",
+    "ADVERTISE",
+    "PATCH",
+    "TRANSFORMS",
+    "MSIFASTINSTALL",
+    "WIXUI_INSTALLDIR",
+];
+
+/// Control types whose `Property` receives user input
+const INPUT_CONTROLS: &[&str] = &[
+    "Edit",
+    "MaskedEdit",
+    "PathEdit",
+    "ComboBox",
+    "ListBox",
+    "ListView",
+    "RadioButtonGroup",
+    "CheckBox",
+    "DirectoryCombo",
+    "DirectoryList",
+];
+
+/// Element that assigns a property from other properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AssignmentKind {
+    /// `<SetProperty Id="X" Value="..."/>`
+    SetProperty,
+    /// Type-51 `<CustomAction Property="X" Value="..."/>`
+    CustomAction,
+    /// `<Publish Property="X" Value="..."/>` (UI only)
+    Publish,
+}
+
+impl AssignmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SetProperty => "SetProperty",
+            Self::CustomAction => "CustomAction",
+            Self::Publish => "Publish",
+        }
+    }
+}
+
+/// How an elevated custom action consumes a property
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SinkKind {
+    /// Referenced from `ExeCommand`, or the executable path of a type-50 action
+    ExeCommand,
+    /// Read as `CustomActionData` from the property named after the action
+    CustomActionData,
+}
+
+impl SinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExeCommand => "ExeCommand",
+            Self::CustomActionData => "CustomActionData",
+        }
+    }
+}
+
+/// A single flow fact extracted from one element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropertyFlow {
+    /// `<Property>` or directory definition
+    Define {
+        property: String,
+        directory: bool,
+        location: Location,
+    },
+    /// A property assigned from formatted inputs
+    Assign {
+        target: String,
+        inputs: Vec<String>,
+        kind: AssignmentKind,
+        location: Location,
+    },
+    /// A property bound to a dialog input control
+    Input {
+        property: String,
+        control: String,
+        location: Location,
+    },
+    /// An elevated custom action consuming properties
+    Sink {
+        action: String,
+        inputs: Vec<String>,
+        kind: SinkKind,
+        location: Location,
+    },
+}
+
+impl PropertyFlow {
+    /// Location of the element the fact was extracted from
+    pub fn location(&self) -> &Location {
+        match self {
+            Self::Define { location, .. }
+            | Self::Assign { location, .. }
+            | Self::Input { location, .. }
+            | Self::Sink { location, .. } => location,
+        }
+    }
+}
+
+/// Property names referenced as `[PROP]` in a formatted string
+pub fn formatted_references(value: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    PROPERTY_REF
+        .captures_iter(value)
+        .map(|c| c[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+/// Whether a property is public (all uppercase) and can be set on the command line
+pub fn is_public_property(name: &str) -> bool {
+    name.chars().any(|c| c.is_ascii_uppercase()) && !name.chars().any(|c| c.is_ascii_lowercase())
+}
+
+/// Extract property flow facts from a document
+pub fn extract_flows(doc: &WixDocument) -> Vec<PropertyFlow> {
+    let mut flows = Vec::new();
+    let location =
+        |node: &roxmltree::Node| Location::new(doc.file().to_path_buf(), doc.node_range(node));
+
+    for node in doc.root().descendants().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "Property" => {
+                if let Some(id) = node.attribute("Id") {
+                    flows.push(PropertyFlow::Define {
+                        property: id.to_string(),
+                        directory: false,
+                        location: location(&node),
+                    });
+                }
+            }
+            "Directory" | "StandardDirectory" | "DirectoryRef" => {
+                if let Some(id) = node.attribute("Id") {
+                    flows.push(PropertyFlow::Define {
+                        property: id.to_string(),
+                        directory: true,
+                        location: location(&node),
+                    });
+                }
+            }
+            "SetProperty" => {
+                if let (Some(id), Some(value)) = (node.attribute("Id"), node.attribute("Value")) {
+                    flows.push(PropertyFlow::Assign {
+                        target: id.to_string(),
+                        inputs: formatted_references(value),
+                        kind: AssignmentKind::SetProperty,
+                        location: location(&node),
+                    });
+                }
+            }
+            "Publish" => {
+                if let Some(target) = node.attribute("Property") {
+                    flows.push(PropertyFlow::Assign {
+                        target: target.to_string(),
+                        inputs: formatted_references(node.attribute("Value").unwrap_or("")),
+                        kind: AssignmentKind::Publish,
+                        location: location(&node),
+                    });
+                }
+            }
+            "Control" => {
+                let control_type = node.attribute("Type").unwrap_or("");
+                if let Some(property) = node.attribute("Property") {
+                    if INPUT_CONTROLS.contains(&control_type) {
+                        let dialog = node
+                            .ancestors()
+                            .find(|a| a.tag_name().name() == "Dialog")
+                            .and_then(|d| d.attribute("Id"));
+                        let id = node.attribute("Id").unwrap_or("?");
+                        flows.push(PropertyFlow::Input {
+                            property: property.to_string(),
+                            control: match dialog {
+                                Some(dialog) => format!("{}/{}", dialog, id),
+                                None => id.to_string(),
+                            },
+                            location: location(&node),
+                        });
+                    }
+                }
+            }
+            "CustomAction" => extract_custom_action(node, location(&node), &mut flows),
+            _ => {}
+        }
+    }
+
+    flows
+}
+
+fn extract_custom_action(node: roxmltree::Node, location: Location, flows: &mut Vec<PropertyFlow>) {
+    let Some(id) = node.attribute("Id") else {
+        return;
+    };
+    let exe_command = node.attribute("ExeCommand");
+
+    // Type 51: set a property from a formatted value
+    if let (Some(target), Some(value), None) = (
+        node.attribute("Property"),
+        node.attribute("Value"),
+        exe_command,
+    ) {
+        flows.push(PropertyFlow::Assign {
+            target: target.to_string(),
+            inputs: formatted_references(value),
+            kind: AssignmentKind::CustomAction,
+            location,
+        });
+        return;
+    }
+
+    // Deferred actions with Impersonate="no" run as LocalSystem
+    let execute = node.attribute("Execute").unwrap_or("immediate");
+    let elevated = matches!(execute, "deferred" | "commit" | "rollback")
+        && node.attribute("Impersonate") == Some("no");
+    if !elevated {
+        return;
+    }
+
+    if let Some(command) = exe_command {
+        let mut inputs = formatted_references(command);
+        // Type 50: the executable path itself comes from a property
+        if let Some(property) = node.attribute("Property") {
+            if !inputs.iter().any(|i| i == property) {
+                inputs.insert(0, property.to_string());
+            }
+        }
+        flows.push(PropertyFlow::Sink {
+            action: id.to_string(),
+            inputs,
+            kind: SinkKind::ExeCommand,
+            location: location.clone(),
+        });
+    }
+
+    flows.push(PropertyFlow::Sink {
+        action: id.to_string(),
+        inputs: vec![id.to_string()],
+        kind: SinkKind::CustomActionData,
+        location,
+    });
+}
+
+/// An assignment edge: `inputs` flow into `target`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub target: String,
+    pub inputs: Vec<String>,
+    pub kind: AssignmentKind,
+    pub location: Location,
+}
+
+/// An elevated custom action reading properties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowSink {
+    pub action: String,
+    pub inputs: Vec<String>,
+    pub kind: SinkKind,
+    pub location: Location,
+}
+
+/// Why a property is considered user-controllable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaintSource {
+    /// Public property, settable on the command line
+    PublicProperty { definition: Option<Location> },
+    /// Bound to a dialog input control
+    UserInput { control: String, location: Location },
+}
+
+/// A path from a user-controllable property to an elevated sink
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaintPath {
+    /// The tainted property the path starts from
+    pub property: String,
+    pub source: TaintSource,
+    /// Assignments in order from the source to the property read by the sink
+    pub steps: Vec<Assignment>,
+    pub sink: FlowSink,
+}
+
+impl TaintPath {
+    /// Property names along the path, source first
+    pub fn chain(&self) -> Vec<&str> {
+        std::iter::once(self.property.as_str())
+            .chain(self.steps.iter().map(|s| s.target.as_str()))
+            .collect()
+    }
+}
+
+/// Property flow graph built from the facts of all project files
+#[derive(Debug, Default)]
+pub struct PropertyFlowGraph {
+    definitions: HashMap<String, Location>,
+    directories: HashSet<String>,
+    assignments: HashMap<String, Vec<Assignment>>,
+    inputs: HashMap<String, (String, Location)>,
+    sinks: Vec<FlowSink>,
+}
+
+impl PropertyFlowGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a graph from flow facts
+    pub fn from_flows<'a>(flows: impl IntoIterator<Item = &'a PropertyFlow>) -> Self {
+        let mut graph = Self::new();
+        graph.extend(flows);
+        graph
+    }
+
+    /// Add flow facts to the graph
+    pub fn extend<'a>(&mut self, flows: impl IntoIterator<Item = &'a PropertyFlow>) {
+        for flow in flows {
+            self.add(flow.clone());
+        }
+    }
+
+    /// Add a single flow fact
+    pub fn add(&mut self, flow: PropertyFlow) {
+        match flow {
+            PropertyFlow::Define {
+                property,
+                directory,
+                location,
+            } => {
+                if directory {
+                    self.directories.insert(property);
+                } else {
+                    self.definitions.entry(property).or_insert(location);
+                }
+            }
+            PropertyFlow::Assign {
+                target,
+                inputs,
+                kind,
+                location,
+            } => {
+                self.assignments
+                    .entry(target.clone())
+                    .or_default()
+                    .push(Assignment {
+                        target,
+                        inputs,
+                        kind,
+                        location,
+                    });
+            }
+            PropertyFlow::Input {
+                property,
+                control,
+                location,
+            } => {
+                self.inputs.entry(property).or_insert((control, location));
+            }
+            PropertyFlow::Sink {
+                action,
+                inputs,
+                kind,
+                location,
+            } => self.sinks.push(FlowSink {
+                action,
+                inputs,
+                kind,
+                location,
+            }),
+        }
+    }
+
+    /// Assignments whose target is `property`
+    pub fn assignments_to(&self, property: &str) -> &[Assignment] {
+        self.assignments
+            .get(property)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Elevated custom action sinks
+    pub fn sinks(&self) -> &[FlowSink] {
+        &self.sinks
+    }
+
+    /// Why `property` is user-controllable, if it is
+    ///
+    /// Public properties count unless they are directories, set by Windows
+    /// Installer, or overwritten in the execute sequence by `SetProperty` or
+    /// a type-51 action (whose inputs are then followed instead).
+    pub fn source(&self, property: &str) -> Option<TaintSource> {
+        if let Some((control, location)) = self.inputs.get(property) {
+            return Some(TaintSource::UserInput {
+                control: control.clone(),
+                location: location.clone(),
+            });
+        }
+
+        let overwritten = self
+            .assignments_to(property)
+            .iter()
+            .any(|a| a.kind != AssignmentKind::Publish);
+        if is_public_property(property)
+            && !overwritten
+            && !self.directories.contains(property)
+            && !BUILTIN_PUBLIC.contains(&property)
+        {
+            return Some(TaintSource::PublicProperty {
+                definition: self.definitions.get(property).cloned(),
+            });
+        }
+
+        None
+    }
+
+    /// All paths from user-controllable properties into elevated sinks
+    ///
+    /// Each sink input is searched breadth-first backwards along assignments,
+    /// so every reachable source is reported once with its shortest chain.
+    pub fn taint_paths(&self) -> Vec<TaintPath> {
+        let mut paths = Vec::new();
+
+        for sink in &self.sinks {
+            let mut reported = HashSet::new();
+            for input in &sink.inputs {
+                // property -> assignment that carries it one step closer to the sink
+                let mut next: HashMap<&str, Option<&Assignment>> = HashMap::new();
+                let mut queue = VecDeque::new();
+                next.insert(input.as_str(), None);
+                queue.push_back(input.as_str());
+
+                while let Some(property) = queue.pop_front() {
+                    if let Some(source) = self.source(property) {
+                        if reported.insert(property.to_string()) {
+                            let mut steps = Vec::new();
+                            let mut current = property;
+                            while let Some(Some(assignment)) = next.get(current) {
+                                steps.push((*assignment).clone());
+                                current = assignment.target.as_str();
+                            }
+                            paths.push(TaintPath {
+                                property: property.to_string(),
+                                source,
+                                steps,
+                                sink: sink.clone(),
+                            });
+                        }
+                        continue;
+                    }
+
+                    for assignment in self.assignments_to(property) {
+                        for upstream in &assignment.inputs {
+                            if !next.contains_key(upstream.as_str()) {
+                                next.insert(upstream.as_str(), Some(assignment));
+                                queue.push_back(upstream.as_str());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn graph(source: &str) -> PropertyFlowGraph {
+        let doc = WixDocument::parse(source, Path::new("test.wxs")).unwrap();
+        PropertyFlowGraph::from_flows(&extract_flows(&doc))
+    }
+
+    #[test]
+    fn test_formatted_references() {
+        assert_eq!(
+            formatted_references("[#file] [A] [!b] [$C] [%PATH] \"[INSTALLDIR]x\" [A]"),
+            vec!["A", "INSTALLDIR"]
+        );
+    }
+
+    #[test]
+    fn test_is_public_property() {
+        assert!(is_public_property("USER_NAME"));
+        assert!(is_public_property("PORT2"));
+        assert!(!is_public_property("UserName"));
+        assert!(!is_public_property("_1"));
+    }
+
+    #[test]
+    fn test_set_property_chain_to_custom_action_data() {
+        let g = graph(
+            r#"<Wix>
+                <Property Id="SERVICEARGS" />
+                <SetProperty Id="Args" Value="--cfg [SERVICEARGS]" Before="InstallFiles" />
+                <SetProperty Id="RunTool" Value="[Args] [INSTALLFOLDER]" Before="RunTool" />
+                <StandardDirectory Id="ProgramFiles6432Folder">
+                    <Directory Id="INSTALLFOLDER" Name="App" />
+                </StandardDirectory>
+                <CustomAction Id="RunTool" DllEntry="Run" BinaryRef="Ca" Execute="deferred" Impersonate="no" />
+            </Wix>"#,
+        );
+
+        let paths = g.taint_paths();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].chain(), vec!["SERVICEARGS", "Args", "RunTool"]);
+        assert_eq!(paths[0].sink.kind, SinkKind::CustomActionData);
+        assert!(matches!(
+            paths[0].source,
+            TaintSource::PublicProperty {
+                definition: Some(_)
+            }
+        ));
+    }
+
+    #[test]
+    fn test_dialog_input_into_exe_command() {
+        let g = graph(
+            r#"<Wix>
+                <Dialog Id="ConfigDlg">
+                    <Control Id="Host" Type="Edit" Property="hostName" />
+                </Dialog>
+                <CustomAction Id="Register" ExeCommand="reg.exe /h [hostName]" Directory="INSTALLFOLDER"
+                              Execute="deferred" Impersonate="no" />
+            </Wix>"#,
+        );
+
+        let paths = g.taint_paths();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].sink.kind, SinkKind::ExeCommand);
+        assert!(matches!(
+            &paths[0].source,
+            TaintSource::UserInput { control, .. } if control == "ConfigDlg/Host"
+        ));
+    }
+
+    #[test]
+    fn test_overwritten_public_property_is_not_source() {
+        let g = graph(
+            r#"<Wix>
+                <CustomAction Id="SetCmd" Property="CMD" Value="notepad.exe" />
+                <CustomAction Id="Run" ExeCommand="[CMD]" Directory="X" Execute="deferred" Impersonate="no" />
+            </Wix>"#,
+        );
+        assert!(g.taint_paths().is_empty());
+    }
+
+    #[test]
+    fn test_impersonated_action_is_not_sink() {
+        let g = graph(
+            r#"<Wix>
+                <CustomAction Id="Run" ExeCommand="[USERCMD]" Directory="X" Execute="deferred" />
+            </Wix>"#,
+        );
+        assert!(g.sinks().is_empty());
+        assert!(g.taint_paths().is_empty());
+    }
+
+    #[test]
+    fn test_cycles_terminate() {
+        let g = graph(
+            r#"<Wix>
+                <SetProperty Id="a" Value="[b]" Before="X" />
+                <SetProperty Id="b" Value="[a][TOKEN]" Before="X" />
+                <SetProperty Id="Run" Value="[a]" Before="Run" />
+                <CustomAction Id="Run" DllEntry="Run" BinaryRef="Ca" Execute="deferred" Impersonate="no" />
+            </Wix>"#,
+        );
+        let paths = g.taint_paths();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].chain(), vec!["TOKEN", "b", "a", "Run"]);
+    }
+}
@@ -26,6 +26,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::core::debt::{DebtRating, TechnicalDebt};
+use crate::core::history::{HistoryMetric, RunMetrics};
 use crate::core::{AnalysisResult, IssueType, Severity};
 
 /// Quality gate with configurable conditions
@@ -160,11 +161,36 @@ impl QualityGate {
 
     /// Evaluate the quality gate against analysis results
     pub fn evaluate(&self, results: &[AnalysisResult], lines_of_code: usize) -> GateResult {
-        let debt = TechnicalDebt::from_results(results, lines_of_code);
+        self.evaluate_against(results, lines_of_code, None)
+    }
+
+    /// Evaluate the quality gate, checking delta conditions against a
+    /// reference snapshot (e.g. the latest main-branch run in the history).
+    /// Delta conditions pass when there is no reference.
+    pub fn evaluate_against(
+        &self,
+        results: &[AnalysisResult],
+        lines_of_code: usize,
+        reference: Option<&RunMetrics>,
+    ) -> GateResult {
+        let current = RunMetrics::from_results(results, lines_of_code);
+        self.evaluate_run(results, &current, reference)
+    }
+
+    /// Evaluate the quality gate with the metrics of the current run, which
+    /// may carry measurements beyond the results such as the install size
+    pub fn evaluate_run(
+        &self,
+        results: &[AnalysisResult],
+        current: &RunMetrics,
+        reference: Option<&RunMetrics>,
+    ) -> GateResult {
+        let debt = TechnicalDebt::from_results(results, current.lines_of_code);
         let mut failures = Vec::new();
 
         for condition in &self.conditions {
-            if let Some(failure) = condition.check(results, &debt) {
+            let delta = reference.map(|r| (r, current));
+            if let Some(failure) = condition.check(results, &debt, delta) {
                 failures.push(failure);
             }
         }
@@ -198,6 +224,13 @@ pub enum GateCondition {
     MaxDebtMinutes { max: u32 },
     /// Maximum complexity rating
     MaxComplexityRating { max: char },
+    /// Maximum increase of a metric versus a reference snapshot
+    MaxIncrease { metric: HistoryMetric, max: f64 },
+    /// Maximum relative increase of a metric versus a reference snapshot
+    MaxGrowth {
+        metric: HistoryMetric,
+        max_percent: f64,
+    },
 }
 
 impl GateCondition {
@@ -263,8 +296,32 @@ impl GateCondition {
         }
     }
 
+    /// No increase of a metric versus the reference snapshot
+    pub fn no_increase(metric: HistoryMetric) -> Self {
+        Self::MaxIncrease { metric, max: 0.0 }
+    }
+
+    /// Growth of a metric by at most `max_percent` versus the reference
+    /// snapshot, e.g. install size +10%
+    pub fn max_growth(metric: HistoryMetric, max_percent: f64) -> Self {
+        Self::MaxGrowth {
+            metric,
+            max_percent,
+        }
+    }
+
+    /// Whether this condition compares against a reference snapshot
+    pub fn is_delta(&self) -> bool {
+        matches!(self, Self::MaxIncrease { .. } | Self::MaxGrowth { .. })
+    }
+
     /// Check if condition passes, returns failure message if not
-    fn check(&self, results: &[AnalysisResult], debt: &TechnicalDebt) -> Option<GateFailure> {
+    fn check(
+        &self,
+        results: &[AnalysisResult],
+        debt: &TechnicalDebt,
+        delta: Option<(&RunMetrics, &RunMetrics)>,
+    ) -> Option<GateFailure> {
         match self {
             Self::MaxIssueCount {
                 severity,
@@ -321,6 +378,43 @@ impl GateCondition {
                 // For now, always passes
                 None
             }
+            Self::MaxIncrease { metric, max } => {
+                let (reference, current) = delta?;
+                let before = metric.value(reference)?;
+                let after = metric.value(current)?;
+                if after - before > *max {
+                    Some(GateFailure {
+                        condition: self.description(),
+                        expected: format!("<= {}", metric.format(before + max)),
+                        actual: metric.format(after),
+                    })
+                } else {
+                    None
+                }
+            }
+            Self::MaxGrowth {
+                metric,
+                max_percent,
+            } => {
+                let (reference, current) = delta?;
+                let before = metric.value(reference)?;
+                let after = metric.value(current)?;
+                let limit = before * (1.0 + max_percent / 100.0);
+                if after > limit {
+                    let growth = if before > 0.0 {
+                        format!(" (+{:.1}%)", (after - before) / before * 100.0)
+                    } else {
+                        String::new()
+                    };
+                    Some(GateFailure {
+                        condition: self.description(),
+                        expected: format!("<= {}", metric.format(limit)),
+                        actual: format!("{}{}", metric.format(after), growth),
+                    })
+                } else {
+                    None
+                }
+            }
         }
     }
 
@@ -357,6 +451,18 @@ impl GateCondition {
             Self::MaxComplexityRating { max } => {
                 format!("Complexity rating <= {}", max)
             }
+            Self::MaxIncrease { metric, max } if *max == 0.0 => {
+                format!("No increase in {}", metric.as_str().to_lowercase())
+            }
+            Self::MaxIncrease { metric, max } => {
+                format!("{} increase <= {}", metric.as_str(), max)
+            }
+            Self::MaxGrowth {
+                metric,
+                max_percent,
+            } => {
+                format!("{} growth <= {:.1}%", metric.as_str(), max_percent)
+            }
         }
     }
 }
@@ -459,7 +565,11 @@ fn count_issues(
         .count()
 }
 
-fn get_rating(results: &[AnalysisResult], debt: &TechnicalDebt, rating_type: RatingType) -> char {
+pub(crate) fn get_rating(
+    results: &[AnalysisResult],
+    debt: &TechnicalDebt,
+    rating_type: RatingType,
+) -> char {
     match rating_type {
         RatingType::Security => {
             let vuln_count = results
@@ -703,4 +813,54 @@ mod tests {
         assert_eq!(get_rating(&results, &debt, RatingType::Security), 'A');
         assert_eq!(get_rating(&results, &debt, RatingType::Reliability), 'A');
     }
+
+    #[test]
+    fn test_no_increase_versus_reference() {
+        let gate = QualityGate::new("Delta")
+            .with_condition(GateCondition::no_increase(HistoryMetric::HighIssues));
+        let bug = || Diagnostic::high("BUG-001", IssueType::Bug, "Bug", make_location());
+
+        let main = RunMetrics::from_results(&make_results(vec![bug()]), 100);
+        let results = make_results(vec![bug(), bug()]);
+
+        // Without a reference snapshot, delta conditions pass
+        assert!(gate.evaluate(&results, 100).passed);
+
+        let result = gate.evaluate_against(&results, 100, Some(&main));
+        assert!(!result.passed);
+        assert_eq!(
+            result.failures[0].condition,
+            "No increase in high-severity issues"
+        );
+        assert_eq!(result.failures[0].expected, "<= 1");
+        assert_eq!(result.failures[0].actual, "2");
+
+        let fixed = make_results(vec![bug()]);
+        assert!(gate.evaluate_against(&fixed, 100, Some(&main)).passed);
+    }
+
+    #[test]
+    fn test_install_size_growth() {
+        let gate = QualityGate::new("Footprint")
+            .with_condition(GateCondition::max_growth(HistoryMetric::InstallSize, 10.0));
+        let results = make_results(vec![]);
+        let run = |size: Option<u64>| RunMetrics {
+            install_size: size,
+            ..RunMetrics::from_results(&results, 100)
+        };
+        let main = run(Some(10 * 1024 * 1024));
+
+        assert!(
+            gate.evaluate_run(&results, &run(Some(11 * 1024 * 1024)), Some(&main))
+                .passed
+        );
+        // Unmeasured on either side passes
+        assert!(gate.evaluate_run(&results, &run(None), Some(&main)).passed);
+
+        let result = gate.evaluate_run(&results, &run(Some(12 * 1024 * 1024)), Some(&main));
+        assert!(!result.passed);
+        assert_eq!(result.failures[0].condition, "Install size growth <= 10.0%");
+        assert_eq!(result.failures[0].expected, "<= 11.0 MB");
+        assert_eq!(result.failures[0].actual, "12.0 MB (+20.0%)");
+    }
 }
@@ -0,0 +1,653 @@
+//! Quality history - metrics of past runs for trend reports
+//!
+//! Each recorded run appends one JSON line to
+//! `.wix-analyzer/history/runs.jsonl`, keyed by git commit, branch and date.
+//! Trends show how issues, debt, ratings, duplication and install size
+//! change over time, and quality gates can compare against an earlier
+//! snapshot.
+//!
+//! # History File Format
+//!
+//! ```json
+//! {"timestamp":"2024-01-15T10:30:00Z","commit":"3f2a...","branch":"main","metrics":{"issues":12,...}}
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::debt::{DebtRating, SeverityCounts, TechnicalDebt};
+use super::duplication::DuplicationResult;
+use super::gate::{get_rating, GateResult, RatingType};
+use super::types::AnalysisResult;
+use crate::footprint::{format_size, Footprint};
+
+/// Default history directory, relative to the project root
+pub const HISTORY_DIR_NAME: &str = ".wix-analyzer/history";
+
+/// File holding one run per line
+const HISTORY_FILE_NAME: &str = "runs.jsonl";
+
+/// Metrics recorded for one analysis run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunMetrics {
+    /// Files analyzed
+    pub files: usize,
+    /// Lines of code analyzed
+    pub lines_of_code: usize,
+    /// Total issues
+    pub issues: usize,
+    /// Issues by severity
+    pub by_severity: SeverityCounts,
+    /// Technical debt in minutes
+    pub debt_minutes: u32,
+    /// Debt ratio (percentage)
+    pub debt_ratio: f64,
+    /// Technical debt rating
+    pub debt_rating: DebtRating,
+    pub security_rating: char,
+    pub reliability_rating: char,
+    pub maintainability_rating: char,
+    /// Duplicated lines, when duplication was measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicated_lines: Option<usize>,
+    /// Duplication percentage, when duplication was measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplication_percentage: Option<f64>,
+    /// Disk space of a full install in bytes, when the payload was measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_size: Option<u64>,
+    /// Quality gate outcome, when a gate was evaluated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate_passed: Option<bool>,
+}
+
+impl RunMetrics {
+    /// Compute metrics from analysis results
+    pub fn from_results(results: &[AnalysisResult], lines_of_code: usize) -> Self {
+        let debt = TechnicalDebt::from_results(results, lines_of_code);
+        Self {
+            files: results.iter().map(|r| r.files.len()).sum(),
+            lines_of_code,
+            issues: results.iter().map(|r| r.diagnostics.len()).sum(),
+            by_severity: debt.issues_by_severity.clone(),
+            debt_minutes: debt.total_minutes,
+            debt_ratio: debt.debt_ratio,
+            debt_rating: debt.rating,
+            security_rating: get_rating(results, &debt, RatingType::Security),
+            reliability_rating: get_rating(results, &debt, RatingType::Reliability),
+            maintainability_rating: get_rating(results, &debt, RatingType::Maintainability),
+            duplicated_lines: None,
+            duplication_percentage: None,
+            install_size: None,
+            gate_passed: None,
+        }
+    }
+
+    /// Set the number of files analyzed (results only list files with issues)
+    pub fn with_files(mut self, files: usize) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Add duplication metrics
+    pub fn with_duplication(mut self, duplication: &DuplicationResult) -> Self {
+        self.duplicated_lines = Some(duplication.duplicated_lines);
+        self.duplication_percentage = Some(duplication.duplication_percentage);
+        self
+    }
+
+    /// Add the install size of the package payload
+    pub fn with_footprint(mut self, footprint: &Footprint) -> Self {
+        self.install_size = Some(footprint.total_disk);
+        self
+    }
+
+    /// Add the quality gate outcome
+    pub fn with_gate(mut self, gate: &GateResult) -> Self {
+        self.gate_passed = Some(gate.passed);
+        self
+    }
+}
+
+/// A metric that can be tracked over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryMetric {
+    /// Total issues
+    Issues,
+    /// Blocker issues
+    BlockerIssues,
+    /// High-severity issues (high and blocker)
+    HighIssues,
+    /// Technical debt in minutes
+    DebtMinutes,
+    /// Technical debt ratio
+    DebtRatio,
+    /// Duplicated lines
+    DuplicatedLines,
+    /// Security rating (A = 1 .. E = 5)
+    SecurityRating,
+    /// Reliability rating (A = 1 .. E = 5)
+    ReliabilityRating,
+    /// Maintainability rating (A = 1 .. E = 5)
+    MaintainabilityRating,
+    /// Install size in bytes
+    InstallSize,
+}
+
+impl HistoryMetric {
+    /// Metrics shown in trend reports
+    pub const ALL: [HistoryMetric; 10] = [
+        Self::Issues,
+        Self::BlockerIssues,
+        Self::HighIssues,
+        Self::DebtMinutes,
+        Self::DebtRatio,
+        Self::DuplicatedLines,
+        Self::SecurityRating,
+        Self::ReliabilityRating,
+        Self::MaintainabilityRating,
+        Self::InstallSize,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Issues => "Issues",
+            Self::BlockerIssues => "Blocker issues",
+            Self::HighIssues => "High-severity issues",
+            Self::DebtMinutes => "Technical debt (min)",
+            Self::DebtRatio => "Debt ratio (%)",
+            Self::DuplicatedLines => "Duplicated lines",
+            Self::SecurityRating => "Security rating",
+            Self::ReliabilityRating => "Reliability rating",
+            Self::MaintainabilityRating => "Maintainability rating",
+            Self::InstallSize => "Install size",
+        }
+    }
+
+    /// Value of this metric in a run; `None` when it was not measured
+    pub fn value(&self, metrics: &RunMetrics) -> Option<f64> {
+        let rating = |c: char| (c as u32).checked_sub('A' as u32).map(|v| v as f64 + 1.0);
+        match self {
+            Self::Issues => Some(metrics.issues as f64),
+            Self::BlockerIssues => Some(metrics.by_severity.blocker as f64),
+            Self::HighIssues => {
+                Some((metrics.by_severity.blocker + metrics.by_severity.high) as f64)
+            }
+            Self::DebtMinutes => Some(metrics.debt_minutes as f64),
+            Self::DebtRatio => Some(metrics.debt_ratio),
+            Self::DuplicatedLines => metrics.duplicated_lines.map(|l| l as f64),
+            Self::SecurityRating => rating(metrics.security_rating),
+            Self::ReliabilityRating => rating(metrics.reliability_rating),
+            Self::MaintainabilityRating => rating(metrics.maintainability_rating),
+            Self::InstallSize => metrics.install_size.map(|s| s as f64),
+        }
+    }
+
+    /// Format a value of this metric
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            Self::DebtRatio => format!("{:.1}", value),
+            Self::SecurityRating | Self::ReliabilityRating | Self::MaintainabilityRating => {
+                char::from_u32('A' as u32 + (value as u32).saturating_sub(1))
+                    .unwrap_or('?')
+                    .to_string()
+            }
+            Self::InstallSize => format_size(value.max(0.0) as u64),
+            _ => format!("{}", value as i64),
+        }
+    }
+}
+
+/// One recorded run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    pub metrics: RunMetrics,
+}
+
+impl HistoryEntry {
+    /// Create an entry timestamped now
+    pub fn new(metrics: RunMetrics) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            commit: None,
+            branch: None,
+            metrics,
+        }
+    }
+
+    /// Key the entry by a commit and branch
+    pub fn with_commit(mut self, commit: impl Into<String>, branch: Option<String>) -> Self {
+        self.commit = Some(commit.into());
+        self.branch = branch;
+        self
+    }
+
+    /// Key the entry by the git HEAD of `workdir`, when it is a repository
+    pub fn with_git(mut self, workdir: &Path) -> Self {
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(workdir)
+                .output()
+                .ok()
+                .filter(|o| o.status.success())
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+        self.commit = git(&["rev-parse", "HEAD"]);
+        self.branch = git(&["rev-parse", "--abbrev-ref", "HEAD"]).filter(|b| b != "HEAD");
+        self
+    }
+
+    /// Short commit hash for display
+    pub fn short_commit(&self) -> &str {
+        self.commit
+            .as_deref()
+            .map(|c| &c[..c.len().min(8)])
+            .unwrap_or("-")
+    }
+}
+
+/// Errors reading or writing the history
+#[derive(Debug)]
+pub enum HistoryError {
+    ReadError(PathBuf, String),
+    WriteError(PathBuf, String),
+    ParseError(PathBuf, usize, String),
+}
+
+impl std::fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadError(path, msg) => {
+                write!(f, "Failed to read history '{}': {}", path.display(), msg)
+            }
+            Self::WriteError(path, msg) => {
+                write!(f, "Failed to write history '{}': {}", path.display(), msg)
+            }
+            Self::ParseError(path, line, msg) => {
+                write!(
+                    f,
+                    "Failed to parse history '{}' line {}: {}",
+                    path.display(),
+                    line,
+                    msg
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for HistoryError {}
+
+/// Local history of analysis runs
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    /// History of the project rooted at `root`
+    pub fn open(root: &Path) -> Self {
+        Self::at(root.join(HISTORY_DIR_NAME))
+    }
+
+    /// History in an explicit directory
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file(&self) -> PathBuf {
+        self.dir.join(HISTORY_FILE_NAME)
+    }
+
+    /// Whether any run has been recorded
+    pub fn exists(&self) -> bool {
+        self.file().is_file()
+    }
+
+    /// Append a run
+    pub fn record(&self, entry: &HistoryEntry) -> Result<(), HistoryError> {
+        let path = self.file();
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| HistoryError::WriteError(self.dir.clone(), e.to_string()))?;
+        let line = serde_json::to_string(entry)
+            .map_err(|e| HistoryError::WriteError(path.clone(), e.to_string()))?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| HistoryError::WriteError(path.clone(), e.to_string()))?;
+        writeln!(file, "{}", line).map_err(|e| HistoryError::WriteError(path, e.to_string()))
+    }
+
+    /// All runs, oldest first
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let path = self.file();
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| HistoryError::ReadError(path.clone(), e.to_string()))?;
+
+        let mut entries = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| HistoryError::ParseError(path.clone(), i + 1, e.to_string()))
+            })
+            .collect::<Result<Vec<HistoryEntry>, _>>()?;
+        entries.sort_by_key(|e| e.timestamp);
+        Ok(entries)
+    }
+
+    /// Runs recorded on a branch, oldest first
+    pub fn entries_on_branch(&self, branch: &str) -> Result<Vec<HistoryEntry>, HistoryError> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|e| e.branch.as_deref() == Some(branch))
+            .collect())
+    }
+
+    /// Latest run on a branch, e.g. the main-branch snapshot for delta gates
+    pub fn latest_on_branch(&self, branch: &str) -> Result<Option<HistoryEntry>, HistoryError> {
+        Ok(self.entries_on_branch(branch)?.pop())
+    }
+
+    /// Latest run for a commit (full or abbreviated hash)
+    pub fn latest_for_commit(&self, commit: &str) -> Result<Option<HistoryEntry>, HistoryError> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .rev()
+            .find(|e| e.commit.as_deref().is_some_and(|c| c.starts_with(commit))))
+    }
+}
+
+/// Change of metrics across a series of runs
+#[derive(Debug, Clone)]
+pub struct Trend {
+    entries: Vec<HistoryEntry>,
+}
+
+impl Trend {
+    /// Trend over runs, oldest first
+    pub fn new(entries: Vec<HistoryEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Keep only the last `n` runs
+    pub fn last(mut self, n: usize) -> Self {
+        let skip = self.entries.len().saturating_sub(n);
+        self.entries.drain(..skip);
+        self
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Values of a metric in the runs that measured it
+    pub fn series(&self, metric: HistoryMetric) -> Vec<f64> {
+        self.entries
+            .iter()
+            .filter_map(|e| metric.value(&e.metrics))
+            .collect()
+    }
+
+    /// Change of a metric from the first to the last run
+    pub fn delta(&self, metric: HistoryMetric) -> Option<f64> {
+        let series = self.series(metric);
+        Some(series.last()? - series.first()?)
+    }
+
+    /// Text report: one row per run, then per-metric sparklines
+    pub fn format_text(&self) -> String {
+        let mut out = String::new();
+
+        if self.entries.is_empty() {
+            out.push_str(
+                "No history recorded yet. Run `wix-analyzer --history <paths>` to start.\n",
+            );
+            return out;
+        }
+
+        out.push_str("Quality Trend\n");
+        out.push_str("=============\n\n");
+        out.push_str(&format!(
+            "{:<17} {:<9} {:<12} {:>6} {:>5} {:>10} {:>5} {:>6}\n",
+            "Date", "Commit", "Branch", "Issues", "High", "Debt", "S/R/M", "Dup%"
+        ));
+        for entry in &self.entries {
+            let m = &entry.metrics;
+            out.push_str(&format!(
+                "{:<17} {:<9} {:<12} {:>6} {:>5} {:>10} {:>5} {:>6}\n",
+                entry.timestamp.format("%Y-%m-%d %H:%M"),
+                entry.short_commit(),
+                entry.branch.as_deref().unwrap_or("-"),
+                m.issues,
+                m.by_severity.blocker + m.by_severity.high,
+                super::debt::format_duration(m.debt_minutes),
+                format!(
+                    "{}/{}/{}",
+                    m.security_rating, m.reliability_rating, m.maintainability_rating
+                ),
+                m.duplication_percentage
+                    .map(|p| format!("{:.1}", p))
+                    .unwrap_or_else(|| "-".to_string()),
+            ));
+        }
+
+        out.push_str("\nChanges\n");
+        out.push_str("-------\n");
+        for metric in HistoryMetric::ALL {
+            let series = self.series(metric);
+            let (Some(first), Some(last)) = (series.first(), series.last()) else {
+                continue;
+            };
+            let delta = last - first;
+            let direction = if delta > 0.0 {
+                "worse"
+            } else if delta < 0.0 {
+                "better"
+            } else {
+                "unchanged"
+            };
+            out.push_str(&format!(
+                "  {:<24} {} {} -> {} ({})\n",
+                metric.as_str(),
+                sparkline(&series),
+                metric.format(*first),
+                metric.format(*last),
+                direction
+            ));
+        }
+
+        out
+    }
+
+    /// JSON report with the runs and per-metric deltas
+    pub fn format_json(&self) -> String {
+        let deltas: serde_json::Map<String, serde_json::Value> = HistoryMetric::ALL
+            .iter()
+            .filter_map(|m| {
+                let key = serde_json::to_value(m).ok()?.as_str()?.to_string();
+                Some((key, serde_json::json!(self.delta(*m)?)))
+            })
+            .collect();
+        let report = serde_json::json!({
+            "runs": self.entries,
+            "deltas": deltas,
+        });
+        serde_json::to_string_pretty(&report).unwrap_or_default()
+    }
+}
+
+/// Unicode sparkline of a series
+pub fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max > min {
+                BARS[(((v - min) / (max - min)) * 7.0).round() as usize]
+            } else {
+                BARS[0]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Category, Diagnostic, Location, Position, Range};
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    fn results(high: usize, low: usize) -> Vec<AnalysisResult> {
+        let location = Location::new(
+            PathBuf::from("test.wxs"),
+            Range::new(Position::new(1, 1), Position::new(1, 10)),
+        );
+        let mut result = AnalysisResult::new();
+        result.add_file(PathBuf::from("test.wxs"));
+        for _ in 0..high {
+            result.add(Diagnostic::error(
+                "VAL-001",
+                Category::Validation,
+                "bug",
+                location.clone(),
+            ));
+        }
+        for _ in 0..low {
+            result.add(Diagnostic::info(
+                "BP-001",
+                Category::BestPractice,
+                "smell",
+                location.clone(),
+            ));
+        }
+        vec![result]
+    }
+
+    fn entry(day: u32, branch: &str, high: usize) -> HistoryEntry {
+        let mut entry = HistoryEntry::new(RunMetrics::from_results(&results(high, 1), 100))
+            .with_commit(format!("{:040}", day), Some(branch.to_string()));
+        entry.timestamp = Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap();
+        entry
+    }
+
+    #[test]
+    fn test_run_metrics_from_results() {
+        let metrics = RunMetrics::from_results(&results(2, 3), 200);
+        assert_eq!(metrics.issues, 5);
+        assert_eq!(metrics.by_severity.high, 2);
+        assert_eq!(metrics.reliability_rating, 'B');
+        assert_eq!(HistoryMetric::HighIssues.value(&metrics), Some(2.0));
+        assert_eq!(HistoryMetric::ReliabilityRating.value(&metrics), Some(2.0));
+        assert_eq!(HistoryMetric::DuplicatedLines.value(&metrics), None);
+        assert_eq!(HistoryMetric::InstallSize.value(&metrics), None);
+        assert_eq!(HistoryMetric::InstallSize.format(1536.0), "1.5 KB");
+    }
+
+    #[test]
+    fn test_record_and_read_back() {
+        let temp = TempDir::new().unwrap();
+        let store = HistoryStore::open(temp.path());
+        assert!(!store.exists());
+        assert!(store.entries().unwrap().is_empty());
+
+        // Recorded out of order, read back by date
+        store.record(&entry(3, "feature", 4)).unwrap();
+        store.record(&entry(1, "main", 1)).unwrap();
+        store.record(&entry(2, "main", 2)).unwrap();
+
+        assert!(store.dir().ends_with(HISTORY_DIR_NAME));
+        let entries = store.entries().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].timestamp.format("%d").to_string(), "01");
+
+        let main = store.latest_on_branch("main").unwrap().unwrap();
+        assert_eq!(main.metrics.by_severity.high, 2);
+        assert!(store.latest_on_branch("release").unwrap().is_none());
+
+        let by_commit = store.latest_for_commit("0000000").unwrap().unwrap();
+        assert_eq!(by_commit.branch.as_deref(), Some("feature"));
+    }
+
+    #[test]
+    fn test_corrupt_line_is_reported() {
+        let temp = TempDir::new().unwrap();
+        let store = HistoryStore::open(temp.path());
+        store.record(&entry(1, "main", 1)).unwrap();
+        fs::write(
+            store.dir().join(HISTORY_FILE_NAME),
+            format!(
+                "{}\nnot json\n",
+                serde_json::to_string(&entry(1, "main", 1)).unwrap()
+            ),
+        )
+        .unwrap();
+
+        match store.entries() {
+            Err(HistoryError::ParseError(_, line, _)) => assert_eq!(line, 2),
+            other => panic!("expected parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_trend_deltas_and_text() {
+        let trend = Trend::new(vec![
+            entry(1, "main", 1),
+            entry(2, "main", 3),
+            entry(3, "main", 2),
+        ]);
+        assert_eq!(trend.series(HistoryMetric::HighIssues), vec![1.0, 3.0, 2.0]);
+        assert_eq!(trend.delta(HistoryMetric::HighIssues), Some(1.0));
+
+        let text = trend.format_text();
+        assert!(text.contains("2024-01-02 12:00"));
+        assert!(text.contains("High-severity issues"));
+        assert!(text.contains("▁█▅"));
+        assert!(!text.contains("Duplicated lines"));
+
+        let last = trend.last(2);
+        assert_eq!(last.entries().len(), 2);
+        assert_eq!(last.delta(HistoryMetric::HighIssues), Some(-1.0));
+
+        let json: serde_json::Value = serde_json::from_str(&last.format_json()).unwrap();
+        assert_eq!(json["runs"].as_array().unwrap().len(), 2);
+        assert_eq!(json["deltas"]["high_issues"], -1.0);
+    }
+
+    #[test]
+    fn test_sparkline_flat() {
+        assert_eq!(sparkline(&[2.0, 2.0]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
@@ -6,6 +6,7 @@ use std::path::Path;
 use walkdir::WalkDir;
 
 use super::extractor::{extract_from_source, ExtractionResult};
+use super::flow::{PropertyFlow, PropertyFlowGraph};
 use super::types::{DefinitionKind, ReferenceKind, SymbolDefinition, SymbolReference};
 
 /// Cross-file symbol index for WiX projects
@@ -15,6 +16,8 @@ pub struct SymbolIndex {
     definitions: HashMap<String, HashMap<String, SymbolDefinition>>,
     /// References grouped by canonical type, then by id
     references: HashMap<String, HashMap<String, Vec<SymbolReference>>>,
+    /// Property flow facts from every indexed file
+    flows: Vec<PropertyFlow>,
 }
 
 impl SymbolIndex {
@@ -29,6 +32,30 @@ impl SymbolIndex {
         Ok(())
     }
 
+    /// Replace everything indexed from `file` with the symbols in `source`
+    ///
+    /// Used by editors, which re-index a buffer on every change.
+    pub fn reindex_source(&mut self, source: &str, file: &Path) -> Result<(), String> {
+        let result = extract_from_source(source, file)?;
+        self.remove_file(file);
+        self.add_extraction_result(result);
+        Ok(())
+    }
+
+    /// Drop all definitions, references and flows from a file
+    pub fn remove_file(&mut self, file: &Path) {
+        for defs in self.definitions.values_mut() {
+            defs.retain(|_, def| def.location.file != file);
+        }
+        for refs in self.references.values_mut() {
+            for list in refs.values_mut() {
+                list.retain(|r| r.location.file != file);
+            }
+            refs.retain(|_, list| !list.is_empty());
+        }
+        self.flows.retain(|flow| flow.location().file != file);
+    }
+
     /// Index a file from disk
     pub fn index_file(&mut self, path: &Path) -> Result<(), String> {
         let source = fs::read_to_string(path)
@@ -74,6 +101,8 @@ impl SymbolIndex {
                 .or_default()
                 .push(reference);
         }
+
+        self.flows.extend(result.flows);
     }
 
     /// Add a definition manually (for external/builtin definitions)
@@ -150,15 +179,26 @@ impl SymbolIndex {
             .collect()
     }
 
+    /// Get all property flow facts
+    pub fn property_flows(&self) -> &[PropertyFlow] {
+        &self.flows
+    }
+
+    /// Build the project-wide property flow graph
+    pub fn property_flow_graph(&self) -> PropertyFlowGraph {
+        PropertyFlowGraph::from_flows(&self.flows)
+    }
+
     /// Clear the index
     pub fn clear(&mut self) {
         self.definitions.clear();
         self.references.clear();
+        self.flows.clear();
     }
 }
 
 /// Get canonical type for a reference kind
-fn canonical_type_for_reference(kind: &ReferenceKind) -> String {
+pub(crate) fn canonical_type_for_reference(kind: &ReferenceKind) -> String {
     match kind {
         ReferenceKind::ComponentRef | ReferenceKind::ComponentGroupRef => "Component",
         ReferenceKind::DirectoryRef => "Directory",
@@ -171,7 +211,7 @@ fn canonical_type_for_reference(kind: &ReferenceKind) -> String {
 }
 
 /// Get canonical type for lookup (handles both reference and definition element names)
-fn canonical_type_for_lookup(element_type: &str) -> &str {
+pub(crate) fn canonical_type_for_lookup(element_type: &str) -> &str {
     match element_type {
         "ComponentRef" | "ComponentGroupRef" | "Component" | "ComponentGroup" => "Component",
         "DirectoryRef" | "Directory" | "StandardDirectory" => "Directory",
@@ -324,6 +364,37 @@ mod tests {
         assert_eq!(refs[0].location.file.to_str().unwrap(), "refs.wxs");
     }
 
+    #[test]
+    fn test_reindex_source() {
+        let mut index = SymbolIndex::new();
+        let file = Path::new("product.wxs");
+        index
+            .index_source(
+                r#"<Wix><Component Id="Old" /><ComponentRef Id="Other" /></Wix>"#,
+                file,
+            )
+            .unwrap();
+        index
+            .index_source(r#"<Wix><Component Id="Other" /></Wix>"#, Path::new("b.wxs"))
+            .unwrap();
+
+        index
+            .reindex_source(
+                r#"<Wix><Component Id="New" /><ComponentRef Id="Other" /></Wix>"#,
+                file,
+            )
+            .unwrap();
+
+        assert!(!index.has_definition("Component", "Old"));
+        assert!(index.has_definition("Component", "New"));
+        let other = index.get_definition("Component", "Other").unwrap();
+        assert_eq!(index.find_references(other).len(), 1);
+
+        // A parse failure keeps the last good symbols
+        assert!(index.reindex_source("<Wix><Component", file).is_err());
+        assert!(index.has_definition("Component", "New"));
+    }
+
     #[test]
     fn test_index_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -1,5 +1,6 @@
 //! Core infrastructure for WiX analysis
 
+pub mod audit;
 pub mod baseline;
 pub mod cache;
 pub mod complexity;
@@ -8,16 +9,20 @@ pub mod diff;
 pub mod document;
 pub mod duplication;
 pub mod extractor;
+pub mod flow;
 pub mod gate;
+pub mod history;
 pub mod index;
 pub mod newcode;
 pub mod plugin;
 pub mod profile;
+pub mod script;
 pub mod secrets;
 pub mod suppression;
 pub mod types;
 pub mod watch;
 
+pub use audit::{AuditEntry, SuppressionAudit, SuppressionOrigin, SuppressionReport};
 pub use baseline::{
     filter_baseline, Baseline, BaselineEntry, BaselineError, BaselineStats, BASELINE_FILE_NAME,
 };
@@ -32,23 +37,31 @@ pub use debt::{
 pub use diff::{filter_to_changed, DiffDetector, DiffError, DiffResult, DiffSource};
 pub use document::{NodeExt, WixDocument};
 pub use duplication::{
-    Duplicate, DuplicateLocation, DuplicationConfig, DuplicationDetector, DuplicationRating,
-    DuplicationResult,
+    Duplicate, DuplicateKind, DuplicateLocation, DuplicationConfig, DuplicationDetector,
+    DuplicationMode, DuplicationRating, DuplicationResult,
 };
 pub use extractor::{
     extract_from_source, extract_symbols, symbol_at_position, ExtractionResult, SymbolAtPosition,
 };
+pub use flow::{
+    extract_flows, AssignmentKind, FlowSink, PropertyFlow, PropertyFlowGraph, SinkKind, TaintPath,
+    TaintSource,
+};
 pub use gate::{GateCondition, GateFailure, GateResult, QualityGate, RatingType};
+pub use history::{
+    HistoryEntry, HistoryError, HistoryMetric, HistoryStore, RunMetrics, Trend, HISTORY_DIR_NAME,
+};
 pub use index::SymbolIndex;
 pub use newcode::{
     filter_to_new_code, NewCodeDetector, NewCodeError, NewCodePeriod, NewCodeResult,
 };
 pub use plugin::{
     DeprecatedRuleInfo, PluginCategory, PluginError, PluginManifest, PluginRegistry, PluginRule,
-    PluginSeverity, RuleCondition,
+    PluginSeverity, PACK_EXTENSION, RESERVED_NAMESPACES,
 };
 pub use profile::{available_profiles, profile_descriptions, ProfileName, QualityProfile};
+pub use script::{RuleScript, ScriptError, ScriptIndex, ScriptOutcome, ScriptRunner};
 pub use secrets::{DetectedSecret, SecretSeverity, SecretType, SecretsDetector, SecretsResult};
-pub use suppression::SuppressionContext;
+pub use suppression::{SuppressionContext, SuppressionMeta};
 pub use types::*;
 pub use watch::{FileWatcher, WatchConfig, WatchError, WatchEvent};
@@ -1,60 +1,255 @@
-//! Plugin system for custom analyzer rules
+//! Rule packs - custom analyzer rules loaded from JSON
 //!
-//! Allows loading custom rules from external files or implementing
-//! custom analyzers programmatically.
+//! A rule pack is a versioned set of rules under its own namespace. Most
+//! rules are declarative: an element filter plus a [`Condition`]. Checks a
+//! condition can't express use a sandboxed script instead (see
+//! [`crate::core::script`]), which runs on each element the filter and
+//! condition matched.
 //!
-//! # Plugin Format
-//!
-//! Plugins are defined as JSON files with rule definitions:
+//! # Pack Format
 //!
 //! ```json
 //! {
-//!   "name": "my-custom-rules",
-//!   "version": "1.0.0",
+//!   "name": "acme-rules",
+//!   "version": "1.2.0",
+//!   "namespace": "ACME",
+//!   "minAnalyzerVersion": "0.1.0",
 //!   "rules": [
 //!     {
-//!       "id": "CUSTOM-001",
+//!       "id": "ACME-001",
 //!       "name": "require-description",
 //!       "description": "All packages must have a Description attribute",
 //!       "category": "best_practice",
 //!       "severity": "medium",
 //!       "element": "Package",
 //!       "condition": {
-//!         "type": "missing_attribute",
-//!         "attribute": "Description"
+//!         "type": "attribute_missing",
+//!         "name": "Description"
 //!       },
 //!       "message": "Package is missing Description attribute",
 //!       "help": "Add a Description attribute to improve maintainability"
+//!     },
+//!     {
+//!       "id": "ACME-002",
+//!       "name": "single-reference",
+//!       "description": "Components are referenced from one feature",
+//!       "element": "Component",
+//!       "script": "index.references(\"Component\", node.attr(\"Id\")) > 1",
+//!       "message": "{element} '{attr:Id}' is referenced more than once"
 //!     }
 //!   ]
 //! }
 //! ```
+//!
+//! Packs are authored as a directory (`pack.json` plus `rules/*.json`, with
+//! scripts in separate files via `scriptFile`) and bundled into one file
+//! with `wix-analyzer rules pack`.
+//!
+//! Plugins in the older format (no namespace, snake_case field names such as
+//! `effort_minutes`) still load; the missing namespace is only a warning.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
+use super::script::{RuleScript, ScriptIndex, ScriptOutcome, ScriptRunner};
 use crate::core::{AnalysisResult, Category, Diagnostic, Location, Severity, WixDocument};
+use crate::engine::{Condition, ConditionEvaluator, Node};
+use crate::plugins::wix::WixNode;
+
+/// Rule id prefixes of the built-in analyzers
+pub const RESERVED_NAMESPACES: &[&str] = &["VAL", "BP", "SEC", "DEAD", "DEP", "REL"];
+
+/// File name of a bundled rule pack
+pub const PACK_EXTENSION: &str = "wixrules.json";
 
 /// Plugin manifest
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PluginManifest {
     /// Plugin name
     pub name: String,
     /// Plugin version
     pub version: String,
     /// Optional description
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     /// Optional author
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
+    /// Rule id prefix, e.g. "ACME" for ACME-001
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Oldest analyzer version the pack works with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_analyzer_version: Option<String>,
     /// Rule definitions
+    #[serde(default)]
     pub rules: Vec<PluginRule>,
 }
 
+impl PluginManifest {
+    /// Load a pack from a bundled file or an authoring directory
+    pub fn load(path: &Path) -> Result<Self, PluginError> {
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| PluginError::LoadError(path.to_path_buf(), e.to_string()))?;
+        serde_json::from_str(&content)
+            .map_err(|e| PluginError::ParseError(path.to_path_buf(), e.to_string()))
+    }
+
+    /// Assemble a pack from `pack.json` and `rules/*.json`, inlining script files
+    pub fn from_dir(dir: &Path) -> Result<Self, PluginError> {
+        let pack_file = dir.join("pack.json");
+        let content = std::fs::read_to_string(&pack_file)
+            .map_err(|e| PluginError::LoadError(pack_file.clone(), e.to_string()))?;
+        let mut manifest: PluginManifest = serde_json::from_str(&content)
+            .map_err(|e| PluginError::ParseError(pack_file.clone(), e.to_string()))?;
+
+        let rules_dir = dir.join("rules");
+        let mut rule_files: Vec<PathBuf> = std::fs::read_dir(&rules_dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|e| e == "json"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        rule_files.sort();
+        for path in rule_files {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| PluginError::LoadError(path.clone(), e.to_string()))?;
+            let value: serde_json::Value = serde_json::from_str(&content)
+                .map_err(|e| PluginError::ParseError(path.clone(), e.to_string()))?;
+            let rules: Vec<PluginRule> = if value.is_array() {
+                serde_json::from_value(value)
+            } else {
+                serde_json::from_value(value).map(|rule| vec![rule])
+            }
+            .map_err(|e| PluginError::ParseError(path.clone(), e.to_string()))?;
+            manifest.rules.extend(rules);
+        }
+
+        for rule in &mut manifest.rules {
+            if let Some(file) = rule.script_file.take() {
+                let path = dir.join(&file);
+                let script = std::fs::read_to_string(&path)
+                    .map_err(|e| PluginError::LoadError(path.clone(), e.to_string()))?;
+                rule.script = Some(script);
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// File name of the bundled pack, e.g. `acme-rules-1.2.0.wixrules.json`
+    pub fn file_name(&self) -> String {
+        format!("{}-{}.{}", self.name, self.version, PACK_EXTENSION)
+    }
+
+    /// Problems that keep the pack from loading
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.name.trim().is_empty() {
+            problems.push("pack name is empty".to_string());
+        }
+        if parse_version(&self.version).is_none() {
+            problems.push(format!(
+                "version '{}' is not a semantic version (MAJOR.MINOR.PATCH)",
+                self.version
+            ));
+        }
+        if let Some(required) = &self.min_analyzer_version {
+            match parse_version(required) {
+                None => problems.push(format!(
+                    "minAnalyzerVersion '{}' is not a semantic version",
+                    required
+                )),
+                Some(required_version) => {
+                    let current = env!("CARGO_PKG_VERSION");
+                    if parse_version(current).is_some_and(|v| v < required_version) {
+                        problems.push(format!(
+                            "pack requires wix-analyzer {} or later (this is {})",
+                            required, current
+                        ));
+                    }
+                }
+            }
+        }
+
+        match &self.namespace {
+            None => {}
+            Some(ns) if ns.is_empty() || !ns.chars().all(|c| c.is_ascii_uppercase()) => {
+                problems.push(format!("namespace '{}' must be uppercase letters", ns))
+            }
+            Some(ns) if RESERVED_NAMESPACES.contains(&ns.as_str()) => {
+                problems.push(format!("namespace '{}' is reserved for built-in rules", ns))
+            }
+            Some(_) => {}
+        }
+
+        let mut seen = HashSet::new();
+        for rule in &self.rules {
+            if let Some(ns) = &self.namespace {
+                if !rule
+                    .id
+                    .strip_prefix(ns.as_str())
+                    .is_some_and(|rest| rest.starts_with('-') && rest.len() > 1)
+                {
+                    problems.push(format!("{}: id must start with '{}-'", rule.id, ns));
+                }
+            }
+            if !seen.insert(rule.id.as_str()) {
+                problems.push(format!("{}: duplicate rule id", rule.id));
+            }
+            for pattern in rule.condition.patterns() {
+                if let Err(e) = regex::Regex::new(pattern) {
+                    problems.push(format!("{}: invalid pattern '{}': {}", rule.id, pattern, e));
+                }
+            }
+            if rule.script_file.is_some() {
+                problems.push(format!(
+                    "{}: scriptFile is only allowed in a pack directory",
+                    rule.id
+                ));
+            }
+            if let Some(script) = &rule.script {
+                if let Err(e) = RuleScript::compile(script) {
+                    problems.push(format!("{}: script does not compile: {}", rule.id, e));
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Problems that don't keep the pack from loading
+    ///
+    /// Plugins written before rule packs have no namespace; they still load,
+    /// but their rule ids aren't checked against one.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.namespace.is_none() {
+            warnings.push("namespace is missing; rule ids are not checked".to_string());
+        }
+        warnings
+    }
+}
+
+/// MAJOR.MINOR.PATCH, ignoring pre-release and build suffixes
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
 /// A plugin rule definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct PluginRule {
     /// Rule ID (e.g., "CUSTOM-001")
     pub id: String,
@@ -72,14 +267,21 @@ pub struct PluginRule {
     #[serde(default)]
     pub element: Option<String>,
     /// Condition to check
-    pub condition: RuleCondition,
+    #[serde(default = "always")]
+    pub condition: Condition,
+    /// Script run on elements matching the condition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Script file relative to the pack directory, inlined when packing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script_file: Option<String>,
     /// Error message template
     pub message: String,
     /// Help text
     #[serde(default)]
     pub help: Option<String>,
     /// Effort in minutes to fix
-    #[serde(default)]
+    #[serde(default, alias = "effort_minutes")]
     pub effort_minutes: Option<u32>,
     /// Tags
     #[serde(default)]
@@ -88,23 +290,27 @@ pub struct PluginRule {
     #[serde(default)]
     pub deprecated: bool,
     /// Replacement rule ID (if deprecated)
-    #[serde(default)]
+    #[serde(default, alias = "deprecated_by")]
     pub deprecated_by: Option<String>,
     /// Version when rule was deprecated
-    #[serde(default)]
+    #[serde(default, alias = "deprecated_since")]
     pub deprecated_since: Option<String>,
     /// Documentation URL for the rule
-    #[serde(default)]
+    #[serde(default, alias = "doc_url")]
     pub doc_url: Option<String>,
 }
 
+fn always() -> Condition {
+    Condition::Always
+}
+
 impl PluginRule {
     /// Create a new plugin rule with minimal required fields
     pub fn new(
         id: impl Into<String>,
         name: impl Into<String>,
         description: impl Into<String>,
-        condition: RuleCondition,
+        condition: Condition,
         message: impl Into<String>,
     ) -> Self {
         Self {
@@ -115,6 +321,8 @@ impl PluginRule {
             severity: PluginSeverity::default(),
             element: None,
             condition,
+            script: None,
+            script_file: None,
             message: message.into(),
             help: None,
             effort_minutes: None,
@@ -144,6 +352,12 @@ impl PluginRule {
         self
     }
 
+    /// Set the script run on matching elements
+    pub fn with_script(mut self, script: impl Into<String>) -> Self {
+        self.script = Some(script.into());
+        self
+    }
+
     /// Mark as deprecated
     pub fn deprecated(mut self) -> Self {
         self.deprecated = true;
@@ -216,48 +430,14 @@ impl From<PluginSeverity> for Severity {
     }
 }
 
-/// Rule condition types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "snake_case")]
-pub enum RuleCondition {
-    /// Element is missing a required attribute
-    MissingAttribute { attribute: String },
-    /// Attribute has a specific value
-    AttributeEquals { attribute: String, value: String },
-    /// Attribute matches a pattern
-    AttributeMatches { attribute: String, pattern: String },
-    /// Attribute does NOT match a pattern
-    AttributeNotMatches { attribute: String, pattern: String },
-    /// Element has no children
-    NoChildren,
-    /// Element has specific child element
-    HasChild { element: String },
-    /// Element is missing specific child element
-    MissingChild { element: String },
-    /// Attribute value is in a forbidden list
-    AttributeIn {
-        attribute: String,
-        values: Vec<String>,
-    },
-    /// Attribute value is NOT in an allowed list
-    AttributeNotIn {
-        attribute: String,
-        values: Vec<String>,
-    },
-    /// Compound condition: all must match
-    All { conditions: Vec<RuleCondition> },
-    /// Compound condition: any must match
-    Any { conditions: Vec<RuleCondition> },
-    /// Negation
-    Not { condition: Box<RuleCondition> },
-}
-
 /// Plugin registry
 #[derive(Debug, Default)]
 pub struct PluginRegistry {
     plugins: Vec<PluginManifest>,
     rules_by_element: HashMap<String, Vec<(usize, usize)>>, // (plugin_idx, rule_idx)
     global_rules: Vec<(usize, usize)>,                      // Rules without element filter
+    scripts: HashMap<(usize, usize), RuleScript>,
+    warnings: Vec<String>,
 }
 
 impl PluginRegistry {
@@ -266,13 +446,36 @@ impl PluginRegistry {
         Self::default()
     }
 
-    /// Load a plugin from a JSON file
+    /// Load a rule pack, rejecting it if it doesn't validate
+    ///
+    /// Warnings are kept for [`PluginRegistry::warnings`].
     pub fn load_plugin(&mut self, path: &Path) -> Result<(), PluginError> {
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| PluginError::LoadError(path.to_path_buf(), e.to_string()))?;
-
-        let manifest: PluginManifest = serde_json::from_str(&content)
-            .map_err(|e| PluginError::ParseError(path.to_path_buf(), e.to_string()))?;
+        let manifest = PluginManifest::load(path)?;
+        let problems = manifest.validate();
+        if !problems.is_empty() {
+            return Err(PluginError::InvalidPack(path.to_path_buf(), problems));
+        }
+        self.warnings.extend(
+            manifest
+                .warnings()
+                .into_iter()
+                .map(|w| format!("{}: {}", path.display(), w)),
+        );
+        if let Some(existing) = self
+            .plugins
+            .iter()
+            .find(|p| p.namespace.is_some() && p.namespace == manifest.namespace)
+        {
+            return Err(PluginError::InvalidPack(
+                path.to_path_buf(),
+                vec![format!(
+                    "namespace '{}' is already used by {}@{}",
+                    existing.namespace.as_deref().unwrap_or_default(),
+                    existing.name,
+                    existing.version
+                )],
+            ));
+        }
 
         self.register_plugin(manifest);
         Ok(())
@@ -291,6 +494,14 @@ impl PluginRegistry {
             } else {
                 self.global_rules.push((plugin_idx, rule_idx));
             }
+            // Scripts that don't compile are reported by validate()
+            if let Some(script) = rule
+                .script
+                .as_deref()
+                .and_then(|s| RuleScript::compile(s).ok())
+            {
+                self.scripts.insert((plugin_idx, rule_idx), script);
+            }
         }
 
         self.plugins.push(manifest);
@@ -314,6 +525,16 @@ impl PluginRegistry {
         errors
     }
 
+    /// Registered packs
+    pub fn plugins(&self) -> &[PluginManifest] {
+        &self.plugins
+    }
+
+    /// Warnings from loading packs, prefixed with the pack path
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     /// Get all registered plugin names
     pub fn plugin_names(&self) -> Vec<&str> {
         self.plugins.iter().map(|p| p.name.as_str()).collect()
@@ -378,98 +599,33 @@ impl PluginRegistry {
 
     /// Run plugin rules against a document
     pub fn analyze(&self, doc: &WixDocument) -> AnalysisResult {
-        let mut result = AnalysisResult::new();
-
-        for node in doc.root().descendants() {
-            if !node.is_element() {
-                continue;
-            }
-
-            let element_name = node.tag_name().name();
-
-            // Check element-specific rules
-            if let Some(rules) = self.rules_by_element.get(element_name) {
-                for (plugin_idx, rule_idx) in rules {
-                    let rule = &self.plugins[*plugin_idx].rules[*rule_idx];
-                    if self.check_condition(&node, &rule.condition) {
-                        let range = doc.node_range(&node);
-                        let location = Location::new(doc.file().to_path_buf(), range);
-                        let diag = self.create_diagnostic(rule, location, &node);
-                        result.add(diag);
-                    }
-                }
-            }
+        self.analyze_with_index(doc, &ScriptIndex::default())
+    }
 
-            // Check global rules
-            for (plugin_idx, rule_idx) in &self.global_rules {
-                let rule = &self.plugins[*plugin_idx].rules[*rule_idx];
-                if self.check_condition(&node, &rule.condition) {
-                    let range = doc.node_range(&node);
-                    let location = Location::new(doc.file().to_path_buf(), range);
-                    let diag = self.create_diagnostic(rule, location, &node);
-                    result.add(diag);
-                }
-            }
+    /// Run plugin rules, giving scripts access to the project's symbols
+    pub fn analyze_with_index(&self, doc: &WixDocument, index: &ScriptIndex) -> AnalysisResult {
+        let mut result = AnalysisResult::new();
+        if self.rule_count() == 0 {
+            return result;
         }
 
-        result
-    }
+        let root = doc.root();
+        let Some(root_element) = root.first_element_child() else {
+            return result;
+        };
+        let tree = WixNode::from_node(doc.source(), root_element);
+        let runner = (!self.scripts.is_empty()).then(|| ScriptRunner::new(doc, index));
+        let mut run = RuleRun {
+            registry: self,
+            doc,
+            runner: runner.as_ref(),
+            evaluator: ConditionEvaluator::new(),
+            failed: HashSet::new(),
+            result: &mut result,
+        };
+        run.visit(root_element, &tree);
 
-    fn check_condition(&self, node: &roxmltree::Node, condition: &RuleCondition) -> bool {
-        match condition {
-            RuleCondition::MissingAttribute { attribute } => {
-                node.attribute(attribute.as_str()).is_none()
-            }
-            RuleCondition::AttributeEquals { attribute, value } => {
-                node.attribute(attribute.as_str()) == Some(value.as_str())
-            }
-            RuleCondition::AttributeMatches { attribute, pattern } => {
-                if let Some(val) = node.attribute(attribute.as_str()) {
-                    regex::Regex::new(pattern)
-                        .map(|re| re.is_match(val))
-                        .unwrap_or(false)
-                } else {
-                    false
-                }
-            }
-            RuleCondition::AttributeNotMatches { attribute, pattern } => {
-                if let Some(val) = node.attribute(attribute.as_str()) {
-                    regex::Regex::new(pattern)
-                        .map(|re| !re.is_match(val))
-                        .unwrap_or(true)
-                } else {
-                    true
-                }
-            }
-            RuleCondition::NoChildren => !node.children().any(|c| c.is_element()),
-            RuleCondition::HasChild { element } => node
-                .children()
-                .any(|c| c.is_element() && c.tag_name().name() == element),
-            RuleCondition::MissingChild { element } => !node
-                .children()
-                .any(|c| c.is_element() && c.tag_name().name() == element),
-            RuleCondition::AttributeIn { attribute, values } => {
-                if let Some(val) = node.attribute(attribute.as_str()) {
-                    values.iter().any(|v| v == val)
-                } else {
-                    false
-                }
-            }
-            RuleCondition::AttributeNotIn { attribute, values } => {
-                if let Some(val) = node.attribute(attribute.as_str()) {
-                    !values.iter().any(|v| v == val)
-                } else {
-                    true
-                }
-            }
-            RuleCondition::All { conditions } => {
-                conditions.iter().all(|c| self.check_condition(node, c))
-            }
-            RuleCondition::Any { conditions } => {
-                conditions.iter().any(|c| self.check_condition(node, c))
-            }
-            RuleCondition::Not { condition } => !self.check_condition(node, condition),
-        }
+        result
     }
 
     fn create_diagnostic(
@@ -477,9 +633,10 @@ impl PluginRegistry {
         rule: &PluginRule,
         location: Location,
         node: &roxmltree::Node,
+        message: Option<String>,
     ) -> Diagnostic {
         // Expand message template with node info
-        let message = self.expand_template(&rule.message, node);
+        let message = message.unwrap_or_else(|| self.expand_template(&rule.message, node));
 
         let mut diag = Diagnostic::new(
             &rule.id,
@@ -523,6 +680,69 @@ impl PluginRegistry {
     }
 }
 
+/// State of one pass over a document
+struct RuleRun<'r, 'd> {
+    registry: &'r PluginRegistry,
+    doc: &'d WixDocument<'d>,
+    runner: Option<&'r ScriptRunner>,
+    evaluator: ConditionEvaluator,
+    /// Rules whose script already failed in this document
+    failed: HashSet<(usize, usize)>,
+    result: &'r mut AnalysisResult,
+}
+
+impl RuleRun<'_, '_> {
+    /// Check an element and its descendants; `tree` mirrors `node`
+    fn visit(&mut self, node: roxmltree::Node, tree: &dyn Node) {
+        let registry = self.registry;
+        let element_rules = registry
+            .rules_by_element
+            .get(node.tag_name().name())
+            .into_iter()
+            .flatten();
+        for &key in element_rules.chain(&registry.global_rules) {
+            self.check(key, node, tree);
+        }
+
+        let children = node.children().filter(|c| c.is_element());
+        for (child, child_tree) in children.zip(tree.children()) {
+            self.visit(child, child_tree);
+        }
+    }
+
+    fn check(&mut self, key: (usize, usize), node: roxmltree::Node, tree: &dyn Node) {
+        let rule = &self.registry.plugins[key.0].rules[key.1];
+        if !self.evaluator.evaluate(&rule.condition, tree) {
+            return;
+        }
+
+        let location = || Location::new(self.doc.file().to_path_buf(), self.doc.node_range(&node));
+        let message = match (self.registry.scripts.get(&key), self.runner) {
+            (Some(script), Some(runner)) => match runner.run(script, &node) {
+                Ok(ScriptOutcome::Pass) => return,
+                Ok(ScriptOutcome::Issue(message)) => message,
+                Err(e) => {
+                    if self.failed.insert(key) {
+                        self.result.add(Diagnostic::new(
+                            &rule.id,
+                            rule.category.into(),
+                            Severity::Info,
+                            format!("Script of rule {} failed: {}", rule.id, e),
+                            location(),
+                        ));
+                    }
+                    return;
+                }
+            },
+            _ => None,
+        };
+        let diag = self
+            .registry
+            .create_diagnostic(rule, location(), &node, message);
+        self.result.add(diag);
+    }
+}
+
 /// Information about a deprecated rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeprecatedRuleInfo {
@@ -543,6 +763,7 @@ pub struct DeprecatedRuleInfo {
 pub enum PluginError {
     LoadError(std::path::PathBuf, String),
     ParseError(std::path::PathBuf, String),
+    InvalidPack(std::path::PathBuf, Vec<String>),
 }
 
 impl std::fmt::Display for PluginError {
@@ -554,6 +775,14 @@ impl std::fmt::Display for PluginError {
             Self::ParseError(path, msg) => {
                 write!(f, "Failed to parse plugin '{}': {}", path.display(), msg)
             }
+            Self::InvalidPack(path, problems) => {
+                write!(
+                    f,
+                    "Invalid rule pack '{}': {}",
+                    path.display(),
+                    problems.join("; ")
+                )
+            }
         }
     }
 }
@@ -572,6 +801,8 @@ mod tests {
             version: "1.0.0".to_string(),
             description: Some("Test plugin".to_string()),
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule {
                 id: "TEST-001".to_string(),
                 name: "require-id".to_string(),
@@ -579,9 +810,11 @@ mod tests {
                 category: PluginCategory::Validation,
                 severity: PluginSeverity::High,
                 element: Some("Component".to_string()),
-                condition: RuleCondition::MissingAttribute {
-                    attribute: "Id".to_string(),
+                condition: Condition::AttributeMissing {
+                    name: "Id".to_string(),
                 },
+                script: None,
+                script_file: None,
                 message: "Component is missing Id attribute".to_string(),
                 help: Some("Add an Id attribute".to_string()),
                 effort_minutes: Some(5),
@@ -630,12 +863,14 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-002",
                 "no-local-system",
                 "No LocalSystem",
-                RuleCondition::AttributeEquals {
-                    attribute: "Account".to_string(),
+                Condition::AttributeEquals {
+                    name: "Account".to_string(),
                     value: "LocalSystem".to_string(),
                 },
                 "Service uses LocalSystem",
@@ -660,13 +895,15 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![
                 PluginRule::new(
                     "TEST-003",
                     "valid-guid",
                     "Invalid GUID",
-                    RuleCondition::AttributeNotMatches {
-                        attribute: "Guid".to_string(),
+                    Condition::AttributeNotMatches {
+ name: "Guid".to_string(),
                         pattern: r"^[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}$".to_string(),
                     },
                     "Invalid GUID format",
@@ -692,11 +929,13 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-004",
                 "empty-feature",
                 "Empty feature",
-                RuleCondition::NoChildren,
+                Condition::NoChildren,
                 "Feature has no children",
             )
             .for_element("Feature")
@@ -718,11 +957,13 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-005",
                 "has-condition",
                 "Has condition",
-                RuleCondition::HasChild {
+                Condition::HasChild {
                     element: "Condition".to_string(),
                 },
                 "Component has condition",
@@ -746,11 +987,13 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-006",
                 "missing-file",
                 "Missing file",
-                RuleCondition::MissingChild {
+                Condition::MissingChild {
                     element: "File".to_string(),
                 },
                 "Component has no File",
@@ -775,12 +1018,14 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-007",
                 "forbidden-type",
                 "Forbidden type",
-                RuleCondition::AttributeIn {
-                    attribute: "Execute".to_string(),
+                Condition::AttributeIn {
+                    name: "Execute".to_string(),
                     values: vec!["deferred".to_string(), "rollback".to_string()],
                 },
                 "Using elevated execution",
@@ -805,20 +1050,20 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-008",
                 "compound",
                 "Compound test",
-                RuleCondition::All {
-                    conditions: vec![
-                        RuleCondition::MissingAttribute {
-                            attribute: "Id".to_string(),
-                        },
-                        RuleCondition::MissingAttribute {
-                            attribute: "Guid".to_string(),
-                        },
-                    ],
-                },
+                Condition::All(vec![
+                    Condition::AttributeMissing {
+                        name: "Id".to_string(),
+                    },
+                    Condition::AttributeMissing {
+                        name: "Guid".to_string(),
+                    },
+                ]),
                 "Missing both Id and Guid",
             )
             .for_element("Component")
@@ -841,20 +1086,20 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-009",
                 "any-missing",
                 "Any missing",
-                RuleCondition::Any {
-                    conditions: vec![
-                        RuleCondition::MissingAttribute {
-                            attribute: "Id".to_string(),
-                        },
-                        RuleCondition::MissingAttribute {
-                            attribute: "Guid".to_string(),
-                        },
-                    ],
-                },
+                Condition::Any(vec![
+                    Condition::AttributeMissing {
+                        name: "Id".to_string(),
+                    },
+                    Condition::AttributeMissing {
+                        name: "Guid".to_string(),
+                    },
+                ]),
                 "Missing Id or Guid",
             )
             .for_element("Component")
@@ -877,15 +1122,15 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-010",
                 "has-id",
                 "Has Id",
-                RuleCondition::Not {
-                    condition: Box::new(RuleCondition::MissingAttribute {
-                        attribute: "Id".to_string(),
-                    }),
-                },
+                Condition::Not(Box::new(Condition::AttributeMissing {
+                    name: "Id".to_string(),
+                })),
                 "Component has Id",
             )
             .for_element("Component")
@@ -907,12 +1152,14 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![PluginRule::new(
                 "TEST-011",
                 "template",
                 "Template test",
-                RuleCondition::MissingAttribute {
-                    attribute: "Guid".to_string(),
+                Condition::AttributeMissing {
+                    name: "Guid".to_string(),
                 },
                 "{element} '{attr:Id}' is missing Guid",
             )
@@ -938,13 +1185,15 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![
                 PluginRule::new(
                     "TEST-012",
                     "global",
                     "Global test",
-                    RuleCondition::MissingAttribute {
-                        attribute: "Id".to_string(),
+                    Condition::AttributeMissing {
+                        name: "Id".to_string(),
                     },
                     "Element missing Id",
                 )
@@ -991,13 +1240,15 @@ mod tests {
             version: "1.0".to_string(),
             description: None,
             author: None,
+            namespace: None,
+            min_analyzer_version: None,
             rules: vec![
                 PluginRule::new(
                     "OLD-001",
                     "old-rule",
                     "Old rule",
-                    RuleCondition::MissingAttribute {
-                        attribute: "Id".to_string(),
+                    Condition::AttributeMissing {
+                        name: "Id".to_string(),
                     },
                     "Missing Id",
                 )
@@ -1008,8 +1259,8 @@ mod tests {
                     "NEW-001",
                     "new-rule",
                     "New rule",
-                    RuleCondition::MissingAttribute {
-                        attribute: "Id".to_string(),
+                    Condition::AttributeMissing {
+                        name: "Id".to_string(),
                     },
                     "Missing Id",
                 )
@@ -1049,7 +1300,7 @@ mod tests {
             "TEST-001",
             "test-rule",
             "Test description",
-            RuleCondition::NoChildren,
+            Condition::NoChildren,
             "Test message",
         )
         .for_element("Component")
@@ -1070,4 +1321,232 @@ mod tests {
             Some("https://example.com/rules/TEST-001".to_string())
         );
     }
+
+    fn write_pack(dir: &Path, namespace: &str) {
+        std::fs::create_dir_all(dir.join("rules")).unwrap();
+        std::fs::write(
+            dir.join("pack.json"),
+            format!(
+                r#"{{"name": "acme-rules", "version": "1.2.0", "namespace": "{}"}}"#,
+                namespace
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("rules/packages.json"),
+            r#"{
+                "id": "ACME-001",
+                "name": "require-description",
+                "description": "Packages need a Description",
+                "element": "Package",
+                "condition": {"type": "missing_attribute", "attribute": "Description"},
+                "message": "Package is missing Description"
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("rules/components.json"),
+            r#"[{
+                "id": "ACME-002",
+                "name": "single-reference",
+                "description": "Components belong to one feature",
+                "severity": "high",
+                "element": "Component",
+                "condition": {"type": "attribute_exists", "name": "Id"},
+                "scriptFile": "scripts/single_reference.rhai",
+                "message": "unused"
+            }]"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("scripts")).unwrap();
+        std::fs::write(
+            dir.join("scripts/single_reference.rhai"),
+            r#"let refs = index.references("Component", node.attr("Id"));
+if refs > 1 { `Component ${node.attr("Id")} is referenced ${refs} times` }"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_pack_directory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        write_pack(temp.path(), "ACME");
+
+        let manifest = PluginManifest::from_dir(temp.path()).unwrap();
+        assert_eq!(manifest.rules.len(), 2);
+        assert_eq!(manifest.file_name(), "acme-rules-1.2.0.wixrules.json");
+        let scripted = manifest.rules.iter().find(|r| r.id == "ACME-002").unwrap();
+        assert!(scripted
+            .script
+            .as_deref()
+            .unwrap()
+            .contains("index.references"));
+        assert!(scripted.script_file.is_none());
+        assert!(manifest.validate().is_empty(), "{:?}", manifest.validate());
+
+        // Bundled form round-trips
+        let bundled = temp.path().join(manifest.file_name());
+        std::fs::write(&bundled, serde_json::to_string(&manifest).unwrap()).unwrap();
+        let mut registry = PluginRegistry::new();
+        registry.load_plugin(&bundled).unwrap();
+        assert_eq!(registry.rule_count(), 2);
+
+        let source = r#"<Wix>
+  <Package Name="P">
+    <Feature Id="A"><ComponentRef Id="Shared" /></Feature>
+    <Feature Id="B"><ComponentRef Id="Shared" /><ComponentRef Id="Own" /></Feature>
+    <Component Id="Shared" />
+    <Component Id="Own" />
+  </Package>
+</Wix>"#;
+        let doc = WixDocument::parse(source, Path::new("test.wxs")).unwrap();
+        let mut index = crate::core::SymbolIndex::new();
+        index.index_source(source, Path::new("test.wxs")).unwrap();
+        let result = registry.analyze_with_index(&doc, &ScriptIndex::new(&index));
+
+        let messages: Vec<(&str, &str)> = result
+            .diagnostics
+            .iter()
+            .map(|d| (d.rule_id.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                ("ACME-001", "Package is missing Description"),
+                ("ACME-002", "Component Shared is referenced 2 times"),
+            ]
+        );
+        assert_eq!(result.diagnostics[1].severity, Severity::High);
+        assert_eq!(result.diagnostics[1].location.range.start.line, 5);
+    }
+
+    #[test]
+    fn test_pack_validation() {
+        let temp = tempfile::TempDir::new().unwrap();
+        write_pack(temp.path(), "SEC");
+        let mut manifest = PluginManifest::from_dir(temp.path()).unwrap();
+        manifest.version = "1.2".to_string();
+        manifest.min_analyzer_version = Some("99.0.0".to_string());
+        manifest.rules.push(
+            PluginRule::new(
+                "ACME-001",
+                "dup",
+                "Duplicate",
+                Condition::AttributeMatches {
+                    name: "Id".to_string(),
+                    pattern: "(".to_string(),
+                },
+                "m",
+            )
+            .with_script("let x = "),
+        );
+
+        let problems = manifest.validate();
+        let has = |text: &str| problems.iter().any(|p| p.contains(text));
+        assert!(has("not a semantic version"), "{:?}", problems);
+        assert!(has("requires wix-analyzer 99.0.0"));
+        assert!(has("'SEC' is reserved"));
+        assert!(has("ACME-001: id must start with 'SEC-'"));
+        assert!(has("ACME-001: duplicate rule id"));
+        assert!(has("invalid pattern"));
+        assert!(has("script does not compile"));
+
+        let mut registry = PluginRegistry::new();
+        let err = registry.load_plugin(temp.path()).unwrap_err();
+        assert!(matches!(err, PluginError::InvalidPack(..)));
+
+        // Two packs can't share a namespace
+        write_pack(temp.path(), "ACME");
+        registry.load_plugin(temp.path()).unwrap();
+        let err = registry.load_plugin(temp.path()).unwrap_err();
+        assert!(err.to_string().contains("already used by acme-rules@1.2.0"));
+    }
+
+    #[test]
+    fn test_legacy_plugin_format_loads() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("custom.json");
+        std::fs::write(
+            &path,
+            r#"{
+  "name": "my-custom-rules",
+  "version": "1.0.0",
+  "rules": [
+    {
+      "id": "CUSTOM-001",
+      "name": "require-description",
+      "description": "All packages must have a Description attribute",
+      "category": "best_practice",
+      "severity": "medium",
+      "element": "Package",
+      "condition": {
+        "type": "missing_attribute",
+        "attribute": "Description"
+      },
+      "message": "Package is missing Description attribute",
+      "help": "Add a Description attribute to improve maintainability",
+      "effort_minutes": 5,
+      "deprecated": true,
+      "deprecated_by": "CUSTOM-002",
+      "deprecated_since": "1.0.0",
+      "doc_url": "https://example.com/rules/CUSTOM-001"
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+
+        let mut registry = PluginRegistry::new();
+        registry.load_plugin(&path).unwrap();
+        assert_eq!(registry.rule_count(), 1);
+        assert_eq!(registry.warnings().len(), 1);
+        assert!(registry.warnings()[0].contains("namespace is missing"));
+
+        let rule = &registry.plugins()[0].rules[0];
+        assert_eq!(rule.effort_minutes, Some(5));
+        assert_eq!(rule.deprecated_by.as_deref(), Some("CUSTOM-002"));
+        assert_eq!(rule.deprecated_since.as_deref(), Some("1.0.0"));
+        assert_eq!(
+            rule.doc_url.as_deref(),
+            Some("https://example.com/rules/CUSTOM-001")
+        );
+
+        let doc =
+            WixDocument::parse("<Wix><Package Name=\"P\" /></Wix>", Path::new("t.wxs")).unwrap();
+        let result = registry.analyze(&doc);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].rule_id, "CUSTOM-001");
+    }
+
+    #[test]
+    fn test_failing_script_reported_once() {
+        let mut registry = PluginRegistry::new();
+        registry.register_plugin(PluginManifest {
+            name: "test".to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            author: None,
+            namespace: None,
+            min_analyzer_version: None,
+            rules: vec![PluginRule::new(
+                "TEST-013",
+                "endless",
+                "Endless",
+                Condition::Always,
+                "never",
+            )
+            .for_element("Component")
+            .with_script("loop {}")],
+        });
+
+        let source = r#"<Wix><Component Id="A" /><Component Id="B" /></Wix>"#;
+        let doc = WixDocument::parse(source, Path::new("test.wxs")).unwrap();
+        let result = registry.analyze(&doc);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result.diagnostics[0].severity, Severity::Info);
+        assert!(result.diagnostics[0]
+            .message
+            .contains("Script of rule TEST-013 failed"));
+    }
 }
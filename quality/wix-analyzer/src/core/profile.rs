@@ -195,6 +195,7 @@ impl QualityProfile {
             enable: self.enable_rules.iter().cloned().collect(),
             disable: self.disable_rules.iter().cloned().collect(),
             severity: std::collections::HashMap::new(),
+            packs: Vec::new(),
         };
         config
     }
@@ -0,0 +1,486 @@
+//! Sandboxed scripts for rule-pack checks that conditions can't express
+//!
+//! Scripts are written in [Rhai](https://rhai.rs) and see a read-only view of
+//! the document and the symbol index. They have no access to files, the
+//! network or the environment, and run under operation, depth and size
+//! limits.
+//!
+//! A script runs for each element that matched its rule's element filter and
+//! condition. It reports an issue by returning `true` or a message string;
+//! `false` or `()` means no issue.
+//!
+//! ```rhai
+//! // Per-user components need a registry key path
+//! let key = node.children("RegistryValue");
+//! if key.is_empty() && index.references("Component", node.attr("Id")) > 1 {
+//!     `Component ${node.attr("Id")} is referenced more than once`
+//! }
+//! ```
+//!
+//! # API
+//!
+//! - `node`: `name`, `text`, `line`, `parent` (or `()`), `children`,
+//!   `children(name)`, `descendants(name)`, `attr(name)` (or `()`),
+//!   `has_attr(name)`, `attrs`
+//! - `doc`: `file`, `root`, `elements(name)`
+//! - `index`: `defined(type, id)`, `definition(type, id)` (`#{file, line}` or
+//!   `()`), `references(type, id)`, `ids(type)`
+
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::document::WixDocument;
+use super::index::{canonical_type_for_lookup, canonical_type_for_reference, SymbolIndex};
+
+/// Operations a script may perform per element
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// A compiled rule script
+#[derive(Debug, Clone)]
+pub struct RuleScript {
+    ast: AST,
+}
+
+impl RuleScript {
+    /// Compile a script, reporting syntax errors and disabled features
+    pub fn compile(source: &str) -> Result<Self, ScriptError> {
+        let ast = sandboxed_engine()
+            .compile(source)
+            .map_err(|e| ScriptError(e.to_string()))?;
+        Ok(Self { ast })
+    }
+}
+
+/// Outcome of running a script on one element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptOutcome {
+    Pass,
+    /// Issue found, optionally with a message replacing the rule's
+    Issue(Option<String>),
+}
+
+/// Compile or runtime error of a rule script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError(pub String);
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// Runs rule scripts against the elements of one document
+pub struct ScriptRunner {
+    engine: Engine,
+    doc: ScriptDocument,
+    index: ScriptIndex,
+    ids: HashMap<roxmltree::NodeId, usize>,
+}
+
+impl ScriptRunner {
+    pub fn new(doc: &WixDocument, index: &ScriptIndex) -> Self {
+        let (tree, ids) = ScriptTree::build(doc);
+        Self {
+            engine: sandboxed_engine(),
+            doc: ScriptDocument {
+                tree: Arc::new(tree),
+                file: doc.file().display().to_string(),
+            },
+            index: index.clone(),
+            ids,
+        }
+    }
+
+    /// Run a script with `node` bound to an element of the document
+    pub fn run(
+        &self,
+        script: &RuleScript,
+        node: &roxmltree::Node,
+    ) -> Result<ScriptOutcome, ScriptError> {
+        let Some(&index) = self.ids.get(&node.id()) else {
+            return Ok(ScriptOutcome::Pass);
+        };
+        let mut scope = Scope::new();
+        scope.push_constant(
+            "node",
+            ScriptNode {
+                tree: self.doc.tree.clone(),
+                index,
+            },
+        );
+        scope.push_constant("doc", self.doc.clone());
+        scope.push_constant("index", self.index.clone());
+
+        let value: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &script.ast)
+            .map_err(|e| ScriptError(e.to_string()))?;
+        if value.is_unit() {
+            return Ok(ScriptOutcome::Pass);
+        }
+        if let Some(found) = value.clone().try_cast::<bool>() {
+            return Ok(if found {
+                ScriptOutcome::Issue(None)
+            } else {
+                ScriptOutcome::Pass
+            });
+        }
+        match value.into_string() {
+            Ok(message) if message.is_empty() => Ok(ScriptOutcome::Pass),
+            Ok(message) => Ok(ScriptOutcome::Issue(Some(message))),
+            Err(kind) => Err(ScriptError(format!(
+                "script must return a bool, a string or (), not {}",
+                kind
+            ))),
+        }
+    }
+}
+
+/// Engine without file, module or eval access and with resource limits
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.on_print(|_| {});
+    engine.on_debug(|_, _, _| {});
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1 << 20);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+
+    engine
+        .register_type_with_name::<ScriptNode>("Node")
+        .register_get("name", |n: &mut ScriptNode| n.element().name.clone())
+        .register_get("text", |n: &mut ScriptNode| n.element().text.clone())
+        .register_get("line", |n: &mut ScriptNode| n.element().line as i64)
+        .register_get("parent", |n: &mut ScriptNode| {
+            n.element()
+                .parent
+                .map(|p| Dynamic::from(n.at(p)))
+                .unwrap_or(Dynamic::UNIT)
+        })
+        .register_get("children", |n: &mut ScriptNode| n.children(None))
+        .register_fn("children", |n: &mut ScriptNode, name: &str| {
+            n.children(Some(name))
+        })
+        .register_fn("descendants", |n: &mut ScriptNode, name: &str| {
+            n.descendants(name)
+        })
+        .register_fn("attr", |n: &mut ScriptNode, name: &str| {
+            n.attribute(name)
+                .map(Dynamic::from)
+                .unwrap_or(Dynamic::UNIT)
+        })
+        .register_fn("has_attr", |n: &mut ScriptNode, name: &str| {
+            n.attribute(name).is_some()
+        })
+        .register_get("attrs", |n: &mut ScriptNode| {
+            n.element()
+                .attributes
+                .iter()
+                .map(|(k, v)| (k.as_str().into(), Dynamic::from(v.clone())))
+                .collect::<Map>()
+        })
+        .register_fn("to_string", |n: &mut ScriptNode| {
+            format!("<{}> at line {}", n.element().name, n.element().line)
+        });
+
+    engine
+        .register_type_with_name::<ScriptDocument>("Document")
+        .register_get("file", |d: &mut ScriptDocument| d.file.clone())
+        .register_get("root", |d: &mut ScriptDocument| {
+            if d.tree.elements.is_empty() {
+                Dynamic::UNIT
+            } else {
+                Dynamic::from(ScriptNode {
+                    tree: d.tree.clone(),
+                    index: 0,
+                })
+            }
+        })
+        .register_fn("elements", |d: &mut ScriptDocument, name: &str| {
+            (0..d.tree.elements.len())
+                .filter(|&i| d.tree.elements[i].name == name)
+                .map(|i| {
+                    Dynamic::from(ScriptNode {
+                        tree: d.tree.clone(),
+                        index: i,
+                    })
+                })
+                .collect::<Array>()
+        });
+
+    engine
+        .register_type_with_name::<ScriptIndex>("Index")
+        .register_fn("defined", |i: &mut ScriptIndex, kind: &str, id: &str| {
+            i.definition(kind, id).is_some()
+        })
+        .register_fn(
+            "definition",
+            |i: &mut ScriptIndex, kind: &str, id: &str| match i.definition(kind, id) {
+                Some((file, line)) => {
+                    let mut map = Map::new();
+                    map.insert("file".into(), Dynamic::from(file.clone()));
+                    map.insert("line".into(), Dynamic::from(*line as i64));
+                    Dynamic::from_map(map)
+                }
+                None => Dynamic::UNIT,
+            },
+        )
+        .register_fn("references", |i: &mut ScriptIndex, kind: &str, id: &str| {
+            i.references(kind, id) as i64
+        })
+        .register_fn("ids", |i: &mut ScriptIndex, kind: &str| {
+            let kind = canonical_type_for_lookup(kind);
+            let mut ids: Vec<&String> = i
+                .definitions
+                .keys()
+                .filter(|(k, _)| k == kind)
+                .map(|(_, id)| id)
+                .collect();
+            ids.sort();
+            ids.into_iter()
+                .map(|id| Dynamic::from(id.clone()))
+                .collect::<Array>()
+        });
+
+    engine
+}
+
+/// Owned copy of a document's elements, shareable with scripts
+#[derive(Debug)]
+struct ScriptTree {
+    /// Elements in document order
+    elements: Vec<ScriptElement>,
+}
+
+#[derive(Debug)]
+struct ScriptElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    line: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl ScriptTree {
+    fn build(doc: &WixDocument) -> (Self, HashMap<roxmltree::NodeId, usize>) {
+        let mut elements: Vec<ScriptElement> = Vec::new();
+        let mut ids: HashMap<roxmltree::NodeId, usize> = HashMap::new();
+        for node in doc.root().descendants().filter(|n| n.is_element()) {
+            let index = elements.len();
+            let parent = node
+                .parent_element()
+                .and_then(|p| ids.get(&p.id()).copied());
+            if let Some(parent) = parent {
+                elements[parent].children.push(index);
+            }
+            elements.push(ScriptElement {
+                name: node.tag_name().name().to_string(),
+                attributes: node
+                    .attributes()
+                    .map(|a| (a.name().to_string(), a.value().to_string()))
+                    .collect(),
+                text: node.text().unwrap_or_default().trim().to_string(),
+                line: doc.node_range(&node).start.line,
+                parent,
+                children: Vec::new(),
+            });
+            ids.insert(node.id(), index);
+        }
+        (Self { elements }, ids)
+    }
+}
+
+/// An element as seen by scripts
+#[derive(Debug, Clone)]
+pub struct ScriptNode {
+    tree: Arc<ScriptTree>,
+    index: usize,
+}
+
+impl ScriptNode {
+    fn element(&self) -> &ScriptElement {
+        &self.tree.elements[self.index]
+    }
+
+    fn at(&self, index: usize) -> ScriptNode {
+        ScriptNode {
+            tree: self.tree.clone(),
+            index,
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        self.element()
+            .attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.clone())
+    }
+
+    fn children(&self, name: Option<&str>) -> Array {
+        self.element()
+            .children
+            .iter()
+            .filter(|&&c| name.is_none_or(|n| self.tree.elements[c].name == n))
+            .map(|&c| Dynamic::from(self.at(c)))
+            .collect()
+    }
+
+    fn descendants(&self, name: &str) -> Array {
+        let mut found = Array::new();
+        let mut stack: Vec<usize> = self.element().children.iter().rev().copied().collect();
+        while let Some(i) = stack.pop() {
+            let element = &self.tree.elements[i];
+            if element.name == name {
+                found.push(Dynamic::from(self.at(i)));
+            }
+            stack.extend(element.children.iter().rev());
+        }
+        found
+    }
+}
+
+/// The document as seen by scripts
+#[derive(Debug, Clone)]
+pub struct ScriptDocument {
+    tree: Arc<ScriptTree>,
+    file: String,
+}
+
+/// Read-only snapshot of a symbol index for scripts
+#[derive(Debug, Clone, Default)]
+pub struct ScriptIndex {
+    /// (canonical type, id) -> (file, line)
+    definitions: Arc<HashMap<(String, String), (String, usize)>>,
+    /// (canonical type, id) -> reference count
+    references: Arc<HashMap<(String, String), usize>>,
+}
+
+impl ScriptIndex {
+    pub fn new(index: &SymbolIndex) -> Self {
+        let definitions = index
+            .all_definitions()
+            .into_iter()
+            .map(|d| {
+                (
+                    (d.kind.canonical_type().to_string(), d.id.clone()),
+                    (
+                        d.location.file.display().to_string(),
+                        d.location.range.start.line,
+                    ),
+                )
+            })
+            .collect();
+        let mut references = HashMap::new();
+        for reference in index.all_references() {
+            *references
+                .entry((
+                    canonical_type_for_reference(&reference.kind),
+                    reference.id.clone(),
+                ))
+                .or_insert(0) += 1;
+        }
+        Self {
+            definitions: Arc::new(definitions),
+            references: Arc::new(references),
+        }
+    }
+
+    fn definition(&self, kind: &str, id: &str) -> Option<&(String, usize)> {
+        let key = (canonical_type_for_lookup(kind).to_string(), id.to_string());
+        self.definitions.get(&key)
+    }
+
+    fn references(&self, kind: &str, id: &str) -> usize {
+        let key = (canonical_type_for_lookup(kind).to_string(), id.to_string());
+        self.references.get(&key).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const SOURCE: &str = r#"<Wix>
+  <Package Name="P">
+    <Feature Id="Main">
+      <ComponentRef Id="App" />
+    </Feature>
+    <Component Id="App"><File Source="app.exe" /></Component>
+    <Component Id="Orphan"><File Source="a.txt" /><File Source="b.txt" /></Component>
+  </Package>
+</Wix>"#;
+
+    fn run(script: &str, element: &str) -> Vec<Result<ScriptOutcome, ScriptError>> {
+        let doc = WixDocument::parse(SOURCE, Path::new("test.wxs")).unwrap();
+        let mut index = SymbolIndex::new();
+        index.index_source(SOURCE, Path::new("test.wxs")).unwrap();
+        let runner = ScriptRunner::new(&doc, &ScriptIndex::new(&index));
+        let script = RuleScript::compile(script).unwrap();
+        doc.root()
+            .descendants()
+            .filter(|n| n.has_tag_name(element))
+            .map(|n| runner.run(&script, &n))
+            .collect()
+    }
+
+    #[test]
+    fn test_node_and_index_api() {
+        let outcomes = run(
+            r#"
+            let id = node.attr("Id");
+            if index.references("Component", id) == 0 {
+                `${node.name} ${id} with ${node.children("File").len()} files is never referenced (line ${node.line}, in ${node.parent.name})`
+            }
+            "#,
+            "Component",
+        );
+        assert_eq!(
+            outcomes,
+            vec![
+                Ok(ScriptOutcome::Pass),
+                Ok(ScriptOutcome::Issue(Some(
+                    "Component Orphan with 2 files is never referenced (line 7, in Package)"
+                        .to_string()
+                ))),
+            ]
+        );
+
+        let outcomes = run(
+            r#"doc.elements("File").len() == 3 && index.defined("ComponentRef", "App") && !node.has_attr("Title")"#,
+            "Feature",
+        );
+        assert_eq!(outcomes, vec![Ok(ScriptOutcome::Issue(None))]);
+
+        let outcomes = run(
+            r#"node.descendants("File").map(|f| f.attr("Source")).contains("app.exe") == false"#,
+            "Package",
+        );
+        assert_eq!(outcomes, vec![Ok(ScriptOutcome::Pass)]);
+    }
+
+    #[test]
+    fn test_sandbox() {
+        assert!(RuleScript::compile(r#"eval("1")"#).is_err());
+        assert!(RuleScript::compile("let x = ").is_err());
+
+        let outcomes = run(r#"import "std" as s; true"#, "Feature");
+        assert!(outcomes[0].is_err());
+
+        let outcomes = run("loop {}", "Feature");
+        let err = outcomes[0].as_ref().unwrap_err();
+        assert!(err.0.contains("operations"), "{}", err);
+
+        let outcomes = run("42", "Feature");
+        assert!(outcomes[0].as_ref().unwrap_err().0.contains("not i64"));
+    }
+}
@@ -9,14 +9,31 @@
 //!
 //! Also supports inline suppression:
 //! - `<Element /> <!-- wix-analyzer-disable-line RULE-001 -->`
+//!
+//! Any directive can say why, by whom and until when issues are suppressed:
+//! - `<!-- wix-analyzer-disable-next-line SEC-001 reason="vendor key" owner=alice until=2025-06-30 -->`
+//!
+//! `wix-analyzer suppressions audit` reports suppressions that are past their
+//! `until` date or no longer hide anything.
 
+use chrono::NaiveDate;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
 use std::sync::LazyLock;
 
 /// Pattern to match suppression comments
 static DISABLE_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"<!--\s*wix-analyzer-disable(?:-next-line|-line)?\s*([\w\-,\s]*)\s*-->").unwrap()
+    Regex::new(
+        r#"<!--\s*wix-analyzer-disable(-next-line|-line)?\s*([\w\-,\s]*?)((?:\s*\b(?:reason|owner|until)\s*=\s*(?:"[^"]*"|'[^']*'|[^\s"'>]+))*)\s*-->"#,
+    )
+    .unwrap()
+});
+
+/// Pattern to match `key=value` metadata in a suppression comment
+static META_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(reason|owner|until)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
 });
 
 /// Pattern to match enable comments
@@ -34,6 +51,49 @@ pub enum SuppressionKind {
     Block,
 }
 
+/// Why, by whom and until when issues are suppressed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SuppressionMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Last day the suppression is meant to apply (YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+impl SuppressionMeta {
+    /// The `until` date, if set and valid
+    pub fn until_date(&self) -> Option<NaiveDate> {
+        self.until
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// Past its `until` date on `today`; an unreadable date counts as expired
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.until.is_some() && self.until_date().is_none_or(|until| until < today)
+    }
+
+    fn parse(text: &str) -> Self {
+        let mut meta = Self::default();
+        for caps in META_PATTERN.captures_iter(text) {
+            let value = caps
+                .get(2)
+                .or(caps.get(3))
+                .or(caps.get(4))
+                .map(|m| m.as_str().trim().to_string());
+            match &caps[1] {
+                "reason" => meta.reason = value,
+                "owner" => meta.owner = value,
+                _ => meta.until = value,
+            }
+        }
+        meta
+    }
+}
+
 /// A suppression directive
 #[derive(Debug, Clone)]
 pub struct Suppression {
@@ -43,6 +103,16 @@ pub struct Suppression {
     pub rules: HashSet<String>,
     /// Type of suppression
     pub kind: SuppressionKind,
+    /// Lines the suppression applies to
+    pub lines: RangeInclusive<usize>,
+    pub meta: SuppressionMeta,
+}
+
+impl Suppression {
+    /// Whether this suppression hides `rule_id` at `line`
+    pub fn covers(&self, rule_id: &str, line: usize) -> bool {
+        self.lines.contains(&line) && (self.rules.is_empty() || self.rules.contains(rule_id))
+    }
 }
 
 /// Suppression context for filtering diagnostics
@@ -52,6 +122,8 @@ pub struct SuppressionContext {
     line_suppressions: HashMap<usize, HashSet<String>>,
     /// Block suppressions: (start_line, end_line, rules)
     block_suppressions: Vec<(usize, usize, HashSet<String>)>,
+    /// Directives in source order
+    suppressions: Vec<Suppression>,
 }
 
 impl SuppressionContext {
@@ -60,56 +132,93 @@ impl SuppressionContext {
         let mut ctx = Self::default();
         let lines: Vec<&str> = source.lines().collect();
 
-        let mut block_start: Option<(usize, HashSet<String>)> = None;
+        // Directive line and metadata of the open block
+        let mut block_start: Option<(usize, SuppressionMeta)> = None;
 
         for (idx, line) in lines.iter().enumerate() {
             let line_num = idx + 1; // 1-based
 
             // Check for enable comment (ends block)
             if ENABLE_PATTERN.is_match(line) {
-                if let Some((start, rules)) = block_start.take() {
-                    ctx.block_suppressions.push((start, line_num, rules));
+                if let Some((start, meta)) = block_start.take() {
+                    ctx.add_block_suppression(start, line_num, meta);
                 }
                 continue;
             }
 
             // Check for disable comment
             if let Some(caps) = DISABLE_PATTERN.captures(line) {
-                let rules_str = caps.get(1).map(|m| m.as_str()).unwrap_or("");
-                let rules = parse_rules(rules_str);
-                let is_inline = line.contains("-line");
-                let is_next_line = line.contains("-next-line");
+                let variant = caps.get(1).map(|m| m.as_str());
+                let rules = parse_rules(caps.get(2).map(|m| m.as_str()).unwrap_or(""));
+                let meta = SuppressionMeta::parse(caps.get(3).map(|m| m.as_str()).unwrap_or(""));
 
-                if is_inline && !is_next_line {
+                match variant {
                     // -line: suppress on this line only
-                    ctx.add_line_suppression(line_num, rules);
-                } else if is_next_line {
+                    Some("-line") => {
+                        ctx.add_line_suppression(line_num, SuppressionKind::Line, rules, meta)
+                    }
                     // -next-line: suppress on the next line
-                    ctx.add_line_suppression(line_num + 1, rules);
-                } else if rules.is_empty() {
+                    Some(_) => {
+                        ctx.add_line_suppression(line_num, SuppressionKind::NextLine, rules, meta)
+                    }
                     // Block disable all rules
-                    block_start = Some((line_num + 1, HashSet::new()));
-                } else {
-                    // Block disable specific rules (or just next line if rules specified)
+                    None if rules.is_empty() => block_start = Some((line_num, meta)),
                     // Convention: with specific rules, it's next-line behavior
-                    ctx.add_line_suppression(line_num + 1, rules);
+                    None => {
+                        ctx.add_line_suppression(line_num, SuppressionKind::NextLine, rules, meta)
+                    }
                 }
             }
         }
 
         // Handle unclosed block (extends to end of file)
-        if let Some((start, rules)) = block_start {
-            ctx.block_suppressions.push((start, lines.len() + 1, rules));
+        if let Some((start, meta)) = block_start {
+            ctx.add_block_suppression(start, lines.len() + 1, meta);
         }
 
+        ctx.suppressions.sort_by_key(|s| s.line);
         ctx
     }
 
-    fn add_line_suppression(&mut self, line: usize, rules: HashSet<String>) {
+    fn add_line_suppression(
+        &mut self,
+        directive_line: usize,
+        kind: SuppressionKind,
+        rules: HashSet<String>,
+        meta: SuppressionMeta,
+    ) {
+        let line = match kind {
+            SuppressionKind::Line => directive_line,
+            _ => directive_line + 1,
+        };
         self.line_suppressions
             .entry(line)
             .or_default()
-            .extend(rules);
+            .extend(rules.iter().cloned());
+        self.suppressions.push(Suppression {
+            line: directive_line,
+            rules,
+            kind,
+            lines: line..=line,
+            meta,
+        });
+    }
+
+    fn add_block_suppression(&mut self, directive_line: usize, end: usize, meta: SuppressionMeta) {
+        let start = directive_line + 1;
+        self.block_suppressions.push((start, end, HashSet::new()));
+        self.suppressions.push(Suppression {
+            line: directive_line,
+            rules: HashSet::new(),
+            kind: SuppressionKind::Block,
+            lines: start..=end,
+            meta,
+        });
+    }
+
+    /// All suppression directives, in source order
+    pub fn suppressions(&self) -> &[Suppression] {
+        &self.suppressions
     }
 
     /// Check if a rule is suppressed at the given line
@@ -309,4 +418,38 @@ mod tests {
         let ctx = SuppressionContext::parse(source);
         assert!(ctx.has_suppressions());
     }
+
+    #[test]
+    fn test_metadata() {
+        let source = r#"
+<!-- wix-analyzer-disable SEC-001, SEC-002 reason="needs on-line activation" owner='Build Team' until=2025-06-30 -->
+<Element />
+<Other /> <!-- wix-analyzer-disable-line BP-001 until=2025-13-01-->
+"#;
+        let ctx = SuppressionContext::parse(source);
+
+        // "-line" in the reason doesn't make it a same-line suppression
+        assert!(ctx.is_suppressed("SEC-002", 3));
+        assert!(ctx.is_suppressed("BP-001", 4));
+
+        let first = &ctx.suppressions()[0];
+        assert_eq!(first.kind, SuppressionKind::NextLine);
+        assert_eq!(first.rules.len(), 2);
+        assert_eq!(first.lines, 3..=3);
+        assert_eq!(
+            first.meta.reason.as_deref(),
+            Some("needs on-line activation")
+        );
+        assert_eq!(first.meta.owner.as_deref(), Some("Build Team"));
+
+        let june = NaiveDate::from_ymd_opt(2025, 6, 30).unwrap();
+        assert_eq!(first.meta.until_date(), Some(june));
+        assert!(!first.meta.is_expired(june));
+        assert!(first.meta.is_expired(june.succ_opt().unwrap()));
+
+        let second = &ctx.suppressions()[1];
+        assert_eq!(second.kind, SuppressionKind::Line);
+        assert_eq!(second.meta.until.as_deref(), Some("2025-13-01"));
+        assert!(second.meta.is_expired(june));
+    }
 }
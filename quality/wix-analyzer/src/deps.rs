@@ -263,16 +263,158 @@ impl DependencyGraph {
 pub struct DependencyAnalyzer;
 
 impl DependencyAnalyzer {
-    /// Analyze a binary file for dependencies
-    pub fn analyze_binary(_path: &PathBuf) -> Vec<Dependency> {
-        // Simulated analysis - in production would parse PE headers
-        Vec::new()
+    /// Analyze a binary file for dependencies: the DLLs it imports
+    pub fn analyze_binary(path: &PathBuf) -> Vec<Dependency> {
+        let Ok(data) = std::fs::read(path) else {
+            return Vec::new();
+        };
+        let Ok(pe) = crate::pe::PeFile::parse(&data) else {
+            return Vec::new();
+        };
+        pe.imports()
+            .into_iter()
+            .map(|import| {
+                let dep_type = match crate::imports::Redistributable::providing(&import.dll) {
+                    Some(redistributable) => redistributable.dependency_type(),
+                    None => DependencyType::NativeDll,
+                };
+                let dep = Dependency::new(&import.dll, dep_type).with_source(path.clone());
+                if import.delay_loaded {
+                    dep.optional()
+                } else {
+                    dep
+                }
+            })
+            .collect()
     }
 
     /// Analyze a WiX source file for dependencies
-    pub fn analyze_wix_source(_path: &PathBuf) -> Vec<Dependency> {
-        // Would parse WiX XML for extension references, merge modules, etc.
-        Vec::new()
+    pub fn analyze_wix_source(path: &PathBuf) -> Vec<Dependency> {
+        let Ok(source) = std::fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        let Ok(doc) = roxmltree::Document::parse(&source) else {
+            return Vec::new();
+        };
+        Self::dependencies_from_document(doc.root())
+            .into_iter()
+            .map(|d| d.with_source(path.clone()))
+            .collect()
+    }
+
+    /// Prerequisites declared in authoring: merge modules, bundle chain
+    /// packages and .NET checks
+    pub fn dependencies_from_document(root: roxmltree::Node) -> Vec<Dependency> {
+        let mut dependencies: Vec<Dependency> = Vec::new();
+        let mut add = |dep: Dependency| {
+            if !dependencies
+                .iter()
+                .any(|d| d.name == dep.name && d.version == dep.version)
+            {
+                dependencies.push(dep);
+            }
+        };
+
+        for node in root.descendants().filter(|n| n.is_element()) {
+            match node.tag_name().name() {
+                "Merge" => {
+                    if let Some(id) = node.attribute("Id") {
+                        add(Dependency::new(id, DependencyType::MergeModule).bundled());
+                    }
+                }
+                "ExePackage" | "MsiPackage" | "MsuPackage" | "BundlePackage" => {
+                    let source = node.attribute("SourceFile").or(node.attribute("Name"));
+                    let Some(name) = node
+                        .attribute("DisplayName")
+                        .or(node.attribute("Id"))
+                        .or(source)
+                    else {
+                        continue;
+                    };
+                    let dep_type = source
+                        .map(Self::redistributable_type)
+                        .filter(|t| *t != DependencyType::Other)
+                        .unwrap_or_else(|| Self::redistributable_type(name));
+                    let mut dep = Dependency::new(name, dep_type);
+                    if let Some(url) = node.attribute("DownloadUrl") {
+                        dep = dep.with_download_url(url);
+                    }
+                    if node.attribute("Permanent") == Some("yes")
+                        || node.attribute("Vital") == Some("no")
+                    {
+                        dep = dep.optional();
+                    }
+                    if node.attribute("Compressed") != Some("no") && dep.download_url.is_none() {
+                        dep = dep.bundled();
+                    }
+                    add(dep);
+                }
+                "PropertyRef" => {
+                    if let Some(version) =
+                        node.attribute("Id").and_then(Self::netfx_property_version)
+                    {
+                        add(
+                            Dependency::new(".NET Framework", DependencyType::DotNetFramework)
+                                .with_version(&version),
+                        );
+                    }
+                }
+                "DotNetCompatibilityCheck" => {
+                    let runtime = node.attribute("RuntimeType").unwrap_or("core");
+                    let name = match runtime {
+                        "aspnet" => "ASP.NET Core Runtime",
+                        "desktop" => ".NET Desktop Runtime",
+                        _ => ".NET Runtime",
+                    };
+                    let mut dep = Dependency::new(name, DependencyType::DotNetCore);
+                    if let Some(version) = node.attribute("Version") {
+                        dep = dep.with_version(version);
+                    }
+                    add(dep);
+                }
+                _ => {}
+            }
+        }
+
+        dependencies
+    }
+
+    /// Kind of redistributable a package or file name refers to
+    pub fn redistributable_type(name: &str) -> DependencyType {
+        let name = name.to_ascii_lowercase();
+        let file = name.rsplit(['\\', '/']).next().unwrap_or(&name);
+        if file.contains("vc_redist") || file.contains("vcredist") || file.contains("_crt_") {
+            DependencyType::VCRuntime
+        } else if file.starts_with("ndp") || file.contains("netfx") || file.contains("dotnetfx") {
+            DependencyType::DotNetFramework
+        } else if file.contains("dotnet-runtime")
+            || file.contains("windowsdesktop-runtime")
+            || file.contains("aspnetcore-runtime")
+            || file.contains("dotnet-hosting")
+        {
+            DependencyType::DotNetCore
+        } else if file.contains("directx") || file.contains("dxsetup") {
+            DependencyType::DirectX
+        } else {
+            DependencyType::Other
+        }
+    }
+
+    /// .NET Framework version checked by a WixNetFxExtension property, e.g.
+    /// `WIX_IS_NETFRAMEWORK_472_OR_LATER_INSTALLED` or `NETFRAMEWORK45`
+    fn netfx_property_version(id: &str) -> Option<String> {
+        let digits = id
+            .strip_prefix("WIX_IS_NETFRAMEWORK_")
+            .and_then(|rest| rest.strip_suffix("_OR_LATER_INSTALLED"))
+            .or_else(|| id.strip_prefix("NETFRAMEWORK"))?;
+        if digits.len() < 2 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        if digits.len() > 3 {
+            return None;
+        }
+        let version: Vec<String> = digits.chars().map(|c| c.to_string()).collect();
+        Some(version.join("."))
     }
 
     /// Check if VC++ runtime is needed
@@ -361,6 +503,46 @@ impl WixExtensionHelper {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_dependencies_from_authoring() {
+        let source = r#"<Wix xmlns:netfx="http://wixtoolset.org/schemas/v4/wxs/netfx">
+  <Package>
+    <PropertyRef Id="WIX_IS_NETFRAMEWORK_472_OR_LATER_INSTALLED" />
+    <PropertyRef Id="NETFRAMEWORK45" />
+    <PropertyRef Id="ARPINSTALLLOCATION" />
+    <Merge Id="CRT" SourceFile="Microsoft_VC143_CRT_x64.msm" />
+    <netfx:DotNetCompatibilityCheck Property="NET8" RuntimeType="desktop" Version="8.0.0" />
+  </Package>
+  <Bundle>
+    <Chain>
+      <ExePackage Id="VCRedist" SourceFile="redist\vc_redist.x64.exe" />
+      <ExePackage Id="DotNet" DownloadUrl="https://example.com/x" Name="windowsdesktop-runtime-8.0-win-x64.exe" />
+    </Chain>
+  </Bundle>
+</Wix>"#;
+        let doc = roxmltree::Document::parse(source).unwrap();
+        let deps = DependencyAnalyzer::dependencies_from_document(doc.root());
+        let find = |name: &str| deps.iter().find(|d| d.name == name).unwrap();
+
+        let netfx: Vec<_> = deps
+            .iter()
+            .filter(|d| d.dep_type == DependencyType::DotNetFramework)
+            .filter_map(|d| d.version.as_deref())
+            .collect();
+        assert_eq!(netfx, vec!["4.7.2", "4.5"]);
+        assert!(find("CRT").bundled);
+        assert_eq!(find("CRT").dep_type, DependencyType::MergeModule);
+        assert_eq!(
+            find(".NET Desktop Runtime").version.as_deref(),
+            Some("8.0.0")
+        );
+        assert_eq!(find("VCRedist").dep_type, DependencyType::VCRuntime);
+        assert!(find("VCRedist").bundled);
+        assert_eq!(find("DotNet").dep_type, DependencyType::DotNetCore);
+        assert!(!find("DotNet").bundled);
+        assert_eq!(deps.len(), 6);
+    }
+
     #[test]
     fn test_dependency_type_as_str() {
         assert_eq!(DependencyType::DotNetFramework.as_str(), ".NET Framework");
@@ -1,14 +1,28 @@
 //! Condition types for data-driven rules
 //!
 //! Conditions are declarative expressions that can be evaluated against nodes.
-//! They form the core of data-driven rules.
+//! They form the core of data-driven rules and rule packs.
+//!
+//! In JSON a condition is an object tagged by `type`:
+//!
+//! ```json
+//! {"type": "all", "conditions": [
+//!   {"type": "attribute_exists", "name": "Guid"},
+//!   {"type": "child_count", "element": "File", "op": ">", "value": 1}
+//! ]}
+//! ```
+//!
+//! The names of the older plugin format (`missing_attribute` with
+//! `attribute`) are accepted as aliases.
 
 use super::types::Node;
 use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 
 /// A condition that can be evaluated against a node
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "ConditionDef", into = "ConditionDef")]
 pub enum Condition {
     /// Attribute is missing: { name: "Id" }
     AttributeMissing { name: String },
@@ -68,6 +82,9 @@ pub enum Condition {
     /// Text content contains substring
     TextContains { substring: String },
 
+    /// Element has no child elements
+    NoChildren,
+
     /// All conditions must be true (AND)
     All(Vec<Condition>),
 
@@ -118,6 +135,223 @@ impl CompareOp {
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+}
+
+impl Serialize for CompareOp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CompareOp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        CompareOp::from_str(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown comparison '{}'", s)))
+    }
+}
+
+/// JSON shape of a condition; compound conditions become objects so the
+/// whole tree can be tagged by `type`
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ConditionDef {
+    #[serde(alias = "missing_attribute")]
+    AttributeMissing {
+        #[serde(alias = "attribute")]
+        name: String,
+    },
+    AttributeEquals {
+        #[serde(alias = "attribute")]
+        name: String,
+        value: String,
+    },
+    AttributeNotEquals {
+        #[serde(alias = "attribute")]
+        name: String,
+        value: String,
+    },
+    AttributeMatches {
+        #[serde(alias = "attribute")]
+        name: String,
+        pattern: String,
+    },
+    AttributeNotMatches {
+        #[serde(alias = "attribute")]
+        name: String,
+        pattern: String,
+    },
+    AttributeIn {
+        #[serde(alias = "attribute")]
+        name: String,
+        values: Vec<String>,
+    },
+    AttributeNotIn {
+        #[serde(alias = "attribute")]
+        name: String,
+        values: Vec<String>,
+    },
+    AttributeExists {
+        #[serde(alias = "attribute")]
+        name: String,
+    },
+    HasChild {
+        element: String,
+    },
+    MissingChild {
+        element: String,
+    },
+    ChildCount {
+        element: String,
+        op: CompareOp,
+        value: usize,
+    },
+    ParentIs {
+        element: String,
+    },
+    ParentNot {
+        element: String,
+    },
+    ParentIn {
+        elements: Vec<String>,
+    },
+    ParentNotIn {
+        elements: Vec<String>,
+    },
+    DepthExceeds {
+        max: usize,
+    },
+    TextMatches {
+        pattern: String,
+    },
+    TextContains {
+        substring: String,
+    },
+    NoChildren,
+    All {
+        conditions: Vec<Condition>,
+    },
+    Any {
+        conditions: Vec<Condition>,
+    },
+    Not {
+        condition: Box<Condition>,
+    },
+    Always,
+    Never,
+}
+
+impl From<ConditionDef> for Condition {
+    fn from(def: ConditionDef) -> Self {
+        match def {
+            ConditionDef::AttributeMissing { name } => Condition::AttributeMissing { name },
+            ConditionDef::AttributeEquals { name, value } => {
+                Condition::AttributeEquals { name, value }
+            }
+            ConditionDef::AttributeNotEquals { name, value } => {
+                Condition::AttributeNotEquals { name, value }
+            }
+            ConditionDef::AttributeMatches { name, pattern } => {
+                Condition::AttributeMatches { name, pattern }
+            }
+            ConditionDef::AttributeNotMatches { name, pattern } => {
+                Condition::AttributeNotMatches { name, pattern }
+            }
+            ConditionDef::AttributeIn { name, values } => Condition::AttributeIn { name, values },
+            ConditionDef::AttributeNotIn { name, values } => {
+                Condition::AttributeNotIn { name, values }
+            }
+            ConditionDef::AttributeExists { name } => Condition::AttributeExists { name },
+            ConditionDef::HasChild { element } => Condition::HasChild { element },
+            ConditionDef::MissingChild { element } => Condition::MissingChild { element },
+            ConditionDef::ChildCount { element, op, value } => {
+                Condition::ChildCount { element, op, value }
+            }
+            ConditionDef::ParentIs { element } => Condition::ParentIs { element },
+            ConditionDef::ParentNot { element } => Condition::ParentNot { element },
+            ConditionDef::ParentIn { elements } => Condition::ParentIn { elements },
+            ConditionDef::ParentNotIn { elements } => Condition::ParentNotIn { elements },
+            ConditionDef::DepthExceeds { max } => Condition::DepthExceeds { max },
+            ConditionDef::TextMatches { pattern } => Condition::TextMatches { pattern },
+            ConditionDef::TextContains { substring } => Condition::TextContains { substring },
+            ConditionDef::NoChildren => Condition::NoChildren,
+            ConditionDef::All { conditions } => Condition::All(conditions),
+            ConditionDef::Any { conditions } => Condition::Any(conditions),
+            ConditionDef::Not { condition } => Condition::Not(condition),
+            ConditionDef::Always => Condition::Always,
+            ConditionDef::Never => Condition::Never,
+        }
+    }
+}
+
+impl From<Condition> for ConditionDef {
+    fn from(condition: Condition) -> Self {
+        match condition {
+            Condition::AttributeMissing { name } => ConditionDef::AttributeMissing { name },
+            Condition::AttributeEquals { name, value } => {
+                ConditionDef::AttributeEquals { name, value }
+            }
+            Condition::AttributeNotEquals { name, value } => {
+                ConditionDef::AttributeNotEquals { name, value }
+            }
+            Condition::AttributeMatches { name, pattern } => {
+                ConditionDef::AttributeMatches { name, pattern }
+            }
+            Condition::AttributeNotMatches { name, pattern } => {
+                ConditionDef::AttributeNotMatches { name, pattern }
+            }
+            Condition::AttributeIn { name, values } => ConditionDef::AttributeIn { name, values },
+            Condition::AttributeNotIn { name, values } => {
+                ConditionDef::AttributeNotIn { name, values }
+            }
+            Condition::AttributeExists { name } => ConditionDef::AttributeExists { name },
+            Condition::HasChild { element } => ConditionDef::HasChild { element },
+            Condition::MissingChild { element } => ConditionDef::MissingChild { element },
+            Condition::ChildCount { element, op, value } => {
+                ConditionDef::ChildCount { element, op, value }
+            }
+            Condition::ParentIs { element } => ConditionDef::ParentIs { element },
+            Condition::ParentNot { element } => ConditionDef::ParentNot { element },
+            Condition::ParentIn { elements } => ConditionDef::ParentIn { elements },
+            Condition::ParentNotIn { elements } => ConditionDef::ParentNotIn { elements },
+            Condition::DepthExceeds { max } => ConditionDef::DepthExceeds { max },
+            Condition::TextMatches { pattern } => ConditionDef::TextMatches { pattern },
+            Condition::TextContains { substring } => ConditionDef::TextContains { substring },
+            Condition::NoChildren => ConditionDef::NoChildren,
+            Condition::All(conditions) => ConditionDef::All { conditions },
+            Condition::Any(conditions) => ConditionDef::Any { conditions },
+            Condition::Not(condition) => ConditionDef::Not { condition },
+            Condition::Always => ConditionDef::Always,
+            Condition::Never => ConditionDef::Never,
+        }
+    }
+}
+
+impl Condition {
+    /// Regex patterns used anywhere in this condition
+    pub fn patterns(&self) -> Vec<&str> {
+        match self {
+            Condition::AttributeMatches { pattern, .. }
+            | Condition::AttributeNotMatches { pattern, .. }
+            | Condition::TextMatches { pattern } => vec![pattern.as_str()],
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                conditions.iter().flat_map(|c| c.patterns()).collect()
+            }
+            Condition::Not(condition) => condition.patterns(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 /// Evaluates conditions against nodes
@@ -208,6 +442,8 @@ impl ConditionEvaluator {
 
             Condition::TextContains { substring } => node.text().contains(substring),
 
+            Condition::NoChildren => node.children().is_empty(),
+
             Condition::All(conditions) => conditions.iter().all(|c| self.evaluate(c, node)),
 
             Condition::Any(conditions) => conditions.iter().any(|c| self.evaluate(c, node)),
@@ -534,4 +770,47 @@ mod tests {
         assert_eq!(CompareOp::from_str(">="), Some(CompareOp::Ge));
         assert_eq!(CompareOp::from_str("invalid"), None);
     }
+
+    #[test]
+    fn test_condition_json() {
+        let json = r#"{"type": "all", "conditions": [
+            {"type": "missing_attribute", "attribute": "Id"},
+            {"type": "child_count", "element": "File", "op": ">", "value": 1},
+            {"type": "not", "condition": {"type": "attribute_matches", "name": "Guid", "pattern": "^\\*$"}}
+        ]}"#;
+        let cond: Condition = serde_json::from_str(json).unwrap();
+        let Condition::All(conditions) = &cond else {
+            panic!("expected all, got {:?}", cond);
+        };
+        assert!(matches!(&conditions[0], Condition::AttributeMissing { name } if name == "Id"));
+        assert!(matches!(
+            conditions[1],
+            Condition::ChildCount {
+                op: CompareOp::Gt,
+                value: 1,
+                ..
+            }
+        ));
+        assert_eq!(cond.patterns(), vec!["^\\*$"]);
+
+        // Written back with the current names
+        let written = serde_json::to_value(&cond).unwrap();
+        assert_eq!(written["conditions"][0]["type"], "attribute_missing");
+        assert_eq!(written["conditions"][0]["name"], "Id");
+        assert_eq!(written["conditions"][1]["op"], ">");
+
+        let err = serde_json::from_str::<Condition>(
+            r#"{"type": "child_count", "element": "File", "op": "~", "value": 1}"#,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown comparison '~'"));
+    }
+
+    #[test]
+    fn test_no_children() {
+        let mut evaluator = ConditionEvaluator::new();
+        assert!(evaluator.evaluate(&Condition::NoChildren, &MockNode::new("Feature")));
+        let node = MockNode::new("Feature").with_child(MockNode::new("ComponentRef"));
+        assert!(!evaluator.evaluate(&Condition::NoChildren, &node));
+    }
 }
@@ -1,5 +1,9 @@
 //! Auto-fix system for applying suggested fixes
 
 mod engine;
+mod refactor;
+mod workspace;
 
 pub use engine::{FixEngine, FixError, FixPreview, FixResult};
+pub use refactor::{RefactorContext, RefactorError, RenameKind};
+pub use workspace::{position_to_offset, TextEdit, WorkspaceEdit};
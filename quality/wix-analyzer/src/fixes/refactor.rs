@@ -0,0 +1,1243 @@
+//! Project-wide refactorings
+//!
+//! Refactorings look at every loaded `.wxs`/`.wxi`/`.wxl` file and return a
+//! [`WorkspaceEdit`]; nothing is written until the edit is applied.
+
+use super::workspace::{TextEdit, WorkspaceEdit};
+use roxmltree::{Document, Node};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use wix_references::{is_standard_name, SymbolKind, STANDARD_DIRECTORIES};
+
+/// Publish events whose Value is a Feature Id
+const FEATURE_EVENTS: &[&str] = &["AddLocal", "AddSource", "Remove", "Reinstall"];
+
+/// Kinds of symbol that can be renamed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameKind {
+    Component,
+    Directory,
+    Feature,
+    Property,
+}
+
+impl RenameKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Component => "Component",
+            Self::Directory => "Directory",
+            Self::Feature => "Feature",
+            Self::Property => "Property",
+        }
+    }
+
+    /// Kind of the symbol in the reference index
+    fn symbol_kind(&self) -> SymbolKind {
+        match self {
+            Self::Component => SymbolKind::Component,
+            Self::Directory => SymbolKind::Directory,
+            Self::Feature => SymbolKind::Feature,
+            Self::Property => SymbolKind::Property,
+        }
+    }
+
+    /// Elements whose Id defines the symbol
+    fn definition_elements(&self) -> &'static [&'static str] {
+        match self {
+            Self::Component => &["Component"],
+            Self::Directory => &["Directory"],
+            Self::Feature => &["Feature"],
+            // SetProperty alone is enough to bring a property into being
+            Self::Property => &["Property", "SetProperty"],
+        }
+    }
+
+    /// Elements whose Id refers to the symbol
+    fn reference_elements(&self) -> &'static [&'static str] {
+        match self {
+            Self::Component => &["ComponentRef"],
+            Self::Directory => &["DirectoryRef", "SetDirectory"],
+            Self::Feature => &["FeatureRef"],
+            Self::Property => &["PropertyRef", "SetProperty"],
+        }
+    }
+
+    /// Attributes, on any element, whose value refers to the symbol
+    fn reference_attributes(&self) -> &'static [&'static str] {
+        match self {
+            Self::Component => &[],
+            Self::Directory => &[
+                "Directory",
+                "DestinationDirectory",
+                "SourceDirectory",
+                "WorkingDirectory",
+            ],
+            Self::Feature => &["Feature"],
+            Self::Property => &["Property", "DestinationProperty", "SourceProperty"],
+        }
+    }
+
+    fn is_direct_reference(&self, node: &Node, attribute: &str) -> bool {
+        let tag = node.tag_name().name();
+        if attribute == "Id" {
+            return self.definition_elements().contains(&tag)
+                || self.reference_elements().contains(&tag);
+        }
+        if *self == Self::Feature
+            && tag == "Publish"
+            && attribute == "Value"
+            && node
+                .attribute("Event")
+                .is_some_and(|e| FEATURE_EVENTS.contains(&e))
+        {
+            return true;
+        }
+        self.reference_attributes().contains(&attribute)
+    }
+
+    /// Whether an identifier embedded in text refers to the symbol, given
+    /// the character before it and the one after it
+    fn matches_embedded(&self, sigil: Option<char>, next: Option<char>, condition: bool) -> bool {
+        match self {
+            // Directories are properties at run time
+            Self::Property | Self::Directory => {
+                (sigil == Some('[') && next == Some(']')) || (condition && sigil.is_none())
+            }
+            Self::Component => {
+                (sigil == Some('$') && (condition || next == Some(']')))
+                    || (condition && sigil == Some('?'))
+            }
+            Self::Feature => condition && matches!(sigil, Some('&') | Some('!')),
+        }
+    }
+}
+
+/// Errors preparing a refactoring
+#[derive(Debug)]
+pub enum RefactorError {
+    ReadError(PathBuf, String),
+    ParseError(PathBuf, String),
+    NotFound(String),
+    Conflict(String),
+    Unsupported(String),
+}
+
+impl std::fmt::Display for RefactorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadError(path, msg) => write!(f, "Failed to read '{}': {}", path.display(), msg),
+            Self::ParseError(path, msg) => {
+                write!(f, "Failed to parse '{}': {}", path.display(), msg)
+            }
+            Self::NotFound(msg) | Self::Conflict(msg) | Self::Unsupported(msg) => {
+                write!(f, "{}", msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RefactorError {}
+
+struct SourceFile {
+    path: PathBuf,
+    source: String,
+}
+
+/// The project files a refactoring may touch
+#[derive(Default)]
+pub struct RefactorContext {
+    files: Vec<SourceFile>,
+}
+
+impl RefactorContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load files from disk
+    pub fn load(paths: &[PathBuf]) -> Result<Self, RefactorError> {
+        let mut context = Self::new();
+        for path in paths {
+            let source = std::fs::read_to_string(path)
+                .map_err(|e| RefactorError::ReadError(path.clone(), e.to_string()))?;
+            context.add_source(path, source);
+        }
+        Ok(context)
+    }
+
+    /// Add a file with its (possibly unsaved) content
+    pub fn add_source(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) {
+        self.files.push(SourceFile {
+            path: path.into(),
+            source: source.into(),
+        });
+    }
+
+    pub fn with_source(mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> Self {
+        self.add_source(path, source);
+        self
+    }
+
+    /// Current content of a loaded file
+    pub fn source(&self, path: &Path) -> Option<&str> {
+        self.file(path).map(|f| f.source.as_str())
+    }
+
+    fn file(&self, path: &Path) -> Option<&SourceFile> {
+        self.files.iter().find(|f| f.path == path)
+    }
+
+    fn parse(&self) -> Result<Vec<(&SourceFile, Document<'_>)>, RefactorError> {
+        self.files
+            .iter()
+            .map(|file| {
+                Document::parse(&file.source)
+                    .map(|doc| (file, doc))
+                    .map_err(|e| RefactorError::ParseError(file.path.clone(), e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Rename a symbol's Id and every reference to it
+    pub fn rename(
+        &self,
+        kind: RenameKind,
+        old: &str,
+        new: &str,
+    ) -> Result<WorkspaceEdit, RefactorError> {
+        if !is_identifier(new) {
+            return Err(RefactorError::Unsupported(format!(
+                "'{}' is not a valid identifier",
+                new
+            )));
+        }
+        if old == new {
+            return Err(RefactorError::Unsupported(format!(
+                "{} is already named '{}'",
+                kind.as_str(),
+                new
+            )));
+        }
+        if is_standard_name(kind.symbol_kind(), old) {
+            return Err(RefactorError::Unsupported(format!(
+                "'{}' is a standard directory or property and cannot be renamed",
+                old
+            )));
+        }
+        if is_standard_name(kind.symbol_kind(), new) {
+            return Err(RefactorError::Conflict(format!(
+                "'{}' is a standard directory or property",
+                new
+            )));
+        }
+
+        let docs = self.parse()?;
+        let defined = |kind: RenameKind, id: &str| {
+            docs.iter().any(|(_, doc)| {
+                kind.definition_elements()
+                    .iter()
+                    .any(|e| elements(doc, e).any(|n| n.attribute("Id") == Some(id)))
+            })
+        };
+        if !defined(kind, old) {
+            return Err(RefactorError::NotFound(format!(
+                "{} '{}' is not defined",
+                kind.as_str(),
+                old
+            )));
+        }
+        // Directories and properties share one namespace
+        if let Some(existing) = [
+            RenameKind::Component,
+            RenameKind::Directory,
+            RenameKind::Feature,
+            RenameKind::Property,
+        ]
+        .into_iter()
+        .filter(|k| k.symbol_kind().shares_namespace(kind.symbol_kind()))
+        .find(|k| defined(*k, new))
+        {
+            return Err(RefactorError::Conflict(format!(
+                "{} '{}' already exists",
+                existing.as_str(),
+                new
+            )));
+        }
+
+        let mut edit =
+            WorkspaceEdit::new(format!("Rename {} '{}' to '{}'", kind.as_str(), old, new));
+        for (file, doc) in &docs {
+            for node in doc.descendants() {
+                if node.is_element() {
+                    for attr in node.attributes() {
+                        let range = attr.range_value();
+                        let replacement = if attr.value() == old
+                            && kind.is_direct_reference(&node, attr.name())
+                        {
+                            Some(new.to_string())
+                        } else {
+                            let raw = &file.source[range.clone()];
+                            rewrite_embedded(kind, raw, old, new, attr.name() == "Condition")
+                        };
+                        if let Some(text) = replacement {
+                            edit.add(
+                                &file.path,
+                                TextEdit::from_offsets(&file.source, range.start, range.end, text),
+                            );
+                        }
+                    }
+                } else if node.is_text() {
+                    let range = node.range();
+                    let condition = node.parent_element().is_some_and(|p| {
+                        matches!(
+                            p.tag_name().name(),
+                            "Condition" | "Publish" | "Custom" | "Show" | "Level"
+                        )
+                    });
+                    let raw = &file.source[range.clone()];
+                    if let Some(text) = rewrite_embedded(kind, raw, old, new, condition) {
+                        edit.add(
+                            &file.path,
+                            TextEdit::from_offsets(&file.source, range.start, range.end, text),
+                        );
+                    }
+                }
+            }
+        }
+        Ok(edit)
+    }
+
+    /// Move components into a new `Fragment` with a `ComponentGroup`, and
+    /// reference the group where the components were included
+    ///
+    /// The fragment goes at the end of `into` (created when it is not a loaded
+    /// file), or at the end of `file`.
+    pub fn extract_component_group(
+        &self,
+        file: &Path,
+        components: &[&str],
+        group: &str,
+        into: Option<&Path>,
+    ) -> Result<WorkspaceEdit, RefactorError> {
+        if components.is_empty() {
+            return Err(RefactorError::Unsupported(
+                "No components selected".to_string(),
+            ));
+        }
+        if !is_identifier(group) {
+            return Err(RefactorError::Unsupported(format!(
+                "'{}' is not a valid identifier",
+                group
+            )));
+        }
+
+        let docs = self.parse()?;
+        if docs.iter().any(|(_, doc)| {
+            elements(doc, "ComponentGroup").any(|n| n.attribute("Id") == Some(group))
+        }) {
+            return Err(RefactorError::Conflict(format!(
+                "ComponentGroup '{}' already exists",
+                group
+            )));
+        }
+        let (source_file, doc) = docs
+            .iter()
+            .find(|(f, _)| f.path == file)
+            .ok_or_else(|| RefactorError::NotFound(format!("{} is not loaded", file.display())))?;
+        let source = source_file.source.as_str();
+
+        let mut nodes = Vec::new();
+        for id in components {
+            let node = elements(doc, "Component")
+                .find(|n| n.attribute("Id") == Some(*id))
+                .ok_or_else(|| {
+                    RefactorError::NotFound(format!(
+                        "Component '{}' is not defined in {}",
+                        id,
+                        file.display()
+                    ))
+                })?;
+            nodes.push(node);
+        }
+        nodes.sort_by_key(|n| n.range().start);
+        nodes.dedup_by_key(|n| n.range().start);
+
+        // Components declared inside a Feature or ComponentGroup lose that
+        // membership when moved, so all must share one such parent
+        let parent = membership_parent(&nodes[0]);
+        if nodes
+            .iter()
+            .any(|n| membership_parent(n).map(|p| p.range()) != parent.map(|p| p.range()))
+        {
+            return Err(RefactorError::Conflict(
+                "Selected components belong to different features or groups".to_string(),
+            ));
+        }
+
+        let unit = indent_unit(source);
+        let group_ref =
+            |indent: &str| format!("{}<ComponentGroupRef Id=\"{}\" />\n", indent, group);
+        let mut edit = WorkspaceEdit::new(format!(
+            "Extract {} component(s) into ComponentGroup '{}'",
+            nodes.len(),
+            group
+        ));
+
+        let mut body = String::new();
+        for (i, node) in nodes.iter().enumerate() {
+            let text = match component_directory(node) {
+                Some(dir) if node.attribute("Directory").is_none() => {
+                    with_directory(source, node, dir)
+                }
+                _ => source[node.range()].to_string(),
+            };
+            body.push_str(&reindent(
+                &text,
+                &line_indent(source, node.range().start),
+                &unit.repeat(3),
+            ));
+            body.push('\n');
+
+            let (start, end) = line_extent(source, node.range());
+            let replacement = match parent {
+                Some(_) if i == 0 => group_ref(&line_indent(source, node.range().start)),
+                _ => String::new(),
+            };
+            edit.add(
+                &source_file.path,
+                TextEdit::from_offsets(source, start, end, replacement),
+            );
+        }
+
+        // Components referenced from features: swap a complete set of
+        // ComponentRefs for the group
+        if parent.is_none() {
+            let ids: BTreeSet<&str> = nodes.iter().filter_map(|n| n.attribute("Id")).collect();
+            for (ref_file, ref_doc) in &docs {
+                let mut parents: Vec<Node> = elements(ref_doc, "ComponentRef")
+                    .filter(|n| n.attribute("Id").is_some_and(|id| ids.contains(id)))
+                    .filter_map(|n| n.parent_element())
+                    .collect();
+                parents.dedup_by_key(|p| p.range().start);
+
+                for ref_parent in parents {
+                    let refs: Vec<Node> = ref_parent
+                        .children()
+                        .filter(|c| c.has_tag_name("ComponentRef"))
+                        .filter(|c| c.attribute("Id").is_some_and(|id| ids.contains(id)))
+                        .collect();
+                    let referenced: BTreeSet<&str> =
+                        refs.iter().filter_map(|r| r.attribute("Id")).collect();
+                    if referenced != ids {
+                        continue;
+                    }
+                    for (i, r) in refs.iter().enumerate() {
+                        let (start, end) = line_extent(&ref_file.source, r.range());
+                        let replacement = if i == 0 {
+                            group_ref(&line_indent(&ref_file.source, r.range().start))
+                        } else {
+                            String::new()
+                        };
+                        edit.add(
+                            &ref_file.path,
+                            TextEdit::from_offsets(&ref_file.source, start, end, replacement),
+                        );
+                    }
+                }
+            }
+        }
+
+        let fragment = format!(
+            "{unit}<Fragment>\n{unit}{unit}<ComponentGroup Id=\"{group}\">\n{body}{unit}{unit}</ComponentGroup>\n{unit}</Fragment>\n",
+            unit = unit,
+            group = group,
+            body = body,
+        );
+        let target = into.unwrap_or(file);
+        match docs.iter().find(|(f, _)| f.path == target) {
+            Some((target_file, target_doc)) => {
+                let offset =
+                    root_close_offset(&target_file.source, target_doc).ok_or_else(|| {
+                        RefactorError::Unsupported(format!(
+                            "{} has no closing root element",
+                            target.display()
+                        ))
+                    })?;
+                edit.add(
+                    &target_file.path,
+                    TextEdit::from_offsets(&target_file.source, offset, offset, fragment),
+                );
+            }
+            None => {
+                let namespace = doc
+                    .root_element()
+                    .tag_name()
+                    .namespace()
+                    .unwrap_or_default();
+                edit.create_file(
+                    target,
+                    format!(
+                        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<Wix xmlns=\"{}\">\n{}</Wix>\n",
+                        namespace, fragment
+                    ),
+                );
+            }
+        }
+
+        Ok(edit)
+    }
+
+    /// Replace the only `ComponentGroupRef` to a group with the group's
+    /// contents, and remove the group
+    pub fn inline_component_group(&self, group: &str) -> Result<WorkspaceEdit, RefactorError> {
+        let docs = self.parse()?;
+        let find = |tag: &'static str| {
+            docs.iter()
+                .flat_map(|(file, doc)| {
+                    elements(doc, tag)
+                        .filter(|n| n.attribute("Id") == Some(group))
+                        .map(move |n| (*file, n))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let definitions = find("ComponentGroup");
+        let (def_file, def) = match definitions.as_slice() {
+            [] => {
+                return Err(RefactorError::NotFound(format!(
+                    "ComponentGroup '{}' is not defined",
+                    group
+                )))
+            }
+            [one] => *one,
+            _ => {
+                return Err(RefactorError::Conflict(format!(
+                    "ComponentGroup '{}' is defined {} times",
+                    group,
+                    definitions.len()
+                )))
+            }
+        };
+        let references = find("ComponentGroupRef");
+        let [(ref_file, reference)] = references.as_slice() else {
+            return Err(RefactorError::Unsupported(format!(
+                "ComponentGroup '{}' is referenced {} times; only single-use groups can be inlined",
+                group,
+                references.len()
+            )));
+        };
+
+        if let Some(attr) = def
+            .attributes()
+            .find(|a| !matches!(a.name(), "Id" | "Directory"))
+        {
+            return Err(RefactorError::Unsupported(format!(
+                "ComponentGroup '{}' sets {}, which cannot be inlined",
+                group,
+                attr.name()
+            )));
+        }
+        if let Some(attr) = reference.attributes().find(|a| a.name() != "Id") {
+            return Err(RefactorError::Unsupported(format!(
+                "The reference to '{}' sets {}, which cannot be inlined",
+                group,
+                attr.name()
+            )));
+        }
+        if ref_file.path == def_file.path && reference.ancestors().any(|a| a == def) {
+            return Err(RefactorError::Unsupported(format!(
+                "ComponentGroup '{}' references itself",
+                group
+            )));
+        }
+
+        let def_source = def_file.source.as_str();
+        let indent = line_indent(&ref_file.source, reference.range().start);
+        let mut body = String::new();
+        for child in def.children().filter(|c| c.is_element()) {
+            let text = match def.attribute("Directory") {
+                Some(dir)
+                    if child.has_tag_name("Component")
+                        && child.attribute("Directory").is_none() =>
+                {
+                    with_directory(def_source, &child, dir)
+                }
+                _ => def_source[child.range()].to_string(),
+            };
+            body.push_str(&reindent(
+                &text,
+                &line_indent(def_source, child.range().start),
+                &indent,
+            ));
+            body.push('\n');
+        }
+
+        let mut edit = WorkspaceEdit::new(format!("Inline ComponentGroup '{}'", group));
+        let (start, end) = line_extent(&ref_file.source, reference.range());
+        edit.add(
+            &ref_file.path,
+            TextEdit::from_offsets(&ref_file.source, start, end, body),
+        );
+
+        // Drop the enclosing Fragment when the group is all it holds
+        let removed = def
+            .parent_element()
+            .filter(|p| p.has_tag_name("Fragment"))
+            .filter(|p| p.children().filter(|c| c.is_element()).count() == 1)
+            .unwrap_or(def);
+        let (start, end) = line_extent(def_source, removed.range());
+        edit.add(
+            &def_file.path,
+            TextEdit::from_offsets(def_source, start, end, ""),
+        );
+        Ok(edit)
+    }
+
+    /// Convert `Directory` elements with standard Ids (and the `TARGETDIR`
+    /// trees holding them) to WiX v4 `StandardDirectory` elements
+    pub fn convert_standard_directories(&self) -> Result<WorkspaceEdit, RefactorError> {
+        let docs = self.parse()?;
+        let mut edit = WorkspaceEdit::new("Convert standard directories to StandardDirectory");
+
+        let is_standard = |n: &Node| {
+            n.has_tag_name("Directory")
+                && n.attribute("Id")
+                    .is_some_and(|id| STANDARD_DIRECTORIES.contains(&id))
+        };
+
+        for (file, doc) in &docs {
+            let source = file.source.as_str();
+            let mut unwrapped = Vec::new();
+
+            // TARGETDIR trees whose children are all standard directories unwrap
+            for wrapper in doc.descendants().filter(|n| {
+                matches!(n.tag_name().name(), "Directory" | "DirectoryRef")
+                    && n.attribute("Id") == Some("TARGETDIR")
+            }) {
+                let children: Vec<Node> = wrapper.children().filter(|c| c.is_element()).collect();
+                if children.is_empty()
+                    || !children
+                        .iter()
+                        .all(|c| is_standard(c) || c.has_tag_name("StandardDirectory"))
+                {
+                    continue;
+                }
+
+                let indent = line_indent(source, wrapper.range().start);
+                let mut text = String::new();
+                for child in &children {
+                    let converted = if is_standard(child) {
+                        standard_directory_text(source, child)
+                    } else {
+                        source[child.range()].to_string()
+                    };
+                    text.push_str(&reindent(
+                        &converted,
+                        &line_indent(source, child.range().start),
+                        &indent,
+                    ));
+                    text.push('\n');
+                }
+                let (start, end) = line_extent(source, wrapper.range());
+                edit.add(&file.path, TextEdit::from_offsets(source, start, end, text));
+                unwrapped.push(wrapper.range());
+            }
+
+            for node in doc.descendants().filter(|n| is_standard(n)) {
+                let in_unwrapped = unwrapped
+                    .iter()
+                    .any(|r| r.start <= node.range().start && node.range().end <= r.end);
+                let top_level = node.parent_element().is_some_and(|p| {
+                    matches!(
+                        p.tag_name().name(),
+                        "Fragment" | "Package" | "Module" | "Product"
+                    )
+                });
+                if in_unwrapped || !top_level {
+                    continue;
+                }
+                let range = node.range();
+                edit.add(
+                    &file.path,
+                    TextEdit::from_offsets(
+                        source,
+                        range.start,
+                        range.end,
+                        standard_directory_text(source, &node),
+                    ),
+                );
+            }
+        }
+        Ok(edit)
+    }
+}
+
+fn elements<'a, 'input>(
+    doc: &'a Document<'input>,
+    tag: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    doc.descendants().filter(move |n| n.has_tag_name(tag))
+}
+
+fn is_identifier(id: &str) -> bool {
+    let mut chars = id.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Rename identifiers embedded in formatted text (`[PROP]`, `[$Comp]`) and,
+/// in conditions, bare property names and `$Comp`/`&Feature` states
+fn rewrite_embedded(
+    kind: RenameKind,
+    raw: &str,
+    old: &str,
+    new: &str,
+    condition: bool,
+) -> Option<String> {
+    let mut out = String::new();
+    let mut last = 0;
+    let mut i = 0;
+    let mut sigil = None;
+    let mut in_quote = false;
+
+    while i < raw.len() {
+        let rest = &raw[i..];
+        if rest.starts_with('"') || rest.starts_with("&quot;") {
+            in_quote = condition && !in_quote;
+            i += if rest.starts_with('"') { 1 } else { 6 };
+            sigil = None;
+            continue;
+        }
+        if rest.starts_with("&amp;") {
+            sigil = Some('&');
+            i += 5;
+            continue;
+        }
+        if rest.starts_with('&') {
+            // Other entities (&lt; ...) are operators; a bare & is CDATA
+            match rest.find(';').filter(|end| *end <= 8) {
+                Some(end) => {
+                    sigil = None;
+                    i += end + 1;
+                }
+                None => {
+                    sigil = Some('&');
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        let c = rest.chars().next()?;
+        if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '.'))
+                .unwrap_or(rest.len());
+            let next = rest[len..].chars().next();
+            if &rest[..len] == old && !in_quote && kind.matches_embedded(sigil, next, condition) {
+                out.push_str(&raw[last..i]);
+                out.push_str(new);
+                last = i + len;
+            }
+            i += len;
+            sigil = None;
+            continue;
+        }
+
+        sigil = matches!(c, '$' | '?' | '!' | '[' | '%' | '#').then_some(c);
+        i += c.len_utf8();
+    }
+
+    (last > 0).then(|| {
+        out.push_str(&raw[last..]);
+        out
+    })
+}
+
+/// Feature or group a component is declared in
+fn membership_parent<'a, 'input>(node: &Node<'a, 'input>) -> Option<Node<'a, 'input>> {
+    node.parent_element().filter(|p| {
+        matches!(
+            p.tag_name().name(),
+            "Feature" | "FeatureRef" | "ComponentGroup"
+        )
+    })
+}
+
+/// Directory of a component from its ancestors
+fn component_directory<'a>(node: &Node<'a, '_>) -> Option<&'a str> {
+    node.ancestors()
+        .skip(1)
+        .find_map(|a| match a.tag_name().name() {
+            "Directory" | "DirectoryRef" | "StandardDirectory" => a.attribute("Id"),
+            "ComponentGroup" => a.attribute("Directory"),
+            _ => None,
+        })
+}
+
+/// Text of a component with a `Directory` attribute added after its Id
+fn with_directory(source: &str, node: &Node, directory: &str) -> String {
+    let range = node.range();
+    let at = node
+        .attributes()
+        .find(|a| a.name() == "Id")
+        .map_or(range.start + "<Component".len(), |a| a.range().end);
+    format!(
+        "{} Directory=\"{}\"{}",
+        &source[range.start..at],
+        directory,
+        &source[at..range.end]
+    )
+}
+
+/// A standard-Id `Directory` element rewritten as `StandardDirectory`
+fn standard_directory_text(source: &str, node: &Node) -> String {
+    let range = node.range();
+    let attrs_end = node
+        .attributes()
+        .map(|a| a.range().end)
+        .max()
+        .unwrap_or(range.start + "<Directory".len());
+    let start_tag_end = attrs_end + source[attrs_end..].find('>').unwrap_or(0) + 1;
+    let self_closing = source[..start_tag_end - 1].trim_end().ends_with('/');
+
+    let id = node.attribute("Id").unwrap_or_default();
+    if self_closing {
+        return format!("<StandardDirectory Id=\"{}\" />", id);
+    }
+    let inner = &source[start_tag_end..range.end];
+    let inner = inner
+        .strip_suffix("</Directory>")
+        .map(|s| format!("{}</StandardDirectory>", s))
+        .unwrap_or_else(|| inner.to_string());
+    format!("<StandardDirectory Id=\"{}\">{}", id, inner)
+}
+
+/// Indentation unit of a file, from its first indented element
+fn indent_unit(source: &str) -> String {
+    source
+        .lines()
+        .find_map(|line| {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            (!indent.is_empty() && trimmed.starts_with('<')).then(|| indent.to_string())
+        })
+        .unwrap_or_else(|| "    ".to_string())
+}
+
+/// Whitespace before `offset` on its line, or nothing if other text precedes it
+fn line_indent(source: &str, offset: usize) -> String {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &source[line_start..offset];
+    if prefix.chars().all(char::is_whitespace) {
+        prefix.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Extend a range to whole lines when it is alone on them, so removing it
+/// leaves no blank line
+fn line_extent(source: &str, range: std::ops::Range<usize>) -> (usize, usize) {
+    let line_start = source[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[range.end..]
+        .find('\n')
+        .map_or(source.len(), |i| range.end + i + 1);
+    let alone = source[line_start..range.start].trim().is_empty()
+        && source[range.end..line_end].trim().is_empty();
+    if alone {
+        (line_start, line_end)
+    } else {
+        (range.start, range.end)
+    }
+}
+
+/// Move a multi-line element from one indentation to another
+fn reindent(text: &str, from: &str, to: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = if i == 0 {
+                line
+            } else {
+                line.strip_prefix(from).unwrap_or(line.trim_start())
+            };
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", to, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Start of the line holding the root element's closing tag
+fn root_close_offset(source: &str, doc: &Document) -> Option<usize> {
+    let end = doc.root_element().range().end;
+    let close = source[..end].rfind("</")?;
+    let line_start = source[..close].rfind('\n').map_or(0, |i| i + 1);
+    Some(if source[line_start..close].trim().is_empty() {
+        line_start
+    } else {
+        close
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(context: &RefactorContext, edit: &WorkspaceEdit, file: &str) -> String {
+        let path = Path::new(file);
+        edit.apply_to(path, context.source(path).unwrap()).unwrap()
+    }
+
+    const PRODUCT: &str = r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+    <Package Name="App" Version="1.0.0" Manufacturer="Acme" UpgradeCode="{11111111-1111-1111-1111-111111111111}">
+        <Property Id="INSTALLMODE" Value="full" />
+        <Feature Id="Main">
+            <ComponentRef Id="AppExe" />
+            <ComponentRef Id="AppConfig" />
+        </Feature>
+        <Publish Dialog="Setup" Control="Next" Event="AddLocal" Value="Main" Condition="INSTALLMODE = &quot;full&quot; AND &amp;Main = 3" />
+        <CustomAction Id="SetCmd" Property="CMD" Value="[INSTALLFOLDER]app.exe [INSTALLMODE]" />
+    </Package>
+</Wix>"#;
+
+    const FILES: &str = r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+    <Fragment>
+        <DirectoryRef Id="INSTALLFOLDER">
+            <Component Id="AppExe" Condition="$AppExe = 3 OR INSTALLMODE">
+                <File Source="app.exe" />
+            </Component>
+            <Component Id="AppConfig">
+                <File Source="app.config" />
+            </Component>
+        </DirectoryRef>
+    </Fragment>
+</Wix>"#;
+
+    const STRINGS: &str = r#"<WixLocalization Culture="en-us" xmlns="http://wixtoolset.org/schemas/v4/wxl">
+    <String Id="Mode">Mode: [INSTALLMODE], not INSTALLMODE</String>
+</WixLocalization>"#;
+
+    fn context() -> RefactorContext {
+        RefactorContext::new()
+            .with_source("product.wxs", PRODUCT)
+            .with_source("files.wxs", FILES)
+            .with_source("strings.wxl", STRINGS)
+    }
+
+    #[test]
+    fn test_rename_property_across_files() {
+        let context = context();
+        let edit = context
+            .rename(RenameKind::Property, "INSTALLMODE", "SETUPMODE")
+            .unwrap();
+        assert_eq!(edit.files().len(), 3);
+
+        let product = apply(&context, &edit, "product.wxs");
+        assert!(product.contains(r#"<Property Id="SETUPMODE""#));
+        assert!(product.contains("Condition=\"SETUPMODE = &quot;full&quot; AND &amp;Main = 3\""));
+        assert!(product.contains("[INSTALLFOLDER]app.exe [SETUPMODE]"));
+
+        let files = apply(&context, &edit, "files.wxs");
+        assert!(files.contains(r#"Condition="$AppExe = 3 OR SETUPMODE""#));
+
+        // Formatted text is renamed; plain words are not
+        let strings = apply(&context, &edit, "strings.wxl");
+        assert!(strings.contains("Mode: [SETUPMODE], not INSTALLMODE"));
+    }
+
+    #[test]
+    fn test_rename_feature_and_component() {
+        let context = context();
+        let edit = context.rename(RenameKind::Feature, "Main", "Core").unwrap();
+        let product = apply(&context, &edit, "product.wxs");
+        assert!(product.contains(r#"<Feature Id="Core">"#));
+        assert!(product.contains(r#"Event="AddLocal" Value="Core""#));
+        assert!(product.contains("&amp;Core = 3"));
+        assert!(!edit.changes.contains_key(Path::new("files.wxs")));
+
+        let edit = context
+            .rename(RenameKind::Component, "AppExe", "MainExe")
+            .unwrap();
+        assert!(apply(&context, &edit, "product.wxs").contains(r#"<ComponentRef Id="MainExe" />"#));
+        let files = apply(&context, &edit, "files.wxs");
+        assert!(
+            files.contains(r#"<Component Id="MainExe" Condition="$MainExe = 3 OR INSTALLMODE">"#)
+        );
+    }
+
+    #[test]
+    fn test_rename_directory() {
+        let context = context();
+        let edit = context
+            .rename(RenameKind::Directory, "INSTALLFOLDER", "APPDIR")
+            .unwrap_err();
+        // Only referenced here, never defined
+        assert!(matches!(edit, RefactorError::NotFound(_)));
+
+        let context = context.with_source(
+            "dirs.wxs",
+            r#"<Wix><Fragment><StandardDirectory Id="ProgramFiles6432Folder"><Directory Id="INSTALLFOLDER" Name="App" /></StandardDirectory></Fragment></Wix>"#,
+        );
+        let edit = context
+            .rename(RenameKind::Directory, "INSTALLFOLDER", "APPDIR")
+            .unwrap();
+        assert!(apply(&context, &edit, "dirs.wxs").contains(r#"<Directory Id="APPDIR""#));
+        assert!(apply(&context, &edit, "files.wxs").contains(r#"<DirectoryRef Id="APPDIR">"#));
+        assert!(apply(&context, &edit, "product.wxs").contains("[APPDIR]app.exe"));
+
+        assert!(matches!(
+            context.rename(RenameKind::Directory, "ProgramFilesFolder", "X"),
+            Err(RefactorError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_directory_property_namespace() {
+        let context = context().with_source(
+            "dirs.wxs",
+            r#"<Wix><Fragment><StandardDirectory Id="ProgramFiles6432Folder"><Directory Id="INSTALLFOLDER" Name="App" /></StandardDirectory><SetProperty Id="LAUNCHPATH" Value="[INSTALLFOLDER]app.exe" After="CostFinalize" /></Fragment></Wix>"#,
+        );
+        assert!(matches!(
+            context.rename(RenameKind::Directory, "INSTALLFOLDER", "INSTALLMODE"),
+            Err(RefactorError::Conflict(_))
+        ));
+        assert!(matches!(
+            context.rename(RenameKind::Property, "INSTALLMODE", "INSTALLFOLDER"),
+            Err(RefactorError::Conflict(_))
+        ));
+
+        // Standard names are neither taken nor given up
+        assert!(matches!(
+            context.rename(RenameKind::Directory, "INSTALLFOLDER", "ProgramFilesFolder"),
+            Err(RefactorError::Conflict(_))
+        ));
+        assert!(matches!(
+            context.rename(RenameKind::Property, "INSTALLMODE", "ProductName"),
+            Err(RefactorError::Conflict(_))
+        ));
+        assert!(matches!(
+            context.rename(RenameKind::Property, "ALLUSERS", "PERUSER"),
+            Err(RefactorError::Unsupported(_))
+        ));
+
+        // Defined only by SetProperty
+        let edit = context
+            .rename(RenameKind::Property, "LAUNCHPATH", "APPPATH")
+            .unwrap();
+        assert!(apply(&context, &edit, "dirs.wxs").contains(r#"<SetProperty Id="APPPATH""#));
+    }
+
+    #[test]
+    fn test_rename_rejects_conflicts_and_bad_names() {
+        let context = context();
+        assert!(matches!(
+            context.rename(RenameKind::Component, "AppExe", "AppConfig"),
+            Err(RefactorError::Conflict(_))
+        ));
+        assert!(matches!(
+            context.rename(RenameKind::Component, "AppExe", "1bad"),
+            Err(RefactorError::Unsupported(_))
+        ));
+        assert!(matches!(
+            context.rename(RenameKind::Feature, "Missing", "Other"),
+            Err(RefactorError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_extract_component_group_into_new_file() {
+        let context = context();
+        let edit = context
+            .extract_component_group(
+                Path::new("files.wxs"),
+                &["AppExe", "AppConfig"],
+                "AppComponents",
+                Some(Path::new("app.wxs")),
+            )
+            .unwrap();
+
+        let files = apply(&context, &edit, "files.wxs");
+        assert!(!files.contains("<Component "));
+        assert!(files.contains("<DirectoryRef Id=\"INSTALLFOLDER\">\n        </DirectoryRef>"));
+
+        // The feature referenced every extracted component
+        let product = apply(&context, &edit, "product.wxs");
+        assert!(product.contains(
+            "<Feature Id=\"Main\">\n            <ComponentGroupRef Id=\"AppComponents\" />\n        </Feature>"
+        ));
+
+        let created = &edit.created_files[Path::new("app.wxs")];
+        assert!(created.contains(r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">"#));
+        assert!(created.contains(
+            "        <ComponentGroup Id=\"AppComponents\">\n            <Component Id=\"AppExe\" Directory=\"INSTALLFOLDER\" Condition=\"$AppExe = 3 OR INSTALLMODE\">\n                <File Source=\"app.exe\" />\n            </Component>\n"
+        ));
+        assert!(roxmltree::Document::parse(created).is_ok());
+    }
+
+    #[test]
+    fn test_extract_from_feature_in_same_file() {
+        let source = r#"<Wix>
+  <Package>
+    <Feature Id="Main">
+      <Component Id="A" Directory="INSTALLFOLDER"><File Source="a" /></Component>
+      <Component Id="B" Directory="INSTALLFOLDER"><File Source="b" /></Component>
+    </Feature>
+  </Package>
+</Wix>"#;
+        let context = RefactorContext::new().with_source("p.wxs", source);
+        let edit = context
+            .extract_component_group(Path::new("p.wxs"), &["B", "A"], "Group", None)
+            .unwrap();
+        let result = apply(&context, &edit, "p.wxs");
+        assert_eq!(
+            result,
+            r#"<Wix>
+  <Package>
+    <Feature Id="Main">
+      <ComponentGroupRef Id="Group" />
+    </Feature>
+  </Package>
+  <Fragment>
+    <ComponentGroup Id="Group">
+      <Component Id="A" Directory="INSTALLFOLDER"><File Source="a" /></Component>
+      <Component Id="B" Directory="INSTALLFOLDER"><File Source="b" /></Component>
+    </ComponentGroup>
+  </Fragment>
+</Wix>"#
+        );
+
+        assert!(context
+            .extract_component_group(Path::new("p.wxs"), &["A"], "Main.Group", None)
+            .is_ok());
+        assert!(matches!(
+            context.extract_component_group(Path::new("p.wxs"), &["C"], "G", None),
+            Err(RefactorError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_inline_single_use_component_group() {
+        let product = r#"<Wix>
+    <Package>
+        <Feature Id="Main">
+            <ComponentGroupRef Id="Group" />
+        </Feature>
+    </Package>
+</Wix>"#;
+        let group = r#"<Wix>
+    <Fragment>
+        <ComponentGroup Id="Group" Directory="INSTALLFOLDER">
+            <Component Id="A">
+                <File Source="a" />
+            </Component>
+            <ComponentRef Id="B" />
+        </ComponentGroup>
+    </Fragment>
+</Wix>"#;
+        let context = RefactorContext::new()
+            .with_source("product.wxs", product)
+            .with_source("group.wxs", group);
+        let edit = context.inline_component_group("Group").unwrap();
+
+        assert_eq!(
+            apply(&context, &edit, "product.wxs"),
+            r#"<Wix>
+    <Package>
+        <Feature Id="Main">
+            <Component Id="A" Directory="INSTALLFOLDER">
+                <File Source="a" />
+            </Component>
+            <ComponentRef Id="B" />
+        </Feature>
+    </Package>
+</Wix>"#
+        );
+        assert_eq!(apply(&context, &edit, "group.wxs"), "<Wix>\n</Wix>");
+
+        let twice = context.with_source(
+            "other.wxs",
+            r#"<Wix><Fragment><FeatureRef Id="Main"><ComponentGroupRef Id="Group" /></FeatureRef></Fragment></Wix>"#,
+        );
+        assert!(matches!(
+            twice.inline_component_group("Group"),
+            Err(RefactorError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_convert_standard_directories() {
+        let source = r#"<Wix>
+    <Package>
+        <Directory Id="TARGETDIR" Name="SourceDir">
+            <Directory Id="ProgramFilesFolder" Name="PFiles">
+                <Directory Id="INSTALLFOLDER" Name="App" />
+            </Directory>
+            <Directory Id="ProgramMenuFolder" />
+        </Directory>
+    </Package>
+    <Fragment>
+        <Directory Id="DesktopFolder">
+            <Directory Id="Shortcuts" Name="App" />
+        </Directory>
+    </Fragment>
+</Wix>"#;
+        let context = RefactorContext::new().with_source("dirs.wxs", source);
+        let edit = context.convert_standard_directories().unwrap();
+        assert_eq!(
+            apply(&context, &edit, "dirs.wxs"),
+            r#"<Wix>
+    <Package>
+        <StandardDirectory Id="ProgramFilesFolder">
+            <Directory Id="INSTALLFOLDER" Name="App" />
+        </StandardDirectory>
+        <StandardDirectory Id="ProgramMenuFolder" />
+    </Package>
+    <Fragment>
+        <StandardDirectory Id="DesktopFolder">
+            <Directory Id="Shortcuts" Name="App" />
+        </StandardDirectory>
+    </Fragment>
+</Wix>"#
+        );
+
+        // TARGETDIR trees with other content are left alone
+        let mixed = RefactorContext::new().with_source(
+            "mixed.wxs",
+            r#"<Wix><Package><Directory Id="TARGETDIR" Name="SourceDir"><Directory Id="ProgramFilesFolder" /><Directory Id="Custom" Name="C" /></Directory></Package></Wix>"#,
+        );
+        assert!(mixed.convert_standard_directories().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_embedded() {
+        let rewrite = |kind, raw, condition| rewrite_embedded(kind, raw, "X", "Y", condition);
+        assert_eq!(
+            rewrite(RenameKind::Property, "[X] [XX] [%X]", false).as_deref(),
+            Some("[Y] [XX] [%X]")
+        );
+        assert_eq!(
+            rewrite(RenameKind::Property, "X AND \"X\" AND Xa", true).as_deref(),
+            Some("Y AND \"X\" AND Xa")
+        );
+        assert_eq!(
+            rewrite(RenameKind::Property, "X &lt; 3", true).as_deref(),
+            Some("Y &lt; 3")
+        );
+        assert_eq!(
+            rewrite(RenameKind::Component, "[$X] $X ?X X", true).as_deref(),
+            Some("[$Y] $Y ?Y X")
+        );
+        assert_eq!(
+            rewrite(RenameKind::Feature, "&X=3 AND !X=2 AND &amp;X", true).as_deref(),
+            Some("&Y=3 AND !Y=2 AND &amp;Y")
+        );
+        assert_eq!(rewrite(RenameKind::Feature, "&amp;X", false), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! Multi-file change sets, modeled on the LSP `WorkspaceEdit`
+//!
+//! Refactorings produce a [`WorkspaceEdit`] that can be previewed, applied
+//! to disk by the CLI, or converted for a language server client.
+
+use super::engine::{FixError, FixResult};
+use crate::core::{Position, Range};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Replace a range of a file (positions are 1-based, like diagnostics)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range, new_text: impl Into<String>) -> Self {
+        Self {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Edit replacing the byte range `start..end` of `source`
+    pub fn from_offsets(
+        source: &str,
+        start: usize,
+        end: usize,
+        new_text: impl Into<String>,
+    ) -> Self {
+        Self::new(Range::from_offsets(source, start, end), new_text)
+    }
+}
+
+/// A set of edits across files, plus files to create
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceEdit {
+    /// What the change does, e.g. "Rename Component 'A' to 'B'"
+    pub label: String,
+    /// Edits to existing files
+    pub changes: BTreeMap<PathBuf, Vec<TextEdit>>,
+    /// New files and their content
+    pub created_files: BTreeMap<PathBuf, String>,
+}
+
+impl WorkspaceEdit {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Add an edit to a file
+    pub fn add(&mut self, file: impl Into<PathBuf>, edit: TextEdit) {
+        self.changes.entry(file.into()).or_default().push(edit);
+    }
+
+    /// Add a file to create
+    pub fn create_file(&mut self, file: impl Into<PathBuf>, content: impl Into<String>) {
+        self.created_files.insert(file.into(), content.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.values().all(|e| e.is_empty()) && self.created_files.is_empty()
+    }
+
+    /// Number of text edits, not counting created files
+    pub fn edit_count(&self) -> usize {
+        self.changes.values().map(|e| e.len()).sum()
+    }
+
+    /// Files touched, edited or created
+    pub fn files(&self) -> Vec<&Path> {
+        let mut files: Vec<&Path> = self
+            .changes
+            .keys()
+            .chain(self.created_files.keys())
+            .map(|p| p.as_path())
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    /// Apply the edits for `file` to its source
+    pub fn apply_to(&self, file: &Path, source: &str) -> Result<String, FixError> {
+        let error = |message: String| FixError {
+            message,
+            file: file.to_path_buf(),
+        };
+
+        let mut spans = Vec::new();
+        for edit in self.changes.get(file).into_iter().flatten() {
+            let start = position_to_offset(source, edit.range.start).ok_or_else(|| {
+                error(format!(
+                    "Edit start {:?} is outside the file",
+                    edit.range.start
+                ))
+            })?;
+            let end = position_to_offset(source, edit.range.end).ok_or_else(|| {
+                error(format!("Edit end {:?} is outside the file", edit.range.end))
+            })?;
+            if end < start {
+                return Err(error(format!("Edit range {:?} is reversed", edit.range)));
+            }
+            spans.push((start, end, edit.new_text.as_str()));
+        }
+
+        spans.sort_by_key(|(start, end, _)| (*start, *end));
+        if let Some(pair) = spans.windows(2).find(|w| w[1].0 < w[0].1) {
+            return Err(error(format!(
+                "Overlapping edits at bytes {}..{} and {}..{}",
+                pair[0].0, pair[0].1, pair[1].0, pair[1].1
+            )));
+        }
+
+        let mut content = source.to_string();
+        for (start, end, text) in spans.into_iter().rev() {
+            content.replace_range(start..end, text);
+        }
+        Ok(content)
+    }
+
+    /// Write the change set to disk
+    pub fn apply(&self) -> Result<Vec<FixResult>, FixError> {
+        // Compute everything before writing anything
+        let mut results = Vec::new();
+        for (file, edits) in &self.changes {
+            let source = std::fs::read_to_string(file).map_err(|e| FixError {
+                message: e.to_string(),
+                file: file.clone(),
+            })?;
+            results.push(FixResult {
+                file: file.clone(),
+                fixes_applied: edits.len(),
+                new_content: self.apply_to(file, &source)?,
+            });
+        }
+        for (file, content) in &self.created_files {
+            if file.exists() && !self.changes.contains_key(file) {
+                return Err(FixError {
+                    message: "File already exists".to_string(),
+                    file: file.clone(),
+                });
+            }
+            results.push(FixResult {
+                file: file.clone(),
+                fixes_applied: 1,
+                new_content: content.clone(),
+            });
+        }
+
+        for result in &results {
+            if let Some(parent) = result.file.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).map_err(|e| FixError {
+                    message: e.to_string(),
+                    file: result.file.clone(),
+                })?;
+            }
+            std::fs::write(&result.file, &result.new_content).map_err(|e| FixError {
+                message: e.to_string(),
+                file: result.file.clone(),
+            })?;
+        }
+        Ok(results)
+    }
+
+    /// Diff-style preview; `source` returns the current content of a file
+    pub fn preview(&self, source: impl Fn(&Path) -> Option<String>) -> String {
+        let mut out = format!("{}\n", self.label);
+
+        for (file, edits) in &self.changes {
+            let Some(content) = source(file) else {
+                out.push_str(&format!("\n{}: cannot read file\n", file.display()));
+                continue;
+            };
+            let lines: Vec<&str> = content.lines().collect();
+            out.push_str(&format!(
+                "\n--- {}\n+++ {}\n",
+                file.display(),
+                file.display()
+            ));
+
+            let mut sorted: Vec<&TextEdit> = edits.iter().collect();
+            sorted.sort_by_key(|e| (e.range.start.line, e.range.start.character));
+            for edit in sorted {
+                // Whole-line edits end at the start of the next line
+                let whole_lines =
+                    edit.range.end.character == 1 && edit.range.end.line > edit.range.start.line;
+                let end_line = if whole_lines {
+                    edit.range.end.line - 1
+                } else {
+                    edit.range.end.line
+                };
+                let first = edit.range.start.line.saturating_sub(1);
+                let last = end_line
+                    .saturating_sub(1)
+                    .min(lines.len().saturating_sub(1));
+                out.push_str(&format!("@@ line {} @@\n", edit.range.start.line));
+
+                let old: Vec<&str> = lines.get(first..=last).unwrap_or_default().to_vec();
+                for line in &old {
+                    out.push_str(&format!("-{}\n", line));
+                }
+                let prefix: String = old
+                    .first()
+                    .map(|l| l.chars().take(edit.range.start.character - 1).collect())
+                    .unwrap_or_default();
+                let suffix: String = match old.last() {
+                    Some(l) if !whole_lines => {
+                        l.chars().skip(edit.range.end.character - 1).collect()
+                    }
+                    _ => String::new(),
+                };
+                let new = format!("{}{}{}", prefix, edit.new_text, suffix);
+                for line in new.lines() {
+                    out.push_str(&format!("+{}\n", line));
+                }
+            }
+        }
+
+        for (file, content) in &self.created_files {
+            out.push_str(&format!("\n--- /dev/null\n+++ {}\n", file.display()));
+            for line in content.lines() {
+                out.push_str(&format!("+{}\n", line));
+            }
+        }
+
+        out
+    }
+}
+
+/// Byte offset of a 1-based position; the column may point just past the line end
+pub fn position_to_offset(source: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 1..position.line {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+    let line_end = source[line_start..]
+        .find('\n')
+        .map_or(source.len(), |i| line_start + i);
+    let line = &source[line_start..line_end];
+
+    let column = position.character.checked_sub(1)?;
+    match line.char_indices().nth(column) {
+        Some((i, _)) => Some(line_start + i),
+        None if column == line.chars().count() => Some(line_end),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_to_offset() {
+        let source = "ab\ncdé\nf";
+        assert_eq!(position_to_offset(source, Position::new(1, 1)), Some(0));
+        assert_eq!(position_to_offset(source, Position::new(2, 3)), Some(5));
+        assert_eq!(position_to_offset(source, Position::new(2, 4)), Some(7));
+        assert_eq!(position_to_offset(source, Position::new(3, 2)), Some(9));
+        assert_eq!(position_to_offset(source, Position::new(2, 9)), None);
+        assert_eq!(position_to_offset(source, Position::new(4, 1)), None);
+
+        // Round-trips with Range::from_offsets
+        for offset in [0, 3, 5, 7, 8, 9] {
+            let range = Range::from_offsets(source, offset, offset);
+            assert_eq!(position_to_offset(source, range.start), Some(offset));
+        }
+    }
+
+    #[test]
+    fn test_apply_edits_in_any_order() {
+        let source = "<A Id=\"x\">\n  <B Ref=\"x\" />\n</A>";
+        let file = Path::new("a.wxs");
+        let mut edit = WorkspaceEdit::new("rename");
+        edit.add(file, TextEdit::from_offsets(source, 21, 22, "renamed"));
+        edit.add(file, TextEdit::from_offsets(source, 7, 8, "renamed"));
+
+        let result = edit.apply_to(file, source).unwrap();
+        assert_eq!(result, "<A Id=\"renamed\">\n  <B Ref=\"renamed\" />\n</A>");
+        assert_eq!(
+            edit.apply_to(Path::new("other.wxs"), source).unwrap(),
+            source
+        );
+        assert_eq!(edit.edit_count(), 2);
+    }
+
+    #[test]
+    fn test_overlapping_edits_rejected() {
+        let source = "abcdef";
+        let file = Path::new("a.wxs");
+        let mut edit = WorkspaceEdit::new("overlap");
+        edit.add(file, TextEdit::from_offsets(source, 0, 3, "x"));
+        edit.add(file, TextEdit::from_offsets(source, 2, 4, "y"));
+
+        let err = edit.apply_to(file, source).unwrap_err();
+        assert!(err.message.contains("Overlapping"));
+    }
+
+    #[test]
+    fn test_preview() {
+        let source = "<A Id=\"x\" />\n<B />";
+        let file = Path::new("a.wxs");
+        let mut edit = WorkspaceEdit::new("Rename 'x' to 'y'");
+        edit.add(file, TextEdit::from_offsets(source, 7, 8, "y"));
+        edit.create_file("new.wxs", "<Wix />\n");
+
+        let preview = edit.preview(|_| Some(source.to_string()));
+        assert!(preview.starts_with("Rename 'x' to 'y'\n"));
+        assert!(preview.contains("-<A Id=\"x\" />\n+<A Id=\"y\" />\n"));
+        assert!(preview.contains("+++ new.wxs\n+<Wix />"));
+
+        // Removing a whole line shows just that line
+        let mut removal = WorkspaceEdit::new("remove");
+        removal.add(file, TextEdit::from_offsets(source, 0, 13, ""));
+        let preview = removal.preview(|_| Some(source.to_string()));
+        assert!(preview.ends_with("@@ line 1 @@\n-<A Id=\"x\" />\n"));
+        assert_eq!(edit.files(), vec![Path::new("a.wxs"), Path::new("new.wxs")]);
+    }
+
+    #[test]
+    fn test_apply_writes_files() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let existing = temp.path().join("a.wxs");
+        std::fs::write(&existing, "<A Id=\"x\" />").unwrap();
+
+        let mut edit = WorkspaceEdit::new("test");
+        edit.add(
+            &existing,
+            TextEdit::from_offsets("<A Id=\"x\" />", 7, 8, "y"),
+        );
+        edit.create_file(temp.path().join("sub/new.wxs"), "<Wix />");
+
+        let results = edit.apply().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&existing).unwrap(),
+            "<A Id=\"y\" />"
+        );
+        assert!(temp.path().join("sub/new.wxs").exists());
+
+        // Refuses to overwrite a file it would create
+        let mut again = WorkspaceEdit::new("again");
+        again.create_file(&existing, "x");
+        assert!(again.apply().is_err());
+    }
+}
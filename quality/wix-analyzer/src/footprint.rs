@@ -0,0 +1,349 @@
+//! Install footprint - disk space a package needs, per feature
+//!
+//! File sizes come from the payload sources, resolved through bind paths and
+//! preprocessor variables. Each file is rounded up to whole clusters the way
+//! Windows Installer costs it. The cabinet size is an estimate from typical
+//! compression ratios per file type, not an actual compression run.
+//! Merge module contents are not included.
+
+use crate::payload::Payload;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// NTFS default cluster size
+pub const DEFAULT_CLUSTER_SIZE: u64 = 4096;
+
+/// Size of one resolved payload file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileFootprint {
+    pub id: String,
+    pub component: Option<String>,
+    pub install_path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Size on disk, rounded up to whole clusters
+    pub disk: u64,
+}
+
+/// Size of a feature
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureFootprint {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    pub components: usize,
+    pub files: usize,
+    /// Bytes installed by the feature itself
+    pub size: u64,
+    /// Disk space of the feature itself
+    pub disk: u64,
+    /// Disk space including sub-features
+    pub total_disk: u64,
+}
+
+/// Install footprint of a package
+#[derive(Debug, Clone, Serialize)]
+pub struct Footprint {
+    pub cluster_size: u64,
+    /// Features in authoring order
+    pub features: Vec<FeatureFootprint>,
+    /// Files whose component no feature installs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unassigned: Option<FeatureFootprint>,
+    /// Resolved files, largest first
+    pub files: Vec<FileFootprint>,
+    /// Bytes installed, each file counted once
+    pub total_size: u64,
+    /// Disk space of a full install
+    pub total_disk: u64,
+    /// Estimated size of the compressed cabinets
+    pub compressed_size: u64,
+    /// Files whose source could not be found and are not counted
+    pub unresolved: usize,
+}
+
+impl Footprint {
+    /// The `n` largest files
+    pub fn largest(&self, n: usize) -> &[FileFootprint] {
+        &self.files[..n.min(self.files.len())]
+    }
+}
+
+/// Computes the install footprint of a payload
+pub struct FootprintAnalysis<'a> {
+    payload: &'a Payload,
+    cluster_size: u64,
+}
+
+impl<'a> FootprintAnalysis<'a> {
+    pub fn new(payload: &'a Payload) -> Self {
+        Self {
+            payload,
+            cluster_size: DEFAULT_CLUSTER_SIZE,
+        }
+    }
+
+    /// Cluster size of the target volume
+    pub fn with_cluster_size(mut self, cluster_size: u64) -> Self {
+        self.cluster_size = cluster_size.max(1);
+        self
+    }
+
+    pub fn run(&self) -> Footprint {
+        let mut files: Vec<FileFootprint> = Vec::new();
+        let mut compressed = 0.0;
+        let mut unresolved = 0;
+        for file in &self.payload.files {
+            let Some(size) = file
+                .path
+                .as_ref()
+                .and_then(|p| std::fs::metadata(p).ok())
+                .map(|m| m.len())
+            else {
+                unresolved += 1;
+                continue;
+            };
+            compressed += size as f64 * compression_ratio(&file.name);
+            files.push(FileFootprint {
+                id: file.id.clone(),
+                component: file.component.clone(),
+                install_path: file.install_path(),
+                size,
+                disk: self.round_up(size),
+            });
+        }
+
+        let by_component = {
+            let mut map: HashMap<&str, Vec<&FileFootprint>> = HashMap::new();
+            for file in &files {
+                if let Some(component) = &file.component {
+                    map.entry(component.as_str()).or_default().push(file);
+                }
+            }
+            map
+        };
+
+        let mut features: Vec<FeatureFootprint> = self
+            .payload
+            .features
+            .iter()
+            .map(|feature| {
+                let feature_files: Vec<&FileFootprint> = feature
+                    .components
+                    .iter()
+                    .flat_map(|c| by_component.get(c.as_str()).into_iter().flatten())
+                    .copied()
+                    .collect();
+                let disk = feature_files.iter().map(|f| f.disk).sum();
+                FeatureFootprint {
+                    id: feature.id.clone(),
+                    title: feature.title.clone(),
+                    parent: feature.parent.clone(),
+                    components: feature.components.len(),
+                    files: feature_files.len(),
+                    size: feature_files.iter().map(|f| f.size).sum(),
+                    disk,
+                    total_disk: disk,
+                }
+            })
+            .collect();
+        for i in 0..features.len() {
+            features[i].total_disk = subtree_disk(&features, &features[i].id, 0);
+        }
+
+        let assigned: HashSet<&str> = self
+            .payload
+            .features
+            .iter()
+            .flat_map(|f| f.components.iter().map(|c| c.as_str()))
+            .collect();
+        let orphans: Vec<&FileFootprint> = files
+            .iter()
+            .filter(|f| f.component.as_deref().is_none_or(|c| !assigned.contains(c)))
+            .collect();
+        let unassigned = (!orphans.is_empty()).then(|| {
+            let components: HashSet<Option<&str>> =
+                orphans.iter().map(|f| f.component.as_deref()).collect();
+            let disk = orphans.iter().map(|f| f.disk).sum();
+            FeatureFootprint {
+                id: "(no feature)".to_string(),
+                title: None,
+                parent: None,
+                components: components.len(),
+                files: orphans.len(),
+                size: orphans.iter().map(|f| f.size).sum(),
+                disk,
+                total_disk: disk,
+            }
+        });
+
+        files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.id.cmp(&b.id)));
+        Footprint {
+            cluster_size: self.cluster_size,
+            features,
+            unassigned,
+            total_size: files.iter().map(|f| f.size).sum(),
+            total_disk: files.iter().map(|f| f.disk).sum(),
+            compressed_size: compressed.round() as u64,
+            files,
+            unresolved,
+        }
+    }
+
+    fn round_up(&self, size: u64) -> u64 {
+        size.div_ceil(self.cluster_size) * self.cluster_size
+    }
+}
+
+/// Disk space of a feature and its sub-features
+fn subtree_disk(features: &[FeatureFootprint], id: &str, depth: usize) -> u64 {
+    if depth > 32 {
+        return 0;
+    }
+    features
+        .iter()
+        .filter(|f| f.id == id)
+        .map(|f| f.disk)
+        .sum::<u64>()
+        + features
+            .iter()
+            .filter(|f| f.parent.as_deref() == Some(id))
+            .map(|f| subtree_disk(features, &f.id, depth + 1))
+            .sum::<u64>()
+}
+
+/// Typical cabinet (MSZIP/LZX) size relative to the original, by extension
+fn compression_ratio(name: &str) -> f64 {
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, e)| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        // Already compressed
+        "zip" | "cab" | "msi" | "msm" | "msp" | "msu" | "7z" | "gz" | "bz2" | "xz" | "nupkg"
+        | "jar" | "docx" | "xlsx" | "pptx" | "chm" | "jpg" | "jpeg" | "png" | "gif" | "mp3"
+        | "mp4" | "wav" | "woff" | "woff2" => 1.0,
+        "pdf" => 0.9,
+        "exe" | "dll" | "sys" | "ocx" | "cpl" | "winmd" => 0.45,
+        "txt" | "md" | "xml" | "json" | "config" | "ini" | "htm" | "html" | "css" | "js"
+        | "ps1" | "psm1" | "resx" | "xaml" | "csv" | "rtf" | "log" => 0.25,
+        _ => 0.6,
+    }
+}
+
+/// Human-readable byte count, e.g. `1.5 MB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::PayloadResolver;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, size: usize) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, vec![b'x'; size]).unwrap();
+        path
+    }
+
+    fn load(temp: &TempDir) -> Payload {
+        write(temp.path(), "app.exe", 10_000);
+        write(temp.path(), "lib.dll", 5_000);
+        write(temp.path(), "manual.txt", 100);
+        write(temp.path(), "tool.exe", 1);
+        let wxs = temp.path().join("Product.wxs");
+        std::fs::write(
+            &wxs,
+            r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+  <Package Name="P" Version="1.0" Manufacturer="M">
+    <Feature Id="Main">
+      <ComponentRef Id="App" />
+      <ComponentRef Id="Lib" />
+      <Feature Id="Docs">
+        <ComponentRef Id="Manual" />
+        <ComponentRef Id="Lib" />
+      </Feature>
+    </Feature>
+    <StandardDirectory Id="ProgramFiles6432Folder">
+      <Directory Id="INSTALLFOLDER" Name="P">
+        <Component Id="App"><File Source="app.exe" /></Component>
+        <Component Id="Lib"><File Source="lib.dll" /></Component>
+        <Component Id="Manual"><File Source="manual.txt" /></Component>
+        <Component Id="Tool"><File Source="tool.exe" /></Component>
+        <Component Id="Gone"><File Source="missing.dll" /></Component>
+      </Directory>
+    </StandardDirectory>
+  </Package>
+</Wix>"#,
+        )
+        .unwrap();
+        Payload::load(&[&wxs], &PayloadResolver::new()).unwrap()
+    }
+
+    #[test]
+    fn test_footprint_per_feature() {
+        let temp = TempDir::new().unwrap();
+        let payload = load(&temp);
+        let footprint = FootprintAnalysis::new(&payload).run();
+
+        assert_eq!(footprint.unresolved, 1);
+        assert_eq!(footprint.total_size, 15_101);
+        // 3 + 2 + 1 + 1 clusters
+        assert_eq!(footprint.total_disk, 7 * 4096);
+
+        let main = &footprint.features[0];
+        assert_eq!((main.files, main.size, main.disk), (2, 15_000, 5 * 4096));
+        let docs = &footprint.features[1];
+        assert_eq!((docs.files, docs.disk), (2, 3 * 4096));
+        // Lib is in both features but counted once per feature
+        assert_eq!(main.total_disk, 8 * 4096);
+
+        let unassigned = footprint.unassigned.as_ref().unwrap();
+        assert_eq!((unassigned.files, unassigned.disk), (1, 4096));
+
+        assert_eq!(footprint.largest(2)[0].id, "app.exe");
+        assert_eq!(footprint.largest(2)[1].id, "lib.dll");
+        assert_eq!(footprint.largest(10).len(), 4);
+        assert_eq!(
+            footprint.largest(1)[0].install_path,
+            "ProgramFiles6432Folder\\P\\app.exe"
+        );
+    }
+
+    #[test]
+    fn test_cluster_size_and_compression() {
+        let temp = TempDir::new().unwrap();
+        let payload = load(&temp);
+        let footprint = FootprintAnalysis::new(&payload)
+            .with_cluster_size(512)
+            .run();
+        // 20 + 10 + 1 + 1 clusters
+        assert_eq!(footprint.total_disk, 32 * 512);
+        assert_eq!(
+            footprint.compressed_size,
+            (15_001.0 * 0.45 + 100.0 * 0.25_f64).round() as u64
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
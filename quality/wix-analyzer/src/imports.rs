@@ -0,0 +1,740 @@
+//! PE import analysis - runtime DLLs the payload needs but doesn't carry
+//!
+//! Every payload binary's import and delay-import tables are resolved the
+//! way the loader would find them once installed: next to the binary, in
+//! the system directory of the oldest Windows the package allows, or from a
+//! redistributable the package has to bundle, chain or check for.
+
+use crate::core::{AnalysisResult, Category, Diagnostic, RelatedInfo, Severity};
+use crate::deps::{DependencyAnalyzer, DependencyType};
+use crate::payload::{Payload, PayloadFile};
+use crate::pe::{Import, Machine, PeFile};
+use std::collections::BTreeMap;
+
+/// Oldest Windows a package supports, as `VersionNT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WindowsVersion {
+    Win7,
+    Win8,
+    Win81,
+    Win10,
+}
+
+impl WindowsVersion {
+    /// From a `VersionNT` value: 601, 602, 603 or 1000
+    pub fn from_version_nt(version: u32) -> Self {
+        match version {
+            0..=601 => Self::Win7,
+            602 => Self::Win8,
+            603..=999 => Self::Win81,
+            _ => Self::Win10,
+        }
+    }
+
+    /// From a name such as `7`, `8.1` or `10`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().trim_start_matches("win") {
+            "7" => Some(Self::Win7),
+            "8" => Some(Self::Win8),
+            "8.1" | "81" => Some(Self::Win81),
+            "10" | "11" => Some(Self::Win10),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Win7 => "Windows 7",
+            Self::Win8 => "Windows 8",
+            Self::Win81 => "Windows 8.1",
+            Self::Win10 => "Windows 10",
+        }
+    }
+}
+
+/// System DLLs present since Windows 7
+const SYSTEM_DLLS_WIN7: &[&str] = &[
+    "activeds.dll",
+    "advapi32.dll",
+    "authz.dll",
+    "avrt.dll",
+    "bcrypt.dll",
+    "cabinet.dll",
+    "cfgmgr32.dll",
+    "clusapi.dll",
+    "comctl32.dll",
+    "comdlg32.dll",
+    "credui.dll",
+    "crypt32.dll",
+    "cryptnet.dll",
+    "cryptui.dll",
+    "d2d1.dll",
+    "d3d10.dll",
+    "d3d10_1.dll",
+    "d3d11.dll",
+    "d3d9.dll",
+    "d3dcompiler_47.dll",
+    "dbghelp.dll",
+    "dhcpcsvc.dll",
+    "dnsapi.dll",
+    "dwmapi.dll",
+    "dwrite.dll",
+    "dxgi.dll",
+    "dxva2.dll",
+    "evr.dll",
+    "fltlib.dll",
+    "gdi32.dll",
+    "gdiplus.dll",
+    "glu32.dll",
+    "hid.dll",
+    "httpapi.dll",
+    "imagehlp.dll",
+    "imm32.dll",
+    "iphlpapi.dll",
+    "kernel32.dll",
+    "kernelbase.dll",
+    "ktmw32.dll",
+    "mf.dll",
+    "mfplat.dll",
+    "mfreadwrite.dll",
+    "mgmtapi.dll",
+    "mlang.dll",
+    "mpr.dll",
+    "msacm32.dll",
+    "mscoree.dll",
+    "msi.dll",
+    "msimg32.dll",
+    "msvcrt.dll",
+    "mswsock.dll",
+    "ncrypt.dll",
+    "netapi32.dll",
+    "newdev.dll",
+    "normaliz.dll",
+    "ntdll.dll",
+    "ntdsapi.dll",
+    "odbc32.dll",
+    "ole32.dll",
+    "oleacc.dll",
+    "oleaut32.dll",
+    "opengl32.dll",
+    "pdh.dll",
+    "powrprof.dll",
+    "propsys.dll",
+    "psapi.dll",
+    "rasapi32.dll",
+    "rpcrt4.dll",
+    "rstrtmgr.dll",
+    "secur32.dll",
+    "setupapi.dll",
+    "shell32.dll",
+    "shlwapi.dll",
+    "sspicli.dll",
+    "taskschd.dll",
+    "tdh.dll",
+    "urlmon.dll",
+    "user32.dll",
+    "userenv.dll",
+    "usp10.dll",
+    "uxtheme.dll",
+    "version.dll",
+    "virtdisk.dll",
+    "wevtapi.dll",
+    "windowscodecs.dll",
+    "winhttp.dll",
+    "wininet.dll",
+    "winmm.dll",
+    "winscard.dll",
+    "winspool.drv",
+    "wintrust.dll",
+    "winusb.dll",
+    "wldap32.dll",
+    "wlanapi.dll",
+    "ws2_32.dll",
+    "wtsapi32.dll",
+    "xmllite.dll",
+];
+
+/// Added in Windows 8
+const SYSTEM_DLLS_WIN8: &[&str] = &["bcryptprimitives.dll", "dcomp.dll", "xinput1_4.dll"];
+
+/// Added in Windows 8.1
+const SYSTEM_DLLS_WIN81: &[&str] = &["shcore.dll"];
+
+/// Added in Windows 10; the Universal CRT became part of the OS
+const SYSTEM_DLLS_WIN10: &[&str] = &["d3d12.dll", "ucrtbase.dll", "windows.storage.dll"];
+
+/// Whether a DLL ships with Windows from `os` on
+pub fn is_system_dll(dll: &str, os: WindowsVersion) -> bool {
+    let dll = dll.to_ascii_lowercase();
+    // API sets resolve to whatever implements them on the running OS
+    if dll.starts_with("api-ms-win-crt-") {
+        return os >= WindowsVersion::Win10;
+    }
+    if dll.starts_with("api-ms-win-") || dll.starts_with("ext-ms-") {
+        return true;
+    }
+    let lists = [
+        (WindowsVersion::Win7, SYSTEM_DLLS_WIN7),
+        (WindowsVersion::Win8, SYSTEM_DLLS_WIN8),
+        (WindowsVersion::Win81, SYSTEM_DLLS_WIN81),
+        (WindowsVersion::Win10, SYSTEM_DLLS_WIN10),
+    ];
+    lists
+        .iter()
+        .filter(|(since, _)| *since <= os)
+        .any(|(_, list)| list.contains(&dll.as_str()))
+}
+
+/// A runtime the package has to install or check for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Redistributable {
+    /// Visual C++ 2015-2022 (v14) runtime
+    VcRuntime14,
+    /// Older Visual C++ runtimes, by version (e.g. 12 for 2013)
+    VcRuntimeLegacy(u32),
+    /// Universal C Runtime, part of Windows 10
+    Ucrt,
+    /// .NET (Core) runtime, loaded through its hosting layer
+    DotNet,
+}
+
+impl Redistributable {
+    /// Runtime that provides a DLL, if any
+    pub fn providing(dll: &str) -> Option<Self> {
+        let dll = dll.to_ascii_lowercase();
+        let stem = dll.strip_suffix(".dll").unwrap_or(&dll);
+        if stem.starts_with("api-ms-win-crt-") || stem == "ucrtbase" {
+            return Some(Self::Ucrt);
+        }
+        if ["hostfxr", "hostpolicy", "coreclr", "clrjit"].contains(&stem) {
+            return Some(Self::DotNet);
+        }
+        for prefix in [
+            "vcruntime",
+            "msvcp",
+            "msvcr",
+            "concrt",
+            "vccorlib",
+            "vcomp",
+            "mfc",
+        ] {
+            let Some(rest) = stem.strip_prefix(prefix) else {
+                continue;
+            };
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            let version = match digits.len() {
+                2 | 3 => digits[..digits.len() - 1].parse::<u32>().ok()?,
+                _ => continue,
+            };
+            return Some(if version >= 14 {
+                Self::VcRuntime14
+            } else {
+                Self::VcRuntimeLegacy(version)
+            });
+        }
+        None
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::VcRuntime14 => "Visual C++ 2015-2022 Redistributable".to_string(),
+            Self::VcRuntimeLegacy(version) => {
+                let year = match version {
+                    12 => "2013",
+                    11 => "2012",
+                    10 => "2010",
+                    9 => "2008",
+                    8 => "2005",
+                    _ => "legacy",
+                };
+                format!("Visual C++ {} Redistributable", year)
+            }
+            Self::Ucrt => "Universal C Runtime".to_string(),
+            Self::DotNet => ".NET Runtime".to_string(),
+        }
+    }
+
+    /// Dependency type that satisfies it when bundled, chained or checked for
+    pub fn dependency_type(&self) -> DependencyType {
+        match self {
+            Self::DotNet => DependencyType::DotNetCore,
+            _ => DependencyType::VCRuntime,
+        }
+    }
+}
+
+/// A payload binary and what it imports
+#[derive(Debug, Clone)]
+pub struct PayloadBinary {
+    pub file: PayloadFile,
+    pub machine: Machine,
+    /// Managed AnyCPU assemblies run with either bitness
+    pub any_cpu: bool,
+    pub imports: Vec<Import>,
+}
+
+/// Resolves payload imports and reports what the package is missing
+pub struct ImportAnalysis<'a> {
+    payload: &'a Payload,
+    binaries: Vec<PayloadBinary>,
+    min_os: WindowsVersion,
+}
+
+impl<'a> ImportAnalysis<'a> {
+    /// Read every resolved payload file that is a PE image; the minimum OS
+    /// comes from the launch conditions, Windows 7 when there are none
+    pub fn new(payload: &'a Payload) -> Self {
+        let binaries = payload
+            .files
+            .iter()
+            .filter_map(|file| {
+                let data = std::fs::read(file.path.as_ref()?).ok()?;
+                let pe = PeFile::parse(&data).ok()?;
+                Some(PayloadBinary {
+                    file: file.clone(),
+                    machine: pe.machine(),
+                    any_cpu: pe.is_any_cpu(),
+                    imports: pe.imports(),
+                })
+            })
+            .collect();
+
+        Self {
+            payload,
+            binaries,
+            min_os: WindowsVersion::from_version_nt(payload.package.min_version_nt.unwrap_or(601)),
+        }
+    }
+
+    /// Override the minimum OS from the launch conditions
+    pub fn with_min_os(mut self, os: WindowsVersion) -> Self {
+        self.min_os = os;
+        self
+    }
+
+    pub fn min_os(&self) -> WindowsVersion {
+        self.min_os
+    }
+
+    pub fn binaries(&self) -> &[PayloadBinary] {
+        &self.binaries
+    }
+
+    /// Payload binaries installed with a name, by install directory
+    fn installed(&self, dll: &str) -> Vec<&PayloadBinary> {
+        self.binaries
+            .iter()
+            .filter(|b| b.file.name.eq_ignore_ascii_case(dll))
+            .collect()
+    }
+
+    /// Whether any payload file has a name, binary or not
+    fn in_payload(&self, name: &str) -> bool {
+        self.payload
+            .files
+            .iter()
+            .any(|f| f.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the package bundles, chains or checks for a runtime
+    fn provides(&self, redistributable: Redistributable) -> bool {
+        let dep_type = redistributable.dependency_type();
+        self.payload
+            .dependencies
+            .iter()
+            .any(|d| d.dep_type == dep_type)
+            || self
+                .payload
+                .merge_modules
+                .iter()
+                .any(|m| DependencyAnalyzer::redistributable_type(&m.source) == dep_type)
+    }
+
+    pub fn run(&self) -> AnalysisResult {
+        let mut result = AnalysisResult::new();
+        // Runtime -> binaries that need it, with the DLL that pulls it in
+        let mut needed: BTreeMap<Redistributable, Vec<(&PayloadBinary, &str)>> = BTreeMap::new();
+
+        for binary in &self.binaries {
+            for import in &binary.imports {
+                let local = self.installed(&import.dll);
+                let beside = local.iter().find(|b| {
+                    b.file
+                        .directory
+                        .eq_ignore_ascii_case(&binary.file.directory)
+                });
+
+                if let Some(imported) = beside {
+                    self.check_architecture(binary, imported, &mut result);
+                    continue;
+                }
+                if is_system_dll(&import.dll, self.min_os) {
+                    continue;
+                }
+                if let Some(redistributable) = Redistributable::providing(&import.dll) {
+                    needed
+                        .entry(redistributable)
+                        .or_default()
+                        .push((binary, &import.dll));
+                    continue;
+                }
+
+                if let Some(elsewhere) = local.first() {
+                    result.add(
+                        Diagnostic::new(
+                            "DEP-004",
+                            Category::Validation,
+                            Severity::Medium,
+                            format!(
+                                "'{}' imports '{}', which is installed to '{}' rather than next to it in '{}'",
+                                binary.file.name,
+                                import.dll,
+                                elsewhere.file.directory,
+                                binary.file.directory
+                            ),
+                            binary.file.location.clone(),
+                        )
+                        .with_help("The loader searches the application directory, not other install folders; install the DLL next to the binary that imports it"),
+                    );
+                    continue;
+                }
+
+                let (severity, when) = if import.delay_loaded {
+                    (Severity::Medium, "on first use")
+                } else {
+                    (Severity::High, "at startup")
+                };
+                result.add(
+                    Diagnostic::new(
+                        "DEP-001",
+                        Category::Validation,
+                        severity,
+                        format!(
+                            "'{}' imports '{}', which is not in the payload, not part of {} and not a known redistributable, so loading fails {}",
+                            binary.file.name,
+                            import.dll,
+                            self.min_os.as_str(),
+                            when
+                        ),
+                        binary.file.location.clone(),
+                    )
+                    .with_help("Add the DLL to the package, or raise the minimum OS in the launch condition if it ships with a later Windows"),
+                );
+            }
+        }
+
+        for (redistributable, users) in &needed {
+            self.check_redistributable(*redistributable, users, &mut result);
+        }
+
+        for diag in &result.diagnostics {
+            let file = diag.location.file.clone();
+            result.files.push(file);
+        }
+        result.files.sort();
+        result.files.dedup();
+        result
+    }
+
+    fn check_architecture(
+        &self,
+        importer: &PayloadBinary,
+        imported: &PayloadBinary,
+        result: &mut AnalysisResult,
+    ) {
+        if importer.any_cpu || imported.any_cpu || importer.machine == imported.machine {
+            return;
+        }
+        result.add(
+            Diagnostic::new(
+                "DEP-003",
+                Category::Validation,
+                Severity::High,
+                format!(
+                    "'{}' ({}) imports '{}', which is built for {}",
+                    importer.file.name,
+                    importer.machine.as_str(),
+                    imported.file.name,
+                    imported.machine.as_str()
+                ),
+                imported.file.location.clone(),
+            )
+            .with_help("A process can only load DLLs of its own architecture; install the matching build of the DLL")
+            .with_related(RelatedInfo::new(
+                importer.file.location.clone(),
+                format!("'{}' is the importing binary", importer.file.name),
+            )),
+        );
+    }
+
+    fn check_redistributable(
+        &self,
+        redistributable: Redistributable,
+        users: &[(&PayloadBinary, &str)],
+        result: &mut AnalysisResult,
+    ) {
+        // App-local deployment: the runtime DLLs ship in the payload
+        if users.iter().all(|(_, dll)| self.in_payload(dll)) {
+            return;
+        }
+        // The VC++ 2015+ redistributable installs the UCRT on older Windows
+        let provided = self.provides(redistributable)
+            || (redistributable == Redistributable::Ucrt
+                && self.provides(Redistributable::VcRuntime14));
+        if provided {
+            return;
+        }
+
+        let mut binaries: Vec<&str> = users.iter().map(|(b, _)| b.file.name.as_str()).collect();
+        binaries.dedup();
+        let mut dlls: Vec<&str> = users.iter().map(|(_, dll)| *dll).collect();
+        dlls.sort_by_key(|d| d.to_ascii_lowercase());
+        dlls.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+        let help = match redistributable {
+            Redistributable::DotNet => "Check for the runtime with netfx:DotNetCompatibilityCheck, chain its installer in a bundle, or publish self-contained",
+            Redistributable::Ucrt => "Chain the Visual C++ 2015-2022 redistributable (it installs the UCRT on older Windows), or require Windows 10 in the launch condition",
+            _ => "Chain the redistributable in a bundle, add its merge module, or deploy the runtime DLLs next to the binaries",
+        };
+        let location = self
+            .payload
+            .package_location
+            .clone()
+            .unwrap_or_else(|| users[0].0.file.location.clone());
+        let mut diag = Diagnostic::new(
+            "DEP-002",
+            Category::Validation,
+            Severity::High,
+            format!(
+                "{} need the {} ({}), but the package doesn't bundle, chain or check for it",
+                list(&binaries),
+                redistributable.name(),
+                dlls.join(", ")
+            ),
+            location,
+        )
+        .with_help(help);
+        for (binary, dll) in users.iter().take(5) {
+            diag = diag.with_related(RelatedInfo::new(
+                binary.file.location.clone(),
+                format!("'{}' imports '{}'", binary.file.name, dll),
+            ));
+        }
+        result.add(diag);
+    }
+}
+
+/// `'a'`, `'a' and 'b'`, `'a', 'b' and 2 more`
+fn list(names: &[&str]) -> String {
+    let quoted: Vec<String> = names.iter().map(|n| format!("'{}'", n)).collect();
+    match quoted.len() {
+        0 => String::new(),
+        1 => quoted[0].clone(),
+        2 | 3 => format!(
+            "{} and {}",
+            quoted[..quoted.len() - 1].join(", "),
+            quoted[quoted.len() - 1]
+        ),
+        n => format!("{} and {} more", quoted[..2].join(", "), n - 2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::PayloadResolver;
+    use crate::pe::tests::{image, import_section};
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn binary(dir: &Path, name: &str, machine: u16, dlls: &[&str], delayed: &[&str]) {
+        let (section, directories) = import_section(dlls, delayed);
+        let data = image(machine, machine != 0x014c, &directories, &section);
+        std::fs::write(dir.join(name), data).unwrap();
+    }
+
+    fn analyze(dir: &Path, body: &str, os: Option<WindowsVersion>) -> AnalysisResult {
+        let wxs = dir.join("Product.wxs");
+        std::fs::write(
+            &wxs,
+            format!(
+                r#"<Wix><Package Name="App" UpgradeCode="{{00000000-0000-4000-8000-000000000001}}">
+<StandardDirectory Id="ProgramFiles64Folder"><Directory Id="INSTALLFOLDER" Name="App" /></StandardDirectory>
+{}
+</Package></Wix>"#,
+                body
+            ),
+        )
+        .unwrap();
+        let payload = Payload::load(&[&wxs], &PayloadResolver::new()).unwrap();
+        let analysis = ImportAnalysis::new(&payload);
+        match os {
+            Some(os) => analysis.with_min_os(os).run(),
+            None => analysis.run(),
+        }
+    }
+
+    fn rules(result: &AnalysisResult) -> Vec<(&str, Severity)> {
+        result
+            .diagnostics
+            .iter()
+            .map(|d| (d.rule_id.as_str(), d.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_system_dlls_by_os() {
+        assert!(is_system_dll("KERNEL32.dll", WindowsVersion::Win7));
+        assert!(!is_system_dll("shcore.dll", WindowsVersion::Win8));
+        assert!(is_system_dll("SHCORE.DLL", WindowsVersion::Win81));
+        assert!(is_system_dll(
+            "api-ms-win-core-synch-l1-2-0.dll",
+            WindowsVersion::Win7
+        ));
+        assert!(!is_system_dll(
+            "api-ms-win-crt-runtime-l1-1-0.dll",
+            WindowsVersion::Win81
+        ));
+        assert!(is_system_dll(
+            "api-ms-win-crt-runtime-l1-1-0.dll",
+            WindowsVersion::Win10
+        ));
+        assert_eq!(WindowsVersion::from_version_nt(603), WindowsVersion::Win81);
+        assert_eq!(WindowsVersion::parse("8.1"), Some(WindowsVersion::Win81));
+    }
+
+    #[test]
+    fn test_redistributables() {
+        assert_eq!(
+            Redistributable::providing("VCRUNTIME140_1.dll"),
+            Some(Redistributable::VcRuntime14)
+        );
+        assert_eq!(
+            Redistributable::providing("msvcp140.dll"),
+            Some(Redistributable::VcRuntime14)
+        );
+        assert_eq!(
+            Redistributable::providing("MSVCR120.dll"),
+            Some(Redistributable::VcRuntimeLegacy(12))
+        );
+        assert_eq!(
+            Redistributable::providing("api-ms-win-crt-heap-l1-1-0.dll"),
+            Some(Redistributable::Ucrt)
+        );
+        assert_eq!(
+            Redistributable::providing("hostfxr.dll"),
+            Some(Redistributable::DotNet)
+        );
+        // msvcrt.dll is the system CRT, not a redistributable
+        assert_eq!(Redistributable::providing("msvcrt.dll"), None);
+        assert_eq!(Redistributable::providing("foo.dll"), None);
+    }
+
+    #[test]
+    fn test_unsatisfied_imports() {
+        let temp = TempDir::new().unwrap();
+        binary(
+            temp.path(),
+            "app.exe",
+            0x8664,
+            &["KERNEL32.dll", "core.dll", "missing.dll"],
+            &["optional.dll", "shcore.dll"],
+        );
+        binary(temp.path(), "core.dll", 0x8664, &["user32.dll"], &[]);
+
+        let body = r#"<ComponentGroup Id="G" Directory="INSTALLFOLDER">
+  <Component><File Source="app.exe" /></Component>
+  <Component><File Source="core.dll" /></Component>
+</ComponentGroup>"#;
+        let result = analyze(temp.path(), body, None);
+        assert_eq!(
+            rules(&result),
+            vec![
+                ("DEP-001", Severity::High),
+                ("DEP-001", Severity::Medium),
+                ("DEP-001", Severity::Medium)
+            ]
+        );
+        assert!(result.diagnostics[0].message.contains("'missing.dll'"));
+        assert!(result.diagnostics[2].message.contains("'shcore.dll'"));
+        assert!(result.diagnostics[2].message.contains("Windows 7"));
+
+        // shcore.dll ships with Windows 8.1
+        let result = analyze(temp.path(), body, Some(WindowsVersion::Win81));
+        assert_eq!(result.diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn test_redistributable_not_chained() {
+        let temp = TempDir::new().unwrap();
+        binary(
+            temp.path(),
+            "app.exe",
+            0x8664,
+            &["VCRUNTIME140.dll", "api-ms-win-crt-runtime-l1-1-0.dll"],
+            &[],
+        );
+        let files = r#"<Component Directory="INSTALLFOLDER"><File Source="app.exe" /></Component>"#;
+
+        let result = analyze(temp.path(), files, None);
+        assert_eq!(
+            rules(&result),
+            vec![("DEP-002", Severity::High), ("DEP-002", Severity::High)]
+        );
+        assert!(result.diagnostics[0]
+            .message
+            .contains("Visual C++ 2015-2022"));
+        assert!(result.diagnostics[1]
+            .message
+            .contains("Universal C Runtime"));
+        assert_eq!(result.diagnostics[0].related.len(), 1);
+
+        // The CRT merge module covers both the VC runtime and the UCRT
+        let with_merge = format!(
+            r#"{}<Merge Id="VCRedist" SourceFile="Microsoft_VC143_CRT_x64.msm" Directory="INSTALLFOLDER" />"#,
+            files
+        );
+        assert!(analyze(temp.path(), &with_merge, None)
+            .diagnostics
+            .is_empty());
+
+        // So does deploying the runtime next to the binary on Windows 10
+        std::fs::write(temp.path().join("vcruntime140.dll"), "not a PE").unwrap();
+        let app_local = format!(
+            r#"{}<Component Directory="INSTALLFOLDER"><File Source="vcruntime140.dll" /></Component>"#,
+            files
+        );
+        assert!(
+            analyze(temp.path(), &app_local, Some(WindowsVersion::Win10))
+                .diagnostics
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_architecture_mismatch_and_placement() {
+        let temp = TempDir::new().unwrap();
+        binary(
+            temp.path(),
+            "app.exe",
+            0x8664,
+            &["native.dll", "helper.dll"],
+            &[],
+        );
+        binary(temp.path(), "native.dll", 0x014c, &[], &[]);
+        binary(temp.path(), "helper.dll", 0x8664, &[], &[]);
+
+        let body = r#"<Component Directory="INSTALLFOLDER"><File Source="app.exe" /></Component>
+<Component Directory="INSTALLFOLDER"><File Source="native.dll" /></Component>
+<Component Directory="INSTALLFOLDER" Subdirectory="plugins"><File Source="helper.dll" /></Component>"#;
+        let result = analyze(temp.path(), body, None);
+        assert_eq!(
+            rules(&result),
+            vec![("DEP-003", Severity::High), ("DEP-004", Severity::Medium)]
+        );
+        assert!(result.diagnostics[0]
+            .message
+            .contains("(x64) imports 'native.dll', which is built for x86"));
+        assert!(result.diagnostics[1].message.contains("App\\plugins"));
+    }
+}
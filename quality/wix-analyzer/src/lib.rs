@@ -33,10 +33,16 @@ pub mod core;
 pub mod deps;
 pub mod engine;
 pub mod fixes;
+pub mod footprint;
+pub mod imports;
 pub mod licenses;
 pub mod lsp;
 pub mod output;
+pub mod payload;
+pub mod pe;
 pub mod plugins;
+pub mod release;
+pub mod sbom;
 
 // Re-export main types
 pub use crate::analyzers::Analyzer;
@@ -95,7 +101,8 @@ pub use crate::core::{
     Range,
     RatingType,
     RelatedInfo,
-    RuleCondition,
+    RuleScript,
+    ScriptIndex,
     SecurityStandard,
     Severity,
     SuppressionContext,
@@ -109,7 +116,7 @@ pub use crate::core::{
     BASELINE_FILE_NAME,
     CACHE_DIR_NAME,
 };
-pub use crate::fixes::FixEngine;
+pub use crate::fixes::{FixEngine, RefactorContext, RenameKind, WorkspaceEdit};
 pub use crate::lsp::{run_server, CodeActionProvider, WixLanguageServer};
 pub use crate::output::{
     get_formatter, CategoryCounts, Formatter, HtmlFormatter, MetricsFormatter, MetricsSummary,
@@ -129,6 +136,25 @@ pub fn analyze_with_source(
     index: &SymbolIndex,
     config: &Config,
     source: Option<&str>,
+) -> AnalysisResult {
+    analyze_file(doc, index, config, source, None)
+}
+
+/// Load the rule packs listed in the configuration
+pub fn load_rule_packs(config: &Config) -> Result<PluginRegistry, String> {
+    let mut registry = PluginRegistry::new();
+    for pack in &config.rules.packs {
+        registry.load_plugin(pack).map_err(|e| e.to_string())?;
+    }
+    Ok(registry)
+}
+
+fn analyze_file(
+    doc: &WixDocument,
+    index: &SymbolIndex,
+    config: &Config,
+    source: Option<&str>,
+    packs: Option<(&PluginRegistry, &ScriptIndex)>,
 ) -> AnalysisResult {
     use analyzers::*;
 
@@ -154,6 +180,10 @@ pub fn analyze_with_source(
         result.merge(analyzer.analyze(doc, index));
     }
 
+    if let Some((registry, script_index)) = packs {
+        result.merge(registry.analyze_with_index(doc, script_index));
+    }
+
     // Filter by enabled rules
     result
         .diagnostics
@@ -181,6 +211,24 @@ pub fn analyze_with_source(
 
 /// Analyze multiple files with cross-file reference resolution
 pub fn analyze_project(files: &[&Path], config: &Config) -> Result<Vec<AnalysisResult>, String> {
+    analyze_project_with(files, config, true)
+}
+
+/// Analyze multiple files without applying inline suppressions
+///
+/// Used to audit suppressions against the issues they hide.
+pub fn analyze_project_unsuppressed(
+    files: &[&Path],
+    config: &Config,
+) -> Result<Vec<AnalysisResult>, String> {
+    analyze_project_with(files, config, false)
+}
+
+fn analyze_project_with(
+    files: &[&Path],
+    config: &Config,
+    suppress: bool,
+) -> Result<Vec<AnalysisResult>, String> {
     // Build cross-file index
     let mut index = SymbolIndex::new();
 
@@ -197,6 +245,9 @@ pub fn analyze_project(files: &[&Path], config: &Config) -> Result<Vec<AnalysisR
             .map_err(|e| format!("Failed to index {}: {}", file.display(), e))?;
     }
 
+    let packs = load_rule_packs(config)?;
+    let script_index = ScriptIndex::new(&index);
+
     // Analyze each file
     let mut results = Vec::new();
 
@@ -211,8 +262,14 @@ pub fn analyze_project(files: &[&Path], config: &Config) -> Result<Vec<AnalysisR
         let doc = WixDocument::parse(&source, file)
             .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))?;
 
-        // Use analyze_with_source to support inline suppression comments
-        let result = analyze_with_source(&doc, &index, config, Some(&source));
+        // Pass the source to support inline suppression comments
+        let result = analyze_file(
+            &doc,
+            &index,
+            config,
+            suppress.then_some(source.as_str()),
+            Some((&packs, &script_index)),
+        );
         if !result.diagnostics.is_empty() {
             results.push(result);
         }
@@ -248,6 +305,8 @@ pub fn analyze_project_parallel(
             .map_err(|e| format!("Failed to index {}: {}", file.display(), e))?;
     }
 
+    let packs = load_rule_packs(config)?;
+    let script_index = ScriptIndex::new(&index);
     let index = Arc::new(index);
 
     // Filter out excluded files
@@ -263,7 +322,13 @@ pub fn analyze_project_parallel(
             let doc = WixDocument::parse(&source, file)
                 .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))?;
 
-            let result = analyze_with_source(&doc, &index, config, Some(&source));
+            let result = analyze_file(
+                &doc,
+                &index,
+                config,
+                Some(&source),
+                Some((&packs, &script_index)),
+            );
             Ok(result)
         })
         .collect();
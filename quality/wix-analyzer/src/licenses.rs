@@ -4,7 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Known license type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -268,13 +268,88 @@ impl LicenseDetector {
     }
 
     /// Detect license from file
-    pub fn detect_from_file(&self, _path: &PathBuf) -> FileLicenseInfo {
-        // In production, would read file and analyze
-        let mut info = FileLicenseInfo::new(_path.clone());
-        info.add_license(DetectedLicense::new(LicenseType::Unknown, 0.0));
+    ///
+    /// Text files are scanned for license text; binaries are attributed the
+    /// license of a license file shipped next to them (`app.dll.license`,
+    /// `app.LICENSE.txt`, `LICENSE`, `COPYING`, ...).
+    pub fn detect_from_file(&self, path: &PathBuf) -> FileLicenseInfo {
+        let mut info = FileLicenseInfo::new(path.clone());
+
+        let text = std::fs::read(path).map(|bytes| {
+            Some(bytes)
+                .filter(|b| !b.contains(&0))
+                .and_then(|b| String::from_utf8(b).ok())
+        });
+        let sources = match text {
+            Err(_) => Vec::new(),
+            Ok(Some(text)) => vec![(path.clone(), text)],
+            Ok(None) => Self::license_files(path)
+                .into_iter()
+                .filter_map(|p| Some((p.clone(), std::fs::read_to_string(&p).ok()?)))
+                .collect(),
+        };
+
+        for (source, text) in sources {
+            for license in self.detect_from_text(&text) {
+                if license.license_type != LicenseType::Unknown {
+                    info.add_license(license.with_source(source.clone()));
+                }
+            }
+            if !info.licenses.is_empty() {
+                break;
+            }
+        }
+
+        if info.licenses.is_empty() {
+            info.add_license(DetectedLicense::new(LicenseType::Unknown, 0.0));
+        }
         info
     }
 
+    /// License files that may cover a binary, most specific first
+    fn license_files(path: &Path) -> Vec<PathBuf> {
+        let Some(dir) = path.parent() else {
+            return Vec::new();
+        };
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let stem = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let candidates = [
+            format!("{}.license", name),
+            format!("{}.license.txt", name),
+            format!("{}.license", stem),
+            format!("{}.license.txt", stem),
+            "license".to_string(),
+            "license.txt".to_string(),
+            "license.md".to_string(),
+            "copying".to_string(),
+            "copying.txt".to_string(),
+        ];
+
+        let entries: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+        candidates
+            .iter()
+            .filter_map(|candidate| {
+                entries
+                    .iter()
+                    .find(|e| {
+                        e.is_file()
+                            && e.file_name()
+                                .map(|n| n.to_string_lossy().to_lowercase() == *candidate)
+                                .unwrap_or(false)
+                    })
+                    .cloned()
+            })
+            .collect()
+    }
+
     /// Scan directory for licenses
     pub fn scan_directory(&self, _path: &PathBuf) -> Vec<FileLicenseInfo> {
         // Would recursively scan directory
@@ -596,4 +671,44 @@ mod tests {
         let notice = report.generate_notice();
         assert!(notice.contains("THIRD-PARTY NOTICES"));
     }
+
+    #[test]
+    fn test_detect_from_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let detector = LicenseDetector::new();
+
+        // Binaries take the license shipped next to them
+        let dll = temp.path().join("zlib.dll");
+        std::fs::write(&dll, b"MZ\0\0binary").unwrap();
+        std::fs::write(
+            temp.path().join("LICENSE.txt"),
+            "MIT License\nPermission is hereby granted, free of charge",
+        )
+        .unwrap();
+        let info = detector.detect_from_file(&dll);
+        let primary = info.primary_license().unwrap();
+        assert_eq!(primary.license_type, LicenseType::MIT);
+        assert_eq!(
+            primary.source_file.as_deref(),
+            Some(temp.path().join("LICENSE.txt").as_path())
+        );
+
+        // A more specific license file wins
+        std::fs::write(
+            temp.path().join("zlib.dll.license"),
+            "ISC License\nPermission to use, copy, modify",
+        )
+        .unwrap();
+        let info = detector.detect_from_file(&dll);
+        assert_eq!(
+            info.primary_license().unwrap().license_type,
+            LicenseType::ISC
+        );
+
+        let unknown = detector.detect_from_file(&temp.path().join("missing.dll"));
+        assert_eq!(
+            unknown.primary_license().unwrap().license_type,
+            LicenseType::Unknown
+        );
+    }
 }
@@ -1,5 +1,6 @@
 //! Code actions for LSP quick fixes
 
+use crate::fixes::WorkspaceEdit as WixWorkspaceEdit;
 use crate::{Diagnostic as WixDiagnostic, Fix, FixAction};
 use tower_lsp::lsp_types::*;
 
@@ -420,6 +421,16 @@ impl CodeActionProvider {
         })
     }
 
+    /// Wrap a refactoring as a code action
+    pub fn refactor_action(&self, edit: &WixWorkspaceEdit) -> Option<CodeAction> {
+        Some(CodeAction {
+            title: edit.label.clone(),
+            kind: Some(CodeActionKind::REFACTOR),
+            edit: Some(workspace_edit_to_lsp(edit)?),
+            ..Default::default()
+        })
+    }
+
     // Helper methods
 
     fn wix_range_to_lsp(&self, range: &crate::core::Range) -> Range {
@@ -472,6 +483,55 @@ impl Default for CodeActionProvider {
     }
 }
 
+/// Convert a refactoring change set to an LSP workspace edit
+///
+/// Paths must be absolute to become file URIs.
+pub fn workspace_edit_to_lsp(edit: &WixWorkspaceEdit) -> Option<WorkspaceEdit> {
+    let provider = CodeActionProvider::new();
+    let mut operations = Vec::new();
+
+    for (path, content) in &edit.created_files {
+        let uri = Url::from_file_path(path).ok()?;
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Create(
+            CreateFile {
+                uri: uri.clone(),
+                options: None,
+                annotation_id: None,
+            },
+        )));
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+            edits: vec![OneOf::Left(TextEdit {
+                range: Range::default(),
+                new_text: content.clone(),
+            })],
+        }));
+    }
+
+    for (path, edits) in &edit.changes {
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: Url::from_file_path(path).ok()?,
+                version: None,
+            },
+            edits: edits
+                .iter()
+                .map(|e| {
+                    OneOf::Left(TextEdit {
+                        range: provider.wix_range_to_lsp(&e.range),
+                        new_text: e.new_text.clone(),
+                    })
+                })
+                .collect(),
+        }));
+    }
+
+    Some(WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    })
+}
+
 /// Generate a new GUID
 fn generate_guid() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -570,4 +630,40 @@ mod tests {
         assert_eq!(provider.get_indent(content, 1), "");
         assert_eq!(provider.get_indent(content, 2), "    ");
     }
+
+    #[test]
+    fn test_workspace_edit_to_lsp() {
+        let source = "<Component Id=\"A\" />";
+        let mut edit = WixWorkspaceEdit::new("Rename Component 'A' to 'B'");
+        edit.add(
+            "/project/a.wxs",
+            crate::fixes::TextEdit::from_offsets(source, 15, 16, "B"),
+        );
+        edit.create_file("/project/new.wxs", "<Wix />");
+
+        let action = CodeActionProvider::new().refactor_action(&edit).unwrap();
+        assert_eq!(action.kind, Some(CodeActionKind::REFACTOR));
+        let Some(DocumentChanges::Operations(ops)) = action.edit.unwrap().document_changes else {
+            panic!("expected document operations");
+        };
+        assert_eq!(ops.len(), 3);
+        assert!(matches!(
+            ops[0],
+            DocumentChangeOperation::Op(ResourceOp::Create(_))
+        ));
+        let DocumentChangeOperation::Edit(rename) = &ops[2] else {
+            panic!("expected a text edit");
+        };
+        assert_eq!(rename.text_document.uri.path(), "/project/a.wxs");
+        let OneOf::Left(text_edit) = &rename.edits[0] else {
+            panic!("expected a plain edit");
+        };
+        assert_eq!(text_edit.range.start, Position::new(0, 15));
+        assert_eq!(text_edit.new_text, "B");
+
+        // Relative paths cannot become URIs
+        let mut relative = WixWorkspaceEdit::new("x");
+        relative.create_file("new.wxs", "");
+        assert!(workspace_edit_to_lsp(&relative).is_none());
+    }
 }
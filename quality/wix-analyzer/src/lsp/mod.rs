@@ -5,5 +5,5 @@
 mod actions;
 mod server;
 
-pub use actions::CodeActionProvider;
+pub use actions::{workspace_edit_to_lsp, CodeActionProvider};
 pub use server::{run_server, WixLanguageServer};
@@ -1,15 +1,26 @@
 //! WiX Analyzer CLI
 
-use clap::{Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use wix_analyzer::{
     analytics::{AnalyticsConfig, AnalyticsGenerator},
-    analyze_project,
+    analyze_project, analyze_project_unsuppressed,
+    core::{
+        Baseline, DuplicationConfig, DuplicationDetector, GateCondition, HistoryEntry,
+        HistoryMetric, HistoryStore, QualityGate, RunMetrics, SuppressionAudit, SuppressionOrigin,
+        Trend, BASELINE_FILE_NAME,
+    },
     deps::{Dependency, DependencyGraph, DependencyReport, DependencyType, WixExtensionHelper},
+    footprint::{format_size, Footprint, FootprintAnalysis, DEFAULT_CLUSTER_SIZE},
     get_formatter,
+    imports::{ImportAnalysis, WindowsVersion},
     licenses::{DetectedLicense, FileLicenseInfo, LicenseDetector, LicenseReport, LicenseType},
-    Config, FixEngine, OutputFormat,
+    payload::{Payload, PayloadResolver},
+    release::{ReleaseCheck, ReleaseSnapshot, UpgradeKind},
+    sbom::{write_sbom, Sbom, SbomFormat},
+    Config, FixEngine, Formatter, HtmlFormatter, OutputFormat, PluginManifest, RefactorContext,
+    RenameKind, Severity, WorkspaceEdit,
 };
 
 #[derive(Parser)]
@@ -75,6 +86,23 @@ struct Cli {
     /// Disable colored output
     #[arg(long, global = true)]
     no_color: bool,
+
+    /// Record this run in the local quality history (.wix-analyzer/history)
+    #[arg(long, global = true)]
+    history: bool,
+
+    /// Fail if high-severity issues increased versus the latest recorded run on this branch
+    #[arg(long, value_name = "BRANCH", global = true)]
+    compare_branch: Option<String>,
+
+    /// With --compare-branch, also fail if the install size grew by more than this percentage
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        global = true,
+        requires = "compare_branch"
+    )]
+    max_install_growth: Option<f64>,
 }
 
 #[derive(Subcommand)]
@@ -132,6 +160,18 @@ enum Commands {
         /// List WiX extensions used
         #[arg(long)]
         extensions: bool,
+
+        /// Check the DLL imports of payload binaries against the payload,
+        /// Windows and the redistributables the package installs
+        #[arg(long)]
+        imports: bool,
+
+        /// Oldest supported Windows: 7, 8, 8.1 or 10 (default: from the launch conditions)
+        #[arg(long, value_name = "VERSION", requires = "imports")]
+        min_os: Option<String>,
+
+        #[command(flatten)]
+        payload: PayloadArgs,
     },
     /// Detect licenses in bundled files
     Licenses {
@@ -150,11 +190,147 @@ enum Commands {
         #[arg(long)]
         types: Vec<String>,
     },
+    /// Check component rules against the previous release
+    ReleaseCheck {
+        /// Current sources (files or directories)
+        paths: Vec<PathBuf>,
+
+        /// Previous release: an .msi, or its source directory
+        #[arg(long, value_name = "OLD.MSI|OLD-WXS-DIR")]
+        previous: PathBuf,
+
+        /// Upgrade kind (auto: minor when the ProductCode is unchanged, unknown
+        /// when the current sources don't fix one)
+        #[arg(long, default_value = "auto")]
+        upgrade: UpgradeArg,
+    },
+    /// Export a software bill of materials of the installed files
+    Sbom {
+        /// WiX sources of the package
+        paths: Vec<PathBuf>,
+
+        /// SBOM standard
+        #[arg(long, default_value = "cyclonedx")]
+        spec: SbomSpecArg,
+
+        /// Write to a file instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+
+        #[command(flatten)]
+        payload: PayloadArgs,
+    },
+    /// Find copy-pasted blocks and elements
+    Duplicates {
+        /// Files or directories to scan
+        paths: Vec<PathBuf>,
+
+        /// Compare element subtrees, ignoring Id/Guid values, attribute
+        /// order and formatting
+        #[arg(long)]
+        structural: bool,
+    },
+    /// Estimate install size per feature from the payload files
+    Footprint {
+        /// Project directory or WiX files
+        paths: Vec<PathBuf>,
+
+        /// Cluster size of the target volume in bytes
+        #[arg(long, default_value_t = DEFAULT_CLUSTER_SIZE)]
+        cluster_size: u64,
+
+        /// Number of largest files to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+
+        #[command(flatten)]
+        payload: PayloadArgs,
+    },
+    /// Project-wide refactorings (preview by default)
+    Refactor {
+        #[command(subcommand)]
+        action: RefactorCommands,
+    },
+    /// Show how quality metrics changed across recorded runs
+    Trend {
+        /// Only runs recorded on this branch
+        #[arg(long)]
+        branch: Option<String>,
+
+        /// Only the last N runs
+        #[arg(long, short = 'n')]
+        last: Option<usize>,
+    },
     /// Generate analytics configuration
     Analytics {
         #[command(subcommand)]
         action: AnalyticsCommands,
     },
+    /// Build, check and list rule packs
+    Rules {
+        #[command(subcommand)]
+        action: RulesCommands,
+    },
+    /// Review inline suppressions and baseline entries
+    Suppressions {
+        #[command(subcommand)]
+        action: SuppressionsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum SuppressionsCommands {
+    /// List suppressions, flagging stale and expired ones
+    Audit {
+        /// Files or directories to analyze
+        paths: Vec<PathBuf>,
+
+        /// Baseline files (default: .wixanalyzer-baseline.json in this or a parent directory)
+        #[arg(long)]
+        baseline: Vec<PathBuf>,
+
+        /// Exit with failure if any suppression has these problems
+        #[arg(long, value_enum, value_delimiter = ',')]
+        fail_on: Vec<AuditProblem>,
+
+        /// Remove stale entries from the baseline files
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum AuditProblem {
+    /// The suppressed issue no longer fires
+    Stale,
+    /// Past its until date
+    Expired,
+    /// No reason given
+    Unexplained,
+}
+
+#[derive(Subcommand)]
+enum RulesCommands {
+    /// Bundle a pack directory (pack.json + rules/*.json) into one file
+    Pack {
+        /// Pack directory
+        dir: PathBuf,
+
+        /// Output file (default: <name>-<version>.wixrules.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check rule packs for errors
+    Validate {
+        /// Bundled packs or pack directories
+        #[arg(required = true)]
+        packs: Vec<PathBuf>,
+    },
+    /// List the rules of packs (default: packs from the configuration)
+    List {
+        /// Bundled packs or pack directories
+        packs: Vec<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -177,11 +353,110 @@ enum AnalyticsCommands {
     Config,
 }
 
+#[derive(Subcommand)]
+enum RefactorCommands {
+    /// Rename an Id and every reference to it
+    Rename {
+        /// What to rename
+        kind: RenameKindArg,
+        /// Current Id
+        old: String,
+        /// New Id
+        new: String,
+        #[command(flatten)]
+        project: RefactorArgs,
+    },
+    /// Move components into a new Fragment with a ComponentGroup
+    Extract {
+        /// File declaring the components
+        #[arg(long)]
+        file: PathBuf,
+        /// Component Ids to extract
+        #[arg(long = "component", required = true)]
+        components: Vec<String>,
+        /// Id of the new ComponentGroup
+        #[arg(long)]
+        group: String,
+        /// File to put the fragment in (created if missing; default: --file)
+        #[arg(long)]
+        into: Option<PathBuf>,
+        #[command(flatten)]
+        project: RefactorArgs,
+    },
+    /// Inline a ComponentGroup that is referenced once
+    Inline {
+        /// ComponentGroup Id
+        group: String,
+        #[command(flatten)]
+        project: RefactorArgs,
+    },
+    /// Convert ProgramFilesFolder-style Directory trees to StandardDirectory
+    StandardDirectories {
+        #[command(flatten)]
+        project: RefactorArgs,
+    },
+}
+
+#[derive(Args)]
+struct RefactorArgs {
+    /// Project files or directories
+    #[arg(long = "path", short = 'p', default_value = ".")]
+    paths: Vec<PathBuf>,
+
+    /// Write the changes instead of previewing them
+    #[arg(long)]
+    apply: bool,
+}
+
+/// How `File Source=` paths are resolved, as for the wix build
+#[derive(Args)]
+struct PayloadArgs {
+    /// Bind path, DIR or NAME=DIR (repeatable)
+    #[arg(long = "bind-path", short = 'b', value_name = "[NAME=]DIR")]
+    bind_paths: Vec<String>,
+
+    /// Preprocessor variable (repeatable)
+    #[arg(long = "define", short = 'd', value_name = "NAME=VALUE")]
+    defines: Vec<String>,
+}
+
+impl PayloadArgs {
+    fn resolver(&self) -> PayloadResolver {
+        let resolver = self
+            .bind_paths
+            .iter()
+            .fold(PayloadResolver::new(), |r, b| r.with_bind_path(b));
+        self.defines.iter().fold(resolver, |r, d| r.with_define(d))
+    }
+}
+
+#[derive(Clone, ValueEnum)]
+enum SbomSpecArg {
+    Cyclonedx,
+    Spdx,
+}
+
+#[derive(Clone, ValueEnum)]
+enum RenameKindArg {
+    Component,
+    Directory,
+    Feature,
+    Property,
+}
+
 #[derive(Clone, ValueEnum)]
 enum Format {
     Text,
     Json,
     Sarif,
+    Html,
+}
+
+#[derive(Clone, ValueEnum)]
+enum UpgradeArg {
+    Auto,
+    Minor,
+    Major,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -217,11 +492,19 @@ fn main() -> ExitCode {
             *fix,
             *fix_dry_run,
         ),
+        Some(Commands::Deps {
+            paths,
+            imports: true,
+            min_os,
+            payload,
+            ..
+        }) => run_imports(&cli, paths, min_os.as_deref(), payload),
         Some(Commands::Deps {
             paths,
             graph,
             check,
             extensions,
+            ..
         }) => run_deps(&cli, paths.clone(), *graph, *check, *extensions),
         Some(Commands::Licenses {
             path,
@@ -229,7 +512,39 @@ fn main() -> ExitCode {
             notice,
             types,
         }) => run_licenses(&cli, path.clone(), *check, *notice, types.clone()),
+        Some(Commands::ReleaseCheck {
+            paths,
+            previous,
+            upgrade,
+        }) => run_release_check(&cli, paths.clone(), previous.clone(), upgrade.clone()),
+        Some(Commands::Sbom {
+            paths,
+            spec,
+            output,
+            payload,
+        }) => run_sbom(&cli, paths, spec, output.as_deref(), payload),
+        Some(Commands::Duplicates { paths, structural }) => {
+            run_duplicates(&cli, paths, *structural)
+        }
+        Some(Commands::Footprint {
+            paths,
+            cluster_size,
+            top,
+            payload,
+        }) => run_footprint(&cli, paths, *cluster_size, *top, payload),
+        Some(Commands::Refactor { action }) => run_refactor(&cli, action),
+        Some(Commands::Trend { branch, last }) => run_trend(&cli, branch.as_deref(), *last),
         Some(Commands::Analytics { action }) => run_analytics(&cli, action),
+        Some(Commands::Rules { action }) => run_rules(&cli, action),
+        Some(Commands::Suppressions {
+            action:
+                SuppressionsCommands::Audit {
+                    paths,
+                    baseline,
+                    fail_on,
+                    prune,
+                },
+        }) => run_suppressions_audit(&cli, paths, baseline, fail_on, *prune),
         None => {
             // Default: run analyze if paths provided
             if !cli.paths.is_empty() {
@@ -313,15 +628,87 @@ fn run_analyze(
         return handle_fixes(&results, &files, fix_dry_run);
     }
 
-    let format = match cli.format {
-        Format::Text => OutputFormat::Text,
-        Format::Json => OutputFormat::Json,
-        Format::Sarif => OutputFormat::Sarif,
-    };
+    let store = HistoryStore::open(&std::env::current_dir().unwrap_or_default());
+    // Install size is gated, shown in the HTML report and recorded in history
+    let wants_footprint =
+        cli.max_install_growth.is_some() || cli.history || matches!(cli.format, Format::Html);
+    let footprint = wants_footprint.then(|| measure_footprint(&files)).flatten();
+    // Run metrics are only needed to record the run or compare against one
+    let metrics = (cli.history || cli.compare_branch.is_some()).then(|| {
+        let metrics = run_metrics(&results, &files);
+        match &footprint {
+            Some(footprint) => metrics.with_footprint(footprint),
+            None => metrics,
+        }
+    });
 
-    let colored = !cli.no_color && atty::is(atty::Stream::Stdout);
-    let formatter = get_formatter(format, colored);
-    println!("{}", formatter.format(&results));
+    // A delta gate without a reference run fails rather than passing unchecked
+    let mut gate_failed = false;
+    let delta_gate = cli.compare_branch.as_ref().and_then(|branch| {
+        let metrics = metrics.as_ref()?;
+        let reference = match store.latest_on_branch(branch) {
+            Ok(Some(entry)) => entry,
+            Ok(None) => {
+                eprintln!(
+                    "Error: no recorded run on branch '{}'; the delta gate was not evaluated",
+                    branch
+                );
+                gate_failed = true;
+                return None;
+            }
+            Err(e) => {
+                eprintln!("Error: {}; the delta gate was not evaluated", e);
+                gate_failed = true;
+                return None;
+            }
+        };
+        let mut gate = QualityGate::new(format!("Delta vs {}", branch))
+            .with_condition(GateCondition::no_increase(HistoryMetric::HighIssues));
+        if let Some(percent) = cli.max_install_growth {
+            gate = gate.with_condition(GateCondition::max_growth(
+                HistoryMetric::InstallSize,
+                percent,
+            ));
+        }
+        Some(gate.evaluate_run(&results, metrics, Some(&reference.metrics)))
+    });
+
+    if let Some(mut metrics) = metrics.filter(|_| cli.history) {
+        if let Some(gate) = &delta_gate {
+            metrics = metrics.with_gate(gate);
+        }
+        let entry =
+            HistoryEntry::new(metrics).with_git(&std::env::current_dir().unwrap_or_default());
+        if let Err(e) = store.record(&entry) {
+            eprintln!("Warning: {}", e);
+        } else if cli.verbose {
+            eprintln!("Recorded run in {}", store.dir().display());
+        }
+    }
+
+    match cli.format {
+        Format::Html => {
+            let history = store.entries().unwrap_or_else(|e| {
+                eprintln!("Warning: {}", e);
+                Vec::new()
+            });
+            let mut formatter = HtmlFormatter::new().with_history(history);
+            if let Some(footprint) = footprint {
+                formatter = formatter.with_footprint(footprint);
+            }
+            println!("{}", formatter.format(&results));
+        }
+        _ => {
+            let format = match cli.format {
+                Format::Json => OutputFormat::Json,
+                Format::Sarif => OutputFormat::Sarif,
+                _ => OutputFormat::Text,
+            };
+            let colored = !cli.no_color && atty::is(atty::Stream::Stdout);
+            let formatter = get_formatter(format, colored);
+            println!("{}", formatter.format(&results));
+        }
+    }
 
     let has_errors = results.iter().any(|r| {
         r.diagnostics
@@ -329,7 +716,15 @@ fn run_analyze(
             .any(|d| d.severity >= wix_analyzer::Severity::High)
     });
 
-    if has_errors {
+    if let Some(gate) = delta_gate {
+        eprintln!("{}", gate.summary());
+        for failure in &gate.failures {
+            eprintln!("  {}", failure);
+        }
+        gate_failed = !gate.passed;
+    }
+
+    if has_errors || gate_failed {
         ExitCode::FAILURE
     } else {
         ExitCode::SUCCESS
@@ -429,6 +824,45 @@ fn run_deps(
     ExitCode::SUCCESS
 }
 
+fn run_imports(
+    cli: &Cli,
+    paths: &[PathBuf],
+    min_os: Option<&str>,
+    payload: &PayloadArgs,
+) -> ExitCode {
+    let min_os = match min_os.map(|v| (v, WindowsVersion::parse(v))) {
+        Some((v, None)) => {
+            eprintln!(
+                "Error: unknown Windows version '{}' (use 7, 8, 8.1 or 10)",
+                v
+            );
+            return ExitCode::FAILURE;
+        }
+        Some((_, os)) => os,
+        None => None,
+    };
+    let payload = match load_payload(paths, payload) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut analysis = ImportAnalysis::new(&payload);
+    if let Some(os) = min_os {
+        analysis = analysis.with_min_os(os);
+    }
+    if cli.verbose {
+        eprintln!(
+            "Checking imports of {} binaries for {}...",
+            analysis.binaries().len(),
+            analysis.min_os().as_str()
+        );
+    }
+    print_result(cli, analysis.run())
+}
+
 fn run_licenses(
     cli: &Cli,
     path: PathBuf,
@@ -503,6 +937,712 @@ fn run_licenses(
     ExitCode::SUCCESS
 }
 
+fn run_release_check(
+    cli: &Cli,
+    paths: Vec<PathBuf>,
+    previous: PathBuf,
+    upgrade: UpgradeArg,
+) -> ExitCode {
+    let files = match collect_files(&paths, &[], &[]) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let files: Vec<PathBuf> = files
+        .into_iter()
+        .filter(|f| f.extension().map(|e| e == "wxs").unwrap_or(false))
+        .collect();
+
+    if files.is_empty() {
+        eprintln!("No WiX files found");
+        return ExitCode::FAILURE;
+    }
+
+    let previous = match ReleaseSnapshot::load(&previous) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let file_refs: Vec<&std::path::Path> = files.iter().map(|p| p.as_path()).collect();
+    let current = match ReleaseSnapshot::from_sources(&file_refs) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut check = ReleaseCheck::new(&previous, &current);
+    match upgrade {
+        UpgradeArg::Auto => {}
+        UpgradeArg::Minor => check = check.with_upgrade(UpgradeKind::Minor),
+        UpgradeArg::Major => check = check.with_upgrade(UpgradeKind::Major),
+    }
+    if check.upgrade() == UpgradeKind::Unknown {
+        eprintln!(
+            "Warning: the current sources have no fixed ProductCode, so the upgrade kind is unknown; \
+             minor-upgrade violations are reported as warnings. Pass --upgrade minor or --upgrade major."
+        );
+    }
+
+    if cli.verbose {
+        eprintln!(
+            "Comparing {} component(s) with {} ({:?} upgrade)...",
+            current.components.len(),
+            previous.source.display(),
+            check.upgrade()
+        );
+    }
+
+    let result = check.run();
+
+    let format = match cli.format {
+        Format::Text => OutputFormat::Text,
+        Format::Json => OutputFormat::Json,
+        Format::Sarif => OutputFormat::Sarif,
+        Format::Html => OutputFormat::Html,
+    };
+    let colored = !cli.no_color && atty::is(atty::Stream::Stdout);
+    let formatter = get_formatter(format, colored);
+    let has_errors = result
+        .diagnostics
+        .iter()
+        .any(|d| d.severity >= wix_analyzer::Severity::High);
+    println!("{}", formatter.format(&[result]));
+
+    if has_errors {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_refactor(cli: &Cli, action: &RefactorCommands) -> ExitCode {
+    let project = match action {
+        RefactorCommands::Rename { project, .. }
+        | RefactorCommands::Extract { project, .. }
+        | RefactorCommands::Inline { project, .. }
+        | RefactorCommands::StandardDirectories { project } => project,
+    };
+
+    let files = match collect_files(&project.paths, &[], &cli.exclude) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let context = match RefactorContext::load(&files) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let edit = match action {
+        RefactorCommands::Rename { kind, old, new, .. } => {
+            let kind = match kind {
+                RenameKindArg::Component => RenameKind::Component,
+                RenameKindArg::Directory => RenameKind::Directory,
+                RenameKindArg::Feature => RenameKind::Feature,
+                RenameKindArg::Property => RenameKind::Property,
+            };
+            context.rename(kind, old, new)
+        }
+        RefactorCommands::Extract {
+            file,
+            components,
+            group,
+            into,
+            ..
+        } => {
+            // Match the spelling collect_files produced for the same file
+            let file = files.iter().find(|f| same_file(f, file)).unwrap_or(file);
+            let components: Vec<&str> = components.iter().map(String::as_str).collect();
+            context.extract_component_group(file, &components, group, into.as_deref())
+        }
+        RefactorCommands::Inline { group, .. } => context.inline_component_group(group),
+        RefactorCommands::StandardDirectories { .. } => context.convert_standard_directories(),
+    };
+    let edit: WorkspaceEdit = match edit {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if edit.is_empty() {
+        println!("{}: nothing to change", edit.label);
+        return ExitCode::SUCCESS;
+    }
+
+    print!(
+        "{}",
+        edit.preview(|path| context.source(path).map(str::to_string))
+    );
+
+    if project.apply {
+        match edit.apply() {
+            Ok(results) => println!("\nUpdated {} file(s)", results.len()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        println!("\n(preview - run with --apply to write the changes)");
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn run_sbom(
+    cli: &Cli,
+    paths: &[PathBuf],
+    spec: &SbomSpecArg,
+    output: Option<&Path>,
+    payload: &PayloadArgs,
+) -> ExitCode {
+    let payload = match load_payload(paths, payload) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let sbom = Sbom::from_payload(&payload);
+    let format = match spec {
+        SbomSpecArg::Cyclonedx => SbomFormat::CycloneDx,
+        SbomSpecArg::Spdx => SbomFormat::Spdx,
+    };
+    if let Err(e) = write_sbom(&sbom, format, output) {
+        eprintln!("Error writing SBOM: {}", e);
+        return ExitCode::FAILURE;
+    }
+    if let Some(output) = output {
+        if cli.verbose {
+            eprintln!(
+                "Wrote {} files and {} dependencies to {}",
+                sbom.files.len(),
+                sbom.dependencies.len(),
+                output.display()
+            );
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Load the payload of the .wxs files under `paths`, warning about
+/// sources that can't be found
+fn load_payload(paths: &[PathBuf], args: &PayloadArgs) -> Result<Payload, String> {
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.to_vec()
+    };
+    let files = collect_files(&paths, &[], &[])?;
+    let files: Vec<&Path> = files
+        .iter()
+        .filter(|f| f.extension().map(|e| e == "wxs").unwrap_or(false))
+        .map(|f| f.as_path())
+        .collect();
+    if files.is_empty() {
+        return Err("No WiX files found".to_string());
+    }
+
+    let payload = Payload::load(&files, &args.resolver())?;
+    for file in payload.unresolved() {
+        eprintln!(
+            "warning: {}: source '{}' of file '{}' not found (add a --bind-path or --define)",
+            file.location.file.display(),
+            file.source,
+            file.id
+        );
+    }
+    Ok(payload)
+}
+
+/// Print a result with the selected formatter; fails on high-severity issues
+fn print_result(cli: &Cli, result: wix_analyzer::AnalysisResult) -> ExitCode {
+    let format = match cli.format {
+        Format::Text => OutputFormat::Text,
+        Format::Json => OutputFormat::Json,
+        Format::Sarif => OutputFormat::Sarif,
+        Format::Html => OutputFormat::Html,
+    };
+    let colored = !cli.no_color && atty::is(atty::Stream::Stdout);
+    let formatter = get_formatter(format, colored);
+    let has_errors = result
+        .diagnostics
+        .iter()
+        .any(|d| d.severity >= wix_analyzer::Severity::High);
+    println!("{}", formatter.format(&[result]));
+
+    if has_errors {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_duplicates(cli: &Cli, paths: &[PathBuf], structural: bool) -> ExitCode {
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.to_vec()
+    };
+    let files = match collect_files(&paths, &[], &[]) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let config = if structural {
+        DuplicationConfig::structural()
+    } else {
+        DuplicationConfig::default()
+    };
+    let mut detector = DuplicationDetector::new(config);
+    for file in &files {
+        if let Ok(content) = std::fs::read_to_string(file) {
+            detector.add_file(&file.display().to_string(), &content);
+        }
+    }
+    let result = detector.results();
+
+    if let Format::Json = cli.format {
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        return ExitCode::SUCCESS;
+    }
+
+    for duplicate in &result.duplicates {
+        println!(
+            "{} ({} lines, {} instances)",
+            duplicate.kind.as_str(),
+            duplicate.lines,
+            duplicate.count()
+        );
+        for location in &duplicate.locations {
+            println!(
+                "  {}:{}-{}",
+                location.file.display(),
+                location.start_line,
+                location.end_line
+            );
+        }
+        if let Some(suggestion) = &duplicate.suggestion {
+            for line in suggestion.lines() {
+                println!("    {}", line);
+            }
+        }
+        println!();
+    }
+    println!(
+        "{} duplicates, {} of {} lines duplicated ({:.1}%)",
+        result.duplicates.len(),
+        result.duplicated_lines,
+        result.total_lines,
+        result.duplication_percentage
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn run_footprint(
+    cli: &Cli,
+    paths: &[PathBuf],
+    cluster_size: u64,
+    top: usize,
+    payload: &PayloadArgs,
+) -> ExitCode {
+    let payload = match load_payload(paths, payload) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let footprint = FootprintAnalysis::new(&payload)
+        .with_cluster_size(cluster_size)
+        .run();
+
+    match cli.format {
+        Format::Html => {
+            println!(
+                "{}",
+                HtmlFormatter::new()
+                    .with_title("WiX Install Size Report")
+                    .with_footprint(footprint)
+                    .format(&[])
+            );
+            return ExitCode::SUCCESS;
+        }
+        Format::Json | Format::Sarif => {
+            println!("{}", serde_json::to_string_pretty(&footprint).unwrap());
+            return ExitCode::SUCCESS;
+        }
+        Format::Text => {}
+    }
+
+    println!(
+        "{:<24} {:>7} {:>12} {:>12} {:>14}",
+        "Feature", "Files", "Size", "On disk", "With children"
+    );
+    for feature in footprint.features.iter().chain(&footprint.unassigned) {
+        let depth = std::iter::successors(feature.parent.as_deref(), |parent| {
+            footprint
+                .features
+                .iter()
+                .find(|f| f.id == *parent)
+                .and_then(|f| f.parent.as_deref())
+        })
+        .count();
+        println!(
+            "{:<24} {:>7} {:>12} {:>12} {:>14}",
+            format!("{}{}", "  ".repeat(depth), feature.id),
+            feature.files,
+            format_size(feature.size),
+            format_size(feature.disk),
+            format_size(feature.total_disk)
+        );
+    }
+
+    if top > 0 && !footprint.files.is_empty() {
+        println!("\nLargest files:");
+        for file in footprint.largest(top) {
+            println!("  {:>10}  {}", format_size(file.size), file.install_path);
+        }
+    }
+
+    println!(
+        "\nTotal: {} on disk ({} payload, {} byte clusters), cabinets ~{}",
+        format_size(footprint.total_disk),
+        format_size(footprint.total_size),
+        footprint.cluster_size,
+        format_size(footprint.compressed_size)
+    );
+    if footprint.unresolved > 0 {
+        println!("{} files not found and not counted", footprint.unresolved);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Install footprint of the analyzed sources, when every payload file
+/// resolves without bind paths; a partial size would make trends misleading
+fn measure_footprint(files: &[PathBuf]) -> Option<Footprint> {
+    let sources: Vec<&Path> = files
+        .iter()
+        .filter(|f| f.extension().map(|e| e == "wxs").unwrap_or(false))
+        .map(|f| f.as_path())
+        .collect();
+    let payload = Payload::load(&sources, &PayloadResolver::new()).ok()?;
+    if payload.files.is_empty() || payload.unresolved().next().is_some() {
+        return None;
+    }
+    Some(FootprintAnalysis::new(&payload).run())
+}
+
+/// Metrics of a run, including duplication across the analyzed files
+fn run_metrics(results: &[wix_analyzer::AnalysisResult], files: &[PathBuf]) -> RunMetrics {
+    let mut detector = DuplicationDetector::with_default_config();
+    let mut lines_of_code = 0;
+    for file in files {
+        if let Ok(content) = std::fs::read_to_string(file) {
+            lines_of_code += content.lines().count();
+            detector.add_file(&file.display().to_string(), &content);
+        }
+    }
+    RunMetrics::from_results(results, lines_of_code)
+        .with_files(files.len())
+        .with_duplication(&detector.results())
+}
+
+fn run_trend(cli: &Cli, branch: Option<&str>, last: Option<usize>) -> ExitCode {
+    let store = HistoryStore::open(&std::env::current_dir().unwrap_or_default());
+    let entries = match branch {
+        Some(branch) => store.entries_on_branch(branch),
+        None => store.entries(),
+    };
+    let entries = match entries {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut trend = Trend::new(entries);
+    if let Some(n) = last {
+        trend = trend.last(n);
+    }
+
+    match cli.format {
+        Format::Json => println!("{}", trend.format_json()),
+        Format::Html => println!(
+            "{}",
+            HtmlFormatter::new()
+                .with_title("WiX Analyzer Trends")
+                .with_history(trend.entries().to_vec())
+                .format(&[])
+        ),
+        _ => print!("{}", trend.format_text()),
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn run_suppressions_audit(
+    cli: &Cli,
+    paths: &[PathBuf],
+    baselines: &[PathBuf],
+    fail_on: &[AuditProblem],
+    prune: bool,
+) -> ExitCode {
+    let mut config = load_config(cli);
+    // Suppressions of info-level issues would otherwise look stale
+    config.min_severity = wix_analyzer::config::MinSeverity::Info;
+
+    let paths = if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.to_vec()
+    };
+    let files = match collect_files(&paths, &[], &cli.exclude) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    // Baseline entries are recorded relative to the baseline's directory
+    let files: Vec<PathBuf> = files
+        .into_iter()
+        .map(|f| std::path::absolute(&f).unwrap_or(f))
+        .collect();
+    let file_refs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
+    let results = match analyze_project_unsuppressed(&file_refs, &config) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let baselines: Vec<PathBuf> = if baselines.is_empty() {
+        let mut dir = std::env::current_dir().unwrap_or_default();
+        loop {
+            let candidate = dir.join(BASELINE_FILE_NAME);
+            if candidate.exists() {
+                break vec![candidate];
+            }
+            if !dir.pop() {
+                break Vec::new();
+            }
+        }
+    } else {
+        baselines.to_vec()
+    };
+    let mut loaded = Vec::new();
+    for path in baselines {
+        let path = std::path::absolute(&path).unwrap_or(path);
+        match Baseline::load(&path) {
+            Ok(baseline) => loaded.push((path, baseline)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let diagnostics = results.iter().flat_map(|r| &r.diagnostics);
+    let mut audit = SuppressionAudit::new(diagnostics.clone());
+    for file in &files {
+        if let Ok(source) = std::fs::read_to_string(file) {
+            audit.audit_source(file, &source);
+        }
+    }
+    for (path, baseline) in &loaded {
+        audit.audit_baseline(path, baseline, &files, path.parent());
+    }
+    let report = audit.finish();
+
+    match cli.format {
+        Format::Json | Format::Sarif => {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap())
+        }
+        _ => print!("{}", report.format_text()),
+    }
+
+    if prune {
+        for (path, baseline) in &mut loaded {
+            let removed = baseline.prune(diagnostics.clone(), &files, path.parent());
+            if removed.is_empty() {
+                continue;
+            }
+            if let Err(e) = baseline.save(path) {
+                eprintln!("Error: {}", e);
+                return ExitCode::FAILURE;
+            }
+            eprintln!(
+                "Pruned {} stale entries from {}",
+                removed.len(),
+                path.display()
+            );
+        }
+    }
+
+    let failed = fail_on.iter().any(|problem| match problem {
+        // Pruned baseline entries are no longer a problem
+        AuditProblem::Stale => report
+            .stale()
+            .any(|e| !(prune && e.origin == SuppressionOrigin::Baseline)),
+        AuditProblem::Expired => report.expired().next().is_some(),
+        AuditProblem::Unexplained => report.without_reason().next().is_some(),
+    });
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn run_rules(cli: &Cli, action: &RulesCommands) -> ExitCode {
+    match action {
+        RulesCommands::Pack { dir, output } => {
+            let manifest = match PluginManifest::from_dir(dir) {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let problems = manifest.validate();
+            if !problems.is_empty() {
+                for problem in &problems {
+                    eprintln!("{}: {}", dir.display(), problem);
+                }
+                return ExitCode::FAILURE;
+            }
+            let output = output
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(manifest.file_name()));
+            let json = serde_json::to_string_pretty(&manifest).unwrap_or_default();
+            if let Err(e) = std::fs::write(&output, json + "\n") {
+                eprintln!("Error: cannot write {}: {}", output.display(), e);
+                return ExitCode::FAILURE;
+            }
+            println!(
+                "Packed {}@{} ({} rules) into {}",
+                manifest.name,
+                manifest.version,
+                manifest.rules.len(),
+                output.display()
+            );
+            ExitCode::SUCCESS
+        }
+        RulesCommands::Validate { packs } => {
+            let mut failed = false;
+            for path in packs {
+                match PluginManifest::load(path) {
+                    Ok(manifest) => {
+                        for warning in manifest.warnings() {
+                            println!("{}: warning: {}", path.display(), warning);
+                        }
+                        let problems = manifest.validate();
+                        if problems.is_empty() {
+                            println!(
+                                "{}: ok ({}@{}, {} rules)",
+                                path.display(),
+                                manifest.name,
+                                manifest.version,
+                                manifest.rules.len()
+                            );
+                        } else {
+                            failed = true;
+                            for problem in problems {
+                                println!("{}: {}", path.display(), problem);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        failed = true;
+                        println!("{}", e);
+                    }
+                }
+            }
+            if failed {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        RulesCommands::List { packs } => {
+            let mut config = load_config(cli);
+            if !packs.is_empty() {
+                config.rules.packs = packs.clone();
+            }
+            let registry = match wix_analyzer::load_rule_packs(&config) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            for warning in registry.warnings() {
+                eprintln!("Warning: {}", warning);
+            }
+            if matches!(cli.format, Format::Json) {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(registry.plugins()).unwrap_or_default()
+                );
+                return ExitCode::SUCCESS;
+            }
+            if registry.rule_count() == 0 {
+                println!("No rule packs configured");
+                return ExitCode::SUCCESS;
+            }
+            for pack in registry.plugins() {
+                for rule in &pack.rules {
+                    println!(
+                        "{:<12} {:<8} {:<16} {}{}  [{}@{}]",
+                        rule.id,
+                        Severity::from(rule.severity).as_str(),
+                        rule.element.as_deref().unwrap_or("*"),
+                        rule.name,
+                        if rule.script.is_some() {
+                            " (script)"
+                        } else {
+                            ""
+                        },
+                        pack.name,
+                        pack.version
+                    );
+                }
+            }
+            ExitCode::SUCCESS
+        }
+    }
+}
+
 fn run_analytics(cli: &Cli, action: &AnalyticsCommands) -> ExitCode {
     match action {
         AnalyticsCommands::Generate {
@@ -3,7 +3,8 @@
 //! Generates self-contained HTML reports with styling and interactivity.
 
 use super::Formatter;
-use crate::core::{AnalysisResult, Diagnostic, IssueType, Severity};
+use crate::core::{AnalysisResult, Diagnostic, HistoryEntry, HistoryMetric, IssueType, Severity};
+use crate::footprint::{format_size, Footprint};
 
 /// HTML formatter
 pub struct HtmlFormatter {
@@ -11,6 +12,10 @@ pub struct HtmlFormatter {
     title: String,
     /// Whether to include inline CSS
     inline_css: bool,
+    /// Recorded runs for the trend charts, oldest first
+    history: Vec<HistoryEntry>,
+    /// Install footprint of the package payload
+    footprint: Option<Footprint>,
 }
 
 impl HtmlFormatter {
@@ -18,6 +23,8 @@ impl HtmlFormatter {
         Self {
             title: "WiX Analyzer Report".to_string(),
             inline_css: true,
+            history: Vec::new(),
+            footprint: None,
         }
     }
 
@@ -25,6 +32,18 @@ impl HtmlFormatter {
         self.title = title.into();
         self
     }
+
+    /// Add trend charts from the quality history
+    pub fn with_history(mut self, history: Vec<HistoryEntry>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Add the install size section
+    pub fn with_footprint(mut self, footprint: Footprint) -> Self {
+        self.footprint = Some(footprint);
+        self
+    }
 }
 
 impl Default for HtmlFormatter {
@@ -156,7 +175,7 @@ impl Formatter for HtmlFormatter {
             <div class="type-card secret"><span class="count">{secrets}</span> Secrets</div>
         </div>
     </section>
-
+{trends}{footprint}
     <section class="issues">
         <h2>Issues</h2>
         <table>
@@ -201,6 +220,12 @@ impl Formatter for HtmlFormatter {
             hotspots = hotspots,
             secrets = secrets,
             rows = rows,
+            trends = format_trends(&self.history),
+            footprint = self
+                .footprint
+                .as_ref()
+                .map(format_footprint)
+                .unwrap_or_default(),
             version = env!("CARGO_PKG_VERSION"),
         )
     }
@@ -285,6 +310,188 @@ fn format_row(diag: &Diagnostic) -> String {
     )
 }
 
+/// Metrics charted in the trends section
+const TREND_CHARTS: [HistoryMetric; 5] = [
+    HistoryMetric::Issues,
+    HistoryMetric::HighIssues,
+    HistoryMetric::DebtMinutes,
+    HistoryMetric::DuplicatedLines,
+    HistoryMetric::InstallSize,
+];
+
+fn format_trends(history: &[HistoryEntry]) -> String {
+    if history.is_empty() {
+        return String::new();
+    }
+
+    let charts: String = TREND_CHARTS
+        .iter()
+        .filter_map(|metric| {
+            let points: Vec<(&HistoryEntry, f64)> = history
+                .iter()
+                .filter_map(|e| Some((e, metric.value(&e.metrics)?)))
+                .collect();
+            if points.is_empty() {
+                return None;
+            }
+            Some(format!(
+                r#"        <div class="trend-chart">
+            <h3>{title}</h3>
+            {svg}
+        </div>
+"#,
+                title = html_escape(metric.as_str()),
+                svg = svg_line_chart(*metric, &points),
+            ))
+        })
+        .collect();
+
+    format!(
+        r#"
+    <section class="trends">
+        <h2>Trends</h2>
+        <p class="trend-range">{runs} runs, {from} to {to}</p>
+        <div class="trend-grid">
+{charts}        </div>
+    </section>
+"#,
+        runs = history.len(),
+        from = history[0].timestamp.format("%Y-%m-%d"),
+        to = history[history.len() - 1].timestamp.format("%Y-%m-%d"),
+        charts = charts,
+    )
+}
+
+/// Number of largest files listed in the footprint section
+const LARGEST_FILES: usize = 10;
+
+fn format_footprint(footprint: &Footprint) -> String {
+    let features: String = footprint
+        .features
+        .iter()
+        .chain(&footprint.unassigned)
+        .map(|f| {
+            format!(
+                r#"                <tr><td>{id}</td><td>{title}</td><td class="size">{files}</td><td class="size">{size}</td><td class="size">{disk}</td><td class="size">{total}</td></tr>
+"#,
+                id = html_escape(&f.id),
+                title = html_escape(f.title.as_deref().unwrap_or("")),
+                files = f.files,
+                size = format_size(f.size),
+                disk = format_size(f.disk),
+                total = format_size(f.total_disk),
+            )
+        })
+        .collect();
+    let largest: String = footprint
+        .largest(LARGEST_FILES)
+        .iter()
+        .map(|f| {
+            format!(
+                r#"                <tr><td class="file">{path}</td><td class="size">{size}</td></tr>
+"#,
+                path = html_escape(&f.install_path),
+                size = format_size(f.size),
+            )
+        })
+        .collect();
+    let unresolved = if footprint.unresolved > 0 {
+        format!(
+            r#"        <p class="note">{} files not found and not counted</p>
+"#,
+            footprint.unresolved
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"
+    <section class="footprint">
+        <h2>Install Size</h2>
+        <div class="stats-grid">
+            <div class="stat-card"><span class="stat-value">{disk}</span><span class="stat-label">On Disk</span></div>
+            <div class="stat-card"><span class="stat-value">{size}</span><span class="stat-label">Payload</span></div>
+            <div class="stat-card"><span class="stat-value">{compressed}</span><span class="stat-label">Cabinets (est.)</span></div>
+        </div>
+{unresolved}        <h3>By Feature</h3>
+        <table>
+            <thead>
+                <tr><th>Feature</th><th>Title</th><th>Files</th><th>Size</th><th>On Disk</th><th>With Sub-features</th></tr>
+            </thead>
+            <tbody>
+{features}            </tbody>
+        </table>
+        <h3>Largest Files</h3>
+        <table>
+            <thead>
+                <tr><th>File</th><th>Size</th></tr>
+            </thead>
+            <tbody>
+{largest}            </tbody>
+        </table>
+    </section>
+"#,
+        disk = format_size(footprint.total_disk),
+        size = format_size(footprint.total_size),
+        compressed = format_size(footprint.compressed_size),
+        unresolved = unresolved,
+        features = features,
+        largest = largest,
+    )
+}
+
+/// Inline SVG line chart of one metric over the recorded runs
+fn svg_line_chart(metric: HistoryMetric, points: &[(&HistoryEntry, f64)]) -> String {
+    const WIDTH: f64 = 320.0;
+    const HEIGHT: f64 = 120.0;
+    const PAD: f64 = 10.0;
+
+    let max = points.iter().map(|(_, v)| *v).fold(0.0, f64::max).max(1.0);
+    let step = if points.len() > 1 {
+        (WIDTH - 2.0 * PAD) / (points.len() - 1) as f64
+    } else {
+        0.0
+    };
+    let coords: Vec<(f64, f64)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, (_, v))| {
+            let x = PAD + i as f64 * step;
+            let y = HEIGHT - PAD - (v / max) * (HEIGHT - 2.0 * PAD);
+            (x, y)
+        })
+        .collect();
+
+    let polyline = coords
+        .iter()
+        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let dots: String = coords
+        .iter()
+        .zip(points)
+        .map(|((x, y), (entry, v))| {
+            format!(
+                r#"<circle cx="{:.1}" cy="{:.1}" r="3"><title>{} {}: {}</title></circle>"#,
+                x,
+                y,
+                entry.timestamp.format("%Y-%m-%d"),
+                html_escape(entry.short_commit()),
+                metric.format(*v)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<svg viewBox="0 0 {w} {h}" width="{w}" height="{h}" role="img"><polyline points="{polyline}"/>{dots}</svg>"#,
+        w = WIDTH,
+        h = HEIGHT,
+        polyline = polyline,
+        dots = dots,
+    )
+}
+
 fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -537,6 +744,37 @@ footer a {
     color: #1976d2;
 }
 
+.trend-grid {
+    display: grid;
+    grid-template-columns: repeat(auto-fit, minmax(340px, 1fr));
+    gap: 15px;
+}
+
+.trend-range {
+    color: #666;
+    margin-bottom: 10px;
+}
+
+.trend-chart svg polyline {
+    fill: none;
+    stroke: #1976d2;
+    stroke-width: 2;
+}
+
+.trend-chart svg circle {
+    fill: #1976d2;
+}
+
+.note {
+    color: #666;
+    margin-bottom: 10px;
+}
+
+.size {
+    font-family: monospace;
+    text-align: right;
+}
+
 @media (max-width: 768px) {
     .stats-grid, .type-grid {
         grid-template-columns: repeat(2, 1fr);
@@ -657,4 +895,70 @@ mod tests {
         assert!(output.contains("High: 1"));
         assert!(output.contains("Medium: 1"));
     }
+
+    #[test]
+    fn test_html_trends() {
+        use crate::core::RunMetrics;
+
+        let entry = |issues: usize| {
+            let metrics = RunMetrics {
+                issues,
+                ..Default::default()
+            };
+            HistoryEntry::new(metrics).with_commit("abcdef0123", Some("main".to_string()))
+        };
+
+        let output = HtmlFormatter::new().format(&[]);
+        assert!(!output.contains("<h2>Trends</h2>"));
+
+        let output = HtmlFormatter::new()
+            .with_history(vec![entry(4), entry(2)])
+            .format(&[]);
+        assert!(output.contains("<h2>Trends</h2>"));
+        assert!(output.contains("2 runs"));
+        assert!(output.contains("<polyline points=\"10.0,10.0 310.0,60.0\"/>"));
+        assert!(output.contains("abcdef01"));
+        // Duplication was never measured, so it gets no chart
+        assert!(!output.contains("Duplicated lines"));
+    }
+
+    #[test]
+    fn test_html_footprint() {
+        use crate::footprint::{FeatureFootprint, FileFootprint};
+
+        let output = HtmlFormatter::new().format(&[]);
+        assert!(!output.contains("<h2>Install Size</h2>"));
+
+        let footprint = Footprint {
+            cluster_size: 4096,
+            features: vec![FeatureFootprint {
+                id: "Main".to_string(),
+                title: Some("Application & Tools".to_string()),
+                parent: None,
+                components: 1,
+                files: 1,
+                size: 3 * 1024 * 1024,
+                disk: 3 * 1024 * 1024,
+                total_disk: 3 * 1024 * 1024,
+            }],
+            unassigned: None,
+            files: vec![FileFootprint {
+                id: "AppExe".to_string(),
+                component: Some("App".to_string()),
+                install_path: "ProgramFiles6432Folder\\Acme\\app.exe".to_string(),
+                size: 3 * 1024 * 1024,
+                disk: 3 * 1024 * 1024,
+            }],
+            total_size: 3 * 1024 * 1024,
+            total_disk: 3 * 1024 * 1024,
+            compressed_size: 1024 * 1024,
+            unresolved: 2,
+        };
+        let output = HtmlFormatter::new().with_footprint(footprint).format(&[]);
+        assert!(output.contains("<h2>Install Size</h2>"));
+        assert!(output.contains("<td>Application &amp; Tools</td>"));
+        assert!(output.contains("ProgramFiles6432Folder\\Acme\\app.exe"));
+        assert!(output.contains("3.0 MB"));
+        assert!(output.contains("2 files not found"));
+    }
 }
@@ -0,0 +1,914 @@
+//! Installer payload - what a package installs, resolved from its sources
+//!
+//! `File Source=` values are resolved the way the WiX binder does it:
+//! preprocessor variables first (`$(var.X)` from `<?define?>` or the command
+//! line, `$(env.X)`, `$(sys.SOURCEFILEDIR)`), then `!(bindpath.name)`
+//! prefixes, then relative paths against the source file's directory and
+//! each unnamed bind path in order.
+
+use crate::core::{Location, WixDocument};
+use crate::deps::{Dependency, DependencyAnalyzer};
+use roxmltree::Node;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Resolves authored `Source` paths to files on disk
+#[derive(Debug, Clone, Default)]
+pub struct PayloadResolver {
+    /// (name, directory); unnamed bind paths are searched for relative sources
+    bind_paths: Vec<(Option<String>, PathBuf)>,
+    /// Command-line preprocessor variables
+    variables: HashMap<String, String>,
+}
+
+impl PayloadResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a bind path, `DIR` or `NAME=DIR` as on the wix command line
+    pub fn with_bind_path(mut self, spec: &str) -> Self {
+        let (name, dir) = match spec.split_once('=') {
+            Some((name, dir)) if !name.is_empty() && !name.contains(['\\', '/']) => {
+                (Some(name.to_string()), dir)
+            }
+            _ => (None, spec),
+        };
+        self.bind_paths.push((name, PathBuf::from(dir)));
+        self
+    }
+
+    /// Define a preprocessor variable, `NAME=VALUE` or `NAME`
+    pub fn with_define(mut self, spec: &str) -> Self {
+        let (name, value) = spec.split_once('=').unwrap_or((spec, ""));
+        self.variables
+            .insert(name.trim().to_string(), value.trim().to_string());
+        self
+    }
+
+    /// Expand preprocessor variables; unknown variables are left as written
+    pub fn expand(&self, value: &str, file: &Path, defines: &HashMap<String, String>) -> String {
+        let mut out = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("$(") {
+            out.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find(')') else {
+                rest = &rest[start..];
+                break;
+            };
+            let name = &rest[start + 2..start + end];
+            match self.variable(name, file, defines) {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&rest[start..=start + end]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn variable(
+        &self,
+        name: &str,
+        file: &Path,
+        defines: &HashMap<String, String>,
+    ) -> Option<String> {
+        let (scope, name) = name.split_once('.').unwrap_or(("var", name));
+        match scope {
+            "var" => self
+                .variables
+                .get(name)
+                .or_else(|| defines.get(name))
+                .cloned(),
+            "env" => std::env::var(name).ok(),
+            "sys" => match name {
+                "SOURCEFILEDIR" => Some(format!("{}\\", source_dir(file).display())),
+                "SOURCEFILEPATH" => Some(file.display().to_string()),
+                "CURRENTDIR" => std::env::current_dir()
+                    .ok()
+                    .map(|d| format!("{}\\", d.display())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Find the file an authored source refers to
+    pub fn resolve(
+        &self,
+        source: &str,
+        file: &Path,
+        defines: &HashMap<String, String>,
+    ) -> Option<PathBuf> {
+        let expanded = self.expand(source, file, defines);
+        if expanded.contains("$(") {
+            return None;
+        }
+
+        let mut candidates = Vec::new();
+        if let Some(rest) = expanded.strip_prefix("!(bindpath.") {
+            let (name, relative) = rest.split_once(')')?;
+            let relative = native_path(relative.trim_start_matches(['\\', '/']));
+            for (bind_name, dir) in &self.bind_paths {
+                if bind_name.as_deref() == Some(name) {
+                    candidates.push(dir.join(&relative));
+                }
+            }
+        } else {
+            let path = native_path(&expanded);
+            if is_absolute(&expanded) {
+                candidates.push(path);
+            } else {
+                candidates.push(source_dir(file).join(&path));
+                for (name, dir) in &self.bind_paths {
+                    if name.is_none() {
+                        candidates.push(dir.join(&path));
+                    }
+                }
+            }
+        }
+
+        candidates.into_iter().find(|c| c.is_file())
+    }
+}
+
+/// Directory of a source file, `.` for bare file names
+fn source_dir(file: &Path) -> &Path {
+    file.parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+/// Authored paths use either separator
+fn native_path(path: &str) -> PathBuf {
+    path.split(['\\', '/']).filter(|s| !s.is_empty()).collect()
+}
+
+fn is_absolute(path: &str) -> bool {
+    path.starts_with(['\\', '/'])
+        || (path.len() > 2
+            && path.as_bytes()[1] == b':'
+            && path.as_bytes()[0].is_ascii_alphabetic())
+}
+
+/// Product identity from the Package (v4+) or Product (v3) element
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageInfo {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub manufacturer: Option<String>,
+    /// `None` when generated per build
+    pub product_code: Option<String>,
+    pub upgrade_code: Option<String>,
+    /// Lowest `VersionNT` the launch conditions allow, e.g. 603 for 8.1
+    pub min_version_nt: Option<u32>,
+}
+
+/// A file the package installs
+#[derive(Debug, Clone)]
+pub struct PayloadFile {
+    /// File Id, or the file name when the Id is left to WiX
+    pub id: String,
+    /// Parent Component Id
+    pub component: Option<String>,
+    /// `Source` as authored
+    pub source: String,
+    /// Resolved file on disk
+    pub path: Option<PathBuf>,
+    /// Installed name
+    pub name: String,
+    /// Install directory, e.g. `ProgramFiles6432Folder\Acme\App`
+    pub directory: String,
+    pub location: Location,
+}
+
+impl PayloadFile {
+    /// Install path including the file name
+    pub fn install_path(&self) -> String {
+        if self.directory.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}\\{}", self.directory, self.name)
+        }
+    }
+}
+
+/// A merge module merged into the package
+#[derive(Debug, Clone)]
+pub struct MergeModule {
+    pub id: String,
+    pub source: String,
+    pub path: Option<PathBuf>,
+    pub directory: String,
+    pub location: Location,
+}
+
+/// A feature and the components it installs
+#[derive(Debug, Clone)]
+pub struct PayloadFeature {
+    pub id: String,
+    pub title: Option<String>,
+    /// Id of the enclosing Feature
+    pub parent: Option<String>,
+    /// Components installed by this feature itself, with component groups
+    /// flattened; components of sub-features are not included
+    pub components: Vec<String>,
+    pub location: Location,
+}
+
+/// Everything a package installs or needs
+#[derive(Debug, Clone, Default)]
+pub struct Payload {
+    pub package: PackageInfo,
+    /// The Package or Product element
+    pub package_location: Option<Location>,
+    pub files: Vec<PayloadFile>,
+    pub merge_modules: Vec<MergeModule>,
+    pub features: Vec<PayloadFeature>,
+    /// Prerequisites declared in authoring
+    pub dependencies: Vec<Dependency>,
+}
+
+/// A Directory element: parent Id and name
+struct DirectoryEntry {
+    parent: Option<String>,
+    name: Option<String>,
+}
+
+/// Directory hierarchy declared by a set of documents
+#[derive(Default)]
+pub struct DirectoryTree {
+    directories: HashMap<String, DirectoryEntry>,
+}
+
+impl DirectoryTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the directories a document declares
+    pub fn add_document(&mut self, root: Node) {
+        collect_directories(root, &mut self.directories);
+    }
+
+    /// Whether a document declares the directory
+    pub fn contains(&self, id: &str) -> bool {
+        self.directories.contains_key(id)
+    }
+
+    /// Id of the directory's parent, if it is declared with one
+    pub fn parent(&self, id: &str) -> Option<&str> {
+        self.directories.get(id)?.parent.as_deref()
+    }
+
+    /// Install path of a directory, e.g. `ProgramFiles6432Folder\Acme\App`
+    pub fn install_path(&self, id: &str) -> String {
+        let mut parts = Vec::new();
+        let mut current = Some(id.to_string());
+        let mut seen = 0;
+        while let Some(id) = current {
+            seen += 1;
+            if seen > 64 {
+                break;
+            }
+            match self.directories.get(&id) {
+                Some(entry) => {
+                    if let Some(name) = &entry.name {
+                        parts.push(name.clone());
+                    }
+                    current = entry.parent.clone();
+                }
+                // Undeclared standard directories like ProgramFilesFolder
+                None => {
+                    if id != "TARGETDIR" {
+                        parts.push(id);
+                    }
+                    current = None;
+                }
+            }
+        }
+        parts.reverse();
+        parts.join("\\")
+    }
+}
+
+/// A component reference in a Feature or ComponentGroup
+enum Member {
+    Component(String),
+    Group(String),
+}
+
+impl Payload {
+    /// Load the payload of a set of source files
+    pub fn load(files: &[&Path], resolver: &PayloadResolver) -> Result<Self, String> {
+        let mut sources = Vec::new();
+        for file in files {
+            let source = std::fs::read_to_string(file)
+                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            sources.push((file.to_path_buf(), source));
+        }
+
+        let mut documents = Vec::new();
+        for (file, source) in &sources {
+            let doc = WixDocument::parse(source, file)
+                .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))?;
+            documents.push(doc);
+        }
+
+        // Directories can be declared in any file, so collect them first
+        let mut directories = DirectoryTree::new();
+        for doc in &documents {
+            directories.add_document(doc.root());
+        }
+
+        let mut payload = Payload::default();
+        let mut groups: HashMap<String, Vec<Member>> = HashMap::new();
+        let mut features: Vec<(PayloadFeature, Vec<Member>)> = Vec::new();
+        for doc in &documents {
+            let defines = collect_defines(doc.root());
+            payload.load_document(doc, &defines, &directories, resolver);
+            collect_features(doc, &mut groups, &mut features);
+            for dependency in DependencyAnalyzer::dependencies_from_document(doc.root()) {
+                if !payload
+                    .dependencies
+                    .iter()
+                    .any(|d| d.name == dependency.name && d.version == dependency.version)
+                {
+                    payload.dependencies.push(dependency);
+                }
+            }
+        }
+
+        for (mut feature, members) in features {
+            let mut seen = Vec::new();
+            flatten_members(&members, &groups, &mut feature.components, &mut seen);
+            payload.features.push(feature);
+        }
+        Ok(payload)
+    }
+
+    fn load_document(
+        &mut self,
+        doc: &WixDocument,
+        defines: &HashMap<String, String>,
+        directories: &DirectoryTree,
+        resolver: &PayloadResolver,
+    ) {
+        let file = doc.file();
+        let expand = |value: Option<&str>| value.map(|v| resolver.expand(v, file, defines));
+
+        for node in doc.root().descendants().filter(|n| n.is_element()) {
+            match node.tag_name().name() {
+                "Package" | "Product" => {
+                    let code = if node.tag_name().name() == "Product" {
+                        node.attribute("Id")
+                    } else {
+                        node.attribute("ProductCode")
+                    };
+                    self.package = PackageInfo {
+                        name: expand(node.attribute("Name")),
+                        version: expand(node.attribute("Version")),
+                        manufacturer: expand(node.attribute("Manufacturer")),
+                        product_code: expand(code).filter(|c| c != "*"),
+                        upgrade_code: expand(node.attribute("UpgradeCode")),
+                        min_version_nt: self.package.min_version_nt,
+                    };
+                    self.package_location =
+                        Some(Location::new(file.to_path_buf(), doc.node_range(&node)));
+                }
+                // v4 Launch, v3 Product/Condition
+                "Launch" | "Condition"
+                    if node.parent_element().is_some_and(|p| {
+                        p.has_tag_name("Package") || p.has_tag_name("Product")
+                    }) =>
+                {
+                    let condition = node
+                        .attribute("Condition")
+                        .or_else(|| node.text())
+                        .unwrap_or_default();
+                    if let Some(version) = min_version_nt(condition) {
+                        let current = self.package.min_version_nt.unwrap_or(0);
+                        self.package.min_version_nt = Some(current.max(version));
+                    }
+                }
+                "File" => {
+                    let Some(source) = node.attribute("Source") else {
+                        continue;
+                    };
+                    let component = node
+                        .ancestors()
+                        .find(|a| a.has_tag_name("Component"))
+                        .and_then(|c| component_id(&c));
+                    let expanded = resolver.expand(source, file, defines);
+                    let name = node
+                        .attribute("Name")
+                        .map(|n| long_name(&resolver.expand(n, file, defines)).to_string())
+                        .or_else(|| expanded.rsplit(['\\', '/']).next().map(|n| n.to_string()))
+                        .unwrap_or_default();
+                    self.files.push(PayloadFile {
+                        id: node.attribute("Id").unwrap_or(&name).to_string(),
+                        component,
+                        source: source.to_string(),
+                        path: resolver.resolve(source, file, defines),
+                        directory: install_directory(&node, directories),
+                        name,
+                        location: Location::new(file.to_path_buf(), doc.node_range(&node)),
+                    });
+                }
+                "Merge" => {
+                    let Some(source) = node.attribute("SourceFile") else {
+                        continue;
+                    };
+                    self.merge_modules.push(MergeModule {
+                        id: node.attribute("Id").unwrap_or(source).to_string(),
+                        source: source.to_string(),
+                        path: resolver.resolve(source, file, defines),
+                        directory: install_directory(&node, directories),
+                        location: Location::new(file.to_path_buf(), doc.node_range(&node)),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Files whose source could not be found
+    pub fn unresolved(&self) -> impl Iterator<Item = &PayloadFile> {
+        self.files.iter().filter(|f| f.path.is_none())
+    }
+}
+
+/// Lowest `VersionNT`/`VersionNT64` a launch condition requires, from
+/// comparisons like `VersionNT >= 603` or `Installed OR VersionNT64 > 601`
+pub fn min_version_nt(condition: &str) -> Option<u32> {
+    let mut min = None;
+    let mut rest = condition;
+    while let Some(at) = rest.find("VersionNT") {
+        rest = &rest[at + "VersionNT".len()..];
+        let tail = rest.strip_prefix("64").unwrap_or(rest).trim_start();
+        let (strict, tail) = if let Some(t) = tail.strip_prefix(">=") {
+            (false, t)
+        } else if let Some(t) = tail.strip_prefix('>') {
+            (true, t)
+        } else {
+            continue;
+        };
+        let digits: String = tail
+            .trim_start()
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        if let Ok(version) = digits.parse::<u32>() {
+            let version = if strict { version + 1 } else { version };
+            min = Some(min.map_or(version, |m: u32| m.max(version)));
+        }
+    }
+    min
+}
+
+/// `<?define Name = "Value"?>` instructions of a document
+pub fn collect_defines(root: Node) -> HashMap<String, String> {
+    let mut defines = HashMap::new();
+    for node in root.descendants() {
+        let Some(pi) = node.pi() else {
+            continue;
+        };
+        if pi.target != "define" {
+            continue;
+        }
+        let Some((name, value)) = pi.value.unwrap_or_default().split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        defines.insert(name.trim().to_string(), value.to_string());
+    }
+    defines
+}
+
+fn collect_directories(root: Node, directories: &mut HashMap<String, DirectoryEntry>) {
+    for node in root.descendants().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "Directory" => {
+                let Some(id) = node.attribute("Id") else {
+                    continue;
+                };
+                let parent = node
+                    .ancestors()
+                    .skip(1)
+                    .find(|a| {
+                        matches!(
+                            a.tag_name().name(),
+                            "Directory" | "DirectoryRef" | "StandardDirectory"
+                        )
+                    })
+                    .and_then(|a| a.attribute("Id"))
+                    .map(|p| p.to_string());
+                // Unnamed top-level directories are standard ones (v3 style)
+                let name = match node.attribute("Name") {
+                    Some(name) => Some(long_name(name))
+                        .filter(|n| *n != "." && *n != "SourceDir")
+                        .map(|n| n.to_string()),
+                    None if parent.as_deref().is_none_or(|p| p == "TARGETDIR") => {
+                        Some(id.to_string())
+                    }
+                    None => None,
+                };
+                directories.insert(id.to_string(), DirectoryEntry { parent, name });
+            }
+            "StandardDirectory" => {
+                if let Some(id) = node.attribute("Id") {
+                    directories.insert(
+                        id.to_string(),
+                        DirectoryEntry {
+                            parent: None,
+                            name: Some(id.to_string()),
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Install directory of a File or Merge element
+fn install_directory(node: &Node, directories: &DirectoryTree) -> String {
+    let mut subdirectories = Vec::new();
+    let mut directory = None;
+    for element in node.ancestors().filter(|a| a.is_element()) {
+        if let Some(sub) = element.attribute("Subdirectory") {
+            subdirectories.push(sub.replace('/', "\\"));
+        }
+        let id = match element.tag_name().name() {
+            "File" | "Component" | "Merge" => element.attribute("Directory"),
+            "Directory" | "DirectoryRef" | "StandardDirectory" => element.attribute("Id"),
+            "ComponentGroup" => element.attribute("Directory"),
+            _ => None,
+        };
+        if id.is_some() {
+            directory = id;
+            break;
+        }
+    }
+
+    let mut parts: Vec<String> = directory
+        .map(|d| directories.install_path(d))
+        .filter(|path| !path.is_empty())
+        .into_iter()
+        .collect();
+    parts.extend(subdirectories.into_iter().rev());
+    parts.join("\\")
+}
+
+/// Component Id; WiX derives a missing one from the key file's Id
+fn component_id(node: &Node) -> Option<String> {
+    node.attribute("Id")
+        .or_else(|| {
+            let files: Vec<Node> = node.children().filter(|c| c.has_tag_name("File")).collect();
+            let key = files
+                .iter()
+                .find(|f| f.attribute("KeyPath") == Some("yes"))
+                .or(files.first())?;
+            key.attribute("Id")
+        })
+        .map(|id| id.to_string())
+}
+
+/// Features and component groups of a document, with their direct members.
+/// `FeatureRef` members are added to the referenced feature.
+fn collect_features(
+    doc: &WixDocument,
+    groups: &mut HashMap<String, Vec<Member>>,
+    features: &mut Vec<(PayloadFeature, Vec<Member>)>,
+) {
+    let members = |node: Node| -> Vec<Member> {
+        node.children()
+            .filter(|c| c.is_element())
+            .filter_map(|c| match c.tag_name().name() {
+                "Component" => component_id(&c).map(Member::Component),
+                "ComponentRef" => c
+                    .attribute("Id")
+                    .map(|id| Member::Component(id.to_string())),
+                "ComponentGroupRef" => c.attribute("Id").map(|id| Member::Group(id.to_string())),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let mut refs = Vec::new();
+    for node in doc.root().descendants().filter(|n| n.is_element()) {
+        let Some(id) = node.attribute("Id") else {
+            continue;
+        };
+        match node.tag_name().name() {
+            "ComponentGroup" => groups
+                .entry(id.to_string())
+                .or_default()
+                .extend(members(node)),
+            "Feature" => {
+                let parent = node
+                    .ancestors()
+                    .skip(1)
+                    .find(|a| a.has_tag_name("Feature") || a.has_tag_name("FeatureRef"))
+                    .and_then(|a| a.attribute("Id"))
+                    .map(|p| p.to_string());
+                let feature = PayloadFeature {
+                    id: id.to_string(),
+                    title: node.attribute("Title").map(|t| t.to_string()),
+                    parent,
+                    components: Vec::new(),
+                    location: Location::new(doc.file().to_path_buf(), doc.node_range(&node)),
+                };
+                features.push((feature, members(node)));
+            }
+            "FeatureRef" => refs.push((id.to_string(), members(node))),
+            _ => {}
+        }
+    }
+    for (id, extra) in refs {
+        if let Some((_, members)) = features.iter_mut().find(|(f, _)| f.id == id) {
+            members.extend(extra);
+        }
+    }
+}
+
+/// Component Ids of a member list with groups expanded, in order and
+/// without duplicates
+fn flatten_members(
+    members: &[Member],
+    groups: &HashMap<String, Vec<Member>>,
+    components: &mut Vec<String>,
+    seen: &mut Vec<String>,
+) {
+    for member in members {
+        match member {
+            Member::Component(id) => {
+                if !components.contains(id) {
+                    components.push(id.clone());
+                }
+            }
+            Member::Group(id) => {
+                if seen.contains(id) {
+                    continue;
+                }
+                seen.push(id.clone());
+                if let Some(members) = groups.get(id) {
+                    flatten_members(members, groups, components, seen);
+                }
+            }
+        }
+    }
+}
+
+/// Long name of a `short|long` pair
+fn long_name(name: &str) -> &str {
+    name.rsplit('|').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let resolver = PayloadResolver::new().with_define("Config=Release");
+        let defines = HashMap::from([("Config".to_string(), "Debug".to_string())]);
+        let file = Path::new("src/Product.wxs");
+
+        // Command line wins over <?define?>
+        assert_eq!(
+            resolver.expand("bin\\$(var.Config)\\app.exe", file, &defines),
+            "bin\\Release\\app.exe"
+        );
+        assert_eq!(
+            resolver.expand("$(sys.SOURCEFILEDIR)a.dll", file, &defines),
+            "src\\a.dll"
+        );
+        assert_eq!(
+            resolver.expand("$(var.Missing)\\a.dll", file, &defines),
+            "$(var.Missing)\\a.dll"
+        );
+    }
+
+    #[test]
+    fn test_resolve_bind_paths() {
+        let temp = TempDir::new().unwrap();
+        let wxs = write(temp.path(), "src/Product.wxs", "<Wix />");
+        let app = write(temp.path(), "bin/app.exe", "");
+        let tool = write(temp.path(), "tools/x64/tool.exe", "");
+        let local = write(temp.path(), "src/local.txt", "");
+
+        let resolver = PayloadResolver::new()
+            .with_bind_path(&temp.path().join("bin").display().to_string())
+            .with_bind_path(&format!("tools={}", temp.path().join("tools").display()));
+        let none = HashMap::new();
+
+        assert_eq!(resolver.resolve("local.txt", &wxs, &none), Some(local));
+        assert_eq!(resolver.resolve("app.exe", &wxs, &none), Some(app));
+        assert_eq!(
+            resolver.resolve("!(bindpath.tools)\\x64\\tool.exe", &wxs, &none),
+            Some(tool)
+        );
+        assert_eq!(resolver.resolve("missing.exe", &wxs, &none), None);
+        assert_eq!(resolver.resolve("$(var.Dir)\\app.exe", &wxs, &none), None);
+    }
+
+    #[test]
+    fn test_load_payload() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "bin/app.exe", "MZ");
+        let product = write(
+            temp.path(),
+            "Product.wxs",
+            r#"<?define Bin = "bin"?>
+<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+  <Package Name="Acme App" Version="1.2.0" Manufacturer="Acme"
+           UpgradeCode="{B7E6A1B2-0000-4000-8000-000000000001}">
+    <StandardDirectory Id="ProgramFiles6432Folder">
+      <Directory Id="Company" Name="Acme">
+        <Directory Id="INSTALLFOLDER" Name="App" />
+      </Directory>
+    </StandardDirectory>
+    <Merge Id="VCRedist" SourceFile="Microsoft_VC143_CRT_x64.msm" Directory="INSTALLFOLDER" />
+  </Package>
+</Wix>"#,
+        );
+        let files = write(
+            temp.path(),
+            "Files.wxs",
+            r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+  <Fragment>
+    <ComponentGroup Id="Main" Directory="INSTALLFOLDER">
+      <Component Id="App">
+        <File Id="AppExe" Source="$(var.Bin)\app.exe" />
+      </Component>
+      <Component Id="Doc" Subdirectory="docs">
+        <File Source="readme.txt" Name="README.txt" />
+      </Component>
+    </ComponentGroup>
+  </Fragment>
+</Wix>"#,
+        );
+
+        // Defines are per file, so Files.wxs needs it on the command line
+        let resolver = PayloadResolver::new().with_define("Bin=bin");
+        let payload = Payload::load(&[&product, &files], &resolver).unwrap();
+
+        assert_eq!(payload.package.name.as_deref(), Some("Acme App"));
+        assert_eq!(payload.package.product_code, None);
+        assert!(payload.package.upgrade_code.is_some());
+        assert_eq!(payload.files.len(), 2);
+
+        let app = &payload.files[0];
+        assert_eq!(app.id, "AppExe");
+        assert_eq!(app.component.as_deref(), Some("App"));
+        assert_eq!(
+            app.install_path(),
+            "ProgramFiles6432Folder\\Acme\\App\\app.exe"
+        );
+        assert!(app.path.is_some());
+
+        let readme = &payload.files[1];
+        assert_eq!(readme.id, "README.txt");
+        assert_eq!(
+            readme.install_path(),
+            "ProgramFiles6432Folder\\Acme\\App\\docs\\README.txt"
+        );
+        assert_eq!(payload.unresolved().count(), 1);
+
+        assert_eq!(payload.merge_modules.len(), 1);
+        assert_eq!(
+            payload.merge_modules[0].directory,
+            "ProgramFiles6432Folder\\Acme\\App"
+        );
+        assert!(payload
+            .dependencies
+            .iter()
+            .any(|d| d.name == "VCRedist" && d.bundled));
+    }
+
+    #[test]
+    fn test_min_version_nt() {
+        assert_eq!(min_version_nt("Installed OR VersionNT >= 603"), Some(603));
+        assert_eq!(min_version_nt("VersionNT64 > 601"), Some(602));
+        assert_eq!(min_version_nt("VersionNT<601"), None);
+        assert_eq!(min_version_nt("NOT VersionNT"), None);
+    }
+
+    #[test]
+    fn test_v3_directory_tree() {
+        let temp = TempDir::new().unwrap();
+        let wxs = write(
+            temp.path(),
+            "Product.wxs",
+            r#"<Wix><Product Id="{11111111-2222-3333-4444-555555555555}" Name="Old" Version="1.0">
+  <Condition Message="Windows 7 or later is required">Installed OR VersionNT &gt;= 601</Condition>
+  <Directory Id="TARGETDIR" Name="SourceDir">
+    <Directory Id="ProgramFilesFolder">
+      <Directory Id="INSTALLDIR" Name="ACME|Acme Tools">
+        <Component Id="C1"><File Id="F1" Source="tool.exe" /></Component>
+      </Directory>
+    </Directory>
+  </Directory>
+</Product></Wix>"#,
+        );
+        let payload = Payload::load(&[&wxs], &PayloadResolver::new()).unwrap();
+        assert_eq!(
+            payload.package.product_code.as_deref(),
+            Some("{11111111-2222-3333-4444-555555555555}")
+        );
+        assert_eq!(payload.package.min_version_nt, Some(601));
+        assert!(payload.package_location.is_some());
+        assert_eq!(
+            payload.files[0].install_path(),
+            "ProgramFilesFolder\\Acme Tools\\tool.exe"
+        );
+    }
+
+    #[test]
+    fn test_directory_tree_across_documents() {
+        let product = roxmltree::Document::parse(
+            r#"<Wix><Package><StandardDirectory Id="ProgramFiles6432Folder">
+  <Directory Id="INSTALLFOLDER" Name="Acme" />
+</StandardDirectory></Package></Wix>"#,
+        )
+        .unwrap();
+        let fragment = roxmltree::Document::parse(
+            r#"<Wix><Fragment><DirectoryRef Id="INSTALLFOLDER">
+  <Directory Id="BinFolder" Name="bin" />
+</DirectoryRef></Fragment></Wix>"#,
+        )
+        .unwrap();
+
+        let mut tree = DirectoryTree::new();
+        tree.add_document(fragment.root());
+        assert_eq!(tree.parent("BinFolder"), Some("INSTALLFOLDER"));
+        assert!(!tree.contains("INSTALLFOLDER"));
+        assert_eq!(tree.install_path("BinFolder"), "INSTALLFOLDER\\bin");
+
+        tree.add_document(product.root());
+        assert!(tree.contains("INSTALLFOLDER"));
+        assert_eq!(
+            tree.install_path("BinFolder"),
+            "ProgramFiles6432Folder\\Acme\\bin"
+        );
+    }
+
+    #[test]
+    fn test_features() {
+        let temp = TempDir::new().unwrap();
+        let wxs = write(
+            temp.path(),
+            "Product.wxs",
+            r#"<Wix xmlns="http://wixtoolset.org/schemas/v4/wxs">
+  <Package Name="P" Version="1.0" Manufacturer="M">
+    <Feature Id="Main" Title="Application">
+      <ComponentGroupRef Id="Core" />
+      <Feature Id="Docs">
+        <ComponentRef Id="Manual" />
+      </Feature>
+    </Feature>
+  </Package>
+  <Fragment>
+    <ComponentGroup Id="Core" Directory="INSTALLFOLDER">
+      <Component><File Id="AppExe" Source="app.exe" /></Component>
+      <ComponentGroupRef Id="Shared" />
+    </ComponentGroup>
+    <ComponentGroup Id="Shared">
+      <Component Id="Lib"><File Source="lib.dll" /></Component>
+      <ComponentGroupRef Id="Core" />
+    </ComponentGroup>
+    <FeatureRef Id="Docs">
+      <Component Id="Manual"><File Source="manual.pdf" /></Component>
+      <Component Id="Help"><File Source="help.chm" /></Component>
+    </FeatureRef>
+  </Fragment>
+</Wix>"#,
+        );
+        let payload = Payload::load(&[&wxs], &PayloadResolver::new()).unwrap();
+
+        let main = &payload.features[0];
+        assert_eq!(main.id, "Main");
+        assert_eq!(main.title.as_deref(), Some("Application"));
+        assert_eq!(main.parent, None);
+        // Unnamed components take the Id of their key file
+        assert_eq!(main.components, ["AppExe", "Lib"]);
+        assert_eq!(payload.files[0].component.as_deref(), Some("AppExe"));
+
+        let docs = &payload.features[1];
+        assert_eq!(docs.parent.as_deref(), Some("Main"));
+        assert_eq!(docs.components, ["Manual", "Help"]);
+    }
+}
@@ -0,0 +1,614 @@
+//! Portable Executable reader for payload binaries
+//!
+//! Describes a PE image for the SBOM and the runtime checks: the target
+//! machine, the version resource, imported and delay-loaded DLLs, and the CLR
+//! header of managed assemblies. Headers, resources and the import table are
+//! read with `goblin` in permissive mode, so malformed payloads come back as
+//! errors or missing data rather than panics. goblin does not read the
+//! delay-load table, so its descriptors are walked here.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use goblin::pe::clr::{Cor20Header, COMIMAGE_FLAGS_32BITREQUIRED, COMIMAGE_FLAGS_ILONLY};
+use goblin::pe::header::{
+    COFF_MACHINE_ARM64, COFF_MACHINE_ARMNT, COFF_MACHINE_X86, COFF_MACHINE_X86_64,
+};
+use goblin::pe::options::{ParseMode, ParseOptions};
+use goblin::pe::resource::ResourceData;
+use goblin::pe::{utils, PE};
+
+/// Descriptors read from the delay-load table at most, against corrupt images
+const MAX_DELAY_IMPORTS: usize = 4096;
+
+/// Target architecture of an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Machine {
+    X86,
+    X64,
+    Arm,
+    Arm64,
+    Unknown(u16),
+}
+
+impl Machine {
+    fn from_u16(value: u16) -> Self {
+        match value {
+            COFF_MACHINE_X86 => Machine::X86,
+            COFF_MACHINE_X86_64 => Machine::X64,
+            COFF_MACHINE_ARMNT => Machine::Arm,
+            COFF_MACHINE_ARM64 => Machine::Arm64,
+            other => Machine::Unknown(other),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Machine::X86 => "x86",
+            Machine::X64 => "x64",
+            Machine::Arm => "arm",
+            Machine::Arm64 => "arm64",
+            Machine::Unknown(_) => "unknown",
+        }
+    }
+}
+
+/// Error reading a PE image
+#[derive(Debug)]
+pub enum PeError {
+    /// No MZ signature: not an executable image
+    NotPe,
+    /// The image is truncated or its headers are inconsistent
+    Malformed(goblin::error::Error),
+}
+
+impl fmt::Display for PeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeError::NotPe => write!(f, "Not a PE image"),
+            PeError::Malformed(e) => write!(f, "Malformed PE image: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PeError::NotPe => None,
+            PeError::Malformed(e) => Some(e),
+        }
+    }
+}
+
+/// Version resource of an image
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// From VS_FIXEDFILEINFO, e.g. "1.2.3.4"
+    pub file_version: Option<String>,
+    pub product_version: Option<String>,
+    /// StringFileInfo values (CompanyName, ...)
+    pub strings: BTreeMap<String, String>,
+}
+
+impl VersionInfo {
+    /// Display version: the FileVersion string, else the fixed file version
+    pub fn version(&self) -> Option<&str> {
+        self.strings
+            .get("FileVersion")
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .or(self.file_version.as_deref())
+    }
+}
+
+/// A DLL an image imports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// DLL name as written in the image, e.g. `KERNEL32.dll`
+    pub dll: String,
+    /// Loaded on first call rather than at startup
+    pub delay_loaded: bool,
+}
+
+/// A parsed PE image borrowing its data
+#[derive(Debug)]
+pub struct PeFile<'a> {
+    data: &'a [u8],
+    pe: PE<'a>,
+    options: ParseOptions,
+}
+
+impl<'a> PeFile<'a> {
+    /// Parse the headers and import table of an image
+    pub fn parse(data: &'a [u8]) -> Result<Self, PeError> {
+        if data.get(..2) != Some(b"MZ") {
+            return Err(PeError::NotPe);
+        }
+        // Resources are parsed on demand by `version_info`
+        let mut options = ParseOptions::default()
+            .with_parse_mode(ParseMode::Permissive)
+            .with_parse_resources(false)
+            .with_parse_tls_data(false);
+        options.parse_attribute_certificates = false;
+        let pe = PE::parse_with_opts(data, &options).map_err(|e| match e {
+            goblin::error::Error::BadMagic(_) => PeError::NotPe,
+            e => PeError::Malformed(e),
+        })?;
+        Ok(Self { data, pe, options })
+    }
+
+    pub fn machine(&self) -> Machine {
+        Machine::from_u16(self.pe.header.coff_header.machine)
+    }
+
+    pub fn is_64bit(&self) -> bool {
+        self.pe.is_64
+    }
+
+    /// CLR header flags; `None` for native images
+    pub fn clr_flags(&self) -> Option<u32> {
+        let directory = *self.directories()?.get_clr_runtime_header()?;
+        let header: Cor20Header = utils::get_data_with_opts(
+            self.data,
+            &self.pe.sections,
+            directory,
+            self.file_alignment(),
+            &self.options,
+        )
+        .ok()?;
+        Some(header.flags)
+    }
+
+    pub fn is_managed(&self) -> bool {
+        self.clr_flags().is_some()
+    }
+
+    /// Managed IL-only image that runs as 32- or 64-bit (AnyCPU)
+    pub fn is_any_cpu(&self) -> bool {
+        self.clr_flags().is_some_and(|f| {
+            f & COMIMAGE_FLAGS_ILONLY != 0 && f & COMIMAGE_FLAGS_32BITREQUIRED == 0
+        })
+    }
+
+    /// Imported and delay-loaded DLLs, in table order
+    pub fn imports(&self) -> Vec<Import> {
+        let mut imports: Vec<Import> = self
+            .pe
+            .import_data
+            .iter()
+            .flat_map(|data| &data.import_data)
+            .filter(|entry| !entry.name.is_empty())
+            .map(|entry| Import {
+                dll: entry.name.to_string(),
+                delay_loaded: false,
+            })
+            .collect();
+        imports.extend(self.delay_imports().into_iter().map(|dll| Import {
+            dll,
+            delay_loaded: true,
+        }));
+        imports
+    }
+
+    /// DLL names of the delay-load table
+    fn delay_imports(&self) -> Vec<String> {
+        let mut dlls = Vec::new();
+        let Some(directory) = self
+            .directories()
+            .and_then(|d| d.get_delay_import_descriptor())
+        else {
+            return dlls;
+        };
+        let Some(table) = self.offset(directory.virtual_address) else {
+            return dlls;
+        };
+
+        // ImgDelayDescr: 32 bytes, Attributes at 0, DllNameRVA at 4
+        for i in 0..MAX_DELAY_IMPORTS {
+            let entry = table + i * 32;
+            let (Some(attributes), Some(name)) =
+                (read_u32(self.data, entry), read_u32(self.data, entry + 4))
+            else {
+                break;
+            };
+            if name == 0 {
+                break;
+            }
+            // Old-style descriptors hold virtual addresses, not RVAs
+            let rva = if attributes & 1 == 0 {
+                (name as u64)
+                    .checked_sub(self.pe.image_base)
+                    .and_then(|r| u32::try_from(r).ok())
+            } else {
+                Some(name)
+            };
+            let dll = rva.and_then(|rva| {
+                utils::try_name(
+                    self.data,
+                    rva as usize,
+                    &self.pe.sections,
+                    self.file_alignment(),
+                    &self.options,
+                )
+                .ok()
+            });
+            if let Some(dll) = dll.filter(|dll| !dll.is_empty()) {
+                dlls.push(dll.to_string());
+            }
+        }
+        dlls
+    }
+
+    /// The version resource, if the image has one
+    pub fn version_info(&self) -> Option<VersionInfo> {
+        let directory = *self.directories()?.get_resource_table()?;
+        let resources = ResourceData::parse_with_opts(
+            self.data,
+            directory,
+            &self.pe.sections,
+            self.file_alignment(),
+            &self.options,
+        )
+        .ok()?;
+        let version = resources.version_info?;
+
+        let mut info = VersionInfo::default();
+        if let Some(fixed) = version.fixed_info {
+            let format = |ms: u32, ls: u32| {
+                format!("{}.{}.{}.{}", ms >> 16, ms & 0xffff, ls >> 16, ls & 0xffff)
+            };
+            info.file_version = Some(format(fixed.file_version_ms, fixed.file_version_ls));
+            info.product_version = Some(format(fixed.product_version_ms, fixed.product_version_ls));
+        }
+
+        let strings = &version.string_info;
+        for (key, value) in [
+            ("Comments", strings.comments()),
+            ("CompanyName", strings.company_name()),
+            ("FileDescription", strings.file_description()),
+            ("FileVersion", strings.file_version()),
+            ("InternalName", strings.internal_name()),
+            ("LegalCopyright", strings.legal_copyright()),
+            ("LegalTrademarks", strings.legal_trademarks()),
+            ("OriginalFilename", strings.original_filename()),
+            ("PrivateBuild", strings.private_build()),
+            ("ProductName", strings.product_name()),
+            ("ProductVersion", strings.product_version()),
+            ("SpecialBuild", strings.special_build()),
+        ] {
+            if let Some(value) = value {
+                info.strings.insert(key.to_string(), value);
+            }
+        }
+
+        Some(info)
+    }
+
+    fn directories(&self) -> Option<&goblin::pe::data_directories::DataDirectories> {
+        self.pe
+            .header
+            .optional_header
+            .as_ref()
+            .map(|h| &h.data_directories)
+    }
+
+    fn file_alignment(&self) -> u32 {
+        self.pe
+            .header
+            .optional_header
+            .as_ref()
+            .map_or(0, |h| h.windows_fields.file_alignment)
+    }
+
+    /// File offset of a relative virtual address
+    fn offset(&self, rva: u32) -> Option<usize> {
+        utils::find_offset(
+            rva as usize,
+            &self.pe.sections,
+            self.file_alignment(),
+            &self.options,
+        )
+    }
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(at..at.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use goblin::pe::resource::{RT_VERSION, VS_FFI_SIGNATURE};
+
+    /// Data directory index of the import table
+    const DIR_IMPORT: usize = 1;
+    /// Data directory index of the resource table
+    const DIR_RESOURCE: usize = 2;
+    /// Data directory index of the delay-load import table
+    const DIR_DELAY_IMPORT: usize = 13;
+    /// Data directory index of the CLR runtime header
+    const DIR_CLR: usize = 14;
+
+    fn utf16(text: &str) -> Vec<u8> {
+        text.encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|u| u.to_le_bytes())
+            .collect()
+    }
+
+    fn pad4(data: &mut Vec<u8>) {
+        while !data.len().is_multiple_of(4) {
+            data.push(0);
+        }
+    }
+
+    /// Serialize a VS_VERSIONINFO-style block
+    fn block(key: &str, value: &[u8], text: bool, children: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![0; 6];
+        data.extend(utf16(key));
+        pad4(&mut data);
+        data.extend(value);
+        for child in children {
+            pad4(&mut data);
+            data.extend(child);
+        }
+        let length = data.len() as u16;
+        let value_length = if text { value.len() / 2 } else { value.len() } as u16;
+        data[0..2].copy_from_slice(&length.to_le_bytes());
+        data[2..4].copy_from_slice(&value_length.to_le_bytes());
+        data[4..6].copy_from_slice(&(text as u16).to_le_bytes());
+        data
+    }
+
+    pub(crate) fn version_resource(version: [u16; 4], strings: &[(&str, &str)]) -> Vec<u8> {
+        let mut fixed = Vec::new();
+        for value in [
+            VS_FFI_SIGNATURE,
+            0x0001_0000,
+            ((version[0] as u32) << 16) | version[1] as u32,
+            ((version[2] as u32) << 16) | version[3] as u32,
+            ((version[0] as u32) << 16) | version[1] as u32,
+            ((version[2] as u32) << 16) | version[3] as u32,
+        ] {
+            fixed.extend(value.to_le_bytes());
+        }
+        fixed.resize(52, 0);
+
+        let strings: Vec<Vec<u8>> = strings
+            .iter()
+            .map(|(key, value)| block(key, &utf16(value), true, &[]))
+            .collect();
+        let table = block("040904b0", &[], true, &strings);
+        let string_info = block("StringFileInfo", &[], true, &[table]);
+        block("VS_VERSION_INFO", &fixed, false, &[string_info])
+    }
+
+    /// A minimal image with one section holding `section` at RVA 0x1000;
+    /// `directories` are (index, rva, size) entries
+    pub(crate) fn image(
+        machine: u16,
+        pe32_plus: bool,
+        directories: &[(usize, u32, u32)],
+        section: &[u8],
+    ) -> Vec<u8> {
+        let mut data = vec![0u8; 0x40];
+        data[0..2].copy_from_slice(b"MZ");
+        data[0x3c..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+        data.extend(b"PE\0\0");
+
+        let optional_size: u16 = if pe32_plus { 240 } else { 224 };
+        data.extend(machine.to_le_bytes());
+        data.extend(1u16.to_le_bytes());
+        data.extend([0; 12]);
+        data.extend(optional_size.to_le_bytes());
+        data.extend(0x2022u16.to_le_bytes());
+
+        let optional = data.len();
+        data.resize(optional + optional_size as usize, 0);
+        let magic: u16 = if pe32_plus { 0x20b } else { 0x10b };
+        data[optional..optional + 2].copy_from_slice(&magic.to_le_bytes());
+        // SectionAlignment and FileAlignment
+        data[optional + 32..optional + 36].copy_from_slice(&0x1000u32.to_le_bytes());
+        data[optional + 36..optional + 40].copy_from_slice(&0x200u32.to_le_bytes());
+        let directories_at = optional + if pe32_plus { 112 } else { 96 };
+        data[directories_at - 4..directories_at].copy_from_slice(&16u32.to_le_bytes());
+        for &(index, rva, size) in directories {
+            let at = directories_at + index * 8;
+            data[at..at + 4].copy_from_slice(&rva.to_le_bytes());
+            data[at + 4..at + 8].copy_from_slice(&size.to_le_bytes());
+        }
+
+        let raw_offset = 0x400u32;
+        let mut header = [0u8; 40];
+        header[..5].copy_from_slice(b".data");
+        header[8..12].copy_from_slice(&(section.len() as u32).to_le_bytes());
+        header[12..16].copy_from_slice(&0x1000u32.to_le_bytes());
+        header[16..20].copy_from_slice(&(section.len() as u32).to_le_bytes());
+        header[20..24].copy_from_slice(&raw_offset.to_le_bytes());
+        data.extend(header);
+
+        data.resize(raw_offset as usize, 0);
+        data.extend(section);
+        data
+    }
+
+    /// A resource section holding one version resource
+    pub(crate) fn resource_section(version: &[u8]) -> Vec<u8> {
+        let directory = |entry_id: u32, target: u32| {
+            let mut dir = vec![0u8; 16];
+            dir[14..16].copy_from_slice(&1u16.to_le_bytes());
+            dir.extend(entry_id.to_le_bytes());
+            dir.extend(target.to_le_bytes());
+            dir
+        };
+        let mut section = Vec::new();
+        section.extend(directory(RT_VERSION as u32, 0x8000_0018));
+        section.extend(directory(1, 0x8000_0030));
+        section.extend(directory(0x409, 0x48));
+        section.extend((0x1000u32 + 0x58).to_le_bytes());
+        section.extend((version.len() as u32).to_le_bytes());
+        section.extend([0; 8]);
+        section.extend(version);
+        section
+    }
+
+    /// An import section at RVA 0x1000 with regular and delay-load tables;
+    /// returns the section and the (import, delay import) directories
+    pub(crate) fn import_section(
+        dlls: &[&str],
+        delayed: &[&str],
+    ) -> (Vec<u8>, [(usize, u32, u32); 2]) {
+        let imports_size = (dlls.len() + 1) * 20;
+        let delayed_at = imports_size;
+        let delayed_size = (delayed.len() + 1) * 32;
+        let mut names = Vec::new();
+        let mut section = vec![0u8; imports_size + delayed_size];
+
+        let mut name_rva = |name: &str| {
+            let rva = 0x1000 + (imports_size + delayed_size + names.len()) as u32;
+            names.extend(name.as_bytes());
+            names.push(0);
+            rva
+        };
+        // Every import address table is the zeroed terminating descriptor
+        let empty_thunks = 0x1000 + (dlls.len() * 20) as u32;
+        for (i, dll) in dlls.iter().enumerate() {
+            let at = i * 20 + 12;
+            section[at..at + 4].copy_from_slice(&name_rva(dll).to_le_bytes());
+            section[at + 4..at + 8].copy_from_slice(&empty_thunks.to_le_bytes());
+        }
+        for (i, dll) in delayed.iter().enumerate() {
+            let at = delayed_at + i * 32;
+            section[at..at + 4].copy_from_slice(&1u32.to_le_bytes());
+            section[at + 4..at + 8].copy_from_slice(&name_rva(dll).to_le_bytes());
+        }
+        section.extend(names);
+
+        let directories = [
+            (DIR_IMPORT, 0x1000, imports_size as u32),
+            (
+                DIR_DELAY_IMPORT,
+                0x1000 + delayed_at as u32,
+                delayed_size as u32,
+            ),
+        ];
+        (section, directories)
+    }
+
+    #[test]
+    fn test_not_pe() {
+        assert!(matches!(PeFile::parse(b"hello"), Err(PeError::NotPe)));
+        let mut data = image(0x8664, true, &[], &[]);
+        data[0x40] = b'X';
+        assert!(PeFile::parse(&data).is_err());
+
+        let data = image(0x8664, true, &[], &[]);
+        assert!(matches!(
+            PeFile::parse(&data[..0x50]),
+            Err(PeError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_machine_and_format() {
+        let data = image(0x014c, false, &[], &[0; 16]);
+        let pe = PeFile::parse(&data).unwrap();
+        assert_eq!(pe.machine(), Machine::X86);
+        assert!(!pe.is_64bit());
+        assert!(pe.version_info().is_none());
+
+        let data = image(0xaa64, true, &[], &[0; 16]);
+        let pe = PeFile::parse(&data).unwrap();
+        assert_eq!(pe.machine().as_str(), "arm64");
+        assert!(pe.is_64bit());
+    }
+
+    #[test]
+    fn test_version_info() {
+        let version = version_resource(
+            [2, 5, 0, 17],
+            &[
+                ("CompanyName", "Acme"),
+                ("FileVersion", "2.5.0.17 (release)"),
+            ],
+        );
+        let section = resource_section(&version);
+        let data = image(
+            0x8664,
+            true,
+            &[(DIR_RESOURCE, 0x1000, section.len() as u32)],
+            &section,
+        );
+
+        let info = PeFile::parse(&data).unwrap().version_info().unwrap();
+        assert_eq!(info.file_version.as_deref(), Some("2.5.0.17"));
+        assert_eq!(info.product_version.as_deref(), Some("2.5.0.17"));
+        assert_eq!(info.strings.get("CompanyName").unwrap(), "Acme");
+        assert_eq!(info.version(), Some("2.5.0.17 (release)"));
+    }
+
+    #[test]
+    fn test_corrupt_resources_are_ignored() {
+        let version = version_resource([1, 0, 0, 0], &[]);
+        let mut section = resource_section(&version);
+        // Data entry pointing past the end of the image
+        section[0x48..0x4c].copy_from_slice(&0xffff_0000u32.to_le_bytes());
+        let data = image(
+            0x8664,
+            true,
+            &[(DIR_RESOURCE, 0x1000, section.len() as u32)],
+            &section,
+        );
+        assert!(PeFile::parse(&data).unwrap().version_info().is_none());
+    }
+
+    #[test]
+    fn test_imports() {
+        let (section, directories) =
+            import_section(&["KERNEL32.dll", "VCRUNTIME140.dll"], &["dbghelp.dll"]);
+        let data = image(0x8664, true, &directories, &section);
+        let pe = PeFile::parse(&data).unwrap();
+
+        let imports = pe.imports();
+        assert_eq!(imports.len(), 3);
+        assert_eq!(imports[0].dll, "KERNEL32.dll");
+        assert!(!imports[1].delay_loaded);
+        assert_eq!(
+            imports[2],
+            Import {
+                dll: "dbghelp.dll".to_string(),
+                delay_loaded: true
+            }
+        );
+        assert!(!pe.is_managed());
+    }
+
+    #[test]
+    fn test_section_offset_overflow() {
+        let (section, directories) = import_section(&["KERNEL32.dll"], &[]);
+        let mut data = image(0x8664, true, &directories, &section);
+        // PointerToRawData of the only section header, just below u32::MAX
+        let header = 0x40 + 4 + 20 + 240;
+        data[header + 20..header + 24].copy_from_slice(&0xffff_fff0u32.to_le_bytes());
+
+        let pe = PeFile::parse(&data).unwrap();
+        assert!(pe.imports().is_empty());
+    }
+
+    #[test]
+    fn test_clr_header() {
+        let mut section = vec![0u8; 72];
+        section[16..20].copy_from_slice(&COMIMAGE_FLAGS_ILONLY.to_le_bytes());
+        let data = image(0x014c, false, &[(DIR_CLR, 0x1000, 72)], &section);
+        let pe = PeFile::parse(&data).unwrap();
+        assert!(pe.is_managed());
+        assert!(pe.is_any_cpu());
+
+        section[16..20]
+            .copy_from_slice(&(COMIMAGE_FLAGS_ILONLY | COMIMAGE_FLAGS_32BITREQUIRED).to_le_bytes());
+        let data = image(0x014c, false, &[(DIR_CLR, 0x1000, 72)], &section);
+        assert!(!PeFile::parse(&data).unwrap().is_any_cpu());
+    }
+}
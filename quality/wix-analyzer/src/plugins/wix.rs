@@ -630,10 +630,24 @@ impl WixNode {
 
     /// Build a node tree from roxmltree document
     pub fn from_roxmltree(source: &str, doc: &roxmltree::Document) -> Self {
-        Self::from_roxmltree_node(source, doc.root(), None)
+        Self::from_node(source, doc.root())
     }
 
-    fn from_roxmltree_node(source: &str, node: roxmltree::Node, parent_kind: Option<&str>) -> Self {
+    /// Build a node tree from a roxmltree node and its element descendants
+    pub fn from_node(source: &str, node: roxmltree::Node) -> Self {
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let parent_kind = node.parent_element().map(|p| p.tag_name().name());
+        Self::from_roxmltree_node(source, &line_starts, node, parent_kind)
+    }
+
+    fn from_roxmltree_node(
+        source: &str,
+        line_starts: &[usize],
+        node: roxmltree::Node,
+        parent_kind: Option<&str>,
+    ) -> Self {
         let kind = if node.is_element() {
             node.tag_name().name().to_string()
         } else if node.is_text() {
@@ -648,8 +662,8 @@ impl WixNode {
 
         let range = {
             let r = node.range();
-            let (start_line, start_col) = offset_to_line_col(source, r.start);
-            let (end_line, end_col) = offset_to_line_col(source, r.end);
+            let (start_line, start_col) = offset_to_line_col(source, line_starts, r.start);
+            let (end_line, end_col) = offset_to_line_col(source, line_starts, r.end);
             (start_line, start_col, end_line, end_col)
         };
 
@@ -662,7 +676,7 @@ impl WixNode {
         let children = node
             .children()
             .filter(|c| c.is_element())
-            .map(|c| Self::from_roxmltree_node(source, c, Some(&current_kind)))
+            .map(|c| Self::from_roxmltree_node(source, line_starts, c, Some(&current_kind)))
             .collect();
 
         Self {
@@ -678,21 +692,15 @@ impl WixNode {
     }
 }
 
-fn offset_to_line_col(source: &str, offset: usize) -> (usize, usize) {
-    let mut line = 1;
-    let mut col = 1;
-    for (i, ch) in source.char_indices() {
-        if i >= offset {
-            break;
-        }
-        if ch == '\n' {
-            line += 1;
-            col = 1;
-        } else {
-            col += 1;
-        }
-    }
-    (line, col)
+/// 1-based line and column (in characters) of a byte offset
+fn offset_to_line_col(source: &str, line_starts: &[usize], offset: usize) -> (usize, usize) {
+    let line = line_starts.partition_point(|&start| start <= offset).max(1);
+    let start = line_starts[line - 1];
+    let col = source
+        .get(start..offset.min(source.len()))
+        .map(|s| s.chars().count())
+        .unwrap_or(0);
+    (line, col + 1)
 }
 
 /// A phantom parent node that only stores the kind (for parent validation)
@@ -0,0 +1,662 @@
+//! Release checks - component rules against a previously shipped release
+//!
+//! Windows Installer component rules can only be checked by comparing with
+//! what was shipped before: a component GUID must keep its key path and
+//! resources forever, components cannot disappear in a minor upgrade, and the
+//! feature tree must stay put. The previous release is loaded either from the
+//! MSI itself or from its sources, then matched with the current sources by
+//! component GUID and Id.
+
+mod msi;
+mod source;
+
+pub use self::msi::MsiDatabase;
+
+use crate::core::{AnalysisResult, Category, Diagnostic, Location, Position, Range};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// What a component's key path points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPath {
+    /// A file, by File Id
+    File(String),
+    /// A registry value; the Id is unknown when the source leaves it to WiX
+    Registry(Option<String>),
+    /// The component's directory
+    Directory,
+}
+
+impl KeyPath {
+    /// Whether two key paths can be told apart (generated registry Ids cannot)
+    fn differs(&self, other: &KeyPath) -> bool {
+        match (self, other) {
+            (KeyPath::Registry(Some(a)), KeyPath::Registry(Some(b))) => a != b,
+            (KeyPath::Registry(_), KeyPath::Registry(_)) => false,
+            _ => self != other,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            KeyPath::File(id) => format!("file '{}'", id),
+            KeyPath::Registry(Some(id)) => format!("registry value '{}'", id),
+            KeyPath::Registry(None) => "a registry value".to_string(),
+            KeyPath::Directory => "its directory".to_string(),
+        }
+    }
+}
+
+/// A component as shipped in one release
+#[derive(Debug, Clone)]
+pub struct ComponentSnapshot {
+    pub id: String,
+    /// Normalized GUID; `None` when WiX computes it (`Guid="*"` or omitted)
+    pub guid: Option<String>,
+    pub directory: Option<String>,
+    pub key_path: KeyPath,
+    /// File Id -> long file name
+    pub files: BTreeMap<String, String>,
+    pub features: BTreeSet<String>,
+    pub location: Location,
+}
+
+impl ComponentSnapshot {
+    /// Directory and file name the computed GUID is derived from
+    fn key_path_target(&self) -> Option<(String, String)> {
+        match &self.key_path {
+            KeyPath::File(id) => Some((
+                self.directory.clone().unwrap_or_default(),
+                self.files.get(id)?.to_ascii_lowercase(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// A feature and its place in the feature tree
+#[derive(Debug, Clone)]
+pub struct FeatureSnapshot {
+    pub parent: Option<String>,
+    pub location: Location,
+}
+
+/// The component model of one release
+#[derive(Debug, Clone)]
+pub struct ReleaseSnapshot {
+    /// MSI file or source directory the snapshot was loaded from
+    pub source: PathBuf,
+    /// Fixed ProductCode; `None` when generated per build
+    pub product_code: Option<String>,
+    pub package: Option<Location>,
+    pub components: Vec<ComponentSnapshot>,
+    pub features: BTreeMap<String, FeatureSnapshot>,
+}
+
+impl ReleaseSnapshot {
+    fn new(source: &Path) -> Self {
+        Self {
+            source: source.to_path_buf(),
+            product_code: None,
+            package: None,
+            components: Vec::new(),
+            features: BTreeMap::new(),
+        }
+    }
+
+    /// Load a release from an `.msi` file, a source directory or a single source file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let is_msi = path
+            .extension()
+            .map(|e| e.eq_ignore_ascii_case("msi"))
+            .unwrap_or(false);
+
+        if is_msi {
+            Self::from_msi(path)
+        } else if path.is_dir() {
+            let files: Vec<PathBuf> = walkdir::WalkDir::new(path)
+                .follow_links(true)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .map(|e| e.into_path())
+                .filter(|p| p.extension().map(|e| e == "wxs").unwrap_or(false))
+                .collect();
+            let refs: Vec<&Path> = files.iter().map(|p| p.as_path()).collect();
+            let mut snapshot = Self::from_sources(&refs)?;
+            snapshot.source = path.to_path_buf();
+            Ok(snapshot)
+        } else if path.is_file() {
+            Self::from_sources(&[path])
+        } else {
+            Err(format!("Path does not exist: {}", path.display()))
+        }
+    }
+
+    /// Load a release from its WiX sources
+    pub fn from_sources(files: &[&Path]) -> Result<Self, String> {
+        source::load(files)
+    }
+
+    /// Load a release from a built MSI package
+    pub fn from_msi(path: &Path) -> Result<Self, String> {
+        let mut db = std::fs::File::open(path)
+            .and_then(MsiDatabase::open)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        msi::load(&mut db, path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    }
+
+    fn component_by_guid(&self, guid: &str) -> Option<&ComponentSnapshot> {
+        self.components
+            .iter()
+            .find(|c| c.guid.as_deref() == Some(guid))
+    }
+
+    fn component_by_id(&self, id: &str) -> Option<&ComponentSnapshot> {
+        self.components.iter().find(|c| c.id == id)
+    }
+
+    /// Location to attach findings about things that no longer exist
+    fn anchor(&self) -> Location {
+        self.package
+            .clone()
+            .unwrap_or_else(|| file_location(&self.source))
+    }
+}
+
+/// Normalize a GUID for comparison; computed GUIDs become `None`
+pub fn normalize_guid(guid: Option<&str>) -> Option<String> {
+    let guid = guid?.trim().trim_start_matches('{').trim_end_matches('}');
+    if guid.is_empty() || guid == "*" {
+        None
+    } else {
+        Some(guid.to_ascii_uppercase())
+    }
+}
+
+/// Location pointing at a whole file (MSI tables have no line numbers)
+fn file_location(file: &Path) -> Location {
+    Location::new(
+        file.to_path_buf(),
+        Range::new(Position::new(1, 1), Position::new(1, 1)),
+    )
+}
+
+/// How the current release upgrades the previous one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeKind {
+    /// Same ProductCode: a small update or minor upgrade
+    Minor,
+    /// New ProductCode
+    Major,
+    /// The current sources leave the ProductCode to the build, so either
+    Unknown,
+}
+
+/// Compares the current release with the previous one
+pub struct ReleaseCheck<'a> {
+    previous: &'a ReleaseSnapshot,
+    current: &'a ReleaseSnapshot,
+    upgrade: UpgradeKind,
+}
+
+impl<'a> ReleaseCheck<'a> {
+    /// Create a check; the upgrade kind is detected from the ProductCodes
+    ///
+    /// Without a fixed ProductCode in the current sources the kind is
+    /// `Unknown`, and minor-upgrade violations are reported as warnings.
+    pub fn new(previous: &'a ReleaseSnapshot, current: &'a ReleaseSnapshot) -> Self {
+        let upgrade = match (&previous.product_code, &current.product_code) {
+            (_, None) => UpgradeKind::Unknown,
+            (Some(before), Some(after)) if before == after => UpgradeKind::Minor,
+            _ => UpgradeKind::Major,
+        };
+        Self {
+            previous,
+            current,
+            upgrade,
+        }
+    }
+
+    /// Override the detected upgrade kind
+    pub fn with_upgrade(mut self, upgrade: UpgradeKind) -> Self {
+        self.upgrade = upgrade;
+        self
+    }
+
+    pub fn upgrade(&self) -> UpgradeKind {
+        self.upgrade
+    }
+
+    /// Severity of a change only a major upgrade allows; `None` in a major upgrade
+    fn minor_violation(&self) -> Option<crate::core::Severity> {
+        match self.upgrade {
+            UpgradeKind::Minor => Some(crate::core::Severity::High),
+            UpgradeKind::Unknown => Some(crate::core::Severity::Medium),
+            UpgradeKind::Major => None,
+        }
+    }
+
+    /// How the upgrade is named in messages
+    fn minor_upgrade(&self) -> &'static str {
+        match self.upgrade {
+            UpgradeKind::Unknown => "what may be a minor upgrade",
+            _ => "a minor upgrade",
+        }
+    }
+
+    /// Run all component rule checks
+    pub fn run(&self) -> AnalysisResult {
+        let mut result = AnalysisResult::new();
+
+        self.check_components(&mut result);
+        self.check_moved_files(&mut result);
+        self.check_features(&mut result);
+
+        for diag in &result.diagnostics {
+            let file = diag.location.file.clone();
+            result.files.push(file);
+        }
+        result.files.sort();
+        result.files.dedup();
+
+        result
+    }
+
+    fn check_components(&self, result: &mut AnalysisResult) {
+        let minor = self.upgrade == UpgradeKind::Minor;
+        let violation = self.minor_violation();
+
+        for old in &self.previous.components {
+            let by_guid = old
+                .guid
+                .as_deref()
+                .and_then(|g| self.current.component_by_guid(g));
+            let Some(new) = by_guid.or_else(|| self.current.component_by_id(&old.id)) else {
+                if let Some(severity) = violation {
+                    result.add(
+                        Diagnostic::new(
+                            "REL-004",
+                            Category::Validation,
+                            severity,
+                            format!(
+                                "Component '{}' was removed in {}",
+                                old.id,
+                                self.minor_upgrade()
+                            ),
+                            self.current.anchor(),
+                        )
+                        .with_help("Components cannot be removed without a major upgrade; keep the component or change the ProductCode"),
+                    );
+                }
+                continue;
+            };
+
+            match (&old.guid, &new.guid) {
+                (Some(old_guid), Some(new_guid)) if old_guid == new_guid => {
+                    self.check_same_guid(old, new, result);
+                }
+                (Some(_), Some(_)) => {
+                    if let Some(severity) = violation {
+                        result.add(
+                            Diagnostic::new(
+                                "REL-004",
+                                Category::Validation,
+                                severity,
+                                format!(
+                                    "Component '{}' changed its GUID in {}, which removes the shipped component",
+                                    new.id,
+                                    self.minor_upgrade()
+                                ),
+                                new.location.clone(),
+                            )
+                            .with_help("Keep the shipped GUID, or ship this change as a major upgrade"),
+                        );
+                    }
+                }
+                (_, None) => {
+                    // Computed GUID: stable only while the key path stays in place
+                    match (old.key_path_target(), new.key_path_target()) {
+                        (Some(before), Some(after)) if before != after => {
+                            let diag = Diagnostic::new(
+                                "REL-006",
+                                Category::Validation,
+                                if minor {
+                                    crate::core::Severity::High
+                                } else {
+                                    crate::core::Severity::Medium
+                                },
+                                format!(
+                                    "Component '{}' has a computed GUID and its key path moved from '{}\\{}' to '{}\\{}', so its GUID changes",
+                                    new.id, before.0, before.1, after.0, after.1
+                                ),
+                                new.location.clone(),
+                            )
+                            .with_help("Give the component an explicit GUID of its own, or keep the key path at its shipped location");
+                            result.add(diag);
+                        }
+                        _ => self.check_same_guid(old, new, result),
+                    }
+                }
+                (None, Some(_)) => {}
+            }
+
+            if let Some(severity) =
+                violation.filter(|_| !old.features.is_empty() && new.features.is_empty())
+            {
+                result.add(
+                    Diagnostic::new(
+                        "REL-004",
+                        Category::Validation,
+                        severity,
+                        format!(
+                            "Component '{}' is no longer installed by any feature in {}",
+                            new.id,
+                            self.minor_upgrade()
+                        ),
+                        new.location.clone(),
+                    )
+                    .with_help(
+                        "Keep the component referenced from its features, or ship a major upgrade",
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Key path and resources must not change under a shipped GUID
+    fn check_same_guid(
+        &self,
+        old: &ComponentSnapshot,
+        new: &ComponentSnapshot,
+        result: &mut AnalysisResult,
+    ) {
+        if old.key_path.differs(&new.key_path) {
+            result.add(
+                Diagnostic::error(
+                    "REL-001",
+                    Category::Validation,
+                    format!(
+                        "Component '{}' changed its key path from {} to {} under the same GUID",
+                        new.id,
+                        old.key_path.describe(),
+                        new.key_path.describe()
+                    ),
+                    new.location.clone(),
+                )
+                .with_help("A changed key path needs a new component GUID"),
+            );
+        }
+
+        let moved = self.moved_files();
+        let added: Vec<&str> = new
+            .files
+            .keys()
+            .filter(|f| !old.files.contains_key(*f) && !moved.contains_key(f.as_str()))
+            .map(String::as_str)
+            .collect();
+        let removed: Vec<&str> = old
+            .files
+            .keys()
+            .filter(|f| !new.files.contains_key(*f) && !moved.contains_key(f.as_str()))
+            .map(String::as_str)
+            .collect();
+
+        for (files, verb) in [(added, "added to"), (removed, "removed from")] {
+            if !files.is_empty() {
+                result.add(
+                    Diagnostic::error(
+                        "REL-002",
+                        Category::Validation,
+                        format!(
+                            "File(s) {} {} component '{}' under the same GUID",
+                            files.join(", "),
+                            verb,
+                            new.id
+                        ),
+                        new.location.clone(),
+                    )
+                    .with_help("Put new resources in a new component, and give a component a new GUID when its resources change"),
+                );
+            }
+        }
+    }
+
+    /// Files whose component changed: file Id -> (previous, current)
+    fn moved_files(&self) -> HashMap<&str, (&ComponentSnapshot, &ComponentSnapshot)> {
+        let owners = |snapshot: &'a ReleaseSnapshot| -> HashMap<&'a str, &'a ComponentSnapshot> {
+            snapshot
+                .components
+                .iter()
+                .flat_map(|c| c.files.keys().map(move |f| (f.as_str(), c)))
+                .collect()
+        };
+        let before = owners(self.previous);
+        let after = owners(self.current);
+
+        before
+            .into_iter()
+            .filter_map(|(file, old)| {
+                let new = after.get(file)?;
+                (new.id != old.id && (new.guid.is_none() || new.guid != old.guid))
+                    .then_some((file, (old, *new)))
+            })
+            .collect()
+    }
+
+    fn check_moved_files(&self, result: &mut AnalysisResult) {
+        let mut moved: Vec<_> = self.moved_files().into_iter().collect();
+        moved.sort_by_key(|(file, _)| *file);
+
+        for (file, (old, new)) in moved {
+            result.add(
+                Diagnostic::error(
+                    "REL-003",
+                    Category::Validation,
+                    format!(
+                        "File '{}' moved from component '{}' to component '{}'",
+                        file, old.id, new.id
+                    ),
+                    new.location.clone(),
+                )
+                .with_help("Moving a file changes the resources of both components; give both new GUIDs or keep the file where it shipped"),
+            );
+        }
+    }
+
+    fn check_features(&self, result: &mut AnalysisResult) {
+        let minor = self.upgrade == UpgradeKind::Minor;
+
+        for (id, old) in &self.previous.features {
+            let Some(new) = self.current.features.get(id) else {
+                continue;
+            };
+            if old.parent == new.parent {
+                continue;
+            }
+
+            let describe = |parent: &Option<String>| match parent {
+                Some(p) => format!("'{}'", p),
+                None => "the root".to_string(),
+            };
+            result.add(
+                Diagnostic::new(
+                    "REL-005",
+                    Category::Validation,
+                    if minor {
+                        crate::core::Severity::High
+                    } else {
+                        crate::core::Severity::Medium
+                    },
+                    format!(
+                        "Feature '{}' was reparented from {} to {}",
+                        id,
+                        describe(&old.parent),
+                        describe(&new.parent)
+                    ),
+                    new.location.clone(),
+                )
+                .with_help("The feature tree can only change in a major upgrade"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(source: &str) -> ReleaseSnapshot {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("product.wxs");
+        std::fs::write(&file, source).unwrap();
+        ReleaseSnapshot::from_sources(&[file.as_path()]).unwrap()
+    }
+
+    fn rules(previous: &str, current: &str) -> Vec<String> {
+        let previous = snapshot(previous);
+        let current = snapshot(current);
+        ReleaseCheck::new(&previous, &current)
+            .run()
+            .diagnostics
+            .into_iter()
+            .map(|d| d.rule_id)
+            .collect()
+    }
+
+    const BASE: &str = r#"<Wix><Package ProductCode="{11111111-1111-1111-1111-111111111111}">
+        <StandardDirectory Id="ProgramFiles6432Folder">
+            <Directory Id="INSTALLFOLDER" Name="App">
+                <Component Id="Main" Guid="{AAAAAAAA-0000-0000-0000-000000000001}">
+                    <File Id="AppExe" Source="app.exe" />
+                    <File Id="AppCfg" Source="app.config" />
+                </Component>
+                <Component Id="Lib">
+                    <File Id="LibDll" Source="lib.dll" />
+                </Component>
+            </Directory>
+        </StandardDirectory>
+        <Feature Id="Complete">
+            <ComponentRef Id="Main" />
+            <ComponentRef Id="Lib" />
+            <Feature Id="Docs" />
+        </Feature>
+    </Package></Wix>"#;
+
+    #[test]
+    fn test_unchanged_release_is_clean() {
+        assert!(rules(BASE, BASE).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_guid() {
+        assert_eq!(normalize_guid(Some("{abc-1}")), Some("ABC-1".to_string()));
+        assert_eq!(normalize_guid(Some("*")), None);
+        assert_eq!(normalize_guid(None), None);
+    }
+
+    #[test]
+    fn test_key_path_changed_under_same_guid() {
+        let current = BASE.replace(
+            r#"<File Id="AppCfg" Source="app.config" />"#,
+            r#"<File Id="AppCfg" Source="app.config" KeyPath="yes" />"#,
+        );
+        assert_eq!(rules(BASE, &current), vec!["REL-001"]);
+    }
+
+    #[test]
+    fn test_resource_added_under_same_guid() {
+        let current = BASE.replace(
+            r#"<File Id="AppCfg" Source="app.config" />"#,
+            r#"<File Id="AppCfg" Source="app.config" /><File Id="Readme" Source="readme.txt" />"#,
+        );
+        assert_eq!(rules(BASE, &current), vec!["REL-002"]);
+    }
+
+    #[test]
+    fn test_file_moved_between_components() {
+        let current = BASE
+            .replace(r#"<File Id="AppCfg" Source="app.config" />"#, "")
+            .replace(
+                r#"<File Id="LibDll" Source="lib.dll" />"#,
+                r#"<File Id="LibDll" Source="lib.dll" /><File Id="AppCfg" Source="app.config" />"#,
+            );
+        let found = rules(BASE, &current);
+        assert!(found.contains(&"REL-003".to_string()));
+        assert!(!found.contains(&"REL-002".to_string()));
+    }
+
+    #[test]
+    fn test_component_removed_in_minor_upgrade() {
+        let current = BASE.replace(
+            r#"<Component Id="Lib">
+                    <File Id="LibDll" Source="lib.dll" />
+                </Component>"#,
+            "",
+        );
+        assert!(rules(BASE, &current).contains(&"REL-004".to_string()));
+
+        // A new ProductCode makes it a major upgrade
+        let major = current.replace("11111111-1111", "22222222-2222");
+        assert!(!rules(BASE, &major).contains(&"REL-004".to_string()));
+    }
+
+    #[test]
+    fn test_component_removed_without_current_product_code() {
+        let current = BASE
+            .replace(
+                r#" ProductCode="{11111111-1111-1111-1111-111111111111}""#,
+                "",
+            )
+            .replace(
+                r#"<Component Id="Lib">
+                    <File Id="LibDll" Source="lib.dll" />
+                </Component>"#,
+                "",
+            );
+        let previous = snapshot(BASE);
+        let current = snapshot(&current);
+        let check = ReleaseCheck::new(&previous, &current);
+        assert_eq!(check.upgrade(), UpgradeKind::Unknown);
+
+        let result = check.run();
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].rule_id, "REL-004");
+        assert_eq!(
+            result.diagnostics[0].severity,
+            crate::core::Severity::Medium
+        );
+        assert!(result.diagnostics[0]
+            .message
+            .contains("what may be a minor upgrade"));
+    }
+
+    #[test]
+    fn test_feature_reparented() {
+        let current = BASE
+            .replace(r#"<Feature Id="Docs" />"#, "")
+            .replace("</Package>", r#"<Feature Id="Docs" /></Package>"#);
+        assert_eq!(rules(BASE, &current), vec!["REL-005"]);
+    }
+
+    #[test]
+    fn test_computed_guid_key_path_moved() {
+        let current = BASE.replace(
+            r#"<File Id="LibDll" Source="lib.dll" />"#,
+            r#"<File Id="LibDll" Source="lib.dll" Name="core.dll" />"#,
+        );
+        let previous = snapshot(BASE);
+        let current = snapshot(&current);
+        let result = ReleaseCheck::new(&previous, &current).run();
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].rule_id, "REL-006");
+        assert_eq!(result.diagnostics[0].severity, crate::core::Severity::High);
+
+        let result = ReleaseCheck::new(&previous, &current)
+            .with_upgrade(UpgradeKind::Major)
+            .run();
+        assert_eq!(
+            result.diagnostics[0].severity,
+            crate::core::Severity::Medium
+        );
+    }
+}
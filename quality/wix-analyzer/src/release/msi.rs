@@ -0,0 +1,239 @@
+//! MSI database reader
+//!
+//! A shipped package is opened with the `msi` crate; the Property, Component,
+//! File, FeatureComponents and Feature tables give its component model.
+
+use super::{
+    file_location, normalize_guid, ComponentSnapshot, FeatureSnapshot, KeyPath, ReleaseSnapshot,
+};
+use ::msi::{Package, Row, Select};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, Read, Seek};
+use std::path::Path;
+
+const COMPONENT_REGISTRY_KEY_PATH: i32 = 0x4;
+
+/// An open MSI database
+pub type MsiDatabase<F> = Package<F>;
+
+/// Rows of a table; missing tables are empty
+fn rows<F: Read + Seek>(db: &mut MsiDatabase<F>, table: &str) -> io::Result<Vec<Row>> {
+    if !db.has_table(table) {
+        return Ok(Vec::new());
+    }
+    Ok(db.select_rows(Select::table(table))?.collect())
+}
+
+/// String cell of a row; missing columns and nulls are `None`
+fn str_value<'a>(row: &'a Row, column: &str) -> Option<&'a str> {
+    if row.has_column(column) {
+        row[column].as_str()
+    } else {
+        None
+    }
+}
+
+/// Integer cell of a row; missing columns and nulls are `None`
+fn int_value(row: &Row, column: &str) -> Option<i32> {
+    if row.has_column(column) {
+        row[column].as_int()
+    } else {
+        None
+    }
+}
+
+/// Build a release snapshot from the Property, Component, File, FeatureComponents and Feature tables
+pub(super) fn load<F: Read + Seek>(
+    db: &mut MsiDatabase<F>,
+    path: &Path,
+) -> io::Result<ReleaseSnapshot> {
+    let mut snapshot = ReleaseSnapshot::new(path);
+    let location = file_location(path);
+
+    snapshot.product_code = rows(db, "Property")?
+        .iter()
+        .find(|row| str_value(row, "Property") == Some("ProductCode"))
+        .and_then(|row| normalize_guid(str_value(row, "Value")));
+
+    let mut files: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+    for row in rows(db, "File")? {
+        if let (Some(file), Some(component)) =
+            (str_value(&row, "File"), str_value(&row, "Component_"))
+        {
+            // FileName is "SHORT~1.EXT|Long Name.ext" when a short name is needed
+            let name = str_value(&row, "FileName").unwrap_or(file);
+            let name = name.rsplit('|').next().unwrap_or(name);
+            files
+                .entry(component.to_string())
+                .or_default()
+                .insert(file.to_string(), name.to_string());
+        }
+    }
+
+    let mut features: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for row in rows(db, "FeatureComponents")? {
+        if let (Some(feature), Some(component)) =
+            (str_value(&row, "Feature_"), str_value(&row, "Component_"))
+        {
+            features
+                .entry(component.to_string())
+                .or_default()
+                .insert(feature.to_string());
+        }
+    }
+
+    for row in rows(db, "Component")? {
+        let Some(id) = str_value(&row, "Component") else {
+            continue;
+        };
+        let attributes = int_value(&row, "Attributes").unwrap_or(0);
+        let key_path = match str_value(&row, "KeyPath") {
+            None => KeyPath::Directory,
+            Some(key) if attributes & COMPONENT_REGISTRY_KEY_PATH != 0 => {
+                KeyPath::Registry(Some(key.to_string()))
+            }
+            Some(key) => KeyPath::File(key.to_string()),
+        };
+
+        snapshot.components.push(ComponentSnapshot {
+            id: id.to_string(),
+            guid: normalize_guid(str_value(&row, "ComponentId")),
+            directory: str_value(&row, "Directory_").map(str::to_string),
+            key_path,
+            files: files.remove(id).unwrap_or_default(),
+            features: features.remove(id).unwrap_or_default(),
+            location: location.clone(),
+        });
+    }
+
+    for row in rows(db, "Feature")? {
+        if let Some(id) = str_value(&row, "Feature") {
+            snapshot.features.insert(
+                id.to_string(),
+                FeatureSnapshot {
+                    parent: str_value(&row, "Feature_Parent").map(str::to_string),
+                    location: location.clone(),
+                },
+            );
+        }
+    }
+
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::msi::{Column, Insert, PackageType, Value};
+    use std::io::Cursor;
+
+    fn add_table(
+        package: &mut Package<Cursor<Vec<u8>>>,
+        name: &str,
+        columns: Vec<Column>,
+        rows: Vec<Vec<Value>>,
+    ) {
+        package.create_table(name, columns).unwrap();
+        package.insert_rows(Insert::into(name).rows(rows)).unwrap();
+    }
+
+    fn s(v: &str) -> Value {
+        Value::from(v)
+    }
+
+    #[test]
+    fn test_read_tables_and_snapshot() {
+        let mut package = Package::create(PackageType::Installer, Cursor::new(Vec::new())).unwrap();
+        add_table(
+            &mut package,
+            "Property",
+            vec![
+                Column::build("Property").primary_key().id_string(72),
+                Column::build("Value").text_string(0),
+            ],
+            vec![vec![s("ProductCode"), s("{1111-AA}")]],
+        );
+        add_table(
+            &mut package,
+            "Component",
+            vec![
+                Column::build("Component").primary_key().id_string(72),
+                Column::build("ComponentId").nullable().string(38),
+                Column::build("Directory_").id_string(72),
+                Column::build("Attributes").int16(),
+                Column::build("Condition").nullable().string(255),
+                Column::build("KeyPath").nullable().id_string(72),
+            ],
+            vec![
+                vec![
+                    s("Main"),
+                    s("{aaaa-01}"),
+                    s("INSTALLFOLDER"),
+                    Value::Int(0),
+                    Value::Null,
+                    s("AppExe"),
+                ],
+                vec![
+                    s("Reg"),
+                    s("{aaaa-02}"),
+                    s("INSTALLFOLDER"),
+                    Value::Int(4),
+                    Value::Null,
+                    s("RegKey"),
+                ],
+            ],
+        );
+        add_table(
+            &mut package,
+            "File",
+            vec![
+                Column::build("File").primary_key().id_string(72),
+                Column::build("Component_").id_string(72),
+                Column::build("FileName").string(255),
+            ],
+            vec![vec![s("AppExe"), s("Main"), s("APP~1.EXE|My App.exe")]],
+        );
+        add_table(
+            &mut package,
+            "FeatureComponents",
+            vec![
+                Column::build("Feature_").primary_key().id_string(38),
+                Column::build("Component_").primary_key().id_string(72),
+            ],
+            vec![vec![s("Complete"), s("Main")]],
+        );
+        add_table(
+            &mut package,
+            "Feature",
+            vec![
+                Column::build("Feature").primary_key().id_string(38),
+                Column::build("Feature_Parent").nullable().id_string(38),
+            ],
+            vec![
+                vec![s("Complete"), Value::Null],
+                vec![s("Docs"), s("Complete")],
+            ],
+        );
+
+        let mut db = MsiDatabase::open(package.into_inner().unwrap()).unwrap();
+        assert!(rows(&mut db, "Missing").unwrap().is_empty());
+
+        let snapshot = load(&mut db, Path::new("old.msi")).unwrap();
+        assert_eq!(snapshot.product_code.as_deref(), Some("1111-AA"));
+        assert_eq!(snapshot.components.len(), 2);
+
+        let main = &snapshot.components[0];
+        assert_eq!(main.guid.as_deref(), Some("AAAA-01"));
+        assert_eq!(main.key_path, KeyPath::File("AppExe".to_string()));
+        assert_eq!(main.files["AppExe"], "My App.exe");
+        assert!(main.features.contains("Complete"));
+        assert_eq!(
+            snapshot.components[1].key_path,
+            KeyPath::Registry(Some("RegKey".to_string()))
+        );
+        assert_eq!(
+            snapshot.features["Docs"].parent.as_deref(),
+            Some("Complete")
+        );
+    }
+}
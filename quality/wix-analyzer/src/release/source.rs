@@ -0,0 +1,266 @@
+//! Load a release snapshot from WiX sources
+
+use super::{normalize_guid, ComponentSnapshot, FeatureSnapshot, KeyPath, ReleaseSnapshot};
+use crate::core::{Location, WixDocument};
+use roxmltree::Node;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Element ids a component or feature group pulls into a parent
+#[derive(Default)]
+struct Membership {
+    /// Component Id -> features that reference it directly
+    component_features: HashMap<String, BTreeSet<String>>,
+    /// ComponentGroup Id -> features that reference it
+    group_features: HashMap<String, BTreeSet<String>>,
+    /// ComponentGroup Id -> member components and nested groups
+    group_components: HashMap<String, Vec<String>>,
+    group_groups: HashMap<String, Vec<String>>,
+    /// FeatureGroup Id -> parent features that reference it
+    feature_group_parents: HashMap<String, Option<String>>,
+    /// Feature Id -> FeatureGroup it is declared in
+    features_in_group: HashMap<String, String>,
+}
+
+pub(super) fn load(files: &[&Path]) -> Result<ReleaseSnapshot, String> {
+    let mut snapshot = ReleaseSnapshot::new(files.first().copied().unwrap_or(Path::new(".")));
+    let mut membership = Membership::default();
+
+    for file in files {
+        let source = std::fs::read_to_string(file)
+            .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+        let doc = WixDocument::parse(&source, file)
+            .map_err(|e| format!("Failed to parse {}: {}", file.display(), e))?;
+        load_document(&doc, &mut snapshot, &mut membership);
+    }
+
+    resolve_membership(&mut snapshot, &membership);
+    Ok(snapshot)
+}
+
+fn location(doc: &WixDocument, node: &Node) -> Location {
+    Location::new(doc.file().to_path_buf(), doc.node_range(node))
+}
+
+/// File name from a `Source` path, accepting either separator
+fn source_file_name(source: &str) -> Option<&str> {
+    source.rsplit(['\\', '/']).next().filter(|n| !n.is_empty())
+}
+
+/// Nearest ancestor with one of `tags`, by Id
+fn ancestor_id<'a>(node: &Node<'a, '_>, tags: &[&str]) -> Option<(&'a str, &'a str)> {
+    node.ancestors()
+        .skip(1)
+        .filter(|a| a.is_element())
+        .find(|a| tags.contains(&a.tag_name().name()))
+        .and_then(|a| Some((a.tag_name().name(), a.attribute("Id")?)))
+}
+
+fn load_document(doc: &WixDocument, snapshot: &mut ReleaseSnapshot, membership: &mut Membership) {
+    for node in doc.root().descendants().filter(|n| n.is_element()) {
+        match node.tag_name().name() {
+            "Package" | "Product" => {
+                let code = if node.tag_name().name() == "Product" {
+                    node.attribute("Id")
+                } else {
+                    node.attribute("ProductCode")
+                };
+                snapshot.product_code = normalize_guid(code);
+                snapshot.package = Some(location(doc, &node));
+            }
+            "Component" => {
+                if let Some(component) = load_component(doc, &node) {
+                    if let Some((tag, parent)) =
+                        ancestor_id(&node, &["Feature", "FeatureRef", "ComponentGroup"])
+                    {
+                        add_to_parent(membership, tag, parent, &component.id, false);
+                    }
+                    snapshot.components.push(component);
+                }
+            }
+            "ComponentRef" | "ComponentGroupRef" => {
+                let Some(id) = node.attribute("Id") else {
+                    continue;
+                };
+                let is_group = node.tag_name().name() == "ComponentGroupRef";
+                if let Some((tag, parent)) =
+                    ancestor_id(&node, &["Feature", "FeatureRef", "ComponentGroup"])
+                {
+                    add_to_parent(membership, tag, parent, id, is_group);
+                }
+            }
+            "Feature" => {
+                let Some(id) = node.attribute("Id") else {
+                    continue;
+                };
+                let parent = ancestor_id(&node, &["Feature", "FeatureRef", "FeatureGroup"]);
+                if let Some(("FeatureGroup", group)) = parent {
+                    membership
+                        .features_in_group
+                        .insert(id.to_string(), group.to_string());
+                }
+                snapshot.features.insert(
+                    id.to_string(),
+                    FeatureSnapshot {
+                        parent: parent
+                            .filter(|(tag, _)| *tag != "FeatureGroup")
+                            .map(|(_, p)| p.to_string()),
+                        location: location(doc, &node),
+                    },
+                );
+            }
+            "FeatureGroupRef" => {
+                if let Some(id) = node.attribute("Id") {
+                    let parent =
+                        ancestor_id(&node, &["Feature", "FeatureRef"]).map(|(_, p)| p.to_string());
+                    membership
+                        .feature_group_parents
+                        .insert(id.to_string(), parent);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn add_to_parent(membership: &mut Membership, tag: &str, parent: &str, id: &str, is_group: bool) {
+    let parent = parent.to_string();
+    match (tag, is_group) {
+        ("ComponentGroup", false) => membership
+            .group_components
+            .entry(parent)
+            .or_default()
+            .push(id.to_string()),
+        ("ComponentGroup", true) => membership
+            .group_groups
+            .entry(parent)
+            .or_default()
+            .push(id.to_string()),
+        (_, false) => {
+            membership
+                .component_features
+                .entry(id.to_string())
+                .or_default()
+                .insert(parent);
+        }
+        (_, true) => {
+            membership
+                .group_features
+                .entry(id.to_string())
+                .or_default()
+                .insert(parent);
+        }
+    }
+}
+
+fn load_component(doc: &WixDocument, node: &Node) -> Option<ComponentSnapshot> {
+    let mut files = std::collections::BTreeMap::new();
+    let mut first_file = None;
+    let mut first_registry = None;
+    let mut key_path = node
+        .attribute("KeyPath")
+        .filter(|k| *k == "yes")
+        .map(|_| KeyPath::Directory);
+
+    for child in node.children().filter(|n| n.is_element()) {
+        let is_key = child.attribute("KeyPath") == Some("yes");
+        match child.tag_name().name() {
+            "File" => {
+                let source_name = child.attribute("Source").and_then(source_file_name);
+                let Some(id) = child.attribute("Id").or(source_name) else {
+                    continue;
+                };
+                let name = child.attribute("Name").or(source_name).unwrap_or(id);
+                files.insert(id.to_string(), name.to_string());
+                first_file.get_or_insert_with(|| id.to_string());
+                if is_key {
+                    key_path = Some(KeyPath::File(id.to_string()));
+                }
+            }
+            "RegistryValue" | "RegistryKey" => {
+                let id = child.attribute("Id").map(str::to_string);
+                first_registry.get_or_insert_with(|| id.clone());
+                if is_key {
+                    key_path = Some(KeyPath::Registry(id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let id = node
+        .attribute("Id")
+        .map(str::to_string)
+        .or_else(|| first_file.clone())?;
+    let key_path = key_path
+        .or_else(|| first_file.map(KeyPath::File))
+        .or_else(|| first_registry.map(KeyPath::Registry))
+        .unwrap_or(KeyPath::Directory);
+
+    let directory = node
+        .attribute("Directory")
+        .or_else(|| {
+            node.ancestors()
+                .skip(1)
+                .find_map(|a| match a.tag_name().name() {
+                    "Directory" | "DirectoryRef" | "StandardDirectory" => a.attribute("Id"),
+                    "ComponentGroup" => a.attribute("Directory"),
+                    _ => None,
+                })
+        })
+        .map(str::to_string);
+
+    Some(ComponentSnapshot {
+        id,
+        guid: normalize_guid(node.attribute("Guid")),
+        directory,
+        key_path,
+        files,
+        features: BTreeSet::new(),
+        location: location(doc, node),
+    })
+}
+
+/// Resolve component -> feature through component groups, and feature parents through feature groups
+fn resolve_membership(snapshot: &mut ReleaseSnapshot, membership: &Membership) {
+    let mut component_features = membership.component_features.clone();
+
+    for (group, features) in &membership.group_features {
+        let mut stack = vec![group.as_str()];
+        let mut seen = BTreeSet::new();
+        while let Some(group) = stack.pop() {
+            if !seen.insert(group) {
+                continue;
+            }
+            for component in membership.group_components.get(group).into_iter().flatten() {
+                component_features
+                    .entry(component.clone())
+                    .or_default()
+                    .extend(features.iter().cloned());
+            }
+            stack.extend(
+                membership
+                    .group_groups
+                    .get(group)
+                    .into_iter()
+                    .flatten()
+                    .map(String::as_str),
+            );
+        }
+    }
+
+    for component in &mut snapshot.components {
+        if let Some(features) = component_features.get(&component.id) {
+            component.features = features.clone();
+        }
+    }
+
+    for (feature, group) in &membership.features_in_group {
+        if let (Some(snapshot_feature), Some(parent)) = (
+            snapshot.features.get_mut(feature),
+            membership.feature_group_parents.get(group),
+        ) {
+            snapshot_feature.parent = parent.clone();
+        }
+    }
+}
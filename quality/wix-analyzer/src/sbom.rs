@@ -0,0 +1,747 @@
+//! Software bill of materials for installer payloads
+//!
+//! Every installed file becomes a component with its SHA-256, PE version,
+//! detected license and install path. Merge modules and prerequisites are
+//! dependencies of the product, which is identified by its ProductCode and
+//! UpgradeCode. Exported as CycloneDX 1.5 JSON or SPDX 2.3 JSON.
+
+use crate::deps::{Dependency, DependencyType};
+use crate::licenses::{LicenseDetector, LicenseType};
+use crate::payload::{PackageInfo, Payload};
+use crate::pe::PeFile;
+use serde_json::{json, Value};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// SBOM standard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    CycloneDx,
+    /// SPDX 2.3 JSON
+    Spdx,
+}
+
+/// An installed file
+#[derive(Debug, Clone)]
+pub struct SbomFile {
+    /// File Id
+    pub id: String,
+    pub name: String,
+    /// e.g. `ProgramFiles6432Folder\Acme\App\app.exe`
+    pub install_path: String,
+    /// Parent Component Id
+    pub component: Option<String>,
+    /// Resolved source file; `None` when it could not be found
+    pub path: Option<PathBuf>,
+    pub sha256: Option<String>,
+    pub sha1: Option<String>,
+    /// From the PE version resource
+    pub version: Option<String>,
+    /// CompanyName from the PE version resource
+    pub supplier: Option<String>,
+    pub copyright: Option<String>,
+    pub license: Option<LicenseType>,
+}
+
+/// A dependency of the product, with its file when it is bundled
+#[derive(Debug, Clone)]
+pub struct SbomDependency {
+    pub dependency: Dependency,
+    pub sha256: Option<String>,
+}
+
+/// Bill of materials of one package
+#[derive(Debug, Clone)]
+pub struct Sbom {
+    pub package: PackageInfo,
+    pub files: Vec<SbomFile>,
+    pub dependencies: Vec<SbomDependency>,
+    /// RFC 3339 creation time
+    pub timestamp: String,
+}
+
+impl Sbom {
+    /// Describe a payload, hashing and inspecting each resolved file
+    pub fn from_payload(payload: &Payload) -> Self {
+        let detector = LicenseDetector::new();
+
+        let files = payload
+            .files
+            .iter()
+            .map(|file| {
+                let mut entry = SbomFile {
+                    id: file.id.clone(),
+                    name: file.name.clone(),
+                    install_path: file.install_path(),
+                    component: file.component.clone(),
+                    path: file.path.clone(),
+                    sha256: None,
+                    sha1: None,
+                    version: None,
+                    supplier: None,
+                    copyright: None,
+                    license: None,
+                };
+                let Some(path) = &file.path else {
+                    return entry;
+                };
+                let Ok(data) = std::fs::read(path) else {
+                    return entry;
+                };
+
+                entry.sha256 = Some(sha256_hex(&data));
+                entry.sha1 = Some(sha1_hex(&data));
+                if let Some(info) = PeFile::parse(&data).ok().and_then(|pe| pe.version_info()) {
+                    entry.version = info.version().map(|v| v.to_string());
+                    entry.supplier = info.strings.get("CompanyName").cloned();
+                    entry.copyright = info.strings.get("LegalCopyright").cloned();
+                }
+                entry.license = detector
+                    .detect_from_file(path)
+                    .primary_license()
+                    .map(|l| l.license_type)
+                    .filter(|l| *l != LicenseType::Unknown);
+                entry
+            })
+            .collect();
+
+        let dependencies = payload
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                let module = payload
+                    .merge_modules
+                    .iter()
+                    .filter(|_| dependency.dep_type == DependencyType::MergeModule)
+                    .find(|m| m.id == dependency.name);
+                let path = module.and_then(|m| m.path.as_deref());
+                SbomDependency {
+                    dependency: dependency.clone(),
+                    sha256: path
+                        .and_then(|p| std::fs::read(p).ok())
+                        .map(|d| sha256_hex(&d)),
+                }
+            })
+            .collect();
+
+        Self {
+            package: payload.package.clone(),
+            files,
+            dependencies,
+            timestamp: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        }
+    }
+
+    pub fn with_timestamp(mut self, timestamp: &str) -> Self {
+        self.timestamp = timestamp.to_string();
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.package.name.as_deref().unwrap_or("Installer")
+    }
+
+    /// Stable document id from the product identity, contents and time
+    fn uuid(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.name().as_bytes());
+        for code in [&self.package.product_code, &self.package.upgrade_code] {
+            hasher.update(code.as_deref().unwrap_or_default().as_bytes());
+        }
+        for file in &self.files {
+            hasher.update(file.install_path.as_bytes());
+            hasher.update(file.sha256.as_deref().unwrap_or_default().as_bytes());
+        }
+        hasher.update(self.timestamp.as_bytes());
+        let mut bytes: [u8; 16] = hasher.finalize()[..16].try_into().unwrap();
+        // RFC 4122 version 4 layout
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+
+    /// Files that could not be found on disk
+    pub fn unresolved(&self) -> impl Iterator<Item = &SbomFile> {
+        self.files.iter().filter(|f| f.sha256.is_none())
+    }
+
+    pub fn to_json(&self, format: SbomFormat) -> String {
+        let value = match format {
+            SbomFormat::CycloneDx => self.to_cyclonedx(),
+            SbomFormat::Spdx => self.to_spdx(),
+        };
+        serde_json::to_string_pretty(&value).unwrap_or_default()
+    }
+
+    /// CycloneDX 1.5 document
+    pub fn to_cyclonedx(&self) -> Value {
+        let mut properties = Vec::new();
+        if let Some(code) = &self.package.product_code {
+            properties.push(json!({"name": "msi:ProductCode", "value": code}));
+        }
+        if let Some(code) = &self.package.upgrade_code {
+            properties.push(json!({"name": "msi:UpgradeCode", "value": code}));
+        }
+        let mut product = json!({
+            "type": "application",
+            "bom-ref": "product",
+            "name": self.name(),
+            "properties": properties,
+        });
+        if let Some(version) = &self.package.version {
+            product["version"] = json!(version);
+        }
+        if let Some(manufacturer) = &self.package.manufacturer {
+            product["supplier"] = json!({"name": manufacturer});
+        }
+
+        let mut components = Vec::new();
+        let mut refs = Vec::new();
+        for file in &self.files {
+            let bom_ref = format!("file:{}", file.id);
+            let mut properties =
+                vec![json!({"name": "msi:InstallPath", "value": file.install_path})];
+            if let Some(component) = &file.component {
+                properties.push(json!({"name": "msi:Component", "value": component}));
+            }
+            let mut component = json!({
+                "type": "file",
+                "bom-ref": bom_ref,
+                "name": file.name,
+                "properties": properties,
+            });
+            if let Some(version) = &file.version {
+                component["version"] = json!(version);
+            }
+            if let Some(hash) = &file.sha256 {
+                component["hashes"] = json!([{"alg": "SHA-256", "content": hash}]);
+            }
+            if let Some(license) = file.license {
+                component["licenses"] = json!([cyclonedx_license(license)]);
+            }
+            if let Some(supplier) = &file.supplier {
+                component["supplier"] = json!({"name": supplier});
+            }
+            if let Some(copyright) = &file.copyright {
+                component["copyright"] = json!(copyright);
+            }
+            components.push(component);
+            refs.push(bom_ref);
+        }
+
+        // Imported DLLs can share a name across payload files; refs must be unique
+        let mut dependency_refs = HashSet::new();
+        for dep in &self.dependencies {
+            let d = &dep.dependency;
+            let base = match &d.version {
+                Some(version) => format!("dependency:{}@{}", d.name, version),
+                None => format!("dependency:{}", d.name),
+            };
+            let mut bom_ref = base.clone();
+            let mut n = 2;
+            while !dependency_refs.insert(bom_ref.clone()) {
+                bom_ref = format!("{}-{}", base, n);
+                n += 1;
+            }
+            let component_type = match d.dep_type {
+                DependencyType::DotNetFramework
+                | DependencyType::DotNetCore
+                | DependencyType::VCRuntime
+                | DependencyType::DirectX => "framework",
+                _ => "library",
+            };
+            let mut properties = vec![
+                json!({"name": "msi:DependencyType", "value": d.dep_type.as_str()}),
+                json!({"name": "msi:Bundled", "value": d.bundled.to_string()}),
+            ];
+            if let Some(url) = &d.download_url {
+                properties.push(json!({"name": "msi:DownloadUrl", "value": url}));
+            }
+            let mut component = json!({
+                "type": component_type,
+                "bom-ref": bom_ref,
+                "name": d.name,
+                "scope": if d.required { "required" } else { "optional" },
+                "properties": properties,
+            });
+            if let Some(version) = &d.version {
+                component["version"] = json!(version);
+            }
+            if let Some(hash) = &dep.sha256 {
+                component["hashes"] = json!([{"alg": "SHA-256", "content": hash}]);
+            }
+            components.push(component);
+            refs.push(bom_ref);
+        }
+
+        json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "serialNumber": format!("urn:uuid:{}", self.uuid()),
+            "version": 1,
+            "metadata": {
+                "timestamp": self.timestamp,
+                "tools": {
+                    "components": [{
+                        "type": "application",
+                        "name": "wix-analyzer",
+                        "version": env!("CARGO_PKG_VERSION"),
+                    }]
+                },
+                "component": product,
+            },
+            "components": components,
+            "dependencies": [{"ref": "product", "dependsOn": refs}],
+        })
+    }
+
+    /// SPDX 2.3 document
+    ///
+    /// SPDX requires a SHA-1 per file, so files that could not be found are
+    /// listed in the document comment instead of as files.
+    pub fn to_spdx(&self) -> Value {
+        let mut ids = HashSet::new();
+        let mut unique_id = |prefix: &str, name: &str| {
+            let base = format!("SPDXRef-{}-{}", prefix, spdx_id_part(name));
+            let mut id = base.clone();
+            let mut n = 2;
+            while !ids.insert(id.clone()) {
+                id = format!("{}-{}", base, n);
+                n += 1;
+            }
+            id
+        };
+
+        let mut external_refs = Vec::new();
+        for (kind, code) in [
+            ("msi-product-code", &self.package.product_code),
+            ("msi-upgrade-code", &self.package.upgrade_code),
+        ] {
+            if let Some(code) = code {
+                external_refs.push(json!({
+                    "referenceCategory": "OTHER",
+                    "referenceType": kind,
+                    "referenceLocator": code,
+                }));
+            }
+        }
+        let mut product = json!({
+            "SPDXID": "SPDXRef-Package",
+            "name": self.name(),
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": true,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": "NOASSERTION",
+            "copyrightText": "NOASSERTION",
+            "primaryPackagePurpose": "INSTALL",
+            "externalRefs": external_refs,
+        });
+        if let Some(version) = &self.package.version {
+            product["versionInfo"] = json!(version);
+        }
+        if let Some(manufacturer) = &self.package.manufacturer {
+            product["supplier"] = json!(format!("Organization: {}", manufacturer));
+        }
+
+        let mut relationships = vec![json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": "SPDXRef-Package",
+        })];
+        let mut license_refs = Vec::new();
+        let mut files = Vec::new();
+        for file in &self.files {
+            let (Some(sha1), Some(sha256)) = (&file.sha1, &file.sha256) else {
+                continue;
+            };
+            let id = unique_id("File", &file.id);
+            let license = match file.license {
+                Some(license) => {
+                    let expression = spdx_license(license);
+                    if expression.starts_with("LicenseRef-") && !license_refs.contains(&license) {
+                        license_refs.push(license);
+                    }
+                    expression
+                }
+                None => "NOASSERTION".to_string(),
+            };
+            let mut entry = json!({
+                "SPDXID": id,
+                "fileName": format!("./{}", file.install_path.replace('\\', "/")),
+                "checksums": [
+                    {"algorithm": "SHA1", "checksumValue": sha1},
+                    {"algorithm": "SHA256", "checksumValue": sha256},
+                ],
+                "licenseConcluded": license,
+                "licenseInfoInFiles": [license],
+                "copyrightText": file.copyright.as_deref().unwrap_or("NOASSERTION"),
+            });
+            if let Some(version) = &file.version {
+                entry["comment"] = json!(format!("Version {}", version));
+            }
+            files.push(entry);
+            relationships.push(json!({
+                "spdxElementId": "SPDXRef-Package",
+                "relationshipType": "CONTAINS",
+                "relatedSpdxElement": id,
+            }));
+        }
+
+        let mut packages = vec![product];
+        for dep in &self.dependencies {
+            let d = &dep.dependency;
+            let id = unique_id("Dependency", &d.name);
+            let mut package = json!({
+                "SPDXID": id,
+                "name": d.name,
+                "downloadLocation": d.download_url.as_deref().unwrap_or("NOASSERTION"),
+                "filesAnalyzed": false,
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": "NOASSERTION",
+                "copyrightText": "NOASSERTION",
+                "comment": d.dep_type.as_str(),
+            });
+            if let Some(version) = &d.version {
+                package["versionInfo"] = json!(version);
+            }
+            if let Some(hash) = &dep.sha256 {
+                package["checksums"] = json!([{"algorithm": "SHA256", "checksumValue": hash}]);
+            }
+            packages.push(package);
+            let relationship = if d.bundled {
+                "CONTAINS"
+            } else if d.required {
+                "DEPENDS_ON"
+            } else {
+                "OPTIONAL_DEPENDENCY_OF"
+            };
+            relationships.push(if relationship == "OPTIONAL_DEPENDENCY_OF" {
+                json!({
+                    "spdxElementId": id,
+                    "relationshipType": relationship,
+                    "relatedSpdxElement": "SPDXRef-Package",
+                })
+            } else {
+                json!({
+                    "spdxElementId": "SPDXRef-Package",
+                    "relationshipType": relationship,
+                    "relatedSpdxElement": id,
+                })
+            });
+        }
+
+        let mut document = json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": self.name(),
+            "documentNamespace": format!(
+                "https://spdx.org/spdxdocs/{}-{}",
+                spdx_id_part(self.name()),
+                self.uuid()
+            ),
+            "creationInfo": {
+                "created": self.timestamp,
+                "creators": [format!("Tool: wix-analyzer-{}", env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "files": files,
+            "relationships": relationships,
+        });
+        if !license_refs.is_empty() {
+            document["hasExtractedLicensingInfos"] = license_refs
+                .iter()
+                .map(|l| {
+                    json!({
+                        "licenseId": spdx_license(*l),
+                        "name": l.spdx_id(),
+                        "extractedText": format!("{} license terms", l.spdx_id()),
+                    })
+                })
+                .collect();
+        }
+        let unresolved: Vec<&str> = self.unresolved().map(|f| f.install_path.as_str()).collect();
+        if !unresolved.is_empty() {
+            document["comment"] = json!(format!(
+                "Source files not found, omitted: {}",
+                unresolved.join(", ")
+            ));
+        }
+        document
+    }
+}
+
+fn cyclonedx_license(license: LicenseType) -> Value {
+    if license.is_open_source() {
+        json!({"license": {"id": license.spdx_id()}})
+    } else {
+        json!({"license": {"name": license.spdx_id()}})
+    }
+}
+
+/// SPDX license expression; non-SPDX licenses become `LicenseRef-`s
+fn spdx_license(license: LicenseType) -> String {
+    if license.is_open_source() {
+        license.spdx_id().to_string()
+    } else {
+        format!("LicenseRef-{}", license.spdx_id())
+    }
+}
+
+/// SPDX ids allow letters, digits, `.` and `-`
+fn spdx_id_part(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// SHA-1, which SPDX 2.3 requires for every file
+pub fn sha1_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha1::digest(data))
+}
+
+/// Write an SBOM, to stdout when `output` is `None`
+pub fn write_sbom(sbom: &Sbom, format: SbomFormat, output: Option<&Path>) -> std::io::Result<()> {
+    let json = sbom.to_json(format);
+    match output {
+        Some(path) => std::fs::write(path, json + "\n"),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::payload::PayloadResolver;
+    use crate::pe::tests::{image, resource_section, version_resource};
+    use tempfile::TempDir;
+
+    fn sample() -> (TempDir, Sbom) {
+        let temp = TempDir::new().unwrap();
+        let version = version_resource(
+            [3, 1, 4, 0],
+            &[("CompanyName", "Acme Corp"), ("LegalCopyright", "(c) Acme")],
+        );
+        let section = resource_section(&version);
+        let exe = image(0x8664, true, &[(2, 0x1000, section.len() as u32)], &section);
+        std::fs::write(temp.path().join("app.exe"), exe).unwrap();
+        std::fs::write(
+            temp.path().join("app.exe.license"),
+            "Apache License\nVersion 2.0\nLicensed under the Apache License",
+        )
+        .unwrap();
+
+        let wxs = temp.path().join("Product.wxs");
+        std::fs::write(
+            &wxs,
+            r#"<Wix>
+  <Package Name="Acme App" Version="3.1.4" Manufacturer="Acme Corp"
+           ProductCode="{6A1C4D3B-0000-4000-8000-000000000001}"
+           UpgradeCode="{6A1C4D3B-0000-4000-8000-000000000002}">
+    <PropertyRef Id="WIX_IS_NETFRAMEWORK_48_OR_LATER_INSTALLED" />
+    <StandardDirectory Id="ProgramFiles64Folder">
+      <Directory Id="INSTALLFOLDER" Name="Acme">
+        <Component Id="Main">
+          <File Id="AppExe" Source="app.exe" />
+          <File Id="Missing" Source="missing.dll" />
+        </Component>
+        <Merge Id="CRT" SourceFile="crt.msm" />
+      </Directory>
+    </StandardDirectory>
+  </Package>
+</Wix>"#,
+        )
+        .unwrap();
+
+        let payload = Payload::load(&[&wxs], &PayloadResolver::new()).unwrap();
+        let sbom = Sbom::from_payload(&payload).with_timestamp("2026-01-02T03:04:05Z");
+        (temp, sbom)
+    }
+
+    #[test]
+    fn test_sha1() {
+        assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(sha1_hex(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            sha1_hex(&[b'a'; 1000]),
+            "291e9a6c66994949b57ba5e650361e98fc36b1ba"
+        );
+    }
+
+    #[test]
+    fn test_files_are_inspected() {
+        let (_temp, sbom) = sample();
+        let app = &sbom.files[0];
+        assert_eq!(app.install_path, "ProgramFiles64Folder\\Acme\\app.exe");
+        assert_eq!(app.version.as_deref(), Some("3.1.4.0"));
+        assert_eq!(app.supplier.as_deref(), Some("Acme Corp"));
+        assert_eq!(app.license, Some(LicenseType::Apache2));
+        assert_eq!(app.sha256.as_ref().unwrap().len(), 64);
+        assert_eq!(sbom.unresolved().count(), 1);
+    }
+
+    #[test]
+    fn test_cyclonedx() {
+        let (_temp, sbom) = sample();
+        let bom = sbom.to_cyclonedx();
+
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["specVersion"], "1.5");
+        assert!(bom["serialNumber"]
+            .as_str()
+            .unwrap()
+            .starts_with("urn:uuid:"));
+        let product = &bom["metadata"]["component"];
+        assert_eq!(product["name"], "Acme App");
+        assert_eq!(product["properties"][0]["name"], "msi:ProductCode");
+        assert_eq!(
+            product["properties"][1]["value"],
+            "{6A1C4D3B-0000-4000-8000-000000000002}"
+        );
+
+        let app = &bom["components"][0];
+        assert_eq!(app["type"], "file");
+        assert_eq!(app["version"], "3.1.4.0");
+        assert_eq!(app["hashes"][0]["alg"], "SHA-256");
+        assert_eq!(app["licenses"][0]["license"]["id"], "Apache-2.0");
+        assert_eq!(
+            app["properties"][0]["value"],
+            "ProgramFiles64Folder\\Acme\\app.exe"
+        );
+        assert!(bom["components"][1].get("hashes").is_none());
+
+        let names: Vec<&str> = bom["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["app.exe", "missing.dll", ".NET Framework", "CRT"]
+        );
+        assert_eq!(bom["components"][2]["type"], "framework");
+        assert_eq!(
+            bom["dependencies"][0]["dependsOn"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+
+        // Same inputs, same serial number
+        assert_eq!(bom["serialNumber"], sbom.to_cyclonedx()["serialNumber"]);
+    }
+
+    #[test]
+    fn test_cyclonedx_dependency_refs_are_unique() {
+        let (_temp, mut sbom) = sample();
+        for version in [Some("14.0"), Some("14.38"), Some("14.38"), None] {
+            let mut dependency = Dependency::new("vcruntime140.dll", DependencyType::NativeDll);
+            dependency.version = version.map(str::to_string);
+            sbom.dependencies.push(SbomDependency {
+                dependency,
+                sha256: None,
+            });
+        }
+        let bom = sbom.to_cyclonedx();
+
+        let refs: Vec<&str> = bom["components"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["bom-ref"].as_str().unwrap())
+            .filter(|r| r.contains("vcruntime140"))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                "dependency:vcruntime140.dll@14.0",
+                "dependency:vcruntime140.dll@14.38",
+                "dependency:vcruntime140.dll@14.38-2",
+                "dependency:vcruntime140.dll",
+            ]
+        );
+        assert_eq!(
+            bom["dependencies"][0]["dependsOn"]
+                .as_array()
+                .unwrap()
+                .len(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_spdx() {
+        let (_temp, sbom) = sample();
+        let doc = sbom.to_spdx();
+
+        assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+        assert_eq!(doc["packages"][0]["SPDXID"], "SPDXRef-Package");
+        assert_eq!(
+            doc["packages"][0]["externalRefs"][0]["referenceLocator"],
+            "{6A1C4D3B-0000-4000-8000-000000000001}"
+        );
+        assert_eq!(doc["packages"][0]["supplier"], "Organization: Acme Corp");
+
+        let files = doc["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["fileName"], "./ProgramFiles64Folder/Acme/app.exe");
+        assert_eq!(files[0]["checksums"][0]["algorithm"], "SHA1");
+        assert_eq!(files[0]["licenseConcluded"], "Apache-2.0");
+        assert_eq!(files[0]["copyrightText"], "(c) Acme");
+        assert!(doc["comment"].as_str().unwrap().contains("missing.dll"));
+
+        let relationships: Vec<(&str, &str)> = doc["relationships"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                (
+                    r["relationshipType"].as_str().unwrap(),
+                    r["relatedSpdxElement"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            relationships,
+            vec![
+                ("DESCRIBES", "SPDXRef-Package"),
+                ("CONTAINS", "SPDXRef-File-AppExe"),
+                ("DEPENDS_ON", "SPDXRef-Dependency-.NET-Framework"),
+                ("CONTAINS", "SPDXRef-Dependency-CRT"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spdx_license_refs() {
+        assert_eq!(spdx_license(LicenseType::MIT), "MIT");
+        assert_eq!(spdx_license(LicenseType::EULA), "LicenseRef-EULA");
+        assert_eq!(spdx_id_part("My App_1.0"), "My-App-1.0");
+    }
+}
@@ -37,6 +37,11 @@ impl Formatter {
         }
     }
 
+    /// wix-data used for ordering, if it was loaded
+    pub fn wix_data(&self) -> Option<&WixData> {
+        self.data.as_ref()
+    }
+
     /// Format XML source string
     pub fn format(&self, source: &str) -> Result<String, FormatError> {
         let doc = Document::parse(source)?;
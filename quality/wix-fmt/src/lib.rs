@@ -12,3 +12,4 @@ mod writer;
 pub use config::{FormatConfig, IndentStyle};
 pub use formatter::{format, format_file, Formatter};
 pub use loader::{LoadError, WixData};
+pub use ordering::{sort_attributes, sort_children};